@@ -133,8 +133,9 @@ impl BoundingBox {
 }
 
 /// Tipo de objeto detectado
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 pub enum ObjectClass {
+    #[default]
     Person,
     Vehicle,
     Car,
@@ -153,12 +154,6 @@ pub enum ObjectClass {
     Custom(String),
 }
 
-impl Default for ObjectClass {
-    fn default() -> Self {
-        Self::Person
-    }
-}
-
 impl ObjectClass {
     pub fn is_vehicle(&self) -> bool {
         matches!(self, Self::Vehicle | Self::Car | Self::Truck | Self::Bus | Self::Motorcycle | Self::Bicycle)
@@ -310,9 +305,10 @@ impl AnalyticsZone {
 }
 
 /// Tipo de zona
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum ZoneType {
     /// Zona de detecção
+    #[default]
     Detection,
     /// Zona de interesse (ROI)
     ROI,
@@ -326,12 +322,6 @@ pub enum ZoneType {
     Intrusion,
 }
 
-impl Default for ZoneType {
-    fn default() -> Self {
-        Self::Detection
-    }
-}
-
 /// Linha de contagem
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CountingLine {
@@ -652,6 +642,61 @@ impl CameraAnalyticsConfig {
     }
 }
 
+/// Tipo de sabotagem (tampering) de câmera
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TamperKind {
+    /// Lente coberta/pintada (baixa variância ou imagem escura)
+    Obstruction,
+    /// Perda de foco (queda de nitidez)
+    Defocus,
+    /// Câmera redirecionada (cena diferente do fundo aprendido)
+    SceneChange,
+    /// Imagem congelada
+    FrozenImage,
+    /// Sem frames
+    VideoLoss,
+}
+
+impl TamperKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Obstruction => "obstruction",
+            Self::Defocus => "defocus",
+            Self::SceneChange => "scene_change",
+            Self::FrozenImage => "frozen_image",
+            Self::VideoLoss => "video_loss",
+        }
+    }
+}
+
+/// Configuração de detecção de sabotagem por câmera
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TamperDetectionConfig {
+    /// Detecção habilitada
+    pub enabled: bool,
+
+    /// Sensibilidade (0.0 = mínima, 1.0 = máxima)
+    pub sensitivity: f32,
+
+    /// Tempo mínimo da condição antes de gerar evento
+    pub min_duration_seconds: u32,
+
+    /// Tempo sem frames para considerar perda de vídeo
+    pub video_loss_timeout_seconds: u32,
+}
+
+impl Default for TamperDetectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sensitivity: 0.5,
+            min_duration_seconds: 5,
+            video_loss_timeout_seconds: 10,
+        }
+    }
+}
+
 /// Modelo de detecção
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DetectionModel {
@@ -672,21 +717,16 @@ impl Default for DetectionModel {
 }
 
 /// Tamanho do modelo
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum ModelSize {
     Nano,
     Small,
+    #[default]
     Medium,
     Large,
     XLarge,
 }
 
-impl Default for ModelSize {
-    fn default() -> Self {
-        Self::Medium
-    }
-}
-
 /// Calibração de cena (para medições reais)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SceneCalibration {
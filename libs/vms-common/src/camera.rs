@@ -1,5 +1,8 @@
 //! Tipos relacionados a câmeras
 
+use crate::analytics::TamperDetectionConfig;
+use crate::media_profile::{CameraMediaProfiles, MediaProfileId, MediaProfileUsage};
+use crate::telemetry::CameraStreamStats;
 use crate::types::{CameraId, FrameRate, Resolution};
 use serde::{Deserialize, Serialize};
 
@@ -27,6 +30,62 @@ pub enum CameraProtocol {
     MJPEG,
 }
 
+/// Stream oferecido pela câmera (ex: main stream, sub stream)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraStream {
+    /// Nome do stream (ex: "main", "sub")
+    pub name: String,
+
+    /// URL RTSP do stream
+    pub url: String,
+
+    /// Perfil de mídia associado, se houver
+    #[serde(default)]
+    pub profile_id: Option<MediaProfileId>,
+
+    /// Usos atendidos por este stream (cada uso vira um subject NATS)
+    pub usages: Vec<MediaProfileUsage>,
+
+    /// Resolução esperada do stream
+    #[serde(default)]
+    pub resolution: Option<Resolution>,
+}
+
+impl CameraStream {
+    /// Cria um novo stream
+    pub fn new(name: impl Into<String>, url: impl Into<String>, usages: Vec<MediaProfileUsage>) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+            profile_id: None,
+            usages,
+            resolution: None,
+        }
+    }
+
+    /// Cria um stream a partir de um perfil, com os usos que o perfil atende na câmera
+    pub fn from_profile(
+        profiles: &CameraMediaProfiles,
+        profile_id: MediaProfileId,
+        url: impl Into<String>,
+    ) -> Option<Self> {
+        let profile = profiles.profiles.iter().find(|p| p.id == profile_id)?;
+
+        Some(Self {
+            name: profile.name.clone(),
+            url: url.into(),
+            profile_id: Some(profile_id),
+            usages: profiles.usages_of(profile_id),
+            resolution: Some(profile.resolution),
+        })
+    }
+
+    /// Verifica se o stream atende um uso
+    pub fn serves(&self, usage: MediaProfileUsage) -> bool {
+        self.usages.contains(&usage)
+    }
+}
+
 /// Configuração de uma câmera
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CameraConfig {
@@ -59,6 +118,14 @@ pub struct CameraConfig {
 
     /// Habilitar análise de IA
     pub ai_enabled: bool,
+
+    /// Streams adicionais (main/sub). Vazio = apenas `url` para todos os usos
+    #[serde(default)]
+    pub streams: Vec<CameraStream>,
+
+    /// Detecção de sabotagem (obstrução, desfoque, redirecionamento, congelamento)
+    #[serde(default)]
+    pub tamper_detection: TamperDetectionConfig,
 }
 
 impl CameraConfig {
@@ -75,6 +142,8 @@ impl CameraConfig {
             fps: FrameRate::new(25.0),
             recording_enabled: true,
             ai_enabled: false,
+            streams: Vec::new(),
+            tamper_detection: TamperDetectionConfig::default(),
         }
     }
 
@@ -96,6 +165,48 @@ impl CameraConfig {
         self.ai_enabled = enabled;
         self
     }
+
+    /// Adiciona um stream (main/sub)
+    pub fn with_stream(mut self, stream: CameraStream) -> Self {
+        self.streams.push(stream);
+        self
+    }
+
+    /// Streams que a ingestão deve abrir
+    ///
+    /// Sem streams configurados, a `url` principal atende todos os usos.
+    pub fn effective_streams(&self) -> Vec<CameraStream> {
+        if !self.streams.is_empty() {
+            return self.streams.clone();
+        }
+
+        let mut main = CameraStream::new(
+            "main",
+            self.url.clone(),
+            vec![
+                MediaProfileUsage::Recording,
+                MediaProfileUsage::LiveView,
+                MediaProfileUsage::Mobile,
+                MediaProfileUsage::Analytics,
+            ],
+        );
+        main.resolution = Some(self.resolution);
+        vec![main]
+    }
+
+    /// Retorna o stream que atende um uso
+    pub fn stream_for(&self, usage: MediaProfileUsage) -> Option<CameraStream> {
+        self.effective_streams().into_iter().find(|s| s.serves(usage))
+    }
+
+    /// A ingestão precisa reabrir os streams: origem RTSP, credenciais,
+    /// streams (main/sub) ou detecção de sabotagem mudaram
+    pub fn ingest_changed(&self, previous: &CameraConfig) -> bool {
+        self.effective_streams() != previous.effective_streams()
+            || self.username != previous.username
+            || self.password != previous.password
+            || self.tamper_detection != previous.tamper_detection
+    }
 }
 
 /// Informações de uma câmera
@@ -108,6 +219,27 @@ pub struct CameraInfo {
     pub current_bitrate: u64, // bits por segundo
 }
 
+impl CameraInfo {
+    /// Resumo da telemetria dos streams: fps do stream principal (ou do
+    /// primeiro reportado) e bitrate somado de todos os streams
+    pub fn from_stats(id: CameraId, name: impl Into<String>, streams: &[CameraStreamStats]) -> Self {
+        let main = streams.iter().find(|s| s.stream == "main").or_else(|| streams.first());
+        let current_fps = main.map(|s| s.fps).unwrap_or_default();
+
+        Self {
+            id,
+            name: name.into(),
+            status: if current_fps > 0.0 {
+                CameraStatus::Online
+            } else {
+                CameraStatus::Offline
+            },
+            current_fps,
+            current_bitrate: streams.iter().map(|s| s.bitrate).sum(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -125,4 +257,95 @@ mod tests {
         assert!(config.ai_enabled);
         assert_eq!(config.username, Some("admin".to_string()));
     }
+
+    #[test]
+    fn test_camera_info_from_stats() {
+        let id = CameraId::new();
+        let mut sub = crate::telemetry::StreamTelemetry::new().snapshot(id, "sub", 0);
+        sub.fps = 15.0;
+        sub.bitrate = 512_000;
+        let mut main = sub.clone();
+        main.stream = "main".to_string();
+        main.fps = 25.0;
+        main.bitrate = 4_000_000;
+
+        let info = CameraInfo::from_stats(id, "Doca 1", &[sub.clone(), main]);
+        assert_eq!(info.status, CameraStatus::Online);
+        assert_eq!(info.current_fps, 25.0);
+        assert_eq!(info.current_bitrate, 4_512_000);
+
+        // Sem stream principal: o primeiro reportado
+        assert_eq!(CameraInfo::from_stats(id, "Doca 1", &[sub]).current_fps, 15.0);
+
+        let idle = CameraInfo::from_stats(id, "Doca 1", &[]);
+        assert_eq!(idle.status, CameraStatus::Offline);
+        assert_eq!((idle.current_fps, idle.current_bitrate), (0.0, 0));
+    }
+
+    #[test]
+    fn test_effective_streams_fallback() {
+        let config = CameraConfig::new("Cam".to_string(), "rtsp://cam/main".to_string());
+        let streams = config.effective_streams();
+
+        assert_eq!(streams.len(), 1);
+        assert_eq!(streams[0].url, "rtsp://cam/main");
+        assert!(streams[0].serves(MediaProfileUsage::Recording));
+        assert!(streams[0].serves(MediaProfileUsage::Analytics));
+    }
+
+    #[test]
+    fn test_ingest_changed() {
+        let config = CameraConfig::new("Portaria".to_string(), "rtsp://10.0.0.5/main".to_string())
+            .with_credentials("admin".to_string(), "secret".to_string());
+        let mut same = config.clone();
+        same.name = "Portaria Norte".to_string();
+        assert!(!same.ingest_changed(&config));
+
+        let mut url = config.clone();
+        url.url = "rtsp://10.0.0.6/main".to_string();
+        assert!(url.ingest_changed(&config));
+
+        let password = config.clone().with_credentials("admin".to_string(), "new".to_string());
+        assert!(password.ingest_changed(&config));
+
+        let sub = config
+            .clone()
+            .with_stream(CameraStream::new("main", "rtsp://10.0.0.5/main", vec![MediaProfileUsage::Recording]))
+            .with_stream(CameraStream::new("sub", "rtsp://10.0.0.5/sub", vec![MediaProfileUsage::Analytics]));
+        assert!(sub.ingest_changed(&config));
+
+        let mut tamper = config.clone();
+        tamper.tamper_detection.enabled = true;
+        assert!(tamper.ingest_changed(&config));
+    }
+
+    #[test]
+    fn test_main_and_sub_streams() {
+        let config = CameraConfig::new("Cam".to_string(), "rtsp://cam/main".to_string())
+            .with_stream(CameraStream::new(
+                "main",
+                "rtsp://cam/main",
+                vec![MediaProfileUsage::Recording, MediaProfileUsage::LiveView],
+            ))
+            .with_stream(CameraStream::new(
+                "sub",
+                "rtsp://cam/sub",
+                vec![MediaProfileUsage::Analytics, MediaProfileUsage::Mobile],
+            ));
+
+        assert_eq!(config.effective_streams().len(), 2);
+        assert_eq!(config.stream_for(MediaProfileUsage::Recording).unwrap().url, "rtsp://cam/main");
+        assert_eq!(config.stream_for(MediaProfileUsage::Analytics).unwrap().url, "rtsp://cam/sub");
+    }
+
+    #[test]
+    fn test_stream_from_profile() {
+        let profiles = CameraMediaProfiles::new(CameraId::new());
+        let analytics_id = profiles.analytics_profile_id.unwrap();
+
+        let stream = CameraStream::from_profile(&profiles, analytics_id, "rtsp://cam/sub").unwrap();
+        assert_eq!(stream.profile_id, Some(analytics_id));
+        assert_eq!(stream.usages, vec![MediaProfileUsage::Analytics]);
+        assert_eq!(stream.resolution, Some(Resolution::HD_720P));
+    }
 }
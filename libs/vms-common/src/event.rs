@@ -3,6 +3,7 @@
 //! Define eventos que podem ser detectados e ações que podem ser executadas.
 //! Similar ao sistema de eventos do Digifort.
 
+use crate::analytics::TamperKind;
 use crate::types::CameraId;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -64,9 +65,10 @@ impl Default for ActionId {
 }
 
 /// Severidade do evento
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum EventSeverity {
     Debug,
+    #[default]
     Info,
     Warning,
     Alert,
@@ -74,16 +76,11 @@ pub enum EventSeverity {
     Emergency,
 }
 
-impl Default for EventSeverity {
-    fn default() -> Self {
-        Self::Info
-    }
-}
-
 /// Categoria do evento
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum EventCategory {
     /// Sistema (conectividade, erros, etc)
+    #[default]
     System,
     /// Movimento detectado
     Motion,
@@ -105,12 +102,6 @@ pub enum EventCategory {
     Custom(String),
 }
 
-impl Default for EventCategory {
-    fn default() -> Self {
-        Self::System
-    }
-}
-
 /// Tipo de trigger de evento
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EventTrigger {
@@ -168,6 +159,9 @@ pub enum EventTrigger {
     /// Obstrução de câmera
     CameraObstructed { camera_id: CameraId },
 
+    /// Sabotagem de câmera (desfoque, redirecionamento, imagem congelada, perda de vídeo)
+    CameraTampered { camera_id: CameraId, kind: TamperKind },
+
     /// Disco cheio
     DiskFull {
         disk_path: String,
@@ -264,6 +258,7 @@ impl Event {
             EventTrigger::CameraOffline { camera_id } => Some(*camera_id),
             EventTrigger::CameraOnline { camera_id } => Some(*camera_id),
             EventTrigger::CameraObstructed { camera_id } => Some(*camera_id),
+            EventTrigger::CameraTampered { camera_id, .. } => Some(*camera_id),
             EventTrigger::PTZPresetReached { camera_id, .. } => Some(*camera_id),
             _ => None,
         };
@@ -0,0 +1,344 @@
+//! Cliente do vms-gateway
+//!
+//! Registra a instância (`POST /registry/register`), mantém o registro vivo
+//! com heartbeats e busca a configuração do próprio serviço
+//! (`GET /config/services/{serviço}`), a única que vem com segredos. Todas
+//! as chamadas levam o token de serviço de [`ServiceCredential`].
+//!
+//! Os serviços entram com [`join_gateway`] na partida; sem `VMS_GATEWAY_URL`
+//! seguem só com a configuração local (variáveis de ambiente).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::registry::{RegistrationResponse, ServiceRegistration};
+use crate::service_auth::ServiceCredential;
+
+/// Variável de ambiente com a URL do gateway
+pub const GATEWAY_URL_ENV: &str = "VMS_GATEWAY_URL";
+
+/// Variável de ambiente com a URL anunciada no registro
+pub const PUBLIC_URL_ENV: &str = "VMS_PUBLIC_URL";
+
+/// Intervalo entre heartbeats até o gateway indicar o seu
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Error)]
+pub enum GatewayError {
+    #[error("Gateway request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Gateway returned {status}: {body}")]
+    Status { status: u16, body: String },
+}
+
+pub type Result<T> = std::result::Result<T, GatewayError>;
+
+pub struct GatewayClient {
+    client: Client,
+    base_url: String,
+    credential: ServiceCredential,
+}
+
+impl GatewayClient {
+    pub fn new(base_url: impl Into<String>, credential: ServiceCredential) -> Self {
+        Self {
+            client: Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            credential,
+        }
+    }
+
+    /// Cliente de `VMS_GATEWAY_URL` com a credencial de `VMS_SERVICE_SECRET`
+    pub fn from_env(service: &str) -> Option<Self> {
+        let base_url = std::env::var(GATEWAY_URL_ENV).ok().filter(|url| !url.is_empty())?;
+        match ServiceCredential::from_env(service) {
+            Some(credential) => Some(Self::new(base_url, credential)),
+            None => {
+                warn!("⚠️  {} set without VMS_SERVICE_SECRET: not registering with the gateway", GATEWAY_URL_ENV);
+                None
+            }
+        }
+    }
+
+    pub fn service(&self) -> &str {
+        self.credential.service()
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<T> {
+        let response = request
+            .header(reqwest::header::AUTHORIZATION, self.credential.bearer())
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(GatewayError::Status {
+                status: status.as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            });
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Registra a instância; a resposta traz a configuração do serviço
+    pub async fn register(&self, registration: &ServiceRegistration) -> Result<RegistrationResponse> {
+        let url = format!("{}/registry/register", self.base_url);
+        self.send(self.client.post(url).json(registration)).await
+    }
+
+    /// Renova o registro; `false` se o gateway não conhece mais a instância
+    pub async fn heartbeat(&self, instance_id: Uuid) -> Result<bool> {
+        let url = format!("{}/registry/instances/{}/heartbeat", self.base_url, instance_id);
+        match self.send::<serde_json::Value>(self.client.post(url)).await {
+            Ok(_) => Ok(true),
+            Err(GatewayError::Status { status, .. }) if status == StatusCode::NOT_FOUND.as_u16() => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Remove a instância do registro (desligamento)
+    pub async fn deregister(&self, instance_id: Uuid) -> Result<()> {
+        let url = format!("{}/registry/instances/{}", self.base_url, instance_id);
+        let response = self
+            .client
+            .delete(url)
+            .header(reqwest::header::AUTHORIZATION, self.credential.bearer())
+            .send()
+            .await?;
+        match response.status() {
+            status if status.is_success() || status == StatusCode::NOT_FOUND => Ok(()),
+            status => Err(GatewayError::Status {
+                status: status.as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            }),
+        }
+    }
+
+    /// Configuração do serviço: sua seção mais NATS e observabilidade
+    pub async fn service_config(&self) -> Result<serde_json::Value> {
+        let url = format!("{}/config/services/{}", self.base_url, self.service());
+        self.send(self.client.get(url)).await
+    }
+
+    /// Mantém a instância registrada: heartbeats no intervalo pedido pelo
+    /// gateway e novo registro (com o mesmo id) quando ele esquece a
+    /// instância, por reinício ou expiração. `registration.instance_id` vem
+    /// do primeiro [`register`](Self::register), se ele deu certo.
+    pub async fn keep_registered(&self, mut registration: ServiceRegistration, mut interval: Duration) {
+        loop {
+            tokio::time::sleep(interval).await;
+
+            if let Some(id) = registration.instance_id {
+                match self.heartbeat(id).await {
+                    Ok(true) => continue,
+                    Ok(false) => warn!("⚠️  Instance {} unknown to the gateway, registering again", id),
+                    Err(e) => {
+                        warn!("⚠️  Gateway heartbeat failed: {}", e);
+                        continue;
+                    }
+                }
+            }
+
+            match self.register(&registration).await {
+                Ok(response) => {
+                    info!("✅ Registered with the gateway as {} instance {}", self.service(), response.instance.id);
+                    registration.instance_id = Some(response.instance.id);
+                    interval = Duration::from_secs(response.heartbeat_interval_secs.max(1));
+                }
+                Err(e) => warn!("⚠️  Gateway registration failed: {}", e),
+            }
+        }
+    }
+}
+
+/// URL pela qual o gateway alcança o serviço: `VMS_PUBLIC_URL`, senão
+/// `http://$HOSTNAME:<porta>`
+pub fn public_url(port: u16) -> String {
+    std::env::var(PUBLIC_URL_ENV)
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| {
+            let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_string());
+            format!("http://{}:{}", host, port)
+        })
+}
+
+/// Registra o serviço no gateway de `VMS_GATEWAY_URL`, mantém o registro
+/// vivo em segundo plano e devolve a configuração do serviço
+///
+/// Devolve `Value::Null` sem gateway configurado ou se ele não respondeu; o
+/// registro segue sendo tentado junto dos heartbeats.
+pub async fn join_gateway(service: &str, url: String) -> serde_json::Value {
+    let Some(gateway) = GatewayClient::from_env(service) else {
+        return serde_json::Value::Null;
+    };
+    let mut registration = ServiceRegistration {
+        instance_id: None,
+        service: service.to_string(),
+        url,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        metadata: HashMap::new(),
+        token: None,
+    };
+
+    let mut interval = DEFAULT_HEARTBEAT_INTERVAL;
+    let config = match gateway.register(&registration).await {
+        Ok(response) => {
+            info!("🛰️  Registered with vms-gateway as instance {}", response.instance.id);
+            registration.instance_id = Some(response.instance.id);
+            interval = Duration::from_secs(response.heartbeat_interval_secs.max(1));
+            response.config.unwrap_or_default()
+        }
+        Err(e) => {
+            warn!("⚠️  vms-gateway registration failed, using local configuration: {}", e);
+            serde_json::Value::Null
+        }
+    };
+
+    let gateway = Arc::new(gateway);
+    tokio::spawn(async move { gateway.keep_registered(registration, interval).await });
+    config
+}
+
+/// URL do NATS: `NATS_URL`, senão a da configuração central, senão local
+pub fn nats_url(config: &serde_json::Value) -> String {
+    std::env::var("NATS_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .or_else(|| config["nats"]["url"].as_str().map(String::from))
+        .unwrap_or_else(|| "nats://localhost:4222".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::ServiceInstance;
+    use crate::service_auth::verify_service_token;
+    use axum::extract::{Path, State};
+    use axum::http::{HeaderMap, StatusCode as AxumStatus};
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    const SECRET: &str = "service-secret";
+
+    /// Gateway de teste: aceita só tokens válidos e esquece instâncias sob demanda
+    #[derive(Clone, Default)]
+    struct Gateway {
+        instances: Arc<Mutex<HashMap<Uuid, ServiceInstance>>>,
+    }
+
+    fn caller(headers: &HeaderMap) -> std::result::Result<String, AxumStatus> {
+        let token = headers["authorization"].to_str().unwrap().strip_prefix("Bearer ").unwrap();
+        verify_service_token(SECRET.as_bytes(), token, chrono::Utc::now().timestamp())
+            .map_err(|_| AxumStatus::UNAUTHORIZED)
+    }
+
+    async fn serve(gateway: Gateway) -> String {
+        let app = Router::new()
+            .route(
+                "/registry/register",
+                post(|State(gateway): State<Gateway>, headers: HeaderMap, Json(registration): Json<ServiceRegistration>| async move {
+                    assert_eq!(caller(&headers)?, registration.service);
+                    let instance = ServiceInstance::new(registration);
+                    gateway.instances.lock().unwrap().insert(instance.id, instance.clone());
+                    Ok::<_, AxumStatus>((
+                        AxumStatus::CREATED,
+                        Json(RegistrationResponse {
+                            instance,
+                            heartbeat_interval_secs: 1,
+                            config: None,
+                        }),
+                    ))
+                }),
+            )
+            .route(
+                "/registry/instances/:id/heartbeat",
+                post(|State(gateway): State<Gateway>, headers: HeaderMap, Path(id): Path<Uuid>| async move {
+                    caller(&headers)?;
+                    gateway.instances.lock().unwrap().get(&id).cloned().map(Json).ok_or(AxumStatus::NOT_FOUND)
+                }),
+            )
+            .route(
+                "/config/services/:service",
+                get(|headers: HeaderMap, Path(service): Path<String>| async move {
+                    assert_eq!(caller(&headers)?, service);
+                    Ok::<_, AxumStatus>(Json(serde_json::json!({ "streaming": { "srt_token_secret": "srt-secret" } })))
+                }),
+            )
+            .with_state(gateway);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    fn registration() -> ServiceRegistration {
+        ServiceRegistration {
+            instance_id: None,
+            service: "vms-stream".to_string(),
+            url: "http://10.0.0.7:9094".to_string(),
+            version: "0.1.0".to_string(),
+            metadata: HashMap::new(),
+            token: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_heartbeat_and_config() {
+        let gateway = Gateway::default();
+        let url = serve(gateway.clone()).await;
+        let client = GatewayClient::new(&url, ServiceCredential::new("vms-stream", SECRET));
+
+        let response = client.register(&registration()).await.unwrap();
+        let id = response.instance.id;
+        assert!(client.heartbeat(id).await.unwrap());
+
+        let config = client.service_config().await.unwrap();
+        assert_eq!(config["streaming"]["srt_token_secret"], "srt-secret");
+
+        // Gateway reiniciado: instância desconhecida
+        gateway.instances.lock().unwrap().clear();
+        assert!(!client.heartbeat(id).await.unwrap());
+
+        let forged = GatewayClient::new(&url, ServiceCredential::new("vms-stream", "other"));
+        assert!(matches!(
+            forged.service_config().await,
+            Err(GatewayError::Status { status: 401, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_keep_registered_registers_again_with_same_id() {
+        let gateway = Gateway::default();
+        let url = serve(gateway.clone()).await;
+        let client = GatewayClient::new(&url, ServiceCredential::new("vms-stream", SECRET));
+
+        let mut registration = registration();
+        registration.instance_id = Some(Uuid::new_v4());
+        let id = registration.instance_id.unwrap();
+        tokio::spawn(async move { client.keep_registered(registration, Duration::from_millis(50)).await });
+
+        for _ in 0..40 {
+            if gateway.instances.lock().unwrap().contains_key(&id) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("instance was not registered again");
+    }
+}
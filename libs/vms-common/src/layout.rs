@@ -52,7 +52,7 @@ impl std::fmt::Display for MosaicId {
 }
 
 /// Template de layout predefinido
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum LayoutTemplate {
     /// 1 câmera (tela cheia)
     Single,
@@ -61,6 +61,7 @@ pub enum LayoutTemplate {
     /// 2 câmeras empilhadas
     Split1x2,
     /// 4 câmeras (2x2)
+    #[default]
     Grid2x2,
     /// 6 câmeras (3x2)
     Grid3x2,
@@ -84,12 +85,6 @@ pub enum LayoutTemplate {
     Custom,
 }
 
-impl Default for LayoutTemplate {
-    fn default() -> Self {
-        Self::Grid2x2
-    }
-}
-
 impl LayoutTemplate {
     /// Número de slots disponíveis no template
     pub fn slot_count(&self) -> usize {
@@ -478,11 +473,12 @@ pub struct SequenceItem {
 }
 
 /// Tipo de transição entre mosaicos
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum Transition {
     /// Troca instantânea
     None,
     /// Fade
+    #[default]
     Fade,
     /// Slide horizontal
     SlideHorizontal,
@@ -490,12 +486,6 @@ pub enum Transition {
     SlideVertical,
 }
 
-impl Default for Transition {
-    fn default() -> Self {
-        Self::Fade
-    }
-}
-
 /// Sequência de mosaicos (timer)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MosaicSequence {
@@ -546,7 +536,7 @@ impl MosaicSequence {
         self.items.iter().map(|i| i.duration_seconds).sum()
     }
 
-    pub fn next(&mut self) -> Option<&SequenceItem> {
+    pub fn advance(&mut self) -> Option<&SequenceItem> {
         if self.items.is_empty() {
             return None;
         }
@@ -717,7 +707,7 @@ mod tests {
         assert_eq!(sequence.total_duration_seconds(), 60);
         assert_eq!(sequence.current_index, 0);
 
-        sequence.next();
+        sequence.advance();
         assert_eq!(sequence.current_index, 1);
     }
 }
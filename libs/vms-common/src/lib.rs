@@ -9,14 +9,20 @@
 //! - `config`: Configurações do sistema
 //! - `error`: Tipos de erro
 //! - `event`: Sistema de eventos e ações
+//! - `gateway_client`: Registro no vms-gateway e configuração central
 //! - `layout`: Layouts e mosaicos
 //! - `lpr`: Reconhecimento de placas
 //! - `map`: Mapas sinópticos e operacionais
 //! - `media_profile`: Perfis de mídia multi-streaming
 //! - `playback`: Timeline e reprodução de vídeo
 //! - `ptz`: Controle PTZ avançado
+//! - `registry`: Registro de serviços e heartbeats
+//! - `replication`: Protocolo de replicação de gravações
 //! - `schedule`: Agendamento de gravação
+//! - `service_auth`: Autenticação entre serviços
+//! - `srt`: Acesso aos streams SRT (streamid e tokens)
 //! - `stream`: Tipos de streaming
+//! - `telemetry`: Telemetria de saúde e qualidade de stream
 //! - `types`: Tipos básicos compartilhados
 //! - `user`: Usuários e permissões
 //! - `webrtc`: WebRTC signaling types
@@ -26,14 +32,20 @@ pub mod camera;
 pub mod config;
 pub mod error;
 pub mod event;
+pub mod gateway_client;
 pub mod layout;
 pub mod lpr;
 pub mod map;
 pub mod media_profile;
 pub mod playback;
 pub mod ptz;
+pub mod registry;
+pub mod replication;
 pub mod schedule;
+pub mod service_auth;
+pub mod srt;
 pub mod stream;
+pub mod telemetry;
 pub mod types;
 pub mod user;
 pub mod webrtc;
@@ -41,7 +53,7 @@ pub mod webrtc;
 // Re-exports principais
 pub use error::{Error, Result};
 pub use types::{CameraId, StreamId, Resolution, FrameRate, Timestamp};
-pub use camera::{CameraConfig, CameraStatus, CameraInfo};
+pub use camera::{CameraConfig, CameraStatus, CameraInfo, CameraStream};
 pub use media_profile::{MediaProfile, MediaProfileId, VideoCodec, AudioCodec};
 pub use schedule::{RecordingSchedule, RecordingMode, ScheduleId};
 pub use event::{Event, EventId, EventTrigger, EventCategory, EventSeverity};
@@ -52,5 +64,10 @@ pub use user::{User, UserId, Role, Permission, Group, Session, AuditLog};
 pub use analytics::{AnalyticsZone, AnalyticsRule, DetectedObject, ObjectClass, CountingLine};
 pub use lpr::{PlateRead, PlateList, ParkingZone, CameraLPRConfig};
 pub use map::{Map, MapObject, MapType, GeoCoordinates};
-pub use webrtc::{WebRtcOfferRequest, WebRtcAnswerResponse, IceCandidateRequest, ApiErrorBody};
+pub use telemetry::{CameraStreamStats, StreamTelemetry};
+pub use webrtc::{
+    WebRtcOfferRequest, NodeOfferRequest, WebRtcAnswerResponse, IceCandidateRequest, IceCandidate, IceCandidatesResponse,
+    IceServer, ApiErrorBody, SDP_CONTENT_TYPE, SDPFRAG_CONTENT_TYPE, RTSP_URL_HEADER, RTSP_USERNAME_HEADER,
+    RTSP_PASSWORD_HEADER,
+};
 
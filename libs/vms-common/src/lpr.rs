@@ -58,9 +58,10 @@ impl Default for ParkingZoneId {
 }
 
 /// País/região para formatação de placa
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum PlateRegion {
     /// Brasil (padrão Mercosul)
+    #[default]
     Brazil,
     /// Brasil (padrão antigo)
     BrazilOld,
@@ -74,12 +75,6 @@ pub enum PlateRegion {
     Custom(String),
 }
 
-impl Default for PlateRegion {
-    fn default() -> Self {
-        Self::Brazil
-    }
-}
-
 impl PlateRegion {
     /// Regex para validação de placa
     pub fn validation_regex(&self) -> &'static str {
@@ -95,8 +90,9 @@ impl PlateRegion {
 }
 
 /// Tipo de veículo detectado
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum VehicleType {
+    #[default]
     Unknown,
     Car,
     Motorcycle,
@@ -105,15 +101,10 @@ pub enum VehicleType {
     Van,
 }
 
-impl Default for VehicleType {
-    fn default() -> Self {
-        Self::Unknown
-    }
-}
-
 /// Cor do veículo
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum VehicleColor {
+    #[default]
     Unknown,
     White,
     Black,
@@ -128,12 +119,6 @@ pub enum VehicleColor {
     Custom(String),
 }
 
-impl Default for VehicleColor {
-    fn default() -> Self {
-        Self::Unknown
-    }
-}
-
 /// Leitura de placa
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlateRead {
@@ -296,14 +281,15 @@ impl PlateListEntry {
     }
 
     pub fn is_expired(&self) -> bool {
-        self.expires_at.map_or(false, |exp| Utc::now() > exp)
+        self.expires_at.is_some_and(|exp| Utc::now() > exp)
     }
 }
 
 /// Tipo de lista de placas
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum PlateListType {
     /// Permitido (whitelist)
+    #[default]
     Allowed,
     /// Bloqueado (blacklist)
     Blocked,
@@ -319,12 +305,6 @@ pub enum PlateListType {
     Custom,
 }
 
-impl Default for PlateListType {
-    fn default() -> Self {
-        Self::Allowed
-    }
-}
-
 /// Lista de placas
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlateList {
@@ -509,9 +489,10 @@ impl CameraLPRConfig {
 }
 
 /// Engine de OCR
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub enum OCREngine {
     /// PaddleOCR (Apache 2.0)
+    #[default]
     PaddleOCR,
     /// EasyOCR
     EasyOCR,
@@ -523,12 +504,6 @@ pub enum OCREngine {
     CustomONNX { path: String },
 }
 
-impl Default for OCREngine {
-    fn default() -> Self {
-        Self::PaddleOCR
-    }
-}
-
 /// Zona de estacionamento
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParkingZone {
@@ -46,24 +46,20 @@ impl Default for MapObjectId {
 }
 
 /// Tipo de mapa
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum MapType {
     /// Mapa sinóptico (imagem de planta baixa)
+    #[default]
     Synoptic,
     /// Mapa operacional (geográfico real)
     Operational,
 }
 
-impl Default for MapType {
-    fn default() -> Self {
-        Self::Synoptic
-    }
-}
-
 /// Provedor de mapas operacionais
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub enum MapProvider {
     /// OpenStreetMap (gratuito)
+    #[default]
     OpenStreetMap,
     /// Google Maps
     GoogleMaps { api_key: String },
@@ -73,12 +69,6 @@ pub enum MapProvider {
     OfflineTiles { url: String },
 }
 
-impl Default for MapProvider {
-    fn default() -> Self {
-        Self::OpenStreetMap
-    }
-}
-
 /// Coordenadas geográficas
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct GeoCoordinates {
@@ -194,8 +184,9 @@ pub enum MapObjectType {
 }
 
 /// Status de objeto no mapa
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum MapObjectStatus {
+    #[default]
     Normal,
     Active,
     Alert,
@@ -204,12 +195,6 @@ pub enum MapObjectStatus {
     Disabled,
 }
 
-impl Default for MapObjectStatus {
-    fn default() -> Self {
-        Self::Normal
-    }
-}
-
 impl MapObjectStatus {
     pub fn color(&self) -> &'static str {
         match self {
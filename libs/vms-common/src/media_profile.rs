@@ -32,11 +32,12 @@ impl std::fmt::Display for MediaProfileId {
 }
 
 /// Codec de vídeo
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum VideoCodec {
     /// Motion JPEG
     MJPEG,
     /// H.264/AVC
+    #[default]
     H264,
     /// H.265/HEVC
     H265,
@@ -44,14 +45,8 @@ pub enum VideoCodec {
     AV1,
 }
 
-impl Default for VideoCodec {
-    fn default() -> Self {
-        Self::H264
-    }
-}
-
 /// Codec de áudio
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum AudioCodec {
     /// PCM não comprimido
     PCM,
@@ -62,38 +57,29 @@ pub enum AudioCodec {
     /// G.726
     G726,
     /// AAC
+    #[default]
     AAC,
     /// Opus
     Opus,
 }
 
-impl Default for AudioCodec {
-    fn default() -> Self {
-        Self::AAC
-    }
-}
-
 /// Modo de bitrate
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum BitrateMode {
     /// Bitrate constante
     CBR,
     /// Bitrate variável
+    #[default]
     VBR,
 }
 
-impl Default for BitrateMode {
-    fn default() -> Self {
-        Self::VBR
-    }
-}
-
 /// Uso pretendido do perfil
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum MediaProfileUsage {
     /// Para gravação (alta qualidade)
     Recording,
     /// Para visualização ao vivo
+    #[default]
     LiveView,
     /// Para mobile (baixa resolução)
     Mobile,
@@ -103,9 +89,37 @@ pub enum MediaProfileUsage {
     MotionAware,
 }
 
-impl Default for MediaProfileUsage {
-    fn default() -> Self {
-        Self::LiveView
+impl MediaProfileUsage {
+    /// Identificador curto usado em subjects NATS (ex: `vms.frames.{camera}.recording`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Recording => "recording",
+            Self::LiveView => "liveview",
+            Self::Mobile => "mobile",
+            Self::Analytics => "analytics",
+            Self::MotionAware => "motion",
+        }
+    }
+}
+
+impl std::fmt::Display for MediaProfileUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for MediaProfileUsage {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "recording" => Ok(Self::Recording),
+            "liveview" => Ok(Self::LiveView),
+            "mobile" => Ok(Self::Mobile),
+            "analytics" => Ok(Self::Analytics),
+            "motion" => Ok(Self::MotionAware),
+            other => Err(crate::Error::Config(format!("Unknown media profile usage: {}", other))),
+        }
     }
 }
 
@@ -374,6 +388,21 @@ impl CameraMediaProfiles {
     pub fn add_profile(&mut self, profile: MediaProfile) {
         self.profiles.push(profile);
     }
+
+    /// Lista os usos atendidos por um perfil (um perfil pode servir gravação e live view ao mesmo tempo)
+    pub fn usages_of(&self, profile_id: MediaProfileId) -> Vec<MediaProfileUsage> {
+        [
+            (MediaProfileUsage::Recording, self.recording_profile_id),
+            (MediaProfileUsage::LiveView, self.liveview_profile_id),
+            (MediaProfileUsage::Mobile, self.mobile_profile_id),
+            (MediaProfileUsage::Analytics, self.analytics_profile_id),
+            (MediaProfileUsage::MotionAware, self.motion_profile_id),
+        ]
+        .into_iter()
+        .filter(|(_, id)| *id == Some(profile_id))
+        .map(|(usage, _)| usage)
+        .collect()
+    }
 }
 
 #[cfg(test)]
@@ -407,4 +436,29 @@ mod tests {
         assert!(profiles.get_profile_for_usage(MediaProfileUsage::Recording).is_some());
         assert!(profiles.get_profile_for_usage(MediaProfileUsage::Mobile).is_some());
     }
+
+    #[test]
+    fn test_usages_of_profile() {
+        let mut profiles = CameraMediaProfiles::new(CameraId::new());
+        let recording_id = profiles.recording_profile_id.unwrap();
+        profiles.liveview_profile_id = Some(recording_id);
+
+        let usages = profiles.usages_of(recording_id);
+        assert_eq!(usages, vec![MediaProfileUsage::Recording, MediaProfileUsage::LiveView]);
+        assert!(profiles.usages_of(MediaProfileId::new()).is_empty());
+    }
+
+    #[test]
+    fn test_usage_round_trip() {
+        for usage in [
+            MediaProfileUsage::Recording,
+            MediaProfileUsage::LiveView,
+            MediaProfileUsage::Mobile,
+            MediaProfileUsage::Analytics,
+            MediaProfileUsage::MotionAware,
+        ] {
+            assert_eq!(usage.as_str().parse::<MediaProfileUsage>().unwrap(), usage);
+        }
+        assert!("unknown".parse::<MediaProfileUsage>().is_err());
+    }
 }
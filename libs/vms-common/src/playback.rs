@@ -57,9 +57,10 @@ impl Default for ExportId {
 }
 
 /// Modo de reprodução
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum PlaybackMode {
     /// Normal (1x)
+    #[default]
     Normal,
     /// Em pausa
     Paused,
@@ -71,14 +72,8 @@ pub enum PlaybackMode {
     FrameByFrame,
 }
 
-impl Default for PlaybackMode {
-    fn default() -> Self {
-        Self::Normal
-    }
-}
-
 /// Velocidade de reprodução
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
 pub enum PlaybackSpeed {
     /// 1/8x
     Slowest,
@@ -87,6 +82,7 @@ pub enum PlaybackSpeed {
     /// 1/2x
     Slow,
     /// 1x normal
+    #[default]
     Normal,
     /// 2x
     Fast,
@@ -144,12 +140,6 @@ impl PlaybackSpeed {
     }
 }
 
-impl Default for PlaybackSpeed {
-    fn default() -> Self {
-        Self::Normal
-    }
-}
-
 /// Tipo de segmento na timeline
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TimelineSegmentType {
@@ -446,13 +436,13 @@ impl PlaybackSession {
     pub fn next_frame(&mut self) {
         self.mode = PlaybackMode::FrameByFrame;
         // Avança aproximadamente 1 frame (40ms para 25fps)
-        self.current_position = self.current_position + Duration::milliseconds(40);
+        self.current_position += Duration::milliseconds(40);
     }
 
     /// Frame anterior
     pub fn prev_frame(&mut self) {
         self.mode = PlaybackMode::FrameByFrame;
-        self.current_position = self.current_position - Duration::milliseconds(40);
+        self.current_position -= Duration::milliseconds(40);
     }
 
     /// Seek para posição específica
@@ -487,9 +477,9 @@ pub struct DigitalZoom {
 impl DigitalZoom {
     pub fn new(factor: f32, center_x: f32, center_y: f32) -> Self {
         Self {
-            factor: factor.max(1.0).min(10.0),
-            center_x: center_x.max(0.0).min(1.0),
-            center_y: center_y.max(0.0).min(1.0),
+            factor: factor.clamp(1.0, 10.0),
+            center_x: center_x.clamp(0.0, 1.0),
+            center_y: center_y.clamp(0.0, 1.0),
         }
     }
 }
@@ -584,11 +574,12 @@ pub enum DewarpViewMode {
 }
 
 /// Formato de exportação
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum ExportFormat {
     /// MKV nativo (mais rápido, preserva qualidade)
     MKV,
     /// MP4 (mais compatível)
+    #[default]
     MP4,
     /// AVI
     AVI,
@@ -598,12 +589,6 @@ pub enum ExportFormat {
     ImageSequence,
 }
 
-impl Default for ExportFormat {
-    fn default() -> Self {
-        Self::MP4
-    }
-}
-
 /// Configuração de exportação
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExportConfig {
@@ -313,11 +313,12 @@ impl PTZTour {
 }
 
 /// Prioridade de controle PTZ
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
 pub enum PTZPriority {
     /// Mais baixa (tour automático)
     Low = 1,
     /// Normal (operadores)
+    #[default]
     Normal = 5,
     /// Alta (supervisores)
     High = 8,
@@ -325,12 +326,6 @@ pub enum PTZPriority {
     Critical = 10,
 }
 
-impl Default for PTZPriority {
-    fn default() -> Self {
-        Self::Normal
-    }
-}
-
 /// Bloqueio de controle PTZ
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PTZLock {
@@ -0,0 +1,98 @@
+//! Registro de serviços
+//!
+//! Cada instância de serviço se registra no vms-gateway (HTTP ou NATS) e
+//! mantém o registro vivo com heartbeats periódicos; o gateway usa o
+//! registro para rotear `/api/v1` e para entregar a seção de configuração
+//! do serviço.
+
+use std::collections::HashMap;
+
+use crate::types::Timestamp;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Subject NATS de heartbeats (payload: `ServiceRegistration`)
+pub const HEARTBEAT_SUBJECT: &str = "vms.registry.heartbeat";
+
+/// Subject NATS de request/reply de configuração (payload: nome da seção)
+pub const CONFIG_REQUEST_SUBJECT: &str = "vms.config.get";
+
+/// Prefixo dos subjects de configuração atualizada
+pub const CONFIG_UPDATED_SUBJECT_PREFIX: &str = "vms.config.updated";
+
+/// Subject onde o gateway publica uma seção após reload
+///
+/// Formato: `vms.config.updated.{section}`
+pub fn config_updated_subject(section: &str) -> String {
+    format!("{}.{}", CONFIG_UPDATED_SUBJECT_PREFIX, section)
+}
+
+/// Registro (ou heartbeat) enviado por uma instância
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceRegistration {
+    /// Id da instância; ausente no primeiro registro
+    #[serde(default)]
+    pub instance_id: Option<Uuid>,
+
+    /// Nome do serviço (ex.: `vms-api`)
+    pub service: String,
+
+    /// URL base HTTP da instância (ex.: `http://10.0.0.4:9095`)
+    pub url: String,
+
+    #[serde(default)]
+    pub version: String,
+
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+
+    /// Token de serviço (`vms_common::service_auth`) dos heartbeats via
+    /// NATS, que não têm header `Authorization`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// Instância viva no registro
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceInstance {
+    pub id: Uuid,
+    pub service: String,
+    pub url: String,
+    pub version: String,
+    pub metadata: HashMap<String, String>,
+    pub registered_at: Timestamp,
+    pub last_heartbeat: Timestamp,
+}
+
+impl ServiceInstance {
+    pub fn new(registration: ServiceRegistration) -> Self {
+        let now = Timestamp::now();
+        Self {
+            id: registration.instance_id.unwrap_or_else(Uuid::new_v4),
+            service: registration.service,
+            url: registration.url.trim_end_matches('/').to_string(),
+            version: registration.version,
+            metadata: registration.metadata,
+            registered_at: now,
+            last_heartbeat: now,
+        }
+    }
+
+    /// Segundos desde o último heartbeat
+    pub fn heartbeat_age_secs(&self) -> i64 {
+        (Utc::now() - self.last_heartbeat.0).num_seconds()
+    }
+}
+
+/// Resposta do gateway a um registro
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistrationResponse {
+    pub instance: ServiceInstance,
+
+    /// Intervalo recomendado entre heartbeats
+    pub heartbeat_interval_secs: u64,
+
+    /// Seção de configuração do serviço (se houver)
+    pub config: Option<serde_json::Value>,
+}
@@ -0,0 +1,59 @@
+//! Replicação de gravações entre sites
+//!
+//! Protocolo entre o vms-replicator e um vms-storage de destino: o arquivo é
+//! enviado em pedaços (`PUT` com `offset`), o destino acumula em `.part` e só
+//! publica o arquivo quando o tamanho total e o SHA-256 conferem. Um `GET`
+//! informa quanto já foi recebido, permitindo retomar após falhas.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncReadExt;
+
+/// Prefixo das rotas de replicação no vms-storage (seguido do path relativo)
+pub const REPLICATION_FILES_PATH: &str = "/api/v1/replication/files";
+
+/// Estado de um arquivo no destino
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplicaStatus {
+    /// Path relativo à raiz de armazenamento
+    pub path: String,
+
+    /// Bytes recebidos (tamanho final se `complete`)
+    pub size: u64,
+
+    /// Arquivo completo e verificado
+    pub complete: bool,
+
+    /// SHA-256 (hex) do arquivo completo
+    pub sha256: Option<String>,
+}
+
+/// Parâmetros de um `PUT` de pedaço
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkParams {
+    /// Posição do pedaço no arquivo (deve ser igual aos bytes já recebidos)
+    pub offset: u64,
+
+    /// Tamanho total do arquivo
+    pub total: u64,
+
+    /// SHA-256 (hex) esperado do arquivo completo
+    pub sha256: String,
+}
+
+/// SHA-256 (hex) de um arquivo, lido em blocos
+pub async fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
@@ -33,11 +33,12 @@ impl std::fmt::Display for ScheduleId {
 }
 
 /// Modo de gravação
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum RecordingMode {
     /// Não gravar
     None,
     /// Gravação contínua
+    #[default]
     Continuous,
     /// Gravar apenas quando movimento detectado
     Motion,
@@ -47,12 +48,6 @@ pub enum RecordingMode {
     MotionOrEvent,
 }
 
-impl Default for RecordingMode {
-    fn default() -> Self {
-        Self::Continuous
-    }
-}
-
 /// Tipo de dia para agendamento
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DayType {
@@ -395,13 +390,7 @@ impl RecordingSchedule {
         }
 
         // 4. Usar agendamento padrão
-        for entry in &self.default_schedule.entries {
-            if entry.period.contains(time) {
-                return Some(entry);
-            }
-        }
-
-        None
+        self.default_schedule.entries.iter().find(|&entry| entry.period.contains(time)).map(|v| v as _)
     }
 }
 
@@ -0,0 +1,179 @@
+//! Autenticação entre serviços
+//!
+//! Chamadas de serviço para serviço (motor de regras acionando relés e
+//! clipes, serviços se registrando no gateway) levam
+//! `Authorization: Bearer svc.<serviço>.<expiração>.<hmac>`, com HMAC-SHA256
+//! de `<serviço>:<expiração>` sobre o segredo `VMS_SERVICE_SECRET`,
+//! compartilhado por todos os serviços. Os tokens são de curta duração e
+//! emitidos a cada requisição, então o segredo nunca trafega.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use thiserror::Error;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Prefixo que distingue um token de serviço de um JWT de usuário
+pub const SERVICE_TOKEN_PREFIX: &str = "svc.";
+
+/// Validade dos tokens emitidos por [`ServiceCredential`]
+pub const SERVICE_TOKEN_TTL_SECS: i64 = 300;
+
+/// Variável de ambiente com o segredo compartilhado
+pub const SERVICE_SECRET_ENV: &str = "VMS_SERVICE_SECRET";
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ServiceAuthError {
+    #[error("Invalid service token")]
+    InvalidToken,
+
+    #[error("Service token expired")]
+    Expired,
+
+    #[error("Service authentication is not configured")]
+    NotConfigured,
+}
+
+/// HMAC-SHA256 de `message` em hex
+pub(crate) fn sign(secret: &[u8], message: &str) -> String {
+    hex::encode(hmac(secret, message).finalize().into_bytes())
+}
+
+/// Confere em tempo constante a assinatura hex de `message`
+pub(crate) fn verify_signature(secret: &[u8], message: &str, signature: &str) -> bool {
+    hex::decode(signature).is_ok_and(|mac| hmac(secret, message).verify_slice(&mac).is_ok())
+}
+
+fn hmac(secret: &[u8], message: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any size");
+    mac.update(message.as_bytes());
+    mac
+}
+
+/// Token do serviço válido até `expires_at` (unix, segundos)
+pub fn issue_service_token(secret: &[u8], service: &str, expires_at: i64) -> String {
+    let mac = sign(secret, &format!("{}:{}", service, expires_at));
+    format!("{}{}.{}.{}", SERVICE_TOKEN_PREFIX, service, expires_at, mac)
+}
+
+/// Confere um token de serviço em `now`; devolve o nome do serviço
+pub fn verify_service_token(secret: &[u8], token: &str, now: i64) -> Result<String, ServiceAuthError> {
+    let token = token
+        .strip_prefix(SERVICE_TOKEN_PREFIX)
+        .ok_or(ServiceAuthError::InvalidToken)?;
+    let (rest, mac) = token.rsplit_once('.').ok_or(ServiceAuthError::InvalidToken)?;
+    let (service, expires_at) = rest.rsplit_once('.').ok_or(ServiceAuthError::InvalidToken)?;
+    let expires_at: i64 = expires_at.parse().map_err(|_| ServiceAuthError::InvalidToken)?;
+
+    if !verify_signature(secret, &format!("{}:{}", service, expires_at), mac) {
+        return Err(ServiceAuthError::InvalidToken);
+    }
+    if now > expires_at {
+        return Err(ServiceAuthError::Expired);
+    }
+    Ok(service.to_string())
+}
+
+/// Segredo compartilhado em `VMS_SERVICE_SECRET`, se configurado
+pub fn service_secret_from_env() -> Option<String> {
+    std::env::var(SERVICE_SECRET_ENV).ok().filter(|secret| !secret.is_empty())
+}
+
+/// Credencial de um serviço para chamar outros serviços
+#[derive(Clone)]
+pub struct ServiceCredential {
+    service: String,
+    secret: String,
+}
+
+impl std::fmt::Debug for ServiceCredential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServiceCredential").field("service", &self.service).finish()
+    }
+}
+
+impl ServiceCredential {
+    pub fn new(service: impl Into<String>, secret: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+            secret: secret.into(),
+        }
+    }
+
+    /// Credencial do serviço com o segredo de `VMS_SERVICE_SECRET`
+    pub fn from_env(service: impl Into<String>) -> Option<Self> {
+        service_secret_from_env().map(|secret| Self::new(service, secret))
+    }
+
+    pub fn service(&self) -> &str {
+        &self.service
+    }
+
+    /// Token novo, válido por [`SERVICE_TOKEN_TTL_SECS`]
+    pub fn token(&self) -> String {
+        let expires_at = chrono::Utc::now().timestamp() + SERVICE_TOKEN_TTL_SECS;
+        issue_service_token(self.secret.as_bytes(), &self.service, expires_at)
+    }
+
+    /// Valor do header `Authorization`
+    pub fn bearer(&self) -> String {
+        format!("Bearer {}", self.token())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_token() {
+        let secret = b"service-secret";
+        let token = issue_service_token(secret, "vms-events", 1_700_000_000);
+        assert!(token.starts_with("svc.vms-events.1700000000."));
+
+        assert_eq!(
+            verify_service_token(secret, &token, 1_699_999_000),
+            Ok("vms-events".to_string())
+        );
+        assert_eq!(
+            verify_service_token(secret, &token, 1_700_000_001),
+            Err(ServiceAuthError::Expired)
+        );
+        assert_eq!(
+            verify_service_token(b"other", &token, 1_699_999_000),
+            Err(ServiceAuthError::InvalidToken)
+        );
+
+        // Outro serviço ou expiração adulterada
+        let forged = token.replacen("vms-events", "vms-admin", 1);
+        assert_eq!(
+            verify_service_token(secret, &forged, 1_699_999_000),
+            Err(ServiceAuthError::InvalidToken)
+        );
+        let forged = token.replacen("1700000000", "1800000000", 1);
+        assert_eq!(
+            verify_service_token(secret, &forged, 1_699_999_000),
+            Err(ServiceAuthError::InvalidToken)
+        );
+
+        // JWT de usuário não passa por token de serviço
+        assert_eq!(
+            verify_service_token(secret, "eyJhbGciOiJIUzI1NiJ9.e30.sig", 1_699_999_000),
+            Err(ServiceAuthError::InvalidToken)
+        );
+    }
+
+    #[test]
+    fn test_credential_issues_fresh_tokens() {
+        let credential = ServiceCredential::new("vms-events", "service-secret");
+        let bearer = credential.bearer();
+        let token = bearer.strip_prefix("Bearer ").unwrap();
+
+        let now = chrono::Utc::now().timestamp();
+        assert_eq!(
+            verify_service_token(b"service-secret", token, now),
+            Ok("vms-events".to_string())
+        );
+        assert!(!format!("{:?}", credential).contains("service-secret"));
+    }
+}
@@ -0,0 +1,265 @@
+//! Acesso aos streams SRT
+//!
+//! No modo listener o cliente escolhe a câmera pelo `streamid`, no formato
+//! de Access Control do SRT (`#!::r=<câmera>,s=<token>,m=request`): `r` é o
+//! recurso (câmera) e `s` o token emitido pelo vms-api. O token é
+//! `<expiração>.<hmac>`, com HMAC-SHA256 de `<escopo>:<recurso>:<expiração>`
+//! sobre um segredo compartilhado entre o vms-api e os nós vms-stream. O
+//! escopo impede que um token de playback sirva para um push (modo caller),
+//! cujo recurso é a câmera junto com o listener de destino, e que tokens SRT
+//! e LL-HLS sejam intercambiáveis quando os dois segredos coincidem.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::service_auth::{sign, verify_signature};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SrtAccessError {
+    #[error("Invalid SRT streamid: {0}")]
+    InvalidStreamId(String),
+
+    #[error("Only playback (m=request) is served")]
+    UnsupportedMode,
+
+    #[error("Invalid SRT access token")]
+    InvalidToken,
+
+    #[error("SRT access token expired")]
+    Expired,
+}
+
+/// Uso autorizado por um token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenScope {
+    /// Playback da câmera no listener SRT
+    Play,
+    /// Push da câmera para um listener remoto (modo caller)
+    Push,
+    /// Playlists e segmentos LL-HLS da câmera
+    Hls,
+}
+
+impl TokenScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Play => "play",
+            Self::Push => "push",
+            Self::Hls => "hls",
+        }
+    }
+}
+
+/// Recurso de um token de push: a câmera só vai para o destino autorizado
+pub fn push_resource(camera_id: &str, address: &str) -> String {
+    format!("{}@{}", camera_id, address)
+}
+
+/// Push de uma câmera para o listener de uma sala de controle, enviado pelo
+/// vms-api ao nó vms-stream da câmera
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SrtCallTarget {
+    pub camera_id: String,
+    /// `host:porta` do listener remoto
+    pub address: String,
+    /// `streamid` esperado pelo listener remoto
+    pub stream_id: Option<String>,
+    /// Latência deste stream (padrão: a do servidor)
+    pub latency_ms: Option<u32>,
+    /// Passphrase deste stream (padrão: a do servidor)
+    pub passphrase: Option<String>,
+    /// Token de push ([`TokenScope::Push`]) para esta câmera e este destino
+    pub token: String,
+}
+
+/// `streamid` de uma conexão SRT: câmera + token de acesso
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrtStreamId {
+    pub camera_id: String,
+    pub token: String,
+}
+
+impl SrtStreamId {
+    pub fn new(camera_id: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            camera_id: camera_id.into(),
+            token: token.into(),
+        }
+    }
+
+    /// Aceita o formato Access Control e o simplificado `<câmera>/<token>`
+    pub fn parse(value: &str) -> Result<Self, SrtAccessError> {
+        let invalid = || SrtAccessError::InvalidStreamId(value.to_string());
+
+        let Some(keys) = value.strip_prefix("#!::") else {
+            let (camera_id, token) = value.split_once('/').ok_or_else(invalid)?;
+            if camera_id.is_empty() || token.is_empty() {
+                return Err(invalid());
+            }
+            return Ok(Self::new(camera_id, token));
+        };
+
+        let (mut camera_id, mut token) = (None, None);
+        for pair in keys.split(',') {
+            match pair.split_once('=').ok_or_else(invalid)? {
+                ("r", resource) => camera_id = Some(resource),
+                ("s", session) => token = Some(session),
+                ("m", "request") => {}
+                ("m", _) => return Err(SrtAccessError::UnsupportedMode),
+                // Demais chaves (u, h, t, ...) não influenciam o acesso
+                _ => {}
+            }
+        }
+
+        match (camera_id, token) {
+            (Some(camera_id), Some(token)) if !camera_id.is_empty() && !token.is_empty() => {
+                Ok(Self::new(camera_id, token))
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// URL `srt://` de playback em `address` (`host:porta`)
+    pub fn url(&self, address: &str) -> String {
+        // `#` iniciaria o fragmento da URL
+        format!("srt://{}?streamid={}", address, self.to_string().replace('#', "%23"))
+    }
+}
+
+impl std::fmt::Display for SrtStreamId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#!::r={},s={},m=request", self.camera_id, self.token)
+    }
+}
+
+fn signed_message(scope: TokenScope, resource: &str, expires_at: i64) -> String {
+    format!("{}:{}:{}", scope.as_str(), resource, expires_at)
+}
+
+/// Token de acesso ao recurso válido até `expires_at` (unix, segundos)
+pub fn issue_token(secret: &[u8], scope: TokenScope, resource: &str, expires_at: i64) -> String {
+    let mac = sign(secret, &signed_message(scope, resource, expires_at));
+    format!("{}.{}", expires_at, mac)
+}
+
+/// Confere escopo, assinatura e validade de um token em `now` (unix, segundos)
+pub fn verify_token(
+    secret: &[u8],
+    scope: TokenScope,
+    resource: &str,
+    token: &str,
+    now: i64,
+) -> Result<(), SrtAccessError> {
+    let (expires_at, mac) = token.split_once('.').ok_or(SrtAccessError::InvalidToken)?;
+    let expires_at: i64 = expires_at.parse().map_err(|_| SrtAccessError::InvalidToken)?;
+
+    if !verify_signature(secret, &signed_message(scope, resource, expires_at), mac) {
+        return Err(SrtAccessError::InvalidToken);
+    }
+    if now > expires_at {
+        return Err(SrtAccessError::Expired);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_id_formats() {
+        let camera = "0b7c6f5e-9a55-4e3c-9a39-3c5f0a7d1e21";
+        let id = SrtStreamId::parse(&format!("#!::u=sala1,r={},s=123.abc,m=request", camera)).unwrap();
+        assert_eq!(id, SrtStreamId::new(camera, "123.abc"));
+        assert_eq!(SrtStreamId::parse(&id.to_string()).unwrap(), id);
+        assert_eq!(SrtStreamId::parse(&format!("{}/123.abc", camera)).unwrap(), id);
+        assert_eq!(
+            id.url("vms.example.com:9000"),
+            format!("srt://vms.example.com:9000?streamid=%23!::r={},s=123.abc,m=request", camera)
+        );
+
+        assert_eq!(
+            SrtStreamId::parse(&format!("#!::r={},s=1.a,m=publish", camera)),
+            Err(SrtAccessError::UnsupportedMode)
+        );
+        assert!(SrtStreamId::parse("#!::r=cam1").is_err());
+        assert!(SrtStreamId::parse("cam1").is_err());
+    }
+
+    #[test]
+    fn test_token() {
+        let secret = b"srt-secret";
+        let token = issue_token(secret, TokenScope::Play, "cam1", 1_700_000_000);
+        let verify = |scope, resource, token: &str, now| verify_token(secret, scope, resource, token, now);
+
+        assert_eq!(verify(TokenScope::Play, "cam1", &token, 1_699_999_000), Ok(()));
+        assert_eq!(
+            verify(TokenScope::Play, "cam1", &token, 1_700_000_001),
+            Err(SrtAccessError::Expired)
+        );
+        // Outra câmera, outro segredo ou expiração adulterada
+        assert_eq!(
+            verify(TokenScope::Play, "cam2", &token, 1_699_999_000),
+            Err(SrtAccessError::InvalidToken)
+        );
+        assert_eq!(
+            verify_token(b"other", TokenScope::Play, "cam1", &token, 1_699_999_000),
+            Err(SrtAccessError::InvalidToken)
+        );
+        let forged = token.replacen("1700000000", "1800000000", 1);
+        assert_eq!(
+            verify(TokenScope::Play, "cam1", &forged, 1_699_999_000),
+            Err(SrtAccessError::InvalidToken)
+        );
+    }
+
+    #[test]
+    fn test_push_token() {
+        let secret = b"srt-secret";
+        let resource = push_resource("cam1", "control-room.example.com:9000");
+        let token = issue_token(secret, TokenScope::Push, &resource, 1_700_000_000);
+
+        assert_eq!(
+            verify_token(secret, TokenScope::Push, &resource, &token, 1_699_999_000),
+            Ok(())
+        );
+        // Outro destino, ou token de playback usado para push (e vice-versa)
+        assert_eq!(
+            verify_token(
+                secret,
+                TokenScope::Push,
+                &push_resource("cam1", "attacker.example.com:9000"),
+                &token,
+                1_699_999_000
+            ),
+            Err(SrtAccessError::InvalidToken)
+        );
+        let play = issue_token(secret, TokenScope::Play, &resource, 1_700_000_000);
+        assert_eq!(
+            verify_token(secret, TokenScope::Push, &resource, &play, 1_699_999_000),
+            Err(SrtAccessError::InvalidToken)
+        );
+        assert_eq!(
+            verify_token(secret, TokenScope::Play, &resource, &token, 1_699_999_000),
+            Err(SrtAccessError::InvalidToken)
+        );
+    }
+
+    #[test]
+    fn test_hls_token_is_not_an_srt_token() {
+        // Mesmo segredo nos dois protocolos
+        let secret = b"shared-secret";
+        let hls = issue_token(secret, TokenScope::Hls, "cam1", 1_700_000_000);
+        let srt = issue_token(secret, TokenScope::Play, "cam1", 1_700_000_000);
+
+        assert_eq!(verify_token(secret, TokenScope::Hls, "cam1", &hls, 1_699_999_000), Ok(()));
+        assert_eq!(
+            verify_token(secret, TokenScope::Play, "cam1", &hls, 1_699_999_000),
+            Err(SrtAccessError::InvalidToken)
+        );
+        assert_eq!(
+            verify_token(secret, TokenScope::Hls, "cam1", &srt, 1_699_999_000),
+            Err(SrtAccessError::InvalidToken)
+        );
+    }
+}
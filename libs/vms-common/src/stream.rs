@@ -1,8 +1,24 @@
 //! Tipos relacionados a streaming
 
-use crate::types::{FrameRate, Resolution, StreamId, Timestamp};
+use crate::media_profile::{MediaProfileId, MediaProfileUsage};
+use crate::types::{CameraId, FrameRate, Resolution, StreamId, Timestamp};
 use serde::{Deserialize, Serialize};
 
+/// Prefixo dos subjects NATS de frames
+pub const FRAMES_SUBJECT_PREFIX: &str = "vms.frames";
+
+/// Subject NATS onde os frames de uma câmera são publicados para um uso
+///
+/// Formato: `vms.frames.{camera_id}.{usage}`
+pub fn frame_subject(camera_id: &CameraId, usage: MediaProfileUsage) -> String {
+    format!("{}.{}.{}", FRAMES_SUBJECT_PREFIX, camera_id, usage.as_str())
+}
+
+/// Filtro de subscription para receber um uso de todas as câmeras
+pub fn frame_subject_filter(usage: MediaProfileUsage) -> String {
+    format!("{}.*.{}", FRAMES_SUBJECT_PREFIX, usage.as_str())
+}
+
 /// Protocolo de streaming
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum StreamProtocol {
@@ -57,6 +73,12 @@ pub struct VideoFrame {
     pub width: u32,
     pub height: u32,
     pub is_keyframe: bool,
+    /// Perfil de mídia do stream de origem
+    #[serde(default)]
+    pub profile_id: Option<MediaProfileId>,
+    /// Uso do stream de origem (gravação, analytics, ...)
+    #[serde(default)]
+    pub usage: Option<MediaProfileUsage>,
 }
 
 impl VideoFrame {
@@ -69,6 +91,8 @@ impl VideoFrame {
             width,
             height,
             is_keyframe: false,
+            profile_id: None,
+            usage: None,
         }
     }
 
@@ -78,6 +102,13 @@ impl VideoFrame {
         self
     }
 
+    /// Marca o perfil/uso do stream de origem
+    pub fn with_profile(mut self, profile_id: Option<MediaProfileId>, usage: MediaProfileUsage) -> Self {
+        self.profile_id = profile_id;
+        self.usage = Some(usage);
+        self
+    }
+
     /// Retorna o tamanho do frame em bytes
     pub fn size(&self) -> usize {
         self.data.len()
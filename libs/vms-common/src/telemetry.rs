@@ -0,0 +1,219 @@
+//! Telemetria de saúde e qualidade de vídeo por stream
+//!
+//! `StreamTelemetry` acumula frames recebidos por um pipeline de ingestão e
+//! estatísticas RTP do jitterbuffer, produzindo snapshots `CameraStreamStats`.
+
+use std::collections::VecDeque;
+
+use crate::types::{CameraId, Timestamp};
+use serde::{Deserialize, Serialize};
+
+/// Janela padrão para cálculo de fps e bitrate
+pub const DEFAULT_WINDOW_MS: u64 = 5_000;
+
+/// Snapshot das métricas de um stream de câmera
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraStreamStats {
+    pub camera_id: CameraId,
+    /// Nome do stream (main, sub, ...)
+    pub stream: String,
+    pub timestamp: Timestamp,
+    /// Frames por segundo medidos na janela
+    pub fps: f64,
+    /// Bitrate medido na janela (bits por segundo)
+    pub bitrate: u64,
+    /// Frames entre os dois últimos keyframes
+    pub gop_length: Option<u32>,
+    /// Intervalo entre os dois últimos keyframes
+    pub keyframe_interval_ms: Option<u64>,
+    /// Pacotes RTP recebidos
+    pub packets_received: u64,
+    /// Pacotes RTP perdidos
+    pub packets_lost: u64,
+    /// Perda de pacotes (porcentagem)
+    pub packet_loss: f32,
+    /// Jitter médio RTP
+    pub jitter_ms: f64,
+    /// Diferença entre relógio de parede e timestamps dos frames
+    /// (positivo = frames atrasados em relação ao tempo real)
+    pub timestamp_drift_ms: i64,
+    pub total_frames: u64,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FrameSample {
+    wall_ms: u64,
+    bytes: usize,
+}
+
+/// Acumulador de telemetria de um stream
+#[derive(Debug, Clone)]
+pub struct StreamTelemetry {
+    window_ms: u64,
+    samples: VecDeque<FrameSample>,
+    frames_since_keyframe: u32,
+    last_keyframe: Option<u64>,
+    gop_length: Option<u32>,
+    keyframe_interval_ms: Option<u64>,
+    /// (relógio de parede, pts) do primeiro frame
+    clock_base: Option<(u64, u64)>,
+    timestamp_drift_ms: i64,
+    packets_received: u64,
+    packets_lost: u64,
+    jitter_ms: f64,
+    total_frames: u64,
+    total_bytes: u64,
+}
+
+impl StreamTelemetry {
+    pub fn new() -> Self {
+        Self::with_window(DEFAULT_WINDOW_MS)
+    }
+
+    pub fn with_window(window_ms: u64) -> Self {
+        Self {
+            window_ms,
+            samples: VecDeque::new(),
+            frames_since_keyframe: 0,
+            last_keyframe: None,
+            gop_length: None,
+            keyframe_interval_ms: None,
+            clock_base: None,
+            timestamp_drift_ms: 0,
+            packets_received: 0,
+            packets_lost: 0,
+            jitter_ms: 0.0,
+            total_frames: 0,
+            total_bytes: 0,
+        }
+    }
+
+    /// Registra um frame recebido
+    ///
+    /// `wall_ms` é o relógio de parede no recebimento e `pts_ms` o timestamp
+    /// de apresentação do frame, quando disponível.
+    pub fn record_frame(&mut self, wall_ms: u64, pts_ms: Option<u64>, bytes: usize, is_keyframe: bool) {
+        self.total_frames += 1;
+        self.total_bytes += bytes as u64;
+        self.samples.push_back(FrameSample { wall_ms, bytes });
+        self.evict(wall_ms);
+
+        if is_keyframe {
+            // Prefere pts para o intervalo de keyframes (independe de jitter de rede)
+            let at = pts_ms.unwrap_or(wall_ms);
+            if let Some(previous) = self.last_keyframe {
+                self.gop_length = Some(self.frames_since_keyframe);
+                self.keyframe_interval_ms = Some(at.saturating_sub(previous));
+            }
+            self.last_keyframe = Some(at);
+            self.frames_since_keyframe = 0;
+        }
+        self.frames_since_keyframe += 1;
+
+        if let Some(pts) = pts_ms {
+            match self.clock_base {
+                Some((wall_base, pts_base)) => {
+                    let wall_elapsed = wall_ms as i64 - wall_base as i64;
+                    let pts_elapsed = pts as i64 - pts_base as i64;
+                    self.timestamp_drift_ms = wall_elapsed - pts_elapsed;
+                }
+                None => self.clock_base = Some((wall_ms, pts)),
+            }
+        }
+    }
+
+    /// Atualiza estatísticas RTP (valores acumulados do jitterbuffer)
+    pub fn set_rtp_stats(&mut self, packets_received: u64, packets_lost: u64, jitter_ms: f64) {
+        self.packets_received = packets_received;
+        self.packets_lost = packets_lost;
+        self.jitter_ms = jitter_ms;
+    }
+
+    /// Reinicia o estado (reconexão do stream)
+    pub fn reset(&mut self) {
+        *self = Self::with_window(self.window_ms);
+    }
+
+    /// Gera snapshot das métricas no instante `now_ms`
+    pub fn snapshot(&mut self, camera_id: CameraId, stream: &str, now_ms: u64) -> CameraStreamStats {
+        self.evict(now_ms);
+
+        let window_secs = self.window_ms as f64 / 1000.0;
+        let bytes: usize = self.samples.iter().map(|s| s.bytes).sum();
+        let expected = self.packets_received + self.packets_lost;
+
+        CameraStreamStats {
+            camera_id,
+            stream: stream.to_string(),
+            timestamp: Timestamp::now(),
+            fps: self.samples.len() as f64 / window_secs,
+            bitrate: ((bytes as f64 * 8.0) / window_secs) as u64,
+            gop_length: self.gop_length,
+            keyframe_interval_ms: self.keyframe_interval_ms,
+            packets_received: self.packets_received,
+            packets_lost: self.packets_lost,
+            packet_loss: if expected > 0 {
+                (self.packets_lost as f64 / expected as f64 * 100.0) as f32
+            } else {
+                0.0
+            },
+            jitter_ms: self.jitter_ms,
+            timestamp_drift_ms: self.timestamp_drift_ms,
+            total_frames: self.total_frames,
+            total_bytes: self.total_bytes,
+        }
+    }
+
+    fn evict(&mut self, now_ms: u64) {
+        while let Some(sample) = self.samples.front() {
+            if now_ms.saturating_sub(sample.wall_ms) >= self.window_ms {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+impl Default for StreamTelemetry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fps_bitrate_and_gop() {
+        let mut telemetry = StreamTelemetry::with_window(1_000);
+
+        // 25 fps, 1000 bytes por frame, keyframe a cada 10 frames
+        for i in 0..50u64 {
+            telemetry.record_frame(i * 40, Some(i * 40), 1_000, i % 10 == 0);
+        }
+
+        let stats = telemetry.snapshot(CameraId::new(), "main", 49 * 40);
+        assert!((stats.fps - 25.0).abs() < 0.01);
+        assert_eq!(stats.bitrate, 200_000);
+        assert_eq!(stats.gop_length, Some(10));
+        assert_eq!(stats.keyframe_interval_ms, Some(400));
+        assert_eq!(stats.timestamp_drift_ms, 0);
+        assert_eq!(stats.total_frames, 50);
+    }
+
+    #[test]
+    fn test_drift_and_packet_loss() {
+        let mut telemetry = StreamTelemetry::new();
+        telemetry.record_frame(1_000, Some(0), 100, true);
+        telemetry.record_frame(2_150, Some(1_000), 100, false);
+        telemetry.set_rtp_stats(990, 10, 3.5);
+
+        let stats = telemetry.snapshot(CameraId::new(), "sub", 2_150);
+        assert_eq!(stats.timestamp_drift_ms, 150);
+        assert!((stats.packet_loss - 1.0).abs() < 0.001);
+        assert_eq!(stats.jitter_ms, 3.5);
+    }
+}
@@ -63,24 +63,20 @@ impl Default for RoleId {
 }
 
 /// Status do usuário
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum UserStatus {
+    #[default]
     Active,
     Inactive,
     Locked,
     PendingActivation,
 }
 
-impl Default for UserStatus {
-    fn default() -> Self {
-        Self::Active
-    }
-}
-
 /// Provedor de autenticação
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum AuthProvider {
     /// Autenticação local
+    #[default]
     Local,
     /// Active Directory / LDAP
     ActiveDirectory { domain: String },
@@ -90,12 +86,6 @@ pub enum AuthProvider {
     OAuth2 { provider: String },
 }
 
-impl Default for AuthProvider {
-    fn default() -> Self {
-        Self::Local
-    }
-}
-
 /// Configuração de MFA
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MFAConfig {
@@ -132,9 +122,10 @@ impl Default for MFAConfig {
 }
 
 /// Tipo de MFA
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum MFAType {
     /// TOTP (Google Authenticator, etc)
+    #[default]
     TOTP,
     /// SMS
     SMS,
@@ -144,12 +135,6 @@ pub enum MFAType {
     WebAuthn,
 }
 
-impl Default for MFAType {
-    fn default() -> Self {
-        Self::TOTP
-    }
-}
-
 /// Usuário do sistema
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
@@ -254,7 +239,7 @@ impl User {
 
     pub fn is_locked(&self) -> bool {
         self.status == UserStatus::Locked ||
-            self.locked_until.map_or(false, |until| Utc::now() < until)
+            self.locked_until.is_some_and(|until| Utc::now() < until)
     }
 
     pub fn is_active(&self) -> bool {
@@ -23,6 +23,20 @@ fn default_offer_type() -> String {
     "offer".to_string()
 }
 
+/// Offer forwarded by vms-api to the camera's stream node, with the RTSP
+/// source and credentials taken from the database (never from the browser)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeOfferRequest {
+    pub camera_id: String,
+    pub rtsp_url: String,
+    pub username: String,
+    pub password: String,
+    /// SDP offer from browser
+    pub sdp: String,
+    #[serde(rename = "type", default = "default_offer_type")]
+    pub sdp_type: String,
+}
+
 /// WebRTC answer response to browser/viewer
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebRtcAnswerResponse {
@@ -59,6 +73,51 @@ pub struct IceCandidateRequest {
     pub sdp_m_line_index: Option<u16>,
 }
 
+/// ICE candidate gathered by the server, delivered to the viewer (trickle ICE)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IceCandidate {
+    /// ICE candidate string
+    pub candidate: String,
+    /// SDP media ID
+    #[serde(rename = "sdpMid")]
+    pub sdp_mid: Option<String>,
+    /// SDP media line index
+    #[serde(rename = "sdpMLineIndex")]
+    pub sdp_m_line_index: Option<u16>,
+}
+
+/// Long-poll response with the server's ICE candidates
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IceCandidatesResponse {
+    /// Candidates gathered since the requested cursor
+    pub candidates: Vec<IceCandidate>,
+    /// Cursor for the next poll (`since`)
+    pub next: usize,
+    /// Server finished gathering (no more candidates will follow)
+    pub complete: bool,
+}
+
+/// STUN/TURN server in the browser `RTCIceServer` format
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IceServer {
+    pub urls: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential: Option<String>,
+}
+
+/// Media types of WHEP/WHIP signaling bodies (SDP offer/answer and
+/// trickle ICE fragments, RFC 8840)
+pub const SDP_CONTENT_TYPE: &str = "application/sdp";
+pub const SDPFRAG_CONTENT_TYPE: &str = "application/trickle-ice-sdpfrag";
+
+/// Internal headers with the camera's RTSP source, sent by vms-api along
+/// with a WHEP offer forwarded to a stream node
+pub const RTSP_URL_HEADER: &str = "x-rtsp-url";
+pub const RTSP_USERNAME_HEADER: &str = "x-rtsp-username";
+pub const RTSP_PASSWORD_HEADER: &str = "x-rtsp-password";
+
 /// API error response (standardized)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiErrorBody {
@@ -23,6 +23,10 @@ echo ""
 export NATS_URL="nats://localhost:4222"
 export STORAGE_PATH="./storage"
 export RUST_LOG="info"
+# Segredo compartilhado dos tokens entre serviços (gateway, rule engine)
+export VMS_SERVICE_SECRET="${VMS_SERVICE_SECRET:-vms-dev-service-secret}"
+# Registro e configuração central (o gateway sobe primeiro)
+export VMS_GATEWAY_URL="${VMS_GATEWAY_URL:-http://localhost:8080}"
 
 # Configurar GStreamer no Windows
 if [[ "$OSTYPE" == "msys" ]] || [[ "$OSTYPE" == "cygwin" ]]; then
@@ -52,36 +56,36 @@ start_service() {
 mkdir -p logs
 
 # Iniciar serviços
-echo "1/7 Starting vms-storage..."
+echo "1/7 Starting vms-gateway..."
+target/release/vms-gateway > logs/vms-gateway.log 2>&1 &
+echo $! > logs/vms-gateway.pid
+sleep 2
+
+echo "2/7 Starting vms-storage..."
 target/release/vms-storage > logs/vms-storage.log 2>&1 &
 echo $! > logs/vms-storage.pid
 sleep 2
 
-echo "2/7 Starting vms-ingest..."
+echo "3/7 Starting vms-ingest..."
 target/release/vms-ingest > logs/vms-ingest.log 2>&1 &
 echo $! > logs/vms-ingest.pid
 sleep 2
 
-echo "3/7 Starting vms-stream..."
+echo "4/7 Starting vms-stream..."
 target/release/vms-stream > logs/vms-stream.log 2>&1 &
 echo $! > logs/vms-stream.pid
 sleep 2
 
-echo "4/7 Starting vms-ai..."
+echo "5/7 Starting vms-ai..."
 target/release/vms-ai > logs/vms-ai.log 2>&1 &
 echo $! > logs/vms-ai.pid
 sleep 2
 
-echo "5/7 Starting vms-api..."
+echo "6/7 Starting vms-api..."
 target/release/vms-api > logs/vms-api.log 2>&1 &
 echo $! > logs/vms-api.pid
 sleep 2
 
-echo "6/7 Starting vms-gateway..."
-target/release/vms-gateway > logs/vms-gateway.log 2>&1 &
-echo $! > logs/vms-gateway.pid
-sleep 2
-
 echo "7/7 Starting vms-replicator..."
 target/release/vms-replicator > logs/vms-replicator.log 2>&1 &
 echo $! > logs/vms-replicator.pid
@@ -96,9 +100,9 @@ echo "  - vms-ingest:     http://localhost:9091/metrics"
 echo "  - vms-storage:    http://localhost:9092/metrics"
 echo "  - vms-ai:         http://localhost:9093/metrics"
 echo "  - vms-stream:     http://localhost:9094/metrics"
-echo "  - vms-api:        http://localhost:8080"
-echo "  - vms-gateway:    http://localhost:8081"
-echo "  - vms-replicator: http://localhost:9095"
+echo "  - vms-api:        http://localhost:9095"
+echo "  - vms-gateway:    http://localhost:8080"
+echo "  - vms-replicator: http://localhost:9102"
 echo ""
 echo "Logs available in logs/ directory"
 echo ""
@@ -5,7 +5,8 @@ use async_nats::{Client, Subscriber};
 use std::sync::Arc;
 use tokio_stream::StreamExt;
 use tracing::{debug, error, info, warn};
-use vms_common::stream::VideoFrame;
+use vms_common::media_profile::MediaProfileUsage;
+use vms_common::stream::{frame_subject_filter, VideoFrame};
 use vms_common::types::CameraId;
 
 use crate::detector::{Detection, ObjectDetector};
@@ -60,9 +61,10 @@ impl AIProcessor {
     pub async fn start_processing(&self) -> Result<()> {
         info!("🎬 Starting AI frame processor");
 
+        // Analytics usa o sub stream (baixa resolução) quando a câmera oferece
         let subscriber = self
             .client
-            .subscribe("vms.frames.>")
+            .subscribe(frame_subject_filter(MediaProfileUsage::Analytics))
             .await
             .context("Failed to subscribe to frames")?;
 
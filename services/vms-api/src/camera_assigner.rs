@@ -0,0 +1,165 @@
+//! Camera Assigner - distribui câmeras entre nós vms-ingest
+//!
+//! Cada nó registra-se na API e envia heartbeats periódicos. Câmeras sem nó,
+//! ou atribuídas a um nó que parou de responder, são movidas para o nó vivo
+//! com mais capacidade livre. Atribuições a nós vivos não são alteradas.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::db::camera_repository::CameraRepository;
+use crate::db::server_repository::ServerRepository;
+use crate::models::server::ServerStatus;
+
+/// Intervalo de heartbeat esperado dos nós
+pub const HEARTBEAT_INTERVAL_SECS: u64 = 10;
+
+/// Heartbeats perdidos antes de considerar o nó morto
+const MISSED_HEARTBEATS: u64 = 3;
+
+pub struct CameraAssigner {
+    camera_repo: Arc<CameraRepository>,
+    server_repo: Arc<ServerRepository>,
+    heartbeat_timeout: chrono::Duration,
+}
+
+impl CameraAssigner {
+    pub fn new(camera_repo: Arc<CameraRepository>, server_repo: Arc<ServerRepository>) -> Self {
+        Self {
+            camera_repo,
+            server_repo,
+            heartbeat_timeout: chrono::Duration::seconds(
+                (HEARTBEAT_INTERVAL_SECS * MISSED_HEARTBEATS) as i64,
+            ),
+        }
+    }
+
+    /// Tempo sem heartbeat após o qual um nó é considerado morto
+    pub fn heartbeat_timeout(&self) -> chrono::Duration {
+        self.heartbeat_timeout
+    }
+
+    /// Inicia rebalanceamento periódico em background
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(HEARTBEAT_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.rebalance().await {
+                    warn!("⚠️ Camera rebalance failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Recalcula atribuições e persiste as mudanças
+    pub async fn rebalance(&self) -> Result<usize> {
+        let servers = self.server_repo.list().await?;
+
+        // Nós com heartbeat expirado passam a offline
+        for server in &servers {
+            if server.status == ServerStatus::Online && !server.is_alive(self.heartbeat_timeout) {
+                warn!("💔 Node {} ({}) missed heartbeats, marking offline", server.name, server.id);
+                self.server_repo.mark_offline(server.id).await?;
+            }
+        }
+
+        let nodes: Vec<(Uuid, u32)> = servers
+            .iter()
+            .filter(|s| s.is_alive(self.heartbeat_timeout))
+            .map(|s| (s.id, s.max_cameras))
+            .collect();
+
+        let cameras: Vec<(Uuid, Option<Uuid>)> = self
+            .camera_repo
+            .list()
+            .await?
+            .into_iter()
+            .filter(|c| c.enabled)
+            .map(|c| (c.id, c.server_id))
+            .collect();
+
+        let changes = plan_assignments(&cameras, &nodes);
+        for (camera_id, server_id) in &changes {
+            self.camera_repo.assign_server(*camera_id, Some(*server_id)).await?;
+            info!("🔀 Camera {} assigned to node {}", camera_id, server_id);
+        }
+
+        Ok(changes.len())
+    }
+}
+
+/// Planeja atribuições de câmeras para nós vivos
+///
+/// `cameras` são pares (câmera, nó atual) e `nodes` pares (nó vivo, capacidade).
+/// Retorna apenas as câmeras que precisam mudar de nó. Sem nós vivos, nada muda.
+pub fn plan_assignments(cameras: &[(Uuid, Option<Uuid>)], nodes: &[(Uuid, u32)]) -> Vec<(Uuid, Uuid)> {
+    let mut load: HashMap<Uuid, u32> = nodes.iter().map(|(id, _)| (*id, 0)).collect();
+    let capacity: HashMap<Uuid, u32> = nodes.iter().copied().collect();
+
+    let mut pending = Vec::new();
+    for (camera_id, server_id) in cameras {
+        match server_id.and_then(|sid| load.get_mut(&sid)) {
+            Some(count) => *count += 1,
+            None => pending.push(*camera_id),
+        }
+    }
+
+    let mut changes = Vec::new();
+    for camera_id in pending {
+        let target = load
+            .iter()
+            .filter(|(id, count)| **count < capacity[*id])
+            .max_by_key(|(id, count)| (capacity[*id] - **count, std::cmp::Reverse(**id)))
+            .map(|(id, _)| *id);
+
+        match target {
+            Some(node_id) => {
+                *load.get_mut(&node_id).unwrap() += 1;
+                changes.push((camera_id, node_id));
+            }
+            None => break,
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_moves_orphans_to_least_loaded_node() {
+        let node_a = Uuid::new_v4();
+        let node_b = Uuid::new_v4();
+        let dead = Uuid::new_v4();
+        let cameras: Vec<(Uuid, Option<Uuid>)> = vec![
+            (Uuid::new_v4(), Some(node_a)),
+            (Uuid::new_v4(), Some(node_a)),
+            (Uuid::new_v4(), Some(dead)),
+            (Uuid::new_v4(), None),
+        ];
+
+        let changes = plan_assignments(&cameras, &[(node_a, 10), (node_b, 10)]);
+
+        // Câmeras em nó vivo ficam onde estão; órfãs vão para o nó mais livre
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0], (cameras[2].0, node_b));
+        assert!(changes.iter().all(|(id, _)| *id != cameras[0].0 && *id != cameras[1].0));
+    }
+
+    #[test]
+    fn test_plan_respects_capacity_and_no_nodes() {
+        let node = Uuid::new_v4();
+        let cameras: Vec<(Uuid, Option<Uuid>)> = (0..3).map(|_| (Uuid::new_v4(), None)).collect();
+
+        assert_eq!(plan_assignments(&cameras, &[(node, 2)]).len(), 2);
+        assert!(plan_assignments(&cameras, &[]).is_empty());
+    }
+}
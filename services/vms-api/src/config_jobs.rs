@@ -0,0 +1,277 @@
+//! Config Jobs - configuração em massa de câmeras via ONVIF
+//!
+//! Aplica um `MediaProfile` (resolução, fps, bitrate, GOP), data/hora/NTP e
+//! usuários a um conjunto de câmeras, em paralelo, registrando o resultado de
+//! cada câmera. Jobs ficam em memória (os mais recentes).
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use futures::StreamExt;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use uuid::Uuid;
+use vms_onvif::media::apply_media_profile;
+use vms_onvif::{DateTimeMode, OnvifDevice, OnvifUser};
+
+use crate::db::camera_repository::CameraRepository;
+use crate::models::camera::Camera;
+use crate::models::config_job::{
+    CameraJobResult, CameraJobStatus, ConfigJob, ConfigJobRequest, JobStatus, StreamSelector,
+};
+
+/// Câmeras configuradas simultaneamente
+const MAX_PARALLEL: usize = 8;
+
+/// Jobs mantidos em memória
+const MAX_JOBS: usize = 50;
+
+/// O que foi aplicado em uma câmera
+#[derive(Default)]
+struct Outcome {
+    applied: Vec<String>,
+    warnings: Vec<String>,
+}
+
+pub struct ConfigJobManager {
+    camera_repo: Arc<CameraRepository>,
+    jobs: RwLock<HashMap<Uuid, ConfigJob>>,
+}
+
+impl ConfigJobManager {
+    pub fn new(camera_repo: Arc<CameraRepository>) -> Self {
+        Self {
+            camera_repo,
+            jobs: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Cria o job e o executa em background
+    pub async fn start(self: &Arc<Self>, mut request: ConfigJobRequest) -> Result<ConfigJob, String> {
+        request.validate()?;
+
+        let mut seen = HashSet::new();
+        request.camera_ids.retain(|id| seen.insert(*id));
+
+        let job = ConfigJob::new(&request.camera_ids);
+        {
+            let mut jobs = self.jobs.write().await;
+            if jobs.len() >= MAX_JOBS {
+                let mut finished: Vec<_> = jobs
+                    .values()
+                    .filter(|j| j.status == JobStatus::Completed)
+                    .map(|j| (j.created_at, j.id))
+                    .collect();
+                finished.sort();
+                let excess = jobs.len() + 1 - MAX_JOBS;
+                for (_, id) in finished.into_iter().take(excess) {
+                    jobs.remove(&id);
+                }
+            }
+            jobs.insert(job.id, job.clone());
+        }
+
+        info!("🛠️ Config job {} started for {} cameras", job.id, request.camera_ids.len());
+        let manager = self.clone();
+        let job_id = job.id;
+        tokio::spawn(async move { manager.run(job_id, request).await });
+
+        Ok(job)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Option<ConfigJob> {
+        self.jobs.read().await.get(&id).cloned()
+    }
+
+    /// Jobs do mais recente ao mais antigo
+    pub async fn list(&self) -> Vec<ConfigJob> {
+        let mut jobs: Vec<_> = self.jobs.read().await.values().cloned().collect();
+        jobs.sort_by_key(|j| std::cmp::Reverse(j.created_at));
+        jobs
+    }
+
+    async fn run(self: Arc<Self>, job_id: Uuid, request: ConfigJobRequest) {
+        let request = Arc::new(request);
+
+        futures::stream::iter(request.camera_ids.clone())
+            .for_each_concurrent(MAX_PARALLEL, |camera_id| {
+                let manager = self.clone();
+                let request = request.clone();
+                async move {
+                    manager
+                        .update(job_id, camera_id, |r| r.status = CameraJobStatus::Running)
+                        .await;
+
+                    let camera = manager.camera_repo.get(camera_id).await.ok().flatten();
+                    let camera_name = camera.as_ref().map(|c| c.name.clone());
+                    let mut outcome = Outcome::default();
+                    let result = match camera {
+                        Some(camera) => manager.apply(camera, &request, &mut outcome).await,
+                        None => Err(anyhow!("Camera not found")),
+                    };
+
+                    if let Err(e) = &result {
+                        warn!("⚠️ Config job {}: camera {} failed: {:#}", job_id, camera_id, e);
+                    }
+                    manager
+                        .update(job_id, camera_id, move |r| {
+                            r.camera_name = camera_name;
+                            r.finished_at = Some(Utc::now());
+                            r.applied = outcome.applied;
+                            r.warnings = outcome.warnings;
+                            match result {
+                                Ok(()) => r.status = CameraJobStatus::Succeeded,
+                                Err(e) => {
+                                    r.status = CameraJobStatus::Failed;
+                                    r.error = Some(format!("{:#}", e));
+                                }
+                            }
+                        })
+                        .await;
+                }
+            })
+            .await;
+
+        if let Some(job) = self.jobs.write().await.get_mut(&job_id) {
+            job.status = JobStatus::Completed;
+            job.finished_at = Some(Utc::now());
+            info!(
+                "✅ Config job {} completed: {} succeeded, {} failed",
+                job_id, job.succeeded, job.failed
+            );
+        }
+    }
+
+    async fn update(&self, job_id: Uuid, camera_id: Uuid, change: impl FnOnce(&mut CameraJobResult)) {
+        let mut jobs = self.jobs.write().await;
+        let Some(job) = jobs.get_mut(&job_id) else {
+            return;
+        };
+        if let Some(result) = job.results.iter_mut().find(|r| r.camera_id == camera_id) {
+            change(result);
+        }
+        job.succeeded = job.results.iter().filter(|r| r.status == CameraJobStatus::Succeeded).count();
+        job.failed = job.results.iter().filter(|r| r.status == CameraJobStatus::Failed).count();
+    }
+
+    /// Aplica o job a uma câmera; em caso de falha, `outcome` mantém o que
+    /// já foi aplicado. Usuários por último (e o do VMS depois dos demais): a
+    /// troca da senha invalida a autenticação das chamadas seguintes.
+    async fn apply(&self, mut camera: Camera, request: &ConfigJobRequest, outcome: &mut Outcome) -> Result<()> {
+        let onvif_url = camera
+            .onvif_endpoint()
+            .ok_or_else(|| anyhow!("Camera has no ONVIF endpoint configured"))?;
+        let mut device = OnvifDevice::new(&onvif_url, &camera.username, &camera.password)?;
+        device.connect().await.context("ONVIF connect")?;
+
+        let mut camera_changed = false;
+
+        if let Some(profile) = &request.media_profile {
+            let profiles = device.get_profiles().await?;
+            let target = match request.stream {
+                StreamSelector::Main => profiles.iter().max_by_key(|p| p.resolution.0 * p.resolution.1),
+                StreamSelector::Sub => profiles.iter().min_by_key(|p| p.resolution.0 * p.resolution.1),
+            }
+            .ok_or_else(|| anyhow!("Device has no media profiles"))?;
+            let token = target
+                .video_encoder_token
+                .as_deref()
+                .ok_or_else(|| anyhow!("Profile {} has no video encoder", target.token))?;
+
+            let media = device.media()?;
+            let options = match media.get_video_encoder_options(token).await {
+                Ok(options) => Some(options),
+                Err(e) => {
+                    outcome.warnings.push(format!("Encoder options unavailable: {}", e));
+                    None
+                }
+            };
+            let current = media.get_video_encoder_configuration(token).await?;
+            let (config, warnings) = apply_media_profile(&current, options.as_ref(), profile);
+            media
+                .set_video_encoder_configuration(&config)
+                .await
+                .context("SetVideoEncoderConfiguration")?;
+
+            outcome.warnings.extend(warnings);
+            outcome.applied.push(format!(
+                "Video encoder {}: {} {}x{} {}fps {}kbps GOP {}",
+                config.token,
+                config.encoding,
+                config.width,
+                config.height,
+                config.frame_rate_limit,
+                config.bitrate_limit,
+                config.gov_length.map(|g| g.to_string()).unwrap_or_else(|| "-".to_string())
+            ));
+
+            if request.stream == StreamSelector::Main {
+                camera.resolution_width = config.width;
+                camera.resolution_height = config.height;
+                camera.framerate = config.frame_rate_limit as f32;
+                camera_changed = true;
+            }
+        }
+
+        let system = device.system();
+        if let Some(time) = &request.time {
+            let mode = if time.ntp_servers.is_empty() {
+                DateTimeMode::Manual(Utc::now())
+            } else {
+                system.set_ntp(&time.ntp_servers).await.context("SetNTP")?;
+                outcome.applied.push(format!("NTP servers: {}", time.ntp_servers.join(", ")));
+                DateTimeMode::Ntp
+            };
+            system
+                .set_system_date_and_time(mode, time.timezone.as_deref())
+                .await
+                .context("SetSystemDateAndTime")?;
+            outcome.applied.push(match mode {
+                DateTimeMode::Ntp => "Date/time from NTP".to_string(),
+                DateTimeMode::Manual(now) => format!("Date/time set to {}", now.to_rfc3339()),
+            });
+        }
+
+        let (own, others): (Vec<_>, Vec<_>) = request.users.iter().partition(|u| u.username == camera.username);
+        for change in others.into_iter().chain(own) {
+            if change.delete {
+                if change.username == camera.username {
+                    outcome
+                        .warnings
+                        .push(format!("User {} is used by the VMS; not deleted", change.username));
+                    continue;
+                }
+                system.delete_users(std::slice::from_ref(&change.username)).await?;
+                outcome.applied.push(format!("User {} deleted", change.username));
+            } else {
+                let user = OnvifUser {
+                    username: change.username.clone(),
+                    password: change.password.clone(),
+                    level: change.level,
+                };
+                system.upsert_user(&user).await?;
+                outcome
+                    .applied
+                    .push(format!("User {} set ({})", change.username, change.level.as_str()));
+
+                // Senha do próprio VMS alterada: atualizar o cadastro da câmera
+                if let (true, Some(password)) = (change.username == camera.username, &change.password) {
+                    camera.password = password.clone();
+                    camera_changed = true;
+                }
+            }
+        }
+
+        if camera_changed {
+            camera.updated_at = Utc::now();
+            self.camera_repo
+                .update(camera.id, &camera)
+                .await
+                .context("Failed to update camera record")?;
+        }
+
+        Ok(())
+    }
+}
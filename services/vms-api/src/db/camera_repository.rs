@@ -37,6 +37,7 @@ impl CameraRepository {
                 username TEXT NOT NULL,
                 password TEXT NOT NULL,
                 rtsp_url TEXT NOT NULL,
+                sub_rtsp_url TEXT,
                 onvif_url TEXT,
                 transport TEXT NOT NULL DEFAULT 'auto',
                 use_ssl BOOLEAN NOT NULL DEFAULT 0,
@@ -48,12 +49,19 @@ impl CameraRepository {
                 framerate REAL NOT NULL DEFAULT 30.0,
                 codec TEXT NOT NULL DEFAULT 'h264',
                 
+                -- PTZ
+                ptz_supported BOOLEAN NOT NULL DEFAULT 0,
+                
                 -- Gravação
                 recording_mode TEXT NOT NULL DEFAULT 'disabled',
                 recording_dir TEXT,
                 audio_enabled BOOLEAN NOT NULL DEFAULT 0,
                 retention_days INTEGER NOT NULL DEFAULT 30,
                 
+                -- Sabotagem
+                tamper_detection_enabled BOOLEAN NOT NULL DEFAULT 0,
+                tamper_sensitivity REAL NOT NULL DEFAULT 0.5,
+                
                 -- Localização
                 shortcut TEXT,
                 latitude REAL,
@@ -78,6 +86,24 @@ impl CameraRepository {
             .execute(&self.pool)
             .await; // Ignore error if column already exists
 
+        // Migration: add sub_rtsp_url column (multi-stream ingest)
+        let _ = sqlx::query("ALTER TABLE cameras ADD COLUMN sub_rtsp_url TEXT")
+            .execute(&self.pool)
+            .await;
+
+        // Migration: add tamper detection columns
+        let _ = sqlx::query("ALTER TABLE cameras ADD COLUMN tamper_detection_enabled BOOLEAN NOT NULL DEFAULT 0")
+            .execute(&self.pool)
+            .await;
+        let _ = sqlx::query("ALTER TABLE cameras ADD COLUMN tamper_sensitivity REAL NOT NULL DEFAULT 0.5")
+            .execute(&self.pool)
+            .await;
+
+        // Migration: add ptz_supported column (ONVIF provisioning)
+        let _ = sqlx::query("ALTER TABLE cameras ADD COLUMN ptz_supported BOOLEAN NOT NULL DEFAULT 0")
+            .execute(&self.pool)
+            .await;
+
         Ok(())
     }
 
@@ -87,13 +113,14 @@ impl CameraRepository {
             r#"
             INSERT INTO cameras (
                 id, name, description, manufacturer, model, firmware, enabled,
-                ip_address, rtsp_port, onvif_port, username, password, rtsp_url, onvif_url,
+                ip_address, rtsp_port, onvif_port, username, password, rtsp_url, sub_rtsp_url, onvif_url,
                 transport, use_ssl, timeout_ms,
-                resolution_width, resolution_height, framerate, codec,
+                resolution_width, resolution_height, framerate, codec, ptz_supported,
                 recording_mode, recording_dir, audio_enabled, retention_days,
+                tamper_detection_enabled, tamper_sensitivity,
                 shortcut, latitude, longitude, server_id,
                 created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(camera.id.to_string())
@@ -109,6 +136,7 @@ impl CameraRepository {
         .bind(&camera.username)
         .bind(&camera.password)
         .bind(&camera.rtsp_url)
+        .bind(&camera.sub_rtsp_url)
         .bind(&camera.onvif_url)
         .bind(camera.transport.as_str())
         .bind(camera.use_ssl)
@@ -117,10 +145,13 @@ impl CameraRepository {
         .bind(camera.resolution_height as i64)
         .bind(camera.framerate as f64)
         .bind(&camera.codec)
+        .bind(camera.ptz_supported)
         .bind(camera.recording_mode.as_str())
         .bind(&camera.recording_dir)
         .bind(camera.audio_enabled)
         .bind(camera.retention_days as i64)
+        .bind(camera.tamper_detection_enabled)
+        .bind(camera.tamper_sensitivity as f64)
         .bind(&camera.shortcut)
         .bind(camera.latitude)
         .bind(camera.longitude)
@@ -179,9 +210,10 @@ impl CameraRepository {
             UPDATE cameras SET
                 name = ?, description = ?, manufacturer = ?, model = ?, firmware = ?, enabled = ?,
                 ip_address = ?, rtsp_port = ?, onvif_port = ?, username = ?, password = ?,
-                rtsp_url = ?, onvif_url = ?, transport = ?, use_ssl = ?, timeout_ms = ?,
-                resolution_width = ?, resolution_height = ?, framerate = ?, codec = ?,
+                rtsp_url = ?, sub_rtsp_url = ?, onvif_url = ?, transport = ?, use_ssl = ?, timeout_ms = ?,
+                resolution_width = ?, resolution_height = ?, framerate = ?, codec = ?, ptz_supported = ?,
                 recording_mode = ?, recording_dir = ?, audio_enabled = ?, retention_days = ?,
+                tamper_detection_enabled = ?, tamper_sensitivity = ?,
                 shortcut = ?, latitude = ?, longitude = ?, server_id = ?, updated_at = ?
             WHERE id = ?
             "#,
@@ -198,6 +230,7 @@ impl CameraRepository {
         .bind(&camera.username)
         .bind(&camera.password)
         .bind(&camera.rtsp_url)
+        .bind(&camera.sub_rtsp_url)
         .bind(&camera.onvif_url)
         .bind(camera.transport.as_str())
         .bind(camera.use_ssl)
@@ -206,10 +239,13 @@ impl CameraRepository {
         .bind(camera.resolution_height as i64)
         .bind(camera.framerate as f64)
         .bind(&camera.codec)
+        .bind(camera.ptz_supported)
         .bind(camera.recording_mode.as_str())
         .bind(&camera.recording_dir)
         .bind(camera.audio_enabled)
         .bind(camera.retention_days as i64)
+        .bind(camera.tamper_detection_enabled)
+        .bind(camera.tamper_sensitivity as f64)
         .bind(&camera.shortcut)
         .bind(camera.latitude)
         .bind(camera.longitude)
@@ -222,6 +258,18 @@ impl CameraRepository {
         Ok(())
     }
 
+    /// Assign camera to a server node (None = unassigned)
+    pub async fn assign_server(&self, id: Uuid, server_id: Option<Uuid>) -> Result<()> {
+        sqlx::query("UPDATE cameras SET server_id = ?, updated_at = ? WHERE id = ?")
+            .bind(server_id.map(|sid| sid.to_string()))
+            .bind(chrono::Utc::now().to_rfc3339())
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
     /// Delete camera
     pub async fn delete(&self, id: Uuid) -> Result<()> {
         sqlx::query("DELETE FROM cameras WHERE id = ?")
@@ -249,6 +297,7 @@ impl CameraRepository {
             username: row.get("username"),
             password: row.get("password"),
             rtsp_url: row.get("rtsp_url"),
+            sub_rtsp_url: row.get("sub_rtsp_url"),
             onvif_url: row.get("onvif_url"),
             transport: TransportProtocol::from_str(row.get("transport")),
             use_ssl: row.get("use_ssl"),
@@ -259,10 +308,15 @@ impl CameraRepository {
             framerate: row.get::<f64, _>("framerate") as f32,
             codec: row.get("codec"),
             
+            ptz_supported: row.get("ptz_supported"),
+            
             recording_mode: RecordingMode::from_str(row.get("recording_mode")),
             recording_dir: row.get("recording_dir"),
             audio_enabled: row.get("audio_enabled"),
             retention_days: row.get::<i64, _>("retention_days") as u32,
+
+            tamper_detection_enabled: row.get("tamper_detection_enabled"),
+            tamper_sensitivity: row.get::<f64, _>("tamper_sensitivity") as f32,
             
             shortcut: row.get("shortcut"),
             latitude: row.get("latitude"),
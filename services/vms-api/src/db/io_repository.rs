@@ -0,0 +1,168 @@
+//! Camera I/O ports database repository
+
+use anyhow::Result;
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::io::{IoPort, IoPortKind};
+
+pub struct IoRepository {
+    pool: SqlitePool,
+}
+
+impl IoRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Create io_ports table
+    pub async fn create_table(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS io_ports (
+                id TEXT PRIMARY KEY,
+                device_id TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                port INTEGER NOT NULL,
+                token TEXT NOT NULL,
+                name TEXT NOT NULL,
+                mode TEXT,
+                delay_ms INTEGER,
+                idle_state TEXT,
+                state BOOLEAN,
+                updated_at TEXT NOT NULL,
+                UNIQUE (device_id, kind, port)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Replace the ports of a camera, keeping the last known output state
+    pub async fn replace_device_ports(&self, device_id: Uuid, ports: &[IoPort]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let previous = sqlx::query("SELECT port, state FROM io_ports WHERE device_id = ? AND kind = 'output'")
+            .bind(device_id.to_string())
+            .fetch_all(&mut *tx)
+            .await?;
+        let previous_state = |port: u8| {
+            previous
+                .iter()
+                .find(|row| row.get::<i64, _>("port") == port as i64)
+                .and_then(|row| row.get::<Option<bool>, _>("state"))
+        };
+
+        sqlx::query("DELETE FROM io_ports WHERE device_id = ?")
+            .bind(device_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+
+        for port in ports {
+            let state = match port.kind {
+                IoPortKind::Output => port.state.or_else(|| previous_state(port.port)),
+                IoPortKind::Input => None,
+            };
+
+            sqlx::query(
+                r#"
+                INSERT INTO io_ports (
+                    id, device_id, kind, port, token, name, mode, delay_ms, idle_state, state, updated_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(port.id.to_string())
+            .bind(port.device_id.to_string())
+            .bind(port.kind.as_str())
+            .bind(port.port as i64)
+            .bind(&port.token)
+            .bind(&port.name)
+            .bind(&port.mode)
+            .bind(port.delay_ms.map(|d| d as i64))
+            .bind(&port.idle_state)
+            .bind(state)
+            .bind(port.updated_at.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// All ports, optionally filtered by camera
+    pub async fn list(&self, device_id: Option<Uuid>) -> Result<Vec<IoPort>> {
+        let rows = match device_id {
+            Some(id) => {
+                sqlx::query("SELECT * FROM io_ports WHERE device_id = ? ORDER BY kind, port")
+                    .bind(id.to_string())
+                    .fetch_all(&self.pool)
+                    .await?
+            }
+            None => {
+                sqlx::query("SELECT * FROM io_ports ORDER BY device_id, kind, port")
+                    .fetch_all(&self.pool)
+                    .await?
+            }
+        };
+
+        Ok(rows.iter().filter_map(|row| self.row_to_port(row)).collect())
+    }
+
+    /// Get port by camera, kind and number
+    pub async fn get(&self, device_id: Uuid, kind: IoPortKind, port: u8) -> Result<Option<IoPort>> {
+        let row = sqlx::query("SELECT * FROM io_ports WHERE device_id = ? AND kind = ? AND port = ?")
+            .bind(device_id.to_string())
+            .bind(kind.as_str())
+            .bind(port as i64)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.as_ref().and_then(|row| self.row_to_port(row)))
+    }
+
+    /// Record relay mode and state after a command
+    pub async fn update_output(&self, port: &IoPort) -> Result<()> {
+        sqlx::query("UPDATE io_ports SET mode = ?, delay_ms = ?, state = ?, updated_at = ? WHERE id = ?")
+            .bind(&port.mode)
+            .bind(port.delay_ms.map(|d| d as i64))
+            .bind(port.state)
+            .bind(port.updated_at.to_rfc3339())
+            .bind(port.id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Delete ports of a removed camera
+    pub async fn delete_device(&self, device_id: Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM io_ports WHERE device_id = ?")
+            .bind(device_id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    fn row_to_port(&self, row: &sqlx::sqlite::SqliteRow) -> Option<IoPort> {
+        Some(IoPort {
+            id: Uuid::parse_str(row.get("id")).ok()?,
+            device_id: Uuid::parse_str(row.get("device_id")).ok()?,
+            kind: IoPortKind::from_str(row.get("kind")),
+            port: row.get::<i64, _>("port") as u8,
+            token: row.get("token"),
+            name: row.get("name"),
+            mode: row.get("mode"),
+            delay_ms: row.get::<Option<i64>, _>("delay_ms").map(|d| d as u64),
+            idle_state: row.get("idle_state"),
+            state: row.get("state"),
+            updated_at: chrono::DateTime::parse_from_rfc3339(row.get("updated_at"))
+                .ok()?
+                .with_timezone(&chrono::Utc),
+        })
+    }
+}
@@ -1,3 +1,5 @@
 pub mod camera_repository;
 pub mod user_repository;
 pub mod server_repository;
+pub mod ptz_repository;
+pub mod io_repository;
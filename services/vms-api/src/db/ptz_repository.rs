@@ -0,0 +1,209 @@
+//! PTZ presets and tours database repository
+
+use anyhow::Result;
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+use vms_common::ptz::{PTZPosition, PTZPreset, PTZPresetId, PTZTour, PTZTourId};
+use vms_common::types::CameraId;
+
+pub struct PtzRepository {
+    pool: SqlitePool,
+}
+
+impl PtzRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Create ptz_presets and ptz_tours tables
+    pub async fn create_table(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS ptz_presets (
+                id TEXT PRIMARY KEY,
+                camera_id TEXT NOT NULL,
+                preset_index INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                pan REAL NOT NULL DEFAULT 0,
+                tilt REAL NOT NULL DEFAULT 0,
+                zoom REAL NOT NULL DEFAULT 0,
+                is_home BOOLEAN NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                UNIQUE (camera_id, preset_index)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS ptz_tours (
+                id TEXT PRIMARY KEY,
+                camera_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                description TEXT,
+                points TEXT NOT NULL,
+                loop_forever BOOLEAN NOT NULL DEFAULT 1,
+                repeat_count INTEGER NOT NULL DEFAULT 0
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Presets of a camera
+    pub async fn list_presets(&self, camera_id: Uuid) -> Result<Vec<PTZPreset>> {
+        let rows = sqlx::query("SELECT * FROM ptz_presets WHERE camera_id = ? ORDER BY preset_index")
+            .bind(camera_id.to_string())
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter().filter_map(|row| self.row_to_preset(row)).collect())
+    }
+
+    /// Insert or replace the preset of the same index
+    pub async fn save_preset(&self, preset: &PTZPreset) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO ptz_presets (
+                id, camera_id, preset_index, name, pan, tilt, zoom, is_home, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (camera_id, preset_index) DO UPDATE SET
+                name = excluded.name,
+                pan = excluded.pan,
+                tilt = excluded.tilt,
+                zoom = excluded.zoom
+            "#,
+        )
+        .bind(preset.id.0.to_string())
+        .bind(preset.camera_id.0.to_string())
+        .bind(preset.preset_index as i64)
+        .bind(&preset.name)
+        .bind(preset.position.pan as f64)
+        .bind(preset.position.tilt as f64)
+        .bind(preset.position.zoom as f64)
+        .bind(preset.is_home)
+        .bind(preset.created_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Delete preset by index
+    pub async fn delete_preset(&self, camera_id: Uuid, preset_index: u8) -> Result<()> {
+        sqlx::query("DELETE FROM ptz_presets WHERE camera_id = ? AND preset_index = ?")
+            .bind(camera_id.to_string())
+            .bind(preset_index as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Mark one preset as the camera home position
+    pub async fn set_home(&self, camera_id: Uuid, preset_index: u8) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE ptz_presets SET is_home = (preset_index = ?) WHERE camera_id = ?",
+        )
+        .bind(preset_index as i64)
+        .bind(camera_id.to_string())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Tours of a camera
+    pub async fn list_tours(&self, camera_id: Uuid) -> Result<Vec<PTZTour>> {
+        let rows = sqlx::query("SELECT * FROM ptz_tours WHERE camera_id = ? ORDER BY name")
+            .bind(camera_id.to_string())
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter().filter_map(|row| self.row_to_tour(row)).collect())
+    }
+
+    /// Insert or replace tour
+    pub async fn save_tour(&self, tour: &PTZTour) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO ptz_tours (
+                id, camera_id, name, description, points, loop_forever, repeat_count
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(tour.id.0.to_string())
+        .bind(tour.camera_id.0.to_string())
+        .bind(&tour.name)
+        .bind(&tour.description)
+        .bind(serde_json::to_string(&tour.points)?)
+        .bind(tour.loop_forever)
+        .bind(tour.repeat_count as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Delete tour
+    pub async fn delete_tour(&self, camera_id: Uuid, tour_id: PTZTourId) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM ptz_tours WHERE camera_id = ? AND id = ?")
+            .bind(camera_id.to_string())
+            .bind(tour_id.0.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Delete presets and tours of a removed camera
+    pub async fn delete_camera(&self, camera_id: Uuid) -> Result<()> {
+        for table in ["ptz_presets", "ptz_tours"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE camera_id = ?", table))
+                .bind(camera_id.to_string())
+                .execute(&self.pool)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    fn row_to_preset(&self, row: &sqlx::sqlite::SqliteRow) -> Option<PTZPreset> {
+        Some(PTZPreset {
+            id: PTZPresetId(Uuid::parse_str(row.get("id")).ok()?),
+            camera_id: CameraId(Uuid::parse_str(row.get("camera_id")).ok()?),
+            preset_index: row.get::<i64, _>("preset_index") as u8,
+            name: row.get("name"),
+            description: None,
+            position: PTZPosition {
+                pan: row.get::<f64, _>("pan") as f32,
+                tilt: row.get::<f64, _>("tilt") as f32,
+                zoom: row.get::<f64, _>("zoom") as f32,
+            },
+            icon: None,
+            is_home: row.get("is_home"),
+            thumbnail: None,
+            created_at: chrono::DateTime::parse_from_rfc3339(row.get("created_at"))
+                .ok()?
+                .with_timezone(&chrono::Utc),
+        })
+    }
+
+    fn row_to_tour(&self, row: &sqlx::sqlite::SqliteRow) -> Option<PTZTour> {
+        Some(PTZTour {
+            id: PTZTourId(Uuid::parse_str(row.get("id")).ok()?),
+            camera_id: CameraId(Uuid::parse_str(row.get("camera_id")).ok()?),
+            name: row.get("name"),
+            description: row.get("description"),
+            points: serde_json::from_str(row.get("points")).ok()?,
+            loop_forever: row.get("loop_forever"),
+            repeat_count: row.get::<i64, _>("repeat_count") as u32,
+            is_active: false,
+        })
+    }
+}
@@ -28,6 +28,7 @@ impl ServerRepository {
                 password TEXT NOT NULL,
                 status TEXT NOT NULL DEFAULT 'offline',
                 enabled BOOLEAN NOT NULL DEFAULT 1,
+                max_cameras INTEGER NOT NULL DEFAULT 100,
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL,
                 last_seen TEXT
@@ -37,6 +38,11 @@ impl ServerRepository {
         .execute(&self.pool)
         .await?;
 
+        // Migration: add max_cameras column (camera sharding)
+        let _ = sqlx::query("ALTER TABLE servers ADD COLUMN max_cameras INTEGER NOT NULL DEFAULT 100")
+            .execute(&self.pool)
+            .await;
+
         Ok(())
     }
 
@@ -46,8 +52,8 @@ impl ServerRepository {
             r#"
             INSERT INTO servers (
                 id, name, ip, port, username, password, status,
-                enabled, created_at, updated_at, last_seen
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                enabled, max_cameras, created_at, updated_at, last_seen
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(server.id.to_string())
@@ -58,6 +64,7 @@ impl ServerRepository {
         .bind(&server.password)
         .bind(server.status.as_str())
         .bind(server.enabled)
+        .bind(server.max_cameras as i64)
         .bind(server.created_at.to_rfc3339())
         .bind(server.updated_at.to_rfc3339())
         .bind(server.last_seen.map(|dt| dt.to_rfc3339()))
@@ -87,7 +94,18 @@ impl ServerRepository {
         Ok(row.and_then(|row| self.row_to_server(&row)))
     }
 
+    /// Get server by name (ingest node identity)
+    pub async fn get_by_name(&self, name: &str) -> Result<Option<Server>> {
+        let row = sqlx::query("SELECT * FROM servers WHERE name = ?")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.and_then(|row| self.row_to_server(&row)))
+    }
+
     /// Update server
+    #[allow(clippy::too_many_arguments)]
     pub async fn update(
         &self,
         id: Uuid,
@@ -97,6 +115,7 @@ impl ServerRepository {
         username: Option<String>,
         password: Option<String>,
         enabled: Option<bool>,
+        max_cameras: Option<u32>,
     ) -> Result<()> {
         let now = chrono::Utc::now().to_rfc3339();
 
@@ -154,6 +173,15 @@ impl ServerRepository {
                 .await?;
         }
 
+        if let Some(m) = max_cameras {
+            sqlx::query("UPDATE servers SET max_cameras = ?, updated_at = ? WHERE id = ?")
+                .bind(m as i64)
+                .bind(&now)
+                .bind(id.to_string())
+                .execute(&self.pool)
+                .await?;
+        }
+
         Ok(())
     }
 
@@ -170,6 +198,18 @@ impl ServerRepository {
         Ok(())
     }
 
+    /// Mark server offline without touching last_seen (heartbeat timeout)
+    pub async fn mark_offline(&self, id: Uuid) -> Result<()> {
+        sqlx::query("UPDATE servers SET status = ?, updated_at = ? WHERE id = ?")
+            .bind(ServerStatus::Offline.as_str())
+            .bind(chrono::Utc::now().to_rfc3339())
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
     /// Delete server
     pub async fn delete(&self, id: Uuid) -> Result<()> {
         sqlx::query("DELETE FROM servers WHERE id = ?")
@@ -191,6 +231,7 @@ impl ServerRepository {
             password: row.get("password"),
             status: ServerStatus::from_str(row.get("status")),
             enabled: row.get("enabled"),
+            max_cameras: row.get::<i64, _>("max_cameras") as u32,
             created_at: chrono::DateTime::parse_from_rfc3339(row.get("created_at"))
                 .ok()?
                 .with_timezone(&chrono::Utc),
@@ -0,0 +1,205 @@
+//! I/O Service - saídas a relé e entradas digitais de câmeras ONVIF
+//!
+//! Cada câmera com I/O é registrada como dispositivo (`device_id` = id da
+//! câmera); regras e objetos de mapa acionam sirenes e portões pelo número
+//! da porta. Como no PTZ, a sessão ONVIF de cada câmera é mantida entre
+//! comandos ([`OnvifSessions`]).
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use thiserror::Error;
+use tracing::{info, warn};
+use uuid::Uuid;
+use vms_onvif::device_io::io_port_numbers;
+use vms_onvif::{OnvifDevice, RelayMode, RelayOutput};
+
+use crate::db::camera_repository::CameraRepository;
+use crate::db::io_repository::IoRepository;
+use crate::models::io::{IoPort, IoPortKind};
+use crate::onvif_sessions::{OnvifSessions, SessionError};
+
+#[derive(Debug, Error)]
+pub enum IoError {
+    #[error("Camera not found")]
+    CameraNotFound,
+
+    #[error("Camera has no ONVIF endpoint configured")]
+    NoOnvif,
+
+    #[error("Output {0} not found")]
+    PortNotFound(u8),
+
+    #[error("Storage error: {0}")]
+    Storage(anyhow::Error),
+
+    #[error("ONVIF error: {0}")]
+    Device(#[from] anyhow::Error),
+}
+
+impl From<SessionError> for IoError {
+    fn from(e: SessionError) -> Self {
+        match e {
+            SessionError::CameraNotFound => Self::CameraNotFound,
+            SessionError::NoOnvif => Self::NoOnvif,
+            SessionError::Device(e) => Self::Device(e),
+        }
+    }
+}
+
+pub struct IoService {
+    io_repo: Arc<IoRepository>,
+    sessions: OnvifSessions<OnvifDevice>,
+}
+
+impl IoService {
+    pub fn new(camera_repo: Arc<CameraRepository>, io_repo: Arc<IoRepository>) -> Self {
+        Self {
+            io_repo,
+            sessions: OnvifSessions::new(camera_repo, "I/O"),
+        }
+    }
+
+    /// Portas registradas (todas ou de uma câmera)
+    pub async fn list(&self, device_id: Option<Uuid>) -> Result<Vec<IoPort>, IoError> {
+        self.io_repo.list(device_id).await.map_err(IoError::Storage)
+    }
+
+    /// Lê relés e entradas da câmera e atualiza o registro
+    pub async fn sync(&self, device_id: Uuid) -> Result<Vec<IoPort>, IoError> {
+        let io = self.session(device_id).await?.device_io();
+        let result = async { Ok((io.get_relay_outputs().await?, io.get_digital_inputs().await?)) }.await;
+        let (relays, inputs) = self.check(device_id, result).await?;
+        let now = Utc::now();
+
+        let tokens: Vec<&str> = relays.iter().map(|r| r.token.as_str()).collect();
+        let mut ports: Vec<IoPort> = relays
+            .iter()
+            .zip(io_port_numbers(&tokens))
+            .map(|(relay, port)| IoPort {
+                id: Uuid::new_v4(),
+                device_id,
+                kind: IoPortKind::Output,
+                port,
+                token: relay.token.clone(),
+                name: format!("Output {}", port),
+                mode: Some(relay.mode.as_str().to_lowercase()),
+                delay_ms: Some(relay.delay_time.as_millis() as u64),
+                idle_state: Some(relay.idle_state.clone()),
+                state: None,
+                updated_at: now,
+            })
+            .collect();
+
+        let tokens: Vec<&str> = inputs.iter().map(|i| i.token.as_str()).collect();
+        ports.extend(inputs.iter().zip(io_port_numbers(&tokens)).map(|(input, port)| IoPort {
+            id: Uuid::new_v4(),
+            device_id,
+            kind: IoPortKind::Input,
+            port,
+            token: input.token.clone(),
+            name: format!("Input {}", port),
+            mode: None,
+            delay_ms: None,
+            idle_state: input.idle_state.clone(),
+            state: None,
+            updated_at: now,
+        }));
+
+        self.io_repo
+            .replace_device_ports(device_id, &ports)
+            .await
+            .map_err(IoError::Storage)?;
+        info!(
+            "🔌 Camera {} I/O synced: {} outputs, {} inputs",
+            device_id,
+            relays.len(),
+            inputs.len()
+        );
+
+        self.list(Some(device_id)).await
+    }
+
+    /// Aciona uma saída; com `pulse_ms` a câmera devolve o relé ao repouso
+    pub async fn set_output(
+        &self,
+        device_id: Uuid,
+        port: u8,
+        state: bool,
+        pulse_ms: Option<u32>,
+    ) -> Result<IoPort, IoError> {
+        let output = match self.output(device_id, port).await? {
+            Some(output) => output,
+            None => {
+                self.sync(device_id).await?;
+                self.output(device_id, port).await?.ok_or(IoError::PortNotFound(port))?
+            }
+        };
+
+        let relay = RelayOutput {
+            token: output.token.clone(),
+            mode: match output.mode.as_deref() {
+                Some("monostable") => RelayMode::Monostable,
+                _ => RelayMode::Bistable,
+            },
+            delay_time: Duration::from_millis(output.delay_ms.unwrap_or_default()),
+            idle_state: output.idle_state.clone().unwrap_or_else(|| "open".to_string()),
+        };
+
+        let io = self.session(device_id).await?.device_io();
+        let result = match pulse_ms.filter(|_| state) {
+            Some(ms) => io
+                .pulse_relay(&relay, Duration::from_millis(ms as u64))
+                .await
+                .map(|_| (RelayMode::Monostable, ms as u64)),
+            None => io
+                .set_relay(&relay, state)
+                .await
+                .map(|_| (RelayMode::Bistable, relay.delay_time.as_millis() as u64)),
+        };
+        let (mode, delay_ms) = self.check(device_id, result).await?;
+        info!("⚡ Output {} set to {} (pulse {:?})", output.output_id(), state, pulse_ms);
+
+        // Um pulso termina em repouso; o modo do relé acompanha o último comando
+        let output = IoPort {
+            mode: Some(mode.as_str().to_lowercase()),
+            delay_ms: Some(delay_ms),
+            state: Some(state && mode == RelayMode::Bistable),
+            updated_at: Utc::now(),
+            ..output
+        };
+        self.io_repo.update_output(&output).await.map_err(IoError::Storage)?;
+        Ok(output)
+    }
+
+    /// Descarta a sessão (câmera alterada ou removida)
+    pub async fn invalidate(&self, device_id: Uuid) {
+        self.sessions.invalidate(device_id).await;
+    }
+
+    /// Remove o registro de I/O de uma câmera
+    pub async fn remove(&self, device_id: Uuid) {
+        self.invalidate(device_id).await;
+        if let Err(e) = self.io_repo.delete_device(device_id).await {
+            warn!("⚠️ Failed to delete I/O ports of camera {}: {}", device_id, e);
+        }
+    }
+
+    async fn output(&self, device_id: Uuid, port: u8) -> Result<Option<IoPort>, IoError> {
+        self.io_repo
+            .get(device_id, IoPortKind::Output, port)
+            .await
+            .map_err(IoError::Storage)
+    }
+
+    async fn check<T>(&self, device_id: Uuid, result: anyhow::Result<T>) -> Result<T, IoError> {
+        self.sessions.check(device_id, result).await
+    }
+
+    async fn session(&self, device_id: Uuid) -> Result<Arc<OnvifDevice>, IoError> {
+        self.sessions
+            .get(device_id, |_, device| async move { Ok::<_, IoError>(device) })
+            .await
+    }
+}
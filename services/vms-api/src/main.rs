@@ -10,16 +10,40 @@ use sqlx::sqlite::SqlitePoolOptions;
 use std::sync::Arc;
 use tokio::net::TcpListener;
 use tracing::info;
+use vms_common::gateway_client::{join_gateway, public_url};
 
 mod db;
 mod models;
 mod routes;
 mod recording_manager;
+mod camera_assigner;
+mod ptz_controller;
+mod ptz_service;
+mod io_service;
+mod onvif_sessions;
+mod config_jobs;
+mod stream_client;
+mod stream_stats;
 
+use camera_assigner::CameraAssigner;
+use config_jobs::ConfigJobManager;
 use db::camera_repository::CameraRepository;
 use db::user_repository::UserRepository;
+use db::ptz_repository::PtzRepository;
+use db::io_repository::IoRepository;
 use db::server_repository::ServerRepository;
+use ptz_controller::PtzController;
+use ptz_service::PtzService;
+use io_service::IoService;
 use recording_manager::RecordingManager;
+use stream_client::StreamClient;
+use stream_stats::StreamStatsStore;
+
+/// Nome do serviço no registro do vms-gateway
+const SERVICE_NAME: &str = "vms-api";
+
+/// Porta da API REST
+const HTTP_PORT: u16 = 9095;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -27,6 +51,15 @@ pub struct AppState {
     pub user_repo: Arc<UserRepository>,
     pub server_repo: Arc<ServerRepository>,
     pub recording_manager: Arc<RecordingManager>,
+    pub camera_assigner: Arc<CameraAssigner>,
+    pub ptz_service: Arc<PtzService>,
+    pub ptz_controller: Arc<PtzController>,
+    pub io_service: Arc<IoService>,
+    pub config_jobs: Arc<ConfigJobManager>,
+    /// Sinalização WebRTC repassada ao nó vms-stream da câmera
+    pub stream_client: Arc<StreamClient>,
+    /// Última telemetria reportada pelos nós de ingestão, por câmera
+    pub stream_stats: Arc<StreamStatsStore>,
 }
 
 #[tokio::main]
@@ -58,13 +91,52 @@ async fn main() -> Result<()> {
     let server_repo = ServerRepository::new(pool.clone());
     server_repo.create_table().await?;
 
+    let ptz_repo = PtzRepository::new(pool.clone());
+    ptz_repo.create_table().await?;
+
+    let io_repo = IoRepository::new(pool.clone());
+    io_repo.create_table().await?;
+
     info!("✅ Database tables created");
 
+    let camera_repo = Arc::new(camera_repo);
+    let server_repo = Arc::new(server_repo);
+
+    // Distribuição de câmeras entre nós de ingestão
+    let camera_assigner = Arc::new(CameraAssigner::new(camera_repo.clone(), server_repo.clone()));
+    camera_assigner.clone().spawn();
+    info!("🔀 Camera assigner started");
+
+    // Controle PTZ: sessões ONVIF, arbitragem por prioridade e tours
+    let ptz_service = Arc::new(PtzService::new(camera_repo.clone()));
+    let ptz_controller = Arc::new(PtzController::new(ptz_service.clone(), Arc::new(ptz_repo)));
+    ptz_controller.clone().spawn();
+    info!("🎮 PTZ controller started");
+
+    // Saídas a relé e entradas digitais das câmeras
+    let io_service = Arc::new(IoService::new(camera_repo.clone(), Arc::new(io_repo)));
+
+    // Configuração em massa via ONVIF
+    let config_jobs = Arc::new(ConfigJobManager::new(camera_repo.clone()));
+
+    // Sinalização WebRTC com os nós vms-stream
+    let stream_client = Arc::new(StreamClient::new(camera_repo.clone(), server_repo.clone()));
+
+    // Telemetria expira junto com o heartbeat dos nós
+    let stream_stats = Arc::new(StreamStatsStore::new(camera_assigner.heartbeat_timeout()));
+
     let state = AppState {
-        camera_repo: Arc::new(camera_repo),
+        camera_repo,
         user_repo: Arc::new(user_repo),
-        server_repo: Arc::new(server_repo),
+        server_repo,
         recording_manager: Arc::new(RecordingManager::new()),
+        camera_assigner,
+        ptz_service,
+        ptz_controller,
+        io_service,
+        config_jobs,
+        stream_client,
+        stream_stats,
     };
 
     // Auth routes
@@ -100,10 +172,28 @@ async fn main() -> Result<()> {
                 .delete(routes::cameras_v2::delete_camera),
         )
         .route("/test", post(routes::cameras_v2::test_camera_connection))
+        .route("/discover", post(routes::onvif::discover_cameras))
+        .route("/onvif", post(routes::onvif::add_onvif_camera))
+        .route("/onvif/probe", post(routes::onvif::probe_camera))
         .route("/:id/recording/start", post(routes::recordings::start_recording))
         .route("/:id/recording/stop", post(routes::recordings::stop_recording))
         .route("/:id/recording/status", get(routes::recordings::recording_status))
         .route("/:id/recordings", get(routes::recordings::list_recordings))
+        .route("/:id/stats", get(routes::cameras_v2::camera_stats))
+        .route("/:id/srt", post(routes::streams::camera_srt_url))
+        .route("/:id/srt/push", post(routes::streams::camera_srt_push))
+        .route("/:id/hls", post(routes::streams::camera_hls_url))
+        .route("/:id/ptz", get(routes::ptz::get_ptz).post(routes::ptz::control_ptz))
+        .route("/:id/ptz/control", get(routes::ptz::get_control))
+        .route("/:id/ptz/release", post(routes::ptz::release_control))
+        .route("/:id/ptz/presets/:index/home", post(routes::ptz::set_home_preset))
+        .route(
+            "/:id/ptz/tours",
+            get(routes::ptz::list_tours).post(routes::ptz::create_tour),
+        )
+        .route("/:id/ptz/tours/:tour_id", delete(routes::ptz::delete_tour))
+        .route("/:id/io", get(routes::io::list_camera_ports))
+        .route("/:id/io/sync", post(routes::io::sync_camera_ports))
         .with_state(state.clone());
 
     // Legacy routes (backward compatibility)
@@ -119,6 +209,9 @@ async fn main() -> Result<()> {
 
     // WebRTC routes
     let webrtc_routes = routes::webrtc::router();
+    let whep_routes = routes::whep::router();
+    let talk_routes = routes::talk::router();
+    let playback_routes = routes::playback::router();
 
     // Server routes
     let server_routes = Router::new()
@@ -132,7 +225,26 @@ async fn main() -> Result<()> {
                 .put(routes::servers::update_server)
                 .delete(routes::servers::delete_server),
         )
+        .route("/register", post(routes::servers::register_node))
         .route("/:id/health", get(routes::servers::health_check_server))
+        .route("/:id/heartbeat", post(routes::servers::heartbeat))
+        .route("/:id/cameras", get(routes::servers::list_server_cameras))
+        .route("/:id/stats", post(routes::servers::report_stats))
+        .with_state(state.clone());
+
+    // I/O routes
+    let io_routes = Router::new()
+        .route("/", get(routes::io::list_ports))
+        .route("/:device_id/outputs/:port", post(routes::io::set_output))
+        .with_state(state.clone());
+
+    // Config job routes
+    let config_job_routes = Router::new()
+        .route(
+            "/",
+            get(routes::config_jobs::list_jobs).post(routes::config_jobs::create_job),
+        )
+        .route("/:id", get(routes::config_jobs::get_job))
         .with_state(state.clone());
 
     // API v1 routes
@@ -141,7 +253,13 @@ async fn main() -> Result<()> {
         .nest("/users", user_routes)
         .nest("/cameras", camera_routes)
         .nest("/servers", server_routes)
+        .nest("/io", io_routes)
+        .nest("/config-jobs", config_job_routes)
         .nest("/webrtc", webrtc_routes)
+        .nest("/whep", whep_routes)
+        .nest("/talk", talk_routes)
+        // Fora de `/playback`, que o gateway encaminha ao vms-storage
+        .nest("/webrtc/playback", playback_routes)
         .merge(legacy_routes)
         // MJPEG removed - using GStreamer vms-player for preview
         .route("/filesystem/list", get(routes::filesystem::list_directory))
@@ -153,7 +271,9 @@ async fn main() -> Result<()> {
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
-        .allow_headers(Any);
+        .allow_headers(Any)
+        // WHEP players read the session resource and ICE servers
+        .expose_headers([axum::http::header::LOCATION, axum::http::header::LINK]);
 
     let app = Router::new()
         .route("/health", get(routes::health_check))
@@ -165,9 +285,13 @@ async fn main() -> Result<()> {
         .layer(cors)
         .fallback(routes::not_found);
 
-    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], 9095));
+    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], HTTP_PORT));
     let listener = TcpListener::bind(addr).await?;
 
+    // Registro no vms-gateway, que encaminha `/api/v1` a esta instância; a
+    // configuração central não tem seção própria do vms-api
+    join_gateway(SERVICE_NAME, public_url(HTTP_PORT)).await;
+
     info!("🌐 HTTP API listening on http://{}", addr);
     info!("🔐 Auth API: http://{}/api/v1/auth/login", addr);
     info!("👥 Users API: http://{}/api/v1/users", addr);
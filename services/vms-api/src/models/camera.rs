@@ -83,6 +83,8 @@ pub struct Camera {
     pub username: String,
     pub password: String,
     pub rtsp_url: String,
+    /// Sub stream (baixa resolução) usado por analytics e mobile
+    pub sub_rtsp_url: Option<String>,
     pub onvif_url: Option<String>,
     pub transport: TransportProtocol,
     pub use_ssl: bool,
@@ -94,12 +96,20 @@ pub struct Camera {
     pub framerate: f32,
     pub codec: String,
     
+    // === PTZ ===
+    pub ptz_supported: bool,
+    
     // === Gravação ===
     pub recording_mode: RecordingMode,
     pub recording_dir: Option<String>,
     pub audio_enabled: bool,
     pub retention_days: u32,
     
+    // === Sabotagem (tampering) ===
+    pub tamper_detection_enabled: bool,
+    /// Sensibilidade 0.0-1.0
+    pub tamper_sensitivity: f32,
+    
     // === Localização ===
     pub shortcut: Option<String>,
     pub latitude: Option<f64>,
@@ -131,6 +141,8 @@ pub struct CreateCameraRequest {
     pub username: String,
     pub password: String,
     pub stream_path: Option<String>,
+    /// Path do sub stream (ex: "stream2"), se a câmera oferecer
+    pub sub_stream_path: Option<String>,
     #[serde(default)]
     pub transport: TransportProtocol,
     #[serde(default)]
@@ -147,6 +159,12 @@ pub struct CreateCameraRequest {
     #[serde(default = "default_retention")]
     pub retention_days: u32,
     
+    // Sabotagem
+    #[serde(default)]
+    pub tamper_detection_enabled: bool,
+    #[serde(default = "default_tamper_sensitivity")]
+    pub tamper_sensitivity: f32,
+    
     // Localização
     pub shortcut: Option<String>,
     pub latitude: Option<f64>,
@@ -159,6 +177,7 @@ pub struct CreateCameraRequest {
 fn default_rtsp_port() -> u16 { 554 }
 fn default_timeout() -> u32 { 30000 }
 fn default_retention() -> u32 { 30 }
+fn default_tamper_sensitivity() -> f32 { 0.5 }
 
 /// Request to update a camera
 #[derive(Debug, Deserialize)]
@@ -175,6 +194,7 @@ pub struct UpdateCameraRequest {
     pub onvif_port: Option<Option<u16>>,
     pub username: Option<String>,
     pub password: Option<String>,
+    pub sub_rtsp_url: Option<Option<String>>,
     pub transport: Option<TransportProtocol>,
     pub use_ssl: Option<bool>,
     pub timeout_ms: Option<u32>,
@@ -184,6 +204,9 @@ pub struct UpdateCameraRequest {
     pub audio_enabled: Option<bool>,
     pub retention_days: Option<u32>,
     
+    pub tamper_detection_enabled: Option<bool>,
+    pub tamper_sensitivity: Option<f32>,
+    
     pub shortcut: Option<Option<String>>,
     pub latitude: Option<Option<f64>>,
     pub longitude: Option<Option<f64>>,
@@ -211,6 +234,7 @@ pub struct CameraPublic {
     pub ip_address: String,
     pub rtsp_port: u16,
     pub rtsp_url: String,
+    pub sub_rtsp_url: Option<String>,
     pub transport: TransportProtocol,
     pub resolution_width: u32,
     pub resolution_height: u32,
@@ -232,6 +256,7 @@ impl From<Camera> for CameraPublic {
             ip_address: c.ip_address,
             rtsp_port: c.rtsp_port,
             rtsp_url: c.rtsp_url,
+            sub_rtsp_url: c.sub_rtsp_url,
             transport: c.transport,
             resolution_width: c.resolution_width,
             resolution_height: c.resolution_height,
@@ -254,6 +279,9 @@ impl Camera {
             "rtsp://{}:{}/{}",
             req.ip_address, req.rtsp_port, stream_path
         );
+        let sub_rtsp_url = req.sub_stream_path.as_ref().map(|path| {
+            format!("rtsp://{}:{}/{}", req.ip_address, req.rtsp_port, path)
+        });
         
         let onvif_url = req.onvif_port.map(|port| {
             format!("http://{}:{}", req.ip_address, port)
@@ -276,6 +304,7 @@ impl Camera {
             username: req.username,
             password: req.password,
             rtsp_url,
+            sub_rtsp_url,
             onvif_url,
             transport: req.transport,
             use_ssl: req.use_ssl,
@@ -286,11 +315,16 @@ impl Camera {
             framerate: 30.0,
             codec: "h264".to_string(),
             
+            ptz_supported: false,
+            
             recording_mode: req.recording_mode,
             recording_dir: req.recording_dir,
             audio_enabled: req.audio_enabled,
             retention_days: req.retention_days,
             
+            tamper_detection_enabled: req.tamper_detection_enabled,
+            tamper_sensitivity: req.tamper_sensitivity.clamp(0.0, 1.0),
+            
             shortcut: req.shortcut,
             latitude: req.latitude,
             longitude: req.longitude,
@@ -301,4 +335,12 @@ impl Camera {
             updated_at: now,
         }
     }
+
+    /// ONVIF endpoint: explicit URL or derived from IP and ONVIF port
+    pub fn onvif_endpoint(&self) -> Option<String> {
+        self.onvif_url
+            .clone()
+            .filter(|u| !u.is_empty())
+            .or_else(|| self.onvif_port.map(|port| format!("http://{}:{}", self.ip_address, port)))
+    }
 }
@@ -0,0 +1,148 @@
+//! Bulk camera configuration job model
+//!
+//! A job pushes the same settings (video encoder from a `MediaProfile`,
+//! time/NTP and users) to a set of cameras via ONVIF and reports the
+//! outcome of each camera.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use vms_common::media_profile::MediaProfile;
+use vms_onvif::UserLevel;
+
+/// Which camera stream receives the media profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamSelector {
+    /// Highest resolution media profile
+    #[default]
+    Main,
+    /// Lowest resolution media profile
+    Sub,
+}
+
+/// Camera clock settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeSettings {
+    /// NTP servers (IP or DNS); empty = set the clock to the server time
+    #[serde(default)]
+    pub ntp_servers: Vec<String>,
+    /// POSIX TZ string (e.g. `BRT3`)
+    pub timezone: Option<String>,
+}
+
+/// Camera user to create/update or delete
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraUserChange {
+    pub username: String,
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+    #[serde(default = "default_user_level")]
+    pub level: UserLevel,
+    #[serde(default)]
+    pub delete: bool,
+}
+
+fn default_user_level() -> UserLevel {
+    UserLevel::User
+}
+
+/// Create job request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigJobRequest {
+    pub camera_ids: Vec<Uuid>,
+    pub media_profile: Option<MediaProfile>,
+    #[serde(default)]
+    pub stream: StreamSelector,
+    pub time: Option<TimeSettings>,
+    #[serde(default)]
+    pub users: Vec<CameraUserChange>,
+}
+
+impl ConfigJobRequest {
+    /// Reject empty jobs
+    pub fn validate(&self) -> Result<(), String> {
+        if self.camera_ids.is_empty() {
+            return Err("No cameras selected".to_string());
+        }
+        if self.media_profile.is_none() && self.time.is_none() && self.users.is_empty() {
+            return Err("Nothing to apply: set media_profile, time or users".to_string());
+        }
+        if let Some(user) = self.users.iter().find(|u| !u.delete && u.password.is_none()) {
+            return Err(format!("Password required for user {}", user.username));
+        }
+        Ok(())
+    }
+}
+
+/// Job status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Running,
+    Completed,
+}
+
+/// Status of one camera in the job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CameraJobStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// Outcome of one camera
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraJobResult {
+    pub camera_id: Uuid,
+    pub camera_name: Option<String>,
+    pub status: CameraJobStatus,
+    /// Settings applied
+    pub applied: Vec<String>,
+    /// Adjustments made to fit the camera (clamped values, unsupported items)
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl CameraJobResult {
+    pub fn pending(camera_id: Uuid) -> Self {
+        Self {
+            camera_id,
+            camera_name: None,
+            status: CameraJobStatus::Pending,
+            applied: Vec::new(),
+            warnings: Vec::new(),
+            error: None,
+            finished_at: None,
+        }
+    }
+}
+
+/// Bulk configuration job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigJob {
+    pub id: Uuid,
+    pub status: JobStatus,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<CameraJobResult>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl ConfigJob {
+    pub fn new(camera_ids: &[Uuid]) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            status: JobStatus::Running,
+            succeeded: 0,
+            failed: 0,
+            results: camera_ids.iter().copied().map(CameraJobResult::pending).collect(),
+            created_at: Utc::now(),
+            finished_at: None,
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! I/O port model - camera relay outputs and digital inputs
+//!
+//! Each ONVIF camera with I/O is a device (`device_id` = camera id) whose
+//! ports are addressed by number, as used by rule actions and map objects.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Port direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IoPortKind {
+    /// Relay output (siren, gate, light)
+    Output,
+    /// Digital input (alarm sensor)
+    Input,
+}
+
+impl IoPortKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Output => "output",
+            Self::Input => "input",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "input" => Self::Input,
+            _ => Self::Output,
+        }
+    }
+}
+
+/// I/O port registered from a camera
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IoPort {
+    pub id: Uuid,
+    /// Camera that owns the port
+    pub device_id: Uuid,
+    pub kind: IoPortKind,
+    /// Port number (from the ONVIF token when possible, else 1-based position)
+    pub port: u8,
+    /// ONVIF token
+    pub token: String,
+    pub name: String,
+    /// Relay mode (`monostable` / `bistable`)
+    pub mode: Option<String>,
+    /// Relay pulse duration in milliseconds
+    pub delay_ms: Option<u64>,
+    /// Idle state (`open` / `closed`)
+    pub idle_state: Option<String>,
+    /// Last state commanded by the VMS (outputs only)
+    pub state: Option<bool>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl IoPort {
+    /// Identifier used by rule actions (`device_id:port`)
+    pub fn output_id(&self) -> String {
+        format!("{}:{}", self.device_id, self.port)
+    }
+}
+
+/// Set output request
+#[derive(Debug, Deserialize)]
+pub struct SetOutputRequest {
+    pub state: bool,
+    /// Pulse duration; the camera returns the relay to idle afterwards
+    pub pulse_ms: Option<u32>,
+}
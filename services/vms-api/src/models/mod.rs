@@ -1,3 +1,5 @@
 pub mod camera;
 pub mod user;
 pub mod server;
+pub mod io;
+pub mod config_job;
@@ -17,6 +17,8 @@ pub struct Server {
     pub password: String,
     pub status: ServerStatus,
     pub enabled: bool,
+    /// Capacidade do nó (câmeras simultâneas)
+    pub max_cameras: u32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub last_seen: Option<DateTime<Utc>>,
@@ -34,6 +36,7 @@ impl Server {
             password,
             status: ServerStatus::Offline,
             enabled: true,
+            max_cameras: default_max_cameras(),
             created_at: now,
             updated_at: now,
             last_seen: None,
@@ -49,6 +52,81 @@ impl Server {
     pub fn webrtc_offer_url(&self) -> String {
         format!("{}/api/v1/webrtc/offer", self.base_url())
     }
+
+    /// Get the URL that receives browser ICE candidates
+    pub fn webrtc_ice_url(&self) -> String {
+        format!("{}/api/v1/webrtc/ice", self.base_url())
+    }
+
+    /// Get the long-poll URL for a viewer's server-side ICE candidates
+    pub fn webrtc_candidates_url(&self, peer_id: Uuid) -> String {
+        format!("{}/api/v1/webrtc/peers/{}/candidates", self.base_url(), peer_id)
+    }
+
+    /// Get the resource of a WebRTC viewer
+    pub fn webrtc_peer_url(&self, peer_id: Uuid) -> String {
+        format!("{}/api/v1/webrtc/peers/{}", self.base_url(), peer_id)
+    }
+
+    /// Get the URL listing the node's STUN/TURN servers
+    pub fn webrtc_ice_servers_url(&self) -> String {
+        format!("{}/api/v1/webrtc/ice-servers", self.base_url())
+    }
+
+    /// Get the WHEP endpoint for a camera on this node
+    pub fn whep_url(&self, camera_id: Uuid) -> String {
+        format!("{}/api/v1/whep/{}", self.base_url(), camera_id)
+    }
+
+    /// Get the WHEP session resource of a viewer
+    pub fn whep_resource_url(&self, camera_id: Uuid, peer_id: Uuid) -> String {
+        format!("{}/api/v1/whep/{}/{}", self.base_url(), camera_id, peer_id)
+    }
+
+    /// Get the talk-down endpoint (operator audio to the camera speaker)
+    pub fn talk_url(&self, camera_id: Uuid) -> String {
+        format!("{}/api/v1/talk/{}", self.base_url(), camera_id)
+    }
+
+    /// Get the endpoint that plays an audio clip on the camera speaker
+    pub fn talk_clip_url(&self, camera_id: Uuid) -> String {
+        format!("{}/api/v1/talk/{}/clip", self.base_url(), camera_id)
+    }
+
+    /// Get the resource of a talk session
+    pub fn talk_resource_url(&self, camera_id: Uuid, talk_id: Uuid) -> String {
+        format!("{}/api/v1/talk/{}/{}", self.base_url(), camera_id, talk_id)
+    }
+
+    /// Get the recorded playback endpoint for a camera, from `start`
+    pub fn playback_url(&self, camera_id: Uuid, start: DateTime<Utc>) -> String {
+        format!(
+            "{}/api/v1/playback/{}?start={}",
+            self.base_url(),
+            camera_id,
+            urlencoding::encode(&start.to_rfc3339())
+        )
+    }
+
+    /// Get the resource of a playback session
+    pub fn playback_resource_url(&self, camera_id: Uuid, playback_id: Uuid) -> String {
+        format!("{}/api/v1/playback/{}/{}", self.base_url(), camera_id, playback_id)
+    }
+
+    /// Get the SRT caller endpoint (push to a remote listener)
+    pub fn srt_callers_url(&self) -> String {
+        format!("{}/api/v1/srt/callers", self.base_url())
+    }
+
+    /// Node is enabled and sent a heartbeat within the timeout
+    pub fn is_alive(&self, heartbeat_timeout: chrono::Duration) -> bool {
+        self.enabled
+            && self.status == ServerStatus::Online
+            && self
+                .last_seen
+                .map(|seen| Utc::now() - seen <= heartbeat_timeout)
+                .unwrap_or(false)
+    }
 }
 
 /// Server status
@@ -88,6 +166,7 @@ pub struct ServerPublic {
     pub username: String,
     pub status: ServerStatus,
     pub enabled: bool,
+    pub max_cameras: u32,
     pub webrtc_url: String,
     pub created_at: DateTime<Utc>,
     pub last_seen: Option<DateTime<Utc>>,
@@ -103,6 +182,7 @@ impl From<Server> for ServerPublic {
             username: s.username.clone(),
             status: s.status,
             enabled: s.enabled,
+            max_cameras: s.max_cameras,
             webrtc_url: s.webrtc_offer_url(),
             created_at: s.created_at,
             last_seen: s.last_seen,
@@ -122,6 +202,7 @@ pub struct CreateServerRequest {
 }
 
 fn default_port() -> u16 { 9094 }
+fn default_max_cameras() -> u32 { 100 }
 
 /// Request to update a server
 #[derive(Debug, Deserialize)]
@@ -132,4 +213,24 @@ pub struct UpdateServerRequest {
     pub username: Option<String>,
     pub password: Option<String>,
     pub enabled: Option<bool>,
+    pub max_cameras: Option<u32>,
+}
+
+/// Request sent by an ingest node when it starts
+#[derive(Debug, Deserialize)]
+pub struct RegisterNodeRequest {
+    pub name: String,
+    pub ip: String,
+    /// Stream port advertised by the node (vms-stream)
+    #[serde(default = "default_port")]
+    pub port: u16,
+    #[serde(default = "default_max_cameras")]
+    pub max_cameras: u32,
+}
+
+/// Response to node registration
+#[derive(Debug, Serialize)]
+pub struct RegisterNodeResponse {
+    pub server_id: Uuid,
+    pub heartbeat_interval_secs: u64,
 }
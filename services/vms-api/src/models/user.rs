@@ -3,6 +3,9 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use vms_common::ptz::PTZPriority;
+
+use super::camera::Camera;
 
 /// User role for authorization
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -79,6 +82,35 @@ impl User {
     pub fn can_control_ptz(&self) -> bool {
         matches!(self.role, UserRole::Admin | UserRole::Operator)
     }
+
+    /// Check if user can switch camera relay outputs (gates, sirens)
+    pub fn can_control_io(&self) -> bool {
+        matches!(self.role, UserRole::Admin | UserRole::Operator)
+    }
+
+    /// Check if user can speak through a camera speaker
+    pub fn can_talk(&self) -> bool {
+        matches!(self.role, UserRole::Admin | UserRole::Operator)
+    }
+
+    /// Check if user can push a camera to a remote SRT listener
+    pub fn can_push_streams(&self) -> bool {
+        matches!(self.role, UserRole::Admin | UserRole::Operator)
+    }
+
+    /// Check if user can watch a camera (disabled cameras: admins only)
+    pub fn can_view_camera(&self, camera: &Camera) -> bool {
+        self.enabled && (camera.enabled || self.is_admin())
+    }
+
+    /// PTZ control priority (None if user cannot control PTZ)
+    pub fn ptz_priority(&self) -> Option<PTZPriority> {
+        match self.role {
+            UserRole::Admin => Some(PTZPriority::High),
+            UserRole::Operator => Some(PTZPriority::Normal),
+            UserRole::Viewer => None,
+        }
+    }
 }
 
 /// Request to create new user
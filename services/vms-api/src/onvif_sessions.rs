@@ -0,0 +1,97 @@
+//! Sessões ONVIF por câmera
+//!
+//! PTZ e I/O mantêm o dispositivo conectado entre comandos, evitando
+//! GetCapabilities a cada pulso ou movimento. Cada serviço guarda o que
+//! precisa junto do dispositivo (o PTZ, por exemplo, o profile com PTZ).
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tracing::warn;
+use uuid::Uuid;
+use vms_onvif::{OnvifDevice, OnvifError};
+
+use crate::db::camera_repository::CameraRepository;
+use crate::models::camera::Camera;
+
+#[derive(Debug, Error)]
+pub enum SessionError {
+    #[error("Camera not found")]
+    CameraNotFound,
+
+    #[error("Camera has no ONVIF endpoint configured")]
+    NoOnvif,
+
+    #[error("ONVIF error: {0}")]
+    Device(#[from] anyhow::Error),
+}
+
+pub struct OnvifSessions<S> {
+    camera_repo: Arc<CameraRepository>,
+    /// Serviço dono das sessões, para os logs (`PTZ`, `I/O`)
+    kind: &'static str,
+    sessions: RwLock<HashMap<Uuid, Arc<S>>>,
+}
+
+impl<S> OnvifSessions<S> {
+    pub fn new(camera_repo: Arc<CameraRepository>, kind: &'static str) -> Self {
+        Self {
+            camera_repo,
+            kind,
+            sessions: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Sessão da câmera; sem sessão, conecta e monta com `open`
+    pub async fn get<E, F, Fut>(&self, camera_id: Uuid, open: F) -> Result<Arc<S>, E>
+    where
+        E: From<SessionError>,
+        F: FnOnce(Camera, OnvifDevice) -> Fut,
+        Fut: Future<Output = Result<S, E>>,
+    {
+        if let Some(session) = self.sessions.read().await.get(&camera_id) {
+            return Ok(session.clone());
+        }
+
+        let camera = self
+            .camera_repo
+            .get(camera_id)
+            .await
+            .map_err(SessionError::Device)?
+            .ok_or(SessionError::CameraNotFound)?;
+        let onvif_url = camera.onvif_endpoint().ok_or(SessionError::NoOnvif)?;
+
+        let mut device =
+            OnvifDevice::new(&onvif_url, &camera.username, &camera.password).map_err(SessionError::Device)?;
+        device.connect().await.map_err(SessionError::Device)?;
+
+        let session = Arc::new(open(camera, device).await?);
+        self.sessions.write().await.insert(camera_id, session.clone());
+        Ok(session)
+    }
+
+    /// Descarta a sessão (câmera alterada ou removida)
+    pub async fn invalidate(&self, camera_id: Uuid) {
+        self.sessions.write().await.remove(&camera_id);
+    }
+
+    /// Falhas de transporte/autenticação derrubam a sessão para reconectar
+    pub async fn check<T, E>(&self, camera_id: Uuid, result: anyhow::Result<T>) -> Result<T, E>
+    where
+        E: From<anyhow::Error>,
+    {
+        if let Err(e) = &result {
+            if matches!(
+                e.downcast_ref::<OnvifError>(),
+                Some(OnvifError::Http(_) | OnvifError::NotAuthorized)
+            ) {
+                warn!("{} session of camera {} dropped: {}", self.kind, camera_id, e);
+                self.invalidate(camera_id).await;
+            }
+        }
+        Ok(result?)
+    }
+}
@@ -0,0 +1,594 @@
+//! PTZ Controller - arbitragem de controle PTZ e tours no servidor
+//!
+//! Operadores obtêm um bloqueio (`PTZLock`) com a prioridade do seu papel ao
+//! enviar comandos; o bloqueio é renovado a cada comando e expira após
+//! `LOCK_DURATION_SECS` sem uso. Tours rodam no servidor com prioridade `Low`:
+//! um operador de prioridade maior assume a câmera e o tour fica pausado até
+//! o bloqueio ser liberado ou expirar. Sem bloqueio nem tour ativo, a câmera
+//! volta ao preset home após `IDLE_HOME_SECS` parada.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+use vms_common::ptz::{
+    PTZCapabilities, PTZCommand, PTZLock, PTZPreset, PTZPriority, PTZState, PTZTour,
+    PTZTourId, TourPoint,
+};
+use vms_common::types::CameraId;
+
+use crate::db::ptz_repository::PtzRepository;
+use crate::ptz_service::{PtzError, PtzService};
+
+/// Validade do bloqueio de um operador desde o último comando
+pub const LOCK_DURATION_SECS: u32 = 60;
+
+/// Tempo parado (sem bloqueio nem tour) antes de voltar ao home
+pub const IDLE_HOME_SECS: u64 = 120;
+
+/// Intervalo de verificação de bloqueios, pausas e dwell
+const TICK: Duration = Duration::from_secs(1);
+
+/// Controle de uma câmera
+struct CameraControl {
+    state: PTZState,
+    last_activity: Instant,
+    at_home: bool,
+    tour_task: Option<JoinHandle<()>>,
+}
+
+/// Estado de arbitragem exposto na API
+#[derive(Debug, Serialize)]
+pub struct PtzControlState {
+    pub lock: Option<PTZLock>,
+    pub active_tour_id: Option<PTZTourId>,
+    pub tour_paused: bool,
+    pub presets: Vec<PTZPreset>,
+    pub tours: Vec<PTZTour>,
+}
+
+pub struct PtzController {
+    ptz_service: Arc<PtzService>,
+    ptz_repo: Arc<PtzRepository>,
+    cameras: Mutex<HashMap<Uuid, CameraControl>>,
+}
+
+impl PtzController {
+    pub fn new(ptz_service: Arc<PtzService>, ptz_repo: Arc<PtzRepository>) -> Self {
+        Self {
+            ptz_service,
+            ptz_repo,
+            cameras: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Inicia a supervisão de bloqueios expirados e retorno ao home
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(TICK);
+            loop {
+                interval.tick().await;
+                self.supervise().await;
+            }
+        })
+    }
+
+    /// Executa um comando de um operador, respeitando o bloqueio atual
+    pub async fn command(
+        self: &Arc<Self>,
+        camera_id: Uuid,
+        user: &str,
+        priority: PTZPriority,
+        command: PTZCommand,
+    ) -> Result<(), PtzError> {
+        match command {
+            PTZCommand::StartTour { tour_id } => return self.start_tour(camera_id, user, priority, tour_id).await,
+            PTZCommand::StopTour => return self.stop_tour(camera_id, user, priority).await,
+            _ => {}
+        }
+
+        let command = self
+            .with_control(camera_id, |control| -> Result<_, PtzError> {
+                authorize(&control.state, user, priority)?;
+                acquire(&mut control.state, user, priority);
+                control.last_activity = Instant::now();
+                control.state.is_moving = matches!(command, PTZCommand::ContinuousMove { .. });
+                if is_movement(&command) {
+                    control.at_home = matches!(command, PTZCommand::GotoHome);
+                }
+                Ok(match command {
+                    PTZCommand::GotoHome => home_command(&control.state),
+                    other => other,
+                })
+            })
+            .await??;
+
+        self.ptz_service.execute(camera_id, &command).await?;
+
+        match command {
+            PTZCommand::SetPreset { preset_index, name } => self.record_preset(camera_id, preset_index, &name).await,
+            PTZCommand::RemovePreset { preset_index } => {
+                self.ptz_repo
+                    .delete_preset(camera_id, preset_index)
+                    .await
+                    .map_err(PtzError::Storage)?;
+                self.with_control(camera_id, |control| {
+                    control.state.presets.retain(|p| p.preset_index != preset_index)
+                })
+                .await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Libera o bloqueio do operador (um tour pausado é retomado)
+    pub async fn release(&self, camera_id: Uuid, user: &str) -> Result<(), PtzError> {
+        self.with_control(camera_id, |control| match &control.state.lock {
+            Some(lock) if lock.locked_by == user => {
+                info!("🔓 PTZ lock released by {} on camera {}", user, camera_id);
+                control.state.lock = None;
+                Ok(())
+            }
+            Some(lock) if !lock.is_expired() => Err(locked(lock)),
+            _ => Ok(()),
+        })
+        .await?
+    }
+
+    /// Bloqueio, tour ativo, presets e tours da câmera
+    pub async fn control_state(&self, camera_id: Uuid) -> Result<PtzControlState, PtzError> {
+        self.with_control(camera_id, |control| {
+            let state = &control.state;
+            PtzControlState {
+                lock: state.lock.clone().filter(|lock| !lock.is_expired()),
+                active_tour_id: state.active_tour_id,
+                tour_paused: state.active_tour_id.is_some_and(|tour_id| {
+                    state.lock.as_ref().map(|lock| lock.locked_by.as_str()) != Some(tour_user(tour_id).as_str())
+                }),
+                presets: state.presets.clone(),
+                tours: state.tours.clone(),
+            }
+        })
+        .await
+    }
+
+    /// Cadastra um tour; todos os pontos devem referenciar presets conhecidos
+    pub async fn save_tour(&self, camera_id: Uuid, tour: PTZTour) -> Result<PTZTour, PtzError> {
+        self.with_control(camera_id, |control| {
+            if tour.points.is_empty() {
+                return Err(PtzError::InvalidTour("Tour has no points".to_string()));
+            }
+            let known = |point: &TourPoint| control.state.presets.iter().any(|p| p.id == point.preset_id);
+            if !tour.points.iter().all(known) {
+                return Err(PtzError::PresetNotFound);
+            }
+            Ok(())
+        })
+        .await??;
+
+        self.ptz_repo.save_tour(&tour).await.map_err(PtzError::Storage)?;
+        self.with_control(camera_id, |control| {
+            control.state.tours.retain(|t| t.id != tour.id);
+            control.state.tours.push(tour.clone());
+        })
+        .await?;
+
+        info!("🗺️ PTZ tour '{}' saved for camera {}", tour.name, camera_id);
+        Ok(tour)
+    }
+
+    /// Remove um tour (interrompendo-o se estiver ativo)
+    pub async fn delete_tour(&self, camera_id: Uuid, tour_id: PTZTourId) -> Result<(), PtzError> {
+        if !self
+            .ptz_repo
+            .delete_tour(camera_id, tour_id)
+            .await
+            .map_err(PtzError::Storage)?
+        {
+            return Err(PtzError::TourNotFound);
+        }
+
+        self.with_control(camera_id, |control| {
+            if control.state.active_tour_id == Some(tour_id) {
+                stop_tour_task(control);
+            }
+            control.state.tours.retain(|t| t.id != tour_id);
+        })
+        .await
+    }
+
+    /// Define o preset usado como home
+    pub async fn set_home(&self, camera_id: Uuid, preset_index: u8) -> Result<(), PtzError> {
+        self.with_control(camera_id, |control| {
+            control
+                .state
+                .get_preset_by_index(preset_index)
+                .map(|_| ())
+                .ok_or(PtzError::PresetNotFound)
+        })
+        .await??;
+
+        self.ptz_repo
+            .set_home(camera_id, preset_index)
+            .await
+            .map_err(PtzError::Storage)?;
+        self.with_control(camera_id, |control| {
+            for preset in &mut control.state.presets {
+                preset.is_home = preset.preset_index == preset_index;
+            }
+        })
+        .await
+    }
+
+    /// Câmera removida: interrompe o tour e apaga presets e tours
+    pub async fn remove(&self, camera_id: Uuid) {
+        if let Some(mut control) = self.cameras.lock().await.remove(&camera_id) {
+            stop_tour_task(&mut control);
+        }
+        if let Err(e) = self.ptz_repo.delete_camera(camera_id).await {
+            warn!("⚠️ Failed to delete PTZ data of camera {}: {}", camera_id, e);
+        }
+    }
+
+    async fn start_tour(
+        self: &Arc<Self>,
+        camera_id: Uuid,
+        user: &str,
+        priority: PTZPriority,
+        tour_id: PTZTourId,
+    ) -> Result<(), PtzError> {
+        let tour = self
+            .with_control(camera_id, |control| -> Result<_, PtzError> {
+                authorize(&control.state, user, priority)?;
+                let tour = control
+                    .state
+                    .tours
+                    .iter()
+                    .find(|t| t.id == tour_id)
+                    .cloned()
+                    .ok_or(PtzError::TourNotFound)?;
+
+                stop_tour_task(control);
+                control.state.lock = Some(tour_lock(&tour));
+                control.state.active_tour_id = Some(tour_id);
+                for t in &mut control.state.tours {
+                    t.is_active = t.id == tour_id;
+                }
+                Ok(tour)
+            })
+            .await??;
+
+        info!("🔁 PTZ tour '{}' started by {} on camera {}", tour.name, user, camera_id);
+        let handle = tokio::spawn(self.clone().run_tour(camera_id, tour));
+        self.with_control(camera_id, |control| {
+            if control.state.active_tour_id == Some(tour_id) {
+                control.tour_task = Some(handle);
+            }
+        })
+        .await
+    }
+
+    async fn stop_tour(&self, camera_id: Uuid, user: &str, priority: PTZPriority) -> Result<(), PtzError> {
+        self.with_control(camera_id, |control| {
+            authorize(&control.state, user, priority)?;
+            if control.state.active_tour_id.is_some() {
+                info!("⏹️ PTZ tour stopped by {} on camera {}", user, camera_id);
+                stop_tour_task(control);
+            }
+            Ok(())
+        })
+        .await?
+    }
+
+    async fn run_tour(self: Arc<Self>, camera_id: Uuid, tour: PTZTour) {
+        let mut round = 0;
+        while tour.loop_forever || round < tour.repeat_count.max(1) {
+            for point in &tour.points {
+                while !self.visit(camera_id, &tour, point).await {}
+            }
+            round += 1;
+        }
+
+        info!("🏁 PTZ tour '{}' finished on camera {}", tour.name, camera_id);
+        let _ = self
+            .with_control(camera_id, |control| {
+                control.tour_task = None;
+                stop_tour_task(control);
+            })
+            .await;
+    }
+
+    /// Visita um ponto do tour; false se o controle foi perdido durante o dwell
+    async fn visit(&self, camera_id: Uuid, tour: &PTZTour, point: &TourPoint) -> bool {
+        self.wait_for_control(camera_id, tour).await;
+
+        let preset_index = self
+            .with_control(camera_id, |control| {
+                control.at_home = false;
+                control.last_activity = Instant::now();
+                control
+                    .state
+                    .presets
+                    .iter()
+                    .find(|p| p.id == point.preset_id)
+                    .map(|p| p.preset_index)
+            })
+            .await
+            .ok()
+            .flatten();
+
+        let Some(preset_index) = preset_index else {
+            warn!("⚠️ PTZ tour '{}': preset {} no longer exists", tour.name, point.preset_id.0);
+            tokio::time::sleep(TICK).await;
+            return true;
+        };
+
+        let command = PTZCommand::GotoPreset {
+            preset_index,
+            speed: Some(point.speed),
+        };
+        if let Err(e) = self.ptz_service.execute(camera_id, &command).await {
+            warn!("⚠️ PTZ tour '{}' failed to reach preset {}: {}", tour.name, preset_index, e);
+        }
+
+        let owner = tour_user(tour.id);
+        for _ in 0..point.dwell_time_seconds {
+            tokio::time::sleep(TICK).await;
+            let in_control = self
+                .with_control(camera_id, |control| {
+                    control.state.lock.as_ref().is_some_and(|lock| lock.locked_by == owner)
+                })
+                .await
+                .unwrap_or(false);
+            if !in_control {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Aguarda o tour recuperar o controle (pausa enquanto um operador opera)
+    async fn wait_for_control(&self, camera_id: Uuid, tour: &PTZTour) {
+        let mut paused = false;
+        loop {
+            let granted = self
+                .with_control(camera_id, |control| claim_for_tour(&mut control.state, tour))
+                .await
+                .unwrap_or(false);
+            if granted {
+                if paused {
+                    info!("▶️ PTZ tour '{}' resumed on camera {}", tour.name, camera_id);
+                }
+                return;
+            }
+            if !paused {
+                info!("⏸️ PTZ tour '{}' paused on camera {}", tour.name, camera_id);
+                paused = true;
+            }
+            tokio::time::sleep(TICK).await;
+        }
+    }
+
+    /// Registra o preset salvo na câmera com a posição atual
+    async fn record_preset(&self, camera_id: Uuid, preset_index: u8, name: &str) -> Result<(), PtzError> {
+        let position = match self.ptz_service.status(camera_id).await {
+            Ok(status) => status.position,
+            Err(e) => {
+                warn!("⚠️ Could not read PTZ position for preset {}: {}", preset_index, e);
+                Default::default()
+            }
+        };
+
+        let preset = self
+            .with_control(camera_id, |control| {
+                let mut preset = PTZPreset::new(CameraId(camera_id), preset_index, name, position);
+                if let Some(existing) = control.state.get_preset_by_index(preset_index) {
+                    preset.id = existing.id;
+                    preset.is_home = existing.is_home;
+                    preset.created_at = existing.created_at;
+                }
+                preset
+            })
+            .await?;
+
+        self.ptz_repo.save_preset(&preset).await.map_err(PtzError::Storage)?;
+        self.with_control(camera_id, |control| {
+            let presets = &mut control.state.presets;
+            presets.retain(|p| p.preset_index != preset_index);
+            presets.push(preset);
+            presets.sort_by_key(|p| p.preset_index);
+        })
+        .await
+    }
+
+    /// Expira bloqueios e devolve ao home câmeras paradas
+    async fn supervise(&self) {
+        let mut homing = Vec::new();
+        {
+            let mut cameras = self.cameras.lock().await;
+            for (camera_id, control) in cameras.iter_mut() {
+                if let Some(lock) = control.state.lock.as_ref().filter(|lock| lock.is_expired()) {
+                    info!("🔓 PTZ lock of {} expired on camera {}", lock.locked_by, camera_id);
+                    control.state.lock = None;
+                }
+
+                let idle = control.last_activity.elapsed() >= Duration::from_secs(IDLE_HOME_SECS);
+                if idle && !control.at_home && control.state.lock.is_none() && control.state.active_tour_id.is_none() {
+                    control.at_home = true;
+                    homing.push((*camera_id, home_command(&control.state)));
+                }
+            }
+        }
+
+        for (camera_id, command) in homing {
+            info!("🏠 Camera {} idle, returning to home", camera_id);
+            if let Err(e) = self.ptz_service.execute(camera_id, &command).await {
+                warn!("⚠️ Failed to return camera {} to home: {}", camera_id, e);
+            }
+        }
+    }
+
+    async fn with_control<T>(
+        &self,
+        camera_id: Uuid,
+        f: impl FnOnce(&mut CameraControl) -> T,
+    ) -> Result<T, PtzError> {
+        let mut cameras = self.cameras.lock().await;
+        let control = match cameras.entry(camera_id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let mut state = PTZState::new(CameraId(camera_id), PTZCapabilities::default());
+                state.presets = self.ptz_repo.list_presets(camera_id).await.map_err(PtzError::Storage)?;
+                state.tours = self.ptz_repo.list_tours(camera_id).await.map_err(PtzError::Storage)?;
+                entry.insert(CameraControl {
+                    state,
+                    last_activity: Instant::now(),
+                    at_home: true,
+                    tour_task: None,
+                })
+            }
+        };
+        Ok(f(control))
+    }
+}
+
+fn locked(lock: &PTZLock) -> PtzError {
+    PtzError::Locked {
+        locked_by: lock.locked_by.clone(),
+        priority: lock.priority,
+    }
+}
+
+fn authorize(state: &PTZState, user: &str, priority: PTZPriority) -> Result<(), PtzError> {
+    match &state.lock {
+        Some(lock) if !state.can_control(user, priority) => Err(locked(lock)),
+        _ => Ok(()),
+    }
+}
+
+/// Toma (ou renova) o bloqueio para o operador
+fn acquire(state: &mut PTZState, user: &str, priority: PTZPriority) {
+    let mut lock = PTZLock::new(state.camera_id, user, priority);
+    lock.max_duration_seconds = Some(LOCK_DURATION_SECS);
+    state.lock = Some(lock);
+}
+
+/// Identidade do tour como dono do bloqueio
+fn tour_user(tour_id: PTZTourId) -> String {
+    format!("tour:{}", tour_id.0)
+}
+
+fn tour_lock(tour: &PTZTour) -> PTZLock {
+    let mut lock = PTZLock::new(tour.camera_id, &tour_user(tour.id), PTZPriority::Low);
+    lock.max_duration_seconds = None;
+    lock.reason = Some(format!("Tour {}", tour.name));
+    lock
+}
+
+/// Retoma o bloqueio para o tour se nenhum operador o detém
+fn claim_for_tour(state: &mut PTZState, tour: &PTZTour) -> bool {
+    if !state.can_control(&tour_user(tour.id), PTZPriority::Low) {
+        return false;
+    }
+    state.lock = Some(tour_lock(tour));
+    true
+}
+
+fn stop_tour_task(control: &mut CameraControl) {
+    if let Some(task) = control.tour_task.take() {
+        task.abort();
+    }
+    if let Some(tour_id) = control.state.active_tour_id.take() {
+        if control.state.lock.as_ref().is_some_and(|lock| lock.locked_by == tour_user(tour_id)) {
+            control.state.lock = None;
+        }
+    }
+    for tour in &mut control.state.tours {
+        tour.is_active = false;
+    }
+    control.last_activity = Instant::now();
+}
+
+/// Comandos que tiram a câmera da posição atual
+fn is_movement(command: &PTZCommand) -> bool {
+    matches!(
+        command,
+        PTZCommand::ContinuousMove { .. }
+            | PTZCommand::AbsoluteMove { .. }
+            | PTZCommand::RelativeMove { .. }
+            | PTZCommand::GotoPreset { .. }
+            | PTZCommand::GotoHome
+    )
+}
+
+/// Preset marcado como home, ou o home nativo da câmera
+fn home_command(state: &PTZState) -> PTZCommand {
+    match state.get_home_preset() {
+        Some(preset) => PTZCommand::GotoPreset {
+            preset_index: preset.preset_index,
+            speed: None,
+        },
+        None => PTZCommand::GotoHome,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vms_common::ptz::PTZPosition;
+
+    fn state_with_tour() -> (PTZState, PTZTour) {
+        let camera_id = CameraId::new();
+        let mut state = PTZState::new(camera_id, PTZCapabilities::default());
+        let preset = PTZPreset::new(camera_id, 1, "Gate", PTZPosition::default());
+        let mut tour = PTZTour::new(camera_id, "Perimeter");
+        tour.add_point(preset.id, 10);
+        state.presets.push(preset);
+        (state, tour)
+    }
+
+    #[test]
+    fn test_operator_pauses_tour_until_release() {
+        let (mut state, tour) = state_with_tour();
+        assert!(claim_for_tour(&mut state, &tour));
+
+        // Operador de prioridade normal assume; o tour não recupera o controle
+        authorize(&state, "op", PTZPriority::Normal).unwrap();
+        acquire(&mut state, "op", PTZPriority::Normal);
+        assert!(!claim_for_tour(&mut state, &tour));
+        assert!(matches!(
+            authorize(&state, "other", PTZPriority::Normal),
+            Err(PtzError::Locked { .. })
+        ));
+
+        // Bloqueio liberado (ou expirado): o tour retoma
+        state.lock = None;
+        assert!(claim_for_tour(&mut state, &tour));
+        assert_eq!(state.lock.unwrap().priority, PTZPriority::Low);
+    }
+
+    #[test]
+    fn test_expired_lock_is_overridable() {
+        let (mut state, _) = state_with_tour();
+        acquire(&mut state, "admin", PTZPriority::High);
+        assert!(authorize(&state, "op", PTZPriority::Normal).is_err());
+
+        state.lock.as_mut().unwrap().locked_at -= chrono::Duration::seconds(LOCK_DURATION_SECS as i64 + 1);
+        assert!(authorize(&state, "op", PTZPriority::Normal).is_ok());
+    }
+
+    #[test]
+    fn test_home_command_prefers_home_preset() {
+        let (mut state, _) = state_with_tour();
+        assert!(matches!(home_command(&state), PTZCommand::GotoHome));
+
+        state.presets[0].is_home = true;
+        assert!(matches!(home_command(&state), PTZCommand::GotoPreset { preset_index: 1, .. }));
+    }
+}
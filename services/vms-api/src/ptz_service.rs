@@ -0,0 +1,152 @@
+//! PTZ Service - controle PTZ de câmeras via ONVIF
+//!
+//! Mantém uma sessão ONVIF por câmera (dispositivo conectado e profile com
+//! PTZ), evitando GetCapabilities/GetProfiles a cada comando.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use thiserror::Error;
+use tracing::info;
+use uuid::Uuid;
+use vms_common::ptz::{PTZCapabilities, PTZCommand, PTZPriority};
+use vms_onvif::{CameraProfile, OnvifDevice, OnvifPreset, PtzStatus};
+
+use crate::db::camera_repository::CameraRepository;
+use crate::onvif_sessions::{OnvifSessions, SessionError};
+
+#[derive(Debug, Error)]
+pub enum PtzError {
+    #[error("Camera not found")]
+    CameraNotFound,
+
+    #[error("Camera has no ONVIF endpoint configured")]
+    NoOnvif,
+
+    #[error("Camera does not support PTZ")]
+    NotSupported,
+
+    #[error("PTZ locked by {locked_by} ({priority:?})")]
+    Locked {
+        locked_by: String,
+        priority: PTZPriority,
+    },
+
+    #[error("Tour not found")]
+    TourNotFound,
+
+    #[error("Preset not found")]
+    PresetNotFound,
+
+    #[error("Invalid tour: {0}")]
+    InvalidTour(String),
+
+    #[error("Storage error: {0}")]
+    Storage(anyhow::Error),
+
+    #[error("ONVIF error: {0}")]
+    Device(#[from] anyhow::Error),
+}
+
+impl From<SessionError> for PtzError {
+    fn from(e: SessionError) -> Self {
+        match e {
+            SessionError::CameraNotFound => Self::CameraNotFound,
+            SessionError::NoOnvif => Self::NoOnvif,
+            SessionError::Device(e) => Self::Device(e),
+        }
+    }
+}
+
+/// Dispositivo conectado e profile usado para PTZ
+pub struct PtzSession {
+    pub device: OnvifDevice,
+    pub profile: CameraProfile,
+}
+
+/// Estado PTZ consultado na câmera
+#[derive(Debug, Serialize)]
+pub struct PtzInfo {
+    pub camera_id: Uuid,
+    pub profile_token: String,
+    pub capabilities: PTZCapabilities,
+    pub status: PtzStatus,
+    pub presets: Vec<OnvifPreset>,
+}
+
+pub struct PtzService {
+    sessions: OnvifSessions<PtzSession>,
+}
+
+impl PtzService {
+    pub fn new(camera_repo: Arc<CameraRepository>) -> Self {
+        Self {
+            sessions: OnvifSessions::new(camera_repo, "PTZ"),
+        }
+    }
+
+    /// Executa um comando PTZ
+    pub async fn execute(&self, camera_id: Uuid, command: &PTZCommand) -> Result<(), PtzError> {
+        let session = self.session(camera_id).await?;
+        let result = session.device.execute_ptz(&session.profile, command).await;
+        self.check(camera_id, result).await
+    }
+
+    /// Capacidades, posição atual e presets
+    pub async fn info(&self, camera_id: Uuid) -> Result<PtzInfo, PtzError> {
+        let session = self.session(camera_id).await?;
+        let result = async {
+            let ptz = session.device.ptz().ok_or_else(|| anyhow::anyhow!("Device has no PTZ service"))?;
+            let token = session.profile.token.as_str();
+            Ok(PtzInfo {
+                camera_id,
+                profile_token: token.to_string(),
+                capabilities: session.device.ptz_capabilities(&session.profile).await?,
+                status: ptz.get_status(token).await?,
+                presets: ptz.get_presets(token).await?,
+            })
+        }
+        .await;
+        self.check(camera_id, result).await
+    }
+
+    /// Posição atual
+    pub async fn status(&self, camera_id: Uuid) -> Result<PtzStatus, PtzError> {
+        let session = self.session(camera_id).await?;
+        let result = match session.device.ptz() {
+            Some(ptz) => ptz.get_status(&session.profile.token).await,
+            None => Err(anyhow::anyhow!("Device has no PTZ service")),
+        };
+        self.check(camera_id, result).await
+    }
+
+    /// Descarta a sessão (câmera alterada ou removida)
+    pub async fn invalidate(&self, camera_id: Uuid) {
+        self.sessions.invalidate(camera_id).await;
+    }
+
+    async fn check<T>(&self, camera_id: Uuid, result: anyhow::Result<T>) -> Result<T, PtzError> {
+        self.sessions.check(camera_id, result).await
+    }
+
+    async fn session(&self, camera_id: Uuid) -> Result<Arc<PtzSession>, PtzError> {
+        self.sessions
+            .get(camera_id, |camera, device: OnvifDevice| async move {
+                if device.ptz().is_none() {
+                    return Err(PtzError::NotSupported);
+                }
+
+                let profiles = device.get_profiles().await?;
+                let profile = profiles
+                    .iter()
+                    .find(|p| p.ptz_configuration_token.is_some())
+                    .or_else(|| profiles.first())
+                    .cloned()
+                    .ok_or(PtzError::NotSupported)?;
+
+                info!("🎮 PTZ session for camera {} (profile {})", camera.name, profile.token);
+                Ok(PtzSession { device, profile })
+            })
+            .await
+    }
+}
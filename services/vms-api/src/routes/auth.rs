@@ -1,15 +1,18 @@
 //! Authentication and User API routes
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    async_trait,
+    extract::{FromRequestParts, Path, State},
+    http::{header::AUTHORIZATION, request::Parts, StatusCode},
     response::IntoResponse,
     Json,
 };
 use chrono::{Duration, Utc};
-use jsonwebtoken::{encode, EncodingKey, Header};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use vms_common::service_auth::{service_secret_from_env, verify_service_token, SERVICE_TOKEN_PREFIX};
+use vms_common::ApiErrorBody;
 
 use crate::{
     db::user_repository::UserRepository,
@@ -34,6 +37,97 @@ pub struct Claims {
 const JWT_SECRET: &[u8] = b"vms-enterprise-secret-key-change-in-production";
 const JWT_EXPIRATION_HOURS: i64 = 24;
 
+/// Validate a token issued by `login` (signature and expiry)
+pub fn decode_token(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+    decode::<Claims>(token, &DecodingKey::from_secret(JWT_SECRET), &Validation::default())
+        .map(|data| data.claims)
+}
+
+type AuthRejection = (StatusCode, Json<ApiErrorBody>);
+
+fn unauthorized(message: &str) -> AuthRejection {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(ApiErrorBody::new("SESSION_TOKEN_INVALID", message)),
+    )
+}
+
+/// Token of the `Authorization: Bearer <token>` header
+fn bearer_token(parts: &Parts) -> Result<&str, AuthRejection> {
+    parts
+        .headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .ok_or_else(|| unauthorized("Missing bearer token"))
+}
+
+/// Enabled user of a JWT issued by `login`
+async fn user_from_token(token: &str, state: &AppState) -> Result<User, AuthRejection> {
+    let claims = decode_token(token).map_err(|_| unauthorized("Invalid or expired token"))?;
+    let user_id: Uuid = claims.sub.parse().map_err(|_| unauthorized("Invalid token subject"))?;
+
+    match state.user_repo.get(user_id).await {
+        Ok(Some(user)) if user.enabled => Ok(user),
+        Ok(_) => Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiErrorBody::new("USER_DISABLED", "Unknown or disabled user")),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiErrorBody::new("DB_ERROR", e.to_string())),
+        )),
+    }
+}
+
+/// Enabled user authenticated by `Authorization: Bearer <jwt>`
+pub struct AuthUser(pub User);
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(parts)?;
+        user_from_token(token, state).await.map(AuthUser)
+    }
+}
+
+/// Caller of routes shared by operators and internal services (rule engine):
+/// a user JWT or a service token signed with `VMS_SERVICE_SECRET`
+pub enum Principal {
+    User(User),
+    Service(String),
+}
+
+impl Principal {
+    /// Username or service name, for logs
+    pub fn name(&self) -> &str {
+        match self {
+            Self::User(user) => &user.username,
+            Self::Service(service) => service,
+        }
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for Principal {
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(parts)?;
+        if !token.starts_with(SERVICE_TOKEN_PREFIX) {
+            return user_from_token(token, state).await.map(Principal::User);
+        }
+
+        let secret = service_secret_from_env().ok_or_else(|| unauthorized("Service authentication is disabled"))?;
+        verify_service_token(secret.as_bytes(), token, Utc::now().timestamp())
+            .map(Principal::Service)
+            .map_err(|e| unauthorized(&e.to_string()))
+    }
+}
+
 /// POST /api/v1/auth/login - User login
 pub async fn login(
     State(state): State<AppState>,
@@ -7,6 +7,9 @@ use axum::{
     Json,
 };
 use uuid::Uuid;
+use vms_common::camera::CameraInfo;
+use vms_common::telemetry::CameraStreamStats;
+use vms_common::types::CameraId;
 
 use crate::{
     models::camera::{Camera, CreateCameraRequest, UpdateCameraRequest},
@@ -90,7 +93,15 @@ pub async fn update_camera(
     // Apply updates - compute derived values first
     let new_ip = req.ip_address.clone().unwrap_or(existing.ip_address.clone());
     let new_port = req.rtsp_port.unwrap_or(existing.rtsp_port);
-    let new_rtsp_url = format!("rtsp://{}:{}/stream1", new_ip, new_port);
+    // Mantém o path (ex.: obtido via ONVIF) e troca apenas host/porta
+    let new_rtsp_url = url::Url::parse(&existing.rtsp_url)
+        .ok()
+        .and_then(|mut url| {
+            url.set_host(Some(&new_ip)).ok()?;
+            url.set_port(Some(new_port)).ok()?;
+            Some(url.to_string())
+        })
+        .unwrap_or_else(|| format!("rtsp://{}:{}/stream1", new_ip, new_port));
 
     let updated = Camera {
         name: req.name.unwrap_or(existing.name),
@@ -111,17 +122,29 @@ pub async fn update_camera(
         recording_dir: req.recording_dir.unwrap_or(existing.recording_dir),
         audio_enabled: req.audio_enabled.unwrap_or(existing.audio_enabled),
         retention_days: req.retention_days.unwrap_or(existing.retention_days),
+        tamper_detection_enabled: req
+            .tamper_detection_enabled
+            .unwrap_or(existing.tamper_detection_enabled),
+        tamper_sensitivity: req
+            .tamper_sensitivity
+            .map(|s| s.clamp(0.0, 1.0))
+            .unwrap_or(existing.tamper_sensitivity),
         shortcut: req.shortcut.unwrap_or(existing.shortcut),
         latitude: req.latitude.unwrap_or(existing.latitude),
         longitude: req.longitude.unwrap_or(existing.longitude),
         server_id: req.server_id.unwrap_or(existing.server_id),
         rtsp_url: new_rtsp_url,
+        sub_rtsp_url: req.sub_rtsp_url.unwrap_or(existing.sub_rtsp_url),
         onvif_url: existing.onvif_url,
         ..existing
     };
 
     match state.camera_repo.update(id, &updated).await {
-        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Ok(_) => {
+            state.ptz_service.invalidate(id).await;
+            state.io_service.invalidate(id).await;
+            StatusCode::NO_CONTENT.into_response()
+        }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(serde_json::json!({ "error": e.to_string() })),
@@ -136,7 +159,13 @@ pub async fn delete_camera(
     Path(id): Path<Uuid>,
 ) -> impl IntoResponse {
     match state.camera_repo.delete(id).await {
-        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Ok(_) => {
+            state.ptz_service.invalidate(id).await;
+            state.ptz_controller.remove(id).await;
+            state.io_service.remove(id).await;
+            state.stream_stats.remove(id).await;
+            StatusCode::NO_CONTENT.into_response()
+        }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(serde_json::json!({ "error": e.to_string() })),
@@ -202,3 +231,53 @@ pub async fn test_camera_connection(
         }
     }
 }
+
+/// Camera stream telemetry response
+#[derive(Debug, serde::Serialize)]
+pub struct CameraStatsResponse {
+    pub camera_id: Uuid,
+    /// Resumo (fps do stream principal e bitrate total)
+    pub info: CameraInfo,
+    pub streams: Vec<CameraStreamStats>,
+}
+
+/// GET /api/v1/cameras/:id/stats - Stream health and video-quality telemetry
+pub async fn camera_stats(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let name = match state.camera_repo.get(id).await {
+        Ok(Some(camera)) => camera.name,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "Camera not found" })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    };
+
+    match state.stream_stats.get(id).await {
+        Some(streams) => (
+            StatusCode::OK,
+            Json(CameraStatsResponse {
+                camera_id: id,
+                info: CameraInfo::from_stats(CameraId::from_uuid(id), name, &streams),
+                streams,
+            }),
+        )
+            .into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "No stats reported for this camera" })),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,62 @@
+//! Config job routes
+//! Configuração em massa de câmeras (encoder, data/hora, usuários)
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use uuid::Uuid;
+
+use crate::models::config_job::ConfigJobRequest;
+use crate::routes::auth::AuthUser;
+use crate::AppState;
+
+/// POST /api/v1/config-jobs - Iniciar job (202 + estado inicial)
+///
+/// Só administradores: o job grava encoder, relógio e usuários nas câmeras.
+pub async fn create_job(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(req): Json<ConfigJobRequest>,
+) -> impl IntoResponse {
+    if !user.is_admin() {
+        tracing::warn!("🚫 User {} denied camera configuration job", user.username);
+        return (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({ "error": "Only administrators can configure cameras" })),
+        )
+            .into_response();
+    }
+    tracing::info!("🛠️  Config job requested by {}", user.username);
+
+    match state.config_jobs.start(req).await {
+        Ok(job) => (StatusCode::ACCEPTED, Json(job)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": e })),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /api/v1/config-jobs - Jobs recentes
+pub async fn list_jobs(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.config_jobs.list().await)
+}
+
+/// GET /api/v1/config-jobs/:id - Progresso e resultado por câmera
+pub async fn get_job(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.config_jobs.get(id).await {
+        Some(job) => (StatusCode::OK, Json(job)).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Job not found" })),
+        )
+            .into_response(),
+    }
+}
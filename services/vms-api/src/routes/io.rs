@@ -0,0 +1,86 @@
+//! I/O routes
+//! Saídas a relé e entradas digitais das câmeras (sirenes, portões, sensores)
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::io_service::IoError;
+use crate::models::io::SetOutputRequest;
+use crate::routes::auth::Principal;
+use crate::AppState;
+
+fn error_response(e: IoError) -> Response {
+    let status = match e {
+        IoError::CameraNotFound | IoError::PortNotFound(_) => StatusCode::NOT_FOUND,
+        IoError::NoOnvif => StatusCode::UNPROCESSABLE_ENTITY,
+        IoError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        IoError::Device(_) => StatusCode::BAD_GATEWAY,
+    };
+    (status, Json(serde_json::json!({ "error": e.to_string() }))).into_response()
+}
+
+/// GET /api/v1/io - Todas as portas de I/O registradas
+pub async fn list_ports(State(state): State<AppState>) -> impl IntoResponse {
+    match state.io_service.list(None).await {
+        Ok(ports) => (StatusCode::OK, Json(ports)).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// GET /api/v1/cameras/:id/io - Portas de I/O da câmera
+pub async fn list_camera_ports(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.io_service.list(Some(id)).await {
+        Ok(ports) => (StatusCode::OK, Json(ports)).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// POST /api/v1/cameras/:id/io/sync - Ler relés e entradas da câmera
+pub async fn sync_camera_ports(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.io_service.sync(id).await {
+        Ok(ports) => (StatusCode::OK, Json(ports)).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// POST /api/v1/io/:device_id/outputs/:port - Acionar saída (regras e mapa)
+///
+/// Exige um operador/administrador ou o token de serviço do motor de regras.
+pub async fn set_output(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path((device_id, port)): Path<(Uuid, u8)>,
+    Json(req): Json<SetOutputRequest>,
+) -> impl IntoResponse {
+    if let Principal::User(user) = &principal {
+        if !user.can_control_io() {
+            tracing::warn!("🚫 User {} denied output {}:{}", user.username, device_id, port);
+            return (
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({ "error": "User cannot control I/O outputs" })),
+            )
+                .into_response();
+        }
+    }
+    tracing::info!("⚡ Output {}:{} requested by {}", device_id, port, principal.name());
+
+    match state
+        .io_service
+        .set_output(device_id, port, req.state, req.pulse_ms)
+        .await
+    {
+        Ok(output) => (StatusCode::OK, Json(output)).into_response(),
+        Err(e) => error_response(e),
+    }
+}
@@ -7,9 +7,15 @@ pub mod recordings;
 // pub mod mjpeg; // Removed - using GStreamer vms-player for preview
 pub mod auth;
 pub mod webrtc;
+pub mod whep;
+pub mod talk;
+pub mod playback;
 pub mod servers;
 pub mod filesystem;
-// pub mod onvif; // Temporarily disabled
+pub mod onvif;
+pub mod ptz;
+pub mod io;
+pub mod config_jobs;
 
 use axum::{http::StatusCode, Json};
 use serde::{Deserialize, Serialize};
@@ -1,12 +1,23 @@
-//! Rotas de discovery ONVIF
-//! Descobre câmeras na rede local
+//! Rotas ONVIF
+//! Descobre câmeras na rede local via WS-Discovery e cadastra câmeras a
+//! partir do Device/Media Service (vms-onvif)
 
 use axum::{
+    extract::State,
     http::StatusCode,
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use std::time::Duration;
+use tracing::{info, warn};
+use vms_onvif::{DiscoveredDevice, OnvifDevice, OnvifDiscovery, OnvifError};
+
+use crate::models::camera::{Camera, CreateCameraRequest};
+use crate::AppState;
+
+/// Timeout máximo aceito para discovery
+const MAX_DISCOVERY_TIMEOUT_SECS: u64 = 30;
 
 #[derive(Debug, Serialize)]
 pub struct DiscoveredCamera {
@@ -14,9 +25,35 @@ pub struct DiscoveredCamera {
     pub url: String,
     pub device_type: Option<String>,
     pub scopes: Vec<String>,
+    /// EndpointReference WS-Discovery (estável entre reinícios)
+    pub endpoint: String,
+    pub ip: Option<String>,
+    pub hardware: Option<String>,
+    pub location: Option<String>,
+    /// Todas as URLs anunciadas (IPv4/IPv6)
+    pub xaddrs: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
+impl From<DiscoveredDevice> for DiscoveredCamera {
+    fn from(d: DiscoveredDevice) -> Self {
+        Self {
+            name: d
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("Câmera ONVIF - {}", d.ip.as_deref().unwrap_or("?"))),
+            url: d.service_url().unwrap_or_default().to_string(),
+            device_type: d.types.first().cloned(),
+            scopes: d.scopes,
+            endpoint: d.endpoint,
+            ip: d.ip,
+            hardware: d.hardware,
+            location: d.location,
+            xaddrs: d.xaddrs,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
 pub struct DiscoverRequest {
     /// Timeout em segundos (default: 5)
     pub timeout_secs: Option<u64>,
@@ -28,31 +65,24 @@ pub struct DiscoverResponse {
     pub count: usize,
 }
 
-/// POST /cameras/discover - Descobrir câmeras ONVIF na rede
+/// POST /api/v1/cameras/discover - Descobrir câmeras ONVIF na rede
 pub async fn discover_cameras(
-    Json(_req): Json<DiscoverRequest>,
+    body: Option<Json<DiscoverRequest>>,
 ) -> Result<Json<DiscoverResponse>, StatusCode> {
-    info!("🔍 Iniciando discovery ONVIF...");
-
-    let mut cameras = Vec::new();
-
-    // Usar onvif-cam-rs v0.2 para discovery
-    match onvif_cam_rs::client::discover().await {
-        Ok(devices) => {
-            for device in devices {
-                cameras.push(DiscoveredCamera {
-                    name: format!("Câmera ONVIF - {}", device.url_onvif),
-                    url: device.url_onvif.to_string(),
-                    device_type: Some("NetworkVideoTransmitter".to_string()),
-                    scopes: device.scopes.iter().map(|s: &onvif_cam_rs::Scope| s.to_string()).collect(),
-                });
-            }
-        }
+    let req = body.map(|Json(req)| req).unwrap_or_default();
+    let timeout = Duration::from_secs(
+        req.timeout_secs.unwrap_or(5).clamp(1, MAX_DISCOVERY_TIMEOUT_SECS),
+    );
+
+    info!("🔍 Iniciando discovery ONVIF ({}s)...", timeout.as_secs());
+
+    let cameras: Vec<DiscoveredCamera> = match OnvifDiscovery::new().with_timeout(timeout).probe().await {
+        Ok(devices) => devices.into_iter().map(DiscoveredCamera::from).collect(),
         Err(e) => {
-            info!("Erro no discovery: {}", e);
-            // Retorna lista vazia, não erro
+            warn!("Erro no discovery: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
         }
-    }
+    };
 
     let count = cameras.len();
     info!("✅ Discovery completo: {} câmeras", count);
@@ -60,55 +90,252 @@ pub async fn discover_cameras(
     Ok(Json(DiscoverResponse { cameras, count }))
 }
 
-/// Request para conectar a uma câmera
+fn default_onvif_port() -> u16 {
+    80
+}
+
 #[derive(Debug, Deserialize)]
-pub struct ConnectRequest {
-    pub url: String,
-    pub username: Option<String>,
-    pub password: Option<String>,
+pub struct OnvifProbeRequest {
+    pub ip_address: String,
+    #[serde(default = "default_onvif_port")]
+    pub onvif_port: u16,
+    pub username: String,
+    pub password: String,
 }
 
-/// Profile de câmera
-#[derive(Debug, Serialize)]
-pub struct CameraProfile {
+/// Profile de mídia selecionável no cadastro
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbedProfile {
+    pub token: String,
     pub name: String,
-    pub rtsp_url: Option<String>,
+    pub codec: String,
+    pub width: u32,
+    pub height: u32,
+    pub framerate: f32,
+    pub bitrate_kbps: Option<u32>,
+    pub rtsp_url: String,
+    /// Profile com configuração PTZ
+    pub ptz: bool,
 }
 
-/// POST /cameras/profiles - Listar profiles de uma câmera
-/// NOTA: API simplificada devido à mudança na biblioteca onvif-cam-rs v0.2
-pub async fn get_camera_profiles(
-    Json(_req): Json<ConnectRequest>,
-) -> Result<Json<Vec<CameraProfile>>, StatusCode> {
-    info!("📹 Funcionalidade de profiles temporariamente desabilitada");
+/// Dados obtidos do dispositivo antes do cadastro
+#[derive(Debug, Serialize)]
+pub struct OnvifProbeResponse {
+    pub onvif_url: String,
+    pub manufacturer: String,
+    pub model: String,
+    pub firmware: String,
+    pub serial_number: String,
+    pub ptz_supported: bool,
+    /// Profiles do maior para o menor
+    pub profiles: Vec<ProbedProfile>,
+    /// Sugestão de profiles para main e sub stream
+    pub main_profile: Option<String>,
+    pub sub_profile: Option<String>,
+}
 
-    // TODO: Reimplementar usando onvif-cam-rs v0.2 Camera builder
-    // A API v0.2 mudou significativamente e requer uma abordagem diferente
-    // usando Camera::new(device) e camera.build_all().await
+/// Cadastro por ONVIF: formulário de câmera + profiles escolhidos
+#[derive(Debug, Deserialize)]
+pub struct AddOnvifCameraRequest {
+    #[serde(flatten)]
+    pub camera: CreateCameraRequest,
+    /// Token do profile do main stream (default: maior resolução)
+    pub main_profile: Option<String>,
+    /// Token do profile do sub stream (default: menor resolução)
+    pub sub_profile: Option<String>,
+}
 
-    Ok(Json(vec![]))
+/// POST /api/v1/cameras/onvif/probe - Ler dispositivo e profiles via ONVIF
+pub async fn probe_camera(Json(req): Json<OnvifProbeRequest>) -> impl IntoResponse {
+    match probe_device(&req.ip_address, req.onvif_port, &req.username, &req.password).await {
+        Ok(probe) => (StatusCode::OK, Json(probe)).into_response(),
+        Err(e) => probe_error(e),
+    }
 }
 
-/// Request para controle PTZ
-#[derive(Debug, Deserialize)]
-pub struct PtzRequest {
-    pub url: String,
-    pub username: Option<String>,
-    pub password: Option<String>,
-    pub pan: f32,
-    pub tilt: f32,
-    pub zoom: f32,
+/// POST /api/v1/cameras/onvif - Cadastrar câmera preenchida via ONVIF
+pub async fn add_onvif_camera(
+    State(state): State<AppState>,
+    Json(req): Json<AddOnvifCameraRequest>,
+) -> impl IntoResponse {
+    let port = req.camera.onvif_port.unwrap_or_else(default_onvif_port);
+    let probe = match probe_device(&req.camera.ip_address, port, &req.camera.username, &req.camera.password).await {
+        Ok(probe) => probe,
+        Err(e) => return probe_error(e),
+    };
+
+    let (main, sub) = match select_profiles(
+        &probe.profiles,
+        req.main_profile.as_deref().or(probe.main_profile.as_deref()),
+        req.sub_profile.as_deref().or(probe.sub_profile.as_deref()),
+    ) {
+        Ok(selected) => selected,
+        Err(error) => {
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": error }))).into_response()
+        }
+    };
+
+    let mut camera = Camera::from_request(req.camera);
+    apply_probe(&mut camera, &probe, main, sub);
+
+    match state.camera_repo.create(&camera).await {
+        Ok(_) => {
+            info!("📹 Camera {} added via ONVIF ({} {})", camera.name, probe.manufacturer, probe.model);
+            (StatusCode::CREATED, Json(camera)).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Consulta Device Service (informações) e Media Service (profiles e URIs)
+async fn probe_device(
+    ip_address: &str,
+    port: u16,
+    username: &str,
+    password: &str,
+) -> anyhow::Result<OnvifProbeResponse> {
+    let onvif_url = format!("http://{}:{}", ip_address, port);
+    let mut device = OnvifDevice::new(&onvif_url, username, password)?;
+    device.connect().await?;
+
+    let info = device.get_device_info().await?;
+    let mut profiles = Vec::new();
+    for profile in device.get_profiles().await? {
+        let rtsp_url = match device.get_stream_uri(&profile.token).await {
+            Ok(uri) => uri,
+            Err(e) => {
+                warn!("Profile {} without stream URI: {}", profile.token, e);
+                continue;
+            }
+        };
+        profiles.push(ProbedProfile {
+            token: profile.token,
+            name: profile.name,
+            codec: profile.video_encoding.to_lowercase(),
+            width: profile.resolution.0,
+            height: profile.resolution.1,
+            framerate: profile.framerate,
+            bitrate_kbps: profile.bitrate_kbps,
+            rtsp_url,
+            ptz: profile.ptz_configuration_token.is_some(),
+        });
+    }
+    profiles.sort_by_key(|p| std::cmp::Reverse(p.width * p.height));
+
+    let (main, sub) = select_profiles(&profiles, None, None).unwrap_or((None, None));
+    Ok(OnvifProbeResponse {
+        onvif_url,
+        manufacturer: info.manufacturer,
+        model: info.model,
+        firmware: info.firmware_version,
+        serial_number: info.serial_number,
+        ptz_supported: device.ptz().is_some(),
+        main_profile: main.map(|p| p.token.clone()),
+        sub_profile: sub.map(|p| p.token.clone()),
+        profiles,
+    })
+}
+
+/// Escolhe main e sub stream pelos tokens informados ou, sem token, pela
+/// maior e menor resolução
+fn select_profiles<'a>(
+    profiles: &'a [ProbedProfile],
+    main: Option<&str>,
+    sub: Option<&str>,
+) -> Result<(Option<&'a ProbedProfile>, Option<&'a ProbedProfile>), String> {
+    let find = |token: &str| {
+        profiles
+            .iter()
+            .find(|p| p.token == token)
+            .ok_or_else(|| format!("Unknown media profile: {}", token))
+    };
+
+    let main = match main {
+        Some(token) => find(token)?,
+        None => match profiles.iter().max_by_key(|p| p.width * p.height) {
+            Some(profile) => profile,
+            None => return Err("Device has no usable media profiles".to_string()),
+        },
+    };
+    let sub = match sub {
+        Some(token) => Some(find(token)?),
+        None => profiles.iter().filter(|p| p.token != main.token).min_by_key(|p| p.width * p.height),
+    };
+
+    Ok((Some(main), sub.filter(|p| p.token != main.token)))
 }
 
-/// POST /cameras/ptz - Controle PTZ
-/// NOTA: API simplificada devido à mudança na biblioteca onvif-cam-rs v0.2
-pub async fn control_ptz(
-    Json(_req): Json<PtzRequest>,
-) -> Result<StatusCode, StatusCode> {
-    info!("📹 Funcionalidade PTZ temporariamente desabilitada");
+/// Preenche a câmera com os dados do dispositivo; campos informados no
+/// formulário (fabricante, modelo, firmware) têm precedência
+fn apply_probe(
+    camera: &mut Camera,
+    probe: &OnvifProbeResponse,
+    main: Option<&ProbedProfile>,
+    sub: Option<&ProbedProfile>,
+) {
+    camera.manufacturer = camera.manufacturer.take().or(Some(probe.manufacturer.clone()));
+    camera.model = camera.model.take().or(Some(probe.model.clone()));
+    camera.firmware = camera.firmware.take().or(Some(probe.firmware.clone()));
+    camera.onvif_url = Some(probe.onvif_url.clone());
+    camera.onvif_port = url::Url::parse(&probe.onvif_url).ok().and_then(|u| u.port_or_known_default());
+    camera.ptz_supported = probe.ptz_supported && main.map_or(true, |p| p.ptz);
 
-    // TODO: Reimplementar usando onvif-cam-rs v0.2
-    // A API v0.2 mudou e pode não ter suporte direto a PTZ
+    if let Some(main) = main {
+        camera.rtsp_url = main.rtsp_url.clone();
+        if let Some(port) = url::Url::parse(&main.rtsp_url).ok().and_then(|u| u.port()) {
+            camera.rtsp_port = port;
+        }
+        camera.resolution_width = main.width;
+        camera.resolution_height = main.height;
+        camera.framerate = main.framerate;
+        camera.codec = main.codec.clone();
+    }
+    camera.sub_rtsp_url = sub.map(|p| p.rtsp_url.clone());
+}
 
-    Ok(StatusCode::NOT_IMPLEMENTED)
+fn probe_error(e: anyhow::Error) -> Response {
+    let status = match e.downcast_ref::<OnvifError>() {
+        Some(OnvifError::NotAuthorized) => StatusCode::UNAUTHORIZED,
+        _ => StatusCode::BAD_GATEWAY,
+    };
+    (status, Json(serde_json::json!({ "error": format!("ONVIF: {}", e) }))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(token: &str, width: u32, height: u32) -> ProbedProfile {
+        ProbedProfile {
+            token: token.to_string(),
+            name: token.to_string(),
+            codec: "h264".to_string(),
+            width,
+            height,
+            framerate: 25.0,
+            bitrate_kbps: None,
+            rtsp_url: format!("rtsp://10.0.0.5:554/{}", token),
+            ptz: false,
+        }
+    }
+
+    #[test]
+    fn test_select_profiles_defaults_and_explicit() {
+        let profiles = vec![profile("sub", 640, 360), profile("main", 1920, 1080), profile("third", 1280, 720)];
+
+        let (main, sub) = select_profiles(&profiles, None, None).unwrap();
+        assert_eq!(main.unwrap().token, "main");
+        assert_eq!(sub.unwrap().token, "sub");
+
+        let (main, sub) = select_profiles(&profiles, Some("third"), Some("third")).unwrap();
+        assert_eq!(main.unwrap().token, "third");
+        assert!(sub.is_none());
+
+        assert!(select_profiles(&profiles, Some("missing"), None).is_err());
+        assert!(select_profiles(&[], None, None).is_err());
+    }
 }
@@ -0,0 +1,149 @@
+//! Recorded playback over WebRTC
+//!
+//! Players post a WHEP-style SDP offer with `?start=` (RFC 3339) and a
+//! `control` data channel; it is forwarded to the camera's vms-stream node,
+//! which reads the recording from vms-storage and streams it like live video.
+//! Seek, pause, speed and frame stepping then go over the data channel, so
+//! the same low-latency player (and TURN setup) serves live and recorded
+//! footage. The session `Location` is used for trickle ICE and teardown.
+//!
+//! Mounted at `/api/v1/webrtc/playback`: the gateway sends `/api/v1/playback`
+//! to vms-storage, which serves the recordings themselves.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{patch, post},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use vms_common::{ApiErrorBody, SDPFRAG_CONTENT_TYPE, SDP_CONTENT_TYPE};
+
+use crate::routes::auth::AuthUser;
+use crate::routes::webrtc::{authorized_camera, stream_error};
+use crate::routes::whep::require_content_type;
+use crate::stream_client::StreamError;
+use crate::AppState;
+
+type ApiError = (StatusCode, Json<ApiErrorBody>);
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/:camera_id", post(handle_offer))
+        .route("/:camera_id/:playback_id", patch(handle_patch).delete(handle_delete))
+}
+
+#[derive(Debug, Deserialize)]
+struct PlaybackQuery {
+    /// First recorded instant to play
+    start: DateTime<Utc>,
+}
+
+/// On an offer, a 404 from the node means nothing was recorded at `start`
+fn playback_error(e: StreamError) -> ApiError {
+    match e {
+        StreamError::PeerNotFound => (
+            StatusCode::NOT_FOUND,
+            Json(ApiErrorBody::new("NO_RECORDING", "No recording of the camera at that time")),
+        ),
+        e => stream_error(e),
+    }
+}
+
+/// Playback offer: answer SDP with `201 Created` and the session resource
+async fn handle_offer(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(camera_id): Path<Uuid>,
+    Query(query): Query<PlaybackQuery>,
+    headers: HeaderMap,
+    offer: String,
+) -> Result<Response, ApiError> {
+    require_content_type(&headers, SDP_CONTENT_TYPE)?;
+    tracing::info!(
+        "📼 Playback offer received for camera {} at {} from {}",
+        camera_id,
+        query.start,
+        user.username
+    );
+
+    let client = &state.stream_client;
+    let camera = authorized_camera(&state, &user, camera_id).await?;
+    let server = client.node_of(&camera).await.map_err(stream_error)?;
+    let answer = client
+        .playback_offer(&server, camera.id, query.start, offer)
+        .await
+        .map_err(playback_error)?;
+
+    tracing::info!(
+        "✅ Playback session {} created for camera {} on {}",
+        answer.peer_id,
+        camera_id,
+        server.name
+    );
+
+    let mut response = (
+        StatusCode::CREATED,
+        [(header::CONTENT_TYPE, SDP_CONTENT_TYPE)],
+        answer.sdp,
+    )
+        .into_response();
+    let response_headers = response.headers_mut();
+    let location = format!("/api/v1/webrtc/playback/{}/{}", camera_id, answer.peer_id);
+    if let Ok(location) = HeaderValue::from_str(&location) {
+        response_headers.insert(header::LOCATION, location);
+    }
+    for link in answer.links {
+        if let Ok(link) = HeaderValue::from_str(&link) {
+            response_headers.append(header::LINK, link);
+        }
+    }
+    Ok(response)
+}
+
+/// Trickle ICE from the player, forwarded to the node
+async fn handle_patch(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path((camera_id, playback_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+    fragment: String,
+) -> Result<StatusCode, ApiError> {
+    require_content_type(&headers, SDPFRAG_CONTENT_TYPE)?;
+
+    let camera = authorized_camera(&state, &user, camera_id).await?;
+    let server = state.stream_client.node_of(&camera).await.map_err(stream_error)?;
+    state
+        .stream_client
+        .playback_patch(&server, camera_id, playback_id, fragment)
+        .await
+        .map_err(stream_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Ends a playback session
+async fn handle_delete(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path((camera_id, playback_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    tracing::info!(
+        "⏹️ Playback session {} closed on camera {} by {}",
+        playback_id,
+        camera_id,
+        user.username
+    );
+
+    let camera = authorized_camera(&state, &user, camera_id).await?;
+    let server = state.stream_client.node_of(&camera).await.map_err(stream_error)?;
+    state
+        .stream_client
+        .playback_delete(&server, camera_id, playback_id)
+        .await
+        .map_err(stream_error)?;
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,184 @@
+//! PTZ routes
+//! Controle Pan-Tilt-Zoom de câmeras ONVIF, arbitrado por prioridade
+//!
+//! O usuário (e a prioridade que vem do seu papel) sai do JWT; comandos,
+//! bloqueios, tours e preset home exigem um usuário que controla PTZ.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+use vms_common::ptz::{PTZCommand, PTZPriority, PTZTour, PTZTourId, TourPoint};
+use vms_common::types::CameraId;
+
+use crate::models::user::User;
+use crate::ptz_service::PtzError;
+use crate::routes::auth::AuthUser;
+use crate::AppState;
+
+/// Comando PTZ de um operador
+#[derive(Debug, Deserialize)]
+pub struct PtzControlRequest {
+    pub command: PTZCommand,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTourRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub points: Vec<TourPoint>,
+    #[serde(default = "default_loop_forever")]
+    pub loop_forever: bool,
+    #[serde(default)]
+    pub repeat_count: u32,
+}
+
+fn default_loop_forever() -> bool {
+    true
+}
+
+fn error_response(e: PtzError) -> Response {
+    let status = match e {
+        PtzError::CameraNotFound | PtzError::TourNotFound | PtzError::PresetNotFound => StatusCode::NOT_FOUND,
+        PtzError::NoOnvif | PtzError::NotSupported => StatusCode::UNPROCESSABLE_ENTITY,
+        PtzError::InvalidTour(_) => StatusCode::BAD_REQUEST,
+        PtzError::Locked { .. } => StatusCode::LOCKED,
+        PtzError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        PtzError::Device(_) => StatusCode::BAD_GATEWAY,
+    };
+    (status, Json(serde_json::json!({ "error": e.to_string() }))).into_response()
+}
+
+/// Prioridade PTZ do usuário, ou a recusa
+fn user_priority(user: &User) -> Result<PTZPriority, Response> {
+    user.ptz_priority().ok_or_else(|| {
+        tracing::warn!("🚫 User {} denied PTZ control", user.username);
+        (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({ "error": "User cannot control PTZ" })),
+        )
+            .into_response()
+    })
+}
+
+/// POST /api/v1/cameras/:id/ptz - Executar comando PTZ
+pub async fn control_ptz(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<PtzControlRequest>,
+) -> impl IntoResponse {
+    let priority = match user_priority(&user) {
+        Ok(priority) => priority,
+        Err(response) => return response,
+    };
+
+    match state.ptz_controller.command(id, &user.username, priority, req.command).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// GET /api/v1/cameras/:id/ptz - Capacidades, posição e presets
+pub async fn get_ptz(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.ptz_service.info(id).await {
+        Ok(info) => (StatusCode::OK, Json(info)).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// GET /api/v1/cameras/:id/ptz/control - Bloqueio, tour ativo, presets e tours
+pub async fn get_control(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.ptz_controller.control_state(id).await {
+        Ok(control) => (StatusCode::OK, Json(control)).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// POST /api/v1/cameras/:id/ptz/release - Liberar o bloqueio do usuário
+pub async fn release_control(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.ptz_controller.release(id, &user.username).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// POST /api/v1/cameras/:id/ptz/presets/:index/home - Definir preset home
+pub async fn set_home_preset(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path((id, index)): Path<(Uuid, u8)>,
+) -> impl IntoResponse {
+    if let Err(response) = user_priority(&user) {
+        return response;
+    }
+
+    match state.ptz_controller.set_home(id, index).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// GET /api/v1/cameras/:id/ptz/tours - Listar tours
+pub async fn list_tours(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.ptz_controller.control_state(id).await {
+        Ok(control) => (StatusCode::OK, Json(control.tours)).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// POST /api/v1/cameras/:id/ptz/tours - Criar tour
+pub async fn create_tour(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<CreateTourRequest>,
+) -> impl IntoResponse {
+    if let Err(response) = user_priority(&user) {
+        return response;
+    }
+
+    let mut tour = PTZTour::new(CameraId(id), &req.name);
+    tour.description = req.description;
+    tour.points = req.points;
+    tour.loop_forever = req.loop_forever;
+    tour.repeat_count = req.repeat_count;
+
+    match state.ptz_controller.save_tour(id, tour).await {
+        Ok(tour) => (StatusCode::CREATED, Json(tour)).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// DELETE /api/v1/cameras/:id/ptz/tours/:tour_id - Remover tour
+pub async fn delete_tour(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path((id, tour_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    if let Err(response) = user_priority(&user) {
+        return response;
+    }
+
+    match state.ptz_controller.delete_tour(id, PTZTourId(tour_id)).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => error_response(e),
+    }
+}
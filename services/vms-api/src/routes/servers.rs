@@ -1,6 +1,7 @@
 //! Server API routes
 //!
-//! CRUD operations for streaming servers (nodes)
+//! CRUD operations for streaming servers (nodes), plus ingest node
+//! registration, heartbeats and camera assignment
 
 use axum::{
     extract::{Path, State},
@@ -9,9 +10,14 @@ use axum::{
     Json,
 };
 use uuid::Uuid;
+use vms_common::telemetry::CameraStreamStats;
 
 use crate::{
-    models::server::{CreateServerRequest, Server, ServerPublic, UpdateServerRequest},
+    camera_assigner::HEARTBEAT_INTERVAL_SECS,
+    models::server::{
+        CreateServerRequest, RegisterNodeRequest, RegisterNodeResponse, Server, ServerPublic,
+        ServerStatus, UpdateServerRequest,
+    },
     AppState,
 };
 
@@ -79,7 +85,16 @@ pub async fn update_server(
 ) -> impl IntoResponse {
     match state
         .server_repo
-        .update(id, req.name, req.ip, req.port, req.username, req.password, req.enabled)
+        .update(
+            id,
+            req.name,
+            req.ip,
+            req.port,
+            req.username,
+            req.password,
+            req.enabled,
+            req.max_cameras,
+        )
         .await
     {
         Ok(_) => StatusCode::NO_CONTENT.into_response(),
@@ -109,6 +124,151 @@ pub async fn delete_server(
     }
 }
 
+/// POST /api/v1/servers/register - Register (or re-register) an ingest node
+pub async fn register_node(
+    State(state): State<AppState>,
+    Json(req): Json<RegisterNodeRequest>,
+) -> impl IntoResponse {
+    let existing = match state.server_repo.get_by_name(&req.name).await {
+        Ok(existing) => existing,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    };
+
+    let result = match existing {
+        Some(server) => state
+            .server_repo
+            .update(
+                server.id,
+                None,
+                Some(req.ip.clone()),
+                Some(req.port),
+                None,
+                None,
+                None,
+                Some(req.max_cameras),
+            )
+            .await
+            .map(|_| server.id),
+        None => {
+            let mut server = Server::new(
+                req.name.clone(),
+                req.ip.clone(),
+                req.port,
+                String::new(),
+                String::new(),
+            );
+            server.max_cameras = req.max_cameras;
+            state.server_repo.create(&server).await.map(|_| server.id)
+        }
+    };
+
+    let server_id = match result {
+        Ok(id) => id,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    };
+
+    if let Err(e) = state.server_repo.update_status(server_id, ServerStatus::Online).await {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response();
+    }
+
+    tracing::info!("🛰️ Ingest node registered: {} ({}:{})", req.name, req.ip, req.port);
+
+    if let Err(e) = state.camera_assigner.rebalance().await {
+        tracing::warn!("⚠️ Camera rebalance failed: {}", e);
+    }
+
+    (
+        StatusCode::OK,
+        Json(RegisterNodeResponse {
+            server_id,
+            heartbeat_interval_secs: HEARTBEAT_INTERVAL_SECS,
+        }),
+    )
+        .into_response()
+}
+
+/// POST /api/v1/servers/:id/heartbeat - Node liveness heartbeat
+pub async fn heartbeat(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.server_repo.get(id).await {
+        Ok(Some(server)) => {
+            let was_online = server.status == ServerStatus::Online;
+            if let Err(e) = state.server_repo.update_status(id, ServerStatus::Online).await {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({ "error": e.to_string() })),
+                )
+                    .into_response();
+            }
+
+            // Nó voltou: pode receber câmeras órfãs imediatamente
+            if !was_online {
+                tracing::info!("💚 Node {} back online", server.name);
+                if let Err(e) = state.camera_assigner.rebalance().await {
+                    tracing::warn!("⚠️ Camera rebalance failed: {}", e);
+                }
+            }
+
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Server not found" })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /api/v1/servers/:id/cameras - Cameras assigned to a node
+pub async fn list_server_cameras(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.camera_repo.list_by_server(id).await {
+        Ok(cameras) => (StatusCode::OK, Json(cameras)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// POST /api/v1/servers/:id/stats - Stream telemetry reported by a node
+pub async fn report_stats(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(stats): Json<Vec<CameraStreamStats>>,
+) -> impl IntoResponse {
+    let cameras = state.stream_stats.report(id, stats).await;
+    tracing::debug!("📊 Node {} reported stats for {} cameras", id, cameras);
+
+    StatusCode::NO_CONTENT
+}
+
 /// Health check response
 #[derive(Debug, serde::Serialize)]
 pub struct HealthCheckResponse {
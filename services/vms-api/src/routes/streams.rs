@@ -1,12 +1,38 @@
 //! Rotas de streams
+//!
+//! Os tokens de acesso SRT e LL-HLS só são emitidos a usuários autenticados
+//! que podem ver a câmera; a rota legada `/streams` não emite tokens.
 
-use axum::{extract::Path, http::StatusCode, Json};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
 use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+use vms_common::srt::{issue_token, push_resource, SrtCallTarget, SrtStreamId, TokenScope};
+use vms_common::ApiErrorBody;
+
+use crate::routes::auth::AuthUser;
+use crate::routes::webrtc::{authorized_camera, stream_error};
+use crate::AppState;
+
+type ApiError = (StatusCode, Json<ApiErrorBody>);
+
+/// Validade do token de acesso SRT
+const SRT_TOKEN_TTL_SECS: i64 = 24 * 3600;
+
+/// Validade do token de push: o nó o confere assim que recebe o pedido
+const SRT_PUSH_TOKEN_TTL_SECS: i64 = 60;
+
+/// Validade do token de acesso HLS (painéis ficam abertos o dia todo)
+const HLS_TOKEN_TTL_SECS: i64 = 24 * 3600;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct StreamRequest {
     pub camera_id: String,
-    pub protocol: String, // "webrtc" ou "srt"
+    pub protocol: String, // "webrtc"
 }
 
 #[derive(Debug, Serialize)]
@@ -24,7 +50,6 @@ pub async fn start_stream(
     let stream_id = uuid::Uuid::new_v4().to_string();
     let url = match req.protocol.as_str() {
         "webrtc" => format!("webrtc://localhost:8443/stream/{}", stream_id),
-        "srt" => format!("srt://localhost:9000/stream/{}", stream_id),
         _ => return Err(StatusCode::BAD_REQUEST),
     };
 
@@ -38,6 +63,146 @@ pub async fn start_stream(
     ))
 }
 
+/// URL de stream com token de acesso à câmera
+#[derive(Debug, Serialize)]
+pub struct StreamUrlResponse {
+    pub camera_id: Uuid,
+    pub protocol: String,
+    pub url: String,
+    /// Validade do token (unix, segundos)
+    pub expires_at: i64,
+}
+
+/// Push da câmera para o listener SRT de uma sala de controle
+#[derive(Debug, Deserialize)]
+pub struct SrtPushRequest {
+    /// `host:porta` do listener remoto
+    pub address: String,
+    pub stream_id: Option<String>,
+    pub latency_ms: Option<u32>,
+    pub passphrase: Option<String>,
+}
+
+/// Segredo dos tokens SRT, compartilhado com os nós vms-stream
+fn srt_secret() -> Result<String, ApiError> {
+    std::env::var("STREAM_SRT_SECRET")
+        .ok()
+        .filter(|secret| !secret.is_empty())
+        .ok_or_else(|| {
+            warn!("⚠️ STREAM_SRT_SECRET not set: cannot issue SRT access tokens");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(ApiErrorBody::new("SRT_DISABLED", "SRT access tokens are not configured")),
+            )
+        })
+}
+
+/// POST /api/v1/cameras/:id/srt - URL de playback SRT com token no `streamid`
+pub async fn camera_srt_url(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(camera_id): Path<Uuid>,
+) -> Result<Json<StreamUrlResponse>, ApiError> {
+    authorized_camera(&state, &user, camera_id).await?;
+    let secret = srt_secret()?;
+    let address = std::env::var("STREAM_SRT_ADDRESS").unwrap_or_else(|_| "localhost:9000".to_string());
+
+    let camera = camera_id.to_string();
+    let expires_at = chrono::Utc::now().timestamp() + SRT_TOKEN_TTL_SECS;
+    let token = issue_token(secret.as_bytes(), TokenScope::Play, &camera, expires_at);
+    info!("🎫 SRT token for camera {} issued to {}", camera_id, user.username);
+
+    Ok(Json(StreamUrlResponse {
+        camera_id,
+        protocol: "srt".to_string(),
+        url: SrtStreamId::new(camera, token).url(&address),
+        expires_at,
+    }))
+}
+
+/// POST /api/v1/cameras/:id/hls - URL da playlist LL-HLS com token da câmera
+pub async fn camera_hls_url(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(camera_id): Path<Uuid>,
+) -> Result<Json<StreamUrlResponse>, ApiError> {
+    authorized_camera(&state, &user, camera_id).await?;
+    let secret = std::env::var("STREAM_HLS_SECRET")
+        .ok()
+        .filter(|secret| !secret.is_empty())
+        .ok_or_else(|| {
+            warn!("⚠️ STREAM_HLS_SECRET not set: cannot issue HLS access tokens");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(ApiErrorBody::new("HLS_DISABLED", "HLS access tokens are not configured")),
+            )
+        })?;
+    let base_url = std::env::var("STREAM_HLS_BASE_URL").unwrap_or_else(|_| "http://localhost:9094".to_string());
+
+    let expires_at = chrono::Utc::now().timestamp() + HLS_TOKEN_TTL_SECS;
+    let token = issue_token(secret.as_bytes(), TokenScope::Hls, &camera_id.to_string(), expires_at);
+    info!("🎫 HLS token for camera {} issued to {}", camera_id, user.username);
+
+    Ok(Json(StreamUrlResponse {
+        camera_id,
+        protocol: "hls".to_string(),
+        url: format!(
+            "{}/api/v1/hls/{}/index.m3u8?token={}",
+            base_url.trim_end_matches('/'),
+            camera_id,
+            token
+        ),
+        expires_at,
+    }))
+}
+
+/// POST /api/v1/cameras/:id/srt/push - Empurra a câmera para um listener SRT
+/// remoto a partir do nó vms-stream dela
+pub async fn camera_srt_push(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(camera_id): Path<Uuid>,
+    Json(req): Json<SrtPushRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    if !user.can_push_streams() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiErrorBody::new("SRT_PUSH_FORBIDDEN", "Not allowed to push camera streams")),
+        ));
+    }
+    let camera = authorized_camera(&state, &user, camera_id).await?;
+    let server = state.stream_client.node_of(&camera).await.map_err(stream_error)?;
+    let secret = srt_secret()?;
+
+    let camera = camera_id.to_string();
+    let expires_at = chrono::Utc::now().timestamp() + SRT_PUSH_TOKEN_TTL_SECS;
+    let token = issue_token(
+        secret.as_bytes(),
+        TokenScope::Push,
+        &push_resource(&camera, &req.address),
+        expires_at,
+    );
+    let target = SrtCallTarget {
+        camera_id: camera,
+        address: req.address,
+        stream_id: req.stream_id,
+        latency_ms: req.latency_ms,
+        passphrase: req.passphrase,
+        token,
+    };
+    let id = state
+        .stream_client
+        .srt_push(&server, &target)
+        .await
+        .map_err(stream_error)?;
+
+    info!(
+        "📡 SRT push {} of camera {} to {} started by {}",
+        id, camera_id, target.address, user.username
+    );
+    Ok((StatusCode::CREATED, Json(serde_json::json!({ "id": id }))))
+}
+
 /// Para um stream
 pub async fn stop_stream(
     Path(_stream_id): Path<String>,
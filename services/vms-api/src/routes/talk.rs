@@ -0,0 +1,205 @@
+//! Talk-down: audio from the operator (or a recorded clip) to a camera speaker
+//!
+//! Operators post a WHIP-style SDP offer with their microphone track; it is
+//! forwarded to the camera's vms-stream node with the camera's RTSP source,
+//! and the node plays the audio through the ONVIF backchannel in G.711 while
+//! the camera microphone comes back on the same connection. Rules post an
+//! audio file instead, which is played once. A camera speaker carries one
+//! talk at a time. Every route needs an operator allowed to talk; clips and
+//! teardown also accept the rule engine's service token.
+
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{patch, post},
+    Json, Router,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use vms_common::{ApiErrorBody, SDPFRAG_CONTENT_TYPE, SDP_CONTENT_TYPE};
+
+use crate::models::user::User;
+use crate::routes::auth::{AuthUser, Principal};
+use crate::routes::webrtc::{authorized_camera, stream_error};
+use crate::routes::whep::require_content_type;
+use crate::stream_client::StreamError;
+use crate::AppState;
+
+type ApiError = (StatusCode, Json<ApiErrorBody>);
+
+/// Largest audio clip accepted (same limit as the stream node)
+const CLIP_MAX_BYTES: usize = 10 * 1024 * 1024;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/:camera_id", post(handle_offer))
+        .route(
+            "/:camera_id/clip",
+            post(handle_clip).layer(DefaultBodyLimit::max(CLIP_MAX_BYTES)),
+        )
+        .route("/:camera_id/:talk_id", patch(handle_patch).delete(handle_delete))
+}
+
+/// Node errors specific to talk sessions, then the common mapping
+fn talk_error(e: StreamError) -> ApiError {
+    match &e {
+        StreamError::Node(status) if status.as_u16() == 409 => (
+            StatusCode::CONFLICT,
+            Json(ApiErrorBody::new("SPEAKER_BUSY", "Camera speaker is already in use")),
+        ),
+        StreamError::Node(status) if status.as_u16() == 502 => (
+            StatusCode::BAD_GATEWAY,
+            Json(ApiErrorBody::new(
+                "NO_BACKCHANNEL",
+                "Camera has no usable ONVIF audio backchannel",
+            )),
+        ),
+        _ => stream_error(e),
+    }
+}
+
+/// Users must be allowed to speak through cameras
+fn require_talk(user: &User, camera_id: Uuid) -> Result<(), ApiError> {
+    if user.can_talk() {
+        return Ok(());
+    }
+    tracing::warn!("🚫 User {} denied talk-down on camera {}", user.username, camera_id);
+    Err((
+        StatusCode::FORBIDDEN,
+        Json(ApiErrorBody::new("TALK_FORBIDDEN", "Not allowed to speak through cameras")),
+    ))
+}
+
+/// Operator talk offer: answer SDP with `201 Created` and the session resource
+async fn handle_offer(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(camera_id): Path<Uuid>,
+    headers: HeaderMap,
+    offer: String,
+) -> Result<Response, ApiError> {
+    require_content_type(&headers, SDP_CONTENT_TYPE)?;
+    require_talk(&user, camera_id)?;
+    tracing::info!("🎙️ Talk offer received for camera {} from {}", camera_id, user.username);
+
+    let client = &state.stream_client;
+    let camera = authorized_camera(&state, &user, camera_id).await?;
+    let server = client.node_of(&camera).await.map_err(stream_error)?;
+    let answer = client
+        .talk_offer(&server, &camera, offer)
+        .await
+        .map_err(talk_error)?;
+
+    tracing::info!(
+        "✅ Talk session {} opened on camera {} via {} (user {})",
+        answer.peer_id,
+        camera_id,
+        server.name,
+        user.username
+    );
+
+    let mut response = (
+        StatusCode::CREATED,
+        [(header::CONTENT_TYPE, SDP_CONTENT_TYPE)],
+        answer.sdp,
+    )
+        .into_response();
+    let response_headers = response.headers_mut();
+    let location = format!("/api/v1/talk/{}/{}", camera_id, answer.peer_id);
+    if let Ok(location) = HeaderValue::from_str(&location) {
+        response_headers.insert(header::LOCATION, location);
+    }
+    for link in answer.links {
+        if let Ok(link) = HeaderValue::from_str(&link) {
+            response_headers.append(header::LINK, link);
+        }
+    }
+    Ok(response)
+}
+
+/// Plays an audio clip on the camera speaker (rule actions, recorded warnings)
+async fn handle_clip(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path(camera_id): Path<Uuid>,
+    headers: HeaderMap,
+    clip: Bytes,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    if clip.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiErrorBody::new("EMPTY_CLIP", "Audio clip body is empty")),
+        ));
+    }
+    tracing::info!(
+        "📼 Audio clip for camera {} from {} ({} bytes)",
+        camera_id,
+        principal.name(),
+        clip.len()
+    );
+
+    let client = &state.stream_client;
+    let camera = match &principal {
+        Principal::User(user) => {
+            require_talk(user, camera_id)?;
+            authorized_camera(&state, user, camera_id).await?
+        }
+        Principal::Service(_) => client.camera(camera_id).await.map_err(stream_error)?,
+    };
+    let server = client.node_of(&camera).await.map_err(stream_error)?;
+    let content_type = headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok());
+    let talk_id = client
+        .talk_clip(&server, &camera, content_type, clip.to_vec())
+        .await
+        .map_err(talk_error)?;
+
+    Ok((StatusCode::ACCEPTED, Json(json!({ "id": talk_id }))))
+}
+
+/// Trickle ICE from the operator, forwarded to the node
+async fn handle_patch(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path((camera_id, talk_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+    fragment: String,
+) -> Result<StatusCode, ApiError> {
+    require_content_type(&headers, SDPFRAG_CONTENT_TYPE)?;
+    require_talk(&user, camera_id)?;
+
+    let server = state.stream_client.node_for(camera_id).await.map_err(stream_error)?;
+    state
+        .stream_client
+        .talk_patch(&server, camera_id, talk_id, fragment)
+        .await
+        .map_err(stream_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Ends a talk session (or stops a clip still playing)
+async fn handle_delete(
+    State(state): State<AppState>,
+    principal: Principal,
+    Path((camera_id, talk_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    if let Principal::User(user) = &principal {
+        require_talk(user, camera_id)?;
+    }
+    tracing::info!(
+        "🔇 Talk session {} closed on camera {} by {}",
+        talk_id,
+        camera_id,
+        principal.name()
+    );
+
+    let server = state.stream_client.node_for(camera_id).await.map_err(stream_error)?;
+    state
+        .stream_client
+        .talk_delete(&server, camera_id, talk_id)
+        .await
+        .map_err(stream_error)?;
+    Ok(StatusCode::OK)
+}
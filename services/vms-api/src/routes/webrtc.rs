@@ -4,17 +4,24 @@
 //! Target latency: 50-150ms
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    routing::post,
+    routing::{get, post},
     Json, Router,
 };
 use uuid::Uuid;
 
-use vms_common::{ApiErrorBody, WebRtcAnswerResponse, IceCandidateRequest};
+use vms_common::{ApiErrorBody, WebRtcAnswerResponse, IceCandidateRequest, IceCandidatesResponse, IceServer};
 
+use crate::models::camera::Camera;
+use crate::models::server::Server;
+use crate::models::user::User;
+use crate::routes::auth::AuthUser;
+use crate::stream_client::StreamError;
 use crate::AppState;
 
+type ApiError = (StatusCode, Json<ApiErrorBody>);
+
 /// Request for SDP offer
 #[derive(Debug, serde::Deserialize)]
 pub struct OfferRequest {
@@ -25,70 +32,153 @@ pub struct OfferRequest {
 
 fn default_offer() -> String { "offer".to_string() }
 
+/// Long-poll cursor for server ICE candidates
+#[derive(Debug, serde::Deserialize)]
+pub struct CandidatesQuery {
+    #[serde(default)]
+    pub since: usize,
+    pub timeout_ms: Option<u64>,
+}
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/offer/:camera_id", post(handle_offer))
         .route("/ice/:camera_id", post(handle_ice))
-        .route("/stop/:peer_id", post(handle_stop))
+        .route("/candidates/:camera_id/:peer_id", get(handle_candidates))
+        .route("/ice-servers/:camera_id", get(handle_ice_servers))
+        .route("/stop/:camera_id/:peer_id", post(handle_stop))
 }
 
-/// Handle SDP offer from browser and return answer
+/// Handle SDP offer from browser: forwarded to the camera's vms-stream node
 async fn handle_offer(
     State(state): State<AppState>,
+    AuthUser(user): AuthUser,
     Path(camera_id): Path<Uuid>,
     Json(offer): Json<OfferRequest>,
-) -> Result<Json<WebRtcAnswerResponse>, (StatusCode, Json<ApiErrorBody>)> {
+) -> Result<Json<WebRtcAnswerResponse>, ApiError> {
     tracing::info!("📡 WebRTC offer received for camera {} ({} bytes)", camera_id, offer.sdp.len());
 
-    // 1) Get camera to verify it exists
-    let _camera = state.camera_repo
-        .get(camera_id)
+    if offer.sdp_type != "offer" {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiErrorBody::new("INVALID_SDP_TYPE", "Expected an SDP offer")),
+        ));
+    }
+
+    let camera = authorized_camera(&state, &user, camera_id).await?;
+    let server = state.stream_client.node_of(&camera).await.map_err(stream_error)?;
+    let answer = state
+        .stream_client
+        .offer(&server, &camera, offer.sdp)
         .await
-        .map_err(|e| (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiErrorBody::new("DB_ERROR", e.to_string()))
-        ))?
-        .ok_or_else(|| (
-            StatusCode::NOT_FOUND,
-            Json(ApiErrorBody::new("CAMERA_NOT_FOUND", "Camera not found"))
-        ))?;
-
-    // 2) Generate peer_id and expiration
-    let peer_id = Uuid::new_v4();
-    let expires_at = chrono::Utc::now().timestamp() + 3600; // 1 hour
-
-    // TODO: In production, integrate with vms-stream WebRTCServer
-    // For now, return a placeholder SDP answer
-    tracing::info!("✅ WebRTC session {} created for camera {}", peer_id, camera_id);
-
-    let sdp_answer = format!(
-        "v=0\r\no=- {} 2 IN IP4 127.0.0.1\r\ns=-\r\nt=0 0\r\na=group:BUNDLE 0\r\nm=video 9 UDP/TLS/RTP/SAVPF 96\r\nc=IN IP4 0.0.0.0\r\na=rtcp:9 IN IP4 0.0.0.0\r\na=ice-ufrag:vms\r\na=ice-pwd:vmssecret\r\na=setup:active\r\na=mid:0\r\na=sendonly\r\na=rtpmap:96 H264/90000\r\na=fmtp:96 level-asymmetry-allowed=1;packetization-mode=1;profile-level-id=42e01f\r\n",
-        peer_id.as_u128()
+        .map_err(stream_error)?;
+
+    tracing::info!(
+        "✅ WebRTC viewer {} created for camera {} on {} (user {})",
+        answer.peer_id,
+        camera_id,
+        server.name,
+        user.username
     );
+    Ok(Json(answer))
+}
 
-    Ok(Json(WebRtcAnswerResponse {
-        sdp: sdp_answer,
-        sdp_type: "answer".to_string(),
-        peer_id,
-        expires_at,
-        rtp_port: 0,
-    }))
+/// Camera the user is allowed to watch
+pub(crate) async fn authorized_camera(state: &AppState, user: &User, camera_id: Uuid) -> Result<Camera, ApiError> {
+    let camera = state.stream_client.camera(camera_id).await.map_err(stream_error)?;
+    if !user.can_view_camera(&camera) {
+        tracing::warn!("🚫 User {} denied live view of camera {}", user.username, camera_id);
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiErrorBody::new("CAMERA_FORBIDDEN", "Not allowed to view this camera")),
+        ));
+    }
+    Ok(camera)
 }
 
-/// Handle ICE candidate from browser
+pub(crate) fn stream_error(e: StreamError) -> ApiError {
+    let (status, code) = match &e {
+        StreamError::CameraNotFound => (StatusCode::NOT_FOUND, "CAMERA_NOT_FOUND"),
+        StreamError::PeerNotFound => (StatusCode::NOT_FOUND, "PEER_NOT_FOUND"),
+        StreamError::Unassigned | StreamError::ServerNotFound(_) => {
+            (StatusCode::SERVICE_UNAVAILABLE, "NO_STREAM_NODE")
+        }
+        // Nó recusou o viewer: limite de viewers da câmera ou câmera sem vídeo
+        StreamError::Node(status) if status.as_u16() == 503 => (StatusCode::SERVICE_UNAVAILABLE, "VIEWER_LIMIT"),
+        StreamError::Node(status) if status.as_u16() == 504 => (StatusCode::GATEWAY_TIMEOUT, "CAMERA_TIMEOUT"),
+        StreamError::Unreachable(_) | StreamError::Node(_) | StreamError::InvalidResponse(_) => {
+            (StatusCode::BAD_GATEWAY, "STREAM_NODE_ERROR")
+        }
+        StreamError::Storage(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DB_ERROR"),
+    };
+    (status, Json(ApiErrorBody::new(code, e.to_string())))
+}
+
+/// Node of a camera the user is allowed to watch
+async fn authorized_node(state: &AppState, user: &User, camera_id: Uuid) -> Result<Server, ApiError> {
+    let camera = authorized_camera(state, user, camera_id).await?;
+    state.stream_client.node_of(&camera).await.map_err(stream_error)
+}
+
+/// Handle ICE candidate from browser: forwarded to the camera's vms-stream node
 async fn handle_ice(
-    State(_state): State<AppState>,
-    Path(_camera_id): Path<Uuid>,
-    Json(_candidate): Json<IceCandidateRequest>,
-) -> StatusCode {
-    StatusCode::OK
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(camera_id): Path<Uuid>,
+    Json(candidate): Json<IceCandidateRequest>,
+) -> Result<StatusCode, ApiError> {
+    tracing::debug!("🧊 ICE candidate for camera {} viewer {}", camera_id, candidate.peer_id);
+
+    let server = authorized_node(&state, &user, camera_id).await?;
+    state
+        .stream_client
+        .add_ice_candidate(&server, &candidate)
+        .await
+        .map_err(stream_error)?;
+    Ok(StatusCode::NO_CONTENT)
 }
 
-/// Stop a WebRTC session
+/// Server ICE candidates for a viewer (long-poll, trickle ICE)
+async fn handle_candidates(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path((camera_id, peer_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<CandidatesQuery>,
+) -> Result<Json<IceCandidatesResponse>, ApiError> {
+    let server = authorized_node(&state, &user, camera_id).await?;
+    let candidates = state
+        .stream_client
+        .candidates(&server, peer_id, query.since, query.timeout_ms)
+        .await
+        .map_err(stream_error)?;
+    Ok(Json(candidates))
+}
+
+/// STUN/TURN servers to configure the browser's RTCPeerConnection with
+/// (TURN credentials only go to users who may watch the camera)
+async fn handle_ice_servers(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(camera_id): Path<Uuid>,
+) -> Result<Json<Vec<IceServer>>, ApiError> {
+    let server = authorized_node(&state, &user, camera_id).await?;
+    let servers = state.stream_client.ice_servers(&server).await.map_err(stream_error)?;
+    Ok(Json(servers))
+}
+
+/// Stop a WebRTC viewer: the node removes it from the camera pipeline
 async fn handle_stop(
-    State(_state): State<AppState>,
-    Path(peer_id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, Json<ApiErrorBody>)> {
-    tracing::info!("🛑 WebRTC session {} stop requested", peer_id);
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path((camera_id, peer_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    tracing::info!("🛑 WebRTC viewer {} of camera {} stopped by {}", peer_id, camera_id, user.username);
+
+    let server = authorized_node(&state, &user, camera_id).await?;
+    state
+        .stream_client
+        .close_peer(&server, peer_id)
+        .await
+        .map_err(stream_error)?;
     Ok(StatusCode::OK)
 }
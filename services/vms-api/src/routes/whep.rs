@@ -0,0 +1,129 @@
+//! WHEP (WebRTC-HTTP Egress Protocol) playback
+//!
+//! Standard players post a plain SDP offer for a camera (authenticated with
+//! the user's bearer token, as WHEP allows); it is forwarded to
+//! the camera's vms-stream node together with the camera's RTSP source, and
+//! the answer (with the server's ICE candidates) comes back with a session
+//! `Location` used for trickle ICE (PATCH) and teardown (DELETE).
+
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{patch, post},
+    Json, Router,
+};
+use uuid::Uuid;
+
+use vms_common::{ApiErrorBody, SDPFRAG_CONTENT_TYPE, SDP_CONTENT_TYPE};
+
+use crate::routes::auth::AuthUser;
+use crate::routes::webrtc::{authorized_camera, stream_error};
+use crate::AppState;
+
+type ApiError = (StatusCode, Json<ApiErrorBody>);
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/:camera_id", post(handle_offer))
+        .route("/:camera_id/:peer_id", patch(handle_patch).delete(handle_delete))
+}
+
+/// Rejects bodies that are not of the expected media type
+pub(crate) fn require_content_type(headers: &HeaderMap, expected: &str) -> Result<(), ApiError> {
+    let matches = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().eq_ignore_ascii_case(expected))
+        .unwrap_or(false);
+    if matches {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Json(ApiErrorBody::new(
+                "UNSUPPORTED_MEDIA_TYPE",
+                format!("Expected Content-Type: {}", expected),
+            )),
+        ))
+    }
+}
+
+/// WHEP offer: answer SDP with `201 Created` and the session resource
+async fn handle_offer(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(camera_id): Path<Uuid>,
+    headers: HeaderMap,
+    offer: String,
+) -> Result<Response, ApiError> {
+    require_content_type(&headers, SDP_CONTENT_TYPE)?;
+    tracing::info!("📡 WHEP offer received for camera {} ({} bytes)", camera_id, offer.len());
+
+    let client = &state.stream_client;
+    let camera = authorized_camera(&state, &user, camera_id).await?;
+    let server = client.node_of(&camera).await.map_err(stream_error)?;
+    let answer = client
+        .whep_offer(&server, &camera, offer)
+        .await
+        .map_err(stream_error)?;
+
+    tracing::info!("✅ WHEP session {} created for camera {} on {}", answer.peer_id, camera_id, server.name);
+
+    let mut response = (
+        StatusCode::CREATED,
+        [(header::CONTENT_TYPE, SDP_CONTENT_TYPE)],
+        answer.sdp,
+    )
+        .into_response();
+    let response_headers = response.headers_mut();
+    let location = format!("/api/v1/whep/{}/{}", camera_id, answer.peer_id);
+    if let Ok(location) = HeaderValue::from_str(&location) {
+        response_headers.insert(header::LOCATION, location);
+    }
+    for link in answer.links {
+        if let Ok(link) = HeaderValue::from_str(&link) {
+            response_headers.append(header::LINK, link);
+        }
+    }
+    Ok(response)
+}
+
+/// Trickle ICE from the player, forwarded to the node
+async fn handle_patch(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path((camera_id, peer_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+    fragment: String,
+) -> Result<StatusCode, ApiError> {
+    require_content_type(&headers, SDPFRAG_CONTENT_TYPE)?;
+
+    let camera = authorized_camera(&state, &user, camera_id).await?;
+    let server = state.stream_client.node_of(&camera).await.map_err(stream_error)?;
+    state
+        .stream_client
+        .whep_patch(&server, camera_id, peer_id, fragment)
+        .await
+        .map_err(stream_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// WHEP session teardown
+async fn handle_delete(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path((camera_id, peer_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    let camera = authorized_camera(&state, &user, camera_id).await?;
+    tracing::info!("🛑 WHEP session {} closed for camera {} by {}", peer_id, camera_id, user.username);
+
+    let server = state.stream_client.node_of(&camera).await.map_err(stream_error)?;
+    state
+        .stream_client
+        .whep_delete(&server, camera_id, peer_id)
+        .await
+        .map_err(stream_error)?;
+    Ok(StatusCode::OK)
+}
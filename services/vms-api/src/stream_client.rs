@@ -0,0 +1,613 @@
+//! Stream Client - sinalização WebRTC com o nó vms-stream da câmera
+//!
+//! A câmera é resolvida para o `Server` ao qual está atribuída e a
+//! sinalização (ofertas SDP, candidatos ICE, servidores STUN/TURN, WHEP) é
+//! repassada à API HTTP desse nó, assim como o áudio enviado ao alto-falante
+//! da câmera (fala do operador e clipes), o playback de gravações e os
+//! pushes SRT para salas de controle. A origem RTSP e as credenciais da
+//! câmera saem do banco; o browser nunca as envia.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::header::{CONTENT_TYPE, LINK, LOCATION};
+use serde::Deserialize;
+use thiserror::Error;
+use uuid::Uuid;
+use vms_common::srt::SrtCallTarget;
+use vms_common::{
+    IceCandidateRequest, IceCandidatesResponse, IceServer, NodeOfferRequest, WebRtcAnswerResponse,
+    RTSP_PASSWORD_HEADER, RTSP_URL_HEADER, RTSP_USERNAME_HEADER, SDPFRAG_CONTENT_TYPE, SDP_CONTENT_TYPE,
+};
+
+use crate::db::camera_repository::CameraRepository;
+use crate::db::server_repository::ServerRepository;
+use crate::models::camera::Camera;
+use crate::models::server::Server;
+
+/// Folga sobre o long-poll do nó antes de desistir da requisição
+const POLL_GRACE: Duration = Duration::from_secs(5);
+
+/// Ofertas: o nó espera o primeiro keyframe da câmera (e, no WHEP, a
+/// coleta de candidatos) antes de responder
+const OFFER_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Error)]
+pub enum StreamError {
+    #[error("Camera not found")]
+    CameraNotFound,
+
+    #[error("Camera is not assigned to a streaming node")]
+    Unassigned,
+
+    #[error("Streaming node {0} not found")]
+    ServerNotFound(Uuid),
+
+    #[error("Viewer not found on streaming node")]
+    PeerNotFound,
+
+    #[error("Streaming node unreachable: {0}")]
+    Unreachable(#[from] reqwest::Error),
+
+    #[error("Streaming node returned {0}")]
+    Node(reqwest::StatusCode),
+
+    #[error("Invalid response from streaming node: {0}")]
+    InvalidResponse(&'static str),
+
+    #[error("Storage error: {0}")]
+    Storage(#[from] anyhow::Error),
+}
+
+/// Resposta WHEP do nó (também a das sessões de fala)
+#[derive(Debug)]
+pub struct WhepAnswer {
+    /// SDP com os candidatos do servidor
+    pub sdp: String,
+    pub peer_id: Uuid,
+    /// Headers `Link` dos servidores STUN/TURN, repassados ao player
+    pub links: Vec<String>,
+}
+
+pub struct StreamClient {
+    camera_repo: Arc<CameraRepository>,
+    server_repo: Arc<ServerRepository>,
+    http: reqwest::Client,
+}
+
+impl StreamClient {
+    pub fn new(camera_repo: Arc<CameraRepository>, server_repo: Arc<ServerRepository>) -> Self {
+        Self {
+            camera_repo,
+            server_repo,
+            http: reqwest::Client::builder()
+                .connect_timeout(Duration::from_secs(3))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    pub async fn camera(&self, camera_id: Uuid) -> Result<Camera, StreamError> {
+        self.camera_repo
+            .get(camera_id)
+            .await?
+            .ok_or(StreamError::CameraNotFound)
+    }
+
+    /// Nó vms-stream que atende a câmera
+    pub async fn node_for(&self, camera_id: Uuid) -> Result<Server, StreamError> {
+        let camera = self.camera(camera_id).await?;
+        self.node_of(&camera).await
+    }
+
+    pub async fn node_of(&self, camera: &Camera) -> Result<Server, StreamError> {
+        let server_id = camera.server_id.ok_or(StreamError::Unassigned)?;
+        self.server_repo
+            .get(server_id)
+            .await?
+            .ok_or(StreamError::ServerNotFound(server_id))
+    }
+
+    /// Oferta SDP do browser; o nó cria o viewer (e o pipeline da câmera, se
+    /// preciso) e devolve a resposta com `peer_id` e validade
+    pub async fn offer(
+        &self,
+        server: &Server,
+        camera: &Camera,
+        sdp: String,
+    ) -> Result<WebRtcAnswerResponse, StreamError> {
+        let request = NodeOfferRequest {
+            camera_id: camera.id.to_string(),
+            rtsp_url: camera.rtsp_url.clone(),
+            username: camera.username.clone(),
+            password: camera.password.clone(),
+            sdp,
+            sdp_type: "offer".to_string(),
+        };
+        let response = self
+            .http
+            .post(server.webrtc_offer_url())
+            .timeout(OFFER_TIMEOUT)
+            .json(&request)
+            .send()
+            .await?;
+        Ok(check(response).await?.json().await?)
+    }
+
+    /// Repassa um candidato do browser ao webrtcbin do viewer
+    pub async fn add_ice_candidate(&self, server: &Server, candidate: &IceCandidateRequest) -> Result<(), StreamError> {
+        let response = self
+            .http
+            .post(server.webrtc_ice_url())
+            .timeout(POLL_GRACE)
+            .json(candidate)
+            .send()
+            .await?;
+        check(response).await.map(|_| ())
+    }
+
+    /// Candidatos do servidor a partir de `since` (long-poll no nó)
+    pub async fn candidates(
+        &self,
+        server: &Server,
+        peer_id: Uuid,
+        since: usize,
+        timeout_ms: Option<u64>,
+    ) -> Result<IceCandidatesResponse, StreamError> {
+        let mut request = self
+            .http
+            .get(server.webrtc_candidates_url(peer_id))
+            .query(&[("since", since as u64)]);
+        if let Some(timeout_ms) = timeout_ms {
+            request = request.query(&[("timeout_ms", timeout_ms)]);
+        }
+        // Sem timeout explícito o nó usa o próprio limite (30s)
+        let wait = Duration::from_millis(timeout_ms.unwrap_or(30_000)) + POLL_GRACE;
+        let response = check(request.timeout(wait).send().await?).await?;
+        Ok(response.json().await?)
+    }
+
+    /// Servidores STUN/TURN configurados no nó
+    pub async fn ice_servers(&self, server: &Server) -> Result<Vec<IceServer>, StreamError> {
+        let response = self
+            .http
+            .get(server.webrtc_ice_servers_url())
+            .timeout(POLL_GRACE)
+            .send()
+            .await?;
+        Ok(check(response).await?.json().await?)
+    }
+
+    /// Remove o viewer do pipeline da câmera no nó
+    pub async fn close_peer(&self, server: &Server, peer_id: Uuid) -> Result<(), StreamError> {
+        self.delete_resource(server.webrtc_peer_url(peer_id)).await
+    }
+
+    /// Oferta WHEP, com a origem RTSP da câmera para o nó montar o pipeline
+    pub async fn whep_offer(
+        &self,
+        server: &Server,
+        camera: &Camera,
+        offer: String,
+    ) -> Result<WhepAnswer, StreamError> {
+        self.sdp_offer(server.whep_url(camera.id), Some(camera), offer).await
+    }
+
+    /// Oferta de fala do operador: o nó abre o backchannel da câmera
+    pub async fn talk_offer(
+        &self,
+        server: &Server,
+        camera: &Camera,
+        offer: String,
+    ) -> Result<WhepAnswer, StreamError> {
+        self.sdp_offer(server.talk_url(camera.id), Some(camera), offer).await
+    }
+
+    /// Oferta de playback: o nó lê a gravação do vms-storage a partir de `start`
+    pub async fn playback_offer(
+        &self,
+        server: &Server,
+        camera_id: Uuid,
+        start: DateTime<Utc>,
+        offer: String,
+    ) -> Result<WhepAnswer, StreamError> {
+        self.sdp_offer(server.playback_url(camera_id, start), None, offer).await
+    }
+
+    /// Toca um clipe de áudio no alto-falante da câmera; devolve a sessão
+    pub async fn talk_clip(
+        &self,
+        server: &Server,
+        camera: &Camera,
+        content_type: Option<&str>,
+        clip: Vec<u8>,
+    ) -> Result<Uuid, StreamError> {
+        #[derive(Deserialize)]
+        struct Started {
+            id: Uuid,
+        }
+
+        let request = self
+            .http
+            .post(server.talk_clip_url(camera.id))
+            .timeout(OFFER_TIMEOUT)
+            .header(CONTENT_TYPE, content_type.unwrap_or("application/octet-stream"));
+        let response = with_rtsp_source(request, camera).body(clip).send().await?;
+        let started: Started = check(response).await?.json().await?;
+        Ok(started.id)
+    }
+
+    /// Push SRT da câmera para o listener de uma sala de controle; devolve o
+    /// id da conexão no nó
+    pub async fn srt_push(&self, server: &Server, target: &SrtCallTarget) -> Result<Uuid, StreamError> {
+        #[derive(Deserialize)]
+        struct Started {
+            id: Uuid,
+        }
+
+        let response = self
+            .http
+            .post(server.srt_callers_url())
+            .timeout(OFFER_TIMEOUT)
+            .json(target)
+            .send()
+            .await?;
+        let started: Started = check(response).await?.json().await?;
+        Ok(started.id)
+    }
+
+    /// POST de SDP (com a origem RTSP, se a sessão abre a câmera); `Location`
+    /// do nó termina no id da sessão
+    async fn sdp_offer(&self, url: String, camera: Option<&Camera>, offer: String) -> Result<WhepAnswer, StreamError> {
+        let mut request = self
+            .http
+            .post(url)
+            .timeout(OFFER_TIMEOUT)
+            .header(CONTENT_TYPE, SDP_CONTENT_TYPE);
+        if let Some(camera) = camera {
+            request = with_rtsp_source(request, camera);
+        }
+        let response = request.body(offer).send().await?;
+        let response = check(response).await?;
+
+        // Location do nó: /api/v1/{whep,talk,playback}/:camera_id/:session_id
+        let peer_id = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|location| location.rsplit('/').next())
+            .and_then(|id| id.parse().ok())
+            .ok_or(StreamError::InvalidResponse("missing session Location"))?;
+        let links = response
+            .headers()
+            .get_all(LINK)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .map(String::from)
+            .collect();
+
+        Ok(WhepAnswer {
+            sdp: response.text().await?,
+            peer_id,
+            links,
+        })
+    }
+
+    /// Candidatos do player (`trickle-ice-sdpfrag`)
+    pub async fn whep_patch(
+        &self,
+        server: &Server,
+        camera_id: Uuid,
+        peer_id: Uuid,
+        fragment: String,
+    ) -> Result<(), StreamError> {
+        self.patch_resource(server.whep_resource_url(camera_id, peer_id), fragment).await
+    }
+
+    /// Encerra a sessão WHEP do viewer
+    pub async fn whep_delete(&self, server: &Server, camera_id: Uuid, peer_id: Uuid) -> Result<(), StreamError> {
+        self.delete_resource(server.whep_resource_url(camera_id, peer_id)).await
+    }
+
+    /// Candidatos do operador numa sessão de fala
+    pub async fn talk_patch(
+        &self,
+        server: &Server,
+        camera_id: Uuid,
+        talk_id: Uuid,
+        fragment: String,
+    ) -> Result<(), StreamError> {
+        self.patch_resource(server.talk_resource_url(camera_id, talk_id), fragment).await
+    }
+
+    /// Encerra a fala (ou interrompe o clipe) na câmera
+    pub async fn talk_delete(&self, server: &Server, camera_id: Uuid, talk_id: Uuid) -> Result<(), StreamError> {
+        self.delete_resource(server.talk_resource_url(camera_id, talk_id)).await
+    }
+
+    /// Candidatos do player numa sessão de playback
+    pub async fn playback_patch(
+        &self,
+        server: &Server,
+        camera_id: Uuid,
+        playback_id: Uuid,
+        fragment: String,
+    ) -> Result<(), StreamError> {
+        self.patch_resource(server.playback_resource_url(camera_id, playback_id), fragment).await
+    }
+
+    /// Encerra o playback da gravação
+    pub async fn playback_delete(&self, server: &Server, camera_id: Uuid, playback_id: Uuid) -> Result<(), StreamError> {
+        self.delete_resource(server.playback_resource_url(camera_id, playback_id)).await
+    }
+
+    async fn patch_resource(&self, url: String, fragment: String) -> Result<(), StreamError> {
+        let response = self
+            .http
+            .patch(url)
+            .timeout(POLL_GRACE)
+            .header(CONTENT_TYPE, SDPFRAG_CONTENT_TYPE)
+            .body(fragment)
+            .send()
+            .await?;
+        check(response).await.map(|_| ())
+    }
+
+    async fn delete_resource(&self, url: String) -> Result<(), StreamError> {
+        let response = self.http.delete(url).timeout(POLL_GRACE).send().await?;
+        check(response).await.map(|_| ())
+    }
+}
+
+/// Origem RTSP e credenciais da câmera, para o nó abrir a conexão
+fn with_rtsp_source(request: reqwest::RequestBuilder, camera: &Camera) -> reqwest::RequestBuilder {
+    request
+        .header(RTSP_URL_HEADER, &camera.rtsp_url)
+        .header(RTSP_USERNAME_HEADER, &camera.username)
+        .header(RTSP_PASSWORD_HEADER, &camera.password)
+}
+
+async fn check(response: reqwest::Response) -> Result<reqwest::Response, StreamError> {
+    match response.status() {
+        status if status.is_success() => Ok(response),
+        reqwest::StatusCode::NOT_FOUND => Err(StreamError::PeerNotFound),
+        status => Err(StreamError::Node(status)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::{Path, Query},
+        http::{HeaderMap, StatusCode},
+        routing::{get, post},
+        Json, Router,
+    };
+    use std::collections::HashMap;
+    use vms_common::IceCandidate;
+
+    fn camera() -> Camera {
+        let request = serde_json::from_value(serde_json::json!({
+            "name": "Doca 1",
+            "ip_address": "10.0.0.20",
+            "username": "admin",
+            "password": "secret",
+        }))
+        .unwrap();
+        Camera::from_request(request)
+    }
+
+    /// Stand-in do vms-stream com um único viewer conhecido
+    async fn spawn_node(peer_id: Uuid) -> Server {
+        let app = Router::new()
+            .route(
+                "/api/v1/webrtc/offer",
+                post(move |Json(req): Json<NodeOfferRequest>| async move {
+                    // Credenciais vêm do banco, não do browser
+                    assert_eq!(req.rtsp_url, "rtsp://10.0.0.20:554/stream1");
+                    assert_eq!((req.username.as_str(), req.password.as_str()), ("admin", "secret"));
+                    Json(WebRtcAnswerResponse {
+                        sdp: format!("answer to {}", req.sdp),
+                        sdp_type: "answer".to_string(),
+                        peer_id,
+                        expires_at: 1_700_003_600,
+                        rtp_port: 0,
+                    })
+                }),
+            )
+            .route(
+                "/api/v1/webrtc/ice",
+                post(move |Json(req): Json<IceCandidateRequest>| async move {
+                    if req.peer_id == peer_id {
+                        StatusCode::NO_CONTENT
+                    } else {
+                        StatusCode::NOT_FOUND
+                    }
+                }),
+            )
+            .route(
+                "/api/v1/webrtc/peers/:peer_id",
+                axum::routing::delete(move |Path(id): Path<Uuid>| async move {
+                    if id == peer_id {
+                        StatusCode::NO_CONTENT
+                    } else {
+                        StatusCode::NOT_FOUND
+                    }
+                }),
+            )
+            .route(
+                "/api/v1/webrtc/peers/:peer_id/candidates",
+                get(|Path(_): Path<Uuid>, Query(query): Query<HashMap<String, String>>| async move {
+                    let since: usize = query["since"].parse().unwrap();
+                    Json(IceCandidatesResponse {
+                        candidates: vec![IceCandidate {
+                            candidate: "candidate:1 1 UDP 2122252543 10.0.0.5 50000 typ host".to_string(),
+                            sdp_mid: None,
+                            sdp_m_line_index: Some(0),
+                        }],
+                        next: since + 1,
+                        complete: true,
+                    })
+                }),
+            )
+            .route(
+                "/api/v1/whep/:camera_id",
+                post(move |Path(camera_id): Path<Uuid>, headers: HeaderMap, offer: String| async move {
+                    let rtsp = headers[RTSP_URL_HEADER].to_str().unwrap().to_string();
+                    (
+                        StatusCode::CREATED,
+                        [
+                            ("location", format!("/api/v1/whep/{}/{}", camera_id, peer_id)),
+                            ("link", "<stun:stun.l.google.com:19302>; rel=\"ice-server\"".to_string()),
+                        ],
+                        format!("answer to {} from {}", offer, rtsp),
+                    )
+                }),
+            )
+            .route(
+                "/api/v1/playback/:camera_id",
+                post(
+                    move |Path(camera_id): Path<Uuid>,
+                          Query(query): Query<HashMap<String, String>>,
+                          headers: HeaderMap,
+                          offer: String| async move {
+                        // Gravação não precisa da câmera ao vivo
+                        assert!(headers.get(RTSP_URL_HEADER).is_none());
+                        (
+                            StatusCode::CREATED,
+                            [("location", format!("/api/v1/playback/{}/{}", camera_id, peer_id))],
+                            format!("answer to {} from {}", offer, query["start"]),
+                        )
+                    },
+                ),
+            )
+            .route(
+                "/api/v1/srt/callers",
+                post(move |Json(target): Json<SrtCallTarget>| async move {
+                    assert_eq!(target.address, "control-room.example.com:9000");
+                    assert_eq!(target.token, "1700000000.abc");
+                    (StatusCode::CREATED, Json(serde_json::json!({ "id": peer_id })))
+                }),
+            )
+            .route(
+                "/api/v1/talk/:camera_id/clip",
+                post(move |headers: HeaderMap, clip: axum::body::Bytes| async move {
+                    assert_eq!(headers[RTSP_PASSWORD_HEADER], "secret");
+                    assert_eq!(headers["content-type"], "audio/wav");
+                    assert_eq!(&clip[..4], b"RIFF");
+                    (StatusCode::ACCEPTED, Json(serde_json::json!({ "id": peer_id })))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        Server::new(
+            "stream-1".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            String::new(),
+            String::new(),
+        )
+    }
+
+    fn client() -> StreamClient {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new().connect_lazy("sqlite::memory:").unwrap();
+        StreamClient::new(
+            Arc::new(CameraRepository::new(pool.clone())),
+            Arc::new(ServerRepository::new(pool)),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_forward_signaling() {
+        let peer_id = Uuid::new_v4();
+        let server = spawn_node(peer_id).await;
+        let client = client();
+
+        let answer = client.offer(&server, &camera(), "v=0".to_string()).await.unwrap();
+        assert_eq!(answer.sdp, "answer to v=0");
+        assert_eq!(answer.peer_id, peer_id);
+        assert_eq!(answer.expires_at, 1_700_003_600);
+
+        let candidate = |peer_id| IceCandidateRequest {
+            peer_id,
+            candidate: "candidate:2 1 UDP 1686052607 203.0.113.9 61000 typ srflx".to_string(),
+            sdp_mid: Some("0".to_string()),
+            sdp_m_line_index: Some(0),
+        };
+        client.add_ice_candidate(&server, &candidate(peer_id)).await.unwrap();
+        assert!(matches!(
+            client.add_ice_candidate(&server, &candidate(Uuid::new_v4())).await,
+            Err(StreamError::PeerNotFound)
+        ));
+
+        let gathered = client.candidates(&server, peer_id, 3, Some(100)).await.unwrap();
+        assert_eq!(gathered.next, 4);
+        assert!(gathered.complete);
+        assert_eq!(gathered.candidates[0].sdp_m_line_index, Some(0));
+
+        client.close_peer(&server, peer_id).await.unwrap();
+        assert!(matches!(
+            client.close_peer(&server, Uuid::new_v4()).await,
+            Err(StreamError::PeerNotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_forward_whep_offer() {
+        let peer_id = Uuid::new_v4();
+        let server = spawn_node(peer_id).await;
+
+        let answer = client().whep_offer(&server, &camera(), "v=0".to_string()).await.unwrap();
+        assert_eq!(answer.peer_id, peer_id);
+        assert_eq!(answer.sdp, "answer to v=0 from rtsp://10.0.0.20:554/stream1");
+        assert_eq!(answer.links, vec!["<stun:stun.l.google.com:19302>; rel=\"ice-server\""]);
+    }
+
+    #[tokio::test]
+    async fn test_forward_talk_clip() {
+        let talk_id = Uuid::new_v4();
+        let server = spawn_node(talk_id).await;
+
+        let clip = b"RIFF\0\0\0\0WAVE".to_vec();
+        let started = client()
+            .talk_clip(&server, &camera(), Some("audio/wav"), clip)
+            .await
+            .unwrap();
+        assert_eq!(started, talk_id);
+    }
+
+    #[tokio::test]
+    async fn test_forward_srt_push() {
+        let stream_id = Uuid::new_v4();
+        let server = spawn_node(stream_id).await;
+
+        let target = SrtCallTarget {
+            camera_id: camera().id.to_string(),
+            address: "control-room.example.com:9000".to_string(),
+            stream_id: None,
+            latency_ms: Some(500),
+            passphrase: None,
+            token: "1700000000.abc".to_string(),
+        };
+        assert_eq!(client().srt_push(&server, &target).await.unwrap(), stream_id);
+    }
+
+    #[tokio::test]
+    async fn test_forward_playback_offer() {
+        let playback_id = Uuid::new_v4();
+        let server = spawn_node(playback_id).await;
+
+        let start = DateTime::parse_from_rfc3339("2026-10-18T10:15:00+00:00").unwrap().with_timezone(&Utc);
+        let answer = client()
+            .playback_offer(&server, Uuid::new_v4(), start, "v=0".to_string())
+            .await
+            .unwrap();
+        assert_eq!(answer.peer_id, playback_id);
+        assert_eq!(answer.sdp, "answer to v=0 from 2026-10-18T10:15:00+00:00");
+        assert!(answer.links.is_empty());
+    }
+}
@@ -0,0 +1,132 @@
+//! Stream Stats - última telemetria reportada pelos nós de ingestão
+//!
+//! Cada relatório é carimbado com o nó e o instante de recebimento. Entradas
+//! mais velhas que o timeout de heartbeat dos nós (câmera removida,
+//! reatribuída ou nó morto) deixam de ser servidas e são descartadas, e um
+//! relatório substitui tudo o que o mesmo nó reportou antes.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+use vms_common::telemetry::CameraStreamStats;
+
+/// Telemetria de uma câmera e sua origem
+#[derive(Debug, Clone)]
+struct Report {
+    server_id: Uuid,
+    received_at: DateTime<Utc>,
+    streams: Vec<CameraStreamStats>,
+}
+
+pub struct StreamStatsStore {
+    max_age: chrono::Duration,
+    reports: RwLock<HashMap<Uuid, Report>>,
+}
+
+impl StreamStatsStore {
+    pub fn new(max_age: chrono::Duration) -> Self {
+        Self {
+            max_age,
+            reports: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Relatório completo de um nó (todas as câmeras que ele está ingerindo)
+    pub async fn report(&self, server_id: Uuid, stats: Vec<CameraStreamStats>) -> usize {
+        self.report_at(server_id, stats, Utc::now()).await
+    }
+
+    async fn report_at(&self, server_id: Uuid, stats: Vec<CameraStreamStats>, now: DateTime<Utc>) -> usize {
+        let mut by_camera: HashMap<Uuid, Vec<CameraStreamStats>> = HashMap::new();
+        for s in stats {
+            by_camera.entry(*s.camera_id.as_uuid()).or_default().push(s);
+        }
+        let cameras = by_camera.len();
+
+        let mut reports = self.reports.write().await;
+        // Câmeras que o nó deixou de reportar foram paradas ou reatribuídas
+        reports.retain(|_, report| report.server_id != server_id && now - report.received_at <= self.max_age);
+        reports.extend(by_camera.into_iter().map(|(camera_id, streams)| {
+            (
+                camera_id,
+                Report {
+                    server_id,
+                    received_at: now,
+                    streams,
+                },
+            )
+        }));
+        cameras
+    }
+
+    /// Telemetria recente da câmera
+    pub async fn get(&self, camera_id: Uuid) -> Option<Vec<CameraStreamStats>> {
+        self.get_at(camera_id, Utc::now()).await
+    }
+
+    async fn get_at(&self, camera_id: Uuid, now: DateTime<Utc>) -> Option<Vec<CameraStreamStats>> {
+        self.reports
+            .read()
+            .await
+            .get(&camera_id)
+            .filter(|report| now - report.received_at <= self.max_age)
+            .map(|report| report.streams.clone())
+    }
+
+    /// Descarta a telemetria de uma câmera removida
+    pub async fn remove(&self, camera_id: Uuid) {
+        self.reports.write().await.remove(&camera_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vms_common::telemetry::StreamTelemetry;
+    use vms_common::types::CameraId;
+
+    fn stats(camera_id: Uuid, stream: &str) -> CameraStreamStats {
+        StreamTelemetry::new().snapshot(CameraId::from_uuid(camera_id), stream, 0)
+    }
+
+    #[tokio::test]
+    async fn test_stale_reports_expire() {
+        let store = StreamStatsStore::new(chrono::Duration::seconds(30));
+        let (node, camera) = (Uuid::new_v4(), Uuid::new_v4());
+        let t0 = Utc::now();
+
+        let cameras = store
+            .report_at(node, vec![stats(camera, "main"), stats(camera, "sub")], t0)
+            .await;
+        assert_eq!(cameras, 1);
+        assert_eq!(store.get_at(camera, t0 + chrono::Duration::seconds(30)).await.unwrap().len(), 2);
+
+        // Nó parou de reportar
+        assert!(store.get_at(camera, t0 + chrono::Duration::seconds(31)).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_node_report_replaces_previous_cameras() {
+        let store = StreamStatsStore::new(chrono::Duration::seconds(30));
+        let (node_a, node_b) = (Uuid::new_v4(), Uuid::new_v4());
+        let (moved, kept, other) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let t0 = Utc::now();
+
+        store
+            .report_at(node_a, vec![stats(moved, "main"), stats(kept, "main")], t0)
+            .await;
+        store.report_at(node_b, vec![stats(other, "main")], t0).await;
+
+        // `moved` foi reatribuída: o nó A não a reporta mais
+        let t1 = t0 + chrono::Duration::seconds(5);
+        store.report_at(node_a, vec![stats(kept, "main")], t1).await;
+        assert!(store.get_at(moved, t1).await.is_none());
+        assert!(store.get_at(kept, t1).await.is_some());
+        assert!(store.get_at(other, t1).await.is_some());
+
+        store.remove(kept).await;
+        assert!(store.get_at(kept, t1).await.is_none());
+    }
+}
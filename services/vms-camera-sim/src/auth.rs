@@ -0,0 +1,205 @@
+//! Autenticação do lado do dispositivo
+//! HTTP Digest (RFC 2617, qop=auth) e WS-Security UsernameToken
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, Utc};
+use rand::{rngs::OsRng, RngCore};
+use roxmltree::Node;
+use vms_onvif::digest_auth::{md5_hex, parse_www_authenticate_digest};
+use vms_onvif::wsse::password_digest;
+use vms_onvif::xml_utils::{self, ns};
+
+use crate::config::AuthPolicy;
+use crate::state::SimState;
+
+/// Janela aceita para o `Created` do UsernameToken
+const WSSE_MAX_SKEW_SECONDS: i64 = 300;
+
+const PASSWORD_TEXT: &str =
+    "http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-username-token-profile-1.0#PasswordText";
+
+/// Resultado da verificação de uma requisição
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthOutcome {
+    /// Autenticado (ou política sem autenticação)
+    Allowed,
+    /// Responder 401 com desafio Digest
+    Challenge,
+    /// Responder SOAP Fault `ter:NotAuthorized`
+    Denied,
+}
+
+/// Desafio Digest do dispositivo
+///
+/// O nonce é fixo durante a vida do simulador: o cliente interpreta o mesmo
+/// nonce rejeitado duas vezes como credenciais erradas.
+pub struct DigestAuthority {
+    pub realm: String,
+    pub nonce: String,
+}
+
+impl DigestAuthority {
+    pub fn new(realm: &str) -> Self {
+        let mut bytes = [0u8; 16];
+        OsRng.fill_bytes(&mut bytes);
+        Self {
+            realm: realm.to_string(),
+            nonce: bytes.iter().map(|b| format!("{:02x}", b)).collect(),
+        }
+    }
+
+    /// Valor do header `WWW-Authenticate`
+    pub fn challenge(&self) -> String {
+        format!(
+            r#"Digest realm="{}", qop="auth", nonce="{}", algorithm=MD5"#,
+            self.realm, self.nonce
+        )
+    }
+
+    /// Verifica o header `Authorization: Digest ...`
+    pub fn verify(&self, header: &str, method: &str, state: &SimState) -> Option<String> {
+        let fields = parse_www_authenticate_digest(header)?;
+        let field = |name: &str| fields.get(name).map(String::as_str);
+
+        let username = field("username")?;
+        if field("nonce")? != self.nonce || field("realm")? != self.realm {
+            return None;
+        }
+        let password = state.password_of(username)?;
+
+        let ha1 = md5_hex(&format!("{}:{}:{}", username, self.realm, password));
+        let ha2 = md5_hex(&format!("{}:{}", method, field("uri")?));
+        let expected = match field("qop") {
+            Some(qop) => md5_hex(&format!(
+                "{}:{}:{}:{}:{}:{}",
+                ha1,
+                self.nonce,
+                field("nc")?,
+                field("cnonce")?,
+                qop,
+                ha2
+            )),
+            None => md5_hex(&format!("{}:{}:{}", ha1, self.nonce, ha2)),
+        };
+
+        (field("response")? == expected).then(|| username.to_string())
+    }
+}
+
+/// Verifica o UsernameToken do header SOAP contra os usuários e o relógio do dispositivo
+pub fn verify_ws_security(header: Node, state: &SimState) -> Option<String> {
+    let token = xml_utils::descendant(header, ns::WSSE, "UsernameToken")?;
+    let username = xml_utils::child_text(token, ns::WSSE, "Username")?;
+    let password_node = xml_utils::child(token, ns::WSSE, "Password")?;
+    let received = xml_utils::text(password_node).unwrap_or_default();
+    let password = state.password_of(&username)?;
+
+    if password_node.attribute("Type") == Some(PASSWORD_TEXT) {
+        return (received == password).then_some(username);
+    }
+
+    let nonce = BASE64
+        .decode(xml_utils::child_text(token, ns::WSSE, "Nonce")?)
+        .ok()?;
+    let created = xml_utils::child_text(token, ns::WSU, "Created")?;
+    let created_at = DateTime::parse_from_rfc3339(&created).ok()?.with_timezone(&Utc);
+    if (created_at - state.clock.now()).num_seconds().abs() > WSSE_MAX_SKEW_SECONDS {
+        return None;
+    }
+
+    (password_digest(&nonce, &created, password) == received).then_some(username)
+}
+
+/// Decide a resposta para uma requisição autenticada
+pub fn authorize(
+    policy: AuthPolicy,
+    digest: &DigestAuthority,
+    authorization: Option<&str>,
+    header: Option<Node>,
+    state: &SimState,
+) -> AuthOutcome {
+    if policy == AuthPolicy::None {
+        return AuthOutcome::Allowed;
+    }
+
+    let is_digest = authorization.is_some_and(|h| h.trim_start().to_ascii_lowercase().starts_with("digest "));
+    if policy.allows_digest() && is_digest {
+        return match authorization.and_then(|h| digest.verify(h, "POST", state)) {
+            Some(_) => AuthOutcome::Allowed,
+            None => AuthOutcome::Challenge,
+        };
+    }
+
+    let token = header.filter(|h| xml_utils::descendant(*h, ns::WSSE, "UsernameToken").is_some());
+    if let (true, Some(header)) = (policy.allows_ws_security(), token) {
+        return match verify_ws_security(header, state) {
+            Some(_) => AuthOutcome::Allowed,
+            None => AuthOutcome::Denied,
+        };
+    }
+
+    if policy.allows_digest() {
+        AuthOutcome::Challenge
+    } else {
+        AuthOutcome::Denied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimConfig;
+    use vms_onvif::digest_auth::{build_digest_authorization, DigestChallenge};
+    use vms_onvif::wsse;
+
+    fn state() -> SimState {
+        SimState::new(&SimConfig {
+            username: "admin".to_string(),
+            password: "secret".to_string(),
+            ..SimConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_digest_accepts_client_authorization() {
+        let state = state();
+        let authority = DigestAuthority::new("sim");
+        let challenge = DigestChallenge::from_header(&authority.challenge()).unwrap();
+
+        let header = build_digest_authorization(&challenge, "POST", "/onvif/device_service", "admin", "secret", 1);
+        assert_eq!(authority.verify(&header, "POST", &state).as_deref(), Some("admin"));
+
+        let wrong = build_digest_authorization(&challenge, "POST", "/onvif/device_service", "admin", "nope", 2);
+        assert!(authority.verify(&wrong, "POST", &state).is_none());
+        assert_eq!(
+            authorize(AuthPolicy::Any, &authority, Some(&wrong), None, &state),
+            AuthOutcome::Challenge
+        );
+    }
+
+    #[test]
+    fn test_ws_security_checks_password_and_clock() {
+        let mut state = state();
+        let authority = DigestAuthority::new("sim");
+        let wrap = |token: String| format!(r#"<s:Header xmlns:s="{}">{}</s:Header>"#, ns::SOAP12, token);
+
+        let valid = wrap(wsse::username_token("admin", "secret", Utc::now()));
+        let doc = xml_utils::parse(&valid).unwrap();
+        assert_eq!(
+            authorize(AuthPolicy::Any, &authority, None, Some(doc.root_element()), &state),
+            AuthOutcome::Allowed
+        );
+        // Só Digest: o UsernameToken é ignorado e o cliente recebe o desafio
+        assert_eq!(
+            authorize(AuthPolicy::Digest, &authority, None, Some(doc.root_element()), &state),
+            AuthOutcome::Challenge
+        );
+
+        // Relógio da câmera 1h adiantado: token fora da janela
+        state.clock.offset = chrono::Duration::hours(1);
+        assert_eq!(
+            authorize(AuthPolicy::WsSecurity, &authority, None, Some(doc.root_element()), &state),
+            AuthOutcome::Denied
+        );
+    }
+}
@@ -0,0 +1,206 @@
+//! Configuração do simulador
+//! Portas, credenciais, identidade do dispositivo e streams anunciados
+
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::PathBuf;
+
+/// Autenticação exigida nas chamadas ONVIF
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthPolicy {
+    /// Aceita qualquer requisição
+    None,
+    /// Só HTTP Digest (WS-Security é ignorado)
+    Digest,
+    /// Só WS-Security UsernameToken
+    WsSecurity,
+    /// Aceita qualquer um dos dois (comportamento da maioria das câmeras)
+    Any,
+}
+
+impl AuthPolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Some(Self::None),
+            "digest" => Some(Self::Digest),
+            "wsse" | "ws-security" => Some(Self::WsSecurity),
+            "any" | "both" => Some(Self::Any),
+            _ => None,
+        }
+    }
+
+    pub fn allows_digest(&self) -> bool {
+        matches!(self, Self::Digest | Self::Any)
+    }
+
+    pub fn allows_ws_security(&self) -> bool {
+        matches!(self, Self::WsSecurity | Self::Any)
+    }
+}
+
+/// Origem do vídeo servido por RTSP
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VideoSource {
+    /// `videotestsrc` com o padrão informado (ex: `smpte`, `ball`)
+    TestPattern(String),
+    /// Arquivo de vídeo decodificado e re-encodado em H.264
+    File(PathBuf),
+}
+
+/// Stream (profile de mídia) anunciado pelo simulador
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamConfig {
+    /// Token do profile ONVIF
+    pub token: String,
+    pub name: String,
+    /// Mount RTSP (ex: `/main`)
+    pub mount: String,
+    pub width: u32,
+    pub height: u32,
+    pub framerate: u32,
+    pub bitrate_kbps: u32,
+    /// GOP em frames
+    pub gop: u32,
+}
+
+impl StreamConfig {
+    /// Token do VideoEncoderConfiguration do profile
+    pub fn encoder_token(&self) -> String {
+        format!("{}_encoder", self.token)
+    }
+}
+
+/// Configuração completa do simulador
+#[derive(Debug, Clone)]
+pub struct SimConfig {
+    /// Endereço de bind dos serviços HTTP/RTSP
+    pub bind: IpAddr,
+    /// Endereço anunciado nos XAddrs e URIs RTSP
+    pub advertise: IpAddr,
+    /// Porta ONVIF (0 = efêmera)
+    pub onvif_port: u16,
+    /// Porta RTSP (0 = efêmera)
+    pub rtsp_port: u16,
+    /// Responder Probes WS-Discovery
+    pub discovery: bool,
+    /// Porta UDP do WS-Discovery (0 = efêmera, para probes unicast em testes)
+    pub discovery_port: u16,
+    /// Entrar no grupo multicast 239.255.255.250
+    pub discovery_multicast: bool,
+    pub username: String,
+    pub password: String,
+    pub auth: AuthPolicy,
+    pub manufacturer: String,
+    pub model: String,
+    pub firmware_version: String,
+    pub serial_number: String,
+    pub hardware_id: String,
+    /// Nome anunciado nos scopes de discovery
+    pub name: String,
+    /// Anunciar PTZ Service
+    pub ptz: bool,
+    pub video_source: VideoSource,
+    pub streams: Vec<StreamConfig>,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            bind: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            advertise: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            onvif_port: 0,
+            rtsp_port: 0,
+            discovery: true,
+            discovery_port: 0,
+            discovery_multicast: false,
+            username: "admin".to_string(),
+            password: "admin".to_string(),
+            auth: AuthPolicy::Any,
+            manufacturer: "VMS".to_string(),
+            model: "SIM-1000".to_string(),
+            firmware_version: env!("CARGO_PKG_VERSION").to_string(),
+            serial_number: "SIM0001".to_string(),
+            hardware_id: "SIM-1000-HW".to_string(),
+            name: "Camera Simulator".to_string(),
+            ptz: true,
+            video_source: VideoSource::TestPattern("smpte".to_string()),
+            streams: vec![
+                StreamConfig {
+                    token: "Profile_1".to_string(),
+                    name: "MainStream".to_string(),
+                    mount: "/main".to_string(),
+                    width: 1920,
+                    height: 1080,
+                    framerate: 25,
+                    bitrate_kbps: 4096,
+                    gop: 50,
+                },
+                StreamConfig {
+                    token: "Profile_2".to_string(),
+                    name: "SubStream".to_string(),
+                    mount: "/sub".to_string(),
+                    width: 640,
+                    height: 360,
+                    framerate: 15,
+                    bitrate_kbps: 512,
+                    gop: 30,
+                },
+            ],
+        }
+    }
+}
+
+impl SimConfig {
+    /// Configuração a partir de variáveis de ambiente (binário `vms-camera-sim`)
+    pub fn from_env() -> Self {
+        let mut config = Self {
+            bind: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            onvif_port: 8000,
+            rtsp_port: 8554,
+            discovery_port: 3702,
+            discovery_multicast: true,
+            ..Self::default()
+        };
+
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+
+        if let Some(ip) = var("SIM_ADVERTISE_IP").and_then(|v| v.parse().ok()) {
+            config.advertise = ip;
+        }
+        if let Some(port) = var("SIM_ONVIF_PORT").and_then(|v| v.parse().ok()) {
+            config.onvif_port = port;
+        }
+        if let Some(port) = var("SIM_RTSP_PORT").and_then(|v| v.parse().ok()) {
+            config.rtsp_port = port;
+        }
+        if let Some(username) = var("SIM_USERNAME") {
+            config.username = username;
+        }
+        if let Some(password) = var("SIM_PASSWORD") {
+            config.password = password;
+        }
+        if let Some(auth) = var("SIM_AUTH").and_then(|v| AuthPolicy::parse(&v)) {
+            config.auth = auth;
+        }
+        if let Some(name) = var("SIM_NAME") {
+            config.name = name;
+        }
+        if let Some(ptz) = var("SIM_PTZ") {
+            config.ptz = ptz != "0" && ptz != "false";
+        }
+        if let Some(discovery) = var("SIM_DISCOVERY") {
+            config.discovery = discovery != "0" && discovery != "false";
+        }
+        if let Some(path) = var("SIM_VIDEO_FILE") {
+            config.video_source = VideoSource::File(PathBuf::from(path));
+        } else if let Some(pattern) = var("SIM_TEST_PATTERN") {
+            config.video_source = VideoSource::TestPattern(pattern);
+        }
+
+        config
+    }
+
+    /// Profile pelo token
+    pub fn stream(&self, token: &str) -> Option<&StreamConfig> {
+        self.streams.iter().find(|s| s.token == token)
+    }
+}
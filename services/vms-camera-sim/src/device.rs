@@ -0,0 +1,210 @@
+//! Device Management Service simulado
+//! Capacidades, informações, data/hora, NTP e usuários
+
+use chrono::{Datelike, NaiveDate, Timelike, Utc};
+use roxmltree::Node;
+use vms_onvif::wsse::escape;
+use vms_onvif::xml_utils::{self, ns};
+use vms_onvif::{OnvifUser, UserLevel};
+
+use crate::server::Context;
+use crate::soap::{arg, Fault};
+
+/// Executa uma operação do Device Service; retorna o elemento de resposta
+pub fn handle(ctx: &mut Context, operation: Node) -> Result<String, Fault> {
+    let name = operation.tag_name().name();
+    let body = match name {
+        "GetSystemDateAndTime" => system_date_and_time(ctx),
+        "SetSystemDateAndTime" => set_system_date_and_time(ctx, operation)?,
+        "SetNTP" => set_ntp(ctx, operation),
+        "GetCapabilities" => capabilities(ctx),
+        "GetServices" => services(ctx),
+        "GetDeviceInformation" => format!(
+            "<tds:Manufacturer>{}</tds:Manufacturer><tds:Model>{}</tds:Model><tds:FirmwareVersion>{}</tds:FirmwareVersion><tds:SerialNumber>{}</tds:SerialNumber><tds:HardwareId>{}</tds:HardwareId>",
+            escape(&ctx.config.manufacturer),
+            escape(&ctx.config.model),
+            escape(&ctx.config.firmware_version),
+            escape(&ctx.config.serial_number),
+            escape(&ctx.config.hardware_id)
+        ),
+        "GetUsers" => ctx
+            .state
+            .users
+            .iter()
+            .map(|u| {
+                format!(
+                    "<tds:User><tt:Username>{}</tt:Username><tt:UserLevel>{}</tt:UserLevel></tds:User>",
+                    escape(&u.username),
+                    u.level.as_str()
+                )
+            })
+            .collect(),
+        "CreateUsers" => create_users(ctx, operation)?,
+        "SetUser" => set_users(ctx, operation)?,
+        "DeleteUsers" => delete_users(ctx, operation)?,
+        other => return Err(Fault::action_not_supported(other)),
+    };
+
+    Ok(format!(
+        r#"<tds:{name}Response xmlns:tds="{}" xmlns:tt="{}">{}</tds:{name}Response>"#,
+        ns::DEVICE,
+        ns::SCHEMA,
+        body,
+        name = name
+    ))
+}
+
+fn system_date_and_time(ctx: &Context) -> String {
+    let now = ctx.state.clock.now();
+    let date_time = format!(
+        "<tt:Time><tt:Hour>{}</tt:Hour><tt:Minute>{}</tt:Minute><tt:Second>{}</tt:Second></tt:Time><tt:Date><tt:Year>{}</tt:Year><tt:Month>{}</tt:Month><tt:Day>{}</tt:Day></tt:Date>",
+        now.hour(),
+        now.minute(),
+        now.second(),
+        now.year(),
+        now.month(),
+        now.day()
+    );
+
+    format!(
+        "<tds:SystemDateAndTime><tt:DateTimeType>{}</tt:DateTimeType><tt:DaylightSavings>false</tt:DaylightSavings><tt:TimeZone><tt:TZ>{}</tt:TZ></tt:TimeZone><tt:UTCDateTime>{}</tt:UTCDateTime><tt:LocalDateTime>{}</tt:LocalDateTime></tds:SystemDateAndTime>",
+        ctx.state.clock.date_time_type.as_deref().unwrap_or("Manual"),
+        escape(ctx.state.clock.timezone.as_deref().unwrap_or("UTC0")),
+        date_time,
+        date_time
+    )
+}
+
+fn set_system_date_and_time(ctx: &mut Context, operation: Node) -> Result<String, Fault> {
+    let date_time_type = arg(operation, "DateTimeType").unwrap_or_else(|| "Manual".to_string());
+
+    if date_time_type == "Manual" {
+        let utc = operation
+            .descendants()
+            .find(|n| n.is_element() && n.tag_name().name() == "UTCDateTime")
+            .ok_or_else(|| Fault::invalid_arg("InvalidDateTime", "UTCDateTime is required"))?;
+        let field = |path: &[&str]| -> Option<u32> {
+            xml_utils::path(utc, ns::SCHEMA, path)
+                .and_then(xml_utils::text)
+                .and_then(|v| v.parse().ok())
+        };
+        let time = NaiveDate::from_ymd_opt(
+            field(&["Date", "Year"]).unwrap_or(0) as i32,
+            field(&["Date", "Month"]).unwrap_or(0),
+            field(&["Date", "Day"]).unwrap_or(0),
+        )
+        .and_then(|d| d.and_hms_opt(field(&["Time", "Hour"])?, field(&["Time", "Minute"])?, field(&["Time", "Second"])?))
+        .ok_or_else(|| Fault::invalid_arg("InvalidDateTime", "Invalid UTCDateTime"))?;
+
+        ctx.state.clock.offset = time.and_utc() - Utc::now();
+    }
+
+    ctx.state.clock.date_time_type = Some(date_time_type);
+    if let Some(tz) = xml_utils::descendant_text(operation, ns::SCHEMA, "TZ") {
+        ctx.state.clock.timezone = Some(tz);
+    }
+    Ok(String::new())
+}
+
+fn set_ntp(ctx: &mut Context, operation: Node) -> String {
+    ctx.state.clock.ntp_servers = operation
+        .children()
+        .filter(|n| n.is_element() && n.tag_name().name() == "NTPManual")
+        .filter_map(|entry| {
+            ["IPv4Address", "IPv6Address", "DNSname"]
+                .iter()
+                .find_map(|local| xml_utils::child_text(entry, ns::SCHEMA, local))
+        })
+        .collect();
+    String::new()
+}
+
+fn capabilities(ctx: &Context) -> String {
+    let ptz = if ctx.config.ptz {
+        format!("<tt:PTZ><tt:XAddr>{}</tt:XAddr></tt:PTZ>", ctx.urls.service("ptz_service"))
+    } else {
+        String::new()
+    };
+
+    format!(
+        "<tds:Capabilities><tt:Device><tt:XAddr>{}</tt:XAddr></tt:Device><tt:Media><tt:XAddr>{}</tt:XAddr><tt:StreamingCapabilities><tt:RTPMulticast>false</tt:RTPMulticast><tt:RTP_TCP>true</tt:RTP_TCP><tt:RTP_RTSP_TCP>true</tt:RTP_RTSP_TCP></tt:StreamingCapabilities></tt:Media>{}</tds:Capabilities>",
+        ctx.urls.service("device_service"),
+        ctx.urls.service("media_service"),
+        ptz
+    )
+}
+
+fn services(ctx: &Context) -> String {
+    let mut services = vec![(ns::DEVICE, "device_service"), (ns::MEDIA, "media_service")];
+    if ctx.config.ptz {
+        services.push((ns::PTZ, "ptz_service"));
+    }
+
+    services
+        .into_iter()
+        .map(|(namespace, path)| {
+            format!(
+                "<tds:Service><tds:Namespace>{}</tds:Namespace><tds:XAddr>{}</tds:XAddr><tds:Version><tt:Major>2</tt:Major><tt:Minor>0</tt:Minor></tds:Version></tds:Service>",
+                namespace,
+                ctx.urls.service(path)
+            )
+        })
+        .collect()
+}
+
+/// Usuários do corpo de CreateUsers/SetUser
+fn request_users(operation: Node) -> Vec<OnvifUser> {
+    operation
+        .children()
+        .filter(|n| n.is_element() && n.tag_name().name() == "User")
+        .filter_map(|user| {
+            Some(OnvifUser {
+                username: xml_utils::child_text(user, ns::SCHEMA, "Username")?,
+                password: xml_utils::child_text(user, ns::SCHEMA, "Password"),
+                level: UserLevel::parse(&xml_utils::child_text(user, ns::SCHEMA, "UserLevel").unwrap_or_default()),
+            })
+        })
+        .collect()
+}
+
+fn create_users(ctx: &mut Context, operation: Node) -> Result<String, Fault> {
+    let users = request_users(operation);
+    if let Some(user) = users.iter().find(|u| ctx.state.has_user(&u.username)) {
+        return Err(Fault::sender(
+            &["OperationProhibited", "UsernameClash"],
+            format!("User {} already exists", user.username),
+        ));
+    }
+    ctx.state.users.extend(users);
+    Ok(String::new())
+}
+
+fn set_users(ctx: &mut Context, operation: Node) -> Result<String, Fault> {
+    for change in request_users(operation) {
+        let user = ctx
+            .state
+            .users
+            .iter_mut()
+            .find(|u| u.username == change.username)
+            .ok_or_else(|| Fault::invalid_arg("UsernameMissing", format!("User {} not found", change.username)))?;
+        user.level = change.level;
+        if change.password.is_some() {
+            user.password = change.password;
+        }
+    }
+    Ok(String::new())
+}
+
+fn delete_users(ctx: &mut Context, operation: Node) -> Result<String, Fault> {
+    let usernames: Vec<String> = operation
+        .children()
+        .filter(|n| n.is_element() && n.tag_name().name() == "Username")
+        .filter_map(xml_utils::text)
+        .collect();
+
+    if let Some(missing) = usernames.iter().find(|u| !ctx.state.has_user(u)) {
+        return Err(Fault::invalid_arg("UsernameMissing", format!("User {} not found", missing)));
+    }
+    ctx.state.users.retain(|u| !usernames.contains(&u.username));
+    Ok(String::new())
+}
@@ -0,0 +1,191 @@
+//! Responder WS-Discovery
+//! Responde Probes (multicast ou unicast) com ProbeMatches apontando para o
+//! Device Service do simulador
+
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use tokio::net::UdpSocket;
+use tracing::{debug, info, warn};
+use vms_onvif::discovery::{NS_DISCOVERY, WS_DISCOVERY_MULTICAST};
+use vms_onvif::wsse::escape;
+
+use crate::config::SimConfig;
+use crate::server::Urls;
+
+const NS_ADDRESSING: &str = "http://schemas.xmlsoap.org/ws/2004/08/addressing";
+
+/// Identidade anunciada nos ProbeMatches
+pub struct Announcement {
+    /// `urn:uuid:...` estável durante a vida do simulador
+    pub endpoint: String,
+    pub scopes: String,
+    pub xaddr: String,
+}
+
+impl Announcement {
+    pub fn new(config: &SimConfig, urls: &Urls, endpoint: String) -> Self {
+        let scopes = [
+            "onvif://www.onvif.org/type/video_encoder".to_string(),
+            "onvif://www.onvif.org/Profile/Streaming".to_string(),
+            format!("onvif://www.onvif.org/name/{}", percent_encode(&config.name)),
+            format!("onvif://www.onvif.org/hardware/{}", percent_encode(&config.model)),
+        ]
+        .join(" ");
+
+        Self {
+            endpoint,
+            scopes,
+            xaddr: urls.service("device_service"),
+        }
+    }
+}
+
+/// Socket do responder: porta unicast e, opcionalmente, o grupo multicast
+pub async fn bind(config: &SimConfig) -> Result<UdpSocket> {
+    if !config.discovery_multicast {
+        return UdpSocket::bind(SocketAddr::new(config.bind, config.discovery_port))
+            .await
+            .context("Failed to bind WS-Discovery socket");
+    }
+
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.bind(&SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, config.discovery_port).into())?;
+    socket
+        .join_multicast_v4(WS_DISCOVERY_MULTICAST.ip(), &Ipv4Addr::UNSPECIFIED)
+        .context("Failed to join WS-Discovery multicast group")?;
+    socket.set_nonblocking(true)?;
+
+    Ok(UdpSocket::from_std(socket.into())?)
+}
+
+/// Loop do responder (até a task ser abortada)
+pub async fn run(socket: UdpSocket, announcement: Arc<Announcement>) {
+    let mut buf = vec![0u8; 65_535];
+    loop {
+        let (size, addr) = match socket.recv_from(&mut buf).await {
+            Ok(received) => received,
+            Err(e) => {
+                warn!("⚠️  WS-Discovery receive error: {}", e);
+                continue;
+            }
+        };
+
+        let xml = String::from_utf8_lossy(&buf[..size]);
+        let Some(message_id) = probe_message_id(&xml) else {
+            continue;
+        };
+
+        debug!("🔍 Probe from {}", addr);
+        let reply = probe_matches(&announcement, &message_id);
+        if let Err(e) = socket.send_to(reply.as_bytes(), addr).await {
+            warn!("⚠️  Failed to answer probe from {}: {}", addr, e);
+        } else {
+            info!("📣 Answered WS-Discovery probe from {}", addr);
+        }
+    }
+}
+
+/// MessageID de um Probe por NetworkVideoTransmitter/Device (ou sem Types)
+fn probe_message_id(xml: &str) -> Option<String> {
+    let doc = roxmltree::Document::parse(xml).ok()?;
+    let probe = doc
+        .descendants()
+        .find(|n| n.is_element() && n.tag_name().name() == "Probe" && n.tag_name().namespace() == Some(NS_DISCOVERY))?;
+
+    let types = probe
+        .children()
+        .find(|n| n.is_element() && n.tag_name().name() == "Types")
+        .and_then(|n| n.text())
+        .unwrap_or_default();
+    let wanted = types.split_whitespace().all(|t| {
+        let local = t.rsplit(':').next().unwrap_or(t);
+        local == "NetworkVideoTransmitter" || local == "Device"
+    });
+    if !wanted {
+        return None;
+    }
+
+    doc.descendants()
+        .find(|n| n.is_element() && n.tag_name().name() == "MessageID")
+        .and_then(|n| n.text())
+        .map(|t| t.trim().to_string())
+}
+
+/// Resposta ProbeMatches
+pub fn probe_matches(announcement: &Announcement, relates_to: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:a="{wsa}" xmlns:d="{ns}" xmlns:dn="http://www.onvif.org/ver10/network/wsdl" xmlns:tds="http://www.onvif.org/ver10/device/wsdl">
+    <s:Header>
+        <a:Action>http://schemas.xmlsoap.org/ws/2005/04/discovery/ProbeMatches</a:Action>
+        <a:MessageID>urn:uuid:{message_id}</a:MessageID>
+        <a:RelatesTo>{relates_to}</a:RelatesTo>
+        <a:To>http://schemas.xmlsoap.org/ws/2004/08/addressing/role/anonymous</a:To>
+    </s:Header>
+    <s:Body>
+        <d:ProbeMatches>
+            <d:ProbeMatch>
+                <a:EndpointReference><a:Address>{endpoint}</a:Address></a:EndpointReference>
+                <d:Types>dn:NetworkVideoTransmitter tds:Device</d:Types>
+                <d:Scopes>{scopes}</d:Scopes>
+                <d:XAddrs>{xaddr}</d:XAddrs>
+                <d:MetadataVersion>1</d:MetadataVersion>
+            </d:ProbeMatch>
+        </d:ProbeMatches>
+    </s:Body>
+</s:Envelope>"#,
+        wsa = NS_ADDRESSING,
+        ns = NS_DISCOVERY,
+        message_id = uuid::Uuid::new_v4(),
+        relates_to = escape(relates_to),
+        endpoint = escape(&announcement.endpoint),
+        scopes = escape(&announcement.scopes),
+        xaddr = escape(&announcement.xaddr),
+    )
+}
+
+/// Codifica um valor de scope (`Front Door` → `Front%20Door`)
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vms_onvif::discovery::{build_probe, parse_probe_matches};
+
+    #[test]
+    fn test_probe_matches_parsed_by_client() {
+        let config = SimConfig {
+            name: "Front Door".to_string(),
+            ..SimConfig::default()
+        };
+        let urls = Urls {
+            onvif: "http://10.0.0.5:8000".to_string(),
+            rtsp: "rtsp://10.0.0.5:8554".to_string(),
+        };
+        let announcement = Announcement::new(&config, &urls, "urn:uuid:sim-1".to_string());
+
+        let probe = build_probe("uuid:probe-1");
+        let message_id = probe_message_id(&probe).unwrap();
+        assert_eq!(message_id, "uuid:probe-1");
+
+        let devices = parse_probe_matches(&probe_matches(&announcement, &message_id)).unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].endpoint, "urn:uuid:sim-1");
+        assert_eq!(devices[0].name.as_deref(), Some("Front Door"));
+        assert_eq!(devices[0].hardware.as_deref(), Some("SIM-1000"));
+        assert_eq!(devices[0].xaddrs, vec!["http://10.0.0.5:8000/onvif/device_service".to_string()]);
+    }
+}
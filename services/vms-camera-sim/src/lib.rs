@@ -0,0 +1,168 @@
+//! VMS Camera Simulator
+//! Câmera ONVIF/RTSP simulada para testes de integração offline: Device,
+//! Media e PTZ Service com HTTP Digest/WS-Security, respostas a Probes
+//! WS-Discovery e streams RTSP (test pattern ou arquivo) via gst-rtsp-server
+
+pub mod auth;
+pub mod config;
+pub mod device;
+pub mod discovery;
+pub mod media;
+pub mod ptz;
+pub mod rtsp;
+pub mod server;
+pub mod soap;
+pub mod state;
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use anyhow::{Context, Result};
+use tokio::task::JoinHandle;
+use tracing::info;
+
+pub use config::{AuthPolicy, SimConfig, StreamConfig, VideoSource};
+pub use state::SimState;
+
+use crate::auth::DigestAuthority;
+use crate::discovery::Announcement;
+use crate::server::{Shared, Urls};
+
+/// Simulador em execução; os serviços param no `shutdown` ou no drop
+pub struct CameraSimulator {
+    shared: Arc<Shared>,
+    onvif_addr: SocketAddr,
+    discovery_addr: Option<SocketAddr>,
+    endpoint: String,
+    tasks: Vec<JoinHandle<()>>,
+    #[cfg(feature = "rtsp")]
+    rtsp: Option<rtsp::RtspServer>,
+}
+
+impl CameraSimulator {
+    /// Sobe RTSP (se habilitado), ONVIF e WS-Discovery
+    ///
+    /// Portas 0 são resolvidas para portas efêmeras; os endereços reais ficam
+    /// em `onvif_url`, `rtsp_url` e `discovery_addr`.
+    pub async fn start(mut config: SimConfig) -> Result<Self> {
+        #[cfg(feature = "rtsp")]
+        let rtsp = {
+            let server = rtsp::RtspServer::start(&config)?;
+            config.rtsp_port = server.port();
+            Some(server)
+        };
+
+        let listener = tokio::net::TcpListener::bind(SocketAddr::new(config.bind, config.onvif_port))
+            .await
+            .context("Failed to bind ONVIF port")?;
+        let onvif_addr = listener.local_addr()?;
+        config.onvif_port = onvif_addr.port();
+
+        let urls = Urls {
+            onvif: format!("http://{}", SocketAddr::new(config.advertise, config.onvif_port)),
+            rtsp: format!("rtsp://{}", SocketAddr::new(config.advertise, config.rtsp_port)),
+        };
+        let endpoint = format!("urn:uuid:{}", uuid::Uuid::new_v4());
+        let announcement = Arc::new(Announcement::new(&config, &urls, endpoint.clone()));
+
+        let shared = Arc::new(Shared {
+            digest: DigestAuthority::new(&config.name),
+            state: Mutex::new(SimState::new(&config)),
+            urls,
+            config,
+        });
+
+        let mut tasks = Vec::new();
+        let app = server::router(shared.clone());
+        tasks.push(tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                tracing::error!("❌ ONVIF server error: {}", e);
+            }
+        }));
+
+        let mut discovery_addr = None;
+        if shared.config.discovery {
+            let socket = discovery::bind(&shared.config).await?;
+            discovery_addr = Some(socket.local_addr()?);
+            tasks.push(tokio::spawn(discovery::run(socket, announcement)));
+        }
+
+        info!(
+            "🎥 Camera simulator '{}' ready: ONVIF {} | RTSP {} | auth {:?}",
+            shared.config.name,
+            shared.urls.service("device_service"),
+            shared.urls.rtsp,
+            shared.config.auth
+        );
+
+        Ok(Self {
+            shared,
+            onvif_addr,
+            discovery_addr,
+            endpoint,
+            tasks,
+            #[cfg(feature = "rtsp")]
+            rtsp,
+        })
+    }
+
+    /// Configuração efetiva (com as portas resolvidas)
+    pub fn config(&self) -> &SimConfig {
+        &self.shared.config
+    }
+
+    /// Raiz ONVIF (`http://ip:porta`), como cadastrada no VMS
+    pub fn onvif_url(&self) -> &str {
+        &self.shared.urls.onvif
+    }
+
+    /// URL do Device Service
+    pub fn device_service_url(&self) -> String {
+        self.shared.urls.service("device_service")
+    }
+
+    /// URI RTSP do profile (mesma de GetStreamUri)
+    pub fn rtsp_url(&self, profile_token: &str) -> Option<String> {
+        let stream = self.shared.config.stream(profile_token)?;
+        Some(self.shared.urls.rtsp(&stream.mount))
+    }
+
+    pub fn onvif_addr(&self) -> SocketAddr {
+        self.onvif_addr
+    }
+
+    /// Endereço UDP para probes unicast
+    pub fn discovery_addr(&self) -> Option<SocketAddr> {
+        self.discovery_addr
+    }
+
+    /// EndpointReference anunciado no WS-Discovery
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Estado do dispositivo (para inspeção e ajustes em testes)
+    pub fn state(&self) -> MutexGuard<'_, SimState> {
+        self.shared.state.lock().unwrap()
+    }
+
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
+        #[cfg(feature = "rtsp")]
+        if let Some(mut rtsp) = self.rtsp.take() {
+            rtsp.stop();
+        }
+    }
+}
+
+impl Drop for CameraSimulator {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,41 @@
+//! VMS Camera Simulator
+//! Câmera ONVIF/RTSP simulada, configurada por variáveis de ambiente:
+//! SIM_ONVIF_PORT, SIM_RTSP_PORT, SIM_USERNAME, SIM_PASSWORD,
+//! SIM_AUTH (digest|wsse|any|none), SIM_NAME, SIM_PTZ, SIM_DISCOVERY,
+//! SIM_ADVERTISE_IP, SIM_VIDEO_FILE ou SIM_TEST_PATTERN
+
+use anyhow::Result;
+use tracing::info;
+
+use vms_camera_sim::{CameraSimulator, SimConfig};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_target(false)
+        .with_level(true)
+        .init();
+
+    info!("🎥 VMS Camera Simulator starting...");
+    info!("Version: {}", env!("CARGO_PKG_VERSION"));
+
+    let config = SimConfig::from_env();
+    let simulator = CameraSimulator::start(config).await?;
+
+    for stream in &simulator.config().streams {
+        info!(
+            "📺 {} ({}x{}@{}fps): {}",
+            stream.name,
+            stream.width,
+            stream.height,
+            stream.framerate,
+            simulator.rtsp_url(&stream.token).unwrap_or_default()
+        );
+    }
+
+    tokio::signal::ctrl_c().await?;
+
+    simulator.shutdown();
+    info!("👋 Goodbye!");
+    Ok(())
+}
@@ -0,0 +1,226 @@
+//! Media Service simulado
+//! Profiles, URIs RTSP e configuração dos encoders de vídeo
+
+use roxmltree::Node;
+use vms_onvif::media::parse_video_encoder_configurations;
+use vms_onvif::wsse::escape;
+use vms_onvif::xml_utils::ns;
+use vms_onvif::VideoEncoderConfiguration;
+
+use crate::config::StreamConfig;
+use crate::server::Context;
+use crate::soap::{required_arg, Fault};
+
+/// Token da única fonte de vídeo
+const VIDEO_SOURCE_TOKEN: &str = "VideoSource_1";
+
+/// Token da configuração PTZ associada a todos os profiles
+pub const PTZ_CONFIGURATION_TOKEN: &str = "PTZConfiguration_1";
+
+/// Resoluções aceitas pelo encoder (além das configuradas nos streams)
+const RESOLUTIONS: [(u32, u32); 5] = [(2560, 1440), (1920, 1080), (1280, 720), (640, 360), (320, 180)];
+
+const FRAME_RATE_RANGE: (u32, u32) = (1, 30);
+const GOV_LENGTH_RANGE: (u32, u32) = (1, 150);
+const BITRATE_RANGE: (u32, u32) = (32, 16384);
+
+/// Executa uma operação do Media Service; retorna o elemento de resposta
+pub fn handle(ctx: &mut Context, operation: Node) -> Result<String, Fault> {
+    let name = operation.tag_name().name();
+    let body = match name {
+        "GetProfiles" => ctx
+            .config
+            .streams
+            .iter()
+            .map(|stream| profile_xml(ctx, stream, "trt:Profiles"))
+            .collect(),
+        "GetProfile" => {
+            let stream = stream(ctx, operation)?;
+            profile_xml(ctx, stream, "trt:Profile")
+        }
+        "GetStreamUri" => {
+            let stream = stream(ctx, operation)?;
+            format!(
+                "<trt:MediaUri><tt:Uri>{}</tt:Uri><tt:InvalidAfterConnect>false</tt:InvalidAfterConnect><tt:InvalidAfterReboot>false</tt:InvalidAfterReboot><tt:Timeout>PT0S</tt:Timeout></trt:MediaUri>",
+                escape(&ctx.urls.rtsp(&stream.mount))
+            )
+        }
+        "GetVideoEncoderConfigurations" => ctx
+            .state
+            .encoders
+            .iter()
+            .map(|config| encoder_xml(config, "trt:Configurations"))
+            .collect(),
+        "GetVideoEncoderConfiguration" => encoder_xml(encoder(ctx, operation)?, "trt:Configuration"),
+        "GetVideoEncoderConfigurationOptions" => {
+            encoder(ctx, operation)?;
+            options_xml(ctx)
+        }
+        "SetVideoEncoderConfiguration" => {
+            set_video_encoder_configuration(ctx, operation)?;
+            String::new()
+        }
+        other => return Err(Fault::action_not_supported(other)),
+    };
+
+    Ok(format!(
+        r#"<trt:{name}Response xmlns:trt="{}" xmlns:tt="{}">{}</trt:{name}Response>"#,
+        ns::MEDIA,
+        ns::SCHEMA,
+        body,
+        name = name
+    ))
+}
+
+/// Stream do `ProfileToken` da requisição
+fn stream<'c>(ctx: &'c Context, operation: Node) -> Result<&'c StreamConfig, Fault> {
+    let token = required_arg(operation, "ProfileToken")?;
+    ctx.config
+        .stream(&token)
+        .ok_or_else(|| Fault::invalid_arg("NoProfile", format!("Profile {} does not exist", token)))
+}
+
+fn encoder<'c>(ctx: &'c Context, operation: Node) -> Result<&'c VideoEncoderConfiguration, Fault> {
+    let token = required_arg(operation, "ConfigurationToken")?;
+    ctx.state
+        .encoder(&token)
+        .ok_or_else(|| Fault::invalid_arg("NoConfig", format!("Configuration {} does not exist", token)))
+}
+
+fn profile_xml(ctx: &Context, stream: &StreamConfig, tag: &str) -> String {
+    let encoder = ctx
+        .state
+        .encoder(&stream.encoder_token())
+        .map(|config| encoder_xml(config, "tt:VideoEncoderConfiguration"))
+        .unwrap_or_default();
+    let ptz = if ctx.config.ptz {
+        format!(
+            r#"<tt:PTZConfiguration token="{}"><tt:Name>PTZ</tt:Name><tt:UseCount>{}</tt:UseCount><tt:NodeToken>{}</tt:NodeToken></tt:PTZConfiguration>"#,
+            PTZ_CONFIGURATION_TOKEN,
+            ctx.config.streams.len(),
+            crate::ptz::NODE_TOKEN
+        )
+    } else {
+        String::new()
+    };
+    let (width, height) = ctx
+        .config
+        .streams
+        .iter()
+        .map(|s| (s.width, s.height))
+        .max_by_key(|(w, h)| w * h)
+        .unwrap_or((stream.width, stream.height));
+
+    format!(
+        r#"<{tag} token="{token}" fixed="true"><tt:Name>{name}</tt:Name><tt:VideoSourceConfiguration token="VideoSourceConfig_1"><tt:Name>VideoSource</tt:Name><tt:UseCount>{uses}</tt:UseCount><tt:SourceToken>{source}</tt:SourceToken><tt:Bounds x="0" y="0" width="{width}" height="{height}"/></tt:VideoSourceConfiguration>{encoder}{ptz}</{tag}>"#,
+        tag = tag,
+        token = escape(&stream.token),
+        name = escape(&stream.name),
+        uses = ctx.config.streams.len(),
+        source = VIDEO_SOURCE_TOKEN,
+        width = width,
+        height = height,
+        encoder = encoder,
+        ptz = ptz
+    )
+}
+
+fn encoder_xml(config: &VideoEncoderConfiguration, tag: &str) -> String {
+    let h264 = match (config.gov_length, &config.h264_profile) {
+        (Some(gov), Some(profile)) if config.encoding == "H264" => format!(
+            "<tt:H264><tt:GovLength>{}</tt:GovLength><tt:H264Profile>{}</tt:H264Profile></tt:H264>",
+            gov,
+            escape(profile)
+        ),
+        _ => String::new(),
+    };
+
+    format!(
+        r#"<{tag} token="{token}"><tt:Name>{name}</tt:Name><tt:UseCount>{use_count}</tt:UseCount><tt:Encoding>{encoding}</tt:Encoding><tt:Resolution><tt:Width>{width}</tt:Width><tt:Height>{height}</tt:Height></tt:Resolution><tt:Quality>{quality}</tt:Quality><tt:RateControl><tt:FrameRateLimit>{fps}</tt:FrameRateLimit><tt:EncodingInterval>{interval}</tt:EncodingInterval><tt:BitrateLimit>{bitrate}</tt:BitrateLimit></tt:RateControl>{h264}<tt:Multicast><tt:Address><tt:Type>IPv4</tt:Type><tt:IPv4Address>{mc_address}</tt:IPv4Address></tt:Address><tt:Port>{mc_port}</tt:Port><tt:TTL>{mc_ttl}</tt:TTL><tt:AutoStart>{mc_auto_start}</tt:AutoStart></tt:Multicast><tt:SessionTimeout>{session_timeout}</tt:SessionTimeout></{tag}>"#,
+        tag = tag,
+        token = escape(&config.token),
+        name = escape(&config.name),
+        use_count = config.use_count,
+        encoding = escape(&config.encoding),
+        width = config.width,
+        height = config.height,
+        quality = config.quality,
+        fps = config.frame_rate_limit,
+        interval = config.encoding_interval,
+        bitrate = config.bitrate_limit,
+        h264 = h264,
+        mc_address = escape(&config.multicast.address),
+        mc_port = config.multicast.port,
+        mc_ttl = config.multicast.ttl,
+        mc_auto_start = config.multicast.auto_start,
+        session_timeout = escape(&config.session_timeout)
+    )
+}
+
+/// Resoluções aceitas: as fixas e as dos streams configurados
+fn resolutions(ctx: &Context) -> Vec<(u32, u32)> {
+    let mut resolutions: Vec<_> = RESOLUTIONS.to_vec();
+    for stream in &ctx.config.streams {
+        if !resolutions.contains(&(stream.width, stream.height)) {
+            resolutions.push((stream.width, stream.height));
+        }
+    }
+    resolutions.sort_by_key(|(w, h)| std::cmp::Reverse(w * h));
+    resolutions
+}
+
+fn options_xml(ctx: &Context) -> String {
+    let range = |tag: &str, (min, max): (u32, u32)| format!("<tt:{tag}><tt:Min>{min}</tt:Min><tt:Max>{max}</tt:Max></tt:{tag}>");
+    let resolutions: String = resolutions(ctx)
+        .into_iter()
+        .map(|(w, h)| {
+            format!(
+                "<tt:ResolutionsAvailable><tt:Width>{}</tt:Width><tt:Height>{}</tt:Height></tt:ResolutionsAvailable>",
+                w, h
+            )
+        })
+        .collect();
+
+    format!(
+        "<trt:Options>{quality}<tt:H264>{resolutions}{gov}{fps}{interval}<tt:H264ProfilesSupported>Baseline</tt:H264ProfilesSupported><tt:H264ProfilesSupported>Main</tt:H264ProfilesSupported><tt:H264ProfilesSupported>High</tt:H264ProfilesSupported></tt:H264><tt:Extension><tt:H264>{bitrate}</tt:H264></tt:Extension></trt:Options>",
+        quality = range("QualityRange", (1, 6)),
+        resolutions = resolutions,
+        gov = range("GovLengthRange", GOV_LENGTH_RANGE),
+        fps = range("FrameRateRange", FRAME_RATE_RANGE),
+        interval = range("EncodingIntervalRange", (1, 1)),
+        bitrate = range("BitrateRange", BITRATE_RANGE)
+    )
+}
+
+/// Valida contra as opções anunciadas e grava a configuração
+fn set_video_encoder_configuration(ctx: &mut Context, operation: Node) -> Result<(), Fault> {
+    let config = parse_video_encoder_configurations(operation.document().input_text())
+        .ok()
+        .and_then(|configs| configs.into_iter().next())
+        .ok_or_else(|| Fault::sender(&["InvalidArgVal"], "Configuration is required"))?;
+
+    let in_range = |value: u32, (min, max): (u32, u32)| (min..=max).contains(&value);
+    let invalid = if ctx.state.encoder(&config.token).is_none() {
+        Some(format!("Configuration {} does not exist", config.token))
+    } else if config.encoding != "H264" {
+        Some(format!("Encoding {} not supported", config.encoding))
+    } else if !resolutions(ctx).contains(&(config.width, config.height)) {
+        Some(format!("Resolution {}x{} not supported", config.width, config.height))
+    } else if !in_range(config.frame_rate_limit, FRAME_RATE_RANGE) {
+        Some(format!("FrameRateLimit {} out of range", config.frame_rate_limit))
+    } else if !in_range(config.bitrate_limit, BITRATE_RANGE) {
+        Some(format!("BitrateLimit {} out of range", config.bitrate_limit))
+    } else if config.gov_length.is_some_and(|gov| !in_range(gov, GOV_LENGTH_RANGE)) {
+        Some("GovLength out of range".to_string())
+    } else {
+        None
+    };
+    if let Some(reason) = invalid {
+        return Err(Fault::invalid_arg("ConfigModify", reason));
+    }
+
+    if let Some(current) = ctx.state.encoders.iter_mut().find(|e| e.token == config.token) {
+        *current = config;
+    }
+    Ok(())
+}
@@ -0,0 +1,246 @@
+//! PTZ Service simulado
+//! Movimentos contínuo/absoluto/relativo, presets, home e comandos auxiliares
+
+use roxmltree::Node;
+use vms_common::ptz::PTZPosition;
+use vms_onvif::wsse::escape;
+use vms_onvif::xml_utils::ns;
+
+use crate::media::PTZ_CONFIGURATION_TOKEN;
+use crate::server::Context;
+use crate::soap::{arg, required_arg, Fault};
+
+/// Token do único nó PTZ
+pub const NODE_TOKEN: &str = "PTZNode_1";
+
+const MAX_PRESETS: usize = 128;
+
+const AUX_COMMANDS: [&str; 4] = ["tt:Wiper|On", "tt:Wiper|Off", "tt:IRLamp|On", "tt:IRLamp|Off"];
+
+/// Executa uma operação do PTZ Service; retorna o elemento de resposta
+pub fn handle(ctx: &mut Context, operation: Node) -> Result<String, Fault> {
+    let name = operation.tag_name().name();
+    let ptz = &mut ctx.state.ptz;
+
+    let body = match name {
+        "GetNodes" => node_xml("tptz:PTZNode"),
+        "GetNode" => {
+            let token = required_arg(operation, "NodeToken")?;
+            if token != NODE_TOKEN {
+                return Err(Fault::invalid_arg("NoEntity", format!("Node {} does not exist", token)));
+            }
+            node_xml("tptz:PTZNode")
+        }
+        "GetConfigurationOptions" => {
+            let token = required_arg(operation, "ConfigurationToken")?;
+            if token != PTZ_CONFIGURATION_TOKEN {
+                return Err(Fault::invalid_arg("NoConfig", format!("Configuration {} does not exist", token)));
+            }
+            format!(
+                "<tptz:PTZConfigurationOptions><tt:Spaces>{}</tt:Spaces><tt:PTZTimeout><tt:Min>PT1S</tt:Min><tt:Max>PT60S</tt:Max></tt:PTZTimeout></tptz:PTZConfigurationOptions>",
+                spaces_xml()
+            )
+        }
+        "ContinuousMove" => {
+            check_profile(ctx.config, operation)?;
+            let velocity = vector(operation, "Velocity", PTZPosition::new(0.0, 0.0, 0.0));
+            ptz.continuous_move(velocity);
+            String::new()
+        }
+        "AbsoluteMove" => {
+            check_profile(ctx.config, operation)?;
+            let current = ptz.position();
+            ptz.move_to(vector(operation, "Position", current));
+            String::new()
+        }
+        "RelativeMove" => {
+            check_profile(ctx.config, operation)?;
+            let current = ptz.position();
+            let delta = vector(operation, "Translation", PTZPosition::new(0.0, 0.0, 0.0));
+            ptz.move_to(PTZPosition::new(
+                current.pan + delta.pan,
+                current.tilt + delta.tilt,
+                current.zoom + delta.zoom,
+            ));
+            String::new()
+        }
+        "Stop" => {
+            check_profile(ctx.config, operation)?;
+            ptz.stop();
+            String::new()
+        }
+        "GetStatus" => {
+            check_profile(ctx.config, operation)?;
+            let position = ptz.position();
+            let state = if ptz.is_moving() { "MOVING" } else { "IDLE" };
+            format!(
+                "<tptz:PTZStatus><tt:Position>{}</tt:Position><tt:MoveStatus><tt:PanTilt>{state}</tt:PanTilt><tt:Zoom>{state}</tt:Zoom></tt:MoveStatus><tt:Error>NO error</tt:Error><tt:UtcTime>{}</tt:UtcTime></tptz:PTZStatus>",
+                position_xml(position),
+                ctx.state.clock.now().format("%Y-%m-%dT%H:%M:%SZ"),
+                state = state
+            )
+        }
+        "GetPresets" => {
+            check_profile(ctx.config, operation)?;
+            ptz.presets
+                .iter()
+                .map(|preset| {
+                    format!(
+                        r#"<tptz:Preset token="{}"><tt:Name>{}</tt:Name><tt:PTZPosition>{}</tt:PTZPosition></tptz:Preset>"#,
+                        escape(&preset.token),
+                        escape(&preset.name),
+                        position_xml(preset.position)
+                    )
+                })
+                .collect()
+        }
+        "SetPreset" => {
+            check_profile(ctx.config, operation)?;
+            let token = arg(operation, "PresetToken");
+            let exists = token.as_ref().is_some_and(|t| ptz.presets.iter().any(|p| &p.token == t));
+            if !exists && ptz.presets.len() >= MAX_PRESETS {
+                return Err(Fault::receiver(&["Action", "TooManyPresets"], "Maximum number of presets reached"));
+            }
+            let name = arg(operation, "PresetName").unwrap_or_else(|| format!("Preset {}", ptz.presets.len() + 1));
+            format!("<tptz:PresetToken>{}</tptz:PresetToken>", escape(&ptz.set_preset(token, name)))
+        }
+        "RemovePreset" => {
+            check_profile(ctx.config, operation)?;
+            let token = required_arg(operation, "PresetToken")?;
+            let before = ptz.presets.len();
+            ptz.presets.retain(|p| p.token != token);
+            if ptz.presets.len() == before {
+                return Err(Fault::invalid_arg("NoToken", format!("Preset {} does not exist", token)));
+            }
+            String::new()
+        }
+        "GotoPreset" => {
+            check_profile(ctx.config, operation)?;
+            let token = required_arg(operation, "PresetToken")?;
+            let position = ptz
+                .presets
+                .iter()
+                .find(|p| p.token == token)
+                .map(|p| p.position)
+                .ok_or_else(|| Fault::invalid_arg("NoToken", format!("Preset {} does not exist", token)))?;
+            ptz.move_to(position);
+            String::new()
+        }
+        "GotoHomePosition" => {
+            check_profile(ctx.config, operation)?;
+            let home = ptz.home;
+            ptz.move_to(home);
+            String::new()
+        }
+        "SetHomePosition" => {
+            check_profile(ctx.config, operation)?;
+            ptz.home = ptz.position();
+            String::new()
+        }
+        "SendAuxiliaryCommand" => {
+            check_profile(ctx.config, operation)?;
+            let command = required_arg(operation, "AuxiliaryData")?;
+            if !AUX_COMMANDS.contains(&command.as_str()) {
+                return Err(Fault::invalid_arg("InvalidAuxiliaryData", format!("Unknown command {}", command)));
+            }
+            ptz.aux_commands.push(command.clone());
+            format!("<tptz:AuxiliaryResponse>{}</tptz:AuxiliaryResponse>", escape(&command))
+        }
+        other => return Err(Fault::action_not_supported(other)),
+    };
+
+    Ok(format!(
+        r#"<tptz:{name}Response xmlns:tptz="{}" xmlns:tt="{}">{}</tptz:{name}Response>"#,
+        ns::PTZ,
+        ns::SCHEMA,
+        body,
+        name = name
+    ))
+}
+
+fn check_profile(config: &crate::config::SimConfig, operation: Node) -> Result<(), Fault> {
+    let token = required_arg(operation, "ProfileToken")?;
+    match config.stream(&token) {
+        Some(_) => Ok(()),
+        None => Err(Fault::invalid_arg("NoProfile", format!("Profile {} does not exist", token))),
+    }
+}
+
+/// Lê `<PanTilt x y/>` e `<Zoom x/>` do elemento `local`; componentes
+/// ausentes ficam com o valor de `default`
+fn vector(operation: Node, local: &str, default: PTZPosition) -> PTZPosition {
+    let Some(node) = operation
+        .children()
+        .find(|n| n.is_element() && n.tag_name().name() == local)
+    else {
+        return default;
+    };
+    let attr = |child: &str, name: &str| {
+        node.children()
+            .find(|n| n.is_element() && n.tag_name().name() == child)
+            .and_then(|n| n.attribute(name))
+            .and_then(|v| v.parse::<f32>().ok())
+    };
+
+    PTZPosition::new(
+        attr("PanTilt", "x").unwrap_or(default.pan),
+        attr("PanTilt", "y").unwrap_or(default.tilt),
+        attr("Zoom", "x").unwrap_or(default.zoom),
+    )
+}
+
+fn position_xml(position: PTZPosition) -> String {
+    format!(
+        r#"<tt:PanTilt x="{}" y="{}" space="http://www.onvif.org/ver10/tptz/PanTiltSpaces/PositionGenericSpace"/><tt:Zoom x="{}" space="http://www.onvif.org/ver10/tptz/ZoomSpaces/PositionGenericSpace"/>"#,
+        position.pan, position.tilt, position.zoom
+    )
+}
+
+/// Espaços genéricos normalizados (-1..1 pan/tilt, 0..1 zoom)
+fn spaces_xml() -> String {
+    let range = |tag: &str, min: f32, max: f32| format!("<tt:{tag}><tt:Min>{min}</tt:Min><tt:Max>{max}</tt:Max></tt:{tag}>");
+    let pan_tilt = |tag: &str, space: &str| {
+        format!(
+            "<tt:{tag}><tt:URI>http://www.onvif.org/ver10/tptz/PanTiltSpaces/{space}</tt:URI>{}{}</tt:{tag}>",
+            range("XRange", -1.0, 1.0),
+            range("YRange", -1.0, 1.0)
+        )
+    };
+    let zoom = |tag: &str, space: &str, min: f32| {
+        format!(
+            "<tt:{tag}><tt:URI>http://www.onvif.org/ver10/tptz/ZoomSpaces/{space}</tt:URI>{}</tt:{tag}>",
+            range("XRange", min, 1.0)
+        )
+    };
+
+    [
+        pan_tilt("AbsolutePanTiltPositionSpace", "PositionGenericSpace"),
+        zoom("AbsoluteZoomPositionSpace", "PositionGenericSpace", 0.0),
+        pan_tilt("RelativePanTiltTranslationSpace", "TranslationGenericSpace"),
+        zoom("RelativeZoomTranslationSpace", "TranslationGenericSpace", -1.0),
+        pan_tilt("ContinuousPanTiltVelocitySpace", "VelocityGenericSpace"),
+        zoom("ContinuousZoomVelocitySpace", "VelocityGenericSpace", -1.0),
+        format!(
+            "<tt:PanTiltSpeedSpace><tt:URI>http://www.onvif.org/ver10/tptz/PanTiltSpaces/GenericSpeedSpace</tt:URI>{}</tt:PanTiltSpeedSpace>",
+            range("XRange", 0.0, 1.0)
+        ),
+        zoom("ZoomSpeedSpace", "ZoomGenericSpeedSpace", 0.0),
+    ]
+    .concat()
+}
+
+fn node_xml(tag: &str) -> String {
+    let aux: String = AUX_COMMANDS
+        .iter()
+        .map(|c| format!("<tt:AuxiliaryCommands>{}</tt:AuxiliaryCommands>", c))
+        .collect();
+
+    format!(
+        r#"<{tag} token="{token}" FixedHomePosition="false"><tt:Name>PTZ</tt:Name><tt:SupportedPTZSpaces>{spaces}</tt:SupportedPTZSpaces><tt:MaximumNumberOfPresets>{max}</tt:MaximumNumberOfPresets><tt:HomeSupported>true</tt:HomeSupported>{aux}</{tag}>"#,
+        tag = tag,
+        token = NODE_TOKEN,
+        spaces = spaces_xml(),
+        max = MAX_PRESETS,
+        aux = aux
+    )
+}
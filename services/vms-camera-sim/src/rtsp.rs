@@ -0,0 +1,137 @@
+//! Servidor RTSP (gst-rtsp-server)
+//! Um mount por stream configurado, a partir de `videotestsrc` ou de um
+//! arquivo re-encodado em H.264
+
+use crate::config::{StreamConfig, VideoSource};
+
+/// Launch line da media factory de um stream
+///
+/// O test pattern é ao vivo e compartilhado entre clientes; o arquivo é
+/// reproduzido do início a cada sessão.
+pub fn launch_line(source: &VideoSource, stream: &StreamConfig) -> String {
+    let input = match source {
+        VideoSource::TestPattern(pattern) => format!("videotestsrc is-live=true pattern={}", pattern),
+        VideoSource::File(path) => format!(
+            "filesrc location=\"{}\" ! decodebin ! videoconvert ! videoscale ! videorate",
+            path.display()
+        ),
+    };
+
+    format!(
+        "( {input} ! video/x-raw,width={width},height={height},framerate={fps}/1 ! timeoverlay ! videoconvert ! x264enc tune=zerolatency speed-preset=ultrafast bitrate={bitrate} key-int-max={gop} ! rtph264pay name=pay0 pt=96 config-interval=1 )",
+        input = input,
+        width = stream.width,
+        height = stream.height,
+        fps = stream.framerate,
+        bitrate = stream.bitrate_kbps,
+        gop = stream.gop
+    )
+}
+
+#[cfg(feature = "rtsp")]
+pub use server::RtspServer;
+
+#[cfg(feature = "rtsp")]
+mod server {
+    use anyhow::{anyhow, Context, Result};
+    use gstreamer as gst;
+    use gstreamer_rtsp_server as gst_rtsp_server;
+    use gst::glib;
+    use gst_rtsp_server::prelude::*;
+    use tracing::info;
+
+    use super::launch_line;
+    use crate::config::{SimConfig, VideoSource};
+
+    /// gst-rtsp-server rodando no seu próprio main loop (thread dedicada)
+    pub struct RtspServer {
+        context: glib::MainContext,
+        main_loop: glib::MainLoop,
+        thread: Option<std::thread::JoinHandle<()>>,
+        port: u16,
+    }
+
+    impl RtspServer {
+        pub fn start(config: &SimConfig) -> Result<Self> {
+            gst::init().context("Failed to initialize GStreamer")?;
+
+            let context = glib::MainContext::new();
+            let main_loop = glib::MainLoop::new(Some(&context), false);
+
+            let server = gst_rtsp_server::RTSPServer::new();
+            server.set_address(&config.bind.to_string());
+            server.set_service(&config.rtsp_port.to_string());
+
+            let mounts = server
+                .mount_points()
+                .ok_or_else(|| anyhow!("RTSP server has no mount points"))?;
+            for stream in &config.streams {
+                let factory = gst_rtsp_server::RTSPMediaFactory::new();
+                factory.set_launch(&launch_line(&config.video_source, stream));
+                factory.set_shared(matches!(config.video_source, VideoSource::TestPattern(_)));
+                mounts.add_factory(&stream.mount, factory);
+            }
+
+            // A source pertence ao contexto próprio e morre com ele
+            server
+                .attach(Some(&context))
+                .context("Failed to bind RTSP server")?;
+            let port = u16::try_from(server.bound_port()).context("RTSP server not bound")?;
+            info!("📺 RTSP server listening on port {}", port);
+
+            let thread = {
+                let main_loop = main_loop.clone();
+                std::thread::spawn(move || {
+                    let _server = server;
+                    main_loop.run();
+                })
+            };
+
+            Ok(Self {
+                context,
+                main_loop,
+                thread: Some(thread),
+                port,
+            })
+        }
+
+        pub fn port(&self) -> u16 {
+            self.port
+        }
+
+        pub fn stop(&mut self) {
+            // Via contexto: funciona mesmo se o loop ainda não começou a rodar
+            let main_loop = self.main_loop.clone();
+            self.context.invoke(move || main_loop.quit());
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+
+    impl Drop for RtspServer {
+        fn drop(&mut self) {
+            self.stop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimConfig;
+
+    #[test]
+    fn test_launch_lines() {
+        let config = SimConfig::default();
+        let main = launch_line(&config.video_source, &config.streams[0]);
+        assert!(main.starts_with("( videotestsrc is-live=true pattern=smpte !"));
+        assert!(main.contains("width=1920,height=1080,framerate=25/1"));
+        assert!(main.contains("key-int-max=50"));
+        assert!(main.ends_with("rtph264pay name=pay0 pt=96 config-interval=1 )"));
+
+        let file = launch_line(&VideoSource::File("/tmp/clip.mp4".into()), &config.streams[1]);
+        assert!(file.starts_with("( filesrc location=\"/tmp/clip.mp4\" ! decodebin"));
+        assert!(file.contains("width=640,height=360,framerate=15/1"));
+    }
+}
@@ -0,0 +1,135 @@
+//! Servidor HTTP dos serviços ONVIF
+//! Um único handler SOAP: autentica, registra a operação e despacha pelo
+//! namespace do elemento da operação
+
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Router,
+};
+use tracing::{debug, warn};
+use vms_onvif::soap::SoapVersion;
+use vms_onvif::xml_utils::ns;
+
+use crate::auth::{self, AuthOutcome, DigestAuthority};
+use crate::config::SimConfig;
+use crate::soap::{self, Fault, SoapRequest};
+use crate::state::SimState;
+use crate::{device, media, ptz};
+
+/// Operações aceitas sem autenticação (ONVIF Core, "pre-auth")
+const PRE_AUTH_OPERATIONS: [&str; 2] = ["GetSystemDateAndTime", "GetServices"];
+
+/// Endereços anunciados nas respostas
+#[derive(Debug, Clone)]
+pub struct Urls {
+    /// `http://ip:porta`
+    pub onvif: String,
+    /// `rtsp://ip:porta`
+    pub rtsp: String,
+}
+
+impl Urls {
+    /// XAddr de um serviço (ex: `media_service`)
+    pub fn service(&self, path: &str) -> String {
+        format!("{}/onvif/{}", self.onvif, path)
+    }
+
+    /// URI RTSP de um mount
+    pub fn rtsp(&self, mount: &str) -> String {
+        format!("{}{}", self.rtsp, mount)
+    }
+}
+
+/// Estado compartilhado entre as requisições
+pub struct Shared {
+    pub config: SimConfig,
+    pub urls: Urls,
+    pub digest: DigestAuthority,
+    pub state: Mutex<SimState>,
+}
+
+/// Contexto de uma operação
+pub struct Context<'a> {
+    pub config: &'a SimConfig,
+    pub urls: &'a Urls,
+    pub state: &'a mut SimState,
+}
+
+pub fn router(shared: Arc<Shared>) -> Router {
+    Router::new()
+        .route("/onvif/:service", post(handle_soap))
+        .with_state(shared)
+}
+
+async fn handle_soap(State(shared): State<Arc<Shared>>, headers: HeaderMap, body: String) -> Response {
+    let Ok(doc) = roxmltree::Document::parse(&body) else {
+        return fault_response(SoapVersion::V12, &Fault::sender(&["WellFormed"], "Malformed XML"));
+    };
+    let Some(request) = SoapRequest::parse(&doc) else {
+        return fault_response(SoapVersion::V12, &Fault::version_mismatch());
+    };
+
+    let mut state = shared.state.lock().unwrap();
+    if !PRE_AUTH_OPERATIONS.contains(&request.name()) {
+        let authorization = headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok());
+        match auth::authorize(shared.config.auth, &shared.digest, authorization, request.header, &state) {
+            AuthOutcome::Allowed => {}
+            AuthOutcome::Challenge => {
+                debug!("🔐 {} without valid Digest credentials, sending challenge", request.name());
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    [(header::WWW_AUTHENTICATE, shared.digest.challenge())],
+                )
+                    .into_response();
+            }
+            AuthOutcome::Denied => {
+                debug!("🔐 {} rejected: not authorized", request.name());
+                return fault_response(request.version, &Fault::not_authorized());
+            }
+        }
+    }
+
+    state.requests.push(request.name().to_string());
+    let mut ctx = Context {
+        config: &shared.config,
+        urls: &shared.urls,
+        state: &mut state,
+    };
+
+    let result = match request.namespace() {
+        Some(ns::DEVICE) => device::handle(&mut ctx, request.operation),
+        Some(ns::MEDIA) => media::handle(&mut ctx, request.operation),
+        Some(ns::PTZ) if shared.config.ptz => ptz::handle(&mut ctx, request.operation),
+        _ => Err(Fault::action_not_supported(request.name())),
+    };
+
+    match result {
+        Ok(body) => {
+            debug!("📥 {} OK", request.name());
+            (
+                StatusCode::OK,
+                [(header::CONTENT_TYPE, soap::content_type(request.version))],
+                soap::response(request.version, &body),
+            )
+                .into_response()
+        }
+        Err(fault) => {
+            warn!("⚠️  {} failed: {}", request.name(), fault.reason);
+            fault_response(request.version, &fault)
+        }
+    }
+}
+
+fn fault_response(version: SoapVersion, fault: &Fault) -> Response {
+    (
+        fault.status,
+        [(header::CONTENT_TYPE, soap::content_type(version))],
+        fault.to_xml(version),
+    )
+        .into_response()
+}
@@ -0,0 +1,209 @@
+//! SOAP do lado do dispositivo: leitura da requisição, respostas e Faults
+
+use axum::http::StatusCode;
+use roxmltree::{Document, Node};
+use vms_onvif::soap::{self, SoapVersion};
+use vms_onvif::wsse::escape;
+use vms_onvif::xml_utils::{self, ns};
+
+/// Namespace dos subcódigos de erro ONVIF (`ter:`)
+pub const NS_ERROR: &str = "http://www.onvif.org/ver10/error";
+
+/// Envelope recebido
+pub struct SoapRequest<'a, 'input> {
+    pub version: SoapVersion,
+    /// Header SOAP (WS-Security), se presente
+    pub header: Option<Node<'a, 'input>>,
+    /// Elemento da operação (primeiro filho do Body)
+    pub operation: Node<'a, 'input>,
+}
+
+impl<'a, 'input> SoapRequest<'a, 'input> {
+    /// Extrai versão, header e operação; `None` se não for um envelope SOAP
+    pub fn parse(doc: &'a Document<'input>) -> Option<Self> {
+        let envelope = doc.root_element();
+        let version = if xml_utils::is(envelope, ns::SOAP12, "Envelope") {
+            SoapVersion::V12
+        } else if xml_utils::is(envelope, ns::SOAP11, "Envelope") {
+            SoapVersion::V11
+        } else {
+            return None;
+        };
+
+        Some(Self {
+            version,
+            header: xml_utils::child(envelope, version.namespace(), "Header"),
+            operation: xml_utils::soap_body(doc)?.children().find(|n| n.is_element())?,
+        })
+    }
+
+    /// Nome local da operação (ex: `GetProfiles`)
+    pub fn name(&self) -> &'input str {
+        self.operation.tag_name().name()
+    }
+
+    /// Namespace da operação (identifica o serviço)
+    pub fn namespace(&self) -> Option<&'a str> {
+        self.operation.tag_name().namespace()
+    }
+}
+
+/// SOAP Fault a ser devolvido
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fault {
+    pub status: StatusCode,
+    /// `Sender`, `Receiver` ou `VersionMismatch`
+    pub code: &'static str,
+    /// Subcódigos `ter:` (do mais genérico ao mais específico)
+    pub subcodes: Vec<&'static str>,
+    pub reason: String,
+}
+
+impl Fault {
+    /// Erro do cliente (`env:Sender`)
+    pub fn sender(subcodes: &[&'static str], reason: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            code: "Sender",
+            subcodes: subcodes.to_vec(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Erro do dispositivo (`env:Receiver`)
+    pub fn receiver(subcodes: &[&'static str], reason: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            code: "Receiver",
+            subcodes: subcodes.to_vec(),
+            reason: reason.into(),
+        }
+    }
+
+    pub fn not_authorized() -> Self {
+        Self::sender(&["NotAuthorized"], "Sender not Authorized")
+    }
+
+    pub fn action_not_supported(operation: &str) -> Self {
+        Self::receiver(
+            &["ActionNotSupported"],
+            format!("Optional Action {} Not Implemented", operation),
+        )
+    }
+
+    /// Argumento inválido (ex: `NoProfile`, `NoToken`)
+    pub fn invalid_arg(subcode: &'static str, reason: impl Into<String>) -> Self {
+        Self::sender(&["InvalidArgVal", subcode], reason)
+    }
+
+    /// Envelope em namespace desconhecido
+    pub fn version_mismatch() -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            code: "VersionMismatch",
+            subcodes: Vec::new(),
+            reason: "Unsupported SOAP envelope".to_string(),
+        }
+    }
+
+    /// Envelope do Fault na versão da requisição
+    pub fn to_xml(&self, version: SoapVersion) -> String {
+        let body = match version {
+            SoapVersion::V12 => {
+                let subcodes = self.subcodes.iter().rev().fold(String::new(), |inner, subcode| {
+                    format!("<s:Subcode><s:Value>ter:{}</s:Value>{}</s:Subcode>", subcode, inner)
+                });
+                format!(
+                    r#"<s:Fault xmlns:ter="{}"><s:Code><s:Value>s:{}</s:Value>{}</s:Code><s:Reason><s:Text xml:lang="en">{}</s:Text></s:Reason></s:Fault>"#,
+                    NS_ERROR,
+                    self.code,
+                    subcodes,
+                    escape(&self.reason)
+                )
+            }
+            SoapVersion::V11 => {
+                // SOAP 1.1 não tem subcódigos: "Client.NotAuthorized"
+                let code = match self.code {
+                    "Sender" => "Client",
+                    "Receiver" => "Server",
+                    other => other,
+                };
+                let faultcode = match self.subcodes.last() {
+                    Some(subcode) => format!("s:{}.{}", code, subcode),
+                    None => format!("s:{}", code),
+                };
+                format!(
+                    "<s:Fault><faultcode>{}</faultcode><faultstring>{}</faultstring></s:Fault>",
+                    faultcode,
+                    escape(&self.reason)
+                )
+            }
+        };
+        soap::envelope(version, None, &body)
+    }
+}
+
+/// Resposta de sucesso
+pub fn response(version: SoapVersion, body: &str) -> String {
+    soap::envelope(version, None, body)
+}
+
+/// Content-Type da resposta
+pub fn content_type(version: SoapVersion) -> &'static str {
+    match version {
+        SoapVersion::V11 => "text/xml; charset=utf-8",
+        SoapVersion::V12 => "application/soap+xml; charset=utf-8",
+    }
+}
+
+/// Texto de um filho direto em qualquer namespace de serviço (`ProfileToken` etc.)
+pub fn arg(operation: Node, local: &str) -> Option<String> {
+    operation
+        .children()
+        .find(|n| n.is_element() && n.tag_name().name() == local)
+        .and_then(xml_utils::text)
+}
+
+/// Argumento obrigatório
+pub fn required_arg(operation: Node, local: &'static str) -> Result<String, Fault> {
+    arg(operation, local).ok_or_else(|| Fault::sender(&["InvalidArgVal"], format!("{} is required", local)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_faults_round_trip_through_client_parser() {
+        let xml = Fault::not_authorized().to_xml(SoapVersion::V12);
+        let fault = soap::parse_fault(&xml).unwrap();
+        assert_eq!(fault.code, "Sender");
+        assert!(fault.is_not_authorized());
+
+        let xml = Fault::invalid_arg("NoProfile", "No such profile").to_xml(SoapVersion::V12);
+        let fault = soap::parse_fault(&xml).unwrap();
+        assert_eq!(fault.subcodes, vec!["InvalidArgVal".to_string(), "NoProfile".to_string()]);
+
+        let xml = Fault::not_authorized().to_xml(SoapVersion::V11);
+        assert!(soap::parse_fault(&xml).unwrap().is_not_authorized());
+        let xml = Fault::version_mismatch().to_xml(SoapVersion::V11);
+        assert!(soap::parse_fault(&xml).unwrap().is_version_mismatch());
+    }
+
+    #[test]
+    fn test_parse_request() {
+        let xml = r#"<e:Envelope xmlns:e="http://schemas.xmlsoap.org/soap/envelope/">
+            <e:Body><trt:GetStreamUri xmlns:trt="http://www.onvif.org/ver10/media/wsdl">
+                <trt:ProfileToken> Profile_1 </trt:ProfileToken>
+            </trt:GetStreamUri></e:Body>
+        </e:Envelope>"#;
+        let doc = Document::parse(xml).unwrap();
+        let request = SoapRequest::parse(&doc).unwrap();
+
+        assert_eq!(request.version, SoapVersion::V11);
+        assert_eq!(request.name(), "GetStreamUri");
+        assert_eq!(request.namespace(), Some(ns::MEDIA));
+        assert!(request.header.is_none());
+        assert_eq!(arg(request.operation, "ProfileToken").as_deref(), Some("Profile_1"));
+    }
+}
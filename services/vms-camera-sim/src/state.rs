@@ -0,0 +1,232 @@
+//! Estado mutável do dispositivo simulado
+//! Usuários, encoders, PTZ, relógio e log das operações recebidas (para
+//! asserções em testes)
+
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use vms_common::ptz::PTZPosition;
+use vms_onvif::media::Multicast;
+use vms_onvif::{OnvifUser, UserLevel, VideoEncoderConfiguration};
+
+use crate::config::SimConfig;
+
+/// Fração da faixa normalizada percorrida por segundo à velocidade 1.0
+const PTZ_UNITS_PER_SECOND: f32 = 0.5;
+
+/// Preset PTZ armazenado
+#[derive(Debug, Clone)]
+pub struct SimPreset {
+    pub token: String,
+    pub name: String,
+    pub position: PTZPosition,
+}
+
+/// Estado do PTZ
+#[derive(Debug, Clone)]
+pub struct PtzState {
+    position: PTZPosition,
+    /// Velocidade do ContinuousMove em andamento (zero = parado)
+    velocity: PTZPosition,
+    last_update: Instant,
+    pub home: PTZPosition,
+    pub presets: Vec<SimPreset>,
+    next_preset: u32,
+    /// Comandos auxiliares recebidos (ex: `tt:Wiper|On`)
+    pub aux_commands: Vec<String>,
+}
+
+impl Default for PtzState {
+    fn default() -> Self {
+        Self {
+            position: PTZPosition::new(0.0, 0.0, 0.0),
+            velocity: PTZPosition::new(0.0, 0.0, 0.0),
+            last_update: Instant::now(),
+            home: PTZPosition::new(0.0, 0.0, 0.0),
+            presets: Vec::new(),
+            next_preset: 1,
+            aux_commands: Vec::new(),
+        }
+    }
+}
+
+impl PtzState {
+    /// Posição atual, integrando o ContinuousMove desde a última leitura
+    pub fn position(&mut self) -> PTZPosition {
+        let elapsed = self.last_update.elapsed().as_secs_f32();
+        self.last_update = Instant::now();
+        if self.is_moving() {
+            let step = elapsed * PTZ_UNITS_PER_SECOND;
+            self.position = PTZPosition::new(
+                self.position.pan + self.velocity.pan * step,
+                self.position.tilt + self.velocity.tilt * step,
+                self.position.zoom + self.velocity.zoom * step,
+            );
+        }
+        self.position
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.velocity.pan != 0.0 || self.velocity.tilt != 0.0 || self.velocity.zoom != 0.0
+    }
+
+    pub fn continuous_move(&mut self, velocity: PTZPosition) {
+        self.position();
+        self.velocity = velocity;
+    }
+
+    /// Move instantaneamente para `position` (interrompe o ContinuousMove)
+    pub fn move_to(&mut self, position: PTZPosition) {
+        self.velocity = PTZPosition::new(0.0, 0.0, 0.0);
+        self.position = PTZPosition::new(position.pan, position.tilt, position.zoom);
+        self.last_update = Instant::now();
+    }
+
+    pub fn stop(&mut self) {
+        self.position();
+        self.velocity = PTZPosition::new(0.0, 0.0, 0.0);
+    }
+
+    /// Cria ou sobrescreve um preset na posição atual; retorna o token
+    pub fn set_preset(&mut self, token: Option<String>, name: String) -> String {
+        let position = self.position();
+        if let Some(preset) = token.as_ref().and_then(|t| self.presets.iter_mut().find(|p| &p.token == t)) {
+            preset.name = name;
+            preset.position = position;
+            return preset.token.clone();
+        }
+
+        let token = token.unwrap_or_else(|| {
+            while self.presets.iter().any(|p| p.token == self.next_preset.to_string()) {
+                self.next_preset += 1;
+            }
+            self.next_preset.to_string()
+        });
+        self.presets.push(SimPreset {
+            token: token.clone(),
+            name,
+            position,
+        });
+        token
+    }
+}
+
+/// Configuração de relógio recebida por SetSystemDateAndTime/SetNTP
+#[derive(Debug, Clone, Default)]
+pub struct ClockState {
+    /// `NTP` ou `Manual`
+    pub date_time_type: Option<String>,
+    pub timezone: Option<String>,
+    pub ntp_servers: Vec<String>,
+    /// Diferença aplicada ao relógio anunciado (ajuste manual ou clock skew simulado)
+    pub offset: chrono::Duration,
+}
+
+impl ClockState {
+    /// Horário UTC anunciado pelo dispositivo
+    pub fn now(&self) -> DateTime<Utc> {
+        Utc::now() + self.offset
+    }
+}
+
+/// Estado completo do dispositivo
+#[derive(Debug, Clone)]
+pub struct SimState {
+    pub users: Vec<OnvifUser>,
+    pub encoders: Vec<VideoEncoderConfiguration>,
+    pub ptz: PtzState,
+    pub clock: ClockState,
+    /// Operações recebidas, em ordem (ex: `GetProfiles`)
+    pub requests: Vec<String>,
+}
+
+impl SimState {
+    pub fn new(config: &SimConfig) -> Self {
+        let encoders = config
+            .streams
+            .iter()
+            .map(|stream| VideoEncoderConfiguration {
+                token: stream.encoder_token(),
+                name: format!("{}Encoder", stream.name),
+                use_count: 1,
+                encoding: "H264".to_string(),
+                width: stream.width,
+                height: stream.height,
+                quality: 4.0,
+                frame_rate_limit: stream.framerate,
+                encoding_interval: 1,
+                bitrate_limit: stream.bitrate_kbps,
+                gov_length: Some(stream.gop),
+                h264_profile: Some("Main".to_string()),
+                multicast: Multicast::default(),
+                session_timeout: "PT60S".to_string(),
+            })
+            .collect();
+
+        Self {
+            users: vec![OnvifUser {
+                username: config.username.clone(),
+                password: Some(config.password.clone()),
+                level: UserLevel::Administrator,
+            }],
+            encoders,
+            ptz: PtzState::default(),
+            clock: ClockState::default(),
+            requests: Vec::new(),
+        }
+    }
+
+    /// Senha do usuário, se cadastrado
+    pub fn password_of(&self, username: &str) -> Option<&str> {
+        self.users
+            .iter()
+            .find(|u| u.username == username)
+            .and_then(|u| u.password.as_deref())
+    }
+
+    pub fn has_user(&self, username: &str) -> bool {
+        self.users.iter().any(|u| u.username == username)
+    }
+
+    pub fn encoder(&self, token: &str) -> Option<&VideoEncoderConfiguration> {
+        self.encoders.iter().find(|e| e.token == token)
+    }
+
+    /// Quantas vezes a operação foi recebida
+    pub fn request_count(&self, operation: &str) -> usize {
+        self.requests.iter().filter(|r| r.as_str() == operation).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_get_sequential_tokens_and_overwrite() {
+        let mut ptz = PtzState::default();
+        ptz.move_to(PTZPosition::new(0.5, -0.2, 0.1));
+        assert_eq!(ptz.set_preset(None, "Gate".to_string()), "1");
+        assert_eq!(ptz.set_preset(Some("7".to_string()), "Dock".to_string()), "7");
+        assert_eq!(ptz.set_preset(None, "Lobby".to_string()), "2");
+
+        ptz.move_to(PTZPosition::new(-1.0, 0.0, 0.0));
+        assert_eq!(ptz.set_preset(Some("1".to_string()), "Gate 2".to_string()), "1");
+        assert_eq!(ptz.presets.len(), 3);
+        assert_eq!(ptz.presets[0].name, "Gate 2");
+        assert_eq!(ptz.presets[0].position.pan, -1.0);
+    }
+
+    #[test]
+    fn test_continuous_move_integrates_and_clamps() {
+        let mut ptz = PtzState::default();
+        ptz.continuous_move(PTZPosition::new(1.0, 0.0, 0.0));
+        ptz.last_update -= std::time::Duration::from_secs(10);
+        let position = ptz.position();
+        assert_eq!(position.pan, 1.0);
+        assert!(ptz.is_moving());
+
+        ptz.stop();
+        assert!(!ptz.is_moving());
+    }
+}
@@ -0,0 +1,174 @@
+//! `vms_onvif` contra o simulador: autenticação, mídia, PTZ, configuração e discovery
+
+use std::time::Duration;
+
+use vms_camera_sim::{AuthPolicy, CameraSimulator, SimConfig};
+use vms_common::media_profile::MediaProfile;
+use vms_common::ptz::PTZPosition;
+use vms_common::types::{FrameRate, Resolution};
+use vms_onvif::media::apply_media_profile;
+use vms_onvif::{AuthMode, OnvifDevice, OnvifDiscovery, OnvifUser, UserLevel};
+
+fn config(auth: AuthPolicy) -> SimConfig {
+    SimConfig {
+        username: "admin".to_string(),
+        password: "s3cret".to_string(),
+        auth,
+        ..SimConfig::default()
+    }
+}
+
+async fn connect(sim: &CameraSimulator, username: &str, password: &str) -> anyhow::Result<OnvifDevice> {
+    let mut device = OnvifDevice::new(sim.onvif_url(), username, password)?;
+    device.connect().await?;
+    Ok(device)
+}
+
+#[tokio::test]
+async fn test_digest_negotiation_profiles_and_stream_uri() {
+    let sim = CameraSimulator::start(config(AuthPolicy::Digest)).await.unwrap();
+    let device = connect(&sim, "admin", "s3cret").await.unwrap();
+    assert_eq!(device.client().auth_mode(), AuthMode::Digest);
+
+    let info = device.get_device_info().await.unwrap();
+    assert_eq!(info.model, "SIM-1000");
+
+    let profiles = device.get_profiles().await.unwrap();
+    assert_eq!(profiles.len(), 2);
+    assert_eq!(profiles[0].resolution, (1920, 1080));
+    assert_eq!(profiles[1].video_encoder_token.as_deref(), Some("Profile_2_encoder"));
+    assert!(profiles[0].ptz_configuration_token.is_some());
+
+    let uri = device.get_stream_uri(&profiles[1].token).await.unwrap();
+    assert_eq!(Some(uri), sim.rtsp_url("Profile_2"));
+    assert!(device.get_stream_uri("missing").await.is_err());
+
+    assert!(connect(&sim, "admin", "wrong").await.is_err());
+    assert!(sim.state().request_count("GetProfiles") >= 1);
+}
+
+#[tokio::test]
+async fn test_ws_security_with_camera_clock_skew() {
+    let sim = CameraSimulator::start(config(AuthPolicy::WsSecurity)).await.unwrap();
+    sim.state().clock.offset = chrono::Duration::hours(2);
+
+    let device = connect(&sim, "admin", "s3cret").await.unwrap();
+    assert_eq!(device.client().auth_mode(), AuthMode::WsSecurity);
+    let offset = device.client().clock_offset().unwrap();
+    assert!((offset - chrono::Duration::hours(2)).num_seconds().abs() <= 2);
+
+    assert!(connect(&sim, "admin", "wrong").await.is_err());
+}
+
+#[tokio::test]
+async fn test_ptz_moves_and_presets() {
+    let sim = CameraSimulator::start(config(AuthPolicy::Any)).await.unwrap();
+    let device = connect(&sim, "admin", "s3cret").await.unwrap();
+    let profile = device.get_profiles().await.unwrap().remove(0);
+    let ptz = device.ptz().expect("PTZ service announced");
+
+    let capabilities = device.ptz_capabilities(&profile).await.unwrap();
+    assert!(capabilities.absolute_move && capabilities.continuous_move && capabilities.zoom);
+    assert_eq!(capabilities.max_presets, 128);
+    assert!(capabilities.aux_functions.iter().any(|a| a == "tt:Wiper|On"));
+
+    ptz.absolute_move(&profile.token, PTZPosition::new(0.5, -0.25, 0.3), None)
+        .await
+        .unwrap();
+    let token = ptz.set_preset(&profile.token, "Gate", None).await.unwrap();
+
+    ptz.relative_move(&profile.token, PTZPosition::new(-0.5, 0.25, 0.0), None)
+        .await
+        .unwrap();
+    let status = ptz.get_status(&profile.token).await.unwrap();
+    assert!(status.position.pan.abs() < 1e-6 && !status.is_moving);
+
+    ptz.goto_preset(&profile.token, &token, None).await.unwrap();
+    let status = ptz.get_status(&profile.token).await.unwrap();
+    assert!((status.position.pan - 0.5).abs() < 1e-6);
+
+    ptz.continuous_move(&profile.token, 0.5, 0.0, 0.0).await.unwrap();
+    assert!(ptz.get_status(&profile.token).await.unwrap().is_moving);
+    ptz.stop(&profile.token).await.unwrap();
+    assert!(!ptz.get_status(&profile.token).await.unwrap().is_moving);
+
+    let presets = ptz.get_presets(&profile.token).await.unwrap();
+    assert_eq!(presets.len(), 1);
+    assert_eq!(presets[0].name, "Gate");
+    ptz.remove_preset(&profile.token, &token).await.unwrap();
+    assert!(ptz.remove_preset(&profile.token, &token).await.is_err());
+
+    ptz.send_auxiliary_command(&profile.token, "tt:Wiper|On").await.unwrap();
+    assert_eq!(sim.state().ptz.aux_commands, vec!["tt:Wiper|On".to_string()]);
+}
+
+#[tokio::test]
+async fn test_encoder_time_and_user_configuration() {
+    let sim = CameraSimulator::start(config(AuthPolicy::Any)).await.unwrap();
+    let device = connect(&sim, "admin", "s3cret").await.unwrap();
+    let media = device.media().unwrap();
+
+    let current = media.get_video_encoder_configuration("Profile_1_encoder").await.unwrap();
+    let options = media.get_video_encoder_options("Profile_1_encoder").await.unwrap();
+    let mut profile = MediaProfile::liveview_default();
+    profile.resolution = Resolution::new(1280, 720);
+    profile.fps = FrameRate::new(60.0);
+    let (config, warnings) = apply_media_profile(&current, Some(&options), &profile);
+    assert_eq!(config.frame_rate_limit, 30);
+    assert!(!warnings.is_empty());
+    media.set_video_encoder_configuration(&config).await.unwrap();
+    assert_eq!(sim.state().encoder("Profile_1_encoder").unwrap().width, 1280);
+
+    let mut invalid = config.clone();
+    invalid.width = 1000;
+    assert!(media.set_video_encoder_configuration(&invalid).await.is_err());
+
+    let system = device.system();
+    system.set_ntp(&["pool.ntp.org".to_string()]).await.unwrap();
+    system
+        .set_system_date_and_time(vms_onvif::DateTimeMode::Ntp, Some("BRT3"))
+        .await
+        .unwrap();
+    assert_eq!(sim.state().clock.ntp_servers, vec!["pool.ntp.org".to_string()]);
+    assert_eq!(sim.state().clock.timezone.as_deref(), Some("BRT3"));
+
+    let operator = OnvifUser {
+        username: "operator".to_string(),
+        password: Some("op".to_string()),
+        level: UserLevel::Operator,
+    };
+    system.upsert_user(&operator).await.unwrap();
+    system
+        .upsert_user(&OnvifUser {
+            password: Some("op2".to_string()),
+            ..operator
+        })
+        .await
+        .unwrap();
+    assert!(connect(&sim, "operator", "op2").await.is_ok());
+
+    system.delete_users(&["operator".to_string()]).await.unwrap();
+    assert_eq!(system.get_users().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_unicast_discovery_probe() {
+    let sim = CameraSimulator::start(SimConfig {
+        name: "Lobby Cam".to_string(),
+        ..SimConfig::default()
+    })
+    .await
+    .unwrap();
+
+    let devices = OnvifDiscovery::new()
+        .with_target(sim.discovery_addr().unwrap())
+        .with_timeout(Duration::from_millis(500))
+        .probe()
+        .await
+        .unwrap();
+
+    assert_eq!(devices.len(), 1);
+    assert_eq!(devices[0].endpoint, sim.endpoint());
+    assert_eq!(devices[0].name.as_deref(), Some("Lobby Cam"));
+    assert_eq!(devices[0].service_url(), Some(sim.device_service_url().as_str()));
+}
@@ -12,8 +12,12 @@ pub enum EventType {
     AIDetection,
     /// Camera status change (online/offline)
     CameraStatus,
+    /// Camera tampering (obstruction, defocus, redirection, frozen image, video loss)
+    CameraTampering,
     /// Motion detected
     MotionDetection,
+    /// Digital input state change (camera I/O)
+    DigitalInput,
     /// Line crossing
     LineCrossing,
     /// Area intrusion
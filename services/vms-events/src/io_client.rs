@@ -0,0 +1,135 @@
+//! Cliente das saídas de I/O (relés das câmeras) expostas pelo vms-api
+//!
+//! O vms-api só aciona relés para operadores ou serviços autenticados; o
+//! motor de regras se identifica com o token de serviço (`VMS_SERVICE_SECRET`).
+
+use anyhow::{bail, Result};
+use serde_json::json;
+use tracing::{info, warn};
+use vms_common::service_auth::ServiceCredential;
+
+/// Nome com que o motor de regras se identifica aos outros serviços
+pub const SERVICE_NAME: &str = "vms-events";
+
+/// Credencial de serviço do motor de regras, se configurada
+pub fn service_credential() -> Option<ServiceCredential> {
+    let credential = ServiceCredential::from_env(SERVICE_NAME);
+    if credential.is_none() {
+        warn!("⚠️ VMS_SERVICE_SECRET not set: vms-api will reject rule actions");
+    }
+    credential
+}
+
+/// Cliente HTTP de `/api/v1/io` no vms-api
+#[derive(Clone)]
+pub struct IoClient {
+    http: reqwest::Client,
+    base_url: String,
+    credential: Option<ServiceCredential>,
+}
+
+impl IoClient {
+    pub fn new(base_url: impl Into<String>, credential: Option<ServiceCredential>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            credential,
+        }
+    }
+
+    /// URL do vms-api em `VMS_API_URL` (padrão: localhost:9095)
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var("VMS_API_URL").unwrap_or_else(|_| "http://localhost:9095".to_string()),
+            service_credential(),
+        )
+    }
+
+    /// Aciona a saída `port` do dispositivo (câmera)
+    pub async fn set_output(&self, device_id: &str, port: u8, state: bool, pulse_ms: Option<u32>) -> Result<()> {
+        let url = format!("{}/api/v1/io/{}/outputs/{}", self.base_url, device_id, port);
+        let mut request = self.http.post(&url);
+        if let Some(credential) = &self.credential {
+            request = request.header(reqwest::header::AUTHORIZATION, credential.bearer());
+        }
+        let response = request
+            .json(&json!({ "state": state, "pulse_ms": pulse_ms }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            bail!("{} {}", response.status(), response.text().await.unwrap_or_default());
+        }
+        Ok(())
+    }
+
+    /// Aciona a saída sem bloquear o processamento das regras
+    pub fn spawn_set_output(&self, device_id: String, port: u8, state: bool, pulse_ms: Option<u32>) {
+        let client = self.clone();
+        tokio::spawn(async move {
+            match client.set_output(&device_id, port, state, pulse_ms).await {
+                Ok(()) => info!("⚡ Output {}:{} set to {} (pulse {:?})", device_id, port, state, pulse_ms),
+                Err(e) => warn!("⚠️ Failed to set output {}:{}: {}", device_id, port, e),
+            }
+        });
+    }
+}
+
+/// Separa um `output_id` no formato `device_id:port`
+pub fn parse_output_id(output_id: &str) -> Option<(&str, u8)> {
+    let (device_id, port) = output_id.rsplit_once(':')?;
+    Some((device_id, port.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Router};
+
+    use crate::test_api::{credential, spawn_api, OutputRequest};
+
+    #[test]
+    fn test_parse_output_id() {
+        assert_eq!(parse_output_id("cam-1:2"), Some(("cam-1", 2)));
+        // O device_id pode conter `:`; a porta é o último campo
+        assert_eq!(parse_output_id("a:b:3"), Some(("a:b", 3)));
+        assert_eq!(parse_output_id("cam-1"), None);
+        assert_eq!(parse_output_id("cam-1:x"), None);
+        assert_eq!(parse_output_id("cam-1:300"), None);
+    }
+
+    #[tokio::test]
+    async fn test_set_output_with_service_credential() {
+        let mut api = spawn_api().await;
+        let client = IoClient::new(format!("{}/", api.url), credential());
+
+        client.set_output("cam-1", 2, true, Some(1500)).await.unwrap();
+        assert_eq!(
+            api.outputs.recv().await.unwrap(),
+            OutputRequest {
+                device_id: "cam-1".to_string(),
+                port: 2,
+                body: serde_json::json!({ "state": true, "pulse_ms": 1500 }),
+                service: Some(SERVICE_NAME.to_string()),
+            }
+        );
+
+        // Sem credencial o pedido segue anônimo (e o vms-api recusa)
+        IoClient::new(api.url, None).set_output("cam-1", 1, false, None).await.unwrap();
+        assert_eq!(api.outputs.recv().await.unwrap().service, None);
+    }
+
+    #[tokio::test]
+    async fn test_set_output_reports_api_errors() {
+        let app = Router::new().route(
+            "/api/v1/io/:device_id/outputs/:port",
+            post(|| async { (axum::http::StatusCode::UNAUTHORIZED, "Missing bearer token") }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let error = IoClient::new(url, None).set_output("cam-1", 1, true, None).await.unwrap_err();
+        assert!(error.to_string().contains("401"), "{}", error);
+    }
+}
@@ -13,15 +13,23 @@ use tokio::net::TcpListener;
 use tokio::sync::RwLock;
 use tracing::{info, warn};
 use tracing_subscriber;
+use vms_common::gateway_client::{join_gateway, nats_url, public_url};
 
 mod alarm;
 mod event;
+mod io_client;
 mod rule;
+mod talk_client;
+#[cfg(test)]
+mod test_api;
 
 use alarm::{Alarm, AlarmManager, AlarmPriority, AlarmStatus};
 use event::{Event, EventType};
 use rule::{Rule, RuleEngine};
 
+/// Porta da API REST
+const HTTP_PORT: u16 = 9096;
+
 /// App state
 #[derive(Clone)]
 struct AppState {
@@ -40,9 +48,11 @@ async fn main() -> Result<()> {
     info!("🚀 VMS Events Service starting...");
     info!("Version: {}", env!("CARGO_PKG_VERSION"));
 
+    // Registro no vms-gateway (NATS da configuração central; NATS_URL tem precedência)
+    let central = join_gateway(io_client::SERVICE_NAME, public_url(HTTP_PORT)).await;
+
     // Conectar ao NATS
-    let nats_url = std::env::var("NATS_URL")
-        .unwrap_or_else(|_| "nats://localhost:4222".to_string());
+    let nats_url = nats_url(&central);
     let nats_client = async_nats::connect(&nats_url)
         .await
         .context("Failed to connect to NATS")?;
@@ -105,7 +115,7 @@ async fn main() -> Result<()> {
         .route("/api/v1/rules/:id/disable", post(disable_rule))
         .with_state(state);
 
-    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], 9096));
+    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], HTTP_PORT));
     let listener = TcpListener::bind(addr).await?;
 
     info!("🌐 HTTP API listening on http://{}", addr);
@@ -180,9 +190,23 @@ async fn consume_camera_events(
     while let Some(message) = subscriber.next().await {
         match serde_json::from_slice::<serde_json::Value>(&message.payload) {
             Ok(payload) => {
+                // Sabotagem, movimento e I/O (análise local ou eventos ONVIF da câmera)
+                // chegam como `vms_common::Event`; o tipo vem do trigger
+                let trigger = payload["trigger"].as_object();
+                let has = |name: &str| trigger.is_some_and(|t| t.contains_key(name));
+                let event_type = if has("CameraObstructed") || has("CameraTampered") {
+                    EventType::CameraTampering
+                } else if has("MotionDetected") {
+                    EventType::MotionDetection
+                } else if has("DigitalIO") {
+                    EventType::DigitalInput
+                } else {
+                    EventType::CameraStatus
+                };
+
                 let event = Event {
                     id: uuid::Uuid::new_v4(),
-                    event_type: EventType::CameraStatus,
+                    event_type,
                     timestamp: chrono::Utc::now(),
                     camera_id: payload["camera_id"].as_str().map(|s| s.to_string()),
                     data: payload,
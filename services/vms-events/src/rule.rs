@@ -2,11 +2,13 @@
 
 use super::alarm::{Alarm, AlarmManager, AlarmPriority};
 use super::event::{Event, EventType};
+use super::io_client::{parse_output_id, IoClient};
+use super::talk_client::TalkClient;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::info;
+use tracing::{info, warn};
 use uuid::Uuid;
 
 /// Rule condition
@@ -113,15 +115,30 @@ pub enum RuleAction {
         camera_id: String,
         preset: String,
     },
-    /// Activate output
+    /// Activate output (`device_id:port`) as a pulse
     ActivateOutput { output_id: String, duration_secs: u64 },
+    /// Set camera relay output
+    SetDigitalOutput {
+        device_id: String,
+        port: u8,
+        state: bool,
+        pulse_ms: Option<u32>,
+    },
+    /// Play a recorded clip (from `AUDIO_CLIPS_PATH`) on the camera speaker
+    PlayAudioClip { camera_id: String, audio_file: String },
     /// Run script
     RunScript { script_path: String, args: Vec<String> },
 }
 
 impl RuleAction {
     /// Execute action
-    pub async fn execute(&self, event: &Event, alarm_manager: &AlarmManager) {
+    pub async fn execute(
+        &self,
+        event: &Event,
+        alarm_manager: &AlarmManager,
+        io_client: &IoClient,
+        talk_client: &TalkClient,
+    ) {
         match self {
             RuleAction::CreateAlarm {
                 name,
@@ -175,7 +192,28 @@ impl RuleAction {
                     "⚡ Activating output {} for {}s",
                     output_id, duration_secs
                 );
-                // TODO: Integrate with automation system
+                match parse_output_id(output_id) {
+                    Some((device_id, port)) => {
+                        let pulse_ms = duration_secs.saturating_mul(1000).min(u32::MAX as u64) as u32;
+                        io_client.spawn_set_output(device_id.to_string(), port, true, Some(pulse_ms));
+                    }
+                    None => warn!("⚠️ Invalid output id (expected device_id:port): {}", output_id),
+                }
+            }
+
+            RuleAction::SetDigitalOutput {
+                device_id,
+                port,
+                state,
+                pulse_ms,
+            } => {
+                info!("⚡ Setting output {}:{} to {}", device_id, port, state);
+                io_client.spawn_set_output(device_id.clone(), *port, *state, *pulse_ms);
+            }
+
+            RuleAction::PlayAudioClip { camera_id, audio_file } => {
+                info!("🔈 Playing audio clip {} on camera {}", audio_file, camera_id);
+                talk_client.spawn_play_clip(camera_id.clone(), audio_file.clone());
             }
 
             RuleAction::RunScript { script_path, args } => {
@@ -251,13 +289,22 @@ impl Rule {
 /// Rule engine
 pub struct RuleEngine {
     rules: Arc<RwLock<HashMap<Uuid, Rule>>>,
+    io_client: IoClient,
+    talk_client: TalkClient,
 }
 
 impl RuleEngine {
     /// Create new rule engine
     pub fn new() -> Self {
+        Self::with_clients(IoClient::from_env(), TalkClient::from_env())
+    }
+
+    /// Rule engine driving outputs and speakers through the given clients
+    pub fn with_clients(io_client: IoClient, talk_client: TalkClient) -> Self {
         Self {
             rules: Arc::new(RwLock::new(HashMap::new())),
+            io_client,
+            talk_client,
         }
     }
 
@@ -331,7 +378,7 @@ impl RuleEngine {
 
                 // Execute actions
                 for action in &rule.actions {
-                    action.execute(event, alarm_manager).await;
+                    action.execute(event, alarm_manager, &self.io_client, &self.talk_client).await;
                 }
 
                 // Update last triggered
@@ -346,3 +393,173 @@ impl Default for RuleEngine {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_api::{spawn_api, ClipRequest, OutputRequest};
+
+    fn event(event_type: EventType, camera_id: &str) -> Event {
+        Event {
+            id: Uuid::new_v4(),
+            event_type,
+            timestamp: chrono::Utc::now(),
+            camera_id: Some(camera_id.to_string()),
+            data: serde_json::json!({}),
+        }
+    }
+
+    fn engine(api_url: &str) -> RuleEngine {
+        engine_with_clips(api_url, std::path::Path::new("/nonexistent"))
+    }
+
+    fn engine_with_clips(api_url: &str, clips_path: &std::path::Path) -> RuleEngine {
+        RuleEngine::with_clients(IoClient::new(api_url, None), TalkClient::new(api_url, clips_path, None))
+    }
+
+    #[test]
+    fn test_io_actions_from_json() {
+        let actions: Vec<RuleAction> = serde_json::from_value(serde_json::json!([
+            { "type": "set_digital_output", "device_id": "cam-1", "port": 2, "state": true, "pulse_ms": 500 },
+            { "type": "activate_output", "output_id": "cam-1:3", "duration_secs": 5 },
+            { "type": "play_audio_clip", "camera_id": "cam-1", "audio_file": "warning.wav" },
+        ]))
+        .unwrap();
+
+        assert!(matches!(
+            &actions[0],
+            RuleAction::SetDigitalOutput { device_id, port: 2, state: true, pulse_ms: Some(500) } if device_id == "cam-1"
+        ));
+        assert!(matches!(
+            &actions[1],
+            RuleAction::ActivateOutput { output_id, duration_secs: 5 } if output_id == "cam-1:3"
+        ));
+        assert!(matches!(
+            &actions[2],
+            RuleAction::PlayAudioClip { camera_id, audio_file } if camera_id == "cam-1" && audio_file == "warning.wav"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rule_drives_outputs() {
+        let mut api = spawn_api().await;
+        let engine = engine(&api.url);
+        engine
+            .add(Rule::new(
+                "Portão".to_string(),
+                "Sensor abre o portão e toca a sirene".to_string(),
+                vec![RuleCondition::EventType {
+                    event_type: EventType::DigitalInput,
+                }],
+                vec![
+                    RuleAction::SetDigitalOutput {
+                        device_id: "cam-1".to_string(),
+                        port: 1,
+                        state: true,
+                        pulse_ms: None,
+                    },
+                    RuleAction::ActivateOutput {
+                        output_id: "cam-2:2".to_string(),
+                        duration_secs: 3,
+                    },
+                    // Sem porta: ignorado
+                    RuleAction::ActivateOutput {
+                        output_id: "cam-3".to_string(),
+                        duration_secs: 3,
+                    },
+                ],
+            ))
+            .await;
+
+        let alarms = AlarmManager::new();
+        // Outro tipo de evento não aciona nada
+        engine.process_event(&event(EventType::MotionDetection, "cam-1"), &alarms).await;
+        engine.process_event(&event(EventType::DigitalInput, "cam-1"), &alarms).await;
+
+        let mut received = vec![api.outputs.recv().await.unwrap(), api.outputs.recv().await.unwrap()];
+        received.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+        let output = |device_id: &str, port, body| OutputRequest {
+            device_id: device_id.to_string(),
+            port,
+            body,
+            service: None,
+        };
+        assert_eq!(
+            received,
+            vec![
+                output("cam-1", 1, serde_json::json!({ "state": true, "pulse_ms": null })),
+                output("cam-2", 2, serde_json::json!({ "state": true, "pulse_ms": 3000 })),
+            ]
+        );
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert!(api.outputs.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rule_plays_audio_clip() {
+        let mut api = spawn_api().await;
+        let dir = std::env::temp_dir().join(format!("vms-rule-clips-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("intruder.wav"), b"RIFF").unwrap();
+
+        let engine = engine_with_clips(&api.url, &dir);
+        engine
+            .add(Rule::new(
+                "Aviso".to_string(),
+                "Aviso gravado na sabotagem".to_string(),
+                vec![RuleCondition::EventType {
+                    event_type: EventType::CameraTampering,
+                }],
+                vec![RuleAction::PlayAudioClip {
+                    camera_id: "cam-9".to_string(),
+                    audio_file: "intruder.wav".to_string(),
+                }],
+            ))
+            .await;
+        engine
+            .process_event(&event(EventType::CameraTampering, "cam-1"), &AlarmManager::new())
+            .await;
+
+        assert_eq!(
+            api.clips.recv().await.unwrap(),
+            ClipRequest {
+                camera_id: "cam-9".to_string(),
+                content_type: "audio/wav".to_string(),
+                clip: b"RIFF".to_vec(),
+                service: None,
+            }
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_suppresses_actions() {
+        let mut api = spawn_api().await;
+        let engine = engine(&api.url);
+        let mut rule = Rule::new(
+            "Sirene".to_string(),
+            String::new(),
+            vec![RuleCondition::CameraId {
+                camera_id: "cam-1".to_string(),
+            }],
+            vec![RuleAction::SetDigitalOutput {
+                device_id: "cam-1".to_string(),
+                port: 1,
+                state: true,
+                pulse_ms: Some(1000),
+            }],
+        );
+        rule.cooldown_secs = 60;
+        engine.add(rule).await;
+
+        let alarms = AlarmManager::new();
+        engine.process_event(&event(EventType::DigitalInput, "cam-1"), &alarms).await;
+        engine.process_event(&event(EventType::DigitalInput, "cam-1"), &alarms).await;
+
+        assert_eq!(api.outputs.recv().await.unwrap().port, 1);
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert!(api.outputs.try_recv().is_err());
+    }
+}
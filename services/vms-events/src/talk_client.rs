@@ -0,0 +1,174 @@
+//! Cliente dos clipes de áudio tocados no alto-falante das câmeras
+//!
+//! Os clipes (avisos gravados) ficam em `AUDIO_CLIPS_PATH` no host do
+//! vms-events; a regra cita o arquivo pelo nome e ele é enviado ao vms-api,
+//! que o repassa ao nó vms-stream da câmera (backchannel ONVIF). O envio
+//! leva o token de serviço do motor de regras.
+
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use tracing::{info, warn};
+use vms_common::service_auth::ServiceCredential;
+
+use crate::io_client::service_credential;
+
+/// Cliente HTTP de `/api/v1/talk` no vms-api
+#[derive(Clone)]
+pub struct TalkClient {
+    http: reqwest::Client,
+    base_url: String,
+    clips_path: PathBuf,
+    credential: Option<ServiceCredential>,
+}
+
+impl TalkClient {
+    pub fn new(
+        base_url: impl Into<String>,
+        clips_path: impl Into<PathBuf>,
+        credential: Option<ServiceCredential>,
+    ) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            clips_path: clips_path.into(),
+            credential,
+        }
+    }
+
+    /// vms-api em `VMS_API_URL` e clipes em `AUDIO_CLIPS_PATH`
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var("VMS_API_URL").unwrap_or_else(|_| "http://localhost:9095".to_string()),
+            std::env::var("AUDIO_CLIPS_PATH").unwrap_or_else(|_| "/var/lib/vms/audio-clips".to_string()),
+            service_credential(),
+        )
+    }
+
+    /// Arquivo do clipe; só nomes dentro do diretório de clipes
+    fn clip_path(&self, audio_file: &str) -> Result<PathBuf> {
+        let relative = Path::new(audio_file);
+        if audio_file.is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            bail!("Invalid audio clip name: {}", audio_file);
+        }
+        Ok(self.clips_path.join(relative))
+    }
+
+    /// Toca o clipe na câmera
+    pub async fn play_clip(&self, camera_id: &str, audio_file: &str) -> Result<()> {
+        let path = self.clip_path(audio_file)?;
+        let clip = tokio::fs::read(&path)
+            .await
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        let url = format!("{}/api/v1/talk/{}/clip", self.base_url, camera_id);
+        let mut request = self.http.post(&url);
+        if let Some(credential) = &self.credential {
+            request = request.header(reqwest::header::AUTHORIZATION, credential.bearer());
+        }
+        let response = request
+            .header(reqwest::header::CONTENT_TYPE, content_type(&path))
+            .body(clip)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            bail!("{} {}", response.status(), response.text().await.unwrap_or_default());
+        }
+        Ok(())
+    }
+
+    /// Toca o clipe sem bloquear o processamento das regras
+    pub fn spawn_play_clip(&self, camera_id: String, audio_file: String) {
+        let client = self.clone();
+        tokio::spawn(async move {
+            match client.play_clip(&camera_id, &audio_file).await {
+                Ok(()) => info!("🔈 Playing {} on camera {}", audio_file, camera_id),
+                Err(e) => warn!("⚠️ Failed to play {} on camera {}: {}", audio_file, camera_id, e),
+            }
+        });
+    }
+}
+
+/// Tipo do arquivo pela extensão (o nó decodifica qualquer um deles)
+fn content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("wav") => "audio/wav",
+        Some("mp3") => "audio/mpeg",
+        Some("ogg" | "oga" | "opus") => "audio/ogg",
+        Some("flac") => "audio/flac",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io_client::SERVICE_NAME;
+    use crate::test_api::{credential, spawn_api, ClipRequest};
+
+    /// Diretório de clipes temporário com `warning.wav`
+    fn clips_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vms-clips-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("gate")).unwrap();
+        std::fs::write(dir.join("warning.wav"), b"RIFF\0\0\0\0WAVE").unwrap();
+        std::fs::write(dir.join("gate").join("closing.mp3"), b"ID3").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_clip_path_stays_in_clips_dir() {
+        let client = TalkClient::new("http://localhost:9095", "/var/lib/vms/audio-clips", None);
+
+        assert_eq!(
+            client.clip_path("warning.wav").unwrap(),
+            PathBuf::from("/var/lib/vms/audio-clips/warning.wav")
+        );
+        assert_eq!(
+            client.clip_path("gate/closing.mp3").unwrap(),
+            PathBuf::from("/var/lib/vms/audio-clips/gate/closing.mp3")
+        );
+
+        for name in ["", "../secrets.wav", "gate/../../etc/passwd", "/etc/passwd", "./warning.wav"] {
+            assert!(client.clip_path(name).is_err(), "{:?} accepted", name);
+        }
+    }
+
+    #[test]
+    fn test_content_type() {
+        assert_eq!(content_type(Path::new("a.WAV")), "audio/wav");
+        assert_eq!(content_type(Path::new("a.mp3")), "audio/mpeg");
+        assert_eq!(content_type(Path::new("a.opus")), "audio/ogg");
+        assert_eq!(content_type(Path::new("a.flac")), "audio/flac");
+        assert_eq!(content_type(Path::new("a")), "application/octet-stream");
+    }
+
+    #[tokio::test]
+    async fn test_play_clip_with_service_credential() {
+        let mut api = spawn_api().await;
+        let dir = clips_dir();
+        let client = TalkClient::new(api.url.clone(), &dir, credential());
+
+        client.play_clip("cam-1", "warning.wav").await.unwrap();
+        assert_eq!(
+            api.clips.recv().await.unwrap(),
+            ClipRequest {
+                camera_id: "cam-1".to_string(),
+                content_type: "audio/wav".to_string(),
+                clip: b"RIFF\0\0\0\0WAVE".to_vec(),
+                service: Some(SERVICE_NAME.to_string()),
+            }
+        );
+
+        // Nome fora do diretório ou arquivo inexistente: nada é enviado
+        assert!(client.play_clip("cam-1", "../warning.wav").await.is_err());
+        assert!(client.play_clip("cam-1", "missing.wav").await.is_err());
+        assert!(api.clips.try_recv().is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,96 @@
+//! Stand-in do vms-api para os testes
+//!
+//! Atende `/api/v1/io/:device_id/outputs/:port` e `/api/v1/talk/:camera_id/clip`
+//! e repassa cada pedido ao teste, com o serviço autenticado pelo token
+//! (assinado com [`SERVICE_SECRET`]).
+
+use axum::{
+    body::Bytes,
+    extract::Path,
+    http::HeaderMap,
+    routing::post,
+    Json, Router,
+};
+use tokio::sync::mpsc;
+use vms_common::service_auth::{verify_service_token, ServiceCredential};
+
+use crate::io_client::SERVICE_NAME;
+
+pub const SERVICE_SECRET: &str = "service-secret";
+
+/// Pedido de saída recebido
+#[derive(Debug, PartialEq)]
+pub struct OutputRequest {
+    pub device_id: String,
+    pub port: u8,
+    pub body: serde_json::Value,
+    pub service: Option<String>,
+}
+
+/// Clipe recebido
+#[derive(Debug, PartialEq)]
+pub struct ClipRequest {
+    pub camera_id: String,
+    pub content_type: String,
+    pub clip: Vec<u8>,
+    pub service: Option<String>,
+}
+
+pub struct StandInApi {
+    pub url: String,
+    pub outputs: mpsc::UnboundedReceiver<OutputRequest>,
+    pub clips: mpsc::UnboundedReceiver<ClipRequest>,
+}
+
+/// Credencial do vms-events com o segredo do stand-in
+pub fn credential() -> Option<ServiceCredential> {
+    Some(ServiceCredential::new(SERVICE_NAME, SERVICE_SECRET))
+}
+
+/// Serviço que assinou o `Authorization` do pedido, se válido
+fn service(headers: &HeaderMap) -> Option<String> {
+    let token = headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))?;
+    verify_service_token(SERVICE_SECRET.as_bytes(), token, chrono::Utc::now().timestamp()).ok()
+}
+
+pub async fn spawn_api() -> StandInApi {
+    let (outputs_tx, outputs) = mpsc::unbounded_channel();
+    let (clips_tx, clips) = mpsc::unbounded_channel();
+    let app = Router::new()
+        .route(
+            "/api/v1/io/:device_id/outputs/:port",
+            post(
+                move |Path((device_id, port)): Path<(String, u8)>,
+                      headers: HeaderMap,
+                      Json(body): Json<serde_json::Value>| async move {
+                    let service = service(&headers);
+                    let _ = outputs_tx.send(OutputRequest { device_id, port, body, service });
+                    Json(serde_json::json!({}))
+                },
+            ),
+        )
+        .route(
+            "/api/v1/talk/:camera_id/clip",
+            post(move |Path(camera_id): Path<String>, headers: HeaderMap, clip: Bytes| async move {
+                let content_type = headers
+                    .get("content-type")
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string();
+                let _ = clips_tx.send(ClipRequest {
+                    camera_id,
+                    content_type,
+                    clip: clip.to_vec(),
+                    service: service(&headers),
+                });
+                Json(serde_json::json!({ "id": uuid::Uuid::new_v4() }))
+            }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    StandInApi { url, outputs, clips }
+}
@@ -0,0 +1,180 @@
+//! Endpoints próprios do gateway: configuração e registro de serviços
+//!
+//! Todos exigem um token de serviço ([`ServiceCaller`]); segredos só são
+//! entregues ao serviço dono da seção.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+use vms_common::registry::{RegistrationResponse, ServiceInstance, ServiceRegistration};
+
+use crate::auth::ServiceCaller;
+use crate::nats_consumer::publish_sections;
+use crate::settings::service_config;
+use crate::AppState;
+
+type ApiError = (StatusCode, Json<Value>);
+
+fn not_found(message: &str) -> ApiError {
+    (StatusCode::NOT_FOUND, Json(json!({ "error": message })))
+}
+
+fn forbidden(message: &str) -> ApiError {
+    (StatusCode::FORBIDDEN, Json(json!({ "error": message })))
+}
+
+// Configuração
+
+pub async fn get_config(_caller: ServiceCaller, State(state): State<AppState>) -> Json<Value> {
+    let config = state.config.current().await.redacted();
+    Json(serde_json::to_value(&config).unwrap_or_default())
+}
+
+pub async fn get_section(
+    _caller: ServiceCaller,
+    State(state): State<AppState>,
+    Path(section): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    state
+        .config
+        .current()
+        .await
+        .redacted()
+        .section(&section)
+        .map(Json)
+        .ok_or_else(|| not_found("Configuration section not found"))
+}
+
+/// Configuração de um serviço, com segredos apenas para o próprio serviço
+pub async fn get_service_config(
+    caller: ServiceCaller,
+    State(state): State<AppState>,
+    Path(service): Path<String>,
+) -> Json<Value> {
+    let config = state.config.current().await;
+    if caller.is(&service) {
+        Json(service_config(&config, &service))
+    } else {
+        Json(service_config(&config.redacted(), &service))
+    }
+}
+
+/// Relê o arquivo e publica as seções alteradas
+pub async fn reload_config(
+    caller: ServiceCaller,
+    State(state): State<AppState>,
+) -> Result<Json<Value>, ApiError> {
+    let changed = state.config.reload().await.map_err(|e| {
+        tracing::error!("❌ Configuration reload failed: {:#}", e);
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({ "error": format!("{:#}", e) })),
+        )
+    })?;
+
+    tracing::info!(
+        "🔄 Configuration reloaded by {} ({} sections changed)",
+        caller.0,
+        changed.len()
+    );
+    if let Some(nats) = &state.nats {
+        publish_sections(nats, &*state.config.current().await, &changed).await;
+    }
+
+    Ok(Json(json!({ "changed": changed })))
+}
+
+// Registro
+
+pub async fn list_services(_caller: ServiceCaller, State(state): State<AppState>) -> Json<Vec<ServiceInstance>> {
+    Json(state.registry.list().await)
+}
+
+pub async fn list_service_instances(
+    _caller: ServiceCaller,
+    State(state): State<AppState>,
+    Path(service): Path<String>,
+) -> Json<Vec<ServiceInstance>> {
+    Json(state.registry.instances_of(&service).await)
+}
+
+/// Instância registrada pelo próprio chamador
+async fn owned_instance(caller: &ServiceCaller, state: &AppState, id: Uuid) -> Result<ServiceInstance, ApiError> {
+    let instance = state
+        .registry
+        .get(id)
+        .await
+        .ok_or_else(|| not_found("Instance not registered"))?;
+    if !caller.is(&instance.service) {
+        return Err(forbidden("Instance belongs to another service"));
+    }
+    Ok(instance)
+}
+
+/// Registra a instância e entrega a configuração do serviço
+///
+/// Um serviço só registra (e recebe a configuração de) si mesmo.
+pub async fn register(
+    caller: ServiceCaller,
+    State(state): State<AppState>,
+    Json(registration): Json<ServiceRegistration>,
+) -> Result<(StatusCode, Json<RegistrationResponse>), ApiError> {
+    if registration.service.trim().is_empty() || registration.url.parse::<axum::http::Uri>().is_err() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "service and a valid url are required" })),
+        ));
+    }
+    if !caller.is(&registration.service) {
+        return Err(forbidden("Service token does not match the registered service"));
+    }
+    if let Some(id) = registration.instance_id {
+        if state.registry.get(id).await.is_some() {
+            owned_instance(&caller, &state, id).await?;
+        }
+    }
+
+    let instance = state.registry.register(registration).await;
+    tracing::info!("✅ Registered {} instance {} at {}", instance.service, instance.id, instance.url);
+
+    let config = state.config.current().await;
+    let response = RegistrationResponse {
+        heartbeat_interval_secs: (config.gateway.heartbeat_ttl_secs / 3).max(1),
+        config: Some(service_config(&config, &instance.service)),
+        instance,
+    };
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+pub async fn heartbeat(
+    caller: ServiceCaller,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ServiceInstance>, ApiError> {
+    owned_instance(&caller, &state, id).await?;
+    state
+        .registry
+        .heartbeat(id)
+        .await
+        .map(Json)
+        .ok_or_else(|| not_found("Instance not registered"))
+}
+
+pub async fn deregister(
+    caller: ServiceCaller,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    owned_instance(&caller, &state, id).await?;
+    let instance = state
+        .registry
+        .deregister(id)
+        .await
+        .ok_or_else(|| not_found("Instance not registered"))?;
+    tracing::info!("👋 Deregistered {} instance {}", instance.service, instance.id);
+    Ok(StatusCode::NO_CONTENT)
+}
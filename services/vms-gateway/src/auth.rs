@@ -0,0 +1,60 @@
+//! Autenticação dos endpoints de configuração e registro
+//!
+//! A configuração carrega segredos e o registro decide para onde o proxy
+//! encaminha `/api/v1`, então só serviços com token de
+//! `vms_common::service_auth` (segredo `VMS_SERVICE_SECRET`) os acessam.
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts, StatusCode},
+    Json,
+};
+use serde_json::{json, Value};
+use vms_common::service_auth::{verify_service_token, ServiceAuthError};
+
+use crate::AppState;
+
+type AuthRejection = (StatusCode, Json<Value>);
+
+fn rejection(status: StatusCode, message: &str) -> AuthRejection {
+    (status, Json(json!({ "error": message })))
+}
+
+/// Confere um token de serviço com o segredo do gateway
+pub fn verify(secret: Option<&str>, token: &str) -> Result<String, ServiceAuthError> {
+    let secret = secret.ok_or(ServiceAuthError::NotConfigured)?;
+    verify_service_token(secret.as_bytes(), token, chrono::Utc::now().timestamp())
+}
+
+/// Serviço autenticado por `Authorization: Bearer svc.<serviço>...`
+pub struct ServiceCaller(pub String);
+
+impl ServiceCaller {
+    /// O chamador é o próprio serviço (dono da seção ou da instância)
+    pub fn is(&self, service: &str) -> bool {
+        self.0 == service
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for ServiceCaller {
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim)
+            .ok_or_else(|| rejection(StatusCode::UNAUTHORIZED, "Missing service token"))?;
+
+        verify(state.service_secret.as_deref(), token)
+            .map(ServiceCaller)
+            .map_err(|e| match e {
+                ServiceAuthError::NotConfigured => rejection(StatusCode::SERVICE_UNAVAILABLE, &e.to_string()),
+                _ => rejection(StatusCode::UNAUTHORIZED, &e.to_string()),
+            })
+    }
+}
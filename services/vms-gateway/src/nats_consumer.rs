@@ -0,0 +1,115 @@
+//! Registro e configuração via NATS
+//! - heartbeats em `vms.registry.heartbeat` (com `instance_id` gerado pela
+//!   instância e token de serviço em `token`)
+//! - request/reply em `vms.config.get` (payload: seção ou nome do serviço)
+//! - `vms.config.updated.{seção}` publicado a cada reload
+//!
+//! Os subjects não são autenticados, então a configuração sai sem segredos:
+//! o dono da seção os obtém pelo HTTP com token de serviço.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use futures::StreamExt;
+use serde_json::json;
+use tracing::{info, warn};
+use vms_common::config::VmsConfig;
+use vms_common::registry::{
+    config_updated_subject, ServiceRegistration, CONFIG_REQUEST_SUBJECT, HEARTBEAT_SUBJECT,
+};
+
+use crate::auth;
+use crate::registry::ServiceRegistry;
+use crate::settings::{service_config, ConfigStore};
+
+/// Consome heartbeats e mantém o registro
+pub async fn consume_heartbeats(
+    client: async_nats::Client,
+    registry: Arc<ServiceRegistry>,
+    service_secret: Option<String>,
+) -> Result<()> {
+    let mut subscriber = client.subscribe(HEARTBEAT_SUBJECT).await?;
+    info!("📡 Subscribed to heartbeats: {}", HEARTBEAT_SUBJECT);
+
+    while let Some(message) = subscriber.next().await {
+        match serde_json::from_slice::<ServiceRegistration>(&message.payload) {
+            Ok(registration) if registration.instance_id.is_none() => {
+                warn!("⚠️  Heartbeat from {} without instance_id ignored", registration.service);
+            }
+            Ok(registration) => {
+                let token = registration.token.as_deref().unwrap_or_default();
+                match auth::verify(service_secret.as_deref(), token) {
+                    Ok(service) if service == registration.service => {}
+                    Ok(service) => {
+                        warn!("⚠️  Heartbeat for {} signed by {} ignored", registration.service, service);
+                        continue;
+                    }
+                    Err(e) => {
+                        warn!("⚠️  Heartbeat from {} ignored: {}", registration.service, e);
+                        continue;
+                    }
+                }
+
+                // Não toma o id de uma instância de outro serviço
+                if let Some(id) = registration.instance_id {
+                    if let Some(existing) = registry.get(id).await.filter(|i| i.service != registration.service) {
+                        warn!("⚠️  Heartbeat from {} for {} instance {} ignored", registration.service, existing.service, id);
+                        continue;
+                    }
+                }
+                registry.register(registration).await;
+            }
+            Err(e) => warn!("Failed to parse heartbeat: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Responde pedidos de configuração
+pub async fn serve_config_requests(client: async_nats::Client, store: Arc<ConfigStore>) -> Result<()> {
+    let mut subscriber = client.subscribe(CONFIG_REQUEST_SUBJECT).await?;
+    info!("📡 Serving configuration on {}", CONFIG_REQUEST_SUBJECT);
+
+    while let Some(message) = subscriber.next().await {
+        let Some(reply) = message.reply else {
+            continue;
+        };
+
+        let name = String::from_utf8_lossy(&message.payload).trim().to_string();
+        let config = store.current().await.redacted();
+        let payload = config_for(&config, &name);
+
+        if let Err(e) = client.publish(reply, payload.to_string().into()).await {
+            warn!("Failed to reply configuration request '{}': {}", name, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Publica as seções alteradas por um reload (sem segredos)
+pub async fn publish_sections(client: &async_nats::Client, config: &VmsConfig, sections: &[&str]) {
+    let config = config.redacted();
+    for section in sections {
+        let Some(value) = config.section(section) else {
+            continue;
+        };
+        let subject = config_updated_subject(section);
+        match client.publish(subject.clone(), value.to_string().into()).await {
+            Ok(()) => info!("📤 Published {}", subject),
+            Err(e) => warn!("Failed to publish {}: {}", subject, e),
+        }
+    }
+}
+
+/// Seção pelo nome ou configuração de um serviço (`vms-*`)
+fn config_for(config: &VmsConfig, name: &str) -> serde_json::Value {
+    if let Some(section) = config.section(name) {
+        return section;
+    }
+    if name.starts_with("vms-") {
+        return service_config(config, name);
+    }
+    json!({ "error": format!("Unknown configuration section: {}", name) })
+}
@@ -0,0 +1,185 @@
+//! Reverse proxy de `/api/v1`
+//! O serviço vem da tabela de rotas; a instância, do registro (round-robin)
+//! ou, sem instâncias registradas, da URL fixa em `gateway.upstreams`.
+
+use std::net::SocketAddr;
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Json,
+};
+use hyper_util::client::legacy::{connect::HttpConnector, Client};
+use hyper_util::rt::TokioExecutor;
+use serde_json::json;
+use thiserror::Error;
+use tracing::{debug, warn};
+
+use crate::AppState;
+
+pub type HttpClient = Client<HttpConnector, Body>;
+
+/// Headers que valem só para um salto (RFC 9110 §7.6.1)
+const HOP_BY_HOP: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+#[derive(Debug, Error)]
+pub enum ProxyError {
+    #[error("No instance available for {0}")]
+    NoUpstream(String),
+
+    #[error("Invalid upstream URL: {0}")]
+    InvalidUpstream(String),
+
+    #[error("{service} unavailable: {reason}")]
+    Upstream { service: String, reason: String },
+}
+
+impl IntoResponse for ProxyError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            ProxyError::NoUpstream(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ProxyError::InvalidUpstream(_) | ProxyError::Upstream { .. } => StatusCode::BAD_GATEWAY,
+        };
+        (status, Json(json!({ "error": self.to_string() }))).into_response()
+    }
+}
+
+pub fn client() -> HttpClient {
+    Client::builder(TokioExecutor::new()).build_http()
+}
+
+/// Encaminha a requisição para o serviço responsável pelo path
+pub async fn forward(
+    State(state): State<AppState>,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    mut req: Request,
+) -> Result<Response, ProxyError> {
+    let config = state.config.current().await;
+    let service = config.gateway.service_for(req.uri().path()).to_string();
+
+    let base = match state.registry.pick(&service).await {
+        Some(url) => url,
+        None => config
+            .gateway
+            .upstream(&service)
+            .map(str::to_string)
+            .ok_or_else(|| ProxyError::NoUpstream(service.clone()))?,
+    };
+
+    let path = req
+        .uri()
+        .path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or("/");
+    let uri = target_uri(&base, path)?;
+    debug!("➡️  {} {} → {}", req.method(), path, uri);
+
+    let original_host = req.headers().get(header::HOST).cloned();
+    *req.uri_mut() = uri;
+    let headers = req.headers_mut();
+    strip_hop_by_hop(headers);
+    headers.remove(header::HOST);
+    add_forwarded_headers(headers, peer.map(|ConnectInfo(addr)| addr), original_host);
+
+    let mut response = state.client.request(req).await.map_err(|e| {
+        warn!("⚠️  Proxy to {} ({}) failed: {}", service, base, e);
+        ProxyError::Upstream {
+            service: service.clone(),
+            reason: e.to_string(),
+        }
+    })?;
+
+    strip_hop_by_hop(response.headers_mut());
+    Ok(response.map(Body::new))
+}
+
+/// URL final: base da instância + path e query originais
+fn target_uri(base: &str, path_and_query: &str) -> Result<Uri, ProxyError> {
+    format!("{}{}", base.trim_end_matches('/'), path_and_query)
+        .parse()
+        .map_err(|_| ProxyError::InvalidUpstream(base.to_string()))
+}
+
+/// Remove os headers hop-by-hop, inclusive os listados em `Connection`
+fn strip_hop_by_hop(headers: &mut HeaderMap) {
+    let listed: Vec<HeaderName> = headers
+        .get_all(header::CONNECTION)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|name| HeaderName::from_bytes(name.trim().as_bytes()).ok())
+        .collect();
+
+    for name in listed {
+        headers.remove(name);
+    }
+    for name in HOP_BY_HOP {
+        headers.remove(*name);
+    }
+}
+
+fn add_forwarded_headers(headers: &mut HeaderMap, peer: Option<SocketAddr>, host: Option<HeaderValue>) {
+    if let Some(peer) = peer {
+        let forwarded_for = match headers.get("x-forwarded-for").and_then(|v| v.to_str().ok()) {
+            Some(previous) => format!("{}, {}", previous, peer.ip()),
+            None => peer.ip().to_string(),
+        };
+        if let Ok(value) = HeaderValue::from_str(&forwarded_for) {
+            headers.insert("x-forwarded-for", value);
+        }
+    }
+    if let Some(host) = host {
+        headers.insert("x-forwarded-host", host);
+    }
+    headers
+        .entry("x-forwarded-proto")
+        .or_insert(HeaderValue::from_static("http"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_uri_keeps_path_and_query() {
+        let uri = target_uri("http://10.0.0.1:9095/", "/api/v1/cameras?limit=10").unwrap();
+        assert_eq!(uri.to_string(), "http://10.0.0.1:9095/api/v1/cameras?limit=10");
+        assert!(target_uri("not a url", "/api/v1").is_err());
+    }
+
+    #[test]
+    fn test_strip_hop_by_hop_and_forwarded_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONNECTION, HeaderValue::from_static("keep-alive, x-trace"));
+        headers.insert("x-trace", HeaderValue::from_static("1"));
+        headers.insert(header::TRANSFER_ENCODING, HeaderValue::from_static("chunked"));
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer t"));
+        headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.9"));
+
+        strip_hop_by_hop(&mut headers);
+        add_forwarded_headers(
+            &mut headers,
+            Some("10.0.0.7:50000".parse().unwrap()),
+            Some(HeaderValue::from_static("vms.local")),
+        );
+
+        assert!(headers.get(header::CONNECTION).is_none());
+        assert!(headers.get("x-trace").is_none());
+        assert!(headers.get(header::TRANSFER_ENCODING).is_none());
+        assert_eq!(headers[header::AUTHORIZATION], "Bearer t");
+        assert_eq!(headers["x-forwarded-for"], "203.0.113.9, 10.0.0.7");
+        assert_eq!(headers["x-forwarded-host"], "vms.local");
+        assert_eq!(headers["x-forwarded-proto"], "http");
+    }
+}
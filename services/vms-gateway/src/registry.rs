@@ -0,0 +1,164 @@
+//! Registro de instâncias de serviço com heartbeats
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use tokio::sync::RwLock;
+use uuid::Uuid;
+use vms_common::registry::{ServiceInstance, ServiceRegistration};
+use vms_common::types::Timestamp;
+
+/// Instâncias vivas, indexadas por id
+#[derive(Default)]
+pub struct ServiceRegistry {
+    instances: RwLock<HashMap<Uuid, ServiceInstance>>,
+    /// Cursor do round-robin entre instâncias do mesmo serviço
+    cursor: AtomicUsize,
+}
+
+impl ServiceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra uma instância; com `instance_id` conhecido, atualiza e renova
+    pub async fn register(&self, registration: ServiceRegistration) -> ServiceInstance {
+        let mut instances = self.instances.write().await;
+
+        if let Some(existing) = registration
+            .instance_id
+            .and_then(|id| instances.get_mut(&id))
+        {
+            existing.service = registration.service;
+            existing.url = registration.url.trim_end_matches('/').to_string();
+            existing.version = registration.version;
+            existing.metadata = registration.metadata;
+            existing.last_heartbeat = Timestamp::now();
+            return existing.clone();
+        }
+
+        let instance = ServiceInstance::new(registration);
+        instances.insert(instance.id, instance.clone());
+        instance
+    }
+
+    pub async fn get(&self, id: Uuid) -> Option<ServiceInstance> {
+        self.instances.read().await.get(&id).cloned()
+    }
+
+    /// Renova o heartbeat; `None` se a instância não existe (ou expirou)
+    pub async fn heartbeat(&self, id: Uuid) -> Option<ServiceInstance> {
+        let mut instances = self.instances.write().await;
+        let instance = instances.get_mut(&id)?;
+        instance.last_heartbeat = Timestamp::now();
+        Some(instance.clone())
+    }
+
+    pub async fn deregister(&self, id: Uuid) -> Option<ServiceInstance> {
+        self.instances.write().await.remove(&id)
+    }
+
+    /// Todas as instâncias, ordenadas por serviço e data de registro
+    pub async fn list(&self) -> Vec<ServiceInstance> {
+        let mut list: Vec<_> = self.instances.read().await.values().cloned().collect();
+        list.sort_by(|a, b| {
+            a.service
+                .cmp(&b.service)
+                .then(a.registered_at.0.cmp(&b.registered_at.0))
+        });
+        list
+    }
+
+    pub async fn instances_of(&self, service: &str) -> Vec<ServiceInstance> {
+        let mut list = self.list().await;
+        list.retain(|instance| instance.service == service);
+        list
+    }
+
+    /// URL de uma instância do serviço (round-robin)
+    pub async fn pick(&self, service: &str) -> Option<String> {
+        let instances = self.instances_of(service).await;
+        if instances.is_empty() {
+            return None;
+        }
+        let index = self.cursor.fetch_add(1, Ordering::Relaxed) % instances.len();
+        Some(instances[index].url.clone())
+    }
+
+    /// Remove as instâncias sem heartbeat há mais de `ttl`
+    pub async fn expire(&self, ttl: Duration) -> Vec<ServiceInstance> {
+        let ttl = ttl.as_secs() as i64;
+        let mut instances = self.instances.write().await;
+        let expired: Vec<Uuid> = instances
+            .values()
+            .filter(|instance| instance.heartbeat_age_secs() > ttl)
+            .map(|instance| instance.id)
+            .collect();
+
+        expired
+            .iter()
+            .filter_map(|id| instances.remove(id))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registration(service: &str, url: &str) -> ServiceRegistration {
+        ServiceRegistration {
+            instance_id: None,
+            service: service.to_string(),
+            url: url.to_string(),
+            version: "0.1.0".to_string(),
+            metadata: HashMap::new(),
+            token: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_heartbeat_and_round_robin() {
+        let registry = ServiceRegistry::new();
+        let a = registry.register(registration("vms-api", "http://10.0.0.1:9095/")).await;
+        let b = registry.register(registration("vms-api", "http://10.0.0.2:9095")).await;
+        registry.register(registration("vms-events", "http://10.0.0.3:9096")).await;
+        assert_eq!(a.url, "http://10.0.0.1:9095");
+
+        // Re-registro com id conhecido atualiza a mesma instância
+        let mut again = registration("vms-api", "http://10.0.0.9:9095");
+        again.instance_id = Some(b.id);
+        assert_eq!(registry.register(again).await.id, b.id);
+        assert_eq!(registry.instances_of("vms-api").await.len(), 2);
+
+        let first = registry.pick("vms-api").await.unwrap();
+        let second = registry.pick("vms-api").await.unwrap();
+        assert_ne!(first, second);
+        assert!(registry.pick("vms-lpr").await.is_none());
+
+        assert!(registry.heartbeat(a.id).await.is_some());
+        assert!(registry.deregister(a.id).await.is_some());
+        assert!(registry.heartbeat(a.id).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expire_stale_instances() {
+        let registry = ServiceRegistry::new();
+        let stale = registry.register(registration("vms-storage", "http://10.0.0.4:9092")).await;
+        let live = registry.register(registration("vms-storage", "http://10.0.0.5:9092")).await;
+
+        registry
+            .instances
+            .write()
+            .await
+            .get_mut(&stale.id)
+            .unwrap()
+            .last_heartbeat = Timestamp::from_datetime(chrono::Utc::now() - chrono::Duration::seconds(60));
+
+        let expired = registry.expire(Duration::from_secs(30)).await;
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].id, stale.id);
+        assert_eq!(registry.list().await[0].id, live.id);
+    }
+}
@@ -0,0 +1,206 @@
+//! Carregamento e distribuição da configuração central
+//!
+//! Precedência: padrões de `VmsConfig` ← arquivo TOML ← variáveis
+//! `VMS_<SEÇÃO>__<CAMPO>` ← variáveis legadas (`NATS_URL`, `STORAGE_PATH`).
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use config::{Config, Environment, File, FileFormat};
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+use vms_common::config::VmsConfig;
+
+/// Variáveis de ambiente usadas pelos serviços antes do gateway
+const LEGACY_ENV: &[(&str, &str)] = &[
+    ("NATS_URL", "nats.url"),
+    ("STORAGE_PATH", "storage.base_path"),
+    ("INGEST_MAX_CAMERAS", "ingest.max_cameras"),
+];
+
+/// Carrega a configuração com as variáveis do processo
+pub fn load(path: &Path) -> Result<VmsConfig> {
+    load_with_env(path, std::env::vars().collect())
+}
+
+/// Carrega a configuração com um conjunto explícito de variáveis
+pub fn load_with_env(path: &Path, env: HashMap<String, String>) -> Result<VmsConfig> {
+    // Padrões como fonte (e não só `serde(default)`) para que uma variável
+    // como `VMS_GATEWAY__UPSTREAMS__VMS_API` mescle com o mapa em vez de substituí-lo
+    let mut builder = Config::builder()
+        .add_source(Config::try_from(&VmsConfig::default())?)
+        .add_source(File::from(path).format(FileFormat::Toml).required(false))
+        .add_source(
+            Environment::with_prefix("VMS")
+                .prefix_separator("_")
+                .separator("__")
+                .try_parsing(true)
+                .source(Some(env.clone())),
+        );
+
+    for (var, key) in LEGACY_ENV {
+        if let Some(value) = env.get(*var) {
+            builder = builder.set_override(*key, value.as_str())?;
+        }
+    }
+
+    let mut config: VmsConfig = builder
+        .build()
+        .and_then(|config| config.try_deserialize())
+        .with_context(|| format!("Failed to load configuration from {}", path.display()))?;
+
+    // Variáveis de ambiente não aceitam hífen: `VMS_API` vale para `vms-api`
+    // (a chave com `_` vem depois na ordenação e prevalece)
+    config.gateway.upstreams = std::mem::take(&mut config.gateway.upstreams)
+        .into_iter()
+        .map(|(service, url)| (service.replace('_', "-"), url))
+        .collect();
+
+    Ok(config)
+}
+
+/// Seção própria de cada serviço
+pub fn section_for_service(service: &str) -> Option<&'static str> {
+    match service {
+        "vms-ingest" => Some("ingest"),
+        "vms-storage" => Some("storage"),
+        "vms-stream" => Some("streaming"),
+        "vms-ai" => Some("ai"),
+        "vms-gateway" => Some("gateway"),
+        _ => None,
+    }
+}
+
+/// Configuração entregue a um serviço: a sua seção mais NATS e observabilidade
+pub fn service_config(config: &VmsConfig, service: &str) -> Value {
+    let mut value = json!({
+        "nats": config.section("nats"),
+        "observability": config.section("observability"),
+    });
+    if let Some(section) = section_for_service(service) {
+        value[section] = config.section(section).unwrap_or(Value::Null);
+    }
+    value
+}
+
+/// Configuração corrente, recarregável em tempo de execução
+pub struct ConfigStore {
+    path: PathBuf,
+    current: RwLock<Arc<VmsConfig>>,
+}
+
+impl ConfigStore {
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let config = load(&path)?;
+        Ok(Self::new(path, config))
+    }
+
+    pub fn new(path: PathBuf, config: VmsConfig) -> Self {
+        Self {
+            path,
+            current: RwLock::new(Arc::new(config)),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub async fn current(&self) -> Arc<VmsConfig> {
+        self.current.read().await.clone()
+    }
+
+    /// Relê o arquivo e retorna as seções que mudaram
+    pub async fn reload(&self) -> Result<Vec<&'static str>> {
+        let config = load(&self.path)?;
+        Ok(self.replace(config).await)
+    }
+
+    /// Substitui a configuração e retorna as seções que mudaram
+    pub async fn replace(&self, config: VmsConfig) -> Vec<&'static str> {
+        let mut current = self.current.write().await;
+        let changed = VmsConfig::SECTIONS
+            .iter()
+            .copied()
+            .filter(|section| current.section(section) != config.section(section))
+            .collect();
+        *current = Arc::new(config);
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_toml_with_env_overrides() {
+        let path = std::env::temp_dir().join(format!("vms-gateway-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            "[storage]\nretention_days = 7\n\n[gateway]\nport = 8181\n\n[[cameras]]\nname = \"ignored\"\n",
+        )
+        .unwrap();
+
+        let config = load_with_env(
+            &path,
+            env(&[
+                ("VMS_STREAMING__MAX_VIEWERS", "50"),
+                ("VMS_GATEWAY__UPSTREAMS__VMS_API", "http://api:9095"),
+                ("STORAGE_PATH", "/data/vms"),
+                ("VMS_API_URL", "http://localhost:9095"),
+            ]),
+        )
+        .unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(config.storage.retention_days, 7);
+        assert_eq!(config.storage.base_path, PathBuf::from("/data/vms"));
+        assert_eq!(config.streaming.max_viewers, 50);
+        assert_eq!(config.gateway.port, 8181);
+        assert_eq!(config.gateway.upstream("vms-api"), Some("http://api:9095"));
+        assert_eq!(config.gateway.upstream("vms-events"), Some("http://localhost:9096"));
+        assert_eq!(config.nats.url, "nats://localhost:4222");
+    }
+
+    #[test]
+    fn test_missing_file_uses_defaults() {
+        let config = load_with_env(Path::new("/nonexistent/vms.toml"), HashMap::new()).unwrap();
+        assert_eq!(config.ingest.max_cameras, 100);
+        assert_eq!(config.gateway.default_service, "vms-api");
+    }
+
+    #[test]
+    fn test_example_config_loads() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../config.example.toml");
+        let config = load_with_env(&path, HashMap::new()).unwrap();
+        assert_eq!(config.gateway.port, 8080);
+        assert_eq!(config.gateway.upstream("vms-face"), Some("http://localhost:9101"));
+        assert_eq!(config.gateway.service_for("/api/v1/zones"), "vms-analytics");
+    }
+
+    #[tokio::test]
+    async fn test_replace_reports_changed_sections() {
+        let store = ConfigStore::new(PathBuf::from("config.toml"), VmsConfig::default());
+
+        let mut config = VmsConfig::default();
+        config.ai.enabled = true;
+        config.nats.url = "nats://nats:4222".to_string();
+        assert_eq!(store.replace(config.clone()).await, vec!["ai", "nats"]);
+        assert!(store.replace(config).await.is_empty());
+
+        let config = store.current().await;
+        let value = service_config(&config, "vms-ai");
+        assert_eq!(value["ai"]["enabled"], true);
+        assert_eq!(value["nats"]["url"], "nats://nats:4222");
+        assert!(service_config(&config, "vms-lpr").get("ai").is_none());
+    }
+}
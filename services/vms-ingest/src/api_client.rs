@@ -5,12 +5,19 @@ use anyhow::Result;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
+use vms_common::camera::{CameraConfig, CameraStream};
+use vms_common::media_profile::MediaProfileUsage;
+use vms_common::telemetry::CameraStreamStats;
+use vms_common::types::{CameraId, Resolution};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiCamera {
     pub id: String,
     pub name: String,
     pub rtsp_url: String,
+    /// Sub stream (baixa resolução) para analytics e mobile
+    #[serde(default)]
+    pub sub_rtsp_url: Option<String>,
     #[serde(default)]
     pub onvif_url: Option<String>,
     #[serde(default)]
@@ -27,12 +34,72 @@ pub struct ApiCamera {
     pub codec: String,
     #[serde(default = "default_true")]
     pub enabled: bool,
+    #[serde(default)]
+    pub tamper_detection_enabled: bool,
+    #[serde(default = "default_tamper_sensitivity")]
+    pub tamper_sensitivity: f32,
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_tamper_sensitivity() -> f32 {
+    0.5
+}
+
+impl ApiCamera {
+    /// Converte para configuração de ingestão
+    ///
+    /// Com sub stream: main → gravação/live view, sub → analytics/mobile.
+    /// Sem sub stream: o main atende todos os usos.
+    pub fn to_camera_config(&self) -> CameraConfig {
+        let mut config = CameraConfig::new(self.name.clone(), self.rtsp_url.clone())
+            .with_credentials(self.username.clone(), self.password.clone());
+
+        if let Ok(uuid) = self.id.parse::<uuid::Uuid>() {
+            config.id = CameraId::from_uuid(uuid);
+        }
+        config.tamper_detection.enabled = self.tamper_detection_enabled;
+        config.tamper_detection.sensitivity = self.tamper_sensitivity;
+        if self.resolution_width > 0 && self.resolution_height > 0 {
+            config = config.with_resolution(Resolution::new(self.resolution_width, self.resolution_height));
+        }
+
+        if let Some(sub_url) = self.sub_rtsp_url.as_deref().filter(|u| !u.is_empty()) {
+            let mut main = CameraStream::new(
+                "main",
+                self.rtsp_url.clone(),
+                vec![MediaProfileUsage::Recording, MediaProfileUsage::LiveView],
+            );
+            main.resolution = Some(config.resolution);
+
+            config = config.with_stream(main).with_stream(CameraStream::new(
+                "sub",
+                sub_url,
+                vec![MediaProfileUsage::Analytics, MediaProfileUsage::Mobile],
+            ));
+        }
+
+        config
+    }
+}
+
+/// Registro deste nó de ingestão na API
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeRegistration {
+    pub name: String,
+    pub ip: String,
+    pub port: u16,
+    pub max_cameras: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NodeRegistrationResponse {
+    pub server_id: uuid::Uuid,
+    pub heartbeat_interval_secs: u64,
+}
+
 pub struct ApiClient {
     client: Client,
     base_url: String,
@@ -77,4 +144,61 @@ impl ApiClient {
         info!("Found {} enabled cameras", enabled.len());
         Ok(enabled)
     }
+
+    /// Registra este nó; a API passa a atribuir câmeras a ele
+    pub async fn register_node(&self, node: &NodeRegistration) -> Result<NodeRegistrationResponse> {
+        let url = format!("{}/api/v1/servers/register", self.base_url);
+        let response = self.client.post(&url).json(node).send().await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("API returned error: {}", response.status());
+        }
+
+        let registration: NodeRegistrationResponse = response.json().await?;
+        info!("Registered node {} as {}", node.name, registration.server_id);
+
+        Ok(registration)
+    }
+
+    /// Envia heartbeat; retorna false se a API não conhece mais o nó
+    pub async fn heartbeat(&self, server_id: uuid::Uuid) -> Result<bool> {
+        let url = format!("{}/api/v1/servers/{}/heartbeat", self.base_url, server_id);
+        let response = self.client.post(&url).send().await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        if !response.status().is_success() {
+            anyhow::bail!("API returned error: {}", response.status());
+        }
+
+        Ok(true)
+    }
+
+    /// Busca câmeras habilitadas atribuídas a este nó
+    pub async fn get_assigned_cameras(&self, server_id: uuid::Uuid) -> Result<Vec<ApiCamera>> {
+        let url = format!("{}/api/v1/servers/{}/cameras", self.base_url, server_id);
+        debug!("Fetching assigned cameras from: {}", url);
+
+        let response = self.client.get(&url).send().await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("API returned error: {}", response.status());
+        }
+
+        let cameras: Vec<ApiCamera> = response.json().await?;
+        Ok(cameras.into_iter().filter(|c| c.enabled).collect())
+    }
+
+    /// Envia telemetria dos streams deste nó
+    pub async fn report_stats(&self, server_id: uuid::Uuid, stats: &[CameraStreamStats]) -> Result<()> {
+        let url = format!("{}/api/v1/servers/{}/stats", self.base_url, server_id);
+        let response = self.client.post(&url).json(stats).send().await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("API returned error: {}", response.status());
+        }
+
+        Ok(())
+    }
 }
@@ -1,35 +1,62 @@
 //! Gerenciador de múltiplas câmeras
 
+use crate::nats_publisher::NatsPublisher;
 use crate::pipeline::IngestPipeline;
+use crate::tamper::run_tamper_detection;
 use anyhow::{Context, Result};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, RwLock};
 use tracing::{error, info, warn};
-use vms_common::camera::{CameraConfig, CameraStatus};
+use vms_common::camera::{CameraConfig, CameraStatus, CameraStream};
+use vms_common::telemetry::CameraStreamStats;
 use vms_common::types::CameraId;
 
+/// Tamanho do buffer de frames entre appsink e publisher
+const FRAME_BUFFER_SIZE: usize = 100;
+
+/// Buffer de frames de análise de sabotagem (descarta se o analisador atrasar)
+const TAMPER_BUFFER_SIZE: usize = 4;
+
 /// Gerenciador de câmeras
 pub struct CameraManager {
     cameras: Arc<RwLock<HashMap<CameraId, CameraInstance>>>,
     max_cameras: usize,
+    publisher: Option<Arc<NatsPublisher>>,
 }
 
 struct CameraInstance {
     config: CameraConfig,
-    pipeline: Option<IngestPipeline>,
+    /// Um pipeline por stream (main, sub, ...)
+    pipelines: Vec<IngestPipeline>,
     status: CameraStatus,
     retry_count: u32,
 }
 
+impl CameraInstance {
+    fn stop_pipelines(&mut self) -> Result<()> {
+        for pipeline in self.pipelines.drain(..) {
+            pipeline.stop()?;
+        }
+        Ok(())
+    }
+}
+
 impl CameraManager {
     pub fn new(max_cameras: usize) -> Self {
         Self {
             cameras: Arc::new(RwLock::new(HashMap::new())),
             max_cameras,
+            publisher: None,
         }
     }
 
+    /// Publica os frames de cada stream no NATS
+    pub fn with_publisher(mut self, publisher: Arc<NatsPublisher>) -> Self {
+        self.publisher = Some(publisher);
+        self
+    }
+
     /// Adiciona uma câmera
     pub async fn add_camera(&self, config: CameraConfig) -> Result<()> {
         let mut cameras = self.cameras.write().await;
@@ -45,7 +72,7 @@ impl CameraManager {
             camera_id,
             CameraInstance {
                 config,
-                pipeline: None,
+                pipelines: Vec::new(),
                 status: CameraStatus::Offline,
                 retry_count: 0,
             },
@@ -59,9 +86,7 @@ impl CameraManager {
         let mut cameras = self.cameras.write().await;
 
         if let Some(mut instance) = cameras.remove(&camera_id) {
-            if let Some(pipeline) = instance.pipeline.take() {
-                pipeline.stop()?;
-            }
+            instance.stop_pipelines()?;
             info!("Removed camera: {}", camera_id);
         }
 
@@ -78,13 +103,17 @@ impl CameraManager {
 
         instance.status = CameraStatus::Connecting;
 
-        match IngestPipeline::new(instance.config.clone()) {
-            Ok(pipeline) => {
-                pipeline.start()?;
-                instance.pipeline = Some(pipeline);
+        match self.open_streams(&instance.config).await {
+            Ok(pipelines) => {
+                instance.pipelines = pipelines;
                 instance.status = CameraStatus::Online;
                 instance.retry_count = 0;
-                info!("Started camera: {} ({})", instance.config.name, camera_id);
+                info!(
+                    "Started camera: {} ({}) with {} stream(s)",
+                    instance.config.name,
+                    camera_id,
+                    instance.pipelines.len()
+                );
                 Ok(())
             }
             Err(e) => {
@@ -96,6 +125,67 @@ impl CameraManager {
         }
     }
 
+    /// Abre um pipeline por stream da câmera e liga cada um ao publisher
+    ///
+    /// Câmera com stream sem URL (só ONVIF, resolução ainda em backoff) não
+    /// é aberta; a sincronização a reinicia quando a URL for resolvida.
+    async fn open_streams(&self, config: &CameraConfig) -> Result<Vec<IngestPipeline>> {
+        let streams = config.effective_streams();
+        if let Some(stream) = streams.iter().find(|s| s.url.trim().is_empty()) {
+            anyhow::bail!("Stream '{}' has no RTSP URL (ONVIF resolution pending)", stream.name);
+        }
+
+        let mut pipelines = Vec::new();
+        for stream in streams {
+            match self.open_stream(config, stream).await {
+                Ok(pipeline) => pipelines.push(pipeline),
+                Err(e) => {
+                    // Câmera abre inteira ou não abre: para os streams já
+                    // iniciados; descartá-los encerra as tarefas de sabotagem
+                    for pipeline in pipelines {
+                        if let Err(stop_error) = pipeline.stop() {
+                            warn!(
+                                "Failed to stop stream '{}' of {}: {}",
+                                pipeline.stream().name,
+                                config.name,
+                                stop_error
+                            );
+                        }
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(pipelines)
+    }
+
+    /// Abre e inicia o pipeline de um stream
+    async fn open_stream(&self, config: &CameraConfig, stream: CameraStream) -> Result<IngestPipeline> {
+        let usages = stream.usages.clone();
+        let mut pipeline = IngestPipeline::new(config.clone(), stream)?;
+
+        if let Some(publisher) = &self.publisher {
+            let (tx, rx) = mpsc::channel(FRAME_BUFFER_SIZE);
+            pipeline.set_frame_sender(tx);
+            publisher.start_publishing(rx, config.id, usages).await?;
+
+            let (tamper_tx, tamper_rx) = mpsc::channel(TAMPER_BUFFER_SIZE);
+            if pipeline.set_tamper_sender(tamper_tx) {
+                tokio::spawn(run_tamper_detection(
+                    config.id,
+                    config.name.clone(),
+                    config.tamper_detection.clone(),
+                    tamper_rx,
+                    publisher.clone(),
+                ));
+            }
+        }
+
+        pipeline.start()?;
+        Ok(pipeline)
+    }
+
     /// Para uma câmera
     pub async fn stop_camera(&self, camera_id: CameraId) -> Result<()> {
         let mut cameras = self.cameras.write().await;
@@ -104,8 +194,8 @@ impl CameraManager {
             .get_mut(&camera_id)
             .context("Camera not found")?;
 
-        if let Some(pipeline) = instance.pipeline.take() {
-            pipeline.stop()?;
+        if !instance.pipelines.is_empty() {
+            instance.stop_pipelines()?;
             instance.status = CameraStatus::Offline;
             info!("Stopped camera: {}", camera_id);
         }
@@ -153,17 +243,67 @@ impl CameraManager {
         Ok(())
     }
 
+    /// IDs das câmeras gerenciadas por este nó
+    pub async fn camera_ids(&self) -> Vec<CameraId> {
+        self.cameras.read().await.keys().copied().collect()
+    }
+
+    /// Sincroniza com as câmeras atribuídas a este nó pela API
+    ///
+    /// Câmeras novas são adicionadas e iniciadas; câmeras que saíram da
+    /// atribuição (movidas para outro nó ou removidas) são paradas; câmeras
+    /// cuja origem, credenciais, streams ou sabotagem mudaram são reiniciadas.
+    pub async fn sync_assigned(&self, assigned: Vec<CameraConfig>) {
+        let current = self.camera_ids().await;
+
+        for camera_id in current.iter().filter(|id| !assigned.iter().any(|c| c.id == **id)) {
+            info!("Camera {} no longer assigned to this node", camera_id);
+            if let Err(e) = self.remove_camera(*camera_id).await {
+                warn!("Failed to remove camera {}: {}", camera_id, e);
+            }
+        }
+
+        for config in assigned {
+            let camera_id = config.id;
+            let changed = match self.cameras.read().await.get(&camera_id) {
+                Some(instance) if !config.ingest_changed(&instance.config) => continue,
+                Some(_) => true,
+                None => false,
+            };
+
+            if changed {
+                info!("Camera {} configuration changed, restarting", camera_id);
+                if let Err(e) = self.remove_camera(camera_id).await {
+                    warn!("Failed to stop camera {}: {}", camera_id, e);
+                    continue;
+                }
+            } else {
+                info!("Camera {} assigned to this node", camera_id);
+            }
+
+            if let Err(e) = self.add_camera(config).await {
+                warn!("Failed to add camera {}: {}", camera_id, e);
+                continue;
+            }
+            if let Err(e) = self.start_camera(camera_id).await {
+                warn!("Failed to start camera {}: {}", camera_id, e);
+            }
+        }
+    }
+
     /// Health check de todas as câmeras
     pub async fn health_check(&self) {
         let mut cameras = self.cameras.write().await;
 
         for (camera_id, instance) in cameras.iter_mut() {
-            if let Some(pipeline) = &instance.pipeline {
-                if !pipeline.is_running() {
-                    warn!("Camera {} is not running, will retry", camera_id);
-                    instance.status = CameraStatus::Error;
-                    instance.retry_count += 1;
-                }
+            if let Some(pipeline) = instance.pipelines.iter().find(|p| !p.is_running()) {
+                warn!(
+                    "Camera {} stream '{}' is not running, will retry",
+                    camera_id,
+                    pipeline.stream().name
+                );
+                instance.status = CameraStatus::Error;
+                instance.retry_count += 1;
             }
         }
     }
@@ -189,6 +329,15 @@ impl CameraManager {
         }
     }
 
+    /// Telemetria de todos os streams de todas as câmeras
+    pub async fn stream_stats(&self) -> Vec<CameraStreamStats> {
+        let cameras = self.cameras.read().await;
+        cameras
+            .values()
+            .flat_map(|instance| instance.pipelines.iter().map(|p| p.stats()))
+            .collect()
+    }
+
     /// Retorna status de todas as câmeras
     pub async fn get_all_status(&self) -> Vec<(CameraId, CameraStatus)> {
         let cameras = self.cameras.read().await;
@@ -215,4 +364,16 @@ mod tests {
         manager.add_camera(config).await.unwrap();
         assert_eq!(manager.cameras.read().await.len(), 1);
     }
+
+    #[tokio::test]
+    async fn test_unresolved_stream_is_not_opened() {
+        let manager = CameraManager::new(10);
+        let config = CameraConfig::new("ONVIF Camera".to_string(), String::new());
+        let camera_id = config.id;
+
+        manager.add_camera(config).await.unwrap();
+        let error = manager.start_camera(camera_id).await.unwrap_err();
+        assert!(error.to_string().contains("no RTSP URL"), "{}", error);
+        assert_eq!(manager.get_all_status().await, vec![(camera_id, CameraStatus::Error)]);
+    }
 }
@@ -4,21 +4,31 @@
 
 use anyhow::{Context, Result};
 use std::sync::Arc;
-use tracing::info;
+use tracing::{info, warn};
 use tracing_subscriber;
-use vms_common::camera::CameraConfig;
+use vms_common::gateway_client::{join_gateway, nats_url, public_url};
 
 mod camera_manager;
 mod metrics;
 mod nats_publisher;
 mod onvif;
+mod onvif_events;
 mod pipeline;
+mod tamper;
 mod api_client;
 
 use camera_manager::CameraManager;
 use metrics::IngestMetrics;
 use nats_publisher::NatsPublisher;
-use api_client::ApiClient;
+use api_client::{ApiClient, NodeRegistration};
+use onvif::OnvifStreamResolver;
+use onvif_events::OnvifEventListener;
+
+/// Nome do serviço no registro do vms-gateway
+const SERVICE_NAME: &str = "vms-ingest";
+
+/// Porta do endpoint de métricas (única porta HTTP do nó)
+const METRICS_PORT: u16 = 9091;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -36,8 +46,12 @@ async fn main() -> Result<()> {
     gstreamer::init()?;
     info!("✅ GStreamer initialized");
 
+    // Registro no vms-gateway: seção `ingest` e NATS da configuração
+    // central; NATS_URL e INGEST_MAX_CAMERAS têm precedência
+    let central = join_gateway(SERVICE_NAME, public_url(METRICS_PORT)).await;
+
     // Conectar ao NATS
-    let nats_url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://localhost:4222".to_string());
+    let nats_url = nats_url(&central);
     let nats_publisher = Arc::new(
         NatsPublisher::connect(&nats_url)
             .await
@@ -46,29 +60,113 @@ async fn main() -> Result<()> {
     info!("📡 NATS connected: {}", nats_url);
 
     // Criar gerenciador de câmeras
-    let manager = Arc::new(CameraManager::new(100));
+    let max_cameras: usize = std::env::var("INGEST_MAX_CAMERAS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .or_else(|| central["ingest"]["max_cameras"].as_u64().map(|max| max as usize))
+        .unwrap_or(100);
+    let manager = Arc::new(CameraManager::new(max_cameras).with_publisher(nats_publisher.clone()));
     let metrics = Arc::new(IngestMetrics::new());
 
-    // Buscar câmeras da API
+    // Registrar nó na API (sharding de câmeras entre nós)
     let api_url = std::env::var("VMS_API_URL").unwrap_or_else(|_| "http://localhost:9095".to_string());
-    let api_client = ApiClient::new(api_url);
-    
+    let api_client = Arc::new(ApiClient::new(api_url));
+    let stream_resolver = Arc::new(OnvifStreamResolver::new());
+    let event_listener = Arc::new(OnvifEventListener::new(nats_publisher.clone()));
+    let node = NodeRegistration {
+        name: std::env::var("INGEST_NODE_NAME").unwrap_or_else(|_| {
+            format!("vms-ingest-{}", std::env::var("HOSTNAME").unwrap_or_else(|_| "local".to_string()))
+        }),
+        ip: std::env::var("INGEST_NODE_IP").unwrap_or_else(|_| "127.0.0.1".to_string()),
+        port: std::env::var("INGEST_STREAM_PORT")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(9094),
+        max_cameras: max_cameras as u32,
+    };
+
+    info!("🛰️ Registering node {} with vms-api...", node.name);
+    let registration = match api_client.register_node(&node).await {
+        Ok(registration) => Some(registration),
+        Err(e) => {
+            info!("⚠️  Could not register node: {}", e);
+            None
+        }
+    };
+
+    // Heartbeat: tarefa própria, iniciada antes da carga das câmeras, para
+    // que a resolução ONVIF e a sincronização nunca atrasem o nó a ponto
+    // de expirar na API
+    let interval_secs = registration
+        .as_ref()
+        .map(|r| r.heartbeat_interval_secs)
+        .unwrap_or(10);
+    let (server_tx, mut server_rx) = tokio::sync::watch::channel(registration.as_ref().map(|r| r.server_id));
+    let manager_clone = manager.clone();
+    let api_clone = api_client.clone();
+    tokio::spawn(async move {
+        let mut server_id = *server_tx.borrow();
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_secs));
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            let id = match server_id {
+                Some(id) => id,
+                None => match api_clone.register_node(&node).await {
+                    Ok(registration) => {
+                        server_id = Some(registration.server_id);
+                        server_tx.send_replace(server_id);
+                        registration.server_id
+                    }
+                    Err(e) => {
+                        warn!("Node registration failed: {}", e);
+                        continue;
+                    }
+                },
+            };
+
+            match api_clone.heartbeat(id).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    // Nó removido na API: registrar novamente no próximo ciclo
+                    warn!("Node {} unknown to vms-api, re-registering", id);
+                    server_id = None;
+                    server_tx.send_replace(None);
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Heartbeat failed: {}", e);
+                    continue;
+                }
+            }
+
+            // Telemetria dos streams para /api/v1/cameras/:id/stats
+            let stats = manager_clone.stream_stats().await;
+            if let Err(e) = api_clone.report_stats(id, &stats).await {
+                warn!("Could not report stream stats: {}", e);
+            }
+        }
+    });
+
     info!("📡 Fetching cameras from vms-api...");
-    match api_client.get_enabled_cameras().await {
-        Ok(api_cameras) => {
-            info!("✅ Found {} enabled cameras", api_cameras.len());
+    let cameras = match &registration {
+        Some(registration) => api_client.get_assigned_cameras(registration.server_id).await,
+        // Sem registro: modo nó único, ingere todas as câmeras habilitadas
+        None => api_client.get_enabled_cameras().await,
+    };
+    match cameras {
+        Ok(mut api_cameras) => {
+            info!("✅ Found {} cameras for this node", api_cameras.len());
+            stream_resolver.resolve(&mut api_cameras).await;
+            event_listener.sync(&api_cameras);
             
             for api_camera in api_cameras {
                 info!("📹 Adding camera: {}", api_camera.name);
                 
-                // Converter ApiCamera para CameraConfig with credentials
-                let camera_config = CameraConfig::new(
-                    api_camera.name.clone(),
-                    api_camera.rtsp_url.clone(),
-                ).with_credentials(
-                    api_camera.username.clone(),
-                    api_camera.password.clone(),
-                );
+                // Converter ApiCamera para CameraConfig (main + sub streams)
+                let camera_config = api_camera.to_camera_config();
                 
                 if let Err(e) = manager.add_camera(camera_config).await {
                     info!("⚠️  Failed to add camera {}: {}", api_camera.name, e);
@@ -85,6 +183,36 @@ async fn main() -> Result<()> {
     info!("▶️  Starting all cameras...");
     manager.start_all().await?;
 
+    // Sincronização das câmeras atribuídas (com resolução ONVIF, que pode
+    // esperar câmeras fora do ar)
+    let manager_clone = manager.clone();
+    let api_clone = api_client.clone();
+    let resolver_clone = stream_resolver.clone();
+    let events_clone = event_listener.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_secs));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            let Some(id) = *server_rx.borrow_and_update() else {
+                continue;
+            };
+
+            match api_clone.get_assigned_cameras(id).await {
+                Ok(mut api_cameras) => {
+                    resolver_clone.resolve(&mut api_cameras).await;
+                    events_clone.sync(&api_cameras);
+                    let configs = api_cameras.iter().map(|c| c.to_camera_config()).collect();
+                    manager_clone.sync_assigned(configs).await;
+                }
+                Err(e) => warn!("Could not fetch assigned cameras: {}", e),
+            }
+        }
+    });
+
     // Health check task
     let manager_clone = manager.clone();
     tokio::spawn(async move {
@@ -95,6 +223,17 @@ async fn main() -> Result<()> {
         }
     });
 
+    // Telemetria por câmera para métricas Prometheus
+    let manager_clone = manager.clone();
+    let metrics_clone = metrics.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
+        loop {
+            interval.tick().await;
+            metrics_clone.set_stream_stats(manager_clone.stream_stats().await);
+        }
+    });
+
     // Auto-reconnect task
     let manager_clone = manager.clone();
     let metrics_clone = metrics.clone();
@@ -114,7 +253,7 @@ async fn main() -> Result<()> {
         use tokio::io::AsyncWriteExt;
         use tokio::net::TcpListener;
 
-        let addr = SocketAddr::from(([0, 0, 0, 0], 9091));
+        let addr = SocketAddr::from(([0, 0, 0, 0], METRICS_PORT));
         let listener = TcpListener::bind(addr).await.unwrap();
         info!("📊 Metrics server listening on http://{}/metrics", addr);
 
@@ -1,7 +1,9 @@
 //! Métricas Prometheus para vms-ingest
 
+use std::fmt::Write;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+use vms_common::telemetry::CameraStreamStats;
 
 /// Métricas do serviço de ingestão
 #[derive(Clone)]
@@ -12,6 +14,8 @@ pub struct IngestMetrics {
     pub total_frames: Arc<AtomicU64>,
     pub total_bytes: Arc<AtomicU64>,
     pub reconnect_attempts: Arc<AtomicU64>,
+    /// Último snapshot de telemetria por stream de câmera
+    pub stream_stats: Arc<RwLock<Vec<CameraStreamStats>>>,
 }
 
 impl IngestMetrics {
@@ -23,6 +27,7 @@ impl IngestMetrics {
             total_frames: Arc::new(AtomicU64::new(0)),
             total_bytes: Arc::new(AtomicU64::new(0)),
             reconnect_attempts: Arc::new(AtomicU64::new(0)),
+            stream_stats: Arc::new(RwLock::new(Vec::new())),
         }
     }
 
@@ -46,8 +51,28 @@ impl IngestMetrics {
         self.reconnect_attempts.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Substitui o snapshot de telemetria dos streams
+    pub fn set_stream_stats(&self, stats: Vec<CameraStreamStats>) {
+        let total_frames = stats.iter().map(|s| s.total_frames).sum();
+        let total_bytes = stats.iter().map(|s| s.total_bytes).sum();
+        self.total_frames.store(total_frames, Ordering::Relaxed);
+        self.total_bytes.store(total_bytes, Ordering::Relaxed);
+
+        if let Ok(mut current) = self.stream_stats.write() {
+            *current = stats;
+        }
+    }
+
     /// Exporta métricas em formato Prometheus
     pub fn export(&self) -> String {
+        let mut output = self.export_totals();
+        if let Ok(stats) = self.stream_stats.read() {
+            output.push_str(&export_stream_stats(&stats));
+        }
+        output
+    }
+
+    fn export_totals(&self) -> String {
         format!(
             "# HELP vms_cameras_online Number of cameras online\n\
              # TYPE vms_cameras_online gauge\n\
@@ -77,6 +102,81 @@ impl IngestMetrics {
     }
 }
 
+/// Métricas por câmera/stream com labels `camera_id` e `stream`
+///
+/// Contagens acumuladas desde a abertura do stream são `counter` (sufixo
+/// `_total`); as demais, medidas na janela corrente, são `gauge`.
+fn export_stream_stats(stats: &[CameraStreamStats]) -> String {
+    type Getter = fn(&CameraStreamStats) -> f64;
+    let metrics: [(&str, &str, &str, Getter); 8] = [
+        (
+            "vms_camera_fps",
+            "gauge",
+            "Measured frames per second",
+            |s| s.fps,
+        ),
+        (
+            "vms_camera_bitrate_bps",
+            "gauge",
+            "Measured bitrate in bits per second",
+            |s| s.bitrate as f64,
+        ),
+        (
+            "vms_camera_gop_length",
+            "gauge",
+            "Frames between the last two keyframes",
+            |s| s.gop_length.unwrap_or(0) as f64,
+        ),
+        (
+            "vms_camera_keyframe_interval_ms",
+            "gauge",
+            "Interval between the last two keyframes",
+            |s| s.keyframe_interval_ms.unwrap_or(0) as f64,
+        ),
+        (
+            "vms_camera_rtp_packets_lost_total",
+            "counter",
+            "RTP packets lost since the stream opened",
+            |s| s.packets_lost as f64,
+        ),
+        (
+            "vms_camera_rtp_packet_loss_percent",
+            "gauge",
+            "RTP packet loss percentage",
+            |s| s.packet_loss as f64,
+        ),
+        (
+            "vms_camera_rtp_jitter_ms",
+            "gauge",
+            "Average RTP jitter",
+            |s| s.jitter_ms,
+        ),
+        (
+            "vms_camera_timestamp_drift_ms",
+            "gauge",
+            "Wall clock minus frame timestamp drift",
+            |s| s.timestamp_drift_ms as f64,
+        ),
+    ];
+
+    let mut output = String::new();
+    for (name, kind, help, value) in metrics {
+        let _ = writeln!(output, "# HELP {} {}", name, help);
+        let _ = writeln!(output, "# TYPE {} {}", name, kind);
+        for s in stats {
+            let _ = writeln!(
+                output,
+                "{}{{camera_id=\"{}\",stream=\"{}\"}} {}",
+                name,
+                s.camera_id,
+                s.stream,
+                value(s)
+            );
+        }
+    }
+    output
+}
+
 impl Default for IngestMetrics {
     fn default() -> Self {
         Self::new()
@@ -1,15 +1,19 @@
-//! Publicador NATS para frames de vídeo
+//! Publicador NATS para frames de vídeo e eventos de câmera
 
 use anyhow::{Context, Result};
 use async_nats::Client;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info};
-use vms_common::stream::VideoFrame;
+use vms_common::event::Event;
+use vms_common::media_profile::MediaProfileUsage;
+use vms_common::stream::{frame_subject, VideoFrame};
+use vms_common::types::CameraId;
 
 /// Publicador de frames para NATS
+///
+/// Subjects: `vms.frames.{camera_id}.{usage}` (ex: `.recording`, `.analytics`)
 pub struct NatsPublisher {
     client: Client,
-    subject_prefix: String,
 }
 
 impl NatsPublisher {
@@ -23,42 +27,51 @@ impl NatsPublisher {
 
         info!("Connected to NATS successfully");
 
-        Ok(Self {
-            client,
-            subject_prefix: "vms.frames".to_string(),
-        })
+        Ok(Self { client })
     }
 
-    /// Inicia worker para publicar frames
+    /// Inicia worker para publicar frames de um stream
+    ///
+    /// Um stream pode atender vários usos (ex: main = gravação + live view);
+    /// o frame é publicado em cada subject, marcado com o uso correspondente.
     pub async fn start_publishing(
         &self,
         mut rx: mpsc::Receiver<VideoFrame>,
-        camera_id: String,
+        camera_id: CameraId,
+        usages: Vec<MediaProfileUsage>,
     ) -> Result<()> {
         let client = self.client.clone();
-        let subject = format!("{}.{}", self.subject_prefix, camera_id);
+        let subjects: Vec<(MediaProfileUsage, String)> = usages
+            .iter()
+            .map(|usage| (*usage, frame_subject(&camera_id, *usage)))
+            .collect();
 
-        info!("Starting frame publisher for camera: {}", camera_id);
+        info!("Starting frame publisher for camera: {} ({:?})", camera_id, usages);
 
         tokio::spawn(async move {
             let mut frame_count = 0u64;
 
-            while let Some(frame) = rx.recv().await {
-                // Serializar frame para JSON (em produção usar protobuf)
-                match serde_json::to_vec(&frame) {
-                    Ok(payload) => {
-                        if let Err(e) = client.publish(subject.clone(), payload.into()).await {
-                            error!("Failed to publish frame: {}", e);
-                        } else {
-                            frame_count += 1;
-                            if frame_count % 30 == 0 {
-                                debug!("Published {} frames for camera {}", frame_count, camera_id);
-                            }
+            while let Some(mut frame) = rx.recv().await {
+                for (usage, subject) in &subjects {
+                    frame.usage = Some(*usage);
+
+                    // Serializar frame para JSON (em produção usar protobuf)
+                    let payload = match serde_json::to_vec(&frame) {
+                        Ok(payload) => payload,
+                        Err(e) => {
+                            error!("Failed to serialize frame: {}", e);
+                            continue;
                         }
+                    };
+
+                    if let Err(e) = client.publish(subject.clone(), payload.into()).await {
+                        error!("Failed to publish frame: {}", e);
                     }
-                    Err(e) => {
-                        error!("Failed to serialize frame: {}", e);
-                    }
+                }
+
+                frame_count += 1;
+                if frame_count % 30 == 0 {
+                    debug!("Published {} frames for camera {}", frame_count, camera_id);
                 }
             }
 
@@ -69,8 +82,13 @@ impl NatsPublisher {
     }
 
     /// Publica um frame individual
-    pub async fn publish_frame(&self, camera_id: &str, frame: &VideoFrame) -> Result<()> {
-        let subject = format!("{}.{}", self.subject_prefix, camera_id);
+    pub async fn publish_frame(
+        &self,
+        camera_id: &CameraId,
+        usage: MediaProfileUsage,
+        frame: &VideoFrame,
+    ) -> Result<()> {
+        let subject = frame_subject(camera_id, usage);
         let payload = serde_json::to_vec(frame)?;
 
         self.client
@@ -80,4 +98,17 @@ impl NatsPublisher {
 
         Ok(())
     }
+
+    /// Publica um evento de câmera em `vms.events.camera.{camera_id}`
+    pub async fn publish_camera_event(&self, camera_id: &CameraId, event: &Event) -> Result<()> {
+        let subject = format!("vms.events.camera.{}", camera_id);
+        let payload = serde_json::to_vec(event)?;
+
+        self.client
+            .publish(subject, payload.into())
+            .await
+            .context("Failed to publish camera event")?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,195 @@
+//! Resolução de streams via ONVIF
+//! Câmeras cadastradas apenas com URL ONVIF têm as URLs RTSP obtidas do
+//! Media Service (cliente `vms_onvif`)
+//!
+//! Falhas também são memorizadas: uma câmera fora do ar só é consultada de
+//! novo após um backoff exponencial, e cada consulta tem prazo, para que a
+//! sincronização não fique presa esperando câmeras que não respondem.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use tracing::{info, warn};
+use vms_onvif::OnvifDevice;
+
+use crate::api_client::ApiCamera;
+
+/// Prazo de uma consulta ONVIF (conexão, profiles e stream URIs)
+const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Espera após a primeira falha; dobra a cada falha seguinte
+const RETRY_BACKOFF_BASE: Duration = Duration::from_secs(30);
+
+/// Espera máxima entre consultas a uma câmera que não responde
+const RETRY_BACKOFF_MAX: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone)]
+struct ResolvedStreams {
+    main: String,
+    sub: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum CacheEntry {
+    Resolved(ResolvedStreams),
+    /// Falhas consecutivas e instante da próxima tentativa
+    Failed { failures: u32, retry_at: Instant },
+}
+
+/// Espera antes da próxima consulta após `failures` falhas consecutivas
+fn retry_backoff(failures: u32) -> Duration {
+    RETRY_BACKOFF_BASE
+        .saturating_mul(2u32.saturating_pow(failures.saturating_sub(1)))
+        .min(RETRY_BACKOFF_MAX)
+}
+
+/// Resolve e memoriza URLs RTSP por URL ONVIF
+pub struct OnvifStreamResolver {
+    cache: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl OnvifStreamResolver {
+    pub fn new() -> Self {
+        Self {
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Preenche `rtsp_url`/`sub_rtsp_url` de câmeras sem URL RTSP
+    ///
+    /// Câmeras em backoff ficam sem URL neste ciclo.
+    pub async fn resolve(&self, cameras: &mut [ApiCamera]) {
+        for camera in cameras.iter_mut().filter(|c| c.rtsp_url.is_empty()) {
+            let Some(onvif_url) = camera.onvif_url.clone().filter(|u| !u.is_empty()) else {
+                continue;
+            };
+
+            let cached = self.cache.lock().unwrap().get(&onvif_url).cloned();
+            let failures = match cached {
+                Some(CacheEntry::Resolved(streams)) => {
+                    apply(camera, streams);
+                    continue;
+                }
+                Some(CacheEntry::Failed { retry_at, .. }) if Instant::now() < retry_at => continue,
+                Some(CacheEntry::Failed { failures, .. }) => failures,
+                None => 0,
+            };
+
+            let result = tokio::time::timeout(
+                QUERY_TIMEOUT,
+                query_streams(&onvif_url, &camera.username, &camera.password),
+            )
+            .await
+            .unwrap_or_else(|_| Err(anyhow!("No response in {:?}", QUERY_TIMEOUT)));
+
+            match result {
+                Ok(streams) => {
+                    info!("🔗 Camera {} resolved via ONVIF: {}", camera.name, streams.main);
+                    self.cache
+                        .lock()
+                        .unwrap()
+                        .insert(onvif_url, CacheEntry::Resolved(streams.clone()));
+                    apply(camera, streams);
+                }
+                Err(e) => {
+                    let failures = failures + 1;
+                    let backoff = retry_backoff(failures);
+                    warn!(
+                        "⚠️  Could not resolve streams of {} via ONVIF (retry in {:?}): {}",
+                        camera.name, backoff, e
+                    );
+                    self.cache.lock().unwrap().insert(
+                        onvif_url,
+                        CacheEntry::Failed {
+                            failures,
+                            retry_at: Instant::now() + backoff,
+                        },
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl Default for OnvifStreamResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn apply(camera: &mut ApiCamera, streams: ResolvedStreams) {
+    camera.rtsp_url = streams.main;
+    if camera.sub_rtsp_url.as_deref().filter(|u| !u.is_empty()).is_none() {
+        camera.sub_rtsp_url = streams.sub;
+    }
+}
+
+/// Maior profile → main stream, menor → sub stream
+async fn query_streams(onvif_url: &str, username: &str, password: &str) -> Result<ResolvedStreams> {
+    let mut device = OnvifDevice::new(onvif_url, username, password)?;
+    device.connect().await?;
+
+    let mut profiles = device.get_profiles().await?;
+    profiles.sort_by_key(|p| std::cmp::Reverse(p.resolution.0 * p.resolution.1));
+
+    let main_profile = profiles.first().ok_or_else(|| anyhow!("No media profiles"))?;
+    let main = device.get_stream_uri(&main_profile.token).await?;
+    let sub = match profiles.last().filter(|_| profiles.len() > 1) {
+        Some(profile) => device.get_stream_uri(&profile.token).await.ok(),
+        None => None,
+    };
+
+    Ok(ResolvedStreams { main, sub })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera(onvif_url: &str) -> ApiCamera {
+        serde_json::from_value(serde_json::json!({
+            "id": uuid::Uuid::new_v4().to_string(),
+            "name": "Portaria",
+            "rtsp_url": "",
+            "onvif_url": onvif_url,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        assert_eq!(retry_backoff(1), RETRY_BACKOFF_BASE);
+        assert_eq!(retry_backoff(2), RETRY_BACKOFF_BASE * 2);
+        assert_eq!(retry_backoff(3), RETRY_BACKOFF_BASE * 4);
+        assert_eq!(retry_backoff(10), RETRY_BACKOFF_MAX);
+        assert_eq!(retry_backoff(u32::MAX), RETRY_BACKOFF_MAX);
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_cached_until_backoff_expires() {
+        // Porta fechada: a consulta falha na conexão
+        let url = "http://127.0.0.1:1/onvif/device_service";
+        let resolver = OnvifStreamResolver::new();
+
+        let mut cameras = vec![camera(url)];
+        resolver.resolve(&mut cameras).await;
+        assert!(cameras[0].rtsp_url.is_empty());
+
+        // Em backoff: não consulta de novo nem conta outra falha
+        resolver.resolve(&mut cameras).await;
+        let failures = |resolver: &OnvifStreamResolver| match resolver.cache.lock().unwrap().get(url) {
+            Some(CacheEntry::Failed { failures, .. }) => *failures,
+            other => panic!("unexpected cache entry: {:?}", other),
+        };
+        assert_eq!(failures(&resolver), 1);
+
+        // Backoff vencido: nova tentativa, espera maior
+        if let Some(CacheEntry::Failed { retry_at, .. }) = resolver.cache.lock().unwrap().get_mut(url) {
+            *retry_at = Instant::now();
+        }
+        resolver.resolve(&mut cameras).await;
+        assert_eq!(failures(&resolver), 2);
+    }
+}
@@ -0,0 +1,187 @@
+//! Eventos nativos de câmeras ONVIF
+//! Uma assinatura PullPoint por câmera com URL ONVIF; movimento, sabotagem e
+//! entradas digitais são publicados em `vms.events.camera.{id}`
+//!
+//! Câmeras removidas têm a assinatura cancelada na câmera (Unsubscribe), que
+//! tem poucas vagas de PullPoint e só as libera sozinha ao expirar.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use tokio::sync::oneshot;
+use tracing::{debug, info, warn};
+use vms_common::types::CameraId;
+use vms_onvif::events::{self, OnvifEvents, PullPointSubscription};
+use vms_onvif::OnvifDevice;
+
+use crate::api_client::ApiCamera;
+use crate::nats_publisher::NatsPublisher;
+
+/// Validade pedida para a assinatura (renovada na metade)
+const SUBSCRIPTION_SECS: u64 = 60;
+
+/// Espera máxima de cada PullMessages (abaixo do timeout HTTP do cliente)
+const PULL_TIMEOUT_SECS: u64 = 5;
+
+const MESSAGE_LIMIT: u32 = 100;
+
+/// Backoff entre tentativas de (re)assinatura
+const RETRY_MIN: Duration = Duration::from_secs(5);
+const RETRY_MAX: Duration = Duration::from_secs(300);
+
+/// Prazo do Unsubscribe ao encerrar uma assinatura
+const UNSUBSCRIBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Endpoint ONVIF e credenciais de uma câmera
+#[derive(Debug, Clone, PartialEq)]
+struct EventSource {
+    camera_id: CameraId,
+    name: String,
+    onvif_url: String,
+    username: String,
+    password: String,
+}
+
+/// Mantém as assinaturas de eventos das câmeras atribuídas ao nó
+pub struct OnvifEventListener {
+    publisher: Arc<NatsPublisher>,
+    /// Fonte e sinal de parada (ao ser descartado) da tarefa de cada câmera
+    tasks: Mutex<HashMap<CameraId, (EventSource, oneshot::Sender<()>)>>,
+}
+
+impl OnvifEventListener {
+    pub fn new(publisher: Arc<NatsPublisher>) -> Self {
+        Self {
+            publisher,
+            tasks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Assina câmeras novas e encerra as removidas ou com endpoint alterado
+    pub fn sync(&self, cameras: &[ApiCamera]) {
+        let sources: HashMap<CameraId, EventSource> = cameras
+            .iter()
+            .filter(|c| c.enabled)
+            .filter_map(|c| {
+                let onvif_url = c.onvif_url.clone().filter(|u| !u.is_empty())?;
+                let camera_id = CameraId::from_uuid(c.id.parse().ok()?);
+                Some((
+                    camera_id,
+                    EventSource {
+                        camera_id,
+                        name: c.name.clone(),
+                        onvif_url,
+                        username: c.username.clone(),
+                        password: c.password.clone(),
+                    },
+                ))
+            })
+            .collect();
+
+        let mut tasks = self.tasks.lock().unwrap();
+        tasks.retain(|camera_id, (source, _)| {
+            let keep = sources.get(camera_id) == Some(source);
+            if !keep {
+                // Descartar o sinal de parada faz a tarefa cancelar a assinatura e terminar
+                debug!("🔕 Dropping ONVIF event subscription of {}", source.name);
+            }
+            keep
+        });
+
+        for (camera_id, source) in sources {
+            if tasks.contains_key(&camera_id) {
+                continue;
+            }
+            let (stop, stopped) = oneshot::channel();
+            tokio::spawn(run_subscription(source.clone(), self.publisher.clone(), stopped));
+            tasks.insert(camera_id, (source, stop));
+        }
+    }
+}
+
+/// Mantém a assinatura viva, reassinando com backoff em caso de falha, até
+/// receber a parada
+async fn run_subscription(source: EventSource, publisher: Arc<NatsPublisher>, mut stop: oneshot::Receiver<()>) {
+    let mut retry = RETRY_MIN;
+    loop {
+        match pull_events(&source, &publisher, &mut retry, &mut stop).await {
+            Ok(()) => return,
+            Err(e) => warn!("⚠️  ONVIF events of {} unavailable ({}), retrying in {:?}", source.name, e, retry),
+        }
+        tokio::select! {
+            _ = &mut stop => return,
+            _ = tokio::time::sleep(retry) => {}
+        }
+        retry = (retry * 2).min(RETRY_MAX);
+    }
+}
+
+/// Assina e publica os eventos; `Ok` quando a parada é pedida
+async fn pull_events(
+    source: &EventSource,
+    publisher: &NatsPublisher,
+    retry: &mut Duration,
+    stop: &mut oneshot::Receiver<()>,
+) -> Result<()> {
+    let mut device = OnvifDevice::new(&source.onvif_url, &source.username, &source.password)?;
+    device.connect().await?;
+    let service = device.events().ok_or_else(|| anyhow!("no event service"))?;
+
+    // Entradas da câmera, para numerar as portas como o registro de I/O do vms-api
+    let input_tokens: Vec<String> = match device.device_io().get_digital_inputs().await {
+        Ok(inputs) => inputs.into_iter().map(|input| input.token).collect(),
+        Err(e) => {
+            debug!("Digital inputs of {} unavailable: {}", source.name, e);
+            Vec::new()
+        }
+    };
+
+    let subscription = service.create_pull_point_subscription(SUBSCRIPTION_SECS).await?;
+    info!("🔔 Subscribed to ONVIF events of {}", source.name);
+    *retry = RETRY_MIN;
+
+    let result = tokio::select! {
+        _ = &mut *stop => Ok(()),
+        result = pull_messages(source, publisher, &service, &subscription, &input_tokens) => result,
+    };
+
+    // Parada ou falha: libera a vaga na câmera (se inalcançável, expira sozinha)
+    match tokio::time::timeout(UNSUBSCRIBE_TIMEOUT, service.unsubscribe(&subscription)).await {
+        Ok(Ok(())) => debug!("🔕 Unsubscribed from ONVIF events of {}", source.name),
+        Ok(Err(e)) => debug!("Unsubscribe from {} failed: {}", source.name, e),
+        Err(_) => debug!("Unsubscribe from {} timed out", source.name),
+    }
+    result
+}
+
+/// Renova a assinatura e publica as mensagens até a primeira falha
+async fn pull_messages(
+    source: &EventSource,
+    publisher: &NatsPublisher,
+    service: &OnvifEvents,
+    subscription: &PullPointSubscription,
+    input_tokens: &[String],
+) -> Result<()> {
+    let renew_every = Duration::from_secs(SUBSCRIPTION_SECS / 2);
+    let mut renewed_at = Instant::now();
+    loop {
+        if renewed_at.elapsed() >= renew_every {
+            service.renew(subscription, SUBSCRIPTION_SECS).await?;
+            renewed_at = Instant::now();
+        }
+
+        let messages = service.pull_messages(subscription, PULL_TIMEOUT_SECS, MESSAGE_LIMIT).await?;
+        for message in &messages {
+            let Some(event) = events::to_event(source.camera_id, message, input_tokens) else {
+                debug!("ONVIF event ignored: {} {:?}", message.topic, message.data);
+                continue;
+            };
+            info!("🔔 {}: {}", source.name, event.message);
+            if let Err(e) = publisher.publish_camera_event(&source.camera_id, &event).await {
+                warn!("Failed to publish ONVIF event of {}: {}", source.name, e);
+            }
+        }
+    }
+}
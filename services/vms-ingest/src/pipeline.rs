@@ -5,25 +5,36 @@ use anyhow::{Context, Result};
 use gstreamer as gst;
 use gstreamer::prelude::*;
 use gstreamer_app as gst_app;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
-use vms_common::camera::CameraConfig;
+use vms_common::camera::{CameraConfig, CameraStream};
+use vms_common::media_profile::MediaProfileUsage;
 use vms_common::stream::VideoFrame;
+use vms_common::telemetry::{CameraStreamStats, StreamTelemetry};
+
+use crate::tamper::{GrayFrame, ANALYSIS_FPS, ANALYSIS_HEIGHT, ANALYSIS_WIDTH};
 
 pub struct IngestPipeline {
     pipeline: gst::Pipeline,
     config: Arc<CameraConfig>,
+    stream: Arc<CameraStream>,
     frame_tx: Option<mpsc::Sender<VideoFrame>>,
+    telemetry: Arc<Mutex<StreamTelemetry>>,
+    /// Keyframes recebidos (atividade do encoder para o detector de sabotagem)
+    keyframes: Arc<AtomicU64>,
 }
 
 impl IngestPipeline {
     /// Pipeline EXTREMAMENTE otimizado - Sub 50ms
-    pub fn new(config: CameraConfig) -> Result<Self> {
+    ///
+    /// Um pipeline por stream da câmera (main, sub, ...)
+    pub fn new(config: CameraConfig, stream: CameraStream) -> Result<Self> {
         let pipeline = gst::Pipeline::new();
 
-        info!("🔧 Starting RTSP pipeline for camera: {}", config.name);
-        info!("📹 Source: {}", config.url);
+        info!("🔧 Starting RTSP pipeline for camera: {} [{}]", config.name, stream.name);
+        info!("📹 Source: {} (usages: {:?})", stream.url, stream.usages);
         info!("🔐 Auth: user={:?}, pass_len={:?}", 
             config.username.as_ref().map(|s| s.as_str()),
             config.password.as_ref().map(|s| s.len()));
@@ -31,7 +42,7 @@ impl IngestPipeline {
         // RTSP Source - Simplified configuration for compatibility
         let rtspsrc = gst::ElementFactory::make("rtspsrc")
             .name("source")
-            .property("location", &config.url)
+            .property("location", &stream.url)
             .property("latency", 100u32)                  // Low latency
             .property("drop-on-latency", true)            // Drop old frames
             .build()
@@ -80,18 +91,41 @@ impl IngestPipeline {
             .build();
 
         // Caps para H264 - OTIMIZADO
+        // Sem restrição de profile: sub streams costumam ser baseline/main
         let caps = gst::Caps::builder("video/x-h264")
             .field("stream-format", "byte-stream")
             .field("alignment", "au")
-            .field("profile", "high")                     // High profile
             .build();
         sink.set_caps(Some(&caps));
 
-        // Adicionar elementos
-        pipeline.add_many(&[&depay, &parse, &queue, sink.upcast_ref()])?;
-
-        // Link pipeline
-        gst::Element::link_many(&[&depay, &parse, &queue, sink.upcast_ref()])?;
+        // Detecção de sabotagem: decodifica o stream de analytics em paralelo.
+        // Sem sub stream o analytics é o main em resolução cheia: só os
+        // keyframes são decodificados
+        let tamper_enabled =
+            config.tamper_detection.enabled && stream.serves(MediaProfileUsage::Analytics);
+        let keyframes_only = config.effective_streams().len() == 1;
+
+        if tamper_enabled {
+            let tee = gst::ElementFactory::make("tee")
+                .name("tee")
+                .build()
+                .context("Failed to create tee")?;
+
+            pipeline.add_many(&[&depay, &parse, &tee, &queue, sink.upcast_ref()])?;
+            gst::Element::link_many(&[&depay, &parse, &tee, &queue, sink.upcast_ref()])?;
+            add_tamper_branch(&pipeline, &tee, keyframes_only)?;
+            info!(
+                "🛡️ Tamper detection branch enabled [{}]{}",
+                stream.name,
+                if keyframes_only { " (keyframes only)" } else { "" }
+            );
+        } else {
+            // Adicionar elementos
+            pipeline.add_many(&[&depay, &parse, &queue, sink.upcast_ref()])?;
+
+            // Link pipeline
+            gst::Element::link_many(&[&depay, &parse, &queue, sink.upcast_ref()])?;
+        }
 
         // Conectar RTSP source
         let depay_clone = depay.clone();
@@ -133,21 +167,153 @@ impl IngestPipeline {
         Ok(Self {
             pipeline,
             config: Arc::new(config),
+            stream: Arc::new(stream),
             frame_tx: None,
+            telemetry: Arc::new(Mutex::new(StreamTelemetry::new())),
+            keyframes: Arc::new(AtomicU64::new(0)),
         })
     }
 
+    /// Conecta o appsink a um canal de frames, marcando cada frame com o perfil do stream
     pub fn set_frame_sender(&mut self, tx: mpsc::Sender<VideoFrame>) {
+        if let Some(appsink) = self.get_appsink() {
+            let stream = self.stream.clone();
+            let sender = tx.clone();
+            let telemetry = self.telemetry.clone();
+            let keyframes = self.keyframes.clone();
+
+            appsink.set_callbacks(
+                gst_app::AppSinkCallbacks::builder()
+                    .new_sample(move |sink| {
+                        let sample = sink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+
+                        if let Some(frame) = frame_from_sample(&sample, &stream) {
+                            let pts_ms = sample.buffer().and_then(|b| b.pts()).map(|pts| pts.mseconds());
+                            if let Ok(mut telemetry) = telemetry.lock() {
+                                telemetry.record_frame(
+                                    chrono::Utc::now().timestamp_millis() as u64,
+                                    pts_ms,
+                                    frame.data.len(),
+                                    frame.is_keyframe,
+                                );
+                            }
+                            if frame.is_keyframe {
+                                keyframes.fetch_add(1, Ordering::Relaxed);
+                            }
+
+                            if let Err(e) = sender.try_send(frame) {
+                                debug!("⚠️  Frame dropped [{}]: {}", stream.name, e);
+                            }
+                        }
+
+                        Ok(gst::FlowSuccess::Ok)
+                    })
+                    .build(),
+            );
+        }
+
         self.frame_tx = Some(tx);
     }
 
+    /// Conecta o branch de sabotagem (frames GRAY8 reduzidos) a um canal
+    ///
+    /// Retorna false se o pipeline não tem branch de sabotagem.
+    pub fn set_tamper_sender(&self, tx: mpsc::Sender<GrayFrame>) -> bool {
+        let Some(appsink) = self
+            .pipeline
+            .by_name("tamper_sink")
+            .and_then(|e| e.downcast::<gst_app::AppSink>().ok())
+        else {
+            return false;
+        };
+
+        let stream_name = self.stream.name.clone();
+        let keyframes = self.keyframes.clone();
+        appsink.set_callbacks(
+            gst_app::AppSinkCallbacks::builder()
+                .new_sample(move |sink| {
+                    let sample = sink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                    let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+                    let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+
+                    // GRAY8 com largura múltipla de 4: stride == largura
+                    let frame = GrayFrame {
+                        width: ANALYSIS_WIDTH,
+                        height: ANALYSIS_HEIGHT,
+                        data: map.as_slice().to_vec(),
+                        wall_ms: chrono::Utc::now().timestamp_millis() as u64,
+                        keyframes: keyframes.load(Ordering::Relaxed),
+                    };
+                    if let Err(e) = tx.try_send(frame) {
+                        debug!("⚠️  Tamper frame dropped [{}]: {}", stream_name, e);
+                    }
+
+                    Ok(gst::FlowSuccess::Ok)
+                })
+                .build(),
+        );
+
+        true
+    }
+
+    /// Stream desta pipeline
+    pub fn stream(&self) -> &CameraStream {
+        &self.stream
+    }
+
+    /// Snapshot de telemetria do stream (fps, bitrate, GOP, perda RTP, drift)
+    pub fn stats(&self) -> CameraStreamStats {
+        let (received, lost, jitter_ms) = self.rtp_stats();
+        let mut telemetry = self.telemetry.lock().unwrap_or_else(|e| e.into_inner());
+        telemetry.set_rtp_stats(received, lost, jitter_ms);
+        telemetry.snapshot(
+            self.config.id,
+            &self.stream.name,
+            chrono::Utc::now().timestamp_millis() as u64,
+        )
+    }
+
+    /// Soma as estatísticas dos rtpjitterbuffer criados pelo rtspsrc
+    ///
+    /// Retorna (pacotes recebidos, pacotes perdidos, jitter médio em ms).
+    fn rtp_stats(&self) -> (u64, u64, f64) {
+        let mut received = 0u64;
+        let mut lost = 0u64;
+        let mut jitter_ns = Vec::new();
+
+        for element in self.pipeline.iterate_recurse().into_iter().flatten() {
+            let is_jitterbuffer = element
+                .factory()
+                .map(|f| f.name() == "rtpjitterbuffer")
+                .unwrap_or(false);
+            if !is_jitterbuffer {
+                continue;
+            }
+
+            let stats = element.property::<gst::Structure>("stats");
+            received += stats.get::<u64>("num-pushed").unwrap_or(0);
+            lost += stats.get::<u64>("num-lost").unwrap_or(0);
+            if let Ok(jitter) = stats.get::<u64>("avg-jitter") {
+                jitter_ns.push(jitter);
+            }
+        }
+
+        let jitter_ms = if jitter_ns.is_empty() {
+            0.0
+        } else {
+            jitter_ns.iter().sum::<u64>() as f64 / jitter_ns.len() as f64 / 1_000_000.0
+        };
+
+        (received, lost, jitter_ms)
+    }
+
     pub fn start(&self) -> Result<()> {
         info!("⚡⚡⚡ EXTREME MODE ACTIVATED ⚡⚡⚡");
         info!("📊 Configuration:");
         info!("  - Transport: UDP ONLY");
         info!("  - Buffer: ZERO");
         info!("  - Latency: < 50ms target");
-        info!("  - Quality: 1080p H264 High Profile");
+        info!("  - Stream: {} ({:?})", self.stream.name, self.stream.usages);
         info!("  - Frame drop: AGGRESSIVE");
 
         // Add bus watch for error handling
@@ -182,6 +348,10 @@ impl IngestPipeline {
             }).expect("Failed to add bus watch");
         }
 
+        if let Ok(mut telemetry) = self.telemetry.lock() {
+            telemetry.reset();
+        }
+
         self.pipeline
             .set_state(gst::State::Playing)
             .context("Failed to start pipeline")?;
@@ -192,7 +362,7 @@ impl IngestPipeline {
     }
 
     pub fn stop(&self) -> Result<()> {
-        info!("⏹️  Stopping EXTREME pipeline: {}", self.config.name);
+        info!("⏹️  Stopping EXTREME pipeline: {} [{}]", self.config.name, self.stream.name);
         self.pipeline.set_state(gst::State::Null)?;
         Ok(())
     }
@@ -215,6 +385,116 @@ impl IngestPipeline {
     }
 }
 
+/// Branch tee → decodificação → GRAY8 160x120 @ 2 fps → appsink "tamper_sink"
+///
+/// Com `keyframes_only` (main em resolução cheia) os frames delta são
+/// descartados antes do decoder, que roda em uma única thread.
+fn add_tamper_branch(pipeline: &gst::Pipeline, tee: &gst::Element, keyframes_only: bool) -> Result<()> {
+    let queue = gst::ElementFactory::make("queue")
+        .name("tamper_queue")
+        .property("max-size-buffers", 5u32)
+        .property("max-size-bytes", 0u32)
+        .property("max-size-time", 0u64)
+        .build()
+        .context("Failed to create tamper queue")?;
+    queue.set_property_from_str("leaky", "downstream");
+
+    let keyframe_filter = gst::ElementFactory::make("identity")
+        .name("tamper_keyframes")
+        .build()
+        .context("Failed to create identity")?;
+    if keyframes_only {
+        keyframe_filter.set_property_from_str("drop-buffer-flags", "delta-unit");
+    }
+
+    let decode = gst::ElementFactory::make("avdec_h264")
+        .name("tamper_decode")
+        .build()
+        .context("Failed to create avdec_h264")?;
+    if keyframes_only {
+        decode.set_property("max-threads", 1i32);
+    }
+    let rate = gst::ElementFactory::make("videorate")
+        .name("tamper_rate")
+        .property("drop-only", true)
+        .build()
+        .context("Failed to create videorate")?;
+    let scale = gst::ElementFactory::make("videoscale")
+        .name("tamper_scale")
+        .build()
+        .context("Failed to create videoscale")?;
+    let convert = gst::ElementFactory::make("videoconvert")
+        .name("tamper_convert")
+        .build()
+        .context("Failed to create videoconvert")?;
+
+    let caps = gst::Caps::builder("video/x-raw")
+        .field("format", "GRAY8")
+        .field("width", ANALYSIS_WIDTH as i32)
+        .field("height", ANALYSIS_HEIGHT as i32)
+        .field("framerate", gst::Fraction::new(ANALYSIS_FPS, 1))
+        .build();
+    let sink = gst_app::AppSink::builder()
+        .name("tamper_sink")
+        .sync(false)
+        .max_buffers(1)
+        .drop(true)
+        .caps(&caps)
+        .build();
+
+    pipeline.add_many(&[&queue, &keyframe_filter, &decode, &rate, &scale, &convert, sink.upcast_ref()])?;
+    gst::Element::link_many(&[
+        tee,
+        &queue,
+        &keyframe_filter,
+        &decode,
+        &rate,
+        &scale,
+        &convert,
+        sink.upcast_ref(),
+    ])?;
+
+    Ok(())
+}
+
+/// Converte uma amostra do appsink em frame marcado com o perfil/uso do stream
+///
+/// O frame carrega o primeiro uso do stream; o publisher replica para os demais subjects.
+fn frame_from_sample(sample: &gst::Sample, stream: &CameraStream) -> Option<VideoFrame> {
+    let buffer = sample.buffer()?;
+    let map = buffer.map_readable().ok()?;
+
+    let (width, height) = sample
+        .caps()
+        .and_then(|caps| caps.structure(0))
+        .map(|s| {
+            (
+                s.get::<i32>("width").unwrap_or(0) as u32,
+                s.get::<i32>("height").unwrap_or(0) as u32,
+            )
+        })
+        .filter(|(w, h)| *w > 0 && *h > 0)
+        .or_else(|| stream.resolution.map(|r| (r.width, r.height)))
+        .unwrap_or((1920, 1080));
+
+    let mut frame = VideoFrame::new(
+        vms_common::types::StreamId::new(),
+        map.as_slice().to_vec(),
+        width,
+        height,
+    );
+
+    if !buffer.flags().contains(gst::BufferFlags::DELTA_UNIT) {
+        frame = frame.as_keyframe();
+    }
+
+    if let Some(usage) = stream.usages.first() {
+        frame = frame.with_profile(stream.profile_id, *usage);
+    }
+
+    Some(frame)
+}
+
 impl Drop for IngestPipeline {
     fn drop(&mut self) {
         let _ = self.stop();
@@ -0,0 +1,478 @@
+//! Detecção de sabotagem (tampering) em frames decodificados do stream de
+//! analytics (sub stream ou, sem ele, keyframes do main)
+//!
+//! Analisador leve em CPU sobre frames em escala de cinza de baixa resolução:
+//! lente coberta (baixa variância/escuro), desfoque (queda de nitidez),
+//! redirecionamento (similaridade com o fundo aprendido), imagem congelada
+//! e perda de vídeo. Eventos são publicados em `vms.events.camera.{id}`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+use vms_common::analytics::{TamperDetectionConfig, TamperKind};
+use vms_common::event::{Event, EventCategory, EventSeverity, EventTrigger};
+use vms_common::types::CameraId;
+
+use crate::nats_publisher::NatsPublisher;
+
+/// Resolução do frame de análise (ver branch de tamper do pipeline)
+pub const ANALYSIS_WIDTH: u32 = 160;
+pub const ANALYSIS_HEIGHT: u32 = 120;
+
+/// Frames analisados por segundo
+pub const ANALYSIS_FPS: i32 = 2;
+
+/// Frames para aprender nitidez e fundo antes de avaliar desfoque/redirecionamento
+const WARMUP_FRAMES: u32 = 10;
+
+/// Taxa de atualização do fundo/nitidez de referência (média móvel)
+const LEARNING_RATE: f64 = 0.02;
+
+/// Imagem congelada: duração mínima da condição (cenas estáticas são comuns)
+const FROZEN_MIN_SECONDS: u64 = 60;
+
+/// Imagem congelada: keyframes novos sem mudança na imagem. Um keyframe de
+/// uma cena estática traz ruído novo do sensor; os P-frames entre eles não
+/// (blocos pulados pelo encoder), então só frames idênticos através de
+/// keyframes indicam fonte congelada
+const FROZEN_MIN_KEYFRAMES: u64 = 2;
+
+/// Grade de blocos para comparação de cena
+const GRID_COLS: usize = 16;
+const GRID_ROWS: usize = 12;
+
+/// Frame em escala de cinza (GRAY8)
+#[derive(Debug, Clone)]
+pub struct GrayFrame {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+    /// Relógio de parede no recebimento
+    pub wall_ms: u64,
+    /// Keyframes recebidos pelo stream até este frame (atividade do encoder)
+    pub keyframes: u64,
+}
+
+/// Mudança de estado de uma condição de sabotagem
+#[derive(Debug, Clone, PartialEq)]
+pub struct TamperAlert {
+    pub kind: TamperKind,
+    /// true = condição iniciada, false = condição encerrada
+    pub active: bool,
+    /// Valor medido que disparou a condição
+    pub score: f64,
+}
+
+/// Medidas de um frame
+#[derive(Debug, Clone, Copy)]
+struct FrameMeasures {
+    mean: f64,
+    std_dev: f64,
+    sharpness: f64,
+}
+
+/// Analisador de sabotagem de uma câmera
+pub struct TamperAnalyzer {
+    config: TamperDetectionConfig,
+    frames_seen: u32,
+    sharpness_baseline: Option<f64>,
+    background: Option<Vec<f64>>,
+    previous: Option<Vec<u8>>,
+    /// Contagem de keyframes quando a imagem parou de mudar
+    unchanged_since_keyframe: Option<u64>,
+    /// Início de cada condição ainda não confirmada/ativa
+    pending: HashMap<TamperKind, u64>,
+    active: HashMap<TamperKind, bool>,
+    last_frame_ms: Option<u64>,
+}
+
+impl TamperAnalyzer {
+    pub fn new(config: TamperDetectionConfig) -> Self {
+        Self {
+            config,
+            frames_seen: 0,
+            sharpness_baseline: None,
+            background: None,
+            previous: None,
+            unchanged_since_keyframe: None,
+            pending: HashMap::new(),
+            active: HashMap::new(),
+            last_frame_ms: None,
+        }
+    }
+
+    fn sensitivity(&self) -> f64 {
+        self.config.sensitivity.clamp(0.0, 1.0) as f64
+    }
+
+    fn is_active(&self, kind: TamperKind) -> bool {
+        self.active.get(&kind).copied().unwrap_or(false)
+    }
+
+    /// Analisa um frame e retorna mudanças de estado
+    pub fn analyze(&mut self, frame: &GrayFrame) -> Vec<TamperAlert> {
+        let mut alerts = Vec::new();
+        let now = frame.wall_ms;
+        self.last_frame_ms = Some(now);
+
+        if let Some(alert) = self.update(TamperKind::VideoLoss, false, 0.0, now) {
+            alerts.push(alert);
+        }
+
+        let expected = (frame.width * frame.height) as usize;
+        if frame.width < 3 || frame.height < 3 || frame.data.len() < expected {
+            return alerts;
+        }
+
+        let s = self.sensitivity();
+        let measures = measure(frame);
+        self.frames_seen += 1;
+
+        // Lente coberta: imagem uniforme ou muito escura
+        let obstructed = measures.std_dev < 4.0 + 12.0 * s || measures.mean < 10.0 + 20.0 * s;
+        alerts.extend(self.update(TamperKind::Obstruction, obstructed, measures.std_dev, now));
+
+        // Imagem congelada: frame idêntico ao anterior através de keyframes
+        // novos, por pelo menos FROZEN_MIN_SECONDS
+        if let Some(diff) = self.previous.as_ref().map(|p| mean_abs_diff(p, &frame.data[..expected])) {
+            let unchanged = diff < 0.05 + 0.25 * s;
+            self.unchanged_since_keyframe = match self.unchanged_since_keyframe {
+                Some(since) if unchanged => Some(since),
+                _ if unchanged => Some(frame.keyframes),
+                _ => None,
+            };
+            let frozen = self
+                .unchanged_since_keyframe
+                .is_some_and(|since| frame.keyframes.saturating_sub(since) >= FROZEN_MIN_KEYFRAMES);
+            let min_duration_ms = (self.config.min_duration_seconds as u64).max(FROZEN_MIN_SECONDS) * 1000;
+            alerts.extend(self.update_for(TamperKind::FrozenImage, frozen, diff, now, min_duration_ms));
+        }
+        self.previous = Some(frame.data[..expected].to_vec());
+
+        // Obstrução mascara desfoque e redirecionamento; não aprender com ela
+        if obstructed {
+            return alerts;
+        }
+
+        let blocks = block_means(frame);
+        let learning = self.frames_seen <= WARMUP_FRAMES;
+
+        // Desfoque: nitidez abaixo de uma fração da referência
+        if let Some(baseline) = self.sharpness_baseline.filter(|_| !learning) {
+            let ratio = if baseline > 0.0 { measures.sharpness / baseline } else { 1.0 };
+            let defocused = ratio < 0.3 + 0.4 * s;
+            alerts.extend(self.update(TamperKind::Defocus, defocused, ratio, now));
+        }
+
+        // Redirecionamento: correlação baixa com o fundo aprendido
+        let similarity = self.background.as_ref().map(|b| correlation(b, &blocks));
+        if let Some(similarity) = similarity.filter(|_| !learning) {
+            let redirected = similarity < 0.2 + 0.5 * s;
+            alerts.extend(self.update(TamperKind::SceneChange, redirected, similarity, now));
+        }
+
+        // Referências adaptam-se lentamente (iluminação), exceto durante alertas
+        if !self.is_active(TamperKind::Defocus) && !self.pending.contains_key(&TamperKind::Defocus) {
+            self.sharpness_baseline = Some(match self.sharpness_baseline {
+                Some(b) if !learning => b + LEARNING_RATE * (measures.sharpness - b),
+                Some(b) => b.max(measures.sharpness),
+                None => measures.sharpness,
+            });
+        }
+        if !self.is_active(TamperKind::SceneChange) && !self.pending.contains_key(&TamperKind::SceneChange) {
+            match self.background.as_mut() {
+                Some(background) => {
+                    let rate = if learning { 0.5 } else { LEARNING_RATE };
+                    for (b, v) in background.iter_mut().zip(&blocks) {
+                        *b += rate * (v - *b);
+                    }
+                }
+                None => self.background = Some(blocks),
+            }
+        }
+
+        alerts
+    }
+
+    /// Verifica perda de vídeo (chamado quando nenhum frame chega no timeout)
+    pub fn check_video_loss(&mut self, now_ms: u64) -> Option<TamperAlert> {
+        let last = self.last_frame_ms.unwrap_or(now_ms);
+        let silent_ms = now_ms.saturating_sub(last);
+        let lost = silent_ms >= self.config.video_loss_timeout_seconds as u64 * 1000;
+        self.update(TamperKind::VideoLoss, lost, silent_ms as f64, now_ms)
+    }
+
+    /// Aplica histerese temporal: a condição deve persistir `min_duration_seconds`
+    fn update(&mut self, kind: TamperKind, condition: bool, score: f64, now_ms: u64) -> Option<TamperAlert> {
+        let min_duration_ms = self.config.min_duration_seconds as u64 * 1000;
+        self.update_for(kind, condition, score, now_ms, min_duration_ms)
+    }
+
+    fn update_for(
+        &mut self,
+        kind: TamperKind,
+        condition: bool,
+        score: f64,
+        now_ms: u64,
+        min_duration_ms: u64,
+    ) -> Option<TamperAlert> {
+        if condition {
+            let since = *self.pending.entry(kind).or_insert(now_ms);
+            if !self.is_active(kind) && now_ms.saturating_sub(since) >= min_duration_ms {
+                self.active.insert(kind, true);
+                return Some(TamperAlert { kind, active: true, score });
+            }
+        } else {
+            self.pending.remove(&kind);
+            if self.is_active(kind) {
+                self.active.insert(kind, false);
+                return Some(TamperAlert { kind, active: false, score });
+            }
+        }
+
+        None
+    }
+}
+
+fn measure(frame: &GrayFrame) -> FrameMeasures {
+    let (w, h) = (frame.width as usize, frame.height as usize);
+    let pixels = &frame.data[..w * h];
+
+    let n = pixels.len() as f64;
+    let mean = pixels.iter().map(|&p| p as f64).sum::<f64>() / n;
+    let variance = pixels.iter().map(|&p| (p as f64 - mean).powi(2)).sum::<f64>() / n;
+
+    // Nitidez: média do valor absoluto do Laplaciano
+    let mut laplacian = 0.0;
+    for y in 1..h - 1 {
+        for x in 1..w - 1 {
+            let c = pixels[y * w + x] as f64;
+            let l = pixels[y * w + x - 1] as f64
+                + pixels[y * w + x + 1] as f64
+                + pixels[(y - 1) * w + x] as f64
+                + pixels[(y + 1) * w + x] as f64
+                - 4.0 * c;
+            laplacian += l.abs();
+        }
+    }
+    let inner = ((w - 2) * (h - 2)) as f64;
+
+    FrameMeasures {
+        mean,
+        std_dev: variance.sqrt(),
+        sharpness: laplacian / inner,
+    }
+}
+
+fn mean_abs_diff(a: &[u8], b: &[u8]) -> f64 {
+    let total: u64 = a.iter().zip(b).map(|(&x, &y)| (x as i16 - y as i16).unsigned_abs() as u64).sum();
+    total as f64 / a.len().max(1) as f64
+}
+
+/// Médias de luminância em uma grade GRID_COLS x GRID_ROWS
+fn block_means(frame: &GrayFrame) -> Vec<f64> {
+    let (w, h) = (frame.width as usize, frame.height as usize);
+    let mut sums = vec![0.0; GRID_COLS * GRID_ROWS];
+    let mut counts = vec![0u32; GRID_COLS * GRID_ROWS];
+
+    for y in 0..h {
+        let row = (y * GRID_ROWS / h).min(GRID_ROWS - 1);
+        for x in 0..w {
+            let col = (x * GRID_COLS / w).min(GRID_COLS - 1);
+            sums[row * GRID_COLS + col] += frame.data[y * w + x] as f64;
+            counts[row * GRID_COLS + col] += 1;
+        }
+    }
+
+    sums.iter()
+        .zip(&counts)
+        .map(|(s, &c)| if c > 0 { s / c as f64 } else { 0.0 })
+        .collect()
+}
+
+/// Correlação de Pearson entre duas grades (1.0 = mesma cena)
+fn correlation(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len().min(b.len()) as f64;
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+
+    let mut cov = 0.0;
+    let mut var_a = 0.0;
+    let mut var_b = 0.0;
+    for (x, y) in a.iter().zip(b) {
+        cov += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+
+    if var_a <= f64::EPSILON || var_b <= f64::EPSILON {
+        return 1.0;
+    }
+    cov / (var_a.sqrt() * var_b.sqrt())
+}
+
+/// Cria o evento de barramento para um alerta
+pub fn tamper_event(camera_id: CameraId, camera_name: &str, alert: &TamperAlert) -> Event {
+    let trigger = match alert.kind {
+        TamperKind::Obstruction => EventTrigger::CameraObstructed { camera_id },
+        kind => EventTrigger::CameraTampered { camera_id, kind },
+    };
+
+    let state = if alert.active { "raised" } else { "cleared" };
+    let message = format!("Camera {}: {} {}", camera_name, alert.kind.as_str(), state);
+    let severity = if alert.active { EventSeverity::Warning } else { EventSeverity::Info };
+
+    let mut event = Event::new(trigger, EventCategory::System, &message).with_severity(severity);
+    event.metadata.insert("tamper_kind".to_string(), alert.kind.as_str().to_string());
+    event.metadata.insert("state".to_string(), state.to_string());
+    event.metadata.insert("score".to_string(), format!("{:.3}", alert.score));
+    event
+}
+
+/// Loop de análise de uma câmera; termina quando o pipeline é descartado
+pub async fn run_tamper_detection(
+    camera_id: CameraId,
+    camera_name: String,
+    config: TamperDetectionConfig,
+    mut rx: mpsc::Receiver<GrayFrame>,
+    publisher: Arc<NatsPublisher>,
+) {
+    info!("🛡️ Tamper detection started for camera {} (sensitivity {:.2})", camera_name, config.sensitivity);
+
+    let timeout = std::time::Duration::from_secs(config.video_loss_timeout_seconds.max(1) as u64);
+    let mut analyzer = TamperAnalyzer::new(config);
+
+    loop {
+        let alerts = match tokio::time::timeout(timeout, rx.recv()).await {
+            Ok(Some(frame)) => analyzer.analyze(&frame),
+            Ok(None) => break,
+            Err(_) => analyzer
+                .check_video_loss(chrono::Utc::now().timestamp_millis() as u64)
+                .into_iter()
+                .collect(),
+        };
+
+        for alert in alerts {
+            warn!(
+                "🛡️ Tamper {} on camera {}: {} (score {:.3})",
+                if alert.active { "raised" } else { "cleared" },
+                camera_name,
+                alert.kind.as_str(),
+                alert.score
+            );
+            let event = tamper_event(camera_id, &camera_name, &alert);
+            if let Err(e) = publisher.publish_camera_event(&camera_id, &event).await {
+                warn!("Failed to publish tamper event: {}", e);
+            }
+        }
+    }
+
+    info!("🛡️ Tamper detection stopped for camera {}", camera_name);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> TamperDetectionConfig {
+        TamperDetectionConfig {
+            enabled: true,
+            sensitivity: 0.5,
+            min_duration_seconds: 1,
+            video_loss_timeout_seconds: 5,
+        }
+    }
+
+    /// Cena texturizada com ruído variável por frame
+    fn scene(seed: u32, wall_ms: u64) -> GrayFrame {
+        let (w, h) = (ANALYSIS_WIDTH, ANALYSIS_HEIGHT);
+        let data = (0..w * h)
+            .map(|i| {
+                let (x, y) = (i % w, i / w);
+                let base = if (x / 8 + y / 8) % 2 == 0 { 60 } else { 190 };
+                let noise = ((i ^ seed.wrapping_mul(0x9E37_79B9)).wrapping_mul(2_654_435_761) >> 28) as u8;
+                base + noise
+            })
+            .collect();
+        GrayFrame { width: w, height: h, data, wall_ms, keyframes: 0 }
+    }
+
+    fn uniform(value: u8, wall_ms: u64) -> GrayFrame {
+        GrayFrame {
+            width: ANALYSIS_WIDTH,
+            height: ANALYSIS_HEIGHT,
+            data: vec![value; (ANALYSIS_WIDTH * ANALYSIS_HEIGHT) as usize],
+            wall_ms,
+            keyframes: 0,
+        }
+    }
+
+    #[test]
+    fn test_obstruction_raised_after_min_duration_and_cleared() {
+        let mut analyzer = TamperAnalyzer::new(config());
+        for i in 0..20 {
+            assert!(analyzer.analyze(&scene(i, i as u64 * 500)).is_empty());
+        }
+
+        assert!(analyzer.analyze(&uniform(5, 10_000)).is_empty());
+        let alerts = analyzer.analyze(&uniform(6, 11_000));
+        assert!(alerts.contains(&TamperAlert {
+            kind: TamperKind::Obstruction,
+            active: true,
+            score: 0.0,
+        }));
+
+        let alerts = analyzer.analyze(&scene(99, 11_500));
+        assert!(alerts.iter().any(|a| a.kind == TamperKind::Obstruction && !a.active));
+    }
+
+    #[test]
+    fn test_static_scene_is_not_frozen() {
+        // Cena estática: P-frames idênticos por minutos, mas cada keyframe
+        // (a cada 2 s) traz ruído novo do sensor
+        let mut analyzer = TamperAnalyzer::new(config());
+        for t in 0..600u64 {
+            let keyframes = t / 4;
+            let mut frame = scene(keyframes as u32, t * 500);
+            frame.keyframes = keyframes;
+            let alerts = analyzer.analyze(&frame);
+            assert!(!alerts.iter().any(|a| a.kind == TamperKind::FrozenImage), "t={}", t);
+        }
+
+        // Sem keyframes novos (encoder parado) a imagem idêntica não basta
+        let mut analyzer = TamperAnalyzer::new(config());
+        for t in 0..600u64 {
+            let alerts = analyzer.analyze(&scene(1, t * 500));
+            assert!(!alerts.iter().any(|a| a.kind == TamperKind::FrozenImage), "t={}", t);
+        }
+    }
+
+    #[test]
+    fn test_frozen_image_and_video_loss() {
+        // Keyframes chegando e imagem idêntica: fonte congelada após FROZEN_MIN_SECONDS
+        let mut analyzer = TamperAnalyzer::new(config());
+        let frozen = scene(1, 0);
+        let mut raised_at = None;
+        for t in 0..200u64 {
+            let mut frame = frozen.clone();
+            frame.wall_ms = t * 500;
+            frame.keyframes = t / 4;
+            let alerts = analyzer.analyze(&frame);
+            if alerts.iter().any(|a| a.kind == TamperKind::FrozenImage && a.active) {
+                raised_at = Some(frame.wall_ms);
+                break;
+            }
+        }
+        // Condição confirmada no 2º keyframe novo (4 s) + 60 s de duração mínima
+        assert_eq!(raised_at, Some(64_000));
+        let last_ms = raised_at.unwrap();
+
+        // Sem frames por mais que o timeout: perda de vídeo após min_duration
+        assert!(analyzer.check_video_loss(last_ms + 7_000).is_none());
+        let alert = analyzer.check_video_loss(last_ms + 8_000).unwrap();
+        assert_eq!(alert.kind, TamperKind::VideoLoss);
+        assert!(alert.active);
+    }
+}
@@ -37,20 +37,22 @@ async fn main() -> Result<()> {
     println!("🔍 Enviando GetDeviceInformation");
     println!("----------------------------------");
     
-    let soap_body = r#"<?xml version="1.0" encoding="UTF-8"?>
-<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/" xmlns:tds="http://www.onvif.org/ver10/device/wsdl">
-  <s:Body>
-    <tds:GetDeviceInformation/>
-  </s:Body>
-</s:Envelope>"#;
+    let soap_body = r#"<tds:GetDeviceInformation xmlns:tds="http://www.onvif.org/ver10/device/wsdl"/>"#;
 
-    println!("📤 Request SOAP:");
+    println!("📤 Request SOAP (body):");
     println!("{}", soap_body);
     println!();
 
-    match client.soap_post_digest("/onvif/device_service", None, soap_body).await {
+    match client
+        .call(
+            "/onvif/device_service",
+            "http://www.onvif.org/ver10/device/wsdl/GetDeviceInformation",
+            soap_body,
+        )
+        .await
+    {
         Ok(response) => {
-            println!("✅ Sucesso!");
+            println!("✅ Sucesso! ({:?}, {:?})", client.soap_version(), client.auth_mode());
             println!("📥 Response:");
             println!("{}", &response[..response.len().min(500)]);
         }
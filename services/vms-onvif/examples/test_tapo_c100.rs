@@ -6,30 +6,15 @@
 use anyhow::Result;
 
 // Importar do crate vms_onvif (com underscore)
+use vms_onvif::device::{parse_capabilities, parse_device_info};
 use vms_onvif::OnvifClient;
-use vms_onvif::xml_utils;
 
-fn soap_get_capabilities() -> String {
-    r#"<?xml version="1.0" encoding="UTF-8"?>
-<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope"
-            xmlns:tds="http://www.onvif.org/ver10/device/wsdl">
-  <s:Body>
-    <tds:GetCapabilities>
+const GET_CAPABILITIES: &str = r#"<tds:GetCapabilities xmlns:tds="http://www.onvif.org/ver10/device/wsdl">
       <tds:Category>All</tds:Category>
-    </tds:GetCapabilities>
-  </s:Body>
-</s:Envelope>"#.to_string()
-}
+    </tds:GetCapabilities>"#;
 
-fn soap_get_device_information() -> String {
-    r#"<?xml version="1.0" encoding="UTF-8"?>
-<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope"
-            xmlns:tds="http://www.onvif.org/ver10/device/wsdl">
-  <s:Body>
-    <tds:GetDeviceInformation/>
-  </s:Body>
-</s:Envelope>"#.to_string()
-}
+const GET_DEVICE_INFORMATION: &str =
+    r#"<tds:GetDeviceInformation xmlns:tds="http://www.onvif.org/ver10/device/wsdl"/>"#;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -60,27 +45,23 @@ async fn main() -> Result<()> {
     // Teste 1: GetDeviceInformation
     println!("🔍 Teste 1: GetDeviceInformation");
     println!("----------------------------------");
-    match client.soap_post_digest(
+    match client.call(
         "/onvif/device_service",
-        None,
-        &soap_get_device_information()
+        "http://www.onvif.org/ver10/device/wsdl/GetDeviceInformation",
+        GET_DEVICE_INFORMATION,
     ).await {
         Ok(response) => {
-            println!("✅ Sucesso!");
-            
-            // Parse informações
-            if let Some(manufacturer) = xml_utils::extract_first_tag_text(&response, "Manufacturer") {
-                println!("   Fabricante: {}", manufacturer);
-            }
-            if let Some(model) = xml_utils::extract_first_tag_text(&response, "Model") {
-                println!("   Modelo: {}", model);
-            }
-            if let Some(firmware) = xml_utils::extract_first_tag_text(&response, "FirmwareVersion") {
-                println!("   Firmware: {}", firmware);
-            }
-            if let Some(serial) = xml_utils::extract_first_tag_text(&response, "SerialNumber") {
-                println!("   Serial: {}", serial);
+            println!("✅ Sucesso! ({:?}, {:?})", client.soap_version(), client.auth_mode());
+            if let Some(offset) = client.clock_offset() {
+                println!("   Diferença de relógio: {}s", offset.num_seconds());
             }
+
+            // Parse informações
+            let info = parse_device_info(&response)?;
+            println!("   Fabricante: {}", info.manufacturer);
+            println!("   Modelo: {}", info.model);
+            println!("   Firmware: {}", info.firmware_version);
+            println!("   Serial: {}", info.serial_number);
         }
         Err(e) => {
             println!("❌ Erro: {}", e);
@@ -95,27 +76,27 @@ async fn main() -> Result<()> {
     // Teste 2: GetCapabilities
     println!("🔍 Teste 2: GetCapabilities");
     println!("----------------------------------");
-    match client.soap_post_digest(
+    match client.call(
         "/onvif/device_service",
-        None,
-        &soap_get_capabilities()
+        "http://www.onvif.org/ver10/device/wsdl/GetCapabilities",
+        GET_CAPABILITIES,
     ).await {
         Ok(response) => {
             println!("✅ Sucesso!");
-            
-            // Extrair Media Service URL
-            if let Some(media_url) = xml_utils::extract_first_tag_text(&response, "XAddr") {
+
+            let caps = parse_capabilities(&response)?;
+            if let Some(media_url) = &caps.media_url {
                 println!("   Media Service URL: {}", media_url);
             }
             
             // Verificar capacidades
-            if response.contains("PTZ") {
+            if caps.ptz {
                 println!("   ✓ Suporta PTZ");
             }
-            if response.contains("Analytics") {
+            if caps.analytics {
                 println!("   ✓ Suporta Analytics");
             }
-            if response.contains("Media") {
+            if caps.media {
                 println!("   ✓ Suporta Media");
             }
         }
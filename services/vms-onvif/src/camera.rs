@@ -54,6 +54,18 @@ pub struct CameraProfile {
     pub resolution: (u32, u32),
     /// FPS configurado
     pub framerate: f32,
+    /// Bitrate limite do encoder (kbps)
+    #[serde(default)]
+    pub bitrate_kbps: Option<u32>,
+    /// Token da configuração do encoder de vídeo (Media Service)
+    #[serde(default)]
+    pub video_encoder_token: Option<String>,
+    /// Token da fonte de vídeo (Imaging Service)
+    #[serde(default)]
+    pub video_source_token: Option<String>,
+    /// Token da configuração PTZ (None = profile sem PTZ)
+    #[serde(default)]
+    pub ptz_configuration_token: Option<String>,
 }
 
 /// Request para adicionar uma câmera
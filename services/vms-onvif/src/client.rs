@@ -1,119 +1,315 @@
-//! Cliente ONVIF com HTTP Digest Authentication
-//! Implementação completa para comunicação SOAP com câmeras ONVIF
+//! Cliente ONVIF com negociação de autenticação
+//! Comunicação SOAP com câmeras ONVIF: WS-Security UsernameToken ou HTTP Digest,
+//! SOAP 1.2 com fallback para 1.1 e compensação do relógio da câmera.
 
-use anyhow::Result;
-use reqwest::{Client, StatusCode, header};
-use std::sync::Mutex;
+use chrono::{DateTime, NaiveDate, Utc};
+use reqwest::{header, Client, StatusCode};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
 use thiserror::Error;
 use tracing::{debug, info, warn};
 use url::Url;
 
-use crate::digest_auth::{DigestChallenge, build_digest_authorization};
+use crate::digest_auth::{build_digest_authorization, DigestChallenge};
+use crate::soap::{self, SoapVersion};
+use crate::wsse;
+use crate::xml_utils::{self, ns};
+
+/// Path padrão do Device Service
+pub const DEVICE_SERVICE_PATH: &str = "/onvif/device_service";
+
+/// Tentativas por chamada (negociação de versão e autenticação)
+const MAX_ATTEMPTS: usize = 4;
+
+const GET_SYSTEM_DATE_AND_TIME: &str = "http://www.onvif.org/ver10/device/wsdl/GetSystemDateAndTime";
 
 #[derive(Error, Debug)]
 pub enum OnvifError {
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
-    
+
     #[error("URL error: {0}")]
     Url(#[from] url::ParseError),
-    
+
     #[error("Missing WWW-Authenticate digest header on 401")]
     MissingDigestHeader,
-    
+
     #[error("Could not parse digest challenge")]
     ParseDigest,
-    
+
     #[error("Unexpected status: {0}")]
     UnexpectedStatus(StatusCode),
-    
+
     #[error("SOAP fault: {0}")]
     SoapFault(String),
+
+    #[error("Not authorized (check credentials and camera clock)")]
+    NotAuthorized,
+
+    #[error("XML error: {0}")]
+    Xml(String),
+
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+}
+
+/// Autenticação negociada com a câmera
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthMode {
+    /// Sem credenciais
+    None,
+    /// WS-Security UsernameToken (PasswordDigest) no header SOAP
+    WsSecurity,
+    /// HTTP Digest (RFC 2617)
+    Digest,
 }
 
-/// Cliente ONVIF com suporte a HTTP Digest
+/// Estado negociado, reaproveitado entre chamadas
+#[derive(Debug)]
+struct Session {
+    version: SoapVersion,
+    auth: AuthMode,
+    challenge: Option<DigestChallenge>,
+    /// Relógio da câmera menos relógio local
+    clock_offset: Option<chrono::Duration>,
+}
+
+/// Cliente ONVIF
+///
+/// Clonar é barato: os clones compartilham conexão HTTP e estado negociado.
+#[derive(Clone)]
 pub struct OnvifClient {
     http: Client,
     base: Url,
     username: String,
     password: String,
-    nc: Mutex<u32>, // Nonce count
+    nc: Arc<AtomicU32>, // Nonce count
+    session: Arc<Mutex<Session>>,
 }
 
 impl OnvifClient {
     /// Cria novo cliente ONVIF
+    ///
+    /// `base` pode ser a raiz do dispositivo (`http://ip:porta`) ou a URL
+    /// completa do Device Service.
     pub fn new(base: &str, username: &str, password: &str) -> Result<Self, OnvifError> {
         let client = Client::builder()
             .timeout(std::time::Duration::from_secs(10))
             .danger_accept_invalid_certs(true) // Para câmeras com cert auto-assinado
             .build()?;
 
+        let auth = if username.is_empty() { AuthMode::None } else { AuthMode::WsSecurity };
+
         Ok(Self {
             http: client,
             base: Url::parse(base)?,
             username: username.to_string(),
             password: password.to_string(),
-            nc: Mutex::new(1),
+            nc: Arc::new(AtomicU32::new(1)),
+            session: Arc::new(Mutex::new(Session {
+                version: SoapVersion::V12,
+                auth,
+                challenge: None,
+                clock_offset: None,
+            })),
         })
     }
 
-    /// Envia requisição SOAP com HTTP Digest authentication
-    /// Usa SOAP 1.2 (application/soap+xml) para compatibilidade com Tapo C100
-    pub async fn soap_post_digest(
+    /// URL base informada na criação
+    pub fn base_url(&self) -> &Url {
+        &self.base
+    }
+
+    /// URL do Device Service
+    pub fn device_service_url(&self) -> Url {
+        if self.base.path().len() > 1 {
+            self.base.clone()
+        } else {
+            let mut url = self.base.clone();
+            url.set_path(DEVICE_SERVICE_PATH);
+            url
+        }
+    }
+
+    /// Resolve o XAddr de um serviço
+    ///
+    /// Mantém host e porta usados para alcançar a câmera: atrás de NAT o
+    /// XAddr anunciado costuma apontar para o IP interno.
+    pub fn service_url(&self, xaddr: &str) -> Result<Url, OnvifError> {
+        let announced = self.base.join(xaddr)?;
+        let mut url = self.base.clone();
+        url.set_path(announced.path());
+        url.set_query(announced.query());
+        Ok(url)
+    }
+
+    /// Autenticação em uso
+    pub fn auth_mode(&self) -> AuthMode {
+        self.session.lock().unwrap().auth
+    }
+
+    /// Versão SOAP em uso
+    pub fn soap_version(&self) -> SoapVersion {
+        self.session.lock().unwrap().version
+    }
+
+    /// Diferença entre o relógio da câmera e o local, se já sincronizado
+    pub fn clock_offset(&self) -> Option<chrono::Duration> {
+        self.session.lock().unwrap().clock_offset
+    }
+
+    /// Executa uma operação SOAP autenticada
+    ///
+    /// `service` é o path ou URL do serviço, `action` a URI da operação e
+    /// `body` o elemento da operação (com seus `xmlns`). Retorna o XML de resposta.
+    pub async fn call(&self, service: &str, action: &str, body: &str) -> Result<String, OnvifError> {
+        let url = self.base.join(service)?;
+
+        let needs_clock = {
+            let session = self.session.lock().unwrap();
+            session.auth == AuthMode::WsSecurity && session.clock_offset.is_none()
+        };
+        if needs_clock {
+            if let Err(e) = self.sync_clock().await {
+                debug!("GetSystemDateAndTime failed, assuming synced clock: {}", e);
+                self.session.lock().unwrap().clock_offset = Some(chrono::Duration::zero());
+            }
+        }
+
+        match self.exchange(&url, action, body, true).await {
+            Err(OnvifError::NotAuthorized) if self.auth_mode() == AuthMode::WsSecurity => {
+                // O relógio da câmera pode ter mudado desde a sincronização
+                self.sync_clock().await.map_err(|_| OnvifError::NotAuthorized)?;
+                self.exchange(&url, action, body, true).await
+            }
+            other => other,
+        }
+    }
+
+    /// Sincroniza o relógio com GetSystemDateAndTime (não autenticado)
+    pub async fn sync_clock(&self) -> Result<chrono::Duration, OnvifError> {
+        let body = format!(r#"<tds:GetSystemDateAndTime xmlns:tds="{}"/>"#, ns::DEVICE);
+        let response = self
+            .exchange(&self.device_service_url(), GET_SYSTEM_DATE_AND_TIME, &body, false)
+            .await?;
+
+        let camera_time = parse_system_date_and_time(&response)?;
+        let offset = camera_time - Utc::now();
+        if offset.num_seconds().abs() >= 5 {
+            info!("⏱️ Camera clock skew: {}s", offset.num_seconds());
+        }
+
+        self.session.lock().unwrap().clock_offset = Some(offset);
+        Ok(offset)
+    }
+
+    /// Envia a requisição negociando versão SOAP e autenticação
+    async fn exchange(
         &self,
-        service_path: &str,   // "/onvif/device_service"
-        soap_action: Option<&str>,
-        body_xml: &str,
+        url: &Url,
+        action: &str,
+        body: &str,
+        authenticate: bool,
     ) -> Result<String, OnvifError> {
-        let url = self.base.join(service_path)?;
-        let method = "POST";
+        let (mut version, mut auth, mut challenge, offset) = {
+            let session = self.session.lock().unwrap();
+            (
+                session.version,
+                if authenticate { session.auth } else { AuthMode::None },
+                session.challenge.clone(),
+                session.clock_offset.unwrap_or_else(chrono::Duration::zero),
+            )
+        };
+        let mut version_switched = false;
 
-        debug!("SOAP request to: {}", url);
+        debug!("SOAP {} -> {}", action, url);
 
-        // Primeira tentativa sem autenticação
-        // SOAP 1.2 usa application/soap+xml
-        let mut req = self.http
-            .post(url.clone())
-            .header(header::CONTENT_TYPE, "application/soap+xml; charset=utf-8")
-            .body(body_xml.to_string());
+        for _ in 0..MAX_ATTEMPTS {
+            let security = (auth == AuthMode::WsSecurity)
+                .then(|| wsse::username_token(&self.username, &self.password, Utc::now() + offset));
+            let mut req = self
+                .http
+                .post(url.clone())
+                .header(header::CONTENT_TYPE, version.content_type(action))
+                .body(soap::envelope(version, security.as_deref(), body));
 
-        if let Some(action) = soap_action {
-            req = req.header("SOAPAction", action);
-        }
+            if version == SoapVersion::V11 {
+                req = req.header("SOAPAction", format!("\"{}\"", action));
+            }
+            if let (AuthMode::Digest, Some(c)) = (auth, &challenge) {
+                req = req.header(header::AUTHORIZATION, self.digest_authorization(c, url));
+            }
 
-        let resp = req.send().await?;
-        let status = resp.status();
+            let resp = req.send().await?;
+            let status = resp.status();
+            let www_auth = resp
+                .headers()
+                .get(header::WWW_AUTHENTICATE)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string);
+            let text = resp.text().await?;
 
-        // Se não for 401, retorna direto
-        if status != StatusCode::UNAUTHORIZED {
             if status.is_success() {
-                let text = resp.text().await?;
                 debug!("SOAP response OK ({} bytes)", text.len());
+                let mut session = self.session.lock().unwrap();
+                session.version = version;
+                if authenticate {
+                    session.auth = auth;
+                    session.challenge = challenge;
+                }
                 return Ok(text);
             }
-            let text = resp.text().await?;
-            warn!("SOAP error {}: {}", status, &text[..text.len().min(200)]);
-            return Err(OnvifError::UnexpectedStatus(status));
-        }
 
-        // 401 - Extrair desafio Digest
-        info!("Got 401, performing Digest authentication");
-        
-        let www_auth = resp.headers()
-            .get(header::WWW_AUTHENTICATE)
-            .and_then(|v| v.to_str().ok())
-            .ok_or(OnvifError::MissingDigestHeader)?;
+            if status == StatusCode::UNAUTHORIZED {
+                if !authenticate || self.username.is_empty() {
+                    return Err(OnvifError::NotAuthorized);
+                }
+                let fresh = match www_auth.as_deref() {
+                    Some(h) if h.trim_start().to_ascii_lowercase().starts_with("digest") => {
+                        DigestChallenge::from_header(h).ok_or(OnvifError::ParseDigest)?
+                    }
+                    // 401 sem desafio Digest: WS-Security rejeitado
+                    _ if auth == AuthMode::WsSecurity => return Err(OnvifError::NotAuthorized),
+                    _ => return Err(OnvifError::MissingDigestHeader),
+                };
+                // Mesmo nonce rejeitado duas vezes: credenciais erradas
+                if auth == AuthMode::Digest && challenge.as_ref().map(|c| &c.nonce) == Some(&fresh.nonce) {
+                    return Err(OnvifError::NotAuthorized);
+                }
+                if auth != AuthMode::Digest {
+                    info!("Got 401, switching to HTTP Digest authentication");
+                }
+                auth = AuthMode::Digest;
+                challenge = Some(fresh);
+                continue;
+            }
+
+            let fault = soap::parse_fault(&text);
+            let version_mismatch = status == StatusCode::UNSUPPORTED_MEDIA_TYPE
+                || fault.as_ref().is_some_and(|f| f.is_version_mismatch());
+            if version_mismatch && !version_switched {
+                version = version.other();
+                version_switched = true;
+                info!("Camera rejected SOAP envelope, retrying with {:?}", version);
+                continue;
+            }
 
-        debug!("WWW-Authenticate: {}", www_auth);
+            return match fault {
+                Some(f) if f.is_not_authorized() => Err(OnvifError::NotAuthorized),
+                Some(f) => Err(OnvifError::SoapFault(f.to_string())),
+                None => {
+                    warn!("SOAP error {}: {}", status, &text[..text.len().min(200)]);
+                    Err(OnvifError::UnexpectedStatus(status))
+                }
+            };
+        }
 
-        let challenge = DigestChallenge::from_header(www_auth)
-            .ok_or(OnvifError::ParseDigest)?;
+        Err(OnvifError::NotAuthorized)
+    }
 
-        // Incrementa nonce count
-        let mut nc_guard = self.nc.lock().unwrap();
-        let nc = *nc_guard;
-        *nc_guard = nc.saturating_add(1);
-        drop(nc_guard);
+    /// Header Authorization Digest para a URL
+    fn digest_authorization(&self, challenge: &DigestChallenge, url: &Url) -> String {
+        let nc = self.nc.fetch_add(1, Ordering::Relaxed);
 
         // URI para digest é path + query
         let uri = match url.query() {
@@ -121,42 +317,65 @@ impl OnvifClient {
             None => url.path().to_string(),
         };
 
-        // Gera header Authorization
-        let auth = build_digest_authorization(
-            &challenge,
-            method,
-            &uri,
-            &self.username,
-            &self.password,
-            nc,
-        );
-
-        debug!("Authorization: {}", &auth[..auth.len().min(100)]);
-
-        // Segunda tentativa com autenticação
-        // SOAP 1.2 usa application/soap+xml
-        let mut req2 = self.http
-            .post(url)
-            .header(header::CONTENT_TYPE, "application/soap+xml; charset=utf-8")
-            .header(header::AUTHORIZATION, auth)
-            .body(body_xml.to_string());
-
-        if let Some(action) = soap_action {
-            req2 = req2.header("SOAPAction", action);
-        }
+        build_digest_authorization(challenge, "POST", &uri, &self.username, &self.password, nc)
+    }
+}
 
-        let resp2 = req2.send().await?;
-        let status2 = resp2.status();
-        let text = resp2.text().await?;
+/// Extrai o horário UTC de uma resposta GetSystemDateAndTime
+pub fn parse_system_date_and_time(xml: &str) -> Result<DateTime<Utc>, OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+    let utc = xml_utils::descendant(response, ns::SCHEMA, "UTCDateTime")
+        .ok_or_else(|| OnvifError::InvalidResponse("UTCDateTime ausente".to_string()))?;
 
-        if !status2.is_success() {
-            warn!("SOAP auth failed {}: {}", status2, &text[..text.len().min(200)]);
-            return Err(OnvifError::UnexpectedStatus(status2));
-        }
+    let field = |path: &[&str]| -> Option<u32> {
+        xml_utils::path(utc, ns::SCHEMA, path)
+            .and_then(xml_utils::text)
+            .and_then(|t| t.parse().ok())
+    };
+
+    let date = NaiveDate::from_ymd_opt(
+        field(&["Date", "Year"]).unwrap_or(0) as i32,
+        field(&["Date", "Month"]).unwrap_or(0),
+        field(&["Date", "Day"]).unwrap_or(0),
+    );
+    date.and_then(|d| {
+        d.and_hms_opt(
+            field(&["Time", "Hour"])?,
+            field(&["Time", "Minute"])?,
+            field(&["Time", "Second"])?,
+        )
+    })
+    .map(|naive| naive.and_utc())
+    .ok_or_else(|| OnvifError::InvalidResponse("UTCDateTime inválido".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_system_date_and_time() {
+        let xml = r#"<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:tds="http://www.onvif.org/ver10/device/wsdl" xmlns:tt="http://www.onvif.org/ver10/schema">
+            <s:Body><tds:GetSystemDateAndTimeResponse><tds:SystemDateAndTime>
+                <tt:DateTimeType>NTP</tt:DateTimeType>
+                <tt:UTCDateTime>
+                    <tt:Time><tt:Hour>7</tt:Hour><tt:Minute>50</tt:Minute><tt:Second>45</tt:Second></tt:Time>
+                    <tt:Date><tt:Year>2010</tt:Year><tt:Month>9</tt:Month><tt:Day>16</tt:Day></tt:Date>
+                </tt:UTCDateTime>
+            </tds:SystemDateAndTime></tds:GetSystemDateAndTimeResponse></s:Body>
+        </s:Envelope>"#;
+
+        let time = parse_system_date_and_time(xml).unwrap();
+        assert_eq!(time.to_rfc3339(), "2010-09-16T07:50:45+00:00");
+    }
 
-        info!("SOAP authenticated successfully");
-        debug!("Response ({} bytes)", text.len());
-        
-        Ok(text)
+    #[test]
+    fn test_service_url_keeps_reachable_host() {
+        let client = OnvifClient::new("http://203.0.113.5:8080", "admin", "pass").unwrap();
+        let url = client.service_url("http://192.168.1.10/onvif/media_service").unwrap();
+        assert_eq!(url.as_str(), "http://203.0.113.5:8080/onvif/media_service");
+        assert_eq!(client.device_service_url().path(), DEVICE_SERVICE_PATH);
+        assert_eq!(client.auth_mode(), AuthMode::WsSecurity);
     }
 }
@@ -1,19 +1,26 @@
 //! ONVIF Device - Implementação Completa
-//! Conexão e comunicação com dispositivo ONVIF usando SOAP 1.1 com WS-Addressing
+//! Device Management e Media Service sobre o `OnvifClient`
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Result};
+use vms_common::ptz::{PTZCapabilities, PTZCommand};
 use tracing::{debug, info};
 
 use crate::camera::CameraProfile;
-use crate::client::OnvifClient;
-use crate::xml_utils;
+use crate::client::{OnvifClient, OnvifError};
+use crate::device_io::OnvifDeviceIo;
+use crate::events::OnvifEvents;
+use crate::imaging::OnvifImaging;
+use crate::media::OnvifMedia;
+use crate::ptz::{self, OnvifPtz};
+use crate::system::OnvifSystem;
+use crate::xml_utils::{self, ns};
 
 /// Representa uma conexão ativa com um dispositivo ONVIF
 pub struct OnvifDevice {
     /// Cliente ONVIF
     client: OnvifClient,
-    /// URL do serviço de mídia
-    media_service_url: Option<String>,
+    /// Capacidades obtidas no `connect`
+    capabilities: Option<DeviceCapabilities>,
 }
 
 /// Informações do dispositivo ONVIF
@@ -27,7 +34,7 @@ pub struct DeviceInfo {
 }
 
 /// Capacidades do dispositivo
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct DeviceCapabilities {
     pub analytics: bool,
     pub device: bool,
@@ -35,6 +42,13 @@ pub struct DeviceCapabilities {
     pub imaging: bool,
     pub media: bool,
     pub ptz: bool,
+    /// XAddr de cada serviço anunciado
+    pub media_url: Option<String>,
+    pub ptz_url: Option<String>,
+    pub events_url: Option<String>,
+    pub imaging_url: Option<String>,
+    pub analytics_url: Option<String>,
+    pub device_io_url: Option<String>,
 }
 
 impl OnvifDevice {
@@ -44,7 +58,7 @@ impl OnvifDevice {
 
         Ok(Self {
             client,
-            media_service_url: None,
+            capabilities: None,
         })
     }
 
@@ -54,186 +68,462 @@ impl OnvifDevice {
 
         // Obter capacidades do dispositivo
         let capabilities = self.get_capabilities().await?;
-        
-        info!("✅ Conectado com sucesso");
+
+        info!(
+            "✅ Conectado com sucesso ({:?}, {:?})",
+            self.client.soap_version(),
+            self.client.auth_mode()
+        );
         debug!("Capacidades: {:?}", capabilities);
 
         Ok(())
     }
 
+    /// Cliente SOAP subjacente
+    pub fn client(&self) -> &OnvifClient {
+        &self.client
+    }
+
+    /// Capacidades obtidas no último `connect`/`get_capabilities`
+    pub fn capabilities(&self) -> Option<&DeviceCapabilities> {
+        self.capabilities.as_ref()
+    }
+
     /// Obtém capacidades do dispositivo (GetCapabilities)
-    /// Usa SOAP 1.1 com WS-Addressing para compatibilidade com Tapo C100
     pub async fn get_capabilities(&mut self) -> Result<DeviceCapabilities> {
-        let soap_body = r#"<?xml version="1.0" encoding="UTF-8"?>
-<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:a="http://www.w3.org/2005/08/addressing" xmlns:tds="http://www.onvif.org/ver10/device/wsdl">
-  <s:Header>
-    <a:Action s:mustUnderstand="1">http://www.onvif.org/ver10/device/wsdl/GetCapabilities</a:Action>
-  </s:Header>
-  <s:Body>
-    <tds:GetCapabilities>
-      <tds:Category>All</tds:Category>
-    </tds:GetCapabilities>
-  </s:Body>
-</s:Envelope>"#;
+        let body = format!(
+            r#"<tds:GetCapabilities xmlns:tds="{}"><tds:Category>All</tds:Category></tds:GetCapabilities>"#,
+            ns::DEVICE
+        );
+
+        let response = self
+            .client
+            .call(
+                crate::client::DEVICE_SERVICE_PATH,
+                "http://www.onvif.org/ver10/device/wsdl/GetCapabilities",
+                &body,
+            )
+            .await?;
 
-        let response = self.client.soap_post_digest("/onvif/device_service", None, soap_body).await?;
-        
-        // Parse básico
-        let has_media = response.contains("Media");
-        let has_ptz = response.contains("PTZ");
-        let has_analytics = response.contains("Analytics");
-        
-        // Extrair URL do serviço de mídia
-        if let Some(media_url) = xml_utils::extract_first_tag_text(&response, "XAddr") {
-            self.media_service_url = Some(media_url.clone());
+        let capabilities = parse_capabilities(&response)?;
+        if let Some(media_url) = &capabilities.media_url {
             info!("📹 Media service URL: {}", media_url);
         }
 
-        Ok(DeviceCapabilities {
-            analytics: has_analytics,
-            device: true,
-            events: response.contains("Events"),
-            imaging: response.contains("Imaging"),
-            media: has_media,
-            ptz: has_ptz,
-        })
+        self.capabilities = Some(capabilities.clone());
+        Ok(capabilities)
     }
 
     /// Obtém informações do dispositivo (GetDeviceInformation)
-    /// Usa SOAP 1.1 com WS-Addressing para compatibilidade com Tapo C100
     pub async fn get_device_info(&self) -> Result<DeviceInfo> {
-        let soap_body = r#"<?xml version="1.0" encoding="UTF-8"?>
-<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:a="http://www.w3.org/2005/08/addressing" xmlns:tds="http://www.onvif.org/ver10/device/wsdl">
-  <s:Header>
-    <a:Action s:mustUnderstand="1">http://www.onvif.org/ver10/device/wsdl/GetDeviceInformation</a:Action>
-  </s:Header>
-  <s:Body>
-    <tds:GetDeviceInformation/>
-  </s:Body>
-</s:Envelope>"#;
+        let body = format!(r#"<tds:GetDeviceInformation xmlns:tds="{}"/>"#, ns::DEVICE);
 
-        let response = self.client.soap_post_digest("/onvif/device_service", None, soap_body).await?;
+        let response = self
+            .client
+            .call(
+                crate::client::DEVICE_SERVICE_PATH,
+                "http://www.onvif.org/ver10/device/wsdl/GetDeviceInformation",
+                &body,
+            )
+            .await?;
 
-        Ok(DeviceInfo {
-            manufacturer: xml_utils::extract_first_tag_text(&response, "Manufacturer").unwrap_or_else(|| "Unknown".to_string()),
-            model: xml_utils::extract_first_tag_text(&response, "Model").unwrap_or_else(|| "Unknown".to_string()),
-            firmware_version: xml_utils::extract_first_tag_text(&response, "FirmwareVersion").unwrap_or_else(|| "Unknown".to_string()),
-            serial_number: xml_utils::extract_first_tag_text(&response, "SerialNumber").unwrap_or_else(|| "Unknown".to_string()),
-            hardware_id: xml_utils::extract_first_tag_text(&response, "HardwareId").unwrap_or_else(|| "Unknown".to_string()),
-        })
+        Ok(parse_device_info(&response)?)
     }
 
     /// Lista os profiles de mídia disponíveis (GetProfiles)
-    /// Usa SOAP 1.1 com WS-Addressing para compatibilidade com Tapo C100
     pub async fn get_profiles(&self) -> Result<Vec<CameraProfile>> {
-        // Garantir que temos a URL do serviço de mídia
-        if self.media_service_url.is_none() {
-            return Err(anyhow!("Media service URL not available. Call connect() first"));
-        }
+        let media_url = self.media_service_url()?;
+        let body = format!(r#"<trt:GetProfiles xmlns:trt="{}"/>"#, ns::MEDIA);
 
-        let soap_body = r#"<?xml version="1.0" encoding="UTF-8"?>
-<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:a="http://www.w3.org/2005/08/addressing" xmlns:trt="http://www.onvif.org/ver10/media/wsdl">
-  <s:Header>
-    <a:Action s:mustUnderstand="1">http://www.onvif.org/ver10/media/wsdl/GetProfiles</a:Action>
-  </s:Header>
-  <s:Body>
-    <trt:GetProfiles/>
-  </s:Body>
-</s:Envelope>"#;
-
-        // Extrair path da media service URL
-        let media_path = self.extract_path_from_url(self.media_service_url.as_ref().unwrap())?;
-        
-        let response = self.client.soap_post_digest(&media_path, None, soap_body).await?;
-        
-        let mut profiles = Vec::new();
-        
-        // Parse profiles - procurar por tags <trt:Profiles>
-        let mut pos = 0;
-        while let Some(profile_start) = response[pos..].find("<trt:Profiles") {
-            let abs_start = pos + profile_start;
-            
-            if let Some(profile_end) = response[abs_start..].find("</trt:Profiles>") {
-                let profile_xml = &response[abs_start..abs_start + profile_end + 15];
-                
-                // Extrair token do atributo
-                if let Some(token) = xml_utils::extract_attribute(profile_xml, "trt:Profiles", "token") {
-                    let name = xml_utils::extract_first_tag_text(profile_xml, "Name").unwrap_or_else(|| "Default".to_string());
-                    let encoding = xml_utils::extract_first_tag_text(profile_xml, "Encoding").unwrap_or_else(|| "H264".to_string());
-                    
-                    let width = xml_utils::extract_first_tag_text(profile_xml, "Width")
-                        .and_then(|w| w.parse().ok())
-                        .unwrap_or(1920);
-                    let height = xml_utils::extract_first_tag_text(profile_xml, "Height")
-                        .and_then(|h| h.parse().ok())
-                        .unwrap_or(1080);
-                    
-                    profiles.push(CameraProfile {
-                        token,
-                        name,
-                        video_encoding: encoding,
-                        resolution: (width, height),
-                        framerate: 25.0,
-                    });
-                }
-                
-                pos = abs_start + profile_end + 15;
-            } else {
-                break;
-            }
-        }
+        let response = self
+            .client
+            .call(media_url.as_str(), "http://www.onvif.org/ver10/media/wsdl/GetProfiles", &body)
+            .await?;
 
+        let profiles = parse_profiles(&response)?;
         info!("📹 Found {} media profiles", profiles.len());
         Ok(profiles)
     }
 
     /// Obtém URL RTSP do stream (GetStreamUri)
-    /// Usa SOAP 1.1 com WS-Addressing para compatibilidade com Tapo C100
     pub async fn get_stream_uri(&self, profile_token: &str) -> Result<String> {
-        if self.media_service_url.is_none() {
-            return Err(anyhow!("Media service URL not available. Call connect() first"));
-        }
-
-        let soap_body = format!(r#"<?xml version="1.0" encoding="UTF-8"?>
-<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:a="http://www.w3.org/2005/08/addressing" xmlns:trt="http://www.onvif.org/ver10/media/wsdl" xmlns:tt="http://www.onvif.org/ver10/schema">
-  <s:Header>
-    <a:Action s:mustUnderstand="1">http://www.onvif.org/ver10/media/wsdl/GetStreamUri</a:Action>
-  </s:Header>
-  <s:Body>
-    <trt:GetStreamUri>
+        let media_url = self.media_service_url()?;
+        let body = format!(
+            r#"<trt:GetStreamUri xmlns:trt="{}" xmlns:tt="{}">
       <trt:StreamSetup>
         <tt:Stream>RTP-Unicast</tt:Stream>
-        <tt:Transport>
-          <tt:Protocol>RTSP</tt:Protocol>
-        </tt:Transport>
+        <tt:Transport><tt:Protocol>RTSP</tt:Protocol></tt:Transport>
       </trt:StreamSetup>
       <trt:ProfileToken>{}</trt:ProfileToken>
-    </trt:GetStreamUri>
-  </s:Body>
-</s:Envelope>"#, profile_token);
-
-        let media_path = self.extract_path_from_url(self.media_service_url.as_ref().unwrap())?;
-        
-        let response = self.client.soap_post_digest(&media_path, None, &soap_body).await?;
-        
-        // Extrair URI
-        let uri = xml_utils::extract_first_tag_text(&response, "Uri")
-            .ok_or_else(|| anyhow!("Stream URI not found in response"))?;
+    </trt:GetStreamUri>"#,
+            ns::MEDIA,
+            ns::SCHEMA,
+            crate::wsse::escape(profile_token)
+        );
+
+        let response = self
+            .client
+            .call(media_url.as_str(), "http://www.onvif.org/ver10/media/wsdl/GetStreamUri", &body)
+            .await?;
 
+        let uri = parse_stream_uri(&response)?;
         info!("📺 Stream URI: {}", uri);
         Ok(uri)
     }
 
-    /// Extrai path de uma URL completa
-    fn extract_path_from_url(&self, url: &str) -> Result<String> {
-        use url::Url;
-        let parsed = Url::parse(url).context("Failed to parse media service URL")?;
-        Ok(parsed.path().to_string())
-    }
-
     /// Verifica se o dispositivo suporta PTZ
     pub async fn supports_ptz(&mut self) -> Result<bool> {
         let capabilities = self.get_capabilities().await?;
         Ok(capabilities.ptz)
     }
+
+    /// Serviço PTZ, se anunciado nas capacidades
+    pub fn ptz(&self) -> Option<OnvifPtz> {
+        let xaddr = self.capabilities.as_ref()?.ptz_url.as_deref()?;
+        let url = self.client.service_url(xaddr).ok()?;
+        Some(OnvifPtz::new(self.client.clone(), url.to_string()))
+    }
+
+    /// Imaging Service, se anunciado nas capacidades
+    pub fn imaging(&self) -> Option<OnvifImaging> {
+        let xaddr = self.capabilities.as_ref()?.imaging_url.as_deref()?;
+        let url = self.client.service_url(xaddr).ok()?;
+        Some(OnvifImaging::new(self.client.clone(), url.to_string()))
+    }
+
+    /// Relés (Device Management) e entradas digitais (DeviceIO, se anunciado)
+    pub fn device_io(&self) -> OnvifDeviceIo {
+        let io_service = self
+            .capabilities
+            .as_ref()
+            .and_then(|c| c.device_io_url.as_deref())
+            .and_then(|xaddr| self.client.service_url(xaddr).ok())
+            .map(|url| url.to_string());
+        OnvifDeviceIo::new(self.client.clone(), io_service)
+    }
+
+    /// Media Service (requer `connect`)
+    pub fn media(&self) -> Result<OnvifMedia> {
+        let url = self.media_service_url()?;
+        Ok(OnvifMedia::new(self.client.clone(), url.to_string()))
+    }
+
+    /// Data/hora, NTP e usuários (Device Management Service)
+    pub fn system(&self) -> OnvifSystem {
+        OnvifSystem::new(self.client.clone())
+    }
+
+    /// Event Service, se anunciado nas capacidades
+    pub fn events(&self) -> Option<OnvifEvents> {
+        let xaddr = self.capabilities.as_ref()?.events_url.as_deref()?;
+        let url = self.client.service_url(xaddr).ok()?;
+        Some(OnvifEvents::new(self.client.clone(), url.to_string()))
+    }
+
+    /// Capacidades PTZ do profile (PTZ Service + foco/íris do Imaging Service)
+    pub async fn ptz_capabilities(&self, profile: &CameraProfile) -> Result<PTZCapabilities> {
+        let ptz = self.ptz().ok_or_else(|| anyhow!("Device has no PTZ service"))?;
+        let mut capabilities = ptz.get_capabilities(profile.ptz_configuration_token.as_deref()).await?;
+
+        if let (Some(imaging), Some(source)) = (self.imaging(), profile.video_source_token.as_deref()) {
+            match imaging.get_options(source).await {
+                Ok(options) => {
+                    capabilities.focus = options.focus;
+                    capabilities.iris = options.iris;
+                }
+                Err(e) => debug!("Imaging GetOptions failed: {}", e),
+            }
+        }
+
+        Ok(capabilities)
+    }
+
+    /// Executa um `PTZCommand` no profile
+    ///
+    /// Tours são executados pelo servidor, não pela câmera.
+    pub async fn execute_ptz(&self, profile: &CameraProfile, command: &PTZCommand) -> Result<()> {
+        let ptz = self.ptz().ok_or_else(|| anyhow!("Device has no PTZ service"))?;
+        let token = profile.token.as_str();
+
+        match command {
+            PTZCommand::ContinuousMove { pan, tilt, zoom } => ptz.continuous_move(token, *pan, *tilt, *zoom).await,
+            PTZCommand::Stop => ptz.stop(token).await,
+            PTZCommand::AbsoluteMove { position, speed } => ptz.absolute_move(token, *position, *speed).await,
+            PTZCommand::RelativeMove { delta, speed } => ptz.relative_move(token, *delta, *speed).await,
+            PTZCommand::GotoPreset { preset_index, speed } => {
+                let presets = ptz.get_presets(token).await?;
+                let preset = ptz::preset_token_for_index(&presets, *preset_index)
+                    .ok_or_else(|| anyhow!("Preset {} not found", preset_index))?;
+                ptz.goto_preset(token, &preset, *speed).await
+            }
+            PTZCommand::GotoHome => ptz.goto_home(token, None).await,
+            PTZCommand::SetPreset { preset_index, name } => {
+                let presets = ptz.get_presets(token).await?;
+                // Sobrescreve o preset do índice, se existir; senão sugere o índice como token
+                let existing = ptz::preset_token_for_index(&presets, *preset_index)
+                    .unwrap_or_else(|| preset_index.to_string());
+                ptz.set_preset(token, name, Some(&existing)).await.map(|_| ())
+            }
+            PTZCommand::RemovePreset { preset_index } => {
+                let presets = ptz.get_presets(token).await?;
+                let preset = ptz::preset_token_for_index(&presets, *preset_index)
+                    .ok_or_else(|| anyhow!("Preset {} not found", preset_index))?;
+                ptz.remove_preset(token, &preset).await
+            }
+            PTZCommand::StartTour { .. } | PTZCommand::StopTour => {
+                bail!("PTZ tours are run by the server, not by the camera")
+            }
+            PTZCommand::AutoFocus => {
+                let (imaging, source) = self.with_imaging(profile)?;
+                imaging.set_auto_focus(source).await
+            }
+            PTZCommand::ManualFocus { direction } => {
+                let (imaging, source) = self.with_imaging(profile)?;
+                imaging.move_focus(source, *direction).await
+            }
+            PTZCommand::AutoIris => {
+                let (imaging, source) = self.with_imaging(profile)?;
+                imaging.set_auto_iris(source).await
+            }
+            PTZCommand::ManualIris { direction } => {
+                let (imaging, source) = self.with_imaging(profile)?;
+                imaging.step_iris(source, *direction).await
+            }
+            PTZCommand::AuxFunction { function, enabled } => {
+                // Formato ONVIF: "tt:Wiper|On"
+                let command = if function.contains('|') {
+                    function.clone()
+                } else {
+                    format!("{}|{}", function, if *enabled { "On" } else { "Off" })
+                };
+                ptz.send_auxiliary_command(token, &command).await
+            }
+        }
+    }
+
+    /// Imaging Service e fonte de vídeo do profile
+    fn with_imaging<'a>(&self, profile: &'a CameraProfile) -> Result<(OnvifImaging, &'a str)> {
+        let imaging = self.imaging().ok_or_else(|| anyhow!("Device has no imaging service"))?;
+        let source = profile
+            .video_source_token
+            .as_deref()
+            .ok_or_else(|| anyhow!("Profile {} has no video source", profile.token))?;
+        Ok((imaging, source))
+    }
+
+    /// URL do Media Service (requer `connect`)
+    fn media_service_url(&self) -> Result<url::Url> {
+        let xaddr = self
+            .capabilities
+            .as_ref()
+            .and_then(|c| c.media_url.as_deref())
+            .ok_or_else(|| anyhow!("Media service URL not available. Call connect() first"))?;
+        Ok(self.client.service_url(xaddr)?)
+    }
+}
+
+/// Parse da resposta GetCapabilities
+pub fn parse_capabilities(xml: &str) -> Result<DeviceCapabilities, OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+    let caps = xml_utils::child(response, ns::DEVICE, "Capabilities")
+        .ok_or_else(|| OnvifError::InvalidResponse("Capabilities ausente".to_string()))?;
+
+    let xaddr = |service: &str| {
+        xml_utils::child(caps, ns::SCHEMA, service)
+            .and_then(|s| xml_utils::child_text(s, ns::SCHEMA, "XAddr"))
+    };
+    let device_io_url = xml_utils::path(caps, ns::SCHEMA, &["Extension", "DeviceIO"])
+        .and_then(|s| xml_utils::child_text(s, ns::SCHEMA, "XAddr"));
+
+    let media_url = xaddr("Media");
+    let ptz_url = xaddr("PTZ");
+    let events_url = xaddr("Events");
+    let imaging_url = xaddr("Imaging");
+    let analytics_url = xaddr("Analytics");
+
+    Ok(DeviceCapabilities {
+        analytics: analytics_url.is_some(),
+        device: xml_utils::child(caps, ns::SCHEMA, "Device").is_some(),
+        events: events_url.is_some(),
+        imaging: imaging_url.is_some(),
+        media: media_url.is_some(),
+        ptz: ptz_url.is_some(),
+        media_url,
+        ptz_url,
+        events_url,
+        imaging_url,
+        analytics_url,
+        device_io_url,
+    })
+}
+
+/// Parse da resposta GetDeviceInformation
+pub fn parse_device_info(xml: &str) -> Result<DeviceInfo, OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+    let field = |name: &str| {
+        xml_utils::child_text(response, ns::DEVICE, name).unwrap_or_else(|| "Unknown".to_string())
+    };
+
+    Ok(DeviceInfo {
+        manufacturer: field("Manufacturer"),
+        model: field("Model"),
+        firmware_version: field("FirmwareVersion"),
+        serial_number: field("SerialNumber"),
+        hardware_id: field("HardwareId"),
+    })
+}
+
+/// Parse da resposta GetProfiles
+pub fn parse_profiles(xml: &str) -> Result<Vec<CameraProfile>, OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+
+    let profiles = xml_utils::children(response, ns::MEDIA, "Profiles")
+        .filter_map(|profile| {
+            let token = profile.attribute("token")?.to_string();
+            let encoder = xml_utils::child(profile, ns::SCHEMA, "VideoEncoderConfiguration");
+            let number = |path: &[&str]| {
+                encoder
+                    .and_then(|e| xml_utils::path(e, ns::SCHEMA, path))
+                    .and_then(xml_utils::text)
+            };
+
+            Some(CameraProfile {
+                token,
+                name: xml_utils::child_text(profile, ns::SCHEMA, "Name").unwrap_or_else(|| "Default".to_string()),
+                video_encoding: number(&["Encoding"]).unwrap_or_else(|| "H264".to_string()),
+                resolution: (
+                    number(&["Resolution", "Width"]).and_then(|w| w.parse().ok()).unwrap_or(1920),
+                    number(&["Resolution", "Height"]).and_then(|h| h.parse().ok()).unwrap_or(1080),
+                ),
+                framerate: number(&["RateControl", "FrameRateLimit"])
+                    .and_then(|f| f.parse().ok())
+                    .unwrap_or(25.0),
+                bitrate_kbps: number(&["RateControl", "BitrateLimit"]).and_then(|b| b.parse().ok()),
+                video_encoder_token: encoder.and_then(|e| e.attribute("token")).map(str::to_string),
+                video_source_token: xml_utils::child(profile, ns::SCHEMA, "VideoSourceConfiguration")
+                    .and_then(|c| xml_utils::child_text(c, ns::SCHEMA, "SourceToken")),
+                ptz_configuration_token: xml_utils::child(profile, ns::SCHEMA, "PTZConfiguration")
+                    .and_then(|c| c.attribute("token"))
+                    .map(str::to_string),
+            })
+        })
+        .collect();
+
+    Ok(profiles)
+}
+
+/// Parse da resposta GetStreamUri
+pub fn parse_stream_uri(xml: &str) -> Result<String, OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+
+    xml_utils::path(response, ns::MEDIA, &["MediaUri"])
+        .and_then(|media_uri| xml_utils::child_text(media_uri, ns::SCHEMA, "Uri"))
+        .ok_or_else(|| OnvifError::InvalidResponse("Stream URI not found in response".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_device_info() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope">
+    <s:Body>
+        <tds:GetDeviceInformationResponse xmlns:tds="http://www.onvif.org/ver10/device/wsdl">
+            <tds:Manufacturer>Hikvision</tds:Manufacturer>
+            <tds:Model>DS-2CD2143G0-I</tds:Model>
+            <tds:FirmwareVersion>V5.6.3</tds:FirmwareVersion>
+            <tds:SerialNumber>DS2CD2143G0I20190101AAWRC12345678</tds:SerialNumber>
+            <tds:HardwareId>88</tds:HardwareId>
+        </tds:GetDeviceInformationResponse>
+    </s:Body>
+</s:Envelope>"#;
+
+        let info = parse_device_info(xml).unwrap();
+        assert_eq!(info.manufacturer, "Hikvision");
+        assert_eq!(info.model, "DS-2CD2143G0-I");
+        assert_eq!(info.firmware_version, "V5.6.3");
+    }
+
+    #[test]
+    fn test_parse_capabilities() {
+        let xml = r#"<SOAP-ENV:Envelope xmlns:SOAP-ENV="http://www.w3.org/2003/05/soap-envelope" xmlns:tds="http://www.onvif.org/ver10/device/wsdl" xmlns:tt="http://www.onvif.org/ver10/schema">
+    <SOAP-ENV:Body><tds:GetCapabilitiesResponse><tds:Capabilities>
+        <tt:Device><tt:XAddr>http://192.168.1.169:2020/onvif/device_service</tt:XAddr></tt:Device>
+        <tt:Events><tt:XAddr>http://192.168.1.169:2020/onvif/service</tt:XAddr></tt:Events>
+        <tt:Media><tt:XAddr>http://192.168.1.169:2020/onvif/service</tt:XAddr></tt:Media>
+        <tt:PTZ><tt:XAddr>http://192.168.1.169:2020/onvif/service</tt:XAddr></tt:PTZ>
+        <tt:Extension><tt:DeviceIO><tt:XAddr>http://192.168.1.169:2020/onvif/deviceio</tt:XAddr></tt:DeviceIO></tt:Extension>
+    </tds:Capabilities></tds:GetCapabilitiesResponse></SOAP-ENV:Body>
+</SOAP-ENV:Envelope>"#;
+
+        let caps = parse_capabilities(xml).unwrap();
+        assert!(caps.device && caps.media && caps.ptz && caps.events);
+        assert!(!caps.imaging && !caps.analytics);
+        assert_eq!(caps.media_url.as_deref(), Some("http://192.168.1.169:2020/onvif/service"));
+        assert_eq!(caps.device_io_url.as_deref(), Some("http://192.168.1.169:2020/onvif/deviceio"));
+    }
+
+    #[test]
+    fn test_parse_profiles() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope">
+    <s:Body>
+        <trt:GetProfilesResponse xmlns:trt="http://www.onvif.org/ver10/media/wsdl">
+            <trt:Profiles token="Profile_1" fixed="true">
+                <tt:Name xmlns:tt="http://www.onvif.org/ver10/schema">MainStream</tt:Name>
+                <tt:VideoSourceConfiguration xmlns:tt="http://www.onvif.org/ver10/schema" token="VSC_1">
+                    <tt:SourceToken>VideoSource_1</tt:SourceToken>
+                </tt:VideoSourceConfiguration>
+                <tt:VideoEncoderConfiguration xmlns:tt="http://www.onvif.org/ver10/schema">
+                    <tt:Encoding>H264</tt:Encoding>
+                    <tt:Resolution>
+                        <tt:Width>1920</tt:Width>
+                        <tt:Height>1080</tt:Height>
+                    </tt:Resolution>
+                    <tt:RateControl>
+                        <tt:FrameRateLimit>30</tt:FrameRateLimit>
+                        <tt:BitrateLimit>4096</tt:BitrateLimit>
+                    </tt:RateControl>
+                </tt:VideoEncoderConfiguration>
+                <tt:PTZConfiguration xmlns:tt="http://www.onvif.org/ver10/schema" token="PTZ_1"/>
+            </trt:Profiles>
+        </trt:GetProfilesResponse>
+    </s:Body>
+</s:Envelope>"#;
+
+        let profiles = parse_profiles(xml).unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].token, "Profile_1");
+        assert_eq!(profiles[0].name, "MainStream");
+        assert_eq!(profiles[0].resolution, (1920, 1080));
+        assert_eq!(profiles[0].video_encoding, "H264");
+        assert_eq!(profiles[0].framerate, 30.0);
+        assert_eq!(profiles[0].bitrate_kbps, Some(4096));
+        assert_eq!(profiles[0].video_source_token.as_deref(), Some("VideoSource_1"));
+        assert_eq!(profiles[0].ptz_configuration_token.as_deref(), Some("PTZ_1"));
+    }
+
+    #[test]
+    fn test_parse_stream_uri() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope">
+    <s:Body>
+        <trt:GetStreamUriResponse xmlns:trt="http://www.onvif.org/ver10/media/wsdl">
+            <trt:MediaUri>
+                <tt:Uri xmlns:tt="http://www.onvif.org/ver10/schema">rtsp://192.168.1.100:554/Streaming/Channels/101</tt:Uri>
+                <tt:InvalidAfterConnect xmlns:tt="http://www.onvif.org/ver10/schema">false</tt:InvalidAfterConnect>
+            </trt:MediaUri>
+        </trt:GetStreamUriResponse>
+    </s:Body>
+</s:Envelope>"#;
+
+        let uri = parse_stream_uri(xml).unwrap();
+        assert_eq!(uri, "rtsp://192.168.1.100:554/Streaming/Channels/101");
+    }
 }
@@ -0,0 +1,295 @@
+//! ONVIF Device I/O
+//! Saídas a relé (Device Management Service) e entradas digitais
+//! (DeviceIO Service)
+
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::client::{OnvifClient, OnvifError, DEVICE_SERVICE_PATH};
+use crate::wsse::escape;
+use crate::xml_utils::{self, ns};
+
+/// Modo do relé
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelayMode {
+    /// Volta ao repouso após `delay_time` (pulso)
+    Monostable,
+    /// Mantém o estado até novo comando
+    Bistable,
+}
+
+impl RelayMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Monostable => "Monostable",
+            Self::Bistable => "Bistable",
+        }
+    }
+}
+
+/// Saída a relé
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelayOutput {
+    pub token: String,
+    pub mode: RelayMode,
+    /// Duração do pulso no modo monoestável
+    pub delay_time: Duration,
+    /// Estado de repouso: `open` ou `closed`
+    pub idle_state: String,
+}
+
+/// Entrada digital
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigitalInput {
+    pub token: String,
+    /// Estado de repouso: `open` ou `closed`
+    pub idle_state: Option<String>,
+}
+
+/// Cliente de I/O de um dispositivo
+#[derive(Clone)]
+pub struct OnvifDeviceIo {
+    client: OnvifClient,
+    /// URL do DeviceIO Service (entradas digitais), se anunciado
+    io_service: Option<String>,
+}
+
+impl OnvifDeviceIo {
+    pub fn new(client: OnvifClient, io_service: Option<String>) -> Self {
+        Self { client, io_service }
+    }
+
+    /// Saídas a relé (GetRelayOutputs)
+    pub async fn get_relay_outputs(&self) -> Result<Vec<RelayOutput>> {
+        let body = format!(r#"<tds:GetRelayOutputs xmlns:tds="{}"/>"#, ns::DEVICE);
+        let response = self.call_device("GetRelayOutputs", &body).await?;
+        Ok(parse_relay_outputs(&response)?)
+    }
+
+    /// Entradas digitais (GetDigitalInputs); vazio sem DeviceIO Service
+    pub async fn get_digital_inputs(&self) -> Result<Vec<DigitalInput>> {
+        let Some(service) = &self.io_service else {
+            return Ok(Vec::new());
+        };
+
+        let body = format!(r#"<tmd:GetDigitalInputs xmlns:tmd="{}"/>"#, ns::DEVICE_IO);
+        let action = format!("{}/GetDigitalInputs", ns::DEVICE_IO);
+        let response = self.client.call(service, &action, &body).await?;
+        Ok(parse_digital_inputs(&response)?)
+    }
+
+    /// Configura modo, tempo de pulso e repouso (SetRelayOutputSettings)
+    pub async fn set_relay_output_settings(&self, relay: &RelayOutput) -> Result<()> {
+        let body = format!(
+            r#"<tds:SetRelayOutputSettings xmlns:tds="{}" xmlns:tt="{}">
+      <tds:RelayOutputToken>{}</tds:RelayOutputToken>
+      <tds:Properties>
+        <tt:Mode>{}</tt:Mode>
+        <tt:DelayTime>{}</tt:DelayTime>
+        <tt:IdleState>{}</tt:IdleState>
+      </tds:Properties>
+    </tds:SetRelayOutputSettings>"#,
+            ns::DEVICE,
+            ns::SCHEMA,
+            escape(&relay.token),
+            relay.mode.as_str(),
+            format_duration(relay.delay_time),
+            escape(&relay.idle_state)
+        );
+
+        self.call_device("SetRelayOutputSettings", &body).await.map(|_| ())
+    }
+
+    /// Ativa ou desativa o relé (SetRelayOutputState)
+    pub async fn set_relay_output_state(&self, token: &str, active: bool) -> Result<()> {
+        let body = format!(
+            r#"<tds:SetRelayOutputState xmlns:tds="{}"><tds:RelayOutputToken>{}</tds:RelayOutputToken><tds:LogicalState>{}</tds:LogicalState></tds:SetRelayOutputState>"#,
+            ns::DEVICE,
+            escape(token),
+            if active { "active" } else { "inactive" }
+        );
+
+        self.call_device("SetRelayOutputState", &body).await.map(|_| ())
+    }
+
+    /// Mantém o relé no estado pedido (passa para biestável se necessário)
+    pub async fn set_relay(&self, relay: &RelayOutput, active: bool) -> Result<()> {
+        if relay.mode != RelayMode::Bistable {
+            self.set_relay_output_settings(&RelayOutput {
+                mode: RelayMode::Bistable,
+                ..relay.clone()
+            })
+            .await?;
+        }
+        self.set_relay_output_state(&relay.token, active).await
+    }
+
+    /// Pulso: ativa o relé e a câmera o devolve ao repouso após `duration`
+    pub async fn pulse_relay(&self, relay: &RelayOutput, duration: Duration) -> Result<()> {
+        debug!("📡 Relay {} pulse {:?}", relay.token, duration);
+        if relay.mode != RelayMode::Monostable || relay.delay_time != duration {
+            self.set_relay_output_settings(&RelayOutput {
+                mode: RelayMode::Monostable,
+                delay_time: duration,
+                ..relay.clone()
+            })
+            .await?;
+        }
+        self.set_relay_output_state(&relay.token, true).await
+    }
+
+    async fn call_device(&self, operation: &str, body: &str) -> Result<String> {
+        let action = format!("{}/{}", ns::DEVICE, operation);
+        Ok(self.client.call(DEVICE_SERVICE_PATH, &action, body).await?)
+    }
+}
+
+/// Número da porta a partir do token (`AlarmOut_2`, `DigitalInputToken1` → 2, 1)
+pub fn io_port_number(token: &str) -> Option<u8> {
+    let digits: String = token
+        .chars()
+        .rev()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(char::is_ascii_digit)
+        .collect();
+    digits.chars().rev().collect::<String>().parse().ok()
+}
+
+/// Números das portas de uma câmera (relés ou entradas, na ordem devolvida
+/// por ela): pelo token quando todos são distintos, senão a posição (1..)
+///
+/// É a numeração usada no registro de I/O e nos eventos de entrada digital.
+pub fn io_port_numbers<T: AsRef<str>>(tokens: &[T]) -> Vec<u8> {
+    let numbers: Vec<u8> = tokens.iter().filter_map(|t| io_port_number(t.as_ref())).collect();
+    let mut distinct = numbers.clone();
+    distinct.sort_unstable();
+    distinct.dedup();
+
+    if distinct.len() == tokens.len() {
+        numbers
+    } else {
+        (1..=tokens.len() as u8).collect()
+    }
+}
+
+/// Parse da resposta GetRelayOutputs
+pub fn parse_relay_outputs(xml: &str) -> Result<Vec<RelayOutput>, OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+
+    Ok(xml_utils::children(response, ns::DEVICE, "RelayOutputs")
+        .filter_map(|relay| {
+            let properties = xml_utils::child(relay, ns::SCHEMA, "Properties");
+            let field = |name: &str| properties.and_then(|p| xml_utils::child_text(p, ns::SCHEMA, name));
+
+            Some(RelayOutput {
+                token: relay.attribute("token")?.to_string(),
+                mode: match field("Mode").as_deref() {
+                    Some("Monostable") => RelayMode::Monostable,
+                    _ => RelayMode::Bistable,
+                },
+                delay_time: field("DelayTime").and_then(|d| parse_duration(&d)).unwrap_or_default(),
+                idle_state: field("IdleState").unwrap_or_else(|| "open".to_string()),
+            })
+        })
+        .collect())
+}
+
+/// Parse da resposta GetDigitalInputs
+pub fn parse_digital_inputs(xml: &str) -> Result<Vec<DigitalInput>, OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+
+    Ok(xml_utils::children(response, ns::DEVICE_IO, "DigitalInputs")
+        .filter_map(|input| {
+            Some(DigitalInput {
+                token: input.attribute("token")?.to_string(),
+                idle_state: input.attribute("IdleState").map(str::to_string),
+            })
+        })
+        .collect())
+}
+
+/// Duração xs:duration simples (`PT1M30.5S`)
+fn parse_duration(value: &str) -> Option<Duration> {
+    let time = value.trim().strip_prefix("PT")?;
+    let mut seconds = 0.0;
+    let mut number = String::new();
+    for c in time.chars() {
+        match c {
+            '0'..='9' | '.' => number.push(c),
+            'H' | 'M' | 'S' => {
+                let unit = match c {
+                    'H' => 3600.0,
+                    'M' => 60.0,
+                    _ => 1.0,
+                };
+                seconds += number.parse::<f64>().ok()? * unit;
+                number.clear();
+            }
+            _ => return None,
+        }
+    }
+    Some(Duration::from_secs_f64(seconds))
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("PT{}S", duration.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_relay_outputs_and_inputs() {
+        let relays = r#"<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:tds="http://www.onvif.org/ver10/device/wsdl" xmlns:tt="http://www.onvif.org/ver10/schema">
+            <s:Body><tds:GetRelayOutputsResponse>
+                <tds:RelayOutputs token="AlarmOut_1">
+                    <tt:Properties><tt:Mode>Monostable</tt:Mode><tt:DelayTime>PT1M2.5S</tt:DelayTime><tt:IdleState>closed</tt:IdleState></tt:Properties>
+                </tds:RelayOutputs>
+                <tds:RelayOutputs token="AlarmOut_2">
+                    <tt:Properties><tt:Mode>Bistable</tt:Mode><tt:DelayTime>PT0S</tt:DelayTime><tt:IdleState>open</tt:IdleState></tt:Properties>
+                </tds:RelayOutputs>
+            </tds:GetRelayOutputsResponse></s:Body>
+        </s:Envelope>"#;
+
+        let outputs = parse_relay_outputs(relays).unwrap();
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0].mode, RelayMode::Monostable);
+        assert_eq!(outputs[0].delay_time, Duration::from_millis(62_500));
+        assert_eq!(outputs[0].idle_state, "closed");
+        assert_eq!(outputs[1].mode, RelayMode::Bistable);
+
+        let inputs = r#"<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:tmd="http://www.onvif.org/ver10/deviceIO/wsdl">
+            <s:Body><tmd:GetDigitalInputsResponse>
+                <tmd:DigitalInputs token="DigitalInputToken0" IdleState="closed"/>
+            </tmd:GetDigitalInputsResponse></s:Body>
+        </s:Envelope>"#;
+
+        let inputs = parse_digital_inputs(inputs).unwrap();
+        assert_eq!(inputs[0].token, "DigitalInputToken0");
+        assert_eq!(inputs[0].idle_state.as_deref(), Some("closed"));
+    }
+
+    #[test]
+    fn test_port_number_and_duration_format() {
+        assert_eq!(io_port_number("AlarmOut_2"), Some(2));
+        assert_eq!(io_port_number("DigitalInputToken10"), Some(10));
+        assert_eq!(io_port_number("relay"), None);
+        assert_eq!(format_duration(Duration::from_millis(1500)), "PT1.5S");
+        assert_eq!(parse_duration("PT2H"), Some(Duration::from_secs(7200)));
+    }
+
+    #[test]
+    fn test_port_numbers() {
+        assert_eq!(io_port_numbers(&["AlarmOut_1", "AlarmOut_2"]), vec![1, 2]);
+        assert_eq!(io_port_numbers(&["DigitalInputToken0", "DigitalInputToken1"]), vec![0, 1]);
+        assert_eq!(io_port_numbers(&["RelayA", "RelayB"]), vec![1, 2]);
+        assert_eq!(io_port_numbers(&["Out_3", "Out_3"]), vec![1, 2]);
+    }
+}
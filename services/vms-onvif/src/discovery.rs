@@ -1,16 +1,88 @@
 //! ONVIF WS-Discovery
-//! Descobre câmeras ONVIF na rede local usando WS-Discovery
+//! Descobre câmeras ONVIF na rede local usando WS-Discovery (SOAP over UDP)
+//!
+//! - Ativa: Probe multicast para `dn:NetworkVideoTransmitter`, coletando ProbeMatches
+//! - Passiva: escuta anúncios Hello/Bye no grupo multicast
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
 use std::time::Duration;
-use tracing::{info, warn};
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
 
 use crate::camera::Camera;
 
+/// Grupo multicast WS-Discovery
+pub const WS_DISCOVERY_MULTICAST: SocketAddrV4 =
+    SocketAddrV4::new(Ipv4Addr::new(239, 255, 255, 250), WS_DISCOVERY_PORT);
+
+/// Porta WS-Discovery
+pub const WS_DISCOVERY_PORT: u16 = 3702;
+
+/// Namespace WS-Discovery (2005/04)
+pub const NS_DISCOVERY: &str = "http://schemas.xmlsoap.org/ws/2005/04/discovery";
+const ACTION_HELLO: &str = "http://schemas.xmlsoap.org/ws/2005/04/discovery/Hello";
+const ACTION_BYE: &str = "http://schemas.xmlsoap.org/ws/2005/04/discovery/Bye";
+
+/// Dispositivo anunciado via WS-Discovery
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiscoveredDevice {
+    /// EndpointReference (ex: `urn:uuid:...`), estável entre reinícios
+    pub endpoint: String,
+    /// URLs do device service
+    pub xaddrs: Vec<String>,
+    /// Tipos anunciados (ex: `dn:NetworkVideoTransmitter`)
+    pub types: Vec<String>,
+    /// Scopes ONVIF brutos
+    pub scopes: Vec<String>,
+    /// Nome (`onvif://www.onvif.org/name/...`)
+    pub name: Option<String>,
+    /// Hardware/modelo (`onvif://www.onvif.org/hardware/...`)
+    pub hardware: Option<String>,
+    /// Localização (`onvif://www.onvif.org/location/...`)
+    pub location: Option<String>,
+    /// IP de origem da resposta
+    pub ip: Option<String>,
+}
+
+impl DiscoveredDevice {
+    /// URL do device service (primeiro XAddr)
+    pub fn service_url(&self) -> Option<&str> {
+        self.xaddrs.first().map(|s| s.as_str())
+    }
+
+    /// Converte para `Camera` (sem perfis; use `OnvifDevice` para detalhes)
+    pub fn to_camera(&self) -> Camera {
+        let name = self
+            .name
+            .clone()
+            .or_else(|| self.ip.clone())
+            .unwrap_or_else(|| self.endpoint.clone());
+        let mut camera = Camera::new(name, self.service_url().unwrap_or_default().to_string());
+        camera.hardware_id = self.hardware.clone();
+        camera.model = self.hardware.clone();
+        camera
+    }
+}
+
+/// Anúncio recebido na descoberta passiva
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiscoveryEvent {
+    /// Dispositivo entrou na rede
+    Hello(DiscoveredDevice),
+    /// Dispositivo saiu da rede
+    Bye { endpoint: String },
+}
+
 /// Serviço de descoberta ONVIF
 pub struct OnvifDiscovery {
     /// Timeout para descoberta
     timeout: Duration,
+    /// Destino do Probe (multicast por padrão; unicast em testes)
+    target: SocketAddr,
 }
 
 impl OnvifDiscovery {
@@ -18,6 +90,7 @@ impl OnvifDiscovery {
     pub fn new() -> Self {
         Self {
             timeout: Duration::from_secs(5),
+            target: SocketAddr::V4(WS_DISCOVERY_MULTICAST),
         }
     }
 
@@ -27,24 +100,109 @@ impl OnvifDiscovery {
         self
     }
 
+    /// Envia o Probe para outro destino (ex: responder local ou IP específico)
+    pub fn with_target(mut self, target: SocketAddr) -> Self {
+        self.target = target;
+        self
+    }
+
     /// Descobre câmeras ONVIF na rede local
-    /// 
-    /// NOTA: WS-Discovery requer implementação UDP multicast
-    /// Por enquanto retorna lista vazia - será implementado em fase futura
-    pub async fn discover(&self, _timeout: Duration) -> Result<Vec<Camera>> {
-        info!("🔍 WS-Discovery não implementado ainda");
-        warn!("Use configuração manual de câmeras por enquanto");
-        
-        // TODO: Implementar WS-Discovery (SOAP over UDP multicast)
-        // Referência: https://www.onvif.org/specs/core/ONVIF-Core-Specification.pdf
-        
-        Ok(Vec::new())
-    }
-    
+    pub async fn discover(&self, timeout: Duration) -> Result<Vec<Camera>> {
+        let devices = self.probe_with_timeout(timeout).await?;
+        Ok(devices.iter().map(DiscoveredDevice::to_camera).collect())
+    }
+
     /// Descoberta com probe específico para NetworkVideoTransmitter
     pub async fn discover_nvt(&self) -> Result<Vec<Camera>> {
         self.discover(self.timeout).await
     }
+
+    /// Probe ativo, retornando os dispositivos com scopes e XAddrs
+    pub async fn probe(&self) -> Result<Vec<DiscoveredDevice>> {
+        self.probe_with_timeout(self.timeout).await
+    }
+
+    async fn probe_with_timeout(&self, timeout: Duration) -> Result<Vec<DiscoveredDevice>> {
+        info!("🔍 Sending WS-Discovery probe to {}", self.target);
+
+        let socket = UdpSocket::bind("0.0.0.0:0")
+            .await
+            .context("Failed to bind UDP socket for discovery")?;
+        socket
+            .set_multicast_ttl_v4(4)
+            .context("Failed to set multicast TTL")?;
+
+        let message_id = format!("uuid:{}", uuid::Uuid::new_v4());
+        socket
+            .send_to(build_probe(&message_id).as_bytes(), self.target)
+            .await
+            .context("Failed to send discovery probe")?;
+
+        let mut devices: Vec<DiscoveredDevice> = Vec::new();
+        let mut seen = HashSet::new();
+        let mut buf = vec![0u8; 65_535];
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        loop {
+            let (size, addr) = match tokio::time::timeout_at(deadline, socket.recv_from(&mut buf)).await {
+                Ok(Ok(received)) => received,
+                Ok(Err(e)) if is_transient(&e) => continue,
+                Ok(Err(e)) => {
+                    // Erro persistente do socket: repetir só giraria a CPU até o deadline
+                    warn!("⚠️  Error receiving discovery responses, stopping probe: {}", e);
+                    break;
+                }
+                Err(_) => break,
+            };
+
+            let xml = String::from_utf8_lossy(&buf[..size]);
+            debug!("📥 Discovery response from {}: {} bytes", addr, size);
+
+            // Ignora respostas a probes de outros clientes
+            if relates_to(&xml).is_some_and(|id| id != message_id) {
+                continue;
+            }
+
+            match parse_probe_matches(&xml) {
+                Ok(matches) => {
+                    for mut device in matches {
+                        if seen.insert(device.endpoint.clone()) {
+                            device.ip = Some(addr.ip().to_string());
+                            info!(
+                                "✅ Found ONVIF device: {} at {}",
+                                device.name.as_deref().unwrap_or("Unknown"),
+                                device.service_url().unwrap_or("-")
+                            );
+                            devices.push(device);
+                        }
+                    }
+                }
+                Err(e) => warn!("⚠️  Failed to parse response from {}: {}", addr, e),
+            }
+        }
+
+        info!("✅ Discovery complete: found {} devices", devices.len());
+        Ok(devices)
+    }
+
+    /// Descoberta passiva: escuta Hello/Bye no grupo multicast até o canal fechar
+    pub async fn listen(&self, tx: mpsc::Sender<DiscoveryEvent>) -> Result<()> {
+        let socket = bind_multicast_listener().context("Failed to join WS-Discovery group")?;
+        info!("👂 Listening for WS-Discovery Hello/Bye on {}", WS_DISCOVERY_MULTICAST);
+
+        let mut buf = vec![0u8; 65_535];
+        loop {
+            let (size, addr) = socket.recv_from(&mut buf).await?;
+            let xml = String::from_utf8_lossy(&buf[..size]);
+
+            if let Some(event) = parse_announcement(&xml, Some(addr)) {
+                debug!("📣 WS-Discovery announcement from {}: {:?}", addr, event);
+                if tx.send(event).await.is_err() {
+                    return Ok(());
+                }
+            }
+        }
+    }
 }
 
 impl Default for OnvifDiscovery {
@@ -53,13 +211,264 @@ impl Default for OnvifDiscovery {
     }
 }
 
+/// Erros de recepção que não indicam falha do socket
+fn is_transient(e: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+    matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted)
+}
+
+/// Socket UDP em 0.0.0.0:3702 com SO_REUSEADDR, inscrito no grupo multicast
+fn bind_multicast_listener() -> Result<UdpSocket> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.bind(&SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, WS_DISCOVERY_PORT).into())?;
+    socket.join_multicast_v4(WS_DISCOVERY_MULTICAST.ip(), &Ipv4Addr::UNSPECIFIED)?;
+    socket.set_nonblocking(true)?;
+
+    Ok(UdpSocket::from_std(socket.into())?)
+}
+
+/// Monta mensagem Probe para NetworkVideoTransmitter
+pub fn build_probe(message_id: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:a="http://schemas.xmlsoap.org/ws/2004/08/addressing" xmlns:d="{ns}" xmlns:dn="http://www.onvif.org/ver10/network/wsdl">
+    <s:Header>
+        <a:Action s:mustUnderstand="1">http://schemas.xmlsoap.org/ws/2005/04/discovery/Probe</a:Action>
+        <a:MessageID>{message_id}</a:MessageID>
+        <a:ReplyTo>
+            <a:Address>http://schemas.xmlsoap.org/ws/2004/08/addressing/role/anonymous</a:Address>
+        </a:ReplyTo>
+        <a:To s:mustUnderstand="1">urn:schemas-xmlsoap-org:ws:2005:04:discovery</a:To>
+    </s:Header>
+    <s:Body>
+        <d:Probe>
+            <d:Types>dn:NetworkVideoTransmitter</d:Types>
+        </d:Probe>
+    </s:Body>
+</s:Envelope>"#,
+        ns = NS_DISCOVERY,
+        message_id = message_id,
+    )
+}
+
+/// Extrai todos os ProbeMatch de uma resposta
+pub fn parse_probe_matches(xml: &str) -> Result<Vec<DiscoveredDevice>> {
+    let doc = roxmltree::Document::parse(xml).context("Failed to parse XML response")?;
+
+    let devices = doc
+        .descendants()
+        .filter(|n| n.tag_name().name() == "ProbeMatch")
+        .filter_map(parse_device_node)
+        .collect();
+
+    Ok(devices)
+}
+
+/// Interpreta anúncio Hello/Bye (descoberta passiva)
+pub fn parse_announcement(xml: &str, from: Option<SocketAddr>) -> Option<DiscoveryEvent> {
+    let doc = roxmltree::Document::parse(xml).ok()?;
+    let action = child_text(doc.root(), "Action")?;
+
+    if action == ACTION_HELLO {
+        let node = doc.descendants().find(|n| n.tag_name().name() == "Hello")?;
+        let mut device = parse_device_node(node)?;
+        device.ip = from.map(|addr| addr.ip().to_string());
+        Some(DiscoveryEvent::Hello(device))
+    } else if action == ACTION_BYE {
+        let node = doc.descendants().find(|n| n.tag_name().name() == "Bye")?;
+        Some(DiscoveryEvent::Bye {
+            endpoint: child_text(node, "Address")?,
+        })
+    } else {
+        None
+    }
+}
+
+/// `RelatesTo` do cabeçalho (MessageID do Probe respondido)
+fn relates_to(xml: &str) -> Option<String> {
+    let doc = roxmltree::Document::parse(xml).ok()?;
+    child_text(doc.root(), "RelatesTo")
+}
+
+/// ProbeMatch/Hello: EndpointReference, Types, Scopes, XAddrs
+fn parse_device_node(node: roxmltree::Node) -> Option<DiscoveredDevice> {
+    let endpoint = child_text(node, "Address")?;
+    let list = |tag: &str| -> Vec<String> {
+        child_text(node, tag)
+            .map(|text| text.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default()
+    };
+
+    let scopes = list("Scopes");
+    let scope_value = |kind: &str| {
+        let prefix = format!("onvif://www.onvif.org/{}/", kind);
+        scopes
+            .iter()
+            .find_map(|s| s.strip_prefix(&prefix))
+            .map(percent_decode)
+    };
+
+    Some(DiscoveredDevice {
+        endpoint,
+        xaddrs: list("XAddrs"),
+        types: list("Types"),
+        name: scope_value("name"),
+        hardware: scope_value("hardware"),
+        location: scope_value("location"),
+        scopes,
+        ip: None,
+    })
+}
+
+fn child_text(node: roxmltree::Node, tag: &str) -> Option<String> {
+    node.descendants()
+        .find(|n| n.tag_name().name() == tag)
+        .and_then(|n| n.text())
+        .map(|t| t.trim().to_string())
+}
+
+/// Decodifica `%XX` dos scopes (ex: `Front%20Door` → `Front Door`)
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn probe_match(relates_to: &str, endpoint: &str, xaddr: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<SOAP-ENV:Envelope xmlns:SOAP-ENV="http://www.w3.org/2003/05/soap-envelope" xmlns:wsa="http://schemas.xmlsoap.org/ws/2004/08/addressing" xmlns:d="http://schemas.xmlsoap.org/ws/2005/04/discovery" xmlns:dn="http://www.onvif.org/ver10/network/wsdl">
+    <SOAP-ENV:Header>
+        <wsa:Action>http://schemas.xmlsoap.org/ws/2005/04/discovery/ProbeMatches</wsa:Action>
+        <wsa:RelatesTo>{relates_to}</wsa:RelatesTo>
+    </SOAP-ENV:Header>
+    <SOAP-ENV:Body>
+        <d:ProbeMatches>
+            <d:ProbeMatch>
+                <wsa:EndpointReference><wsa:Address>{endpoint}</wsa:Address></wsa:EndpointReference>
+                <d:Types>dn:NetworkVideoTransmitter tds:Device</d:Types>
+                <d:Scopes>onvif://www.onvif.org/type/video_encoder onvif://www.onvif.org/name/Front%20Door onvif://www.onvif.org/hardware/C100 onvif://www.onvif.org/location/country/brazil</d:Scopes>
+                <d:XAddrs>{xaddr} http://[fe80::1]/onvif/device_service</d:XAddrs>
+                <d:MetadataVersion>1</d:MetadataVersion>
+            </d:ProbeMatch>
+        </d:ProbeMatches>
+    </SOAP-ENV:Body>
+</SOAP-ENV:Envelope>"#
+        )
+    }
+
     #[tokio::test]
     async fn test_discovery_creates_instance() {
         let discovery = OnvifDiscovery::new();
         assert_eq!(discovery.timeout, Duration::from_secs(5));
     }
+
+    #[test]
+    fn test_receive_errors_that_stop_the_probe() {
+        use std::io::{Error, ErrorKind};
+
+        assert!(is_transient(&Error::from(ErrorKind::WouldBlock)));
+        assert!(is_transient(&Error::from(ErrorKind::TimedOut)));
+        assert!(!is_transient(&Error::from(ErrorKind::PermissionDenied)));
+        assert!(!is_transient(&Error::from(ErrorKind::NotConnected)));
+    }
+
+    #[test]
+    fn test_parse_probe_match_scopes() {
+        let xml = probe_match("uuid:1", "urn:uuid:abc", "http://192.168.1.100:2020/onvif/device_service");
+        let devices = parse_probe_matches(&xml).unwrap();
+
+        assert_eq!(devices.len(), 1);
+        let device = &devices[0];
+        assert_eq!(device.endpoint, "urn:uuid:abc");
+        assert_eq!(device.service_url(), Some("http://192.168.1.100:2020/onvif/device_service"));
+        assert_eq!(device.xaddrs.len(), 2);
+        assert_eq!(device.name.as_deref(), Some("Front Door"));
+        assert_eq!(device.hardware.as_deref(), Some("C100"));
+        assert_eq!(device.location.as_deref(), Some("country/brazil"));
+        assert!(device.types.iter().any(|t| t.ends_with("NetworkVideoTransmitter")));
+    }
+
+    #[test]
+    fn test_parse_hello_and_bye() {
+        let hello = r#"<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:a="http://schemas.xmlsoap.org/ws/2004/08/addressing" xmlns:d="http://schemas.xmlsoap.org/ws/2005/04/discovery">
+            <s:Header><a:Action>http://schemas.xmlsoap.org/ws/2005/04/discovery/Hello</a:Action></s:Header>
+            <s:Body><d:Hello>
+                <a:EndpointReference><a:Address>urn:uuid:cam-1</a:Address></a:EndpointReference>
+                <d:Scopes>onvif://www.onvif.org/name/Lobby</d:Scopes>
+                <d:XAddrs>http://10.0.0.5/onvif/device_service</d:XAddrs>
+            </d:Hello></s:Body></s:Envelope>"#;
+        let bye = hello.replace("Hello", "Bye");
+
+        match parse_announcement(hello, Some("10.0.0.5:3702".parse().unwrap())) {
+            Some(DiscoveryEvent::Hello(device)) => {
+                assert_eq!(device.endpoint, "urn:uuid:cam-1");
+                assert_eq!(device.name.as_deref(), Some("Lobby"));
+                assert_eq!(device.ip.as_deref(), Some("10.0.0.5"));
+            }
+            other => panic!("unexpected: {:?}", other),
+        }
+        assert_eq!(
+            parse_announcement(&bye, None),
+            Some(DiscoveryEvent::Bye { endpoint: "urn:uuid:cam-1".to_string() })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_probe_against_local_responder() {
+        let responder = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let target = responder.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; 65_535];
+            let (size, from) = responder.recv_from(&mut buf).await.unwrap();
+            let probe = String::from_utf8_lossy(&buf[..size]).to_string();
+            assert!(probe.contains("NetworkVideoTransmitter"));
+
+            let doc = roxmltree::Document::parse(&probe).unwrap();
+            let message_id = child_text(doc.root(), "MessageID").unwrap();
+
+            // Resposta de outro probe deve ser ignorada
+            let stale = probe_match("uuid:other", "urn:uuid:stale", "http://10.0.0.9/onvif/device_service");
+            responder.send_to(stale.as_bytes(), from).await.unwrap();
+
+            let reply = probe_match(&message_id, "urn:uuid:local", "http://127.0.0.1:8080/onvif/device_service");
+            responder.send_to(reply.as_bytes(), from).await.unwrap();
+            // Duplicata (câmeras respondem em várias interfaces)
+            responder.send_to(reply.as_bytes(), from).await.unwrap();
+        });
+
+        let devices = OnvifDiscovery::new()
+            .with_target(target)
+            .with_timeout(Duration::from_millis(500))
+            .probe()
+            .await
+            .unwrap();
+
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].endpoint, "urn:uuid:local");
+        assert_eq!(devices[0].ip.as_deref(), Some("127.0.0.1"));
+    }
 }
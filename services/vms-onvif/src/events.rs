@@ -0,0 +1,360 @@
+//! ONVIF Event Service (PullPoint)
+//! Assinatura de eventos da câmera (movimento, sabotagem, entradas digitais)
+//! e conversão para `vms_common::event::Event`
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use roxmltree::Node;
+use tracing::debug;
+use vms_common::analytics::TamperKind;
+use vms_common::event::{Event, EventCategory, EventSeverity, EventTrigger};
+use vms_common::types::CameraId;
+
+use crate::client::{OnvifClient, OnvifError};
+use crate::device_io::io_port_numbers;
+use crate::xml_utils::{self, ns};
+
+/// Ações WS-BaseNotification do SubscriptionManager
+const PULL_MESSAGES_ACTION: &str =
+    "http://www.onvif.org/ver10/events/wsdl/PullPointSubscription/PullMessagesRequest";
+const RENEW_ACTION: &str = "http://docs.oasis-open.org/wsn/bw-2/SubscriptionManager/RenewRequest";
+const UNSUBSCRIBE_ACTION: &str =
+    "http://docs.oasis-open.org/wsn/bw-2/SubscriptionManager/UnsubscribeRequest";
+
+/// Assinatura PullPoint ativa
+#[derive(Debug, Clone)]
+pub struct PullPointSubscription {
+    /// Endereço do SubscriptionManager (já apontando para o host configurado)
+    pub address: String,
+}
+
+/// Mensagem de notificação (`wsnt:NotificationMessage` / `tt:Message`)
+#[derive(Debug, Clone, Default)]
+pub struct NotificationMessage {
+    /// Tópico sem prefixos de namespace, ex. `RuleEngine/CellMotionDetector/Motion`
+    pub topic: String,
+    pub utc_time: Option<DateTime<Utc>>,
+    /// `Initialized`, `Changed` ou `Deleted`
+    pub property_operation: Option<String>,
+    /// SimpleItems de `tt:Source`
+    pub source: HashMap<String, String>,
+    /// SimpleItems de `tt:Data`
+    pub data: HashMap<String, String>,
+}
+
+impl NotificationMessage {
+    /// Estado booleano do evento (primeiro item de dados reconhecido)
+    pub fn state(&self) -> Option<bool> {
+        ["IsMotion", "State", "LogicalState", "IsTamper", "IsInside"]
+            .iter()
+            .find_map(|name| self.data.get(*name))
+            .and_then(|value| match value.to_ascii_lowercase().as_str() {
+                "true" | "1" | "on" | "active" => Some(true),
+                "false" | "0" | "off" | "inactive" => Some(false),
+                _ => None,
+            })
+    }
+
+    /// Estado inicial enviado ao assinar (não é uma transição)
+    pub fn is_initial_state(&self) -> bool {
+        self.property_operation.as_deref() == Some("Initialized")
+    }
+}
+
+/// Cliente do Event Service de um dispositivo
+#[derive(Clone)]
+pub struct OnvifEvents {
+    client: OnvifClient,
+    /// URL do Event Service
+    service: String,
+}
+
+impl OnvifEvents {
+    pub fn new(client: OnvifClient, service: String) -> Self {
+        Self { client, service }
+    }
+
+    /// Cria uma assinatura PullPoint (CreatePullPointSubscription)
+    pub async fn create_pull_point_subscription(&self, termination_secs: u64) -> Result<PullPointSubscription> {
+        let body = format!(
+            r#"<tev:CreatePullPointSubscription xmlns:tev="{}"><tev:InitialTerminationTime>PT{}S</tev:InitialTerminationTime></tev:CreatePullPointSubscription>"#,
+            ns::EVENTS,
+            termination_secs
+        );
+
+        let action = format!("{}/EventPortType/CreatePullPointSubscriptionRequest", ns::EVENTS);
+        let response = self.client.call(&self.service, &action, &body).await?;
+        let address = parse_subscription_address(&response)?;
+        let address = self.client.service_url(&address)?.to_string();
+        debug!("📡 PullPoint subscription: {}", address);
+
+        Ok(PullPointSubscription { address })
+    }
+
+    /// Busca mensagens pendentes, aguardando até `timeout_secs` (PullMessages)
+    pub async fn pull_messages(
+        &self,
+        subscription: &PullPointSubscription,
+        timeout_secs: u64,
+        limit: u32,
+    ) -> Result<Vec<NotificationMessage>> {
+        let body = format!(
+            r#"<tev:PullMessages xmlns:tev="{}"><tev:Timeout>PT{}S</tev:Timeout><tev:MessageLimit>{}</tev:MessageLimit></tev:PullMessages>"#,
+            ns::EVENTS,
+            timeout_secs,
+            limit
+        );
+
+        let response = self
+            .client
+            .call(&subscription.address, PULL_MESSAGES_ACTION, &body)
+            .await?;
+        Ok(parse_messages(&response)?)
+    }
+
+    /// Estende a validade da assinatura (Renew)
+    pub async fn renew(&self, subscription: &PullPointSubscription, termination_secs: u64) -> Result<()> {
+        let body = format!(
+            r#"<wsnt:Renew xmlns:wsnt="{}"><wsnt:TerminationTime>PT{}S</wsnt:TerminationTime></wsnt:Renew>"#,
+            ns::WSNT,
+            termination_secs
+        );
+
+        self.client
+            .call(&subscription.address, RENEW_ACTION, &body)
+            .await
+            .map(|_| ())
+            .map_err(Into::into)
+    }
+
+    /// Encerra a assinatura (Unsubscribe)
+    pub async fn unsubscribe(&self, subscription: &PullPointSubscription) -> Result<()> {
+        let body = format!(r#"<wsnt:Unsubscribe xmlns:wsnt="{}"/>"#, ns::WSNT);
+
+        self.client
+            .call(&subscription.address, UNSUBSCRIBE_ACTION, &body)
+            .await
+            .map(|_| ())
+            .map_err(Into::into)
+    }
+}
+
+/// Endereço do SubscriptionManager na resposta de CreatePullPointSubscription
+pub fn parse_subscription_address(xml: &str) -> Result<String, OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+
+    xml_utils::child(response, ns::EVENTS, "SubscriptionReference")
+        .and_then(|reference| xml_utils::child_text(reference, ns::WSA, "Address"))
+        .ok_or_else(|| OnvifError::InvalidResponse("SubscriptionReference sem Address".to_string()))
+}
+
+/// Mensagens de uma resposta PullMessages
+pub fn parse_messages(xml: &str) -> Result<Vec<NotificationMessage>, OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+
+    Ok(xml_utils::children(response, ns::WSNT, "NotificationMessage")
+        .filter_map(|notification| {
+            let topic = xml_utils::child_text(notification, ns::WSNT, "Topic")?;
+            let message = xml_utils::child(notification, ns::WSNT, "Message")
+                .and_then(|m| xml_utils::child(m, ns::SCHEMA, "Message"))?;
+
+            Some(NotificationMessage {
+                topic: strip_topic_prefixes(&topic),
+                utc_time: message
+                    .attribute("UtcTime")
+                    .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                    .map(|t| t.with_timezone(&Utc)),
+                property_operation: message.attribute("PropertyOperation").map(str::to_string),
+                source: simple_items(message, "Source"),
+                data: simple_items(message, "Data"),
+            })
+        })
+        .collect())
+}
+
+/// `tns1:RuleEngine/tnsx:CellMotionDetector/Motion` → `RuleEngine/CellMotionDetector/Motion`
+fn strip_topic_prefixes(topic: &str) -> String {
+    topic
+        .split('/')
+        .map(|segment| segment.rsplit(':').next().unwrap_or(segment))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn simple_items(message: Node, section: &str) -> HashMap<String, String> {
+    xml_utils::child(message, ns::SCHEMA, section)
+        .map(|node| {
+            xml_utils::children(node, ns::SCHEMA, "SimpleItem")
+                .filter_map(|item| Some((item.attribute("Name")?.to_string(), item.attribute("Value")?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Converte uma notificação da câmera em evento do VMS
+///
+/// Mensagens `Initialized` (estado inicial ao assinar) são ignoradas.
+/// Movimento e sabotagem geram evento apenas no início da condição; entradas
+/// digitais geram evento em ambas as transições, com a porta numerada por
+/// [`io_port_numbers`] sobre `input_tokens` (GetDigitalInputs da câmera).
+pub fn to_event(camera_id: CameraId, message: &NotificationMessage, input_tokens: &[String]) -> Option<Event> {
+    if message.is_initial_state() {
+        return None;
+    }
+    let state = message.state()?;
+    let topic = message.topic.as_str();
+
+    let mut event = if topic.starts_with("Device/Trigger/DigitalInput") {
+        let input = message.source.get("InputToken").cloned().unwrap_or_default();
+        let port = input_port(&input, input_tokens);
+        let trigger = EventTrigger::DigitalIO {
+            device_id: camera_id.to_string(),
+            port,
+            state,
+        };
+        let text = format!("Digital input {} {}", input, if state { "active" } else { "inactive" });
+        let mut event = Event::new(trigger, EventCategory::IO, &text).with_severity(EventSeverity::Warning);
+        event.camera_id = Some(camera_id);
+        event.metadata.insert("input_token".to_string(), input);
+        event
+    } else if !state {
+        return None;
+    } else if topic.contains("MotionDetector") || topic == "VideoSource/MotionAlarm" {
+        let zone_id = ["Rule", "VideoAnalyticsConfigurationToken"]
+            .iter()
+            .find_map(|name| message.source.get(*name).cloned());
+        // Sensibilidade é configurada na câmera e não vem no evento
+        let trigger = EventTrigger::MotionDetected {
+            camera_id,
+            zone_id,
+            sensitivity: 0.0,
+        };
+        Event::new(trigger, EventCategory::Motion, "Motion detected by camera")
+    } else if topic == "VideoSource/ImageTooDark" {
+        let trigger = EventTrigger::CameraObstructed { camera_id };
+        Event::new(trigger, EventCategory::System, "Camera obstructed (image too dark)")
+            .with_severity(EventSeverity::Warning)
+    } else {
+        let kind = match topic {
+            "VideoSource/ImageTooBlurry" => TamperKind::Defocus,
+            "VideoSource/GlobalSceneChange/ImagingService" | "VideoSource/GlobalSceneChange/AnalyticsService" => {
+                TamperKind::SceneChange
+            }
+            "VideoSource/SignalLoss" => TamperKind::VideoLoss,
+            _ => return None,
+        };
+        let trigger = EventTrigger::CameraTampered { camera_id, kind };
+        let text = format!("Camera tampering: {}", kind.as_str());
+        Event::new(trigger, EventCategory::System, &text).with_severity(EventSeverity::Warning)
+    };
+
+    if let Some(utc_time) = message.utc_time {
+        event.timestamp = utc_time;
+    }
+    event.metadata.insert("source".to_string(), "onvif".to_string());
+    event.metadata.insert("onvif_topic".to_string(), message.topic.clone());
+    Some(event)
+}
+
+/// Porta de uma entrada digital na numeração do registro de I/O; entrada
+/// fora da lista (lista indisponível) é numerada sozinha
+fn input_port(input: &str, input_tokens: &[String]) -> u8 {
+    match input_tokens.iter().position(|token| token == input) {
+        Some(index) => io_port_numbers(input_tokens)[index],
+        None => io_port_numbers(&[input])[0],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PULL_RESPONSE: &str = r#"<env:Envelope xmlns:env="http://www.w3.org/2003/05/soap-envelope" xmlns:tev="http://www.onvif.org/ver10/events/wsdl" xmlns:wsnt="http://docs.oasis-open.org/wsn/b-2" xmlns:tt="http://www.onvif.org/ver10/schema" xmlns:tns1="http://www.onvif.org/ver10/topics">
+        <env:Body><tev:PullMessagesResponse>
+            <tev:CurrentTime>2024-05-01T10:00:00Z</tev:CurrentTime>
+            <tev:TerminationTime>2024-05-01T10:01:00Z</tev:TerminationTime>
+            <wsnt:NotificationMessage>
+                <wsnt:Topic Dialect="http://www.onvif.org/ver10/tev/topicExpression/ConcreteSet">tns1:RuleEngine/CellMotionDetector/Motion</wsnt:Topic>
+                <wsnt:Message><tt:Message UtcTime="2024-05-01T10:00:00Z" PropertyOperation="Changed">
+                    <tt:Source>
+                        <tt:SimpleItem Name="VideoSourceConfigurationToken" Value="VideoSourceToken"/>
+                        <tt:SimpleItem Name="Rule" Value="MyMotionDetectorRule"/>
+                    </tt:Source>
+                    <tt:Data><tt:SimpleItem Name="IsMotion" Value="true"/></tt:Data>
+                </tt:Message></wsnt:Message>
+            </wsnt:NotificationMessage>
+            <wsnt:NotificationMessage>
+                <wsnt:Topic Dialect="http://www.onvif.org/ver10/tev/topicExpression/ConcreteSet">tns1:Device/tns1:Trigger/DigitalInput</wsnt:Topic>
+                <wsnt:Message><tt:Message UtcTime="2024-05-01T10:00:01Z" PropertyOperation="Changed">
+                    <tt:Source><tt:SimpleItem Name="InputToken" Value="DigitalInput_2"/></tt:Source>
+                    <tt:Data><tt:SimpleItem Name="LogicalState" Value="false"/></tt:Data>
+                </tt:Message></wsnt:Message>
+            </wsnt:NotificationMessage>
+            <wsnt:NotificationMessage>
+                <wsnt:Topic Dialect="http://www.onvif.org/ver10/tev/topicExpression/ConcreteSet">tns1:VideoSource/ImageTooDark</wsnt:Topic>
+                <wsnt:Message><tt:Message UtcTime="2024-05-01T10:00:02Z" PropertyOperation="Initialized">
+                    <tt:Source><tt:SimpleItem Name="VideoSource" Value="VideoSourceToken"/></tt:Source>
+                    <tt:Data><tt:SimpleItem Name="State" Value="true"/></tt:Data>
+                </tt:Message></wsnt:Message>
+            </wsnt:NotificationMessage>
+        </tev:PullMessagesResponse></env:Body>
+    </env:Envelope>"#;
+
+    #[test]
+    fn test_parse_messages() {
+        let messages = parse_messages(PULL_RESPONSE).unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].topic, "RuleEngine/CellMotionDetector/Motion");
+        assert_eq!(messages[0].source.get("Rule").map(String::as_str), Some("MyMotionDetectorRule"));
+        assert_eq!(messages[0].state(), Some(true));
+        assert_eq!(messages[1].topic, "Device/Trigger/DigitalInput");
+        assert_eq!(messages[1].state(), Some(false));
+        assert!(messages[2].is_initial_state());
+    }
+
+    #[test]
+    fn test_map_to_events() {
+        let camera_id = CameraId::new();
+        let messages = parse_messages(PULL_RESPONSE).unwrap();
+
+        let motion = to_event(camera_id, &messages[0], &[]).unwrap();
+        assert!(matches!(
+            motion.trigger,
+            EventTrigger::MotionDetected { zone_id: Some(ref zone), .. } if zone == "MyMotionDetectorRule"
+        ));
+        assert_eq!(motion.camera_id, Some(camera_id));
+
+        let input = to_event(camera_id, &messages[1], &[]).unwrap();
+        assert!(matches!(input.trigger, EventTrigger::DigitalIO { port: 2, state: false, .. }));
+
+        // Mesma numeração do registro de I/O: tokens repetidos → posição
+        let tokens = ["DigitalInput_2".to_string(), "Alarm_2".to_string()];
+        let input = to_event(camera_id, &messages[1], &tokens).unwrap();
+        assert!(matches!(input.trigger, EventTrigger::DigitalIO { port: 1, .. }));
+
+        // Estado inicial não é evento
+        assert!(to_event(camera_id, &messages[2], &[]).is_none());
+
+        let mut dark = messages[2].clone();
+        dark.property_operation = Some("Changed".to_string());
+        assert!(matches!(to_event(camera_id, &dark, &[]).unwrap().trigger, EventTrigger::CameraObstructed { .. }));
+    }
+
+    #[test]
+    fn test_parse_subscription_address() {
+        let xml = r#"<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:tev="http://www.onvif.org/ver10/events/wsdl" xmlns:wsa5="http://www.w3.org/2005/08/addressing">
+            <s:Body><tev:CreatePullPointSubscriptionResponse>
+                <tev:SubscriptionReference><wsa5:Address>http://192.168.1.10/onvif/Subscription?Idx=0</wsa5:Address></tev:SubscriptionReference>
+            </tev:CreatePullPointSubscriptionResponse></s:Body>
+        </s:Envelope>"#;
+
+        assert_eq!(
+            parse_subscription_address(xml).unwrap(),
+            "http://192.168.1.10/onvif/Subscription?Idx=0"
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! ONVIF Imaging Service
+//! Foco e íris da lente (complemento do PTZ)
+
+use anyhow::{bail, Result};
+use tracing::debug;
+
+use crate::client::{OnvifClient, OnvifError};
+use crate::wsse::escape;
+use crate::xml_utils::{self, ns};
+
+/// Passo da íris manual por comando
+const IRIS_STEP: f32 = 1.0;
+
+/// Velocidade do foco contínuo
+const FOCUS_SPEED: f32 = 0.5;
+
+/// Opções de imagem suportadas pela fonte de vídeo
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImagingOptions {
+    /// Suporta ajuste de foco
+    pub focus: bool,
+    /// Suporta ajuste de íris
+    pub iris: bool,
+    /// Faixa da íris (min, max)
+    pub iris_range: Option<(f32, f32)>,
+}
+
+/// Cliente do Imaging Service de um dispositivo
+#[derive(Clone)]
+pub struct OnvifImaging {
+    client: OnvifClient,
+    /// URL do Imaging Service
+    service: String,
+}
+
+impl OnvifImaging {
+    pub fn new(client: OnvifClient, service: String) -> Self {
+        Self { client, service }
+    }
+
+    /// Opções suportadas (GetOptions)
+    pub async fn get_options(&self, video_source_token: &str) -> Result<ImagingOptions> {
+        let body = format!(
+            r#"<timg:GetOptions xmlns:timg="{}"><timg:VideoSourceToken>{}</timg:VideoSourceToken></timg:GetOptions>"#,
+            ns::IMAGING,
+            escape(video_source_token)
+        );
+
+        Ok(parse_options(&self.call("GetOptions", &body).await?)?)
+    }
+
+    /// Foco contínuo: `direction` -1 = perto, 1 = longe, 0 = parar
+    pub async fn move_focus(&self, video_source_token: &str, direction: i8) -> Result<()> {
+        if direction == 0 {
+            return self.stop(video_source_token).await;
+        }
+        debug!("📡 Imaging focus move: {}", direction);
+
+        let body = format!(
+            r#"<timg:Move xmlns:timg="{}" xmlns:tt="{}">
+      <timg:VideoSourceToken>{}</timg:VideoSourceToken>
+      <timg:Focus><tt:Continuous><tt:Speed>{}</tt:Speed></tt:Continuous></timg:Focus>
+    </timg:Move>"#,
+            ns::IMAGING,
+            ns::SCHEMA,
+            escape(video_source_token),
+            FOCUS_SPEED * direction.signum() as f32
+        );
+
+        self.call("Move", &body).await.map(|_| ())
+    }
+
+    /// Para o movimento de foco
+    pub async fn stop(&self, video_source_token: &str) -> Result<()> {
+        let body = format!(
+            r#"<timg:Stop xmlns:timg="{}"><timg:VideoSourceToken>{}</timg:VideoSourceToken></timg:Stop>"#,
+            ns::IMAGING,
+            escape(video_source_token)
+        );
+
+        self.call("Stop", &body).await.map(|_| ())
+    }
+
+    /// Ativa foco automático
+    pub async fn set_auto_focus(&self, video_source_token: &str) -> Result<()> {
+        self.set_settings(video_source_token, "<tt:Focus><tt:AutoFocusMode>AUTO</tt:AutoFocusMode></tt:Focus>")
+            .await
+    }
+
+    /// Ativa exposição (e íris) automática
+    pub async fn set_auto_iris(&self, video_source_token: &str) -> Result<()> {
+        self.set_settings(video_source_token, "<tt:Exposure><tt:Mode>AUTO</tt:Mode></tt:Exposure>")
+            .await
+    }
+
+    /// Íris manual: `direction` -1 = fechar, 1 = abrir
+    pub async fn step_iris(&self, video_source_token: &str, direction: i8) -> Result<()> {
+        let options = self.get_options(video_source_token).await?;
+        let Some((min, max)) = options.iris_range else {
+            bail!("Iris not adjustable on video source {}", video_source_token);
+        };
+
+        let body = format!(
+            r#"<timg:GetImagingSettings xmlns:timg="{}"><timg:VideoSourceToken>{}</timg:VideoSourceToken></timg:GetImagingSettings>"#,
+            ns::IMAGING,
+            escape(video_source_token)
+        );
+        let current = parse_iris(&self.call("GetImagingSettings", &body).await?)?.unwrap_or(min);
+        let iris = (current + IRIS_STEP * direction.signum() as f32).clamp(min, max);
+        debug!("📡 Imaging iris: {} -> {}", current, iris);
+
+        self.set_settings(
+            video_source_token,
+            &format!("<tt:Exposure><tt:Mode>MANUAL</tt:Mode><tt:Iris>{}</tt:Iris></tt:Exposure>", iris),
+        )
+        .await
+    }
+
+    async fn set_settings(&self, video_source_token: &str, settings: &str) -> Result<()> {
+        let body = format!(
+            r#"<timg:SetImagingSettings xmlns:timg="{}" xmlns:tt="{}">
+      <timg:VideoSourceToken>{}</timg:VideoSourceToken>
+      <timg:ImagingSettings>{}</timg:ImagingSettings>
+      <timg:ForcePersistence>true</timg:ForcePersistence>
+    </timg:SetImagingSettings>"#,
+            ns::IMAGING,
+            ns::SCHEMA,
+            escape(video_source_token),
+            settings
+        );
+
+        self.call("SetImagingSettings", &body).await.map(|_| ())
+    }
+
+    async fn call(&self, operation: &str, body: &str) -> Result<String> {
+        let action = format!("{}/{}", ns::IMAGING, operation);
+        Ok(self.client.call(&self.service, &action, body).await?)
+    }
+}
+
+/// Parse da resposta GetOptions
+pub fn parse_options(xml: &str) -> Result<ImagingOptions, OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+    let Some(options) = xml_utils::child(response, ns::IMAGING, "ImagingOptions") else {
+        return Ok(ImagingOptions::default());
+    };
+
+    let iris_range = xml_utils::path(options, ns::SCHEMA, &["Exposure", "Iris"]).and_then(|iris| {
+        let bound = |local: &str| xml_utils::child_text(iris, ns::SCHEMA, local)?.parse().ok();
+        Some((bound("Min")?, bound("Max")?))
+    });
+
+    Ok(ImagingOptions {
+        focus: xml_utils::child(options, ns::SCHEMA, "Focus").is_some(),
+        iris: iris_range.is_some(),
+        iris_range,
+    })
+}
+
+/// Valor atual da íris em GetImagingSettings
+fn parse_iris(xml: &str) -> Result<Option<f32>, OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+    Ok(xml_utils::child(response, ns::IMAGING, "ImagingSettings")
+        .and_then(|settings| xml_utils::path(settings, ns::SCHEMA, &["Exposure", "Iris"]))
+        .and_then(xml_utils::text)
+        .and_then(|v| v.parse().ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_options() {
+        let xml = r#"<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:timg="http://www.onvif.org/ver20/imaging/wsdl" xmlns:tt="http://www.onvif.org/ver10/schema">
+            <s:Body><timg:GetOptionsResponse><timg:ImagingOptions>
+                <tt:Exposure><tt:Mode>AUTO</tt:Mode><tt:Mode>MANUAL</tt:Mode>
+                    <tt:Iris><tt:Min>-22</tt:Min><tt:Max>0</tt:Max></tt:Iris>
+                </tt:Exposure>
+                <tt:Focus><tt:AutoFocusModes>AUTO</tt:AutoFocusModes></tt:Focus>
+            </timg:ImagingOptions></timg:GetOptionsResponse></s:Body>
+        </s:Envelope>"#;
+
+        let options = parse_options(xml).unwrap();
+        assert!(options.focus && options.iris);
+        assert_eq!(options.iris_range, Some((-22.0, 0.0)));
+    }
+}
@@ -1,14 +1,29 @@
 //! VMS ONVIF Library
-//! Cliente ONVIF com HTTP Digest Authentication
+//! Cliente ONVIF unificado: WS-Security ou HTTP Digest negociados
+//! automaticamente, SOAP 1.1/1.2 e compensação do relógio da câmera
 
 pub mod digest_auth;
+pub mod wsse;
+pub mod soap;
 pub mod client;
 pub mod discovery;
 pub mod device;
+pub mod device_io;
+pub mod ptz;
+pub mod imaging;
+pub mod media;
+pub mod events;
+pub mod system;
 pub mod camera;
 pub mod xml_utils;
 
-pub use client::{OnvifClient, OnvifError};
-pub use discovery::OnvifDiscovery;
-pub use device::OnvifDevice;
+pub use client::{AuthMode, OnvifClient, OnvifError};
+pub use discovery::{DiscoveredDevice, DiscoveryEvent, OnvifDiscovery};
+pub use device::{DeviceCapabilities, DeviceInfo, OnvifDevice};
+pub use device_io::{DigitalInput, OnvifDeviceIo, RelayMode, RelayOutput};
+pub use ptz::{OnvifPreset, OnvifPtz, PtzStatus};
+pub use imaging::{ImagingOptions, OnvifImaging};
+pub use media::{OnvifMedia, VideoEncoderConfiguration, VideoEncoderOptions};
+pub use system::{DateTimeMode, OnvifSystem, OnvifUser, UserLevel};
+pub use events::{NotificationMessage, OnvifEvents, PullPointSubscription};
 pub use camera::{Camera, CameraProfile};
@@ -5,18 +5,7 @@ use anyhow::Result;
 use tracing::info;
 use tracing_subscriber;
 
-mod digest_auth;
-mod client;
-mod discovery;
-mod device;
-mod camera;
-
-pub mod xml_utils;
-
-pub use client::{OnvifClient, OnvifError};
-pub use discovery::OnvifDiscovery;
-pub use device::OnvifDevice;
-pub use camera::{Camera, CameraProfile};
+use vms_onvif::OnvifDiscovery;
 
 #[tokio::main]
 async fn main() -> Result<()> {
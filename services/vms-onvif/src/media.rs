@@ -0,0 +1,462 @@
+//! ONVIF Media Service - configuração do encoder de vídeo
+//! GetVideoEncoderConfiguration(s), GetVideoEncoderConfigurationOptions e
+//! SetVideoEncoderConfiguration (Media 1)
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use vms_common::media_profile::{BitrateMode, MediaProfile, VideoCodec};
+
+use crate::client::{OnvifClient, OnvifError};
+use crate::wsse::escape;
+use crate::xml_utils::{self, ns};
+
+/// Configuração de multicast do encoder (preservada ao reescrever)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Multicast {
+    pub address: String,
+    pub port: u16,
+    pub ttl: u32,
+    pub auto_start: bool,
+}
+
+impl Default for Multicast {
+    fn default() -> Self {
+        Self {
+            address: "0.0.0.0".to_string(),
+            port: 0,
+            ttl: 0,
+            auto_start: false,
+        }
+    }
+}
+
+/// VideoEncoderConfiguration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VideoEncoderConfiguration {
+    pub token: String,
+    pub name: String,
+    pub use_count: u32,
+    /// `H264`, `JPEG` ou `MPEG4`
+    pub encoding: String,
+    pub width: u32,
+    pub height: u32,
+    pub quality: f32,
+    pub frame_rate_limit: u32,
+    pub encoding_interval: u32,
+    /// Limite de bitrate (kbps)
+    pub bitrate_limit: u32,
+    /// GOP em frames (H264)
+    pub gov_length: Option<u32>,
+    /// `Baseline`, `Main`, `Extended` ou `High`
+    pub h264_profile: Option<String>,
+    pub multicast: Multicast,
+    pub session_timeout: String,
+}
+
+/// Faixas aceitas por um codec
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EncoderOptions {
+    pub resolutions: Vec<(u32, u32)>,
+    pub frame_rate_range: Option<(u32, u32)>,
+    pub gov_length_range: Option<(u32, u32)>,
+    /// Faixa de bitrate (kbps), quando anunciada em Extension
+    pub bitrate_range: Option<(u32, u32)>,
+    pub h264_profiles: Vec<String>,
+}
+
+/// GetVideoEncoderConfigurationOptions
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VideoEncoderOptions {
+    pub quality_range: Option<(f32, f32)>,
+    pub h264: Option<EncoderOptions>,
+    pub jpeg: Option<EncoderOptions>,
+}
+
+impl VideoEncoderOptions {
+    /// Opções do codec (`H264` / `JPEG`)
+    pub fn for_encoding(&self, encoding: &str) -> Option<&EncoderOptions> {
+        match encoding {
+            "H264" => self.h264.as_ref(),
+            "JPEG" => self.jpeg.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// Cliente do Media Service de um dispositivo
+#[derive(Clone)]
+pub struct OnvifMedia {
+    client: OnvifClient,
+    /// URL do Media Service
+    service: String,
+}
+
+impl OnvifMedia {
+    pub fn new(client: OnvifClient, service: String) -> Self {
+        Self { client, service }
+    }
+
+    /// Todas as configurações de encoder (GetVideoEncoderConfigurations)
+    pub async fn get_video_encoder_configurations(&self) -> Result<Vec<VideoEncoderConfiguration>> {
+        let body = format!(r#"<trt:GetVideoEncoderConfigurations xmlns:trt="{}"/>"#, ns::MEDIA);
+        let response = self.call("GetVideoEncoderConfigurations", &body).await?;
+        Ok(parse_video_encoder_configurations(&response)?)
+    }
+
+    /// Configuração de encoder pelo token (GetVideoEncoderConfiguration)
+    pub async fn get_video_encoder_configuration(&self, token: &str) -> Result<VideoEncoderConfiguration> {
+        let body = format!(
+            r#"<trt:GetVideoEncoderConfiguration xmlns:trt="{}"><trt:ConfigurationToken>{}</trt:ConfigurationToken></trt:GetVideoEncoderConfiguration>"#,
+            ns::MEDIA,
+            escape(token)
+        );
+        let response = self.call("GetVideoEncoderConfiguration", &body).await?;
+        parse_video_encoder_configurations(&response)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| OnvifError::InvalidResponse("Configuration ausente".to_string()).into())
+    }
+
+    /// Faixas suportadas pelo encoder (GetVideoEncoderConfigurationOptions)
+    pub async fn get_video_encoder_options(&self, token: &str) -> Result<VideoEncoderOptions> {
+        let body = format!(
+            r#"<trt:GetVideoEncoderConfigurationOptions xmlns:trt="{}"><trt:ConfigurationToken>{}</trt:ConfigurationToken></trt:GetVideoEncoderConfigurationOptions>"#,
+            ns::MEDIA,
+            escape(token)
+        );
+        let response = self.call("GetVideoEncoderConfigurationOptions", &body).await?;
+        Ok(parse_video_encoder_options(&response)?)
+    }
+
+    /// Grava a configuração (SetVideoEncoderConfiguration, persistente)
+    pub async fn set_video_encoder_configuration(&self, config: &VideoEncoderConfiguration) -> Result<()> {
+        debug!(
+            "📡 SetVideoEncoderConfiguration {}: {} {}x{} {}fps {}kbps",
+            config.token, config.encoding, config.width, config.height, config.frame_rate_limit, config.bitrate_limit
+        );
+
+        let h264 = match (&config.gov_length, &config.h264_profile) {
+            (Some(gov), Some(profile)) if config.encoding == "H264" => format!(
+                "<tt:H264><tt:GovLength>{}</tt:GovLength><tt:H264Profile>{}</tt:H264Profile></tt:H264>",
+                gov,
+                escape(profile)
+            ),
+            _ => String::new(),
+        };
+
+        let body = format!(
+            r#"<trt:SetVideoEncoderConfiguration xmlns:trt="{media}" xmlns:tt="{schema}">
+      <trt:Configuration token="{token}">
+        <tt:Name>{name}</tt:Name>
+        <tt:UseCount>{use_count}</tt:UseCount>
+        <tt:Encoding>{encoding}</tt:Encoding>
+        <tt:Resolution><tt:Width>{width}</tt:Width><tt:Height>{height}</tt:Height></tt:Resolution>
+        <tt:Quality>{quality}</tt:Quality>
+        <tt:RateControl>
+          <tt:FrameRateLimit>{fps}</tt:FrameRateLimit>
+          <tt:EncodingInterval>{interval}</tt:EncodingInterval>
+          <tt:BitrateLimit>{bitrate}</tt:BitrateLimit>
+        </tt:RateControl>
+        {h264}
+        <tt:Multicast>
+          <tt:Address><tt:Type>IPv4</tt:Type><tt:IPv4Address>{mc_address}</tt:IPv4Address></tt:Address>
+          <tt:Port>{mc_port}</tt:Port>
+          <tt:TTL>{mc_ttl}</tt:TTL>
+          <tt:AutoStart>{mc_auto_start}</tt:AutoStart>
+        </tt:Multicast>
+        <tt:SessionTimeout>{session_timeout}</tt:SessionTimeout>
+      </trt:Configuration>
+      <trt:ForcePersistence>true</trt:ForcePersistence>
+    </trt:SetVideoEncoderConfiguration>"#,
+            media = ns::MEDIA,
+            schema = ns::SCHEMA,
+            token = escape(&config.token),
+            name = escape(&config.name),
+            use_count = config.use_count,
+            encoding = escape(&config.encoding),
+            width = config.width,
+            height = config.height,
+            quality = config.quality,
+            fps = config.frame_rate_limit,
+            interval = config.encoding_interval,
+            bitrate = config.bitrate_limit,
+            h264 = h264,
+            mc_address = escape(&config.multicast.address),
+            mc_port = config.multicast.port,
+            mc_ttl = config.multicast.ttl,
+            mc_auto_start = config.multicast.auto_start,
+            session_timeout = escape(&config.session_timeout),
+        );
+
+        self.call("SetVideoEncoderConfiguration", &body).await.map(|_| ())
+    }
+
+    async fn call(&self, operation: &str, body: &str) -> Result<String> {
+        let action = format!("{}/{}", ns::MEDIA, operation);
+        Ok(self.client.call(&self.service, &action, body).await?)
+    }
+}
+
+/// Aplica um `MediaProfile` sobre a configuração atual, respeitando as
+/// opções do encoder. Retorna a nova configuração e os avisos (ajustes ou
+/// itens não suportados pelo Media 1).
+pub fn apply_media_profile(
+    current: &VideoEncoderConfiguration,
+    options: Option<&VideoEncoderOptions>,
+    profile: &MediaProfile,
+) -> (VideoEncoderConfiguration, Vec<String>) {
+    let mut config = current.clone();
+    let mut warnings = Vec::new();
+
+    config.encoding = match profile.video_codec {
+        VideoCodec::H264 => "H264".to_string(),
+        VideoCodec::MJPEG => "JPEG".to_string(),
+        codec => {
+            warnings.push(format!(
+                "{:?} is not configurable via ONVIF Media 1; encoding left as {}",
+                codec, current.encoding
+            ));
+            current.encoding.clone()
+        }
+    };
+    let codec_options = options.and_then(|o| o.for_encoding(&config.encoding));
+
+    // Resolução: exata se suportada, senão a de área mais próxima
+    let wanted = (profile.resolution.width, profile.resolution.height);
+    let (width, height) = match codec_options.filter(|o| !o.resolutions.is_empty()) {
+        Some(o) if !o.resolutions.contains(&wanted) => {
+            let area = (wanted.0 * wanted.1) as i64;
+            let nearest = *o
+                .resolutions
+                .iter()
+                .min_by_key(|(w, h)| ((*w * *h) as i64 - area).abs())
+                .unwrap_or(&wanted);
+            warnings.push(format!(
+                "Resolution {}x{} not supported; using {}x{}",
+                wanted.0, wanted.1, nearest.0, nearest.1
+            ));
+            nearest
+        }
+        _ => wanted,
+    };
+    config.width = width;
+    config.height = height;
+
+    let fps = profile.fps.as_f64().round().max(1.0) as u32;
+    config.frame_rate_limit = clamp(fps, codec_options.and_then(|o| o.frame_rate_range), "Frame rate", &mut warnings);
+    config.encoding_interval = 1;
+
+    // Media 1 só tem limite de bitrate: CBR usa o alvo, VBR o máximo
+    let bitrate = match profile.bitrate_mode {
+        BitrateMode::CBR => profile.bitrate_target,
+        BitrateMode::VBR => profile.bitrate_max.unwrap_or(profile.bitrate_target),
+    };
+    config.bitrate_limit = clamp(
+        (bitrate / 1000).max(1),
+        codec_options.and_then(|o| o.bitrate_range),
+        "Bitrate (kbps)",
+        &mut warnings,
+    );
+
+    if config.encoding == "H264" {
+        config.gov_length = Some(clamp(
+            profile.gop_size.max(1),
+            codec_options.and_then(|o| o.gov_length_range),
+            "GOP",
+            &mut warnings,
+        ));
+
+        let wanted_profile = profile.codec_profile.as_deref().map(capitalize);
+        let supported = codec_options.map(|o| o.h264_profiles.as_slice()).unwrap_or_default();
+        config.h264_profile = match wanted_profile {
+            Some(p) if supported.is_empty() || supported.contains(&p) => Some(p),
+            Some(p) => {
+                warnings.push(format!("H264 profile {} not supported; keeping current", p));
+                current.h264_profile.clone().or_else(|| supported.first().cloned())
+            }
+            None => current.h264_profile.clone().or_else(|| Some("Main".to_string())),
+        };
+    }
+
+    (config, warnings)
+}
+
+fn clamp(value: u32, range: Option<(u32, u32)>, field: &str, warnings: &mut Vec<String>) -> u32 {
+    match range {
+        Some((min, max)) if value < min || value > max => {
+            let clamped = value.clamp(min, max);
+            warnings.push(format!("{} {} out of range {}-{}; using {}", field, value, min, max, clamped));
+            clamped
+        }
+        _ => value,
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let lower = s.to_lowercase();
+    let mut chars = lower.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Parse de GetVideoEncoderConfiguration(s) (`Configuration`/`Configurations`)
+pub fn parse_video_encoder_configurations(xml: &str) -> Result<Vec<VideoEncoderConfiguration>, OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+
+    Ok(response
+        .children()
+        .filter(|n| xml_utils::is(*n, ns::MEDIA, "Configurations") || xml_utils::is(*n, ns::MEDIA, "Configuration"))
+        .filter_map(|config| {
+            let text = |path: &[&str]| xml_utils::path(config, ns::SCHEMA, path).and_then(xml_utils::text);
+            let number = |path: &[&str]| text(path).and_then(|v| v.parse::<f32>().ok());
+
+            Some(VideoEncoderConfiguration {
+                token: config.attribute("token")?.to_string(),
+                name: text(&["Name"]).unwrap_or_default(),
+                use_count: number(&["UseCount"]).unwrap_or(0.0) as u32,
+                encoding: text(&["Encoding"]).unwrap_or_else(|| "H264".to_string()),
+                width: number(&["Resolution", "Width"]).unwrap_or(0.0) as u32,
+                height: number(&["Resolution", "Height"]).unwrap_or(0.0) as u32,
+                quality: number(&["Quality"]).unwrap_or(0.0),
+                frame_rate_limit: number(&["RateControl", "FrameRateLimit"]).unwrap_or(0.0) as u32,
+                encoding_interval: number(&["RateControl", "EncodingInterval"]).unwrap_or(1.0) as u32,
+                bitrate_limit: number(&["RateControl", "BitrateLimit"]).unwrap_or(0.0) as u32,
+                gov_length: number(&["H264", "GovLength"]).map(|g| g as u32),
+                h264_profile: text(&["H264", "H264Profile"]),
+                multicast: Multicast {
+                    address: text(&["Multicast", "Address", "IPv4Address"]).unwrap_or_else(|| "0.0.0.0".to_string()),
+                    port: number(&["Multicast", "Port"]).unwrap_or(0.0) as u16,
+                    ttl: number(&["Multicast", "TTL"]).unwrap_or(0.0) as u32,
+                    auto_start: text(&["Multicast", "AutoStart"]).as_deref() == Some("true"),
+                },
+                session_timeout: text(&["SessionTimeout"]).unwrap_or_else(|| "PT60S".to_string()),
+            })
+        })
+        .collect())
+}
+
+/// Parse de GetVideoEncoderConfigurationOptions
+pub fn parse_video_encoder_options(xml: &str) -> Result<VideoEncoderOptions, OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+    let Some(options) = xml_utils::child(response, ns::MEDIA, "Options") else {
+        return Ok(VideoEncoderOptions::default());
+    };
+
+    let range = |node: roxmltree::Node, local: &str| {
+        let range = xml_utils::child(node, ns::SCHEMA, local)?;
+        let bound = |b: &str| xml_utils::child_text(range, ns::SCHEMA, b)?.parse::<f32>().ok();
+        Some((bound("Min")?, bound("Max")?))
+    };
+    let int_range = |node, local| range(node, local).map(|(min, max)| (min as u32, max as u32));
+
+    let codec = |local: &str| {
+        let node = xml_utils::child(options, ns::SCHEMA, local)?;
+        let bitrate_range = xml_utils::child(options, ns::SCHEMA, "Extension")
+            .and_then(|ext| xml_utils::child(ext, ns::SCHEMA, local))
+            .and_then(|ext| int_range(ext, "BitrateRange"));
+
+        Some(EncoderOptions {
+            resolutions: xml_utils::children(node, ns::SCHEMA, "ResolutionsAvailable")
+                .filter_map(|r| {
+                    Some((
+                        xml_utils::child_text(r, ns::SCHEMA, "Width")?.parse().ok()?,
+                        xml_utils::child_text(r, ns::SCHEMA, "Height")?.parse().ok()?,
+                    ))
+                })
+                .collect(),
+            frame_rate_range: int_range(node, "FrameRateRange"),
+            gov_length_range: int_range(node, "GovLengthRange"),
+            bitrate_range,
+            h264_profiles: xml_utils::children(node, ns::SCHEMA, "H264ProfilesSupported")
+                .filter_map(xml_utils::text)
+                .collect(),
+        })
+    };
+
+    Ok(VideoEncoderOptions {
+        quality_range: range(options, "QualityRange"),
+        h264: codec("H264"),
+        jpeg: codec("JPEG"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:trt="http://www.onvif.org/ver10/media/wsdl" xmlns:tt="http://www.onvif.org/ver10/schema">
+        <s:Body><trt:GetVideoEncoderConfigurationResponse>
+            <trt:Configuration token="VideoEncoder_1">
+                <tt:Name>MainStream</tt:Name><tt:UseCount>1</tt:UseCount><tt:Encoding>H264</tt:Encoding>
+                <tt:Resolution><tt:Width>2560</tt:Width><tt:Height>1440</tt:Height></tt:Resolution>
+                <tt:Quality>4</tt:Quality>
+                <tt:RateControl><tt:FrameRateLimit>30</tt:FrameRateLimit><tt:EncodingInterval>1</tt:EncodingInterval><tt:BitrateLimit>8192</tt:BitrateLimit></tt:RateControl>
+                <tt:H264><tt:GovLength>60</tt:GovLength><tt:H264Profile>High</tt:H264Profile></tt:H264>
+                <tt:Multicast><tt:Address><tt:Type>IPv4</tt:Type><tt:IPv4Address>239.0.0.1</tt:IPv4Address></tt:Address><tt:Port>8600</tt:Port><tt:TTL>1</tt:TTL><tt:AutoStart>false</tt:AutoStart></tt:Multicast>
+                <tt:SessionTimeout>PT5S</tt:SessionTimeout>
+            </trt:Configuration>
+        </trt:GetVideoEncoderConfigurationResponse></s:Body>
+    </s:Envelope>"#;
+
+    const OPTIONS: &str = r#"<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:trt="http://www.onvif.org/ver10/media/wsdl" xmlns:tt="http://www.onvif.org/ver10/schema">
+        <s:Body><trt:GetVideoEncoderConfigurationOptionsResponse><trt:Options>
+            <tt:QualityRange><tt:Min>1</tt:Min><tt:Max>6</tt:Max></tt:QualityRange>
+            <tt:H264>
+                <tt:ResolutionsAvailable><tt:Width>2560</tt:Width><tt:Height>1440</tt:Height></tt:ResolutionsAvailable>
+                <tt:ResolutionsAvailable><tt:Width>1920</tt:Width><tt:Height>1080</tt:Height></tt:ResolutionsAvailable>
+                <tt:ResolutionsAvailable><tt:Width>1280</tt:Width><tt:Height>720</tt:Height></tt:ResolutionsAvailable>
+                <tt:GovLengthRange><tt:Min>1</tt:Min><tt:Max>40</tt:Max></tt:GovLengthRange>
+                <tt:FrameRateRange><tt:Min>1</tt:Min><tt:Max>25</tt:Max></tt:FrameRateRange>
+                <tt:EncodingIntervalRange><tt:Min>1</tt:Min><tt:Max>1</tt:Max></tt:EncodingIntervalRange>
+                <tt:H264ProfilesSupported>Main</tt:H264ProfilesSupported>
+                <tt:H264ProfilesSupported>High</tt:H264ProfilesSupported>
+            </tt:H264>
+            <tt:Extension><tt:H264><tt:BitrateRange><tt:Min>32</tt:Min><tt:Max>16384</tt:Max></tt:BitrateRange></tt:H264></tt:Extension>
+        </trt:Options></trt:GetVideoEncoderConfigurationOptionsResponse></s:Body>
+    </s:Envelope>"#;
+
+    #[test]
+    fn test_parse_video_encoder_configuration() {
+        let configs = parse_video_encoder_configurations(CONFIG).unwrap();
+        assert_eq!(configs.len(), 1);
+        let config = &configs[0];
+        assert_eq!(config.token, "VideoEncoder_1");
+        assert_eq!((config.width, config.height), (2560, 1440));
+        assert_eq!(config.bitrate_limit, 8192);
+        assert_eq!(config.gov_length, Some(60));
+        assert_eq!(config.multicast.address, "239.0.0.1");
+        assert_eq!(config.session_timeout, "PT5S");
+    }
+
+    #[test]
+    fn test_apply_media_profile_within_options() {
+        let current = parse_video_encoder_configurations(CONFIG).unwrap().remove(0);
+        let options = parse_video_encoder_options(OPTIONS).unwrap();
+        assert_eq!(options.h264.as_ref().unwrap().bitrate_range, Some((32, 16384)));
+
+        let mut profile = MediaProfile::liveview_default();
+        profile.resolution = vms_common::types::Resolution::new(1800, 1000);
+        let (config, warnings) = apply_media_profile(&current, Some(&options), &profile);
+
+        assert_eq!((config.width, config.height), (1920, 1080));
+        assert_eq!(config.frame_rate_limit, 25);
+        // VBR: limite = bitrate máximo
+        assert_eq!(config.bitrate_limit, 4000);
+        assert_eq!(config.gov_length, Some(25));
+        assert_eq!(config.h264_profile.as_deref(), Some("Main"));
+        assert_eq!(config.multicast, current.multicast);
+        assert_eq!(warnings.len(), 1);
+
+        profile.video_codec = VideoCodec::H265;
+        profile.gop_size = 100;
+        let (config, warnings) = apply_media_profile(&current, Some(&options), &profile);
+        assert_eq!(config.encoding, "H264");
+        assert_eq!(config.gov_length, Some(40));
+        assert_eq!(warnings.len(), 3);
+    }
+}
@@ -0,0 +1,498 @@
+//! ONVIF PTZ (Pan-Tilt-Zoom) Control
+//! PTZ Service (ver20) mapeado para os tipos de `vms_common::ptz`
+
+use anyhow::Result;
+use roxmltree::Node;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use vms_common::ptz::{PTZCapabilities, PTZPosition, PTZSpeed};
+
+use crate::client::{OnvifClient, OnvifError};
+use crate::wsse::escape;
+use crate::xml_utils::{self, ns};
+
+/// Preset armazenado na câmera
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnvifPreset {
+    /// Token ONVIF do preset
+    pub token: String,
+    pub name: String,
+    pub position: Option<PTZPosition>,
+}
+
+/// Estado retornado por GetStatus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PtzStatus {
+    pub position: PTZPosition,
+    pub is_moving: bool,
+    pub error: Option<String>,
+}
+
+/// Cliente do PTZ Service de um dispositivo
+#[derive(Clone)]
+pub struct OnvifPtz {
+    client: OnvifClient,
+    /// URL do PTZ Service
+    service: String,
+}
+
+impl OnvifPtz {
+    pub fn new(client: OnvifClient, service: String) -> Self {
+        Self { client, service }
+    }
+
+    /// Capacidades via GetNodes e, se houver configuração, GetConfigurationOptions
+    ///
+    /// Foco e íris pertencem ao Imaging Service e ficam `false` aqui.
+    pub async fn get_capabilities(&self, configuration_token: Option<&str>) -> Result<PTZCapabilities> {
+        let body = format!(r#"<tptz:GetNodes xmlns:tptz="{}"/>"#, ns::PTZ);
+        let mut capabilities = parse_nodes(&self.call("GetNodes", &body).await?)?;
+
+        if let Some(token) = configuration_token {
+            let body = format!(
+                r#"<tptz:GetConfigurationOptions xmlns:tptz="{}"><tptz:ConfigurationToken>{}</tptz:ConfigurationToken></tptz:GetConfigurationOptions>"#,
+                ns::PTZ,
+                escape(token)
+            );
+            // Opcional: nem todo firmware implementa
+            match self.call("GetConfigurationOptions", &body).await {
+                Ok(xml) => apply_configuration_options(&xml, &mut capabilities)?,
+                Err(e) => debug!("GetConfigurationOptions failed: {}", e),
+            }
+        }
+
+        Ok(capabilities)
+    }
+
+    /// PTZ continuous move (velocidades -1.0 a 1.0)
+    pub async fn continuous_move(&self, profile_token: &str, pan: f32, tilt: f32, zoom: f32) -> Result<()> {
+        debug!("📡 PTZ continuous move: pan={}, tilt={}, zoom={}", pan, tilt, zoom);
+
+        let body = format!(
+            r#"<tptz:ContinuousMove xmlns:tptz="{}" xmlns:tt="{}">
+      <tptz:ProfileToken>{}</tptz:ProfileToken>
+      <tptz:Velocity>{}</tptz:Velocity>
+    </tptz:ContinuousMove>"#,
+            ns::PTZ,
+            ns::SCHEMA,
+            escape(profile_token),
+            vector(pan, tilt, zoom)
+        );
+
+        self.call("ContinuousMove", &body).await.map(|_| ())
+    }
+
+    /// PTZ absolute move
+    pub async fn absolute_move(&self, profile_token: &str, position: PTZPosition, speed: Option<PTZSpeed>) -> Result<()> {
+        debug!("📡 PTZ absolute move: {:?}", position);
+
+        let body = format!(
+            r#"<tptz:AbsoluteMove xmlns:tptz="{}" xmlns:tt="{}">
+      <tptz:ProfileToken>{}</tptz:ProfileToken>
+      <tptz:Position>{}</tptz:Position>{}
+    </tptz:AbsoluteMove>"#,
+            ns::PTZ,
+            ns::SCHEMA,
+            escape(profile_token),
+            vector(position.pan, position.tilt, position.zoom),
+            speed_element(speed)
+        );
+
+        self.call("AbsoluteMove", &body).await.map(|_| ())
+    }
+
+    /// PTZ relative move (`delta` em coordenadas normalizadas)
+    pub async fn relative_move(&self, profile_token: &str, delta: PTZPosition, speed: Option<PTZSpeed>) -> Result<()> {
+        debug!("📡 PTZ relative move: {:?}", delta);
+
+        let body = format!(
+            r#"<tptz:RelativeMove xmlns:tptz="{}" xmlns:tt="{}">
+      <tptz:ProfileToken>{}</tptz:ProfileToken>
+      <tptz:Translation>{}</tptz:Translation>{}
+    </tptz:RelativeMove>"#,
+            ns::PTZ,
+            ns::SCHEMA,
+            escape(profile_token),
+            vector(delta.pan, delta.tilt, delta.zoom),
+            speed_element(speed)
+        );
+
+        self.call("RelativeMove", &body).await.map(|_| ())
+    }
+
+    /// PTZ stop
+    pub async fn stop(&self, profile_token: &str) -> Result<()> {
+        debug!("📡 PTZ stop");
+
+        let body = format!(
+            r#"<tptz:Stop xmlns:tptz="{}">
+      <tptz:ProfileToken>{}</tptz:ProfileToken>
+      <tptz:PanTilt>true</tptz:PanTilt>
+      <tptz:Zoom>true</tptz:Zoom>
+    </tptz:Stop>"#,
+            ns::PTZ,
+            escape(profile_token)
+        );
+
+        self.call("Stop", &body).await.map(|_| ())
+    }
+
+    /// Lista presets (GetPresets)
+    pub async fn get_presets(&self, profile_token: &str) -> Result<Vec<OnvifPreset>> {
+        let body = format!(
+            r#"<tptz:GetPresets xmlns:tptz="{}"><tptz:ProfileToken>{}</tptz:ProfileToken></tptz:GetPresets>"#,
+            ns::PTZ,
+            escape(profile_token)
+        );
+
+        Ok(parse_presets(&self.call("GetPresets", &body).await?)?)
+    }
+
+    /// Salva a posição atual como preset; retorna o token atribuído pela câmera
+    ///
+    /// Com `preset_token` existente o preset é sobrescrito.
+    pub async fn set_preset(&self, profile_token: &str, name: &str, preset_token: Option<&str>) -> Result<String> {
+        debug!("📡 PTZ set preset: {}", name);
+
+        let token = preset_token
+            .map(|t| format!("\n      <tptz:PresetToken>{}</tptz:PresetToken>", escape(t)))
+            .unwrap_or_default();
+        let body = format!(
+            r#"<tptz:SetPreset xmlns:tptz="{}">
+      <tptz:ProfileToken>{}</tptz:ProfileToken>
+      <tptz:PresetName>{}</tptz:PresetName>{}
+    </tptz:SetPreset>"#,
+            ns::PTZ,
+            escape(profile_token),
+            escape(name),
+            token
+        );
+
+        let xml = self.call("SetPreset", &body).await?;
+        let doc = xml_utils::parse(&xml)?;
+        let response = xml_utils::response_element(&doc)?;
+        Ok(xml_utils::child_text(response, ns::PTZ, "PresetToken")
+            .or_else(|| preset_token.map(str::to_string))
+            .ok_or_else(|| OnvifError::InvalidResponse("PresetToken ausente".to_string()))?)
+    }
+
+    /// Remove preset
+    pub async fn remove_preset(&self, profile_token: &str, preset_token: &str) -> Result<()> {
+        debug!("📡 PTZ remove preset: {}", preset_token);
+
+        let body = format!(
+            r#"<tptz:RemovePreset xmlns:tptz="{}">
+      <tptz:ProfileToken>{}</tptz:ProfileToken>
+      <tptz:PresetToken>{}</tptz:PresetToken>
+    </tptz:RemovePreset>"#,
+            ns::PTZ,
+            escape(profile_token),
+            escape(preset_token)
+        );
+
+        self.call("RemovePreset", &body).await.map(|_| ())
+    }
+
+    /// PTZ goto preset
+    pub async fn goto_preset(&self, profile_token: &str, preset_token: &str, speed: Option<PTZSpeed>) -> Result<()> {
+        debug!("📡 PTZ goto preset: {}", preset_token);
+
+        let body = format!(
+            r#"<tptz:GotoPreset xmlns:tptz="{}" xmlns:tt="{}">
+      <tptz:ProfileToken>{}</tptz:ProfileToken>
+      <tptz:PresetToken>{}</tptz:PresetToken>{}
+    </tptz:GotoPreset>"#,
+            ns::PTZ,
+            ns::SCHEMA,
+            escape(profile_token),
+            escape(preset_token),
+            speed_element(speed)
+        );
+
+        self.call("GotoPreset", &body).await.map(|_| ())
+    }
+
+    /// Vai para a home position
+    pub async fn goto_home(&self, profile_token: &str, speed: Option<PTZSpeed>) -> Result<()> {
+        debug!("📡 PTZ goto home");
+
+        let body = format!(
+            r#"<tptz:GotoHomePosition xmlns:tptz="{}" xmlns:tt="{}">
+      <tptz:ProfileToken>{}</tptz:ProfileToken>{}
+    </tptz:GotoHomePosition>"#,
+            ns::PTZ,
+            ns::SCHEMA,
+            escape(profile_token),
+            speed_element(speed)
+        );
+
+        self.call("GotoHomePosition", &body).await.map(|_| ())
+    }
+
+    /// Salva a posição atual como home position
+    pub async fn set_home(&self, profile_token: &str) -> Result<()> {
+        let body = format!(
+            r#"<tptz:SetHomePosition xmlns:tptz="{}"><tptz:ProfileToken>{}</tptz:ProfileToken></tptz:SetHomePosition>"#,
+            ns::PTZ,
+            escape(profile_token)
+        );
+
+        self.call("SetHomePosition", &body).await.map(|_| ())
+    }
+
+    /// Posição e estado de movimento (GetStatus)
+    pub async fn get_status(&self, profile_token: &str) -> Result<PtzStatus> {
+        let body = format!(
+            r#"<tptz:GetStatus xmlns:tptz="{}"><tptz:ProfileToken>{}</tptz:ProfileToken></tptz:GetStatus>"#,
+            ns::PTZ,
+            escape(profile_token)
+        );
+
+        Ok(parse_status(&self.call("GetStatus", &body).await?)?)
+    }
+
+    /// Comando auxiliar (ex: `tt:Wiper|On`)
+    pub async fn send_auxiliary_command(&self, profile_token: &str, command: &str) -> Result<()> {
+        debug!("📡 PTZ auxiliary command: {}", command);
+
+        let body = format!(
+            r#"<tptz:SendAuxiliaryCommand xmlns:tptz="{}">
+      <tptz:ProfileToken>{}</tptz:ProfileToken>
+      <tptz:AuxiliaryData>{}</tptz:AuxiliaryData>
+    </tptz:SendAuxiliaryCommand>"#,
+            ns::PTZ,
+            escape(profile_token),
+            escape(command)
+        );
+
+        self.call("SendAuxiliaryCommand", &body).await.map(|_| ())
+    }
+
+    async fn call(&self, operation: &str, body: &str) -> Result<String> {
+        let action = format!("{}/{}", ns::PTZ, operation);
+        Ok(self.client.call(&self.service, &action, body).await?)
+    }
+}
+
+/// Token do preset com índice `index` (1-based)
+///
+/// Muitas câmeras usam o próprio índice como token; caso contrário vale a
+/// ordem retornada por GetPresets.
+pub fn preset_token_for_index(presets: &[OnvifPreset], index: u8) -> Option<String> {
+    let wanted = index.to_string();
+    presets
+        .iter()
+        .find(|p| p.token == wanted)
+        .or_else(|| presets.get((index as usize).checked_sub(1)?))
+        .map(|p| p.token.clone())
+}
+
+/// `<tt:PanTilt x y/><tt:Zoom x/>`
+fn vector(pan: f32, tilt: f32, zoom: f32) -> String {
+    format!(r#"<tt:PanTilt x="{}" y="{}"/><tt:Zoom x="{}"/>"#, pan, tilt, zoom)
+}
+
+fn speed_element(speed: Option<PTZSpeed>) -> String {
+    speed
+        .map(|s| format!("\n      <tptz:Speed>{}</tptz:Speed>", vector(s.pan, s.tilt, s.zoom)))
+        .unwrap_or_default()
+}
+
+/// Lê `<PanTilt x y/>` e `<Zoom x/>` de um vetor PTZ
+fn parse_vector(node: Node) -> PTZPosition {
+    let attr = |child: &str, name: &str| {
+        xml_utils::child(node, ns::SCHEMA, child)
+            .and_then(|n| n.attribute(name))
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.0)
+    };
+
+    PTZPosition {
+        pan: attr("PanTilt", "x"),
+        tilt: attr("PanTilt", "y"),
+        zoom: attr("Zoom", "x"),
+    }
+}
+
+/// Aplica os espaços suportados (`SupportedPTZSpaces` ou `Spaces`) às capacidades
+fn apply_spaces(spaces: Node, capabilities: &mut PTZCapabilities) {
+    let has = |local: &str| xml_utils::child(spaces, ns::SCHEMA, local).is_some();
+
+    capabilities.absolute_move = has("AbsolutePanTiltPositionSpace") || has("AbsoluteZoomPositionSpace");
+    capabilities.relative_move = has("RelativePanTiltTranslationSpace") || has("RelativeZoomTranslationSpace");
+    capabilities.continuous_move = has("ContinuousPanTiltVelocitySpace") || has("ContinuousZoomVelocitySpace");
+
+    let pan_tilt = has("AbsolutePanTiltPositionSpace")
+        || has("RelativePanTiltTranslationSpace")
+        || has("ContinuousPanTiltVelocitySpace");
+    capabilities.pan = pan_tilt;
+    capabilities.tilt = pan_tilt;
+    capabilities.zoom = has("AbsoluteZoomPositionSpace")
+        || has("RelativeZoomTranslationSpace")
+        || has("ContinuousZoomVelocitySpace");
+
+    if let Some(range) = xml_utils::path(spaces, ns::SCHEMA, &["PanTiltSpeedSpace", "XRange"]) {
+        let bound = |local: &str| xml_utils::child_text(range, ns::SCHEMA, local).and_then(|v| v.parse().ok());
+        if let (Some(min), Some(max)) = (bound("Min"), bound("Max")) {
+            capabilities.min_speed = min;
+            capabilities.max_speed = max;
+        }
+    }
+}
+
+/// Parse da resposta GetNodes (primeiro nó)
+pub fn parse_nodes(xml: &str) -> Result<PTZCapabilities, OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+    let node = xml_utils::child(response, ns::PTZ, "PTZNode")
+        .ok_or_else(|| OnvifError::InvalidResponse("PTZNode ausente".to_string()))?;
+
+    let mut capabilities = PTZCapabilities {
+        focus: false,
+        iris: false,
+        ..PTZCapabilities::default()
+    };
+    if let Some(spaces) = xml_utils::child(node, ns::SCHEMA, "SupportedPTZSpaces") {
+        apply_spaces(spaces, &mut capabilities);
+    }
+    if let Some(max) = xml_utils::child_text(node, ns::SCHEMA, "MaximumNumberOfPresets").and_then(|v| v.parse().ok()) {
+        capabilities.max_presets = max;
+    }
+    capabilities.aux_functions = xml_utils::children(node, ns::SCHEMA, "AuxiliaryCommands")
+        .filter_map(xml_utils::text)
+        .collect();
+
+    Ok(capabilities)
+}
+
+/// Restringe as capacidades com GetConfigurationOptions
+pub fn apply_configuration_options(xml: &str, capabilities: &mut PTZCapabilities) -> Result<(), OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+    if let Some(spaces) = xml_utils::child(response, ns::PTZ, "PTZConfigurationOptions")
+        .and_then(|options| xml_utils::child(options, ns::SCHEMA, "Spaces"))
+    {
+        apply_spaces(spaces, capabilities);
+    }
+    Ok(())
+}
+
+/// Parse da resposta GetPresets
+pub fn parse_presets(xml: &str) -> Result<Vec<OnvifPreset>, OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+
+    Ok(xml_utils::children(response, ns::PTZ, "Preset")
+        .filter_map(|preset| {
+            let token = preset.attribute("token")?.to_string();
+            Some(OnvifPreset {
+                name: xml_utils::child_text(preset, ns::SCHEMA, "Name").unwrap_or_else(|| token.clone()),
+                position: xml_utils::child(preset, ns::SCHEMA, "PTZPosition").map(parse_vector),
+                token,
+            })
+        })
+        .collect())
+}
+
+/// Parse da resposta GetStatus
+pub fn parse_status(xml: &str) -> Result<PtzStatus, OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+    let status = xml_utils::child(response, ns::PTZ, "PTZStatus")
+        .ok_or_else(|| OnvifError::InvalidResponse("PTZStatus ausente".to_string()))?;
+
+    let is_moving = xml_utils::child(status, ns::SCHEMA, "MoveStatus")
+        .map(|move_status| {
+            xml_utils::children(move_status, ns::SCHEMA, "PanTilt")
+                .chain(xml_utils::children(move_status, ns::SCHEMA, "Zoom"))
+                .filter_map(xml_utils::text)
+                .any(|state| state.eq_ignore_ascii_case("MOVING"))
+        })
+        .unwrap_or(false);
+
+    Ok(PtzStatus {
+        position: xml_utils::child(status, ns::SCHEMA, "Position")
+            .map(parse_vector)
+            .unwrap_or_default(),
+        is_moving,
+        error: xml_utils::child_text(status, ns::SCHEMA, "Error").filter(|e| e != "NO error"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENVELOPE_START: &str = r#"<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:tptz="http://www.onvif.org/ver20/ptz/wsdl" xmlns:tt="http://www.onvif.org/ver10/schema"><s:Body>"#;
+    const ENVELOPE_END: &str = "</s:Body></s:Envelope>";
+
+    fn envelope(body: &str) -> String {
+        format!("{}{}{}", ENVELOPE_START, body, ENVELOPE_END)
+    }
+
+    #[test]
+    fn test_parse_nodes() {
+        let xml = envelope(
+            r#"<tptz:GetNodesResponse><tptz:PTZNode token="PTZNODETOKEN">
+                <tt:Name>PTZ</tt:Name>
+                <tt:SupportedPTZSpaces>
+                    <tt:AbsolutePanTiltPositionSpace><tt:URI>x</tt:URI></tt:AbsolutePanTiltPositionSpace>
+                    <tt:ContinuousPanTiltVelocitySpace><tt:URI>x</tt:URI></tt:ContinuousPanTiltVelocitySpace>
+                    <tt:PanTiltSpeedSpace><tt:URI>x</tt:URI><tt:XRange><tt:Min>0</tt:Min><tt:Max>0.8</tt:Max></tt:XRange></tt:PanTiltSpeedSpace>
+                </tt:SupportedPTZSpaces>
+                <tt:MaximumNumberOfPresets>8</tt:MaximumNumberOfPresets>
+                <tt:HomeSupported>true</tt:HomeSupported>
+                <tt:AuxiliaryCommands>tt:Wiper|On</tt:AuxiliaryCommands>
+            </tptz:PTZNode></tptz:GetNodesResponse>"#,
+        );
+
+        let caps = parse_nodes(&xml).unwrap();
+        assert!(caps.pan && caps.tilt && !caps.zoom);
+        assert!(caps.absolute_move && caps.continuous_move && !caps.relative_move);
+        assert!(!caps.focus && !caps.iris);
+        assert_eq!(caps.max_presets, 8);
+        assert_eq!(caps.max_speed, 0.8);
+        assert_eq!(caps.aux_functions, vec!["tt:Wiper|On".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_presets_and_index() {
+        let xml = envelope(
+            r#"<tptz:GetPresetsResponse>
+                <tptz:Preset token="Preset_A"><tt:Name>Portão</tt:Name>
+                    <tt:PTZPosition><tt:PanTilt x="0.5" y="-0.25"/><tt:Zoom x="0.1"/></tt:PTZPosition>
+                </tptz:Preset>
+                <tptz:Preset token="2"><tt:Name>Doca</tt:Name></tptz:Preset>
+            </tptz:GetPresetsResponse>"#,
+        );
+
+        let presets = parse_presets(&xml).unwrap();
+        assert_eq!(presets.len(), 2);
+        assert_eq!(presets[0].name, "Portão");
+        assert_eq!(presets[0].position.unwrap().tilt, -0.25);
+        assert!(presets[1].position.is_none());
+
+        assert_eq!(preset_token_for_index(&presets, 2).as_deref(), Some("2"));
+        assert_eq!(preset_token_for_index(&presets, 1).as_deref(), Some("Preset_A"));
+        assert_eq!(preset_token_for_index(&presets, 0), None);
+        assert_eq!(preset_token_for_index(&presets, 9), None);
+    }
+
+    #[test]
+    fn test_parse_status() {
+        let xml = envelope(
+            r#"<tptz:GetStatusResponse><tptz:PTZStatus>
+                <tt:Position><tt:PanTilt x="-0.3" y="0.2"/><tt:Zoom x="0.5"/></tt:Position>
+                <tt:MoveStatus><tt:PanTilt>MOVING</tt:PanTilt><tt:Zoom>IDLE</tt:Zoom></tt:MoveStatus>
+                <tt:Error>NO error</tt:Error>
+                <tt:UtcTime>2024-01-01T00:00:00Z</tt:UtcTime>
+            </tptz:PTZStatus></tptz:GetStatusResponse>"#,
+        );
+
+        let status = parse_status(&xml).unwrap();
+        assert_eq!(status.position.pan, -0.3);
+        assert_eq!(status.position.zoom, 0.5);
+        assert!(status.is_moving);
+        assert!(status.error.is_none());
+    }
+}
@@ -0,0 +1,205 @@
+//! Envelope SOAP 1.1/1.2 e parsing de SOAP Faults
+
+use std::fmt;
+
+use crate::xml_utils::{self, ns};
+
+/// Versão do protocolo SOAP
+///
+/// ONVIF especifica SOAP 1.2, mas alguns firmwares só aceitam 1.1
+/// (`text/xml` + header `SOAPAction`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoapVersion {
+    V11,
+    V12,
+}
+
+impl SoapVersion {
+    /// Namespace do envelope
+    pub fn namespace(&self) -> &'static str {
+        match self {
+            SoapVersion::V11 => ns::SOAP11,
+            SoapVersion::V12 => ns::SOAP12,
+        }
+    }
+
+    /// Content-Type da requisição (SOAP 1.2 leva a action no próprio header)
+    pub fn content_type(&self, action: &str) -> String {
+        match self {
+            SoapVersion::V11 => "text/xml; charset=utf-8".to_string(),
+            SoapVersion::V12 => format!("application/soap+xml; charset=utf-8; action=\"{}\"", action),
+        }
+    }
+
+    /// A outra versão (fallback)
+    pub fn other(&self) -> Self {
+        match self {
+            SoapVersion::V11 => SoapVersion::V12,
+            SoapVersion::V12 => SoapVersion::V11,
+        }
+    }
+}
+
+/// Monta o envelope SOAP
+///
+/// `body` é o elemento da operação com seus próprios `xmlns`, e `security`
+/// um header WS-Security opcional (ver `wsse::username_token`).
+pub fn envelope(version: SoapVersion, security: Option<&str>, body: &str) -> String {
+    let header = security
+        .map(|s| format!("\n  <s:Header>\n    {}\n  </s:Header>", s))
+        .unwrap_or_default();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<s:Envelope xmlns:s="{}">{}
+  <s:Body>
+    {}
+  </s:Body>
+</s:Envelope>"#,
+        version.namespace(),
+        header,
+        body
+    )
+}
+
+/// SOAP Fault retornado pela câmera
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoapFault {
+    /// Código principal (ex: `Sender`, `VersionMismatch`), sem prefixo
+    pub code: String,
+    /// Subcódigos (ex: `NotAuthorized`), sem prefixo
+    pub subcodes: Vec<String>,
+    pub reason: String,
+}
+
+impl SoapFault {
+    /// Falha de autenticação (`ter:NotAuthorized` ou equivalente SOAP 1.1)
+    pub fn is_not_authorized(&self) -> bool {
+        self.subcodes.iter().chain(std::iter::once(&self.code)).any(|c| {
+            let c = c.to_ascii_lowercase();
+            c.contains("notauthorized") || c.contains("failedauthentication")
+        })
+    }
+
+    /// Câmera não entende a versão do envelope
+    pub fn is_version_mismatch(&self) -> bool {
+        self.code == "VersionMismatch"
+    }
+}
+
+impl fmt::Display for SoapFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code)?;
+        for subcode in &self.subcodes {
+            write!(f, "/{}", subcode)?;
+        }
+        if !self.reason.is_empty() {
+            write!(f, ": {}", self.reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Extrai o SOAP Fault de uma resposta, se houver
+pub fn parse_fault(xml: &str) -> Option<SoapFault> {
+    let doc = xml_utils::parse(xml).ok()?;
+    let body = xml_utils::soap_body(&doc)?;
+
+    if let Some(fault) = xml_utils::child(body, ns::SOAP12, "Fault") {
+        let mut subcodes = Vec::new();
+        let code = xml_utils::child(fault, ns::SOAP12, "Code")?;
+        let mut subcode = xml_utils::child(code, ns::SOAP12, "Subcode");
+        while let Some(node) = subcode {
+            if let Some(value) = xml_utils::child_text(node, ns::SOAP12, "Value") {
+                subcodes.push(local_name(&value));
+            }
+            subcode = xml_utils::child(node, ns::SOAP12, "Subcode");
+        }
+
+        return Some(SoapFault {
+            code: xml_utils::child_text(code, ns::SOAP12, "Value")
+                .map(|v| local_name(&v))
+                .unwrap_or_default(),
+            subcodes,
+            reason: xml_utils::child(fault, ns::SOAP12, "Reason")
+                .and_then(|r| xml_utils::child_text(r, ns::SOAP12, "Text"))
+                .unwrap_or_default(),
+        });
+    }
+
+    // SOAP 1.1: faultcode/faultstring não qualificados
+    let fault = xml_utils::child(body, ns::SOAP11, "Fault")?;
+    let text_of = |local: &str| {
+        fault
+            .children()
+            .find(|n| n.is_element() && n.tag_name().name() == local)
+            .and_then(xml_utils::text)
+    };
+    let faultcode = text_of("faultcode").map(|c| local_name(&c)).unwrap_or_default();
+    // Alguns firmwares usam "Client.NotAuthorized"
+    let (code, subcodes) = match faultcode.split_once('.') {
+        Some((code, sub)) => (code.to_string(), vec![sub.to_string()]),
+        None => (faultcode.clone(), Vec::new()),
+    };
+
+    Some(SoapFault {
+        code,
+        subcodes,
+        reason: text_of("faultstring").unwrap_or_default(),
+    })
+}
+
+/// Remove o prefixo de um QName (`ter:NotAuthorized` → `NotAuthorized`)
+fn local_name(qname: &str) -> String {
+    qname.rsplit(':').next().unwrap_or(qname).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_versions() {
+        let v12 = envelope(SoapVersion::V12, None, "<tds:GetDeviceInformation/>");
+        assert!(v12.contains(ns::SOAP12));
+        assert!(!v12.contains("<s:Header>"));
+
+        let v11 = envelope(SoapVersion::V11, Some("<Security/>"), "<x/>");
+        assert!(v11.contains(ns::SOAP11));
+        assert!(v11.contains("<s:Header>"));
+        assert!(SoapVersion::V12.content_type("urn:a").contains("action=\"urn:a\""));
+    }
+
+    #[test]
+    fn test_parse_soap12_not_authorized() {
+        let xml = r#"<env:Envelope xmlns:env="http://www.w3.org/2003/05/soap-envelope" xmlns:ter="http://www.onvif.org/ver10/error">
+            <env:Body><env:Fault>
+                <env:Code><env:Value>env:Sender</env:Value>
+                    <env:Subcode><env:Value>ter:NotAuthorized</env:Value></env:Subcode>
+                </env:Code>
+                <env:Reason><env:Text xml:lang="en">Sender not Authorized</env:Text></env:Reason>
+            </env:Fault></env:Body>
+        </env:Envelope>"#;
+
+        let fault = parse_fault(xml).unwrap();
+        assert_eq!(fault.code, "Sender");
+        assert_eq!(fault.subcodes, vec!["NotAuthorized".to_string()]);
+        assert!(fault.is_not_authorized());
+        assert!(!fault.is_version_mismatch());
+    }
+
+    #[test]
+    fn test_parse_soap11_fault() {
+        let xml = r#"<SOAP-ENV:Envelope xmlns:SOAP-ENV="http://schemas.xmlsoap.org/soap/envelope/">
+            <SOAP-ENV:Body><SOAP-ENV:Fault>
+                <faultcode>SOAP-ENV:VersionMismatch</faultcode>
+                <faultstring>Wrong envelope</faultstring>
+            </SOAP-ENV:Fault></SOAP-ENV:Body>
+        </SOAP-ENV:Envelope>"#;
+
+        let fault = parse_fault(xml).unwrap();
+        assert!(fault.is_version_mismatch());
+        assert_eq!(fault.reason, "Wrong envelope");
+        assert!(parse_fault("<ok/>").is_none());
+    }
+}
@@ -0,0 +1,258 @@
+//! ONVIF Device Management - data/hora, NTP e usuários
+
+use std::net::IpAddr;
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::client::{OnvifClient, OnvifError, DEVICE_SERVICE_PATH};
+use crate::wsse::escape;
+use crate::xml_utils::{self, ns};
+
+/// Origem do relógio da câmera
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateTimeMode {
+    /// Sincroniza pelos servidores NTP configurados
+    Ntp,
+    /// Ajuste manual para o horário informado (UTC)
+    Manual(DateTime<Utc>),
+}
+
+/// Nível de acesso de um usuário ONVIF
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UserLevel {
+    Administrator,
+    Operator,
+    User,
+    Anonymous,
+    Extended,
+}
+
+impl UserLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Administrator => "Administrator",
+            Self::Operator => "Operator",
+            Self::User => "User",
+            Self::Anonymous => "Anonymous",
+            Self::Extended => "Extended",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "Administrator" => Self::Administrator,
+            "Operator" => Self::Operator,
+            "Anonymous" => Self::Anonymous,
+            "Extended" => Self::Extended,
+            _ => Self::User,
+        }
+    }
+}
+
+/// Usuário da câmera
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OnvifUser {
+    pub username: String,
+    /// Senha (nunca retornada por GetUsers)
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+    pub level: UserLevel,
+}
+
+/// Cliente das operações de sistema do Device Management Service
+#[derive(Clone)]
+pub struct OnvifSystem {
+    client: OnvifClient,
+}
+
+impl OnvifSystem {
+    pub fn new(client: OnvifClient) -> Self {
+        Self { client }
+    }
+
+    /// Ajusta data/hora e fuso (SetSystemDateAndTime)
+    pub async fn set_system_date_and_time(&self, mode: DateTimeMode, timezone: Option<&str>) -> Result<()> {
+        let (date_time_type, utc) = match mode {
+            DateTimeMode::Ntp => ("NTP", String::new()),
+            DateTimeMode::Manual(now) => (
+                "Manual",
+                format!(
+                    "<tds:UTCDateTime><tt:Time><tt:Hour>{}</tt:Hour><tt:Minute>{}</tt:Minute><tt:Second>{}</tt:Second></tt:Time><tt:Date><tt:Year>{}</tt:Year><tt:Month>{}</tt:Month><tt:Day>{}</tt:Day></tt:Date></tds:UTCDateTime>",
+                    now.hour(),
+                    now.minute(),
+                    now.second(),
+                    now.year(),
+                    now.month(),
+                    now.day()
+                ),
+            ),
+        };
+        let timezone = timezone
+            .map(|tz| format!("<tds:TimeZone><tt:TZ>{}</tt:TZ></tds:TimeZone>", escape(tz)))
+            .unwrap_or_default();
+
+        let body = format!(
+            r#"<tds:SetSystemDateAndTime xmlns:tds="{}" xmlns:tt="{}">
+      <tds:DateTimeType>{}</tds:DateTimeType>
+      <tds:DaylightSavings>false</tds:DaylightSavings>
+      {}{}
+    </tds:SetSystemDateAndTime>"#,
+            ns::DEVICE,
+            ns::SCHEMA,
+            date_time_type,
+            timezone,
+            utc
+        );
+
+        self.call("SetSystemDateAndTime", &body).await.map(|_| ())
+    }
+
+    /// Servidores NTP manuais (SetNTP); endereços IP ou nomes DNS
+    pub async fn set_ntp(&self, servers: &[String]) -> Result<()> {
+        debug!("📡 SetNTP {:?}", servers);
+        let manual: String = servers
+            .iter()
+            .map(|server| match server.parse::<IpAddr>() {
+                Ok(IpAddr::V4(ip)) => format!(
+                    "<tds:NTPManual><tt:Type>IPv4</tt:Type><tt:IPv4Address>{}</tt:IPv4Address></tds:NTPManual>",
+                    ip
+                ),
+                Ok(IpAddr::V6(ip)) => format!(
+                    "<tds:NTPManual><tt:Type>IPv6</tt:Type><tt:IPv6Address>{}</tt:IPv6Address></tds:NTPManual>",
+                    ip
+                ),
+                Err(_) => format!(
+                    "<tds:NTPManual><tt:Type>DNS</tt:Type><tt:DNSname>{}</tt:DNSname></tds:NTPManual>",
+                    escape(server)
+                ),
+            })
+            .collect();
+
+        let body = format!(
+            r#"<tds:SetNTP xmlns:tds="{}" xmlns:tt="{}"><tds:FromDHCP>false</tds:FromDHCP>{}</tds:SetNTP>"#,
+            ns::DEVICE,
+            ns::SCHEMA,
+            manual
+        );
+
+        self.call("SetNTP", &body).await.map(|_| ())
+    }
+
+    /// Usuários cadastrados (GetUsers)
+    pub async fn get_users(&self) -> Result<Vec<OnvifUser>> {
+        let body = format!(r#"<tds:GetUsers xmlns:tds="{}"/>"#, ns::DEVICE);
+        let response = self.call("GetUsers", &body).await?;
+        Ok(parse_users(&response)?)
+    }
+
+    /// Cria usuários (CreateUsers)
+    pub async fn create_users(&self, users: &[OnvifUser]) -> Result<()> {
+        let body = format!(
+            r#"<tds:CreateUsers xmlns:tds="{}" xmlns:tt="{}">{}</tds:CreateUsers>"#,
+            ns::DEVICE,
+            ns::SCHEMA,
+            users_xml(users)
+        );
+        self.call("CreateUsers", &body).await.map(|_| ())
+    }
+
+    /// Altera senha/nível de usuários existentes (SetUser)
+    pub async fn set_users(&self, users: &[OnvifUser]) -> Result<()> {
+        let body = format!(
+            r#"<tds:SetUser xmlns:tds="{}" xmlns:tt="{}">{}</tds:SetUser>"#,
+            ns::DEVICE,
+            ns::SCHEMA,
+            users_xml(users)
+        );
+        self.call("SetUser", &body).await.map(|_| ())
+    }
+
+    /// Remove usuários (DeleteUsers)
+    pub async fn delete_users(&self, usernames: &[String]) -> Result<()> {
+        let names: String = usernames
+            .iter()
+            .map(|u| format!("<tds:Username>{}</tds:Username>", escape(u)))
+            .collect();
+        let body = format!(r#"<tds:DeleteUsers xmlns:tds="{}">{}</tds:DeleteUsers>"#, ns::DEVICE, names);
+        self.call("DeleteUsers", &body).await.map(|_| ())
+    }
+
+    /// Cria o usuário ou atualiza se já existir
+    pub async fn upsert_user(&self, user: &OnvifUser) -> Result<()> {
+        let exists = self
+            .get_users()
+            .await?
+            .iter()
+            .any(|u| u.username == user.username);
+
+        if exists {
+            self.set_users(std::slice::from_ref(user)).await
+        } else {
+            self.create_users(std::slice::from_ref(user)).await
+        }
+    }
+
+    async fn call(&self, operation: &str, body: &str) -> Result<String> {
+        let action = format!("{}/{}", ns::DEVICE, operation);
+        Ok(self.client.call(DEVICE_SERVICE_PATH, &action, body).await?)
+    }
+}
+
+fn users_xml(users: &[OnvifUser]) -> String {
+    users
+        .iter()
+        .map(|user| {
+            let password = user
+                .password
+                .as_deref()
+                .map(|p| format!("<tt:Password>{}</tt:Password>", escape(p)))
+                .unwrap_or_default();
+            format!(
+                "<tds:User><tt:Username>{}</tt:Username>{}<tt:UserLevel>{}</tt:UserLevel></tds:User>",
+                escape(&user.username),
+                password,
+                user.level.as_str()
+            )
+        })
+        .collect()
+}
+
+/// Parse da resposta GetUsers
+pub fn parse_users(xml: &str) -> Result<Vec<OnvifUser>, OnvifError> {
+    let doc = xml_utils::parse(xml)?;
+    let response = xml_utils::response_element(&doc)?;
+
+    Ok(xml_utils::children(response, ns::DEVICE, "User")
+        .filter_map(|user| {
+            Some(OnvifUser {
+                username: xml_utils::child_text(user, ns::SCHEMA, "Username")?,
+                password: None,
+                level: UserLevel::parse(&xml_utils::child_text(user, ns::SCHEMA, "UserLevel").unwrap_or_default()),
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_users() {
+        let xml = r#"<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:tds="http://www.onvif.org/ver10/device/wsdl" xmlns:tt="http://www.onvif.org/ver10/schema">
+            <s:Body><tds:GetUsersResponse>
+                <tds:User><tt:Username>admin</tt:Username><tt:UserLevel>Administrator</tt:UserLevel></tds:User>
+                <tds:User><tt:Username>vms</tt:Username><tt:UserLevel>Operator</tt:UserLevel></tds:User>
+            </tds:GetUsersResponse></s:Body>
+        </s:Envelope>"#;
+
+        let users = parse_users(xml).unwrap();
+        assert_eq!(users.len(), 2);
+        assert_eq!(users[0].username, "admin");
+        assert_eq!(users[1].level, UserLevel::Operator);
+        assert!(users_xml(&users[1..]).contains("<tt:UserLevel>Operator</tt:UserLevel>"));
+    }
+}
@@ -1,97 +1,93 @@
 //! Utilitários para parsing de XML ONVIF
-//! Implementação manual sem dependências pesadas
-
-/// Extrai texto da primeira tag encontrada (ignorando namespace)
-pub fn extract_first_tag_text(xml: &str, tag_local: &str) -> Option<String> {
-    // Procura por <prefix:tag> ou <tag>
-    let patterns = vec![
-        format!("<{}:", tag_local),
-        format!("<{}>", tag_local),
-        format!("<tt:{}>", tag_local),
-        format!("<tds:{}>", tag_local),
-        format!("<trt:{}>", tag_local),
-    ];
-
-    for start_pattern in &patterns {
-        if let Some(start_pos) = xml.find(start_pattern) {
-            // Encontra o fim da tag de abertura
-            if let Some(tag_end) = xml[start_pos..].find('>') {
-                let content_start = start_pos + tag_end + 1;
-                
-                // Procura pela tag de fechamento
-                let end_patterns = vec![
-                    format!("</{}>", tag_local),
-                    format!("</{}:", tag_local),
-                    format!("</tt:{}>", tag_local),
-                    format!("</tds:{}>", tag_local),
-                    format!("</trt:{}>", tag_local),
-                ];
-                
-                for end_pattern in &end_patterns {
-                    if let Some(end_pos) = xml[content_start..].find(end_pattern) {
-                        let text = &xml[content_start..content_start + end_pos];
-                        return Some(text.trim().to_string());
-                    }
-                }
-            }
-        }
-    }
-    
-    None
+//! Parser namespace-aware (roxmltree): elementos são identificados pelo par
+//! (namespace, nome local), independente do prefixo usado pela câmera.
+
+use roxmltree::{Document, Node};
+
+use crate::client::OnvifError;
+
+/// Namespaces usados nas mensagens ONVIF
+pub mod ns {
+    pub const SOAP11: &str = "http://schemas.xmlsoap.org/soap/envelope/";
+    pub const SOAP12: &str = "http://www.w3.org/2003/05/soap-envelope";
+    pub const SCHEMA: &str = "http://www.onvif.org/ver10/schema";
+    pub const DEVICE: &str = "http://www.onvif.org/ver10/device/wsdl";
+    pub const MEDIA: &str = "http://www.onvif.org/ver10/media/wsdl";
+    pub const PTZ: &str = "http://www.onvif.org/ver20/ptz/wsdl";
+    pub const IMAGING: &str = "http://www.onvif.org/ver20/imaging/wsdl";
+    pub const DEVICE_IO: &str = "http://www.onvif.org/ver10/deviceIO/wsdl";
+    pub const EVENTS: &str = "http://www.onvif.org/ver10/events/wsdl";
+    pub const WSNT: &str = "http://docs.oasis-open.org/wsn/b-2";
+    pub const WSA: &str = "http://www.w3.org/2005/08/addressing";
+    pub const WSSE: &str =
+        "http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-wssecurity-secext-1.0.xsd";
+    pub const WSU: &str =
+        "http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-wssecurity-utility-1.0.xsd";
 }
 
-/// Extrai atributo de uma tag XML
-pub fn extract_attribute(xml: &str, tag: &str, attr: &str) -> Option<String> {
-    // Procura pela tag
-    if let Some(tag_start) = xml.find(&format!("<{}", tag)) {
-        if let Some(tag_end) = xml[tag_start..].find('>') {
-            let tag_content = &xml[tag_start..tag_start + tag_end];
-            
-            // Procura pelo atributo
-            let pattern = format!("{}=\"", attr);
-            if let Some(attr_start) = tag_content.find(&pattern) {
-                let value_start = attr_start + pattern.len();
-                if let Some(quote_end) = tag_content[value_start..].find('"') {
-                    return Some(tag_content[value_start..value_start + quote_end].to_string());
-                }
-            }
-        }
-    }
-    None
+/// Faz o parse de uma resposta XML
+pub fn parse(xml: &str) -> Result<Document<'_>, OnvifError> {
+    Document::parse(xml).map_err(|e| OnvifError::Xml(e.to_string()))
 }
 
-/// Extrai todas as ocorrências de uma tag
-pub fn extract_all_tags(xml: &str, tag_local: &str) -> Vec<String> {
-    let mut results = Vec::new();
-    let mut search_from = 0;
-
-    loop {
-        let remaining = &xml[search_from..];
-        if let Some(tag_xml) = extract_tag_block(remaining, tag_local) {
-            let tag_start = remaining.find(&tag_xml).unwrap();
-            results.push(tag_xml.clone());
-            search_from += tag_start + tag_xml.len();
-        } else {
-            break;
-        }
-    }
+/// Verifica se o nó é o elemento `{ns}local`
+pub fn is(node: Node, ns: &str, local: &str) -> bool {
+    node.is_element() && node.tag_name().name() == local && node.tag_name().namespace() == Some(ns)
+}
 
-    results
+/// Primeiro filho direto `{ns}local`
+pub fn child<'a, 'input>(node: Node<'a, 'input>, ns: &str, local: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| is(*n, ns, local))
 }
 
-/// Extrai bloco XML completo de uma tag (incluindo a tag)
-fn extract_tag_block(xml: &str, tag_local: &str) -> Option<String> {
-    // Procura início da tag
-    let start_pattern = format!("<{}", tag_local);
-    if let Some(start_pos) = xml.find(&start_pattern) {
-        // Encontra fim da tag de fechamento
-        let end_pattern = format!("</{}>", tag_local);
-        if let Some(end_pos) = xml[start_pos..].find(&end_pattern) {
-            let block_end = start_pos + end_pos + end_pattern.len();
-            return Some(xml[start_pos..block_end].to_string());
-        }
-    }
-    None
+/// Todos os filhos diretos `{ns}local`
+pub fn children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    ns: &'a str,
+    local: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children().filter(move |n| is(*n, ns, local))
+}
+
+/// Primeiro descendente `{ns}local` (busca em profundidade)
+pub fn descendant<'a, 'input>(node: Node<'a, 'input>, ns: &str, local: &str) -> Option<Node<'a, 'input>> {
+    node.descendants().find(|n| is(*n, ns, local))
+}
+
+/// Texto (sem espaços nas bordas) do primeiro filho direto `{ns}local`
+pub fn child_text(node: Node, ns: &str, local: &str) -> Option<String> {
+    child(node, ns, local).and_then(text)
+}
+
+/// Texto (sem espaços nas bordas) do primeiro descendente `{ns}local`
+pub fn descendant_text(node: Node, ns: &str, local: &str) -> Option<String> {
+    descendant(node, ns, local).and_then(text)
+}
+
+/// Texto do elemento, se não vazio
+pub fn text(node: Node) -> Option<String> {
+    node.text().map(str::trim).filter(|t| !t.is_empty()).map(str::to_string)
+}
+
+/// Segue um caminho de filhos diretos no mesmo namespace
+pub fn path<'a, 'input>(node: Node<'a, 'input>, ns: &str, locals: &[&str]) -> Option<Node<'a, 'input>> {
+    locals.iter().try_fold(node, |current, local| child(current, ns, local))
+}
+
+/// Elemento `Body` do envelope SOAP (1.1 ou 1.2)
+pub fn soap_body<'a, 'input>(doc: &'a Document<'input>) -> Option<Node<'a, 'input>> {
+    let envelope = doc.root_element();
+    [ns::SOAP12, ns::SOAP11]
+        .into_iter()
+        .find(|soap| is(envelope, soap, "Envelope"))
+        .and_then(|soap| child(envelope, soap, "Body"))
+}
+
+/// Primeiro elemento dentro do `Body` (a resposta da operação)
+pub fn response_element<'a, 'input>(doc: &'a Document<'input>) -> Result<Node<'a, 'input>, OnvifError> {
+    soap_body(doc)
+        .and_then(|body| body.children().find(|n| n.is_element()))
+        .ok_or_else(|| OnvifError::InvalidResponse("SOAP Body vazio ou ausente".to_string()))
 }
 
 #[cfg(test)]
@@ -99,23 +95,38 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_extract_first_tag_text() {
-        let xml = r#"<root><tt:XAddr>http://192.168.1.1/onvif</tt:XAddr></root>"#;
-        let result = extract_first_tag_text(xml, "XAddr");
-        assert_eq!(result, Some("http://192.168.1.1/onvif".to_string()));
+    fn test_matches_by_namespace_not_prefix() {
+        let xml = r#"<env:Envelope xmlns:env="http://www.w3.org/2003/05/soap-envelope">
+            <env:Body>
+                <x:GetDeviceInformationResponse xmlns:x="http://www.onvif.org/ver10/device/wsdl">
+                    <x:Model> C100 </x:Model>
+                    <y:Model xmlns:y="urn:other">wrong</y:Model>
+                </x:GetDeviceInformationResponse>
+            </env:Body>
+        </env:Envelope>"#;
+
+        let doc = parse(xml).unwrap();
+        let response = response_element(&doc).unwrap();
+        assert!(is(response, ns::DEVICE, "GetDeviceInformationResponse"));
+        assert_eq!(child_text(response, ns::DEVICE, "Model"), Some("C100".to_string()));
+        assert_eq!(children(response, ns::DEVICE, "Model").count(), 1);
     }
 
     #[test]
-    fn test_extract_attribute() {
-        let xml = r#"<Profile token="profile_1" fixed="true">content</Profile>"#;
-        let result = extract_attribute(xml, "Profile", "token");
-        assert_eq!(result, Some("profile_1".to_string()));
+    fn test_soap11_body_and_path() {
+        let xml = r#"<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/">
+            <s:Body><r xmlns="http://www.onvif.org/ver10/schema"><a><b>42</b></a></r></s:Body>
+        </s:Envelope>"#;
+
+        let doc = parse(xml).unwrap();
+        let response = response_element(&doc).unwrap();
+        let b = path(response, ns::SCHEMA, &["a", "b"]).unwrap();
+        assert_eq!(text(b), Some("42".to_string()));
+        assert_eq!(descendant_text(response, ns::SCHEMA, "b"), Some("42".to_string()));
     }
 
     #[test]
-    fn test_extract_all_tags() {
-        let xml = r#"<root><item>1</item><item>2</item><item>3</item></root>"#;
-        let results = extract_all_tags(xml, "item");
-        assert_eq!(results.len(), 3);
+    fn test_invalid_xml() {
+        assert!(matches!(parse("<unclosed>"), Err(OnvifError::Xml(_))));
     }
 }
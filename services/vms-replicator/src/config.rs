@@ -0,0 +1,154 @@
+//! Configuração do replicador (variáveis de ambiente)
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use url::Url;
+use vms_common::service_auth::SERVICE_SECRET_ENV;
+
+/// Destino da replicação
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetConfig {
+    /// Outro nó vms-storage (`http://dr-site:9092`); `service_secret` assina
+    /// os tokens de serviço exigidos pelas rotas de replicação
+    Storage { url: String, service_secret: Option<String> },
+    /// Endpoint S3-compatível, path-style (MinIO, Ceph, AWS)
+    S3(S3Config),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct S3Config {
+    /// URL do serviço (`http://minio:9000`)
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    /// Prefixo das chaves (sem `/` nas pontas)
+    pub prefix: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplicatorConfig {
+    /// Raiz das gravações do vms-storage de origem
+    pub source_path: PathBuf,
+    pub target: TargetConfig,
+    /// Limite de banda em bytes/s (`None` = sem limite)
+    pub bandwidth_limit: Option<u64>,
+    /// Tamanho de cada pedaço enviado (parte S3 / PUT no vms-storage)
+    pub chunk_size: usize,
+    /// Arquivo sem modificações há esse tempo é considerado finalizado
+    pub settle_time: Duration,
+    pub scan_interval: Duration,
+    /// Progresso persistido (segmentos replicados e uploads S3 em andamento)
+    pub state_path: PathBuf,
+    pub port: u16,
+}
+
+impl ReplicatorConfig {
+    /// Lê a configuração do ambiente
+    ///
+    /// `REPLICATOR_TARGET` aceita `http(s)://nó-vms-storage` (com
+    /// `VMS_SERVICE_SECRET`) ou `s3://bucket/prefixo` (com `S3_ENDPOINT`,
+    /// `S3_REGION`, `S3_ACCESS_KEY` e `S3_SECRET_KEY`).
+    pub fn from_env() -> Result<Self> {
+        let env = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let number = |name: &str, default: u64| -> Result<u64> {
+            match env(name) {
+                Some(value) => value.trim().parse().with_context(|| format!("Invalid {}", name)),
+                None => Ok(default),
+            }
+        };
+
+        let target = env("REPLICATOR_TARGET").ok_or_else(|| anyhow!("REPLICATOR_TARGET is required"))?;
+        let target = parse_target(&target, |name| {
+            env(name).or_else(|| match name {
+                "S3_ACCESS_KEY" => env("AWS_ACCESS_KEY_ID"),
+                "S3_SECRET_KEY" => env("AWS_SECRET_ACCESS_KEY"),
+                "S3_REGION" => env("AWS_REGION"),
+                _ => None,
+            })
+        })?;
+
+        let bandwidth_kbps = number("REPLICATOR_BANDWIDTH_KBPS", 0)?;
+
+        Ok(Self {
+            source_path: env("REPLICATOR_SOURCE_PATH")
+                .or_else(|| env("STORAGE_PATH"))
+                .unwrap_or_else(|| "/storage/vms".to_string())
+                .into(),
+            target,
+            bandwidth_limit: (bandwidth_kbps > 0).then(|| bandwidth_kbps * 1000 / 8),
+            chunk_size: number("REPLICATOR_CHUNK_MB", 8)? as usize * 1024 * 1024,
+            settle_time: Duration::from_secs(number("REPLICATOR_SETTLE_SECS", 120)?),
+            scan_interval: Duration::from_secs(number("REPLICATOR_SCAN_INTERVAL_SECS", 30)?),
+            state_path: env("REPLICATOR_STATE_PATH")
+                .unwrap_or_else(|| "replication-state.json".to_string())
+                .into(),
+            port: number("REPLICATOR_PORT", 9102)? as u16,
+        })
+    }
+}
+
+/// Interpreta o destino; `var` resolve as credenciais
+pub fn parse_target(target: &str, var: impl Fn(&str) -> Option<String>) -> Result<TargetConfig> {
+    let url = Url::parse(target).with_context(|| format!("Invalid replication target: {}", target))?;
+
+    match url.scheme() {
+        "http" | "https" => Ok(TargetConfig::Storage {
+            url: target.trim_end_matches('/').to_string(),
+            service_secret: var(SERVICE_SECRET_ENV),
+        }),
+        "s3" => {
+            let bucket = url
+                .host_str()
+                .filter(|b| !b.is_empty())
+                .ok_or_else(|| anyhow!("S3 target needs a bucket: s3://bucket/prefix"))?;
+            let required = |name: &str| var(name).ok_or_else(|| anyhow!("{} is required for S3 targets", name));
+
+            Ok(TargetConfig::S3(S3Config {
+                endpoint: required("S3_ENDPOINT")?.trim_end_matches('/').to_string(),
+                region: var("S3_REGION").unwrap_or_else(|| "us-east-1".to_string()),
+                bucket: bucket.to_string(),
+                prefix: url.path().trim_matches('/').to_string(),
+                access_key: required("S3_ACCESS_KEY")?,
+                secret_key: required("S3_SECRET_KEY")?,
+            }))
+        }
+        other => bail!("Unsupported replication target scheme: {}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_targets() {
+        let none = |_: &str| None;
+        assert_eq!(
+            parse_target("http://dr-site:9092/", none).unwrap(),
+            TargetConfig::Storage {
+                url: "http://dr-site:9092".to_string(),
+                service_secret: None,
+            }
+        );
+        assert!(parse_target("s3://recordings/site-a", none).is_err());
+        assert!(parse_target("ftp://host", none).is_err());
+
+        let vars = |name: &str| match name {
+            "S3_ENDPOINT" => Some("http://minio:9000/".to_string()),
+            "S3_ACCESS_KEY" => Some("minio".to_string()),
+            "S3_SECRET_KEY" => Some("minio123".to_string()),
+            _ => None,
+        };
+        let TargetConfig::S3(s3) = parse_target("s3://recordings/site-a/", vars).unwrap() else {
+            panic!("expected S3 target");
+        };
+        assert_eq!(s3.endpoint, "http://minio:9000");
+        assert_eq!(s3.bucket, "recordings");
+        assert_eq!(s3.prefix, "site-a");
+        assert_eq!(s3.region, "us-east-1");
+    }
+}
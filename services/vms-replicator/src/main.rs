@@ -1,6 +1,105 @@
 //! VMS Replicator Service
-//! Backup e disaster recovery
+//! Backup e disaster recovery: copia os segmentos finalizados (vídeo e
+//! índices) do vms-storage local para outro nó vms-storage ou para um bucket
+//! S3-compatível, com limite de banda, retomada, verificação de SHA-256 e
+//! lag de replicação por câmera
 
-fn main() {
-    println!("VMS Replicator Service - Coming soon");
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::{extract::State, routing::get, Json, Router};
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tracing::info;
+
+mod config;
+mod metrics;
+mod replicator;
+mod scanner;
+mod state;
+mod targets;
+mod throttle;
+
+#[cfg(test)]
+#[path = "../../vms-storage/src/replication.rs"]
+mod storage_receiver;
+
+use config::ReplicatorConfig;
+use metrics::Metrics;
+use replicator::Replicator;
+use state::ReplicationState;
+use targets::Target;
+
+/// App state
+#[derive(Clone)]
+struct AppState {
+    replicator: Arc<Replicator>,
+    metrics: Arc<Metrics>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_target(false)
+        .with_level(true)
+        .init();
+
+    info!("🚀 VMS Replicator Service starting...");
+    info!("Version: {}", env!("CARGO_PKG_VERSION"));
+
+    let config = ReplicatorConfig::from_env()?;
+    let target = Target::new(&config.target, reqwest::Client::new());
+    info!("📂 Source: {}", config.source_path.display());
+    info!("🎯 Target: {}", target.describe());
+    match config.bandwidth_limit {
+        Some(limit) => info!("🚦 Bandwidth limit: {} kbit/s", limit * 8 / 1000),
+        None => info!("🚦 Bandwidth limit: none"),
+    }
+
+    let state = ReplicationState::load(&config.state_path).await;
+    info!(
+        "💾 State: {} ({} segments already replicated)",
+        config.state_path.display(),
+        state.segments.len()
+    );
+
+    let metrics = Arc::new(Metrics::new());
+    let replicator = Arc::new(Replicator::new(&config, target, state, metrics.clone()));
+    tokio::spawn(replicator.clone().run(config.scan_interval));
+
+    let app = Router::new()
+        .route("/health", get(|| async { "OK" }))
+        .route("/metrics", get(metrics_handler))
+        .route("/api/v1/replication/status", get(status_handler))
+        .with_state(AppState { replicator, metrics });
+
+    let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
+    let listener = TcpListener::bind(addr).await?;
+
+    info!("🌐 API listening on http://{}", addr);
+    info!("📊 Metrics: http://{}/metrics", addr);
+    info!("✅ Service initialized successfully");
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            tokio::signal::ctrl_c().await.ok();
+        })
+        .await?;
+
+    info!("👋 Goodbye!");
+    Ok(())
+}
+
+async fn metrics_handler(State(state): State<AppState>) -> String {
+    state.metrics.render().await
+}
+
+async fn status_handler(State(state): State<AppState>) -> Json<Value> {
+    Json(json!({
+        "target": state.replicator.target().describe(),
+        "bandwidth_limit_bytes_per_sec": state.replicator.throttle().rate(),
+        "last_pass": state.replicator.last_pass().await,
+        "cameras": state.metrics.snapshot().await,
+    }))
 }
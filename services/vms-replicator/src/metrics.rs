@@ -0,0 +1,171 @@
+//! Métricas de replicação por câmera (formato Prometheus)
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::RwLock;
+
+use crate::scanner::Segment;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CameraReplication {
+    /// Idade do segmento finalizado mais antigo ainda não replicado (0 = em dia)
+    pub lag_secs: f64,
+    pub pending_segments: usize,
+    pub pending_bytes: u64,
+    pub replicated_segments: u64,
+    pub replicated_bytes: u64,
+    pub failures: u64,
+    pub last_replicated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Default)]
+pub struct Metrics {
+    cameras: RwLock<BTreeMap<String, CameraReplication>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Atualiza a fila pendente a partir de uma varredura
+    pub async fn update_pending(&self, pending: &[Segment], now: DateTime<Utc>) {
+        let mut cameras = self.cameras.write().await;
+        for camera in cameras.values_mut() {
+            camera.lag_secs = 0.0;
+            camera.pending_segments = 0;
+            camera.pending_bytes = 0;
+        }
+        for segment in pending {
+            let camera = cameras.entry(segment.camera_id.clone()).or_default();
+            let age = (now - segment.modified).num_milliseconds().max(0) as f64 / 1000.0;
+            camera.lag_secs = camera.lag_secs.max(age);
+            camera.pending_segments += 1;
+            camera.pending_bytes += segment.size;
+        }
+    }
+
+    /// Segmento replicado; sai da fila pendente e o lag passa ao próximo
+    pub async fn record_replicated(&self, segment: &Segment, remaining: &[Segment], now: DateTime<Utc>) {
+        let mut cameras = self.cameras.write().await;
+        let camera = cameras.entry(segment.camera_id.clone()).or_default();
+        camera.replicated_segments += 1;
+        camera.replicated_bytes += segment.size;
+        camera.pending_segments = camera.pending_segments.saturating_sub(1);
+        camera.pending_bytes = camera.pending_bytes.saturating_sub(segment.size);
+        camera.last_replicated_at = Some(now);
+        camera.lag_secs = remaining
+            .iter()
+            .filter(|s| s.camera_id == segment.camera_id)
+            .map(|s| (now - s.modified).num_milliseconds().max(0) as f64 / 1000.0)
+            .fold(0.0, f64::max);
+    }
+
+    pub async fn record_failure(&self, camera_id: &str) {
+        self.cameras
+            .write()
+            .await
+            .entry(camera_id.to_string())
+            .or_default()
+            .failures += 1;
+    }
+
+    pub async fn snapshot(&self) -> BTreeMap<String, CameraReplication> {
+        self.cameras.read().await.clone()
+    }
+
+    /// Exposição em texto Prometheus
+    pub async fn render(&self) -> String {
+        let cameras = self.cameras.read().await;
+        let mut out = String::new();
+
+        let mut family = |name: &str, kind: &str, help: &str, value: &dyn Fn(&CameraReplication) -> String| {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            for (camera_id, camera) in cameras.iter() {
+                let _ = writeln!(out, "{}{{camera_id=\"{}\"}} {}", name, camera_id, value(camera));
+            }
+        };
+
+        family(
+            "vms_replication_lag_seconds",
+            "gauge",
+            "Age of the oldest finalized segment not yet replicated",
+            &|c| format!("{:.3}", c.lag_secs),
+        );
+        family(
+            "vms_replication_pending_segments",
+            "gauge",
+            "Finalized segments waiting for replication",
+            &|c| c.pending_segments.to_string(),
+        );
+        family(
+            "vms_replication_pending_bytes",
+            "gauge",
+            "Bytes waiting for replication",
+            &|c| c.pending_bytes.to_string(),
+        );
+        family(
+            "vms_replication_segments_total",
+            "counter",
+            "Segments replicated",
+            &|c| c.replicated_segments.to_string(),
+        );
+        family(
+            "vms_replication_bytes_total",
+            "counter",
+            "Segment bytes replicated",
+            &|c| c.replicated_bytes.to_string(),
+        );
+        family(
+            "vms_replication_failures_total",
+            "counter",
+            "Failed replication attempts",
+            &|c| c.failures.to_string(),
+        );
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn segment(camera_id: &str, relative: &str, size: u64, modified: DateTime<Utc>) -> Segment {
+        Segment {
+            camera_id: camera_id.to_string(),
+            relative: relative.to_string(),
+            path: relative.into(),
+            size,
+            modified,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lag_per_camera() {
+        let now = Utc::now();
+        let old = segment("cam-1", "cam-1/2026-03-01/video_10.mkv", 100, now - Duration::seconds(600));
+        let newer = segment("cam-1", "cam-1/2026-03-01/video_11.mkv", 50, now - Duration::seconds(300));
+
+        let metrics = Metrics::new();
+        metrics.update_pending(&[old.clone(), newer.clone()], now).await;
+        let cam = &metrics.snapshot().await["cam-1"];
+        assert_eq!(cam.pending_segments, 2);
+        assert_eq!(cam.pending_bytes, 150);
+        assert!((cam.lag_secs - 600.0).abs() < 0.01);
+
+        metrics.record_replicated(&old, std::slice::from_ref(&newer), now).await;
+        assert!((metrics.snapshot().await["cam-1"].lag_secs - 300.0).abs() < 0.01);
+
+        metrics.record_replicated(&newer, &[], now).await;
+        let rendered = metrics.render().await;
+        assert!(rendered.contains("vms_replication_lag_seconds{camera_id=\"cam-1\"} 0.000"));
+        assert!(rendered.contains("vms_replication_segments_total{camera_id=\"cam-1\"} 2"));
+        assert!(rendered.contains("vms_replication_bytes_total{camera_id=\"cam-1\"} 150"));
+    }
+}
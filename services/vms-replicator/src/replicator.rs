@@ -0,0 +1,292 @@
+//! Laço de replicação: varre a origem e envia os segmentos finalizados pendentes
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{info, warn};
+use vms_common::replication::sha256_file;
+
+use crate::config::ReplicatorConfig;
+use crate::metrics::Metrics;
+use crate::scanner::{self, Segment};
+use crate::state::ReplicationState;
+use crate::targets::{Target, Upload};
+use crate::throttle::Throttle;
+
+/// Resultado de uma passada
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PassSummary {
+    pub started_at: Option<DateTime<Utc>>,
+    pub pending: usize,
+    pub replicated: usize,
+    pub failed: usize,
+    /// Bytes efetivamente transferidos (retomadas não reenviam o que o destino já tem)
+    pub bytes_sent: u64,
+}
+
+pub struct Replicator {
+    source_path: PathBuf,
+    settle_time: Duration,
+    chunk_size: usize,
+    target: Target,
+    throttle: Arc<Throttle>,
+    state: Mutex<ReplicationState>,
+    metrics: Arc<Metrics>,
+    last_pass: RwLock<PassSummary>,
+}
+
+impl Replicator {
+    pub fn new(config: &ReplicatorConfig, target: Target, state: ReplicationState, metrics: Arc<Metrics>) -> Self {
+        Self {
+            source_path: config.source_path.clone(),
+            settle_time: config.settle_time,
+            chunk_size: config.chunk_size.max(1),
+            target,
+            throttle: Arc::new(Throttle::new(config.bandwidth_limit)),
+            state: Mutex::new(state),
+            metrics,
+            last_pass: RwLock::new(PassSummary::default()),
+        }
+    }
+
+    pub fn target(&self) -> &Target {
+        &self.target
+    }
+
+    pub fn throttle(&self) -> &Throttle {
+        &self.throttle
+    }
+
+    pub async fn last_pass(&self) -> PassSummary {
+        self.last_pass.read().await.clone()
+    }
+
+    /// Executa passadas a cada `interval` até o processo terminar
+    pub async fn run(self: Arc<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match self.run_once().await {
+                Ok(summary) if summary.replicated > 0 || summary.failed > 0 => {
+                    info!(
+                        "🔁 Replication pass: {} replicated, {} failed, {} bytes sent",
+                        summary.replicated, summary.failed, summary.bytes_sent
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => warn!("⚠️  Replication pass failed: {:#}", e),
+            }
+        }
+    }
+
+    /// Uma passada completa sobre os segmentos pendentes
+    pub async fn run_once(&self) -> Result<PassSummary> {
+        let now = Utc::now();
+        let root = self.source_path.clone();
+        let segments = tokio::task::spawn_blocking(move || scanner::scan(&root)).await??;
+
+        let mut pending: Vec<Segment> = {
+            let mut state = self.state.lock().await;
+            state.retain_existing(&segments);
+            segments
+                .into_iter()
+                .filter(|s| s.is_finalized(self.settle_time, now) && !state.is_replicated(s))
+                .collect()
+        };
+        self.metrics.update_pending(&pending, now).await;
+
+        let mut summary = PassSummary {
+            started_at: Some(now),
+            pending: pending.len(),
+            ..Default::default()
+        };
+
+        while !pending.is_empty() {
+            let segment = pending.remove(0);
+            match self.replicate(&segment).await {
+                Ok(sent) => {
+                    summary.replicated += 1;
+                    summary.bytes_sent += sent;
+                    self.metrics.record_replicated(&segment, &pending, Utc::now()).await;
+                }
+                Err(e) => {
+                    summary.failed += 1;
+                    self.metrics.record_failure(&segment.camera_id).await;
+                    warn!("⚠️  Failed to replicate {}: {:#}", segment.relative, e);
+                }
+            }
+        }
+
+        *self.last_pass.write().await = summary.clone();
+        Ok(summary)
+    }
+
+    async fn replicate(&self, segment: &Segment) -> Result<u64> {
+        let sha256 = sha256_file(&segment.path).await?;
+        let upload = Upload {
+            segment,
+            sha256: &sha256,
+            throttle: &self.throttle,
+            chunk_size: self.chunk_size,
+        };
+
+        let mut state = self.state.lock().await;
+        let sent = self.target.replicate(&upload, &mut state).await?;
+
+        // O arquivo mudou durante o envio: fica para a próxima passada
+        let current = tokio::fs::metadata(&segment.path).await?;
+        let modified: DateTime<Utc> = current.modified()?.into();
+        if current.len() != segment.size || modified != segment.modified {
+            anyhow::bail!("{} changed during replication", segment.relative);
+        }
+
+        state.mark_replicated(segment, sha256);
+        state.save().await?;
+        Ok(sent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TargetConfig;
+    use crate::storage_receiver::replication_routes;
+    use std::path::Path;
+
+    const SECRET: &str = "service-secret";
+
+    fn storage(url: &str) -> TargetConfig {
+        TargetConfig::Storage {
+            url: url.to_string(),
+            service_secret: Some(SECRET.to_string()),
+        }
+    }
+
+    async fn spawn_receiver(root: &Path) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = replication_routes(root.to_path_buf(), Some(SECRET.to_string()));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    fn config(source: &Path, target: TargetConfig, state_path: PathBuf) -> ReplicatorConfig {
+        ReplicatorConfig {
+            source_path: source.to_path_buf(),
+            target,
+            bandwidth_limit: None,
+            chunk_size: 1000,
+            settle_time: Duration::ZERO,
+            scan_interval: Duration::from_secs(30),
+            state_path,
+            port: 0,
+        }
+    }
+
+    fn write(root: &Path, relative: &str, data: &[u8]) {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, data).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replicates_to_storage_with_resume() {
+        let source = tempfile::tempdir().unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let url = spawn_receiver(dest.path()).await;
+
+        let video: Vec<u8> = (0..4500u32).map(|i| (i % 251) as u8).collect();
+        write(source.path(), "cam-1/2026-03-01/video_10.mkv", &video);
+        write(source.path(), "cam-1/2026-03-01/index_10.json", b"{\"keyframes\":[]}");
+        // Envio anterior interrompido: o destino já tem os primeiros 2000 bytes
+        write(dest.path(), "cam-1/2026-03-01/video_10.mkv.part", &video[..2000]);
+
+        let state_path = source.path().join(".replication-state.json");
+        let config = config(source.path(), storage(&url), state_path.clone());
+        let target = Target::new(&config.target, reqwest::Client::new());
+        let metrics = Arc::new(Metrics::new());
+        let replicator = Replicator::new(&config, target, ReplicationState::load(&state_path).await, metrics.clone());
+
+        let summary = replicator.run_once().await.unwrap();
+        assert_eq!(summary.replicated, 2);
+        assert_eq!(summary.failed, 0);
+        assert_eq!(summary.bytes_sent, 2500 + 16);
+
+        let copied = std::fs::read(dest.path().join("cam-1/2026-03-01/video_10.mkv")).unwrap();
+        assert_eq!(copied, video);
+        let checksum = std::fs::read_to_string(dest.path().join("cam-1/2026-03-01/video_10.mkv.sha256")).unwrap();
+        assert_eq!(checksum, sha256_file(&source.path().join("cam-1/2026-03-01/video_10.mkv")).await.unwrap());
+        assert_eq!(metrics.snapshot().await["cam-1"].lag_secs, 0.0);
+
+        // Estado persistido: um novo processo não reenvia nada
+        let target = Target::new(&config.target, reqwest::Client::new());
+        let restarted = Replicator::new(&config, target, ReplicationState::load(&state_path).await, metrics);
+        let summary = restarted.run_once().await.unwrap();
+        assert_eq!(summary.pending, 0);
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_replica_is_reported() {
+        let source = tempfile::tempdir().unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let url = spawn_receiver(dest.path()).await;
+
+        write(source.path(), "cam-2/2026-03-01/video_08.mkv", b"local recording");
+        write(dest.path(), "cam-2/2026-03-01/video_08.mkv", b"other recording");
+        write(dest.path(), "cam-2/2026-03-01/video_08.mkv.sha256", &[b'0'; 64]);
+
+        let config = config(source.path(), storage(&url), PathBuf::new());
+        let target = Target::new(&config.target, reqwest::Client::new());
+        let metrics = Arc::new(Metrics::new());
+        let replicator = Replicator::new(&config, target, ReplicationState::default(), metrics.clone());
+
+        let summary = replicator.run_once().await.unwrap();
+        assert_eq!(summary.failed, 1);
+        let camera = &metrics.snapshot().await["cam-2"];
+        assert_eq!(camera.failures, 1);
+        assert_eq!(camera.pending_segments, 1);
+    }
+
+    #[tokio::test]
+    async fn test_receiver_requires_token_and_keeps_local_files() {
+        let dest = tempfile::tempdir().unwrap();
+        let url = spawn_receiver(dest.path()).await;
+        let file_url = format!("{}{}/cam-3/2026-03-01/video_09.mkv", url, vms_common::replication::REPLICATION_FILES_PATH);
+        let client = reqwest::Client::new();
+
+        // Sem token ou com token de outro segredo
+        let response = client.get(&file_url).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+        let forged = vms_common::service_auth::ServiceCredential::new("vms-replicator", "other-secret");
+        let response = client
+            .put(&file_url)
+            .query(&[("offset", "0"), ("total", "4"), ("sha256", "00")])
+            .header(reqwest::header::AUTHORIZATION, forged.bearer())
+            .body("data")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+        assert!(!dest.path().join("cam-3/2026-03-01/video_09.mkv.part").exists());
+
+        // Gravação local sem `.sha256` não é sobrescrita
+        write(dest.path(), "cam-3/2026-03-01/video_09.mkv", b"local");
+        let credential = vms_common::service_auth::ServiceCredential::new("vms-replicator", SECRET);
+        let response = client
+            .put(&file_url)
+            .query(&[("offset", "0"), ("total", "4"), ("sha256", "00")])
+            .header(reqwest::header::AUTHORIZATION, credential.bearer())
+            .body("data")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::CONFLICT);
+        assert_eq!(std::fs::read(dest.path().join("cam-3/2026-03-01/video_09.mkv")).unwrap(), b"local");
+    }
+}
@@ -0,0 +1,157 @@
+//! Varredura de segmentos finalizados no armazenamento de origem
+//!
+//! Reconhece os layouts do vms-storage (`{câmera}/{AAAA-MM-DD}/video_HH.mkv`
+//! e `cameras/{câmera}/{AAAA-MM-DD}/...`): vídeo e índices de um diretório de
+//! data são replicados com o mesmo path relativo.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Extensões replicadas (vídeo e índices)
+const EXTENSIONS: &[&str] = &["mkv", "mp4", "ts", "json", "vms-idx"];
+
+/// Profundidade máxima a partir da raiz
+const MAX_DEPTH: usize = 4;
+
+/// Arquivo de gravação na origem
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub camera_id: String,
+    /// Path relativo à raiz, com `/` (também é a chave no destino)
+    pub relative: String,
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: DateTime<Utc>,
+}
+
+impl Segment {
+    /// Sem escrita há pelo menos `settle`: o gravador fechou o arquivo
+    pub fn is_finalized(&self, settle: Duration, now: DateTime<Utc>) -> bool {
+        now - self.modified >= chrono::Duration::from_std(settle).unwrap_or_default()
+    }
+}
+
+/// Lista os segmentos sob `root`, do mais antigo para o mais novo
+pub fn scan(root: &Path) -> Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+    if root.is_dir() {
+        walk(root, root, 0, &mut segments)?;
+    }
+    segments.sort_by(|a, b| a.modified.cmp(&b.modified).then(a.relative.cmp(&b.relative)));
+    Ok(segments)
+}
+
+fn walk(root: &Path, dir: &Path, depth: usize, segments: &mut Vec<Segment>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if depth < MAX_DEPTH {
+                walk(root, &path, depth + 1, segments)?;
+            }
+        } else if file_type.is_file() {
+            if let Some(segment) = segment_for(root, &path)? {
+                segments.push(segment);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn segment_for(root: &Path, path: &Path) -> Result<Option<Segment>> {
+    let wanted = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| EXTENSIONS.contains(&ext));
+    if !wanted {
+        return Ok(None);
+    }
+
+    // .../{câmera}/{AAAA-MM-DD}/{arquivo}
+    let Some(date_dir) = path.parent() else {
+        return Ok(None);
+    };
+    let is_date = date_dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| NaiveDate::parse_from_str(n, "%Y-%m-%d").is_ok());
+    let camera_id = date_dir
+        .parent()
+        .and_then(|p| p.file_name())
+        .and_then(|n| n.to_str());
+    let (true, Some(camera_id)) = (is_date, camera_id) else {
+        return Ok(None);
+    };
+
+    let Ok(relative) = path.strip_prefix(root) else {
+        return Ok(None);
+    };
+    let relative = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let metadata = fs::metadata(path)?;
+    Ok(Some(Segment {
+        camera_id: camera_id.to_string(),
+        relative,
+        path: path.to_path_buf(),
+        size: metadata.len(),
+        modified: metadata.modified()?.into(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_both_layouts() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let files = [
+            "cam-1/2026-03-01/video_10.mkv",
+            "cameras/cam-2/2026-03-01/video_11.mkv",
+            "cameras/cam-2/2026-03-01/index_11.json",
+            "cam-1/2026-03-01/video_11.mkv.part",
+            "cam-1/2026-03-01/notes.txt",
+            "cam-1/misc/video_09.mkv",
+            ".replication/cam-9/2026-03-01/video_00.mkv",
+        ];
+        for file in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, b"data").unwrap();
+        }
+
+        let mut segments = scan(root).unwrap();
+        segments.sort_by(|a, b| a.relative.cmp(&b.relative));
+        let found: Vec<_> = segments
+            .iter()
+            .map(|s| (s.camera_id.as_str(), s.relative.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("cam-1", "cam-1/2026-03-01/video_10.mkv"),
+                ("cam-2", "cameras/cam-2/2026-03-01/index_11.json"),
+                ("cam-2", "cameras/cam-2/2026-03-01/video_11.mkv"),
+            ]
+        );
+
+        let segment = &segments[0];
+        assert!(!segment.is_finalized(Duration::from_secs(60), Utc::now()));
+        assert!(segment.is_finalized(Duration::from_secs(60), Utc::now() + chrono::Duration::seconds(61)));
+    }
+}
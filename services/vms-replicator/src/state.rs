@@ -0,0 +1,92 @@
+//! Progresso persistido da replicação
+//!
+//! Guarda os segmentos já replicados (tamanho, mtime e SHA-256) e os uploads
+//! multipart S3 em andamento, para retomar após reinício sem reenviar dados.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::scanner::Segment;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplicatedSegment {
+    pub size: u64,
+    pub modified: DateTime<Utc>,
+    pub sha256: String,
+    pub replicated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReplicationState {
+    /// Path relativo → segmento replicado
+    #[serde(default)]
+    pub segments: HashMap<String, ReplicatedSegment>,
+
+    /// Path relativo → upload multipart S3 em andamento
+    #[serde(default)]
+    pub uploads: HashMap<String, String>,
+
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl ReplicationState {
+    /// Carrega o estado (arquivo ausente ou corrompido = estado vazio)
+    pub async fn load(path: &Path) -> Self {
+        let mut state = match tokio::fs::read(path).await {
+            Ok(data) => serde_json::from_slice(&data).unwrap_or_else(|e| {
+                warn!("⚠️  Ignoring unreadable replication state {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        };
+        state.path = path.to_path_buf();
+        state
+    }
+
+    /// Grava de forma atômica (arquivo temporário + rename)
+    pub async fn save(&self) -> Result<()> {
+        if self.path.as_os_str().is_empty() {
+            return Ok(());
+        }
+        let tmp = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp, serde_json::to_vec_pretty(self)?)
+            .await
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        tokio::fs::rename(&tmp, &self.path).await?;
+        Ok(())
+    }
+
+    /// O segmento já foi replicado com este conteúdo (tamanho e mtime iguais)
+    pub fn is_replicated(&self, segment: &Segment) -> bool {
+        self.segments
+            .get(&segment.relative)
+            .is_some_and(|done| done.size == segment.size && done.modified == segment.modified)
+    }
+
+    pub fn mark_replicated(&mut self, segment: &Segment, sha256: String) {
+        self.uploads.remove(&segment.relative);
+        self.segments.insert(
+            segment.relative.clone(),
+            ReplicatedSegment {
+                size: segment.size,
+                modified: segment.modified,
+                sha256,
+                replicated_at: Utc::now(),
+            },
+        );
+    }
+
+    /// Esquece segmentos que não existem mais na origem (retenção)
+    pub fn retain_existing(&mut self, segments: &[Segment]) {
+        let existing: std::collections::HashSet<&str> =
+            segments.iter().map(|s| s.relative.as_str()).collect();
+        self.segments.retain(|path, _| existing.contains(path.as_str()));
+        self.uploads.retain(|path, _| existing.contains(path.as_str()));
+    }
+}
@@ -0,0 +1,85 @@
+//! Destinos de replicação: outro vms-storage (HTTP) ou S3-compatível
+
+pub mod s3;
+pub mod sigv4;
+pub mod storage;
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use bytes::Bytes;
+use thiserror::Error;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use crate::config::TargetConfig;
+use crate::scanner::Segment;
+use crate::state::ReplicationState;
+use crate::throttle::Throttle;
+
+pub use s3::S3Target;
+pub use storage::StorageTarget;
+
+#[derive(Debug, Error)]
+pub enum ReplicationError {
+    #[error("Checksum mismatch for {0}")]
+    ChecksumMismatch(String),
+
+    #[error("{0} already exists on target with different content")]
+    Conflict(String),
+
+    #[error("Target returned {status}: {body}")]
+    Remote { status: u16, body: String },
+}
+
+/// Segmento a enviar
+pub struct Upload<'a> {
+    pub segment: &'a Segment,
+    /// SHA-256 (hex) do arquivo local
+    pub sha256: &'a str,
+    pub throttle: &'a Arc<Throttle>,
+    pub chunk_size: usize,
+}
+
+pub enum Target {
+    Storage(StorageTarget),
+    S3(S3Target),
+}
+
+impl Target {
+    pub fn new(config: &TargetConfig, client: reqwest::Client) -> Self {
+        match config {
+            TargetConfig::Storage { url, service_secret } => Self::Storage(StorageTarget::new(
+                url,
+                service_secret.as_deref(),
+                client,
+            )),
+            TargetConfig::S3(s3) => Self::S3(S3Target::new(s3.clone(), client)),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Storage(target) => target.describe(),
+            Self::S3(target) => target.describe(),
+        }
+    }
+
+    /// Envia o segmento (retomando o que o destino já tem) e confere a
+    /// integridade; retorna os bytes efetivamente transferidos
+    pub async fn replicate(&self, upload: &Upload<'_>, state: &mut ReplicationState) -> Result<u64> {
+        match self {
+            Self::Storage(target) => target.replicate(upload).await,
+            Self::S3(target) => target.replicate(upload, state).await,
+        }
+    }
+}
+
+/// Lê `len` bytes a partir de `offset`
+pub async fn read_chunk(path: &Path, offset: u64, len: usize) -> std::io::Result<Bytes> {
+    let mut file = tokio::fs::File::open(path).await?;
+    file.seek(std::io::SeekFrom::Start(offset)).await?;
+    let mut buf = vec![0u8; len];
+    file.read_exact(&mut buf).await?;
+    Ok(Bytes::from(buf))
+}
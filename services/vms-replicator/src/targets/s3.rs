@@ -0,0 +1,658 @@
+//! Destino S3-compatível (path-style, AWS SigV4)
+//!
+//! Arquivos até `chunk_size` vão num único `PutObject`; maiores usam
+//! multipart upload, cujo `UploadId` fica no estado para retomar pelas partes
+//! já aceitas (`ListParts`). Cada requisição leva o SHA-256 do payload em
+//! `x-amz-content-sha256`, verificado pelo servidor, e o SHA-256 do arquivo
+//! fica em `x-amz-meta-sha256`, conferido por `HeadObject` ao final.
+
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use chrono::Utc;
+use percent_encoding::utf8_percent_encode;
+use reqwest::{Method, StatusCode};
+use tracing::{debug, info};
+use url::Url;
+
+use super::sigv4::{self, Credentials, EMPTY_PAYLOAD_SHA256, URI_ENCODE};
+use super::{read_chunk, ReplicationError, Upload};
+use crate::config::S3Config;
+use crate::state::ReplicationState;
+
+const META_SHA256: &str = "x-amz-meta-sha256";
+
+/// Parte já aceita num upload multipart
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadedPart {
+    pub number: u32,
+    pub etag: String,
+    pub size: u64,
+}
+
+/// Objeto existente (`HeadObject`)
+#[derive(Debug, Clone)]
+pub struct ObjectInfo {
+    pub size: u64,
+    pub sha256: Option<String>,
+}
+
+pub struct S3Target {
+    config: S3Config,
+    client: reqwest::Client,
+}
+
+impl S3Target {
+    pub fn new(config: S3Config, client: reqwest::Client) -> Self {
+        Self { config, client }
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "s3://{}/{} at {}",
+            self.config.bucket, self.config.prefix, self.config.endpoint
+        )
+    }
+
+    /// Chave do objeto para um path relativo
+    pub fn key(&self, relative: &str) -> String {
+        if self.config.prefix.is_empty() {
+            relative.to_string()
+        } else {
+            format!("{}/{}", self.config.prefix, relative)
+        }
+    }
+
+    fn url(&self, key: &str, query: &str) -> Result<Url> {
+        let path = key
+            .split('/')
+            .map(|segment| utf8_percent_encode(segment, URI_ENCODE).to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        let mut url = format!(
+            "{}/{}/{}",
+            self.config.endpoint,
+            utf8_percent_encode(&self.config.bucket, URI_ENCODE),
+            path
+        );
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(query);
+        }
+        Ok(Url::parse(&url)?)
+    }
+
+    /// Requisição assinada; `body` já com o SHA-256 correspondente
+    async fn send(
+        &self,
+        method: Method,
+        url: Url,
+        extra_headers: &[(&str, &str)],
+        body: Option<(reqwest::Body, u64)>,
+        payload_sha256: &str,
+    ) -> Result<reqwest::Response> {
+        let now = Utc::now();
+        let date = sigv4::amz_date(now);
+        let host = sigv4::host_header(&url);
+
+        let mut headers = vec![
+            ("host", host.as_str()),
+            ("x-amz-date", date.as_str()),
+            ("x-amz-content-sha256", payload_sha256),
+        ];
+        headers.extend_from_slice(extra_headers);
+
+        let authorization = sigv4::authorization(
+            method.as_str(),
+            &url,
+            &headers,
+            payload_sha256,
+            now,
+            &Credentials {
+                access_key: &self.config.access_key,
+                secret_key: &self.config.secret_key,
+                region: &self.config.region,
+            },
+        );
+
+        let mut request = self
+            .client
+            .request(method, url)
+            .header(reqwest::header::AUTHORIZATION, authorization);
+        for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+            request = request.header(*name, *value);
+        }
+        if let Some((body, len)) = body {
+            request = request
+                .header(reqwest::header::CONTENT_LENGTH, len)
+                .body(body);
+        }
+
+        Ok(request.send().await?)
+    }
+
+    pub async fn head(&self, key: &str) -> Result<Option<ObjectInfo>> {
+        let response = self
+            .send(Method::HEAD, self.url(key, "")?, &[], None, EMPTY_PAYLOAD_SHA256)
+            .await?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => {
+                let header = |name: &str| {
+                    response
+                        .headers()
+                        .get(name)
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_string)
+                };
+                Ok(Some(ObjectInfo {
+                    size: header("content-length")
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(0),
+                    sha256: header(META_SHA256),
+                }))
+            }
+            status => Err(remote(status, response).await.into()),
+        }
+    }
+
+    pub async fn replicate(&self, upload: &Upload<'_>, state: &mut ReplicationState) -> Result<u64> {
+        let segment = upload.segment;
+        let key = self.key(&segment.relative);
+
+        if let Some(existing) = self.head(&key).await? {
+            if existing.size == segment.size && existing.sha256.as_deref() == Some(upload.sha256) {
+                return Ok(0);
+            }
+        }
+
+        let sent = if segment.size <= upload.chunk_size as u64 {
+            self.put_object(upload, &key).await?
+        } else {
+            self.multipart(upload, &key, state).await?
+        };
+
+        match self.head(&key).await? {
+            Some(object) if object.size == segment.size && object.sha256.as_deref() == Some(upload.sha256) => {
+                Ok(sent)
+            }
+            _ => Err(ReplicationError::ChecksumMismatch(segment.relative.clone()).into()),
+        }
+    }
+
+    async fn put_object(&self, upload: &Upload<'_>, key: &str) -> Result<u64> {
+        let data = read_chunk(&upload.segment.path, 0, upload.segment.size as usize).await?;
+        let len = data.len() as u64;
+        let response = self
+            .send(
+                Method::PUT,
+                self.url(key, "")?,
+                &[(META_SHA256, upload.sha256)],
+                Some((reqwest::Body::wrap_stream(upload.throttle.stream(data)), len)),
+                upload.sha256,
+            )
+            .await?;
+        check(response).await?;
+        Ok(len)
+    }
+
+    async fn multipart(&self, upload: &Upload<'_>, key: &str, state: &mut ReplicationState) -> Result<u64> {
+        let segment = upload.segment;
+
+        // Retoma o upload anterior, se o servidor ainda o conhece
+        let mut resumed = None;
+        if let Some(upload_id) = state.uploads.get(&segment.relative).cloned() {
+            if let Some(parts) = self.list_parts(key, &upload_id).await? {
+                info!(
+                    "⏯️  Resuming multipart upload of {} ({} parts already stored)",
+                    segment.relative,
+                    parts.len()
+                );
+                resumed = Some((upload_id, parts));
+            }
+        }
+        let (upload_id, uploaded) = match resumed {
+            Some(resumed) => resumed,
+            None => {
+                let upload_id = self.create_multipart(key, upload.sha256).await?;
+                state.uploads.insert(segment.relative.clone(), upload_id.clone());
+                state.save().await?;
+                (upload_id, Vec::new())
+            }
+        };
+
+        let chunk = upload.chunk_size as u64;
+        let part_count = segment.size.div_ceil(chunk) as u32;
+        let mut parts = Vec::with_capacity(part_count as usize);
+        let mut sent = 0u64;
+
+        for number in 1..=part_count {
+            let offset = (number as u64 - 1) * chunk;
+            let len = chunk.min(segment.size - offset);
+
+            if let Some(part) = uploaded.iter().find(|p| p.number == number && p.size == len) {
+                parts.push(part.clone());
+                continue;
+            }
+
+            let data = read_chunk(&segment.path, offset, len as usize).await?;
+            let etag = self.upload_part(upload, key, &upload_id, number, data).await?;
+            debug!("📤 {} part {}/{}", segment.relative, number, part_count);
+            parts.push(UploadedPart { number, etag, size: len });
+            sent += len;
+        }
+
+        self.complete_multipart(key, &upload_id, &parts).await?;
+        Ok(sent)
+    }
+
+    async fn create_multipart(&self, key: &str, sha256: &str) -> Result<String> {
+        let response = self
+            .send(
+                Method::POST,
+                self.url(key, "uploads=")?,
+                &[(META_SHA256, sha256)],
+                None,
+                EMPTY_PAYLOAD_SHA256,
+            )
+            .await?;
+        let body = check(response).await?;
+        xml_text(&body, "UploadId").ok_or_else(|| anyhow!("CreateMultipartUpload without UploadId"))
+    }
+
+    async fn upload_part(
+        &self,
+        upload: &Upload<'_>,
+        key: &str,
+        upload_id: &str,
+        number: u32,
+        data: Bytes,
+    ) -> Result<String> {
+        let query = format!(
+            "partNumber={}&uploadId={}",
+            number,
+            utf8_percent_encode(upload_id, URI_ENCODE)
+        );
+        let sha256 = sigv4::sha256_hex(&data);
+        let len = data.len() as u64;
+        let response = self
+            .send(
+                Method::PUT,
+                self.url(key, &query)?,
+                &[],
+                Some((reqwest::Body::wrap_stream(upload.throttle.stream(data)), len)),
+                &sha256,
+            )
+            .await?;
+
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        check(response).await?;
+        etag.ok_or_else(|| anyhow!("UploadPart without ETag"))
+    }
+
+    /// Partes já aceitas; `None` se o upload não existe mais
+    pub async fn list_parts(&self, key: &str, upload_id: &str) -> Result<Option<Vec<UploadedPart>>> {
+        let query = format!("uploadId={}", utf8_percent_encode(upload_id, URI_ENCODE));
+        let response = self
+            .send(Method::GET, self.url(key, &query)?, &[], None, EMPTY_PAYLOAD_SHA256)
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let body = check(response).await?;
+        Ok(Some(parse_parts(&body)?))
+    }
+
+    async fn complete_multipart(&self, key: &str, upload_id: &str, parts: &[UploadedPart]) -> Result<()> {
+        let xml = format!(
+            "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+            parts
+                .iter()
+                .map(|p| format!(
+                    "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                    p.number,
+                    p.etag.replace('"', "&quot;")
+                ))
+                .collect::<String>()
+        );
+        let sha256 = sigv4::sha256_hex(xml.as_bytes());
+        let len = xml.len() as u64;
+        let query = format!("uploadId={}", utf8_percent_encode(upload_id, URI_ENCODE));
+        let response = self
+            .send(
+                Method::POST,
+                self.url(key, &query)?,
+                &[],
+                Some((reqwest::Body::from(xml), len)),
+                &sha256,
+            )
+            .await?;
+
+        // CompleteMultipartUpload pode falhar com 200 e <Error> no corpo
+        let body = check(response).await?;
+        if xml_text(&body, "Code").is_some() {
+            return Err(ReplicationError::Remote { status: 200, body }.into());
+        }
+        Ok(())
+    }
+}
+
+/// Corpo da resposta, ou erro se o status não for 2xx
+async fn check(response: reqwest::Response) -> Result<String> {
+    let status = response.status();
+    if !status.is_success() {
+        return Err(remote(status, response).await.into());
+    }
+    Ok(response.text().await.unwrap_or_default())
+}
+
+async fn remote(status: StatusCode, response: reqwest::Response) -> ReplicationError {
+    let body = response.text().await.unwrap_or_default();
+    if xml_text(&body, "Code").as_deref() == Some("BadDigest")
+        || xml_text(&body, "Code").as_deref() == Some("XAmzContentSHA256Mismatch")
+    {
+        return ReplicationError::ChecksumMismatch(xml_text(&body, "Key").unwrap_or_default());
+    }
+    ReplicationError::Remote {
+        status: status.as_u16(),
+        body,
+    }
+}
+
+fn xml_text(xml: &str, tag: &str) -> Option<String> {
+    let doc = roxmltree::Document::parse(xml).ok()?;
+    doc.descendants()
+        .find(|n| n.is_element() && n.tag_name().name() == tag)
+        .and_then(|n| n.text())
+        .map(str::to_string)
+}
+
+/// `ListPartsResult` → partes
+fn parse_parts(xml: &str) -> Result<Vec<UploadedPart>> {
+    let doc = roxmltree::Document::parse(xml)?;
+    let parts = doc
+        .descendants()
+        .filter(|n| n.is_element() && n.tag_name().name() == "Part")
+        .filter_map(|part| {
+            let child = |name: &str| {
+                part.children()
+                    .find(|c| c.is_element() && c.tag_name().name() == name)
+                    .and_then(|c| c.text())
+            };
+            Some(UploadedPart {
+                number: child("PartNumber")?.parse().ok()?,
+                etag: child("ETag")?.to_string(),
+                size: child("Size")?.parse().ok()?,
+            })
+        })
+        .collect();
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Segment;
+    use crate::throttle::Throttle;
+    use axum::{
+        body::Bytes as Body,
+        extract::State,
+        http::{HeaderMap, Method as HttpMethod, StatusCode as HttpStatus, Uri},
+        response::{IntoResponse, Response},
+        Router,
+    };
+    use std::collections::{BTreeMap, HashMap};
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    /// Objeto: conteúdo e `x-amz-meta-sha256`
+    type Object = (Vec<u8>, Option<String>);
+
+    /// Upload multipart: chave, `x-amz-meta-sha256` e partes recebidas
+    type MultipartUpload = (String, Option<String>, BTreeMap<u32, Vec<u8>>);
+
+    /// Stand-in do MinIO com as operações usadas pelo replicador
+    #[derive(Default)]
+    struct MiniS3 {
+        objects: HashMap<String, Object>,
+        uploads: HashMap<String, MultipartUpload>,
+        next_upload: u32,
+    }
+
+    fn s3_error(status: HttpStatus, code: &str) -> Response {
+        (status, format!("<Error><Code>{}</Code></Error>", code)).into_response()
+    }
+
+    async fn mini_s3(
+        State(store): State<Arc<Mutex<MiniS3>>>,
+        method: HttpMethod,
+        uri: Uri,
+        headers: HeaderMap,
+        body: Body,
+    ) -> Response {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+        if !header("authorization").is_some_and(|a| a.starts_with("AWS4-HMAC-SHA256 Credential=minio/")) {
+            return s3_error(HttpStatus::FORBIDDEN, "AccessDenied");
+        }
+        if header("x-amz-content-sha256").as_deref() != Some(sigv4::sha256_hex(&body).as_str()) {
+            return s3_error(HttpStatus::BAD_REQUEST, "XAmzContentSHA256Mismatch");
+        }
+
+        let path = percent_encoding::percent_decode_str(uri.path()).decode_utf8_lossy().to_string();
+        let key = path.trim_start_matches("/recordings/").to_string();
+        let query: HashMap<String, String> = url::form_urlencoded::parse(uri.query().unwrap_or("").as_bytes())
+            .into_owned()
+            .collect();
+        let mut store = store.lock().unwrap();
+
+        match (method, query.get("uploadId")) {
+            (HttpMethod::HEAD, None) => match store.objects.get(&key) {
+                Some((data, sha256)) => {
+                    let mut response = data.clone().into_response();
+                    if let Some(sha256) = sha256 {
+                        response.headers_mut().insert(META_SHA256, sha256.parse().unwrap());
+                    }
+                    response
+                }
+                None => HttpStatus::NOT_FOUND.into_response(),
+            },
+            (HttpMethod::PUT, None) => {
+                store.objects.insert(key, (body.to_vec(), header(META_SHA256)));
+                ([("etag", "\"object\"")], "").into_response()
+            }
+            (HttpMethod::POST, None) if query.contains_key("uploads") => {
+                store.next_upload += 1;
+                let id = format!("upload-{}", store.next_upload);
+                store.uploads.insert(id.clone(), (key, header(META_SHA256), BTreeMap::new()));
+                format!("<InitiateMultipartUploadResult><UploadId>{}</UploadId></InitiateMultipartUploadResult>", id)
+                    .into_response()
+            }
+            (method, Some(id)) => {
+                let Some((upload_key, sha256, parts)) = store.uploads.get_mut(id) else {
+                    return s3_error(HttpStatus::NOT_FOUND, "NoSuchUpload");
+                };
+                match method {
+                    HttpMethod::PUT => {
+                        let number: u32 = query["partNumber"].parse().unwrap();
+                        let etag = format!("\"{}\"", sigv4::sha256_hex(&body));
+                        parts.insert(number, body.to_vec());
+                        ([("etag", etag)], "").into_response()
+                    }
+                    HttpMethod::GET => format!(
+                        "<ListPartsResult>{}</ListPartsResult>",
+                        parts
+                            .iter()
+                            .map(|(n, data)| format!(
+                                "<Part><PartNumber>{}</PartNumber><ETag>\"{}\"</ETag><Size>{}</Size></Part>",
+                                n,
+                                sigv4::sha256_hex(data),
+                                data.len()
+                            ))
+                            .collect::<String>()
+                    )
+                    .into_response(),
+                    HttpMethod::POST => {
+                        let requested = parse_parts_request(&String::from_utf8_lossy(&body));
+                        if requested != parts.keys().copied().collect::<Vec<_>>() {
+                            return s3_error(HttpStatus::BAD_REQUEST, "InvalidPart");
+                        }
+                        let data = parts.values().flatten().copied().collect();
+                        let object = (data, sha256.clone());
+                        let upload_key = upload_key.clone();
+                        store.uploads.remove(id);
+                        store.objects.insert(upload_key, object);
+                        "<CompleteMultipartUploadResult/>".into_response()
+                    }
+                    _ => HttpStatus::METHOD_NOT_ALLOWED.into_response(),
+                }
+            }
+            _ => HttpStatus::METHOD_NOT_ALLOWED.into_response(),
+        }
+    }
+
+    fn parse_parts_request(xml: &str) -> Vec<u32> {
+        let doc = roxmltree::Document::parse(xml).unwrap();
+        doc.descendants()
+            .filter(|n| n.tag_name().name() == "PartNumber")
+            .filter_map(|n| n.text()?.parse().ok())
+            .collect()
+    }
+
+    async fn spawn_mini_s3() -> (S3Target, Arc<Mutex<MiniS3>>) {
+        let store = Arc::new(Mutex::new(MiniS3::default()));
+        let app = Router::new().fallback(mini_s3).with_state(store.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let target = S3Target::new(
+            S3Config {
+                endpoint: format!("http://{}", addr),
+                region: "us-east-1".to_string(),
+                bucket: "recordings".to_string(),
+                prefix: "site-a".to_string(),
+                access_key: "minio".to_string(),
+                secret_key: "minio123".to_string(),
+            },
+            reqwest::Client::new(),
+        );
+        (target, store)
+    }
+
+    fn segment(root: &Path, relative: &str, data: &[u8]) -> Segment {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, data).unwrap();
+        Segment {
+            camera_id: "cam-1".to_string(),
+            relative: relative.to_string(),
+            path,
+            size: data.len() as u64,
+            modified: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_parse_list_parts() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListPartsResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Bucket>recordings</Bucket><Key>site-a/cam/2026-03-01/video_10.mkv</Key><UploadId>abc</UploadId>
+  <Part><PartNumber>1</PartNumber><ETag>"etag-1"</ETag><Size>5242880</Size></Part>
+  <Part><PartNumber>2</PartNumber><ETag>"etag-2"</ETag><Size>1024</Size></Part>
+</ListPartsResult>"#;
+        let parts = parse_parts(xml).unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(
+            parts[0],
+            UploadedPart {
+                number: 1,
+                etag: "\"etag-1\"".to_string(),
+                size: 5_242_880
+            }
+        );
+        assert_eq!(xml_text(xml, "UploadId").as_deref(), Some("abc"));
+    }
+
+    #[tokio::test]
+    async fn test_put_object_and_skip_existing() {
+        let (target, store) = spawn_mini_s3().await;
+        let dir = tempfile::tempdir().unwrap();
+        let segment = segment(dir.path(), "cam-1/2026-03-01/index_10.json", b"{\"keyframes\":[0]}");
+        let sha256 = vms_common::replication::sha256_file(&segment.path).await.unwrap();
+        let throttle = Arc::new(Throttle::new(None));
+        let upload = Upload {
+            segment: &segment,
+            sha256: &sha256,
+            throttle: &throttle,
+            chunk_size: 1000,
+        };
+        let mut state = ReplicationState::default();
+
+        assert_eq!(target.replicate(&upload, &mut state).await.unwrap(), 17);
+        assert_eq!(target.replicate(&upload, &mut state).await.unwrap(), 0);
+
+        let store = store.lock().unwrap();
+        let (data, meta) = &store.objects["site-a/cam-1/2026-03-01/index_10.json"];
+        assert_eq!(data.as_slice(), b"{\"keyframes\":[0]}");
+        assert_eq!(meta.as_deref(), Some(sha256.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_multipart_resumes_interrupted_upload() {
+        let (target, store) = spawn_mini_s3().await;
+        let dir = tempfile::tempdir().unwrap();
+        let video: Vec<u8> = (0..2500u32).map(|i| (i % 253) as u8).collect();
+        let segment = segment(dir.path(), "cam-1/2026-03-01/video_10.mkv", &video);
+        let sha256 = vms_common::replication::sha256_file(&segment.path).await.unwrap();
+        let throttle = Arc::new(Throttle::new(None));
+        let upload = Upload {
+            segment: &segment,
+            sha256: &sha256,
+            throttle: &throttle,
+            chunk_size: 1000,
+        };
+
+        // Execução anterior interrompida após a primeira parte
+        let key = target.key(&segment.relative);
+        let upload_id = target.create_multipart(&key, &sha256).await.unwrap();
+        target
+            .upload_part(&upload, &key, &upload_id, 1, Bytes::copy_from_slice(&video[..1000]))
+            .await
+            .unwrap();
+        let mut state = ReplicationState::default();
+        state.uploads.insert(segment.relative.clone(), upload_id);
+
+        assert_eq!(target.replicate(&upload, &mut state).await.unwrap(), 1500);
+
+        let store = store.lock().unwrap();
+        assert!(store.uploads.is_empty());
+        let (data, meta) = &store.objects["site-a/cam-1/2026-03-01/video_10.mkv"];
+        assert_eq!(data, &video);
+        assert_eq!(meta.as_deref(), Some(sha256.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_multipart_restarts_unknown_upload() {
+        let (target, store) = spawn_mini_s3().await;
+        let dir = tempfile::tempdir().unwrap();
+        let video = vec![7u8; 2100];
+        let segment = segment(dir.path(), "cam-1/2026-03-01/video_11.mkv", &video);
+        let sha256 = vms_common::replication::sha256_file(&segment.path).await.unwrap();
+        let throttle = Arc::new(Throttle::new(None));
+        let upload = Upload {
+            segment: &segment,
+            sha256: &sha256,
+            throttle: &throttle,
+            chunk_size: 1000,
+        };
+        let mut state = ReplicationState::default();
+        state.uploads.insert(segment.relative.clone(), "expired".to_string());
+
+        assert_eq!(target.replicate(&upload, &mut state).await.unwrap(), 2100);
+        assert_ne!(state.uploads[&segment.relative], "expired");
+        assert_eq!(store.lock().unwrap().objects["site-a/cam-1/2026-03-01/video_11.mkv"].0, video);
+    }
+}
@@ -0,0 +1,150 @@
+//! Destino vms-storage (protocolo de `vms_common::replication`)
+
+use anyhow::{anyhow, Result};
+use percent_encoding::utf8_percent_encode;
+use reqwest::StatusCode;
+use tracing::{debug, warn};
+use vms_common::replication::{ReplicaStatus, REPLICATION_FILES_PATH};
+use vms_common::service_auth::ServiceCredential;
+
+use super::sigv4::URI_ENCODE;
+use super::{read_chunk, ReplicationError, Upload};
+
+/// Ressincronizações de offset toleradas por segmento
+const MAX_RESYNCS: usize = 3;
+
+/// Nome com que o replicador se identifica nos tokens de serviço
+const SERVICE_NAME: &str = "vms-replicator";
+
+pub struct StorageTarget {
+    base: String,
+    client: reqwest::Client,
+    credential: Option<ServiceCredential>,
+}
+
+impl StorageTarget {
+    pub fn new(base: &str, service_secret: Option<&str>, client: reqwest::Client) -> Self {
+        if service_secret.is_none() {
+            warn!("⚠️  VMS_SERVICE_SECRET not set: vms-storage will reject replication requests");
+        }
+        Self {
+            base: base.trim_end_matches('/').to_string(),
+            client,
+            credential: service_secret.map(|secret| ServiceCredential::new(SERVICE_NAME, secret)),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        format!("vms-storage {}", self.base)
+    }
+
+    fn file_url(&self, relative: &str) -> String {
+        let path = relative
+            .split('/')
+            .map(|segment| utf8_percent_encode(segment, URI_ENCODE).to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        format!("{}{}/{}", self.base, REPLICATION_FILES_PATH, path)
+    }
+
+    /// Requisição com o token de serviço, se configurado
+    fn request(&self, method: reqwest::Method, relative: &str) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, self.file_url(relative));
+        match &self.credential {
+            Some(credential) => request.header(reqwest::header::AUTHORIZATION, credential.bearer()),
+            None => request,
+        }
+    }
+
+    pub async fn status(&self, relative: &str) -> Result<ReplicaStatus> {
+        let response = self.request(reqwest::Method::GET, relative).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(remote(status, response).await.into());
+        }
+        Ok(response.json().await?)
+    }
+
+    pub async fn replicate(&self, upload: &Upload<'_>) -> Result<u64> {
+        let segment = upload.segment;
+        let mut remote_status = self.status(&segment.relative).await?;
+
+        if remote_status.complete {
+            return if remote_status.sha256.as_deref() == Some(upload.sha256) {
+                Ok(0)
+            } else {
+                Err(ReplicationError::Conflict(segment.relative.clone()).into())
+            };
+        }
+
+        // Parcial maior que a origem: recomeça do zero (offset 0 trunca o `.part`)
+        let mut offset = if remote_status.size > segment.size {
+            0
+        } else {
+            remote_status.size
+        };
+        if offset > 0 {
+            debug!("⏯️  Resuming {} at {} / {}", segment.relative, offset, segment.size);
+        }
+
+        let mut sent = 0u64;
+        let mut resyncs = 0;
+        loop {
+            let len = (segment.size - offset).min(upload.chunk_size as u64) as usize;
+            let data = read_chunk(&segment.path, offset, len).await?;
+
+            let response = self
+                .request(reqwest::Method::PUT, &segment.relative)
+                .query(&[
+                    ("offset", offset.to_string()),
+                    ("total", segment.size.to_string()),
+                    ("sha256", upload.sha256.to_string()),
+                ])
+                .header(reqwest::header::CONTENT_LENGTH, len)
+                .body(reqwest::Body::wrap_stream(upload.throttle.stream(data)))
+                .send()
+                .await?;
+
+            match response.status() {
+                status if status.is_success() => {
+                    remote_status = response.json().await?;
+                    sent += len as u64;
+                    offset = remote_status.size;
+                }
+                StatusCode::CONFLICT if resyncs < MAX_RESYNCS => {
+                    // Outro envio avançou o arquivo: continua de onde o destino está
+                    resyncs += 1;
+                    let body: serde_json::Value = response.json().await?;
+                    let current: ReplicaStatus = serde_json::from_value(body["status"].clone())
+                        .map_err(|_| anyhow!("Target rejected {} without status", segment.relative))?;
+                    if current.complete {
+                        remote_status = current;
+                    } else {
+                        offset = current.size.min(segment.size);
+                        continue;
+                    }
+                }
+                StatusCode::UNPROCESSABLE_ENTITY => {
+                    return Err(ReplicationError::ChecksumMismatch(segment.relative.clone()).into());
+                }
+                status => return Err(remote(status, response).await.into()),
+            }
+
+            if remote_status.complete {
+                break;
+            }
+        }
+
+        if remote_status.sha256.as_deref() != Some(upload.sha256) {
+            return Err(ReplicationError::ChecksumMismatch(segment.relative.clone()).into());
+        }
+        Ok(sent)
+    }
+}
+
+async fn remote(status: StatusCode, response: reqwest::Response) -> ReplicationError {
+    ReplicationError::Remote {
+        status: status.as_u16(),
+        body: response.text().await.unwrap_or_default(),
+    }
+}
@@ -0,0 +1,113 @@
+//! Limite de banda (token bucket compartilhado por todas as transferências)
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::Stream;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Pedaço máximo liberado por vez (suaviza o envio dentro de um chunk)
+const SLICE: usize = 64 * 1024;
+
+/// Token bucket em bytes/s com rajada de até 1 s
+pub struct Throttle {
+    rate: Option<u64>,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Throttle {
+    /// `None` desativa o limite
+    pub fn new(bytes_per_sec: Option<u64>) -> Self {
+        Self {
+            rate: bytes_per_sec.filter(|r| *r > 0),
+            bucket: Mutex::new(Bucket {
+                tokens: bytes_per_sec.unwrap_or(0) as f64,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn rate(&self) -> Option<u64> {
+        self.rate
+    }
+
+    /// Aguarda até que `bytes` possam ser enviados
+    pub async fn acquire(&self, bytes: usize) {
+        let Some(rate) = self.rate else {
+            return;
+        };
+        let rate = rate as f64;
+
+        let wait = {
+            let mut bucket = self.bucket.lock().await;
+            let now = Instant::now();
+            let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * rate).min(rate);
+            bucket.updated = now;
+
+            // Saldo negativo = dívida paga com espera; chamadas concorrentes
+            // entram na fila atrás dela
+            bucket.tokens -= bytes as f64;
+            if bucket.tokens < 0.0 {
+                Duration::from_secs_f64(-bucket.tokens / rate)
+            } else {
+                Duration::ZERO
+            }
+        };
+
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Stream que libera `data` em fatias respeitando o limite
+    pub fn stream(self: &Arc<Self>, data: Bytes) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static {
+        let throttle = self.clone();
+        futures::stream::unfold((data, throttle), |(mut data, throttle)| async move {
+            if data.is_empty() {
+                return None;
+            }
+            let slice = data.split_to(data.len().min(SLICE));
+            throttle.acquire(slice.len()).await;
+            Some((Ok(slice), (data, throttle)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limit() {
+        let throttle = Arc::new(Throttle::new(Some(100_000)));
+        let start = Instant::now();
+
+        // 1 s de rajada + 400 KB a 100 KB/s
+        let sent: usize = throttle
+            .stream(Bytes::from(vec![0u8; 500_000]))
+            .map(|chunk| chunk.unwrap().len())
+            .fold(0, |acc, n| async move { acc + n })
+            .await;
+
+        assert_eq!(sent, 500_000);
+        let elapsed = start.elapsed().as_secs_f64();
+        assert!((3.9..4.5).contains(&elapsed), "elapsed {}", elapsed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_unlimited() {
+        let throttle = Throttle::new(None);
+        let start = Instant::now();
+        throttle.acquire(10_000_000).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+}
@@ -16,6 +16,8 @@ use axum::{
 use std::net::SocketAddr;
 use tower_http::trace::TraceLayer;
 use tracing::{info, error};
+use vms_common::gateway_client::{join_gateway, nats_url, public_url};
+use vms_common::service_auth::service_secret_from_env;
 
 mod recorder;
 mod indexer;
@@ -23,6 +25,13 @@ mod playback;
 mod export;
 mod storage;
 mod retention;
+mod replication;
+
+/// Nome do serviço no registro do vms-gateway
+const SERVICE_NAME: &str = "vms-storage";
+
+/// Porta da API HTTP
+const HTTP_PORT: u16 = 9092;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -39,14 +48,20 @@ async fn main() -> Result<()> {
     gstreamer::init()?;
     info!("✅ GStreamer initialized");
 
+    // Registro no vms-gateway: seção `storage` e NATS da configuração
+    // central; STORAGE_PATH e NATS_URL têm precedência
+    let central = join_gateway(SERVICE_NAME, public_url(HTTP_PORT)).await;
+
     // Connect to NATS
-    let nats_url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://localhost:4222".to_string());
+    let nats_url = nats_url(&central);
     let nats_client = async_nats::connect(&nats_url).await?;
     info!("✅ Connected to NATS at {}", nats_url);
 
     // Storage configuration
     let storage_path = std::env::var("STORAGE_PATH")
-        .unwrap_or_else(|_| "C:\\storage\\cameras".to_string());
+        .ok()
+        .or_else(|| central["storage"]["base_path"].as_str().map(String::from))
+        .unwrap_or_else(|| "C:\\storage\\cameras".to_string());
     info!("📁 Storage path: {}", storage_path);
 
     // Create storage directory
@@ -62,10 +77,14 @@ async fn main() -> Result<()> {
         .route("/api/v1/playback/:camera_id", get(playback::stream_handler))
         .route("/api/v1/playback/:camera_id/timeline", get(playback::timeline_handler))
         .route("/api/v1/export", post(export::create_export_job))
+        .merge(replication::replication_routes(
+            storage_path.clone().into(),
+            service_secret_from_env(),
+        ))
         .layer(TraceLayer::new_for_http());
 
     // Start HTTP server
-    let addr = SocketAddr::from(([0, 0, 0, 0], 9092));
+    let addr = SocketAddr::from(([0, 0, 0, 0], HTTP_PORT));
     info!("🌐 HTTP API listening on http://{}", addr);
     info!("📊 Metrics: http://{}/metrics", addr);
     info!("✅ Service initialized successfully");
@@ -8,7 +8,8 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio_stream::StreamExt;
 use tracing::{debug, error, info, warn};
-use vms_common::stream::VideoFrame;
+use vms_common::media_profile::MediaProfileUsage;
+use vms_common::stream::{frame_subject_filter, VideoFrame};
 use vms_common::types::CameraId;
 
 use crate::writer::VideoWriter;
@@ -42,12 +43,13 @@ impl NatsConsumer {
     pub async fn start_consuming(&self) -> Result<()> {
         info!("🎬 Starting frame consumer");
 
-        // Subscribe to all camera frames
+        // Subscribe to the recording stream of every camera (high-res main stream)
+        let subject = frame_subject_filter(MediaProfileUsage::Recording);
         let subscriber = self
             .client
-            .subscribe("vms.frames.>")
+            .subscribe(subject.clone())
             .await
-            .context("Failed to subscribe to vms.frames")?;
+            .with_context(|| format!("Failed to subscribe to {}", subject))?;
 
         let writers = self.writers.clone();
         let base_path = self.base_storage_path.clone();
@@ -68,7 +70,7 @@ impl NatsConsumer {
         let mut frame_count = 0u64;
 
         while let Some(message) = subscriber.next().await {
-            // Extract camera_id from subject (vms.frames.{camera_id})
+            // Extract camera_id from subject (vms.frames.{camera_id}.{usage})
             let subject_parts: Vec<&str> = message.subject.split('.').collect();
             if subject_parts.len() < 3 {
                 warn!("Invalid subject format: {}", message.subject);
@@ -98,9 +100,8 @@ impl NatsConsumer {
 
                         // Write frame
                         let timestamp = Utc::now();
-                        let is_keyframe = frame_count % 30 == 1; // Simplified keyframe detection
 
-                        if let Err(e) = writer.write_frame(&frame.data, timestamp, is_keyframe) {
+                        if let Err(e) = writer.write_frame(&frame.data, timestamp, frame.is_keyframe) {
                             error!("Failed to write frame for camera {}: {}", camera_id, e);
                         }
 
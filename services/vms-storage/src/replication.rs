@@ -0,0 +1,236 @@
+//! Recepção de gravações replicadas (destino do vms-replicator)
+//!
+//! `GET  /api/v1/replication/files/*path` → `ReplicaStatus`
+//! `PUT  /api/v1/replication/files/*path?offset&total&sha256` → anexa um pedaço
+//!
+//! Pedaços são acumulados em `<arquivo>.part`; quando o total chega, o SHA-256
+//! é conferido e o arquivo é publicado junto de `<arquivo>.sha256`.
+//!
+//! As duas rotas exigem `Authorization: Bearer svc.<serviço>...` de
+//! `vms_common::service_auth` (segredo `VMS_SERVICE_SECRET`): sem isso
+//! qualquer cliente leria ou gravaria arquivos na raiz de armazenamento.
+
+use std::path::{Component, Path as FsPath, PathBuf};
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use futures::StreamExt;
+use serde_json::{json, Value};
+use tokio::io::AsyncWriteExt;
+use tracing::{info, warn};
+use vms_common::replication::{sha256_file, ChunkParams, ReplicaStatus, REPLICATION_FILES_PATH};
+use vms_common::service_auth::{verify_service_token, ServiceAuthError};
+
+const PART_SUFFIX: &str = ".part";
+const CHECKSUM_SUFFIX: &str = ".sha256";
+
+type ApiError = (StatusCode, Json<Value>);
+
+fn error(status: StatusCode, message: impl Into<String>) -> ApiError {
+    (status, Json(json!({ "error": message.into() })))
+}
+
+#[derive(Clone)]
+pub struct ReplicationState {
+    root: PathBuf,
+    /// Segredo de `VMS_SERVICE_SECRET`; sem ele as rotas respondem 503
+    service_secret: Option<String>,
+}
+
+/// Rotas de replicação sobre a raiz de armazenamento
+pub fn replication_routes(storage_root: PathBuf, service_secret: Option<String>) -> Router {
+    Router::new()
+        .route(
+            &format!("{}/*path", REPLICATION_FILES_PATH),
+            get(status_handler).put(upload_handler),
+        )
+        .with_state(ReplicationState {
+            root: storage_root,
+            service_secret,
+        })
+}
+
+/// Confere o token de serviço do chamador
+fn authorize(state: &ReplicationState, headers: &HeaderMap) -> Result<String, ApiError> {
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .ok_or_else(|| error(StatusCode::UNAUTHORIZED, "Missing service token"))?;
+
+    let secret = state
+        .service_secret
+        .as_deref()
+        .ok_or_else(|| error(StatusCode::SERVICE_UNAVAILABLE, ServiceAuthError::NotConfigured.to_string()))?;
+    verify_service_token(secret.as_bytes(), token, chrono::Utc::now().timestamp())
+        .map_err(|e| error(StatusCode::UNAUTHORIZED, e.to_string()))
+}
+
+/// Quanto do arquivo já foi recebido
+pub async fn status_handler(
+    State(state): State<ReplicationState>,
+    headers: HeaderMap,
+    Path(path): Path<String>,
+) -> Result<Json<ReplicaStatus>, ApiError> {
+    authorize(&state, &headers)?;
+    let target = resolve(&state.root, &path)?;
+    Ok(Json(replica_status(&path, &target).await))
+}
+
+/// Anexa um pedaço; publica o arquivo quando completo e íntegro
+pub async fn upload_handler(
+    State(state): State<ReplicationState>,
+    headers: HeaderMap,
+    Path(path): Path<String>,
+    Query(params): Query<ChunkParams>,
+    body: Body,
+) -> Result<(StatusCode, Json<ReplicaStatus>), ApiError> {
+    let caller = authorize(&state, &headers)?;
+    let target = resolve(&state.root, &path)?;
+    let part = with_suffix(&target, PART_SUFFIX);
+
+    let status = replica_status(&path, &target).await;
+    if status.complete {
+        // Reenvio de um arquivo já publicado: idempotente se o conteúdo é o mesmo
+        return if status.sha256.as_deref() == Some(params.sha256.as_str()) {
+            Ok((StatusCode::OK, Json(status)))
+        } else {
+            Err(error(StatusCode::CONFLICT, "File already replicated with different content"))
+        };
+    }
+    // Arquivo local (gravação deste nó ou publicação interrompida): nunca sobrescrito
+    if tokio::fs::try_exists(&target).await.unwrap_or(true) {
+        return Err(error(StatusCode::CONFLICT, "File exists without checksum"));
+    }
+    // Offset 0 recomeça o arquivo (parcial inconsistente com a origem)
+    if params.offset == 0 && status.size > 0 {
+        let _ = tokio::fs::remove_file(&part).await;
+    } else if params.offset != status.size {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(json!({ "error": "Offset mismatch", "status": status })),
+        ));
+    }
+
+    if let Some(parent) = part.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&part)
+        .await
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let mut size = params.offset;
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| error(StatusCode::BAD_REQUEST, e.to_string()))?;
+        size += chunk.len() as u64;
+        if size > params.total {
+            drop(file);
+            let _ = tokio::fs::remove_file(&part).await;
+            return Err(error(StatusCode::BAD_REQUEST, "Chunk exceeds declared total"));
+        }
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+    file.flush()
+        .await
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    drop(file);
+
+    if size < params.total {
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(ReplicaStatus {
+                path,
+                size,
+                complete: false,
+                sha256: None,
+            }),
+        ));
+    }
+
+    let checksum = sha256_file(&part)
+        .await
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if !checksum.eq_ignore_ascii_case(&params.sha256) {
+        warn!("⚠️  Replica checksum mismatch for {}: discarding", path);
+        let _ = tokio::fs::remove_file(&part).await;
+        return Err(error(StatusCode::UNPROCESSABLE_ENTITY, "Checksum mismatch"));
+    }
+
+    let publish = async {
+        tokio::fs::write(with_suffix(&target, CHECKSUM_SUFFIX), &checksum).await?;
+        tokio::fs::rename(&part, &target).await
+    };
+    publish
+        .await
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    info!("📥 Replica stored: {} ({} bytes, from {})", path, size, caller);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ReplicaStatus {
+            path,
+            size,
+            complete: true,
+            sha256: Some(checksum),
+        }),
+    ))
+}
+
+async fn replica_status(path: &str, target: &FsPath) -> ReplicaStatus {
+    if let Ok(metadata) = tokio::fs::metadata(target).await {
+        let sha256 = tokio::fs::read_to_string(with_suffix(target, CHECKSUM_SUFFIX))
+            .await
+            .ok()
+            .map(|s| s.trim().to_string());
+        return ReplicaStatus {
+            path: path.to_string(),
+            size: metadata.len(),
+            complete: sha256.is_some(),
+            sha256,
+        };
+    }
+
+    let size = tokio::fs::metadata(with_suffix(target, PART_SUFFIX))
+        .await
+        .map(|m| m.len())
+        .unwrap_or(0);
+    ReplicaStatus {
+        path: path.to_string(),
+        size,
+        complete: false,
+        sha256: None,
+    }
+}
+
+/// Path relativo seguro dentro da raiz (sem `..`, absolutos ou arquivos auxiliares)
+fn resolve(root: &FsPath, path: &str) -> Result<PathBuf, ApiError> {
+    let relative = FsPath::new(path);
+    let safe = !path.is_empty()
+        && relative.components().all(|c| matches!(c, Component::Normal(_)))
+        && !path.ends_with(PART_SUFFIX)
+        && !path.ends_with(CHECKSUM_SUFFIX);
+    if !safe {
+        return Err(error(StatusCode::BAD_REQUEST, "Invalid replication path"));
+    }
+    Ok(root.join(relative))
+}
+
+fn with_suffix(path: &FsPath, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
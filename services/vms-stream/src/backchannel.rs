@@ -0,0 +1,259 @@
+//! Áudio bidirecional (talk-down) pelo backchannel ONVIF
+//!
+//! A câmera anuncia no SDP do DESCRIBE, quando o cliente manda
+//! `Require: www.onvif.org/ver20/backchannel`, uma stream de áudio
+//! `sendonly` que recebe RTP do cliente e toca no alto-falante. O operador
+//! fala pelo browser (WebRTC, Opus) ou uma regra manda um clipe gravado; os
+//! dois viram G.711 (PCMU/PCMA, 8 kHz mono) e seguem por essa stream. Só um
+//! áudio por câmera de cada vez: o alto-falante não mistura falantes.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Requisito RTSP que habilita o backchannel (ONVIF Streaming Spec §5.3)
+pub const BACKCHANNEL_REQUIREMENT: &str = "www.onvif.org/ver20/backchannel";
+
+/// Taxa de amostragem do G.711
+pub const G711_CLOCK_RATE: i32 = 8000;
+
+/// Variante do G.711 aceita pelo backchannel da câmera
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum G711 {
+    /// μ-law (PCMU, payload type 0)
+    Mulaw,
+    /// A-law (PCMA, payload type 8)
+    Alaw,
+}
+
+impl G711 {
+    pub fn from_encoding(encoding: &str) -> Option<Self> {
+        match encoding.to_ascii_uppercase().as_str() {
+            "PCMU" => Some(Self::Mulaw),
+            "PCMA" => Some(Self::Alaw),
+            _ => None,
+        }
+    }
+
+    pub fn encoding_name(self) -> &'static str {
+        match self {
+            Self::Mulaw => "PCMU",
+            Self::Alaw => "PCMA",
+        }
+    }
+
+    /// Payload type estático (RFC 3551)
+    pub fn payload_type(self) -> u32 {
+        match self {
+            Self::Mulaw => 0,
+            Self::Alaw => 8,
+        }
+    }
+
+    pub fn encoder(self) -> &'static str {
+        match self {
+            Self::Mulaw => "mulawenc",
+            Self::Alaw => "alawenc",
+        }
+    }
+
+    pub fn payloader(self) -> &'static str {
+        match self {
+            Self::Mulaw => "rtppcmupay",
+            Self::Alaw => "rtppcmapay",
+        }
+    }
+}
+
+/// Papel de uma stream do SDP da câmera na sessão de áudio
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamRole {
+    /// Backchannel (`a=sendonly`) que aceitamos
+    Backchannel(G711),
+    /// Microfone da câmera, devolvido ao operador
+    CameraAudio,
+    /// Vídeo, metadados ou backchannel num codec que não geramos
+    Skip,
+}
+
+/// Classifica uma stream pelos campos das caps do `rtspsrc` (o `a-sendonly`
+/// marca o backchannel); `listen` pede também o microfone da câmera
+pub fn classify_stream(media: &str, encoding: &str, clock_rate: Option<i32>, sendonly: bool, listen: bool) -> StreamRole {
+    if media != "audio" {
+        return StreamRole::Skip;
+    }
+    if !sendonly {
+        return if listen { StreamRole::CameraAudio } else { StreamRole::Skip };
+    }
+    match G711::from_encoding(encoding) {
+        Some(codec) if clock_rate.unwrap_or(G711_CLOCK_RATE) == G711_CLOCK_RATE => StreamRole::Backchannel(codec),
+        _ => StreamRole::Skip,
+    }
+}
+
+/// Payload type do Opus na seção de áudio de uma oferta SDP
+pub fn opus_payload_type(sdp: &str) -> Option<u8> {
+    let mut in_audio = false;
+    for line in sdp.lines().map(str::trim) {
+        if let Some(media) = line.strip_prefix("m=") {
+            in_audio = media.starts_with("audio ");
+        } else if let Some(rtpmap) = line.strip_prefix("a=rtpmap:").filter(|_| in_audio) {
+            let (pt, encoding) = rtpmap.split_once(' ')?;
+            if encoding.to_ascii_lowercase().starts_with("opus/48000") {
+                return pt.parse().ok();
+            }
+        }
+    }
+    None
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum BackchannelError {
+    #[error("Timed out waiting for the camera audio backchannel")]
+    Timeout,
+
+    /// A câmera não anunciou stream `sendonly` em G.711
+    #[error("Camera offers no G.711 audio backchannel ({BACKCHANNEL_REQUIREMENT})")]
+    Unsupported,
+
+    #[error("Camera backchannel failed: {0}")]
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TalkKind {
+    /// Operador falando pelo browser (WebRTC)
+    Live,
+    /// Clipe de áudio enviado (regras, avisos gravados)
+    Clip,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TalkError {
+    #[error("Camera {camera_id} speaker is already in use ({kind:?})")]
+    Busy { camera_id: String, kind: TalkKind },
+}
+
+/// Áudio tocando numa câmera
+#[derive(Debug, Clone, Serialize)]
+pub struct TalkInfo {
+    pub id: Uuid,
+    pub camera_id: String,
+    pub kind: TalkKind,
+    pub started_at: DateTime<Utc>,
+}
+
+/// Alto-falantes em uso, um áudio por câmera
+#[derive(Debug, Default)]
+pub struct TalkRegistry {
+    talks: HashMap<Uuid, TalkInfo>,
+}
+
+impl TalkRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserva o alto-falante da câmera e devolve o id da sessão
+    pub fn claim(&mut self, camera_id: &str, kind: TalkKind) -> Result<Uuid, TalkError> {
+        if let Some(talk) = self.talks.values().find(|talk| talk.camera_id == camera_id) {
+            return Err(TalkError::Busy {
+                camera_id: camera_id.to_string(),
+                kind: talk.kind,
+            });
+        }
+
+        let id = Uuid::new_v4();
+        self.talks.insert(
+            id,
+            TalkInfo {
+                id,
+                camera_id: camera_id.to_string(),
+                kind,
+                started_at: Utc::now(),
+            },
+        );
+        Ok(id)
+    }
+
+    /// Libera o alto-falante; `None` se a sessão já tinha saído
+    pub fn release(&mut self, id: Uuid) -> Option<TalkInfo> {
+        self.talks.remove(&id)
+    }
+
+    pub fn list(&self) -> Vec<TalkInfo> {
+        let mut talks: Vec<TalkInfo> = self.talks.values().cloned().collect();
+        talks.sort_by_key(|talk| talk.started_at);
+        talks
+    }
+
+    pub fn total(&self) -> usize {
+        self.talks.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_stream() {
+        assert_eq!(
+            classify_stream("audio", "pcmu", Some(8000), true, false),
+            StreamRole::Backchannel(G711::Mulaw)
+        );
+        assert_eq!(
+            classify_stream("audio", "PCMA", None, true, true),
+            StreamRole::Backchannel(G711::Alaw)
+        );
+        // Backchannel AAC ou G.711 a 16 kHz: não geramos
+        assert_eq!(classify_stream("audio", "MPEG4-GENERIC", Some(16000), true, true), StreamRole::Skip);
+        assert_eq!(classify_stream("audio", "PCMU", Some(16000), true, true), StreamRole::Skip);
+
+        assert_eq!(classify_stream("audio", "PCMU", Some(8000), false, true), StreamRole::CameraAudio);
+        assert_eq!(classify_stream("audio", "PCMU", Some(8000), false, false), StreamRole::Skip);
+        assert_eq!(classify_stream("video", "H264", Some(90000), false, true), StreamRole::Skip);
+
+        assert_eq!(G711::Alaw.payload_type(), 8);
+        assert_eq!(G711::Mulaw.payloader(), "rtppcmupay");
+    }
+
+    #[test]
+    fn test_opus_payload_type() {
+        let offer = "v=0\r\n\
+                     m=video 9 UDP/TLS/RTP/SAVPF 96\r\n\
+                     a=rtpmap:96 VP8/90000\r\n\
+                     m=audio 9 UDP/TLS/RTP/SAVPF 111 0\r\n\
+                     a=mid:1\r\n\
+                     a=rtpmap:111 opus/48000/2\r\n\
+                     a=rtpmap:0 PCMU/8000\r\n";
+        assert_eq!(opus_payload_type(offer), Some(111));
+
+        let offer = "m=audio 9 UDP/TLS/RTP/SAVPF 0\r\na=rtpmap:0 PCMU/8000\r\n";
+        assert_eq!(opus_payload_type(offer), None);
+    }
+
+    #[test]
+    fn test_one_talk_per_camera() {
+        let mut registry = TalkRegistry::new();
+        let live = registry.claim("cam-1", TalkKind::Live).unwrap();
+        assert_eq!(
+            registry.claim("cam-1", TalkKind::Clip),
+            Err(TalkError::Busy {
+                camera_id: "cam-1".to_string(),
+                kind: TalkKind::Live,
+            })
+        );
+        let clip = registry.claim("cam-2", TalkKind::Clip).unwrap();
+        assert_eq!(registry.total(), 2);
+        assert!(registry.list().iter().any(|talk| talk.id == clip && talk.camera_id == "cam-2"));
+
+        assert_eq!(registry.release(live).unwrap().kind, TalkKind::Live);
+        assert!(registry.release(live).is_none());
+        assert!(registry.claim("cam-1", TalkKind::Clip).is_ok());
+    }
+}
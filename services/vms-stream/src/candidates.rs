@@ -0,0 +1,123 @@
+//! Candidatos ICE do servidor por viewer (trickle ICE)
+//!
+//! O webrtcbin emite `on-ice-candidate` em threads do GStreamer; os
+//! candidatos ficam acumulados aqui e o browser os busca por long-poll em
+//! `GET /api/v1/webrtc/peers/:peer_id/candidates?since=N`.
+
+use std::time::Duration;
+
+use tokio::sync::watch;
+use vms_common::{IceCandidate, IceCandidatesResponse};
+
+#[derive(Debug, Default)]
+struct Gathered {
+    candidates: Vec<IceCandidate>,
+    complete: bool,
+}
+
+/// Candidatos coletados para um viewer
+#[derive(Debug)]
+pub struct CandidateQueue {
+    tx: watch::Sender<Gathered>,
+}
+
+impl Default for CandidateQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CandidateQueue {
+    pub fn new() -> Self {
+        Self {
+            tx: watch::Sender::new(Gathered::default()),
+        }
+    }
+
+    /// Novo candidato local (chamado do callback do GStreamer)
+    pub fn push(&self, sdp_m_line_index: u32, candidate: String) {
+        self.tx.send_modify(|gathered| {
+            gathered.candidates.push(IceCandidate {
+                candidate,
+                sdp_mid: None,
+                sdp_m_line_index: u16::try_from(sdp_m_line_index).ok(),
+            })
+        });
+    }
+
+    /// Coleta encerrada: nenhum candidato novo virá
+    pub fn complete(&self) {
+        self.tx.send_modify(|gathered| gathered.complete = true);
+    }
+
+    /// Espera o fim da coleta (respostas SDP com todos os candidatos, como
+    /// no WHEP/WHIP); `false` se o prazo acabou antes
+    pub async fn wait_complete(&self, timeout: Duration) -> bool {
+        let mut rx = self.tx.subscribe();
+        let complete = tokio::time::timeout(timeout, rx.wait_for(|gathered| gathered.complete))
+            .await
+            .is_ok();
+        complete
+    }
+
+    /// Candidatos a partir de `since`; espera até `timeout` se ainda não há
+    /// nenhum novo e a coleta não terminou
+    pub async fn wait(&self, since: usize, timeout: Duration) -> IceCandidatesResponse {
+        let mut rx = self.tx.subscribe();
+        let _ = tokio::time::timeout(
+            timeout,
+            rx.wait_for(|gathered| gathered.candidates.len() > since || gathered.complete),
+        )
+        .await;
+
+        let gathered = rx.borrow();
+        let since = since.min(gathered.candidates.len());
+        IceCandidatesResponse {
+            candidates: gathered.candidates[since..].to_vec(),
+            next: gathered.candidates.len(),
+            complete: gathered.complete,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_long_poll() {
+        let queue = Arc::new(CandidateQueue::new());
+        queue.push(0, "candidate:1 1 UDP 2122252543 192.168.1.10 50000 typ host".to_string());
+
+        let first = queue.wait(0, Duration::from_secs(10)).await;
+        assert_eq!(first.candidates.len(), 1);
+        assert_eq!(first.candidates[0].sdp_m_line_index, Some(0));
+        assert_eq!(first.next, 1);
+        assert!(!first.complete);
+
+        // Sem novidades: a espera é liberada pelo próximo candidato
+        let waiter = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.wait(1, Duration::from_secs(10)).await }
+        });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        queue.push(0, "candidate:2 1 UDP 1686052607 203.0.113.7 50000 typ srflx".to_string());
+        let second = waiter.await.unwrap();
+        assert_eq!(second.candidates.len(), 1);
+        assert!(second.candidates[0].candidate.contains("srflx"));
+        assert_eq!(second.next, 2);
+
+        // Timeout devolve vazio; fim da coleta devolve na hora
+        let empty = queue.wait(2, Duration::from_secs(5)).await;
+        assert!(empty.candidates.is_empty());
+        assert_eq!(empty.next, 2);
+
+        assert!(!queue.wait_complete(Duration::from_secs(1)).await);
+        queue.complete();
+        assert!(queue.wait_complete(Duration::from_secs(1)).await);
+        let done = queue.wait(2, Duration::from_secs(3600)).await;
+        assert!(done.complete);
+        assert!(done.candidates.is_empty());
+    }
+}
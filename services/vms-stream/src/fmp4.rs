@@ -0,0 +1,346 @@
+//! Fragmented MP4 (CMAF) para H.264
+//!
+//! Monta o segmento de inicialização (`ftyp` + `moov` com o `avcC` da
+//! câmera) e fragmentos `moof` + `mdat` a partir dos access units Annex-B
+//! do NATS, sem passar pelo GStreamer: cada parte LL-HLS é um fragmento.
+
+use anyhow::{bail, Result};
+
+/// Relógio das amostras (90 kHz, como o RTP)
+pub const TIMESCALE: u32 = 90_000;
+
+const TRACK_ID: u32 = 1;
+
+const NAL_SPS: u8 = 7;
+const NAL_PPS: u8 = 8;
+const NAL_AUD: u8 = 9;
+
+/// Flags de amostra do `trun`: keyframe (não depende de outras) ou
+/// dependente e não-sync
+const SAMPLE_SYNC: u32 = 0x0200_0000;
+const SAMPLE_NON_SYNC: u32 = 0x0101_0000;
+
+/// NAL units de um access unit Annex-B (sem os start codes)
+pub fn nal_units(data: &[u8]) -> Vec<&[u8]> {
+    let mut units = Vec::new();
+    let mut start = None;
+    let mut i = 0;
+    while i + 3 <= data.len() {
+        if data[i] == 0 && data[i + 1] == 0 && data[i + 2] == 1 {
+            if let Some(start) = start {
+                units.push(trim_zeros(&data[start..i]));
+            }
+            i += 3;
+            start = Some(i);
+        } else {
+            i += 1;
+        }
+    }
+    if let Some(start) = start {
+        units.push(&data[start..]);
+    }
+    units.retain(|unit| !unit.is_empty());
+    units
+}
+
+/// Zeros finais pertencem ao start code de 4 bytes seguinte
+fn trim_zeros(unit: &[u8]) -> &[u8] {
+    let end = unit.iter().rposition(|byte| *byte != 0).map_or(0, |i| i + 1);
+    &unit[..end]
+}
+
+fn nal_type(unit: &[u8]) -> u8 {
+    unit[0] & 0x1f
+}
+
+/// Amostra em formato AVCC (NALs com prefixo de tamanho de 4 bytes); os
+/// delimitadores de access unit são descartados
+pub fn avcc_sample(data: &[u8]) -> Vec<u8> {
+    let mut sample = Vec::with_capacity(data.len() + 16);
+    for unit in nal_units(data).into_iter().filter(|unit| nal_type(unit) != NAL_AUD) {
+        sample.extend_from_slice(&(unit.len() as u32).to_be_bytes());
+        sample.extend_from_slice(unit);
+    }
+    sample
+}
+
+/// Parâmetros do decoder (`avcC`), extraídos de um keyframe
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AvcConfig {
+    pub sps: Vec<u8>,
+    pub pps: Vec<u8>,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl AvcConfig {
+    /// SPS e PPS do access unit; `None` se ele não os traz
+    pub fn from_access_unit(data: &[u8], width: u32, height: u32) -> Option<Self> {
+        let units = nal_units(data);
+        let sps = units.iter().find(|unit| nal_type(unit) == NAL_SPS && unit.len() >= 4)?;
+        let pps = units.iter().find(|unit| nal_type(unit) == NAL_PPS)?;
+        Some(Self {
+            sps: sps.to_vec(),
+            pps: pps.to_vec(),
+            width: width.min(u16::MAX.into()) as u16,
+            height: height.min(u16::MAX.into()) as u16,
+        })
+    }
+
+    /// `ftyp` + `moov` de uma trilha de vídeo sem amostras (vão nos fragmentos)
+    pub fn init_segment(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(1024);
+        write_box(&mut out, b"ftyp", |out| {
+            out.extend_from_slice(b"iso6");
+            out.extend_from_slice(&0u32.to_be_bytes());
+            for brand in [b"iso6", b"cmfc", b"mp41"] {
+                out.extend_from_slice(brand);
+            }
+        });
+        write_box(&mut out, b"moov", |out| {
+            write_full_box(out, b"mvhd", 0, 0, |out| {
+                put_u32s(out, &[0, 0, 1000, 0]);
+                put_u32s(out, &[0x0001_0000]);
+                out.extend_from_slice(&0x0100u16.to_be_bytes());
+                out.extend_from_slice(&[0; 10]);
+                put_matrix(out);
+                out.extend_from_slice(&[0; 24]);
+                put_u32s(out, &[TRACK_ID + 1]);
+            });
+            write_box(out, b"trak", |out| {
+                // Habilitada e parte da apresentação
+                write_full_box(out, b"tkhd", 0, 0x3, |out| {
+                    put_u32s(out, &[0, 0, TRACK_ID, 0, 0]);
+                    out.extend_from_slice(&[0; 16]);
+                    put_matrix(out);
+                    put_u32s(out, &[u32::from(self.width) << 16, u32::from(self.height) << 16]);
+                });
+                write_box(out, b"mdia", |out| {
+                    write_full_box(out, b"mdhd", 0, 0, |out| {
+                        put_u32s(out, &[0, 0, TIMESCALE, 0]);
+                        // Idioma "und"
+                        out.extend_from_slice(&0x55c4u16.to_be_bytes());
+                        out.extend_from_slice(&[0; 2]);
+                    });
+                    write_full_box(out, b"hdlr", 0, 0, |out| {
+                        put_u32s(out, &[0]);
+                        out.extend_from_slice(b"vide");
+                        out.extend_from_slice(&[0; 12]);
+                        out.extend_from_slice(b"VideoHandler\0");
+                    });
+                    write_box(out, b"minf", |out| {
+                        write_full_box(out, b"vmhd", 0, 1, |out| out.extend_from_slice(&[0; 8]));
+                        write_box(out, b"dinf", |out| {
+                            write_full_box(out, b"dref", 0, 0, |out| {
+                                put_u32s(out, &[1]);
+                                // Mídia no próprio arquivo
+                                write_full_box(out, b"url ", 0, 1, |_| {});
+                            });
+                        });
+                        write_box(out, b"stbl", |out| {
+                            write_full_box(out, b"stsd", 0, 0, |out| {
+                                put_u32s(out, &[1]);
+                                self.write_avc1(out);
+                            });
+                            write_full_box(out, b"stts", 0, 0, |out| put_u32s(out, &[0]));
+                            write_full_box(out, b"stsc", 0, 0, |out| put_u32s(out, &[0]));
+                            write_full_box(out, b"stsz", 0, 0, |out| put_u32s(out, &[0, 0]));
+                            write_full_box(out, b"stco", 0, 0, |out| put_u32s(out, &[0]));
+                        });
+                    });
+                });
+            });
+            write_box(out, b"mvex", |out| {
+                write_full_box(out, b"trex", 0, 0, |out| put_u32s(out, &[TRACK_ID, 1, 0, 0, 0]));
+            });
+        });
+        out
+    }
+
+    fn write_avc1(&self, out: &mut Vec<u8>) {
+        write_box(out, b"avc1", |out| {
+            out.extend_from_slice(&[0; 6]);
+            // data_reference_index
+            out.extend_from_slice(&1u16.to_be_bytes());
+            out.extend_from_slice(&[0; 16]);
+            out.extend_from_slice(&self.width.to_be_bytes());
+            out.extend_from_slice(&self.height.to_be_bytes());
+            // 72 dpi
+            put_u32s(out, &[0x0048_0000, 0x0048_0000, 0]);
+            out.extend_from_slice(&1u16.to_be_bytes());
+            out.extend_from_slice(&[0; 32]);
+            out.extend_from_slice(&0x0018u16.to_be_bytes());
+            out.extend_from_slice(&(-1i16).to_be_bytes());
+            write_box(out, b"avcC", |out| self.write_avcc(out));
+        });
+    }
+
+    fn write_avcc(&self, out: &mut Vec<u8>) {
+        let profile = self.sps[1];
+        out.extend_from_slice(&[1, profile, self.sps[2], self.sps[3]]);
+        // Prefixos de 4 bytes, um SPS
+        out.extend_from_slice(&[0xff, 0xe1]);
+        out.extend_from_slice(&(self.sps.len() as u16).to_be_bytes());
+        out.extend_from_slice(&self.sps);
+        out.push(1);
+        out.extend_from_slice(&(self.pps.len() as u16).to_be_bytes());
+        out.extend_from_slice(&self.pps);
+        // Perfis High: 4:2:0, 8 bits, sem extensões de SPS
+        if matches!(profile, 100 | 110 | 122 | 144) {
+            out.extend_from_slice(&[0xfd, 0xf8, 0xf8, 0]);
+        }
+    }
+}
+
+/// Amostra de um fragmento
+#[derive(Debug, Clone)]
+pub struct Sample {
+    /// Access unit em AVCC (ver `avcc_sample`)
+    pub data: Vec<u8>,
+    /// Duração em unidades de `TIMESCALE`
+    pub duration: u32,
+    pub keyframe: bool,
+}
+
+/// `moof` + `mdat` com as amostras a partir de `decode_time`
+pub fn fragment(sequence: u32, decode_time: u64, samples: &[Sample]) -> Result<Vec<u8>> {
+    if samples.is_empty() {
+        bail!("Fragment without samples");
+    }
+    let payload: usize = samples.iter().map(|sample| sample.data.len()).sum();
+    let mut out = Vec::with_capacity(payload + 128 + samples.len() * 12);
+
+    let mut data_offset_at = 0;
+    write_box(&mut out, b"moof", |out| {
+        write_full_box(out, b"mfhd", 0, 0, |out| put_u32s(out, &[sequence]));
+        write_box(out, b"traf", |out| {
+            // default-base-is-moof: offsets relativos ao início do moof
+            write_full_box(out, b"tfhd", 0, 0x02_0000, |out| put_u32s(out, &[TRACK_ID]));
+            write_full_box(out, b"tfdt", 1, 0, |out| out.extend_from_slice(&decode_time.to_be_bytes()));
+            // data-offset, duração, tamanho e flags por amostra
+            write_full_box(out, b"trun", 0, 0x0701, |out| {
+                put_u32s(out, &[samples.len() as u32]);
+                data_offset_at = out.len();
+                put_u32s(out, &[0]);
+                for sample in samples {
+                    let flags = if sample.keyframe { SAMPLE_SYNC } else { SAMPLE_NON_SYNC };
+                    put_u32s(out, &[sample.duration, sample.data.len() as u32, flags]);
+                }
+            });
+        });
+    });
+
+    // Dados logo após o cabeçalho do mdat
+    let data_offset = (out.len() + 8) as u32;
+    out[data_offset_at..data_offset_at + 4].copy_from_slice(&data_offset.to_be_bytes());
+
+    write_box(&mut out, b"mdat", |out| {
+        for sample in samples {
+            out.extend_from_slice(&sample.data);
+        }
+    });
+    Ok(out)
+}
+
+fn write_box(out: &mut Vec<u8>, kind: &[u8; 4], body: impl FnOnce(&mut Vec<u8>)) {
+    let start = out.len();
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(kind);
+    body(out);
+    let size = (out.len() - start) as u32;
+    out[start..start + 4].copy_from_slice(&size.to_be_bytes());
+}
+
+fn write_full_box(out: &mut Vec<u8>, kind: &[u8; 4], version: u8, flags: u32, body: impl FnOnce(&mut Vec<u8>)) {
+    write_box(out, kind, |out| {
+        out.push(version);
+        out.extend_from_slice(&flags.to_be_bytes()[1..]);
+        body(out);
+    });
+}
+
+fn put_u32s(out: &mut Vec<u8>, values: &[u32]) {
+    for value in values {
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+/// Matriz identidade
+fn put_matrix(out: &mut Vec<u8>) {
+    put_u32s(out, &[0x0001_0000, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// SPS (High, 720p), PPS e IDR em Annex-B, com AUD e start codes mistos
+    const KEYFRAME: &[u8] = &[
+        0, 0, 0, 1, 0x09, 0xf0, // AUD
+        0, 0, 0, 1, 0x67, 0x64, 0x00, 0x1f, 0xac, 0xd9, // SPS
+        0, 0, 1, 0x68, 0xeb, 0xe3, 0xcb, // PPS
+        0, 0, 1, 0x65, 0x88, 0x84, 0x00, // IDR
+    ];
+
+    /// Caixas de primeiro nível: (tipo, tamanho)
+    fn boxes(data: &[u8]) -> Vec<(String, usize)> {
+        let mut found = Vec::new();
+        let mut at = 0;
+        while at + 8 <= data.len() {
+            let size = u32::from_be_bytes(data[at..at + 4].try_into().unwrap()) as usize;
+            found.push((String::from_utf8_lossy(&data[at + 4..at + 8]).to_string(), size));
+            at += size;
+        }
+        assert_eq!(at, data.len(), "box sizes must add up");
+        found
+    }
+
+    #[test]
+    fn test_nal_units_and_avcc() {
+        let units = nal_units(KEYFRAME);
+        assert_eq!(units.iter().map(|unit| nal_type(unit)).collect::<Vec<_>>(), vec![9, 7, 8, 5]);
+        assert_eq!(units[1], &[0x67, 0x64, 0x00, 0x1f, 0xac, 0xd9]);
+
+        let sample = avcc_sample(KEYFRAME);
+        // AUD descartado: SPS (6) + PPS (4) + IDR (4), cada um com prefixo
+        assert_eq!(sample.len(), 3 * 4 + 6 + 4 + 4);
+        assert_eq!(&sample[..5], &[0, 0, 0, 6, 0x67]);
+        assert!(nal_units(&[0x65, 0x88]).is_empty());
+    }
+
+    #[test]
+    fn test_init_segment() {
+        assert!(AvcConfig::from_access_unit(&KEYFRAME[23..], 1280, 720).is_none());
+
+        let config = AvcConfig::from_access_unit(KEYFRAME, 1280, 720).unwrap();
+        let init = config.init_segment();
+        assert_eq!(boxes(&init).iter().map(|(kind, _)| kind.as_str()).collect::<Vec<_>>(), vec!["ftyp", "moov"]);
+
+        let avcc = init.windows(4).position(|window| window == b"avcC").unwrap() + 4;
+        assert_eq!(&init[avcc..avcc + 6], &[1, 0x64, 0x00, 0x1f, 0xff, 0xe1]);
+        // Extensão dos perfis High no fim do avcC
+        let size = u32::from_be_bytes(init[avcc - 8..avcc - 4].try_into().unwrap()) as usize;
+        assert_eq!(&init[avcc - 8 + size - 4..avcc - 8 + size], &[0xfd, 0xf8, 0xf8, 0]);
+    }
+
+    #[test]
+    fn test_fragment_offsets() {
+        assert!(fragment(1, 0, &[]).is_err());
+
+        let samples = vec![
+            Sample { data: avcc_sample(KEYFRAME), duration: 3600, keyframe: true },
+            Sample { data: vec![0, 0, 0, 2, 0x41, 0x9a], duration: 3600, keyframe: false },
+        ];
+        let data = fragment(7, 90_000, &samples).unwrap();
+        let found = boxes(&data);
+        assert_eq!(found[0].0, "moof");
+        assert_eq!(found[1], ("mdat".to_string(), 8 + samples[0].data.len() + 6));
+
+        // data-offset aponta para a primeira amostra dentro do mdat
+        let trun = data.windows(4).position(|window| window == b"trun").unwrap() + 4;
+        let offset = u32::from_be_bytes(data[trun + 8..trun + 12].try_into().unwrap()) as usize;
+        assert_eq!(&data[offset..offset + samples[0].data.len()], samples[0].data.as_slice());
+        let tfdt = data.windows(4).position(|window| window == b"tfdt").unwrap() + 8;
+        assert_eq!(u64::from_be_bytes(data[tfdt..tfdt + 8].try_into().unwrap()), 90_000);
+    }
+}
@@ -0,0 +1,96 @@
+//! Cache do último GOP
+//!
+//! Guarda as access units desde o último keyframe; um viewer novo recebe o
+//! GOP inteiro ao entrar e mostra o primeiro quadro na hora, em vez de
+//! esperar o próximo IDR da câmera.
+
+/// Limite de quadros por GOP (GOPs maiores deixam de ser cacheados até o
+/// próximo keyframe)
+pub const GOP_CACHE_MAX_FRAMES: usize = 300;
+
+#[derive(Debug)]
+pub struct GopCache<T> {
+    frames: Vec<T>,
+    max_frames: usize,
+    /// GOP atual estourou o limite: descarta até o próximo keyframe
+    overflowed: bool,
+}
+
+impl<T: Clone> GopCache<T> {
+    pub fn new(max_frames: usize) -> Self {
+        Self {
+            frames: Vec::new(),
+            max_frames,
+            overflowed: false,
+        }
+    }
+
+    /// Registra uma access unit; um keyframe inicia um GOP novo
+    pub fn push(&mut self, frame: T, keyframe: bool) {
+        if keyframe {
+            self.frames.clear();
+            self.overflowed = false;
+        } else if self.frames.is_empty() || self.overflowed {
+            // Sem keyframe de referência os deltas não decodificam
+            return;
+        }
+
+        if self.frames.len() >= self.max_frames {
+            self.frames.clear();
+            self.overflowed = true;
+            return;
+        }
+        self.frames.push(frame);
+    }
+
+    /// GOP atual (keyframe primeiro), vazio se não há keyframe
+    pub fn snapshot(&self) -> Vec<T> {
+        self.frames.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_last_gop() {
+        let mut cache = GopCache::new(10);
+
+        // Deltas antes do primeiro keyframe são inúteis
+        cache.push("p0", false);
+        assert!(cache.is_empty());
+
+        cache.push("i1", true);
+        cache.push("p1", false);
+        cache.push("p2", false);
+        assert_eq!(cache.snapshot(), vec!["i1", "p1", "p2"]);
+
+        cache.push("i2", true);
+        cache.push("p3", false);
+        assert_eq!(cache.snapshot(), vec!["i2", "p3"]);
+    }
+
+    #[test]
+    fn test_oversized_gop_is_dropped_until_next_keyframe() {
+        let mut cache = GopCache::new(3);
+        cache.push("i1", true);
+        cache.push("p1", false);
+        cache.push("p2", false);
+        cache.push("p3", false);
+        assert!(cache.is_empty());
+        cache.push("p4", false);
+        assert!(cache.is_empty());
+
+        cache.push("i2", true);
+        assert_eq!(cache.len(), 1);
+    }
+}
@@ -0,0 +1,721 @@
+//! GStreamer WebRTC playback of recorded footage
+//!
+//! Two pipelines per session. The output one is a live
+//! `appsrc ! rtph264pay ! webrtcbin` that stays PLAYING for the whole
+//! connection; the segment one reads an hourly MKV from vms-storage
+//! (`souphttpsrc ! matroskademux ! h264parse ! appsink`) and is the one that
+//! pauses, seeks and changes rate. Frames cross over without their
+//! timestamps: `appsrc` stamps them on arrival, so the browser plays whatever
+//! pace the segment pipeline sets (2x, paused, one frame at a time) as a
+//! regular live stream.
+//!
+//! The H.264 is never decoded, so seeks land on the keyframe before the
+//! requested time and stepping only goes forward. Commands come over the
+//! browser's `control` data channel and are applied by a task that owns the
+//! segment pipeline; at the end of an hour it moves on to the next file,
+//! skipping hours without recordings, until it reaches the present.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+use gstreamer_webrtc as gst_webrtc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::candidates::CandidateQueue;
+use crate::gstreamer_webrtc::{configure_webrtcbin, h264_rtp_caps, negotiate};
+use crate::ice::IceConfig;
+use crate::playback::{
+    next_segment, segment_start, segment_url, PlaybackCommand, PlaybackError, PlaybackEvent, PlaybackInfo, Timeline,
+    CONTROL_CHANNEL,
+};
+
+/// Time allowed for a segment to fetch, demux and preroll its first frame
+const PREROLL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time allowed for one frame of a `step`
+const STEP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often a playing session reports its position
+const STATUS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Messages for the task that owns the segment pipeline
+enum Control {
+    /// The browser is connected: playback can begin
+    Connected,
+    /// The browser opened its `control` data channel
+    Channel(gst_webrtc::WebRTCDataChannel),
+    Command(PlaybackCommand),
+    /// The segment starting at this hour reached its end
+    SegmentEnded(DateTime<Utc>),
+    SegmentFailed(DateTime<Utc>, String),
+    Stop,
+}
+
+pub struct PlaybackSession {
+    id: Uuid,
+    camera_id: String,
+    start: DateTime<Utc>,
+    started_at: DateTime<Utc>,
+    pipeline: gst::Pipeline,
+    webrtcbin: gst::Element,
+    candidates: Arc<CandidateQueue>,
+    control: mpsc::UnboundedSender<Control>,
+    /// Handed to its task once the first segment is loaded
+    player: Mutex<Option<Player>>,
+}
+
+impl PlaybackSession {
+    /// Build the output pipeline; footage is loaded by [`PlaybackSession::open`]
+    pub fn new(
+        id: Uuid,
+        camera_id: String,
+        storage_url: &str,
+        start: DateTime<Utc>,
+        ice: &IceConfig,
+        departures: mpsc::UnboundedSender<Uuid>,
+    ) -> Result<Arc<Self>> {
+        info!("📼 Creating playback session {} for camera {} at {}", id, camera_id, start);
+
+        let pipeline = gst::Pipeline::new();
+        let appsrc = gst_app::AppSrc::builder()
+            .name("recording")
+            .is_live(true)
+            .do_timestamp(true)
+            .format(gst::Format::Time)
+            .caps(&byte_stream_caps())
+            .build();
+        let pay = gst::ElementFactory::make("rtph264pay")
+            .property("config-interval", -1i32)
+            .property("pt", 96u32)
+            .build()
+            .context("Failed to create rtph264pay")?;
+        let capsfilter = gst::ElementFactory::make("capsfilter")
+            .property("caps", h264_rtp_caps())
+            .build()
+            .context("Failed to create capsfilter")?;
+        let webrtcbin = gst::ElementFactory::make("webrtcbin")
+            .name(format!("playback-{}", id))
+            .property_from_str("bundle-policy", "max-bundle")
+            .build()
+            .context("Failed to create webrtcbin")?;
+        let candidates = configure_webrtcbin(&webrtcbin, id, ice, &departures);
+
+        webrtcbin.connect("on-new-transceiver", false, |values| {
+            if let Some(transceiver) = values.get(1).and_then(|v| v.get::<gst_webrtc::WebRTCRTPTransceiver>().ok()) {
+                transceiver.set_property("direction", gst_webrtc::WebRTCRTPTransceiverDirection::Sendonly);
+                transceiver.set_property("codec-preferences", h264_rtp_caps());
+            }
+            None
+        });
+
+        // Frames pushed before the browser connects would be lost
+        let (control, commands) = mpsc::unbounded_channel();
+        let connected = control.clone();
+        webrtcbin.connect_notify(Some("connection-state"), move |bin, _| {
+            let state = bin.property::<gst_webrtc::WebRTCPeerConnectionState>("connection-state");
+            if state == gst_webrtc::WebRTCPeerConnectionState::Connected {
+                let _ = connected.send(Control::Connected);
+            }
+        });
+
+        // Commands arrive on the browser's control channel
+        let opened = control.clone();
+        webrtcbin.connect("on-data-channel", false, move |values| {
+            let channel = values.get(1).and_then(|v| v.get::<gst_webrtc::WebRTCDataChannel>().ok())?;
+            let label = channel.property::<Option<String>>("label").unwrap_or_default();
+            if label != CONTROL_CHANNEL {
+                debug!("Ignoring data channel {:?} on playback {}", label, id);
+                return None;
+            }
+            let commands = opened.clone();
+            channel.connect("on-message-string", false, move |values| {
+                let channel = values.first().and_then(|v| v.get::<gst_webrtc::WebRTCDataChannel>().ok())?;
+                let message = values.get(1).and_then(|v| v.get::<Option<String>>().ok()).flatten()?;
+                match PlaybackCommand::parse(&message) {
+                    Ok(command) => {
+                        let _ = commands.send(Control::Command(command));
+                    }
+                    Err(e) => send_event(&channel, &PlaybackEvent::Error { message: e.to_string() }),
+                }
+                None
+            });
+            let _ = opened.send(Control::Channel(channel));
+            None
+        });
+
+        let elements = [appsrc.upcast_ref::<gst::Element>(), &pay, &capsfilter];
+        pipeline.add_many(elements)?;
+        pipeline.add(&webrtcbin)?;
+        gst::Element::link_many(elements).context("Failed to link playback output")?;
+        let webrtc_sink = webrtcbin
+            .request_pad_simple("sink_%u")
+            .context("No sink pad on webrtcbin")?;
+        capsfilter
+            .static_pad("src")
+            .context("No src pad on capsfilter")?
+            .link(&webrtc_sink)?;
+
+        let player = Player {
+            camera_id: camera_id.clone(),
+            storage_url: storage_url.to_string(),
+            appsrc,
+            control: control.clone(),
+            commands,
+            channel: None,
+            connected: false,
+            paused: true,
+            rate: 1.0,
+            ended: false,
+        };
+        Ok(Arc::new(Self {
+            id,
+            camera_id,
+            start,
+            started_at: Utc::now(),
+            pipeline,
+            webrtcbin,
+            candidates,
+            control,
+            player: Mutex::new(Some(player)),
+        }))
+    }
+
+    /// Load the segment holding the requested time, paused until the
+    /// browser connects
+    pub async fn open(&self) -> Result<(), PlaybackError> {
+        let player = self
+            .player
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or_else(|| PlaybackError::Failed("playback already opened".to_string()))?;
+
+        let segment = player.load(segment_start(self.start), self.start).await?;
+        info!("✅ Playback {} of camera {} open at {}", self.id, self.camera_id, self.start);
+        tokio::spawn(player.run(segment));
+        Ok(())
+    }
+
+    /// Handle the browser's SDP offer (video plus the control data channel)
+    pub async fn answer(&self, offer_sdp: String) -> Result<String> {
+        info!("📥 Processing playback offer for camera: {} ({} bytes)", self.camera_id, offer_sdp.len());
+        negotiate(&self.webrtcbin, offer_sdp, Some(&self.candidates)).await
+    }
+
+    pub fn add_ice_candidate(&self, sdp_m_line_index: u32, candidate: &str) -> Result<()> {
+        if candidate.is_empty() {
+            return Ok(());
+        }
+        self.webrtcbin
+            .emit_by_name::<()>("add-ice-candidate", &[&sdp_m_line_index, &candidate]);
+        Ok(())
+    }
+
+    pub fn info(&self) -> PlaybackInfo {
+        PlaybackInfo {
+            id: self.id,
+            camera_id: self.camera_id.clone(),
+            started_at: self.started_at,
+            start: self.start,
+        }
+    }
+
+    pub fn start(&self) -> Result<()> {
+        info!("▶️ Starting playback pipeline for camera: {}", self.camera_id);
+        self.pipeline.set_state(gst::State::Playing)?;
+        Ok(())
+    }
+
+    pub fn stop(&self) -> Result<()> {
+        info!("⏹️ Stopping playback pipeline for camera: {}", self.camera_id);
+        let _ = self.control.send(Control::Stop);
+        self.pipeline.set_state(gst::State::Null)?;
+        Ok(())
+    }
+}
+
+impl Drop for PlaybackSession {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+/// Frames of the segment pipeline on their way to the output `appsrc`
+struct Frames {
+    appsrc: gst_app::AppSrc,
+    /// Off until the segment is positioned (the first preroll is only probed)
+    forward: AtomicBool,
+    state: Mutex<FramesState>,
+    /// Signalled on every forwarded frame (`step` waits on it)
+    shown: Condvar,
+}
+
+#[derive(Default)]
+struct FramesState {
+    /// Stream time of the latest frame out of the demuxer
+    position: Option<Duration>,
+    /// PTS of the last forwarded frame: a prerolled frame is rendered again
+    /// when the pipeline resumes and must not reach the browser twice
+    last_pts: Option<gst::ClockTime>,
+    forwarded: u64,
+}
+
+impl Frames {
+    fn push(&self, sample: gst::Sample) -> Result<gst::FlowSuccess, gst::FlowError> {
+        let mut buffer = sample.buffer_owned().ok_or(gst::FlowError::Error)?;
+        let pts = buffer.pts();
+        let stream_time = sample
+            .segment()
+            .and_then(|segment| segment.downcast_ref::<gst::ClockTime>())
+            .zip(pts)
+            .and_then(|(segment, pts)| segment.to_stream_time(pts));
+
+        {
+            let mut state = self.state.lock().unwrap();
+            if let Some(stream_time) = stream_time {
+                state.position = Some(Duration::from_nanos(stream_time.nseconds()));
+            }
+            if !self.forward.load(Ordering::Acquire) || (pts.is_some() && state.last_pts == pts) {
+                return Ok(gst::FlowSuccess::Ok);
+            }
+            state.last_pts = pts;
+            state.forwarded += 1;
+        }
+        self.shown.notify_all();
+
+        // Restamped by the live appsrc on arrival
+        {
+            let buffer = buffer.make_mut();
+            buffer.set_pts(gst::ClockTime::NONE);
+            buffer.set_dts(gst::ClockTime::NONE);
+        }
+        self.appsrc.push_buffer(buffer)
+    }
+
+    fn position(&self) -> Option<Duration> {
+        self.state.lock().unwrap().position
+    }
+
+    fn forwarded(&self) -> u64 {
+        self.state.lock().unwrap().forwarded
+    }
+
+    /// Wait for a frame after the first `seen`
+    fn wait_shown(&self, seen: u64, timeout: Duration) -> bool {
+        let state = self.state.lock().unwrap();
+        let (_state, result) = self
+            .shown
+            .wait_timeout_while(state, timeout, |state| state.forwarded == seen)
+            .unwrap();
+        !result.timed_out()
+    }
+}
+
+/// One hourly file being read
+struct Segment {
+    pipeline: gst::Pipeline,
+    timeline: Timeline,
+    frames: Arc<Frames>,
+}
+
+impl Segment {
+    /// `souphttpsrc ! matroskademux ! h264parse ! appsink`, prerolled (paused)
+    /// at the keyframe before `target`
+    fn load(
+        camera_id: &str,
+        url: &str,
+        segment: DateTime<Utc>,
+        target: DateTime<Utc>,
+        rate: f64,
+        appsrc: gst_app::AppSrc,
+        control: mpsc::UnboundedSender<Control>,
+    ) -> Result<Self, PlaybackError> {
+        let failed = |e: anyhow::Error| PlaybackError::Failed(format!("{:#}", e));
+        let pipeline = gst::Pipeline::new();
+        let frames = Arc::new(Frames {
+            appsrc,
+            forward: AtomicBool::new(false),
+            state: Mutex::new(FramesState::default()),
+            shown: Condvar::new(),
+        });
+
+        let build = || -> Result<()> {
+            let source = gst::ElementFactory::make("souphttpsrc")
+                .property("location", url)
+                .build()
+                .context("Failed to create souphttpsrc")?;
+            let demux = gst::ElementFactory::make("matroskademux")
+                .build()
+                .context("Failed to create matroskademux")?;
+            // SPS/PPS in-band: every seek and segment switch starts decodable
+            let parse = gst::ElementFactory::make("h264parse")
+                .property("config-interval", -1i32)
+                .build()
+                .context("Failed to create h264parse")?;
+            let appsink = gst_app::AppSink::builder()
+                .caps(&byte_stream_caps())
+                .sync(true)
+                .build();
+
+            let prerolled = frames.clone();
+            let rendered = frames.clone();
+            appsink.set_callbacks(
+                gst_app::AppSinkCallbacks::builder()
+                    .new_preroll(move |sink| {
+                        let sample = sink.pull_preroll().map_err(|_| gst::FlowError::Flushing)?;
+                        prerolled.push(sample)
+                    })
+                    .new_sample(move |sink| {
+                        let sample = sink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                        rendered.push(sample)
+                    })
+                    .build(),
+            );
+
+            pipeline.add_many([&source, &demux, &parse, appsink.upcast_ref()])?;
+            source.link(&demux).context("Failed to link souphttpsrc")?;
+            parse.link(&appsink).context("Failed to link h264parse")?;
+
+            let parse_sink = parse.static_pad("sink").context("No sink pad on h264parse")?;
+            demux.connect_pad_added(move |_demux, src_pad| {
+                let caps = src_pad.current_caps().unwrap_or_else(|| src_pad.query_caps(None));
+                let is_h264 = caps
+                    .structure(0)
+                    .is_some_and(|structure| structure.name() == "video/x-h264");
+                if is_h264 && !parse_sink.is_linked() {
+                    if let Err(e) = src_pad.link(&parse_sink) {
+                        warn!("⚠️ Failed to link recorded video: {:?}", e);
+                    }
+                }
+            });
+            Ok(())
+        };
+        build().map_err(failed)?;
+
+        let bus = pipeline.bus().ok_or_else(|| PlaybackError::Failed("pipeline has no bus".to_string()))?;
+        let preroll = |pipeline: &gst::Pipeline| -> Result<(), PlaybackError> {
+            let (result, _, _) = pipeline.state(gst::ClockTime::from_nseconds(PREROLL_TIMEOUT.as_nanos() as u64));
+            if result.is_ok() {
+                return Ok(());
+            }
+            let _ = pipeline.set_state(gst::State::Null);
+            match bus.pop_filtered(&[gst::MessageType::Error]) {
+                Some(msg) => match msg.view() {
+                    // 404 from vms-storage: no file for that hour
+                    gst::MessageView::Error(err) if err.error().matches(gst::ResourceError::NotFound) => {
+                        Err(PlaybackError::NotRecorded {
+                            camera_id: camera_id.to_string(),
+                            time: target,
+                        })
+                    }
+                    gst::MessageView::Error(err) => Err(PlaybackError::Failed(err.error().to_string())),
+                    _ => Err(PlaybackError::Failed("segment failed to preroll".to_string())),
+                },
+                None => Err(PlaybackError::Failed("timed out loading the recording".to_string())),
+            }
+        };
+
+        // First frame tells how the file counts time; then position for real
+        pipeline
+            .set_state(gst::State::Paused)
+            .map_err(|e| PlaybackError::Failed(e.to_string()))?;
+        preroll(&pipeline)?;
+        let timeline = Timeline::detect(segment, frames.position().unwrap_or_default());
+
+        frames.forward.store(true, Ordering::Release);
+        let loaded = Self {
+            pipeline,
+            timeline,
+            frames,
+        };
+        loaded.seek(target, rate).map_err(failed)?;
+        preroll(&loaded.pipeline)?;
+
+        // End of the hour and failures go to the session task
+        let hour = segment;
+        let name = camera_id.to_string();
+        bus.set_sync_handler(move |_, msg| {
+            match msg.view() {
+                gst::MessageView::Eos(..) => {
+                    let _ = control.send(Control::SegmentEnded(hour));
+                }
+                gst::MessageView::Error(err) => {
+                    error!("❌ Playback error on camera {}: {} ({:?})", name, err.error(), err.debug());
+                    let _ = control.send(Control::SegmentFailed(hour, err.error().to_string()));
+                }
+                _ => {}
+            }
+            gst::BusSyncReply::Drop
+        });
+        Ok(loaded)
+    }
+
+    /// Flushing seek to the keyframe before `time`
+    fn seek(&self, time: DateTime<Utc>, rate: f64) -> Result<()> {
+        // After a flush the same frame may legitimately go out again
+        self.frames.state.lock().unwrap().last_pts = None;
+        let position = gst::ClockTime::from_nseconds(self.timeline.stream_time(time).as_nanos() as u64);
+        self.pipeline
+            .seek(
+                rate,
+                gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT | gst::SeekFlags::SNAP_BEFORE,
+                gst::SeekType::Set,
+                position,
+                gst::SeekType::None,
+                gst::ClockTime::NONE,
+            )
+            .context("Seek rejected")
+    }
+
+    /// Instant rate change (no flush, no jump); demuxers without it get a
+    /// flushing seek at the current position
+    fn set_rate(&self, rate: f64) -> Result<()> {
+        let instant = gst::event::Seek::new(
+            rate,
+            gst::SeekFlags::INSTANT_RATE_CHANGE,
+            gst::SeekType::None,
+            gst::ClockTime::NONE,
+            gst::SeekType::None,
+            gst::ClockTime::NONE,
+        );
+        if self.pipeline.send_event(instant) {
+            return Ok(());
+        }
+        self.seek(self.time(), rate)
+    }
+
+    fn set_paused(&self, paused: bool) -> Result<()> {
+        let state = if paused { gst::State::Paused } else { gst::State::Playing };
+        self.pipeline.set_state(state)?;
+        Ok(())
+    }
+
+    /// Recorded time of the latest frame
+    fn time(&self) -> DateTime<Utc> {
+        self.frames
+            .position()
+            .map(|position| self.timeline.time_at(position))
+            .unwrap_or(self.timeline.segment)
+    }
+}
+
+impl Drop for Segment {
+    fn drop(&mut self) {
+        self.frames.forward.store(false, Ordering::Release);
+        let _ = self.pipeline.set_state(gst::State::Null);
+    }
+}
+
+/// State of a session's playback, owned by its task
+struct Player {
+    camera_id: String,
+    storage_url: String,
+    appsrc: gst_app::AppSrc,
+    control: mpsc::UnboundedSender<Control>,
+    commands: mpsc::UnboundedReceiver<Control>,
+    channel: Option<gst_webrtc::WebRTCDataChannel>,
+    connected: bool,
+    paused: bool,
+    rate: f64,
+    /// Caught up with the present: nothing more recorded
+    ended: bool,
+}
+
+impl Player {
+    /// Load a segment off the runtime threads (HTTP fetch and preroll block)
+    async fn load(&self, segment: DateTime<Utc>, target: DateTime<Utc>) -> Result<Segment, PlaybackError> {
+        let url = segment_url(&self.storage_url, &self.camera_id, segment);
+        debug!("📂 Loading {} at {}", url, target);
+        let camera_id = self.camera_id.clone();
+        let (rate, appsrc, control) = (self.rate, self.appsrc.clone(), self.control.clone());
+        let loaded = tokio::task::spawn_blocking(move || {
+            Segment::load(&camera_id, &url, segment, target, rate, appsrc, control)
+        })
+        .await
+        .map_err(|e| PlaybackError::Failed(e.to_string()))??;
+
+        if !self.paused {
+            loaded.set_paused(false).map_err(|e| PlaybackError::Failed(format!("{:#}", e)))?;
+        }
+        Ok(loaded)
+    }
+
+    async fn run(mut self, mut segment: Segment) {
+        let mut ticker = tokio::time::interval(STATUS_INTERVAL);
+        loop {
+            tokio::select! {
+                control = self.commands.recv() => match control {
+                    None | Some(Control::Stop) => break,
+                    Some(control) => self.handle(control, &mut segment).await,
+                },
+                _ = ticker.tick() => {
+                    if !self.paused {
+                        self.status(&segment);
+                    }
+                }
+            }
+        }
+        debug!("Playback task for camera {} finished", self.camera_id);
+    }
+
+    async fn handle(&mut self, control: Control, segment: &mut Segment) {
+        match control {
+            // Resend the keyframe shown before the connection, then play
+            Control::Connected if !self.connected => {
+                self.connected = true;
+                let started = segment
+                    .seek(segment.time(), self.rate)
+                    .and_then(|()| segment.set_paused(false));
+                match started {
+                    Ok(()) => self.paused = false,
+                    Err(e) => warn!("⚠️ Playback of camera {} failed to start: {:#}", self.camera_id, e),
+                }
+                self.status(segment);
+            }
+            Control::Channel(channel) => {
+                info!("🎛️ Playback control channel open for camera {}", self.camera_id);
+                self.channel = Some(channel);
+                self.status(segment);
+            }
+            Control::Command(command) => {
+                debug!("🎛️ Playback command on camera {}: {:?}", self.camera_id, command);
+                if let Err(e) = self.apply(command, segment).await {
+                    warn!("⚠️ Playback command failed on camera {}: {}", self.camera_id, e);
+                    self.send(&PlaybackEvent::Error { message: e.to_string() });
+                }
+                self.status(segment);
+            }
+            Control::SegmentEnded(hour) if hour == segment.timeline.segment => {
+                self.advance(segment).await;
+            }
+            Control::SegmentFailed(hour, message) if hour == segment.timeline.segment => {
+                self.send(&PlaybackEvent::Error { message });
+                self.advance(segment).await;
+            }
+            // From a segment already replaced (`Stop` ends the task in `run`)
+            Control::Connected | Control::SegmentEnded(_) | Control::SegmentFailed(..) | Control::Stop => {}
+        }
+    }
+
+    async fn apply(&mut self, command: PlaybackCommand, segment: &mut Segment) -> Result<(), PlaybackError> {
+        let failed = |e: anyhow::Error| PlaybackError::Failed(format!("{:#}", e));
+        match command {
+            PlaybackCommand::Pause => {
+                segment.set_paused(true).map_err(failed)?;
+                self.paused = true;
+            }
+            PlaybackCommand::Play if self.ended => {
+                self.send(&PlaybackEvent::End { time: segment.time() });
+            }
+            PlaybackCommand::Play => {
+                segment.set_paused(false).map_err(failed)?;
+                self.paused = false;
+            }
+            PlaybackCommand::Speed { rate } => {
+                segment.set_rate(rate).map_err(failed)?;
+                self.rate = rate;
+            }
+            PlaybackCommand::Seek { time } => {
+                if time > Utc::now() {
+                    return Err(PlaybackError::NotRecorded {
+                        camera_id: self.camera_id.clone(),
+                        time,
+                    });
+                }
+                let hour = segment_start(time);
+                if hour == segment.timeline.segment && !self.ended {
+                    segment.seek(time, self.rate).map_err(failed)?;
+                } else {
+                    // Another hour (or the session had ended): its own file
+                    *segment = self.load(hour, time).await?;
+                }
+                self.ended = false;
+            }
+            PlaybackCommand::Step { frames } => {
+                if !self.paused {
+                    segment.set_paused(true).map_err(failed)?;
+                    self.paused = true;
+                }
+                let (pipeline, shown) = (segment.pipeline.clone(), segment.frames.clone());
+                let stepped = tokio::task::spawn_blocking(move || step(&pipeline, &shown, frames))
+                    .await
+                    .unwrap_or_default();
+                if stepped < frames {
+                    debug!("Stepped {} of {} frames on camera {}", stepped, frames, self.camera_id);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Next hour with recordings; none before the present ends the session
+    async fn advance(&mut self, segment: &mut Segment) {
+        let mut hour = segment.timeline.segment;
+        while let Some(next) = next_segment(hour, Utc::now()) {
+            match self.load(next, next).await {
+                Ok(loaded) => {
+                    info!("⏭️ Playback of camera {} continues at {}", self.camera_id, next);
+                    *segment = loaded;
+                    self.status(segment);
+                    return;
+                }
+                Err(PlaybackError::NotRecorded { .. }) => hour = next,
+                Err(e) => {
+                    warn!("⚠️ Playback of camera {} stopped at {}: {}", self.camera_id, next, e);
+                    self.send(&PlaybackEvent::Error { message: e.to_string() });
+                    break;
+                }
+            }
+        }
+
+        info!("🏁 Playback of camera {} reached the end of recordings", self.camera_id);
+        self.ended = true;
+        self.paused = true;
+        self.send(&PlaybackEvent::End { time: segment.time() });
+    }
+
+    fn status(&self, segment: &Segment) {
+        self.send(&PlaybackEvent::Status {
+            time: segment.time(),
+            paused: self.paused,
+            rate: self.rate,
+        });
+    }
+
+    fn send(&self, event: &PlaybackEvent) {
+        if let Some(channel) = &self.channel {
+            send_event(channel, event);
+        }
+    }
+}
+
+/// Forward `count` frames of a paused segment, one step at a time: skipped
+/// frames would leave the browser's decoder without their references
+fn step(pipeline: &gst::Pipeline, frames: &Frames, count: u32) -> u32 {
+    for stepped in 0..count {
+        let seen = frames.forwarded();
+        let step = gst::event::Step::new(gst::format::Buffers::from_u64(1), 1.0, true, false);
+        if !pipeline.send_event(step) || !frames.wait_shown(seen, STEP_TIMEOUT) {
+            return stepped;
+        }
+    }
+    count
+}
+
+fn send_event(channel: &gst_webrtc::WebRTCDataChannel, event: &PlaybackEvent) {
+    channel.emit_by_name::<()>("send-string", &[&event.to_json()]);
+}
+
+fn byte_stream_caps() -> gst::Caps {
+    gst::Caps::builder("video/x-h264")
+        .field("stream-format", "byte-stream")
+        .field("alignment", "au")
+        .build()
+}
@@ -0,0 +1,540 @@
+//! GStreamer talk-down pipeline: operator audio to the camera speaker
+//!
+//! Each talk opens its own `rtspsrc` connection with `backchannel=onvif`,
+//! which sends the ONVIF backchannel `Require` header and sets up only the
+//! camera's sendonly audio stream (plus its microphone for live talks). The
+//! operator's voice (Opus from a receiving webrtcbin) or an uploaded clip is
+//! decoded, resampled to 8 kHz mono, encoded to the camera's G.711 flavour
+//! and handed to rtspsrc with `push-backchannel-sample` (GStreamer 1.22+).
+//!
+//! Live talks are sendrecv: the camera microphone goes back to the browser as
+//! Opus, mixed over silence so the track exists even when the camera has no
+//! microphone. Clips are paced in real time and end the session at EOS.
+
+use anyhow::{anyhow, bail, Context, Result};
+use bytes::Bytes;
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+use gstreamer_webrtc as gst_webrtc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::backchannel::{classify_stream, opus_payload_type, BackchannelError, StreamRole, G711, G711_CLOCK_RATE};
+use crate::candidates::CandidateQueue;
+use crate::gstreamer_webrtc::{configure_webrtcbin, negotiate, Source};
+use crate::ice::IceConfig;
+
+/// Opus payload type when the offer does not map one
+const DEFAULT_OPUS_PT: u8 = 111;
+
+/// Entry of the encoder chain, linked once the backchannel codec is known
+const TALK_INPUT: &str = "talk-in";
+
+/// Where the audio sent to the camera comes from
+pub enum TalkInput {
+    /// Operator's browser, negotiated with [`TalkSession::answer`]
+    WebRtc,
+    /// Audio file in any format decodebin can read (WAV, MP3, Ogg...)
+    Clip(Bytes),
+}
+
+#[derive(Debug, Clone)]
+enum Backchannel {
+    Pending,
+    Ready { stream_id: u32, codec: G711 },
+    Failed(BackchannelError),
+}
+
+/// The operator's sendrecv webrtcbin
+struct Operator {
+    webrtcbin: gst::Element,
+    candidates: Arc<CandidateQueue>,
+    /// Payloader of the camera microphone, set to the offer's Opus type
+    pay: gst::Element,
+}
+
+pub struct TalkSession {
+    id: Uuid,
+    camera_id: String,
+    pipeline: gst::Pipeline,
+    rtspsrc: gst::Element,
+    backchannel: Arc<watch::Sender<Backchannel>>,
+    operator: Option<Operator>,
+    /// Clip waiting for the backchannel to come up
+    clip: Mutex<Option<Bytes>>,
+    departures: mpsc::UnboundedSender<Uuid>,
+}
+
+impl TalkSession {
+    /// Build the talk pipeline for a camera; nothing is sent before
+    /// [`TalkSession::open`] finds the backchannel
+    pub fn new(
+        id: Uuid,
+        camera_id: String,
+        source: Source,
+        input: TalkInput,
+        ice: &IceConfig,
+        departures: mpsc::UnboundedSender<Uuid>,
+    ) -> Result<Arc<Self>> {
+        info!("🎙️ Creating talk session {} for camera: {}", id, camera_id);
+        let Source::Rtsp { url, username, password } = source else {
+            bail!("Camera {} has no RTSP source to talk through", camera_id);
+        };
+
+        let pipeline = gst::Pipeline::new();
+        let rtspsrc = gst::ElementFactory::make("rtspsrc")
+            .name("source")
+            .property("location", &url)
+            .property("user-id", &username)
+            .property("user-pw", &password)
+            .property("latency", 0u32)
+            .property_from_str("backchannel", "onvif")
+            .build()
+            .context("Failed to create rtspsrc")?;
+        if glib::subclass::SignalId::lookup("push-backchannel-sample", rtspsrc.type_()).is_none() {
+            bail!("rtspsrc has no push-backchannel-sample signal (GStreamer 1.22 or newer required)");
+        }
+        pipeline.add(&rtspsrc)?;
+
+        let listen = matches!(input, TalkInput::WebRtc);
+        let backchannel = Arc::new(watch::Sender::new(Backchannel::Pending));
+
+        // Only the backchannel (and the microphone, for live talks) is set up
+        let found = backchannel.clone();
+        let select_camera = camera_id.clone();
+        rtspsrc.connect("select-stream", false, move |values| {
+            let stream_id = values.get(1).and_then(|v| v.get::<u32>().ok()).unwrap_or_default();
+            let role = values
+                .get(2)
+                .and_then(|v| v.get::<gst::Caps>().ok())
+                .and_then(|caps| {
+                    let structure = caps.structure(0)?;
+                    Some(classify_stream(
+                        structure.get::<&str>("media").ok()?,
+                        structure.get::<&str>("encoding-name").ok()?,
+                        structure.get::<i32>("clock-rate").ok(),
+                        structure.has_field("a-sendonly"),
+                        listen,
+                    ))
+                })
+                .unwrap_or(StreamRole::Skip);
+
+            let selected = match role {
+                StreamRole::Backchannel(codec) => {
+                    info!(
+                        "🔊 Camera {} backchannel on stream {} ({})",
+                        select_camera,
+                        stream_id,
+                        codec.encoding_name()
+                    );
+                    found.send_if_modified(|state| match state {
+                        Backchannel::Pending => {
+                            *state = Backchannel::Ready { stream_id, codec };
+                            true
+                        }
+                        _ => false,
+                    });
+                    true
+                }
+                StreamRole::CameraAudio => true,
+                StreamRole::Skip => false,
+            };
+            Some(selected.to_value())
+        });
+
+        // Every stream is set up by now: no backchannel means no speaker
+        let found = backchannel.clone();
+        rtspsrc.connect_no_more_pads(move |_| {
+            fail_pending(&found, BackchannelError::Unsupported);
+        });
+
+        let operator = if listen {
+            let (operator, mixer) = add_operator(&pipeline, id, ice, &departures)?;
+
+            // Camera microphone into the mix sent back to the operator
+            let pipeline_weak = pipeline.downgrade();
+            rtspsrc.connect_pad_added(move |_src, src_pad| {
+                if !src_pad.name().starts_with("recv_rtp_src") {
+                    return;
+                }
+                let Some(pipeline) = pipeline_weak.upgrade() else { return };
+                let mixer = mixer.clone();
+                let target = move |pipeline: &gst::Pipeline| mixer_input(pipeline, &mixer);
+                match add_decoder(&pipeline, target, false) {
+                    Ok(decodebin) => {
+                        if let Some(sink) = decodebin.static_pad("sink") {
+                            let _ = src_pad.link(&sink);
+                        }
+                        info!("🎧 Camera microphone linked to the operator");
+                    }
+                    Err(e) => warn!("⚠️ Failed to link camera microphone: {:#}", e),
+                }
+            });
+            Some(operator)
+        } else {
+            None
+        };
+
+        // Errors (backchannel refused, camera gone) fail setup or end the talk
+        let failed = backchannel.clone();
+        let bus_departures = departures.clone();
+        let bus_camera = camera_id.clone();
+        pipeline
+            .bus()
+            .context("Pipeline has no bus")?
+            .set_sync_handler(move |_, msg| {
+                if let gst::MessageView::Error(err) = msg.view() {
+                    error!("❌ Talk pipeline error on camera {}: {} ({:?})", bus_camera, err.error(), err.debug());
+                    fail_pending(&failed, BackchannelError::Failed(err.error().to_string()));
+                    let _ = bus_departures.send(id);
+                }
+                gst::BusSyncReply::Drop
+            });
+
+        pipeline.set_latency(gst::ClockTime::from_mseconds(0));
+
+        let clip = match input {
+            TalkInput::Clip(clip) => Some(clip),
+            TalkInput::WebRtc => None,
+        };
+        Ok(Arc::new(Self {
+            id,
+            camera_id,
+            pipeline,
+            rtspsrc,
+            backchannel,
+            operator,
+            clip: Mutex::new(clip),
+            departures,
+        }))
+    }
+
+    /// Wait for the camera to set up its backchannel, then link the encoder
+    /// (and start the clip, if any)
+    pub async fn open(&self, timeout: Duration) -> Result<G711, BackchannelError> {
+        let mut rx = self.backchannel.subscribe();
+        let state = tokio::time::timeout(timeout, rx.wait_for(|state| !matches!(state, Backchannel::Pending)))
+            .await
+            .map_err(|_| BackchannelError::Timeout)?
+            .map(|state| state.clone())
+            .map_err(|_| BackchannelError::Failed("pipeline closed".to_string()))?;
+
+        let (stream_id, codec) = match state {
+            Backchannel::Ready { stream_id, codec } => (stream_id, codec),
+            Backchannel::Failed(e) => return Err(e),
+            Backchannel::Pending => return Err(BackchannelError::Timeout),
+        };
+
+        let clip = self.clip.lock().unwrap().take();
+        let linked = self
+            .add_encoder(stream_id, codec, clip.is_some())
+            .and_then(|()| match clip {
+                Some(clip) => self.play_clip(clip),
+                None => Ok(()),
+            });
+        linked.map_err(|e| BackchannelError::Failed(format!("{:#}", e)))?;
+
+        info!(
+            "✅ Talk session {} open on camera {} ({})",
+            self.id,
+            self.camera_id,
+            codec.encoding_name()
+        );
+        Ok(codec)
+    }
+
+    /// `talk-in` (audioconvert) `! audioresample ! 8 kHz mono ! G.711 ! pay !
+    /// appsink`, each RTP packet pushed into the backchannel
+    fn add_encoder(&self, stream_id: u32, codec: G711, paced: bool) -> Result<()> {
+        let convert = gst::ElementFactory::make("audioconvert")
+            .name(TALK_INPUT)
+            .build()
+            .context("Failed to create audioconvert")?;
+        let resample = gst::ElementFactory::make("audioresample")
+            .build()
+            .context("Failed to create audioresample")?;
+        let capsfilter = gst::ElementFactory::make("capsfilter")
+            .property(
+                "caps",
+                gst::Caps::builder("audio/x-raw")
+                    .field("rate", G711_CLOCK_RATE)
+                    .field("channels", 1i32)
+                    .build(),
+            )
+            .build()
+            .context("Failed to create capsfilter")?;
+        let encoder = gst::ElementFactory::make(codec.encoder())
+            .build()
+            .with_context(|| format!("Failed to create {}", codec.encoder()))?;
+        let pay = gst::ElementFactory::make(codec.payloader())
+            .property("pt", codec.payload_type())
+            .build()
+            .with_context(|| format!("Failed to create {}", codec.payloader()))?;
+
+        // Clips are paced by the clock; live audio goes out as it arrives
+        let appsink = gst_app::AppSink::builder().name("backchannel").sync(paced).build();
+        let rtspsrc = self.rtspsrc.downgrade();
+        let departures = self.departures.clone();
+        let (id, camera_id) = (self.id, self.camera_id.clone());
+        appsink.set_callbacks(
+            gst_app::AppSinkCallbacks::builder()
+                .new_sample(move |sink| {
+                    let sample = sink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                    let rtspsrc = rtspsrc.upgrade().ok_or(gst::FlowError::Flushing)?;
+                    let ret = rtspsrc.emit_by_name::<gst::FlowReturn>("push-backchannel-sample", &[&stream_id, &sample]);
+                    if ret != gst::FlowReturn::Ok {
+                        debug!("Backchannel push on camera {} returned {:?}", camera_id, ret);
+                    }
+                    Ok(gst::FlowSuccess::Ok)
+                })
+                .eos(move |_| {
+                    info!("🔈 Talk session {} finished", id);
+                    let _ = departures.send(id);
+                })
+                .build(),
+        );
+
+        let elements = [convert, resample, capsfilter, encoder, pay, appsink.upcast()];
+        self.pipeline.add_many(&elements)?;
+        gst::Element::link_many(&elements).context("Failed to link backchannel encoder")?;
+        for element in elements.iter().rev() {
+            element.sync_state_with_parent()?;
+        }
+        Ok(())
+    }
+
+    /// Feed the clip through decodebin; EOS ends the session
+    fn play_clip(&self, clip: Bytes) -> Result<()> {
+        info!("📼 Playing {} byte clip on camera {}", clip.len(), self.camera_id);
+
+        let appsrc = gst_app::AppSrc::builder().name("clip").format(gst::Format::Bytes).build();
+        let decodebin = add_decoder(&self.pipeline, talk_input, true)?;
+        self.pipeline.add(&appsrc)?;
+        appsrc.link(&decodebin).context("Failed to link clip to decoder")?;
+        appsrc.sync_state_with_parent()?;
+
+        appsrc
+            .push_buffer(gst::Buffer::from_slice(clip))
+            .map_err(|e| anyhow!("Failed to push clip: {:?}", e))?;
+        appsrc
+            .end_of_stream()
+            .map_err(|e| anyhow!("Failed to end clip: {:?}", e))?;
+        Ok(())
+    }
+
+    /// Handle the operator's SDP offer; the answer carries our candidates
+    pub async fn answer(&self, offer_sdp: String) -> Result<String> {
+        let operator = self
+            .operator
+            .as_ref()
+            .ok_or_else(|| anyhow!("Talk session {} plays a clip", self.id))?;
+
+        // Camera microphone goes back with the payload type the browser mapped
+        let pt = opus_payload_type(&offer_sdp).unwrap_or(DEFAULT_OPUS_PT);
+        operator.pay.set_property("pt", u32::from(pt));
+
+        info!("📥 Processing talk offer for camera: {} ({} bytes)", self.camera_id, offer_sdp.len());
+        negotiate(&operator.webrtcbin, offer_sdp, Some(&operator.candidates)).await
+    }
+
+    /// Add a remote ICE candidate to the operator's webrtcbin
+    pub fn add_ice_candidate(&self, sdp_m_line_index: u32, candidate: &str) -> Result<()> {
+        let operator = self
+            .operator
+            .as_ref()
+            .ok_or_else(|| anyhow!("Talk session {} plays a clip", self.id))?;
+        if candidate.is_empty() {
+            return Ok(());
+        }
+        operator
+            .webrtcbin
+            .emit_by_name::<()>("add-ice-candidate", &[&sdp_m_line_index, &candidate]);
+        Ok(())
+    }
+
+    pub fn start(&self) -> Result<()> {
+        info!("▶️ Starting talk pipeline for camera: {}", self.camera_id);
+        self.pipeline.set_state(gst::State::Playing)?;
+        Ok(())
+    }
+
+    pub fn stop(&self) -> Result<()> {
+        info!("⏹️ Stopping talk pipeline for camera: {}", self.camera_id);
+        self.pipeline.set_state(gst::State::Null)?;
+        Ok(())
+    }
+}
+
+impl Drop for TalkSession {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+fn fail_pending(backchannel: &watch::Sender<Backchannel>, error: BackchannelError) {
+    backchannel.send_if_modified(|state| match state {
+        Backchannel::Pending => {
+            *state = Backchannel::Failed(error);
+            true
+        }
+        _ => false,
+    });
+}
+
+/// Receiving webrtcbin for the operator's voice, with the camera
+/// microphone mixed over silence on the way back
+fn add_operator(
+    pipeline: &gst::Pipeline,
+    id: Uuid,
+    ice: &IceConfig,
+    departures: &mpsc::UnboundedSender<Uuid>,
+) -> Result<(Operator, gst::Element)> {
+    let webrtcbin = gst::ElementFactory::make("webrtcbin")
+        .name("operator")
+        .property_from_str("bundle-policy", "max-bundle")
+        .build()
+        .context("Failed to create webrtcbin")?;
+    let candidates = configure_webrtcbin(&webrtcbin, id, ice, departures);
+
+    webrtcbin.connect("on-new-transceiver", false, |values| {
+        if let Some(transceiver) = values.get(1).and_then(|v| v.get::<gst_webrtc::WebRTCRTPTransceiver>().ok()) {
+            transceiver.set_property("direction", gst_webrtc::WebRTCRTPTransceiverDirection::Sendrecv);
+        }
+        None
+    });
+
+    let make = |factory: &str| {
+        gst::ElementFactory::make(factory)
+            .build()
+            .with_context(|| format!("Failed to create {}", factory))
+    };
+    let silence = gst::ElementFactory::make("audiotestsrc")
+        .property_from_str("wave", "silence")
+        .property("is-live", true)
+        .build()
+        .context("Failed to create audiotestsrc")?;
+    let mixer = gst::ElementFactory::make("audiomixer")
+        .name("listen")
+        .build()
+        .context("Failed to create audiomixer")?;
+    let pay = gst::ElementFactory::make("rtpopuspay")
+        .property("pt", u32::from(DEFAULT_OPUS_PT))
+        .build()
+        .context("Failed to create rtpopuspay")?;
+    let capsfilter = gst::ElementFactory::make("capsfilter")
+        .property(
+            "caps",
+            gst::Caps::builder("application/x-rtp")
+                .field("media", "audio")
+                .field("encoding-name", "OPUS")
+                .field("clock-rate", 48000i32)
+                .build(),
+        )
+        .build()
+        .context("Failed to create capsfilter")?;
+
+    let elements = [
+        silence.clone(),
+        mixer.clone(),
+        make("audioconvert")?,
+        make("audioresample")?,
+        make("opusenc")?,
+        pay.clone(),
+        capsfilter.clone(),
+    ];
+    pipeline.add_many(&elements)?;
+    pipeline.add(&webrtcbin)?;
+    gst::Element::link_many(&elements).context("Failed to link operator audio")?;
+    let webrtc_sink = webrtcbin
+        .request_pad_simple("sink_%u")
+        .context("No sink pad on webrtcbin")?;
+    capsfilter
+        .static_pad("src")
+        .context("No src pad on capsfilter")?
+        .link(&webrtc_sink)?;
+
+    // Operator's voice: depayloaded and decoded into the backchannel encoder
+    let pipeline_weak = pipeline.downgrade();
+    webrtcbin.connect_pad_added(move |_bin, src_pad| {
+        let Some(pipeline) = pipeline_weak.upgrade() else { return };
+        match add_decoder(&pipeline, talk_input, false) {
+            Ok(decodebin) => {
+                if let Some(sink) = decodebin.static_pad("sink") {
+                    let _ = src_pad.link(&sink);
+                }
+                info!("🎙️ Operator audio linked to the camera backchannel");
+            }
+            Err(e) => warn!("⚠️ Failed to link operator audio: {:#}", e),
+        }
+    });
+
+    Ok((
+        Operator {
+            webrtcbin,
+            candidates,
+            pay,
+        },
+        mixer,
+    ))
+}
+
+/// `decodebin` whose decoded audio pad goes to `target`; with `align`, the
+/// audio starts at the pipeline's current running time (clips pushed into a
+/// live pipeline)
+fn add_decoder<F>(pipeline: &gst::Pipeline, target: F, align: bool) -> Result<gst::Element>
+where
+    F: Fn(&gst::Pipeline) -> Option<gst::Pad> + Send + Sync + 'static,
+{
+    let decodebin = gst::ElementFactory::make("decodebin")
+        .build()
+        .context("Failed to create decodebin")?;
+    pipeline.add(&decodebin)?;
+
+    let pipeline_weak = pipeline.downgrade();
+    decodebin.connect_pad_added(move |_bin, src_pad| {
+        let caps = src_pad.current_caps().unwrap_or_else(|| src_pad.query_caps(None));
+        let is_audio = caps
+            .structure(0)
+            .is_some_and(|structure| structure.name().starts_with("audio/x-raw"));
+        let Some(pipeline) = pipeline_weak.upgrade() else { return };
+        let Some(sink) = is_audio.then(|| target(&pipeline)).flatten() else {
+            debug!("Ignoring decoded pad {}", src_pad.name());
+            return;
+        };
+        if align {
+            if let Some(running_time) = pipeline.current_running_time() {
+                src_pad.set_offset(running_time.nseconds() as i64);
+            }
+        }
+        if let Err(e) = src_pad.link(&sink) {
+            warn!("⚠️ Failed to link decoded audio: {:?}", e);
+        }
+    });
+    decodebin.sync_state_with_parent()?;
+    Ok(decodebin)
+}
+
+/// Free sink pad of the backchannel encoder (one voice at a time)
+fn talk_input(pipeline: &gst::Pipeline) -> Option<gst::Pad> {
+    pipeline
+        .by_name(TALK_INPUT)?
+        .static_pad("sink")
+        .filter(|pad| !pad.is_linked())
+}
+
+/// `audioconvert ! audioresample` into a new pad of the microphone mixer
+fn mixer_input(pipeline: &gst::Pipeline, mixer: &gst::Element) -> Option<gst::Pad> {
+    let convert = gst::ElementFactory::make("audioconvert").build().ok()?;
+    let resample = gst::ElementFactory::make("audioresample").build().ok()?;
+    pipeline.add_many([&convert, &resample]).ok()?;
+    convert.link(&resample).ok()?;
+    let mixer_sink = mixer.request_pad_simple("sink_%u")?;
+    resample.static_pad("src")?.link(&mixer_sink).ok()?;
+    resample.sync_state_with_parent().ok()?;
+    convert.sync_state_with_parent().ok()?;
+    convert.static_pad("sink")
+}
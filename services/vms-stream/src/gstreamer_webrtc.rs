@@ -1,249 +1,869 @@
 //! GStreamer WebRTC Pipeline - Ultra Low Latency
 //!
-//! Creates RTSP → webrtcbin pipeline for sub-100ms latency streaming
-//! Uses GLib main context for proper GStreamer async operation
+//! One source per camera (an RTSP pull, or a WHIP publisher such as OBS
+//! pushing into its own webrtcbin) feeds a `tee` of H.264/H.265 access
+//! units; every viewer gets its own `queue ! rtph264pay ! webrtcbin` branch,
+//! so viewers join and leave without renegotiating each other's connections.
+//! ICE is trickled both ways: browser candidates go into the viewer's
+//! webrtcbin, and the candidates it gathers are queued for the viewer to poll
+//! (or, for WHEP/WHIP, embedded in the answer once gathering completes).
+//!
+//! Setup is event driven: the session is ready once the source exposed its
+//! video pad and the first keyframe reached the tee, and SDP negotiation
+//! awaits webrtcbin's promises. The last GOP is cached so a new viewer is
+//! primed with it and shows a frame right away instead of waiting for the
+//! camera's next IDR.
+//!
+//! With transcoding enabled, each rung of the quality ladder is a transcoder
+//! hanging off the camera tee with a tee (and GOP cache) of its own, started
+//! for its first viewer and shared by all of them. Viewers start on the best
+//! layer and move between layers on REMB/TWCC bandwidth estimates; a switch
+//! relinks the viewer branch on the target layer's next keyframe.
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use gstreamer as gst;
 use gstreamer::prelude::*;
+use gstreamer_rtp as gst_rtp;
+use gstreamer_rtp::prelude::RTPHeaderExtensionExt;
 use gstreamer_sdp as gst_sdp;
 use gstreamer_webrtc as gst_webrtc;
-use std::sync::{Arc, Mutex};
-use tracing::{error, info};
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+use vms_common::media_profile::VideoCodec;
+
+use crate::candidates::CandidateQueue;
+use crate::gop::{GopCache, GOP_CACHE_MAX_FRAMES};
+use crate::ice::IceConfig;
+use crate::ladder::{parse_remb, Layer, LayerInfo, LayerSwitcher, RateMeter};
+use crate::readiness::{Readiness, ReadinessError};
+use crate::transcode::{self, TranscodeConfig};
+
+/// Frames queued per viewer before old ones are dropped (slow viewers must
+/// not stall the tee for everyone else); room for a full cached GOP
+const VIEWER_QUEUE_BUFFERS: u32 = GOP_CACHE_MAX_FRAMES as u32 + 100;
+
+/// Maximum wait for each webrtcbin promise during negotiation
+const NEGOTIATION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum wait for ICE gathering when candidates go in the answer
+const GATHERING_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Transport-wide congestion control RTP header extension
+const TWCC_URI: &str = "http://www.ietf.org/id/draft-holmer-rmcat-transport-wide-cc-extensions-01";
+const TWCC_EXTENSION_ID: u32 = 1;
+
+/// RTCP payload-specific feedback carrying REMB (application layer FB)
+const RTCP_PSFB: u32 = 206;
+const PSFB_AFB: u32 = 15;
+
+/// Where a camera's video comes from
+pub enum Source {
+    /// IP camera pulled over RTSP
+    Rtsp {
+        url: String,
+        username: String,
+        password: String,
+    },
+    /// WebRTC publisher pushing H.264 over WHIP
+    Whip,
+}
+
+/// The WHIP publisher's receiving webrtcbin
+struct Publisher {
+    id: Uuid,
+    webrtcbin: gst::Element,
+    candidates: Arc<CandidateQueue>,
+}
+
+/// A viewer branch: `queue ! rtph264pay ! capsfilter ! webrtcbin`
+struct ViewerPeer {
+    elements: Vec<gst::Element>,
+    webrtcbin: gst::Element,
+    candidates: Arc<CandidateQueue>,
+    queue_sink: gst::Pad,
+    layer: Layer,
+    /// Layer being switched to, linked on its next keyframe
+    target: Option<Layer>,
+    /// Whether the viewer follows bandwidth estimates (more than one layer)
+    adaptive: bool,
+    switcher: LayerSwitcher,
+}
+
+/// Tee state shared with its streaming thread
+struct Fanout {
+    gop: GopCache<gst::Buffer>,
+    /// Branches waiting for the next buffer: they get the cached GOP first,
+    /// then are linked to the tee
+    pending: HashMap<Uuid, gst::Pad>,
+    /// Viewer branches moving here from another layer, linked on the next
+    /// keyframe
+    switching: HashMap<Uuid, Switch>,
+    /// Linked branches by their tee request pad
+    attached: HashMap<Uuid, gst::Pad>,
+    rate: RateMeter,
+}
+
+/// A viewer branch leaving `from` for this tee
+struct Switch {
+    queue_sink: gst::Pad,
+    from: Output,
+    /// Runs off the streaming thread once the branch is linked here
+    on_switched: Box<dyn FnOnce() + Send>,
+}
+
+/// A tee and its GOP cache: the camera's own stream or a ladder rendition
+#[derive(Clone)]
+struct Output {
+    tee: gst::Element,
+    fanout: Arc<Mutex<Fanout>>,
+}
+
+impl Output {
+    /// Watch every access unit entering `tee`: cache it, prime joining
+    /// branches and complete switches on keyframes
+    fn new(tee: gst::Element, readiness: Option<Arc<Readiness>>) -> Result<Self> {
+        let fanout = Arc::new(Mutex::new(Fanout {
+            gop: GopCache::new(GOP_CACHE_MAX_FRAMES),
+            pending: HashMap::new(),
+            switching: HashMap::new(),
+            attached: HashMap::new(),
+            rate: RateMeter::new(),
+        }));
+
+        let tee_sink = tee.static_pad("sink").context("No sink pad on tee")?;
+        let tee_weak = tee.downgrade();
+        let fanout_probe = fanout.clone();
+        tee_sink.add_probe(gst::PadProbeType::BUFFER, move |pad, info| {
+            let (Some(gst::PadProbeData::Buffer(buffer)), Some(tee)) = (&info.data, tee_weak.upgrade()) else {
+                return gst::PadProbeReturn::Ok;
+            };
+            let keyframe = !buffer.flags().contains(gst::BufferFlags::DELTA_UNIT);
+
+            let mut fanout = fanout_probe.lock().unwrap();
+            fanout.rate.add(buffer.size(), Instant::now());
+            if !fanout.pending.is_empty() {
+                attach_pending(&tee, pad, &mut fanout, keyframe);
+            }
+            let switches: Vec<_> = if keyframe {
+                fanout.switching.drain().collect()
+            } else {
+                Vec::new()
+            };
+            fanout.gop.push(buffer.clone(), keyframe);
+            drop(fanout);
+
+            for (peer_id, switch) in switches {
+                complete_switch(&tee, pad, &fanout_probe, peer_id, switch);
+            }
+            if keyframe {
+                if let Some(ready) = &readiness {
+                    ready.mark_keyframe();
+                }
+            }
+            gst::PadProbeReturn::Ok
+        });
 
-/// WebRTC stream session using GStreamer
+        Ok(Self { tee, fanout })
+    }
+}
+
+/// A ladder rung being encoded for at least one viewer
+struct Transcoder {
+    elements: Vec<gst::Element>,
+    /// Its branch on the camera tee
+    feed: Uuid,
+    output: Output,
+    /// Viewers on this layer or switching to it
+    users: usize,
+}
+
+/// WebRTC stream session using GStreamer (one per camera, shared by its viewers)
 pub struct GstWebRTCSession {
     pub pipeline: gst::Pipeline,
-    pub webrtcbin: gst::Element,
     pub camera_id: String,
+    /// The camera's own stream, ahead of any transcoder
+    source: Output,
+    /// Camera codec, known once the source's video pad shows up
+    codec: Arc<OnceLock<VideoCodec>>,
+    ice: IceConfig,
+    readiness: Arc<Readiness>,
+    peers: Mutex<HashMap<Uuid, ViewerPeer>>,
+    publisher: Option<Publisher>,
+    /// Quality ladder; without it the camera's H.264 goes out untouched
+    transcode: Option<Arc<TranscodeConfig>>,
+    /// Running transcoders by ladder index
+    transcoders: Mutex<HashMap<usize, Transcoder>>,
+    /// Peers (or the publisher) whose connection failed or closed remotely
+    departures: mpsc::UnboundedSender<Uuid>,
+    /// For GStreamer callbacks (bandwidth estimates, finished switches)
+    me: Weak<Self>,
 }
 
 impl GstWebRTCSession {
     /// Create a new GStreamer WebRTC session for a camera
     pub fn new(
         camera_id: String,
-        rtsp_url: &str,
-        username: &str,
-        password: &str,
-    ) -> Result<Self> {
+        source: Source,
+        ice: IceConfig,
+        transcode: Option<Arc<TranscodeConfig>>,
+        departures: mpsc::UnboundedSender<Uuid>,
+    ) -> Result<Arc<Self>> {
         info!("🚀 Creating GStreamer WebRTC session for camera: {}", camera_id);
-        
-        info!("📹 RTSP URL: {} (user: {})", rtsp_url, username);
-        
+
         // Create pipeline
         let pipeline = gst::Pipeline::new();
-        
-        // RTSP Source with authentication
-        info!("🎬 Creating RTSP pipeline with authentication");
-        let rtspsrc = gst::ElementFactory::make("rtspsrc")
-            .name("source")
-            .property("location", rtsp_url)
-            .property("user-id", username)
-            .property("user-pw", password)
-            .property("latency", 0u32)
-            .property("drop-on-latency", true)
-            .build()
-            .context("Failed to create rtspsrc")?;
-        
-        // RTP Depayloader for H.264
-        let rtph264depay = gst::ElementFactory::make("rtph264depay")
-            .name("depay")
+        let readiness = Arc::new(Readiness::new());
+        let codec = Arc::new(OnceLock::new());
+
+        // Fan-out to viewers and transcoders; keeps flowing while nobody is linked
+        let tee = gst::ElementFactory::make("tee")
+            .name("fanout")
+            .property("allow-not-linked", true)
             .build()
-            .context("Failed to create rtph264depay")?;
-            
-        // H.264 Parser
-        let h264parse = gst::ElementFactory::make("h264parse")
-            .name("parse")
+            .context("Failed to create tee")?;
+        pipeline.add(&tee)?;
+
+        // The source's video pad is depayloaded into the tee once it shows up
+        let sink = SourceSink {
+            tee: tee.clone(),
+            codec: codec.clone(),
+            readiness: readiness.clone(),
+            transcoding: transcode.is_some(),
+        };
+        let publisher = match source {
+            Source::Rtsp { url, username, password } => {
+                info!("📹 RTSP URL: {} (user: {})", url, username);
+                add_rtsp_source(&pipeline, sink, &url, &username, &password)?;
+                None
+            }
+            Source::Whip => {
+                info!("📥 Waiting for WHIP publisher on camera: {}", camera_id);
+                Some(add_whip_source(&pipeline, sink, &ice, &departures)?)
+            }
+        };
+
+        // Every access unit entering the tee: cache it and prime new viewers
+        let source = Output::new(tee, Some(readiness.clone()))?;
+
+        // Errors (auth, unreachable camera) fail pending setups right away
+        let ready = readiness.clone();
+        let error_camera = camera_id.clone();
+        pipeline
+            .bus()
+            .context("Pipeline has no bus")?
+            .set_sync_handler(move |_, msg| {
+                if let gst::MessageView::Error(err) = msg.view() {
+                    error!("❌ Pipeline error on camera {}: {} ({:?})", error_camera, err.error(), err.debug());
+                    ready.fail(err.error().to_string());
+                }
+                gst::BusSyncReply::Drop
+            });
+
+        pipeline.set_latency(gst::ClockTime::from_mseconds(0));
+
+        info!("✅ GStreamer WebRTC pipeline created for camera: {}", camera_id);
+
+        Ok(Arc::new_cyclic(|me| Self {
+            pipeline,
+            camera_id,
+            source,
+            codec,
+            ice,
+            readiness,
+            peers: Mutex::new(HashMap::new()),
+            publisher,
+            transcode,
+            transcoders: Mutex::new(HashMap::new()),
+            departures,
+            me: me.clone(),
+        }))
+    }
+
+    /// WHIP resource id of the publisher, for sessions fed over WHIP
+    pub fn publisher_id(&self) -> Option<Uuid> {
+        self.publisher.as_ref().map(|publisher| publisher.id)
+    }
+
+    /// Handle the WHIP publisher's SDP offer; the answer carries our
+    /// candidates
+    pub async fn publish(&self, offer_sdp: String) -> Result<String> {
+        let publisher = self
+            .publisher
+            .as_ref()
+            .ok_or_else(|| anyhow!("Camera {} is not fed over WHIP", self.camera_id))?;
+
+        info!(
+            "📥 Processing WHIP offer for camera: {} ({} bytes)",
+            self.camera_id,
+            offer_sdp.len()
+        );
+        negotiate(&publisher.webrtcbin, offer_sdp, Some(&publisher.candidates)).await
+    }
+
+    /// Wait until the camera stream is flowing (video pad linked and first
+    /// keyframe cached)
+    pub async fn wait_ready(&self, timeout: Duration) -> Result<(), ReadinessError> {
+        self.readiness.wait(timeout).await
+    }
+
+    /// Add a viewer branch (`queue ! rtph264pay ! webrtcbin`) on the best
+    /// layer; it is linked to that layer's tee on the next buffer, right
+    /// after the cached GOP
+    pub fn add_peer(&self, peer_id: Uuid) -> Result<()> {
+        info!("➕ Adding viewer {} to camera: {}", peer_id, self.camera_id);
+
+        let layers = self.available_layers();
+        let layer = *layers
+            .first()
+            .ok_or_else(|| anyhow!("Camera {} has no layer browsers can play", self.camera_id))?;
+        let adaptive = layers.len() > 1;
+        let output = self.acquire(layer)?;
+
+        let queue = gst::ElementFactory::make("queue")
+            .name(format!("queue-{}", peer_id))
+            .property_from_str("leaky", "downstream")
+            .property("max-size-buffers", VIEWER_QUEUE_BUFFERS)
+            .property("max-size-bytes", 0u32)
+            .property("max-size-time", 0u64)
             .build()
-            .context("Failed to create h264parse")?;
-        
-        // RTP Payloader for WebRTC
-        let rtph264pay = gst::ElementFactory::make("rtph264pay")
-            .name("pay")
+            .context("Failed to create queue")?;
+
+        // RTP Payloader for WebRTC (SPS/PPS on every IDR)
+        let pay = gst::ElementFactory::make("rtph264pay")
+            .name(format!("pay-{}", peer_id))
             .property("config-interval", -1i32)
             .property("pt", 96u32)
             .build()
             .context("Failed to create rtph264pay")?;
-        
+
         // Caps filter for RTP
         let capsfilter = gst::ElementFactory::make("capsfilter")
-            .name("capsfilter")
+            .name(format!("rtpcaps-{}", peer_id))
             .build()
             .context("Failed to create capsfilter")?;
-        
-        let caps = gst::Caps::builder("application/x-rtp")
-            .field("media", "video")
-            .field("clock-rate", 90000i32)
-            .field("encoding-name", "H264")
-            .field("payload", 96i32)
-            .build();
-        capsfilter.set_property("caps", &caps);
-        
-        // WebRTC bin
+        capsfilter.set_property("caps", &h264_rtp_caps());
+
         let webrtcbin = gst::ElementFactory::make("webrtcbin")
-            .name("webrtc")
+            .name(format!("webrtc-{}", peer_id))
             .property_from_str("bundle-policy", "max-bundle")
-            .property_from_str("stun-server", "stun://stun.l.google.com:19302")
             .build()
             .context("Failed to create webrtcbin")?;
-        
-        // Add elements to pipeline
-        pipeline.add_many([&rtspsrc, &rtph264depay, &h264parse, &rtph264pay, &capsfilter, &webrtcbin])?;
-        
-        // Link static elements (depay -> parse -> pay -> capsfilter)
-        gst::Element::link_many([&rtph264depay, &h264parse, &rtph264pay, &capsfilter])
-            .context("Failed to link video elements")?;
-        
-        // Link capsfilter to webrtcbin
-        let caps_src = capsfilter.static_pad("src").expect("No src pad on capsfilter");
-        let webrtc_sink = webrtcbin.request_pad_simple("sink_%u").expect("No sink pad on webrtcbin");
-        caps_src.link(&webrtc_sink)?;
-        
+
+        let candidates = configure_webrtcbin(&webrtcbin, peer_id, &self.ice, &self.departures);
+
+        // TWCC estimates when rtpgccbwe is installed; REMB is hooked up once
+        // the RTP session exists (after negotiation)
+        let twcc = adaptive && self.watch_twcc(peer_id, &webrtcbin, &pay);
+        let preferences = viewer_codec_preferences(adaptive, twcc);
+
         // Set the transceiver direction to SENDONLY and configure H.264 codec
-        webrtcbin.connect("on-new-transceiver", false, |values| {
+        webrtcbin.connect("on-new-transceiver", false, move |values| {
             if let Some(transceiver) = values.get(1).and_then(|v| v.get::<gst_webrtc::WebRTCRTPTransceiver>().ok()) {
                 transceiver.set_property("direction", gst_webrtc::WebRTCRTPTransceiverDirection::Sendonly);
-                
+
                 // Force H.264 codec by setting codec preferences
-                let h264_caps = gst::Caps::builder("application/x-rtp")
-                    .field("media", "video")
-                    .field("encoding-name", "H264")
-                    .field("clock-rate", 90000i32)
-                    .field("payload", 96i32)
-                    .build();
-                transceiver.set_property("codec-preferences", &h264_caps);
-                
+                transceiver.set_property("codec-preferences", &preferences);
+
                 info!("📡 Transceiver configured: SENDONLY + H.264 codec");
             }
             None
         });
-        
-        // Connect RTSP dynamic pads to depayloader
-        let depay_clone = rtph264depay.clone();
-        rtspsrc.connect_pad_added(move |_src, src_pad| {
-            let pad_name = src_pad.name().to_string();
-            info!("🔗 RTSP pad added: {}", pad_name);
-            
-            // Only link video RTP pads
-            if pad_name.contains("recv_rtp_src") {
-                let sink_pad = depay_clone.static_pad("sink").expect("No sink pad");
-                if !sink_pad.is_linked() {
-                    match src_pad.link(&sink_pad) {
-                        Ok(_) => info!("✅ RTSP video stream linked!"),
-                        Err(e) => error!("❌ Failed to link RTSP: {}", e),
-                    }
+
+        let elements = vec![queue.clone(), pay.clone(), capsfilter.clone(), webrtcbin.clone()];
+        if let Err(e) = self.pipeline.add_many(&elements) {
+            self.release(layer);
+            return Err(e.into());
+        }
+
+        let linked = (|| -> Result<gst::Pad> {
+            gst::Element::link_many([&queue, &pay, &capsfilter])?;
+            let webrtc_sink = webrtcbin
+                .request_pad_simple("sink_%u")
+                .context("No sink pad on webrtcbin")?;
+            capsfilter
+                .static_pad("src")
+                .context("No src pad on capsfilter")?
+                .link(&webrtc_sink)?;
+
+            // Bring the branch up (downstream first) before data reaches it
+            for element in elements.iter().rev() {
+                element.sync_state_with_parent()?;
+            }
+            queue.static_pad("sink").context("No sink pad on queue")
+        })();
+
+        match linked {
+            Ok(queue_sink) => {
+                if adaptive {
+                    info!(
+                        "📶 Viewer {} starts on {} ({} layers, {})",
+                        peer_id,
+                        self.layer_name(layer),
+                        layers.len(),
+                        if twcc { "TWCC + REMB" } else { "REMB" }
+                    );
                 }
+                self.peers.lock().unwrap().insert(
+                    peer_id,
+                    ViewerPeer {
+                        elements,
+                        webrtcbin,
+                        candidates,
+                        queue_sink: queue_sink.clone(),
+                        layer,
+                        target: None,
+                        adaptive,
+                        switcher: LayerSwitcher::new(0),
+                    },
+                );
+                output.fanout.lock().unwrap().pending.insert(peer_id, queue_sink);
+                Ok(())
             }
-        });
-        
-        pipeline.set_latency(gst::ClockTime::from_mseconds(0));
-        
-        info!("✅ GStreamer WebRTC pipeline created for camera: {}", camera_id);
-        
-        Ok(Self {
-            pipeline,
-            webrtcbin,
-            camera_id,
+            Err(e) => {
+                teardown(&self.pipeline, elements);
+                self.release(layer);
+                Err(e)
+            }
+        }
+    }
+
+    /// Detach a viewer branch; returns how many viewers remain
+    pub fn remove_peer(&self, peer_id: Uuid) -> usize {
+        let (peer, remaining) = {
+            let mut peers = self.peers.lock().unwrap();
+            let peer = peers.remove(&peer_id);
+            (peer, peers.len())
+        };
+
+        let Some(peer) = peer else {
+            return remaining;
+        };
+        info!("➖ Removing viewer {} from camera: {}", peer_id, self.camera_id);
+
+        // Its layer, plus the one it was switching to
+        let layers: Vec<Layer> = std::iter::once(peer.layer).chain(peer.target).collect();
+        let tee_pads = layers
+            .iter()
+            .filter_map(|layer| self.output(*layer))
+            .filter_map(|output| take_branch(&output, peer_id))
+            .collect();
+        unlink_branch(&self.pipeline, tee_pads, peer.elements);
+
+        for layer in layers {
+            self.release(layer);
+        }
+        remaining
+    }
+
+    /// Layers viewers can be on, best first: the camera's own stream (if
+    /// browsers can decode it) and the ladder
+    fn available_layers(&self) -> Vec<Layer> {
+        let passthrough = matches!(self.codec.get(), None | Some(VideoCodec::H264));
+        let renditions = self.transcode.as_ref().map_or(0, |transcode| transcode.ladder.len());
+        passthrough
+            .then_some(Layer::Source)
+            .into_iter()
+            .chain((0..renditions).map(Layer::Rendition))
+            .collect()
+    }
+
+    /// Target bitrate of a rendition, or the measured one of the camera stream
+    fn layer_bitrate(&self, layer: Layer) -> u64 {
+        match layer {
+            Layer::Source => self.source.fanout.lock().unwrap().rate.bitrate(),
+            Layer::Rendition(index) => self
+                .transcode
+                .as_ref()
+                .and_then(|transcode| transcode.ladder.get(index))
+                .map_or(0, |rendition| rendition.bitrate.into()),
+        }
+    }
+
+    fn layer_name(&self, layer: Layer) -> String {
+        match layer {
+            Layer::Source => "source".to_string(),
+            Layer::Rendition(index) => self
+                .transcode
+                .as_ref()
+                .and_then(|transcode| transcode.ladder.get(index))
+                .map_or_else(|| format!("rendition {}", index), |rendition| rendition.name.clone()),
+        }
+    }
+
+    /// The tee a layer is served from, if it is running
+    fn output(&self, layer: Layer) -> Option<Output> {
+        match layer {
+            Layer::Source => Some(self.source.clone()),
+            Layer::Rendition(index) => self
+                .transcoders
+                .lock()
+                .unwrap()
+                .get(&index)
+                .map(|transcoder| transcoder.output.clone()),
+        }
+    }
+
+    /// Take a reference on a layer, starting its transcoder if needed
+    fn acquire(&self, layer: Layer) -> Result<Output> {
+        let Layer::Rendition(index) = layer else {
+            return Ok(self.source.clone());
+        };
+
+        let mut transcoders = self.transcoders.lock().unwrap();
+        if let Some(transcoder) = transcoders.get_mut(&index) {
+            transcoder.users += 1;
+            return Ok(transcoder.output.clone());
+        }
+
+        let transcoder = self.start_transcoder(index)?;
+        let output = transcoder.output.clone();
+        transcoders.insert(index, transcoder);
+        Ok(output)
+    }
+
+    /// Drop a reference on a layer; a transcoder stops with its last viewer
+    fn release(&self, layer: Layer) {
+        let Layer::Rendition(index) = layer else {
+            return;
+        };
+
+        let stopped = {
+            let mut transcoders = self.transcoders.lock().unwrap();
+            let Some(transcoder) = transcoders.get_mut(&index) else {
+                return;
+            };
+            transcoder.users = transcoder.users.saturating_sub(1);
+            if transcoder.users > 0 {
+                return;
+            }
+            transcoders.remove(&index)
+        };
+
+        if let Some(transcoder) = stopped {
+            info!(
+                "🛑 Transcoder {} stopped on camera {} (no viewers)",
+                self.layer_name(layer),
+                self.camera_id
+            );
+            let tee_pads = take_branch(&self.source, transcoder.feed).into_iter().collect();
+            unlink_branch(&self.pipeline, tee_pads, transcoder.elements);
+        }
+    }
+
+    fn start_transcoder(&self, index: usize) -> Result<Transcoder> {
+        let transcode = self.transcode.as_ref().context("Transcoding is disabled")?;
+        let rendition = transcode
+            .ladder
+            .get(index)
+            .with_context(|| format!("No rendition {} in the ladder", index))?;
+        let codec = *self.codec.get().context("Camera codec not known yet")?;
+
+        let feed = Uuid::new_v4();
+        let branch = transcode::build(
+            &self.pipeline,
+            &format!("rendition{}-{}", index, feed),
+            codec,
+            rendition,
+            transcode.encoder,
+        )?;
+        let output = Output::new(branch.tee, None)?;
+
+        // Primed with the cached GOP like a viewer, so it encodes right away
+        self.source.fanout.lock().unwrap().pending.insert(feed, branch.sink);
+        info!("🎚️ Transcoding camera {} to {}", self.camera_id, rendition.name);
+
+        Ok(Transcoder {
+            elements: branch.elements,
+            feed,
+            output,
+            users: 1,
         })
     }
-    
-    /// Handle SDP offer from browser - runs GLib main context to process GStreamer events
-    pub fn handle_offer(&self, offer_sdp: String) -> Result<String> {
-        info!("📥 Processing SDP offer for camera: {} ({} bytes)", self.camera_id, offer_sdp.len());
-        
-        // Parse SDP offer
-        let sdp = gst_sdp::SDPMessage::parse_buffer(offer_sdp.as_bytes())
-            .map_err(|e| anyhow::anyhow!("Failed to parse SDP: {:?}", e))?;
-        
-        let offer = gst_webrtc::WebRTCSessionDescription::new(
-            gst_webrtc::WebRTCSDPType::Offer,
-            sdp,
+
+    /// Bandwidth estimate for a viewer (on a GStreamer thread)
+    fn on_estimate(&self, peer_id: Uuid, estimate: u64) {
+        let layers = self.available_layers();
+        let bitrates: Vec<u64> = layers.iter().map(|layer| self.layer_bitrate(*layer)).collect();
+
+        let target = {
+            let mut peers = self.peers.lock().unwrap();
+            let Some(peer) = peers.get_mut(&peer_id) else {
+                return;
+            };
+            // One switch at a time
+            if peer.target.is_some() {
+                return;
+            }
+            let Some(next) = peer.switcher.observe(estimate, &bitrates, Instant::now()) else {
+                return;
+            };
+            layers[next]
+        };
+
+        debug!(
+            "📶 Viewer {} estimate {} kbps: switching to {}",
+            peer_id,
+            estimate / 1000,
+            self.layer_name(target)
         );
-        
-        // Set remote description
-        info!("📥 Setting remote description");
-        self.webrtcbin.emit_by_name::<()>(
-            "set-remote-description",
-            &[&offer, &None::<gst::Promise>],
+        // Transcoders are built off the RTCP thread
+        let me = self.me.clone();
+        self.pipeline.call_async(move |_| {
+            let Some(session) = me.upgrade() else {
+                return;
+            };
+            if let Err(e) = session.switch_layer(peer_id, target) {
+                warn!("⚠️ Viewer {} could not switch layer: {:#}", peer_id, e);
+                session.reset_switcher(peer_id);
+            }
+        });
+    }
+
+    /// Move a viewer to another layer on that layer's next keyframe
+    fn switch_layer(&self, peer_id: Uuid, target: Layer) -> Result<()> {
+        let output = self.acquire(target)?;
+
+        let switch = {
+            let mut peers = self.peers.lock().unwrap();
+            match peers.get_mut(&peer_id) {
+                Some(peer) if peer.target.is_none() && peer.layer != target => {
+                    self.output(peer.layer).map(|from| {
+                        peer.target = Some(target);
+                        (peer.queue_sink.clone(), from)
+                    })
+                }
+                _ => None,
+            }
+        };
+        let Some((queue_sink, from)) = switch else {
+            // Viewer left (or is already there) meanwhile
+            self.release(target);
+            return Ok(());
+        };
+
+        info!(
+            "🔀 Viewer {} switching to {} on camera {}",
+            peer_id,
+            self.layer_name(target),
+            self.camera_id
         );
-        
-        // Use shared state for answer
-        let answer_result: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
-        let answer_clone = answer_result.clone();
-        let webrtc = self.webrtcbin.clone();
-        
-        // Create answer with callback
-        info!("📤 Creating SDP answer");
-        let promise = gst::Promise::with_change_func(move |reply| {
-            match reply {
-                Ok(Some(reply)) => {
-                    if let Ok(answer_value) = reply.value("answer") {
-                        if let Ok(answer) = answer_value.get::<gst_webrtc::WebRTCSessionDescription>() {
-                            // Set local description
-                            webrtc.emit_by_name::<()>(
-                                "set-local-description",
-                                &[&answer, &None::<gst::Promise>],
-                            );
-                            
-                            let sdp_text = answer.sdp().to_string();
-                            info!("📤 Answer created ({} bytes)", sdp_text.len());
-                            info!("📤 SDP Answer content:\n{}", sdp_text);
-                            
-                            if let Ok(mut guard) = answer_clone.lock() {
-                                *guard = Some(sdp_text);
-                            }
-                        }
+        let me = self.me.clone();
+        output.fanout.lock().unwrap().switching.insert(
+            peer_id,
+            Switch {
+                queue_sink,
+                from,
+                on_switched: Box::new(move || {
+                    if let Some(session) = me.upgrade() {
+                        session.switched(peer_id, target);
                     }
+                }),
+            },
+        );
+        Ok(())
+    }
+
+    /// The viewer branch is on `target` now: let go of the previous layer
+    fn switched(&self, peer_id: Uuid, target: Layer) {
+        let previous = {
+            let mut peers = self.peers.lock().unwrap();
+            let Some(peer) = peers.get_mut(&peer_id) else {
+                return;
+            };
+            peer.target = None;
+            std::mem::replace(&mut peer.layer, target)
+        };
+        debug!("🔀 Viewer {} now on {}", peer_id, self.layer_name(target));
+        self.release(previous);
+    }
+
+    /// Realign a viewer's switcher with the layer it is actually on
+    fn reset_switcher(&self, peer_id: Uuid) {
+        let layers = self.available_layers();
+        if let Some(peer) = self.peers.lock().unwrap().get_mut(&peer_id) {
+            let current = layers.iter().position(|layer| *layer == peer.layer).unwrap_or(0);
+            peer.switcher = LayerSwitcher::new(current);
+        }
+    }
+
+    /// Bandwidth estimates from TWCC feedback (needs `rtpgccbwe`, from
+    /// gst-plugins-rs); the payloader stamps the transport-wide sequence
+    fn watch_twcc(&self, peer_id: Uuid, webrtcbin: &gst::Element, pay: &gst::Element) -> bool {
+        if gst::ElementFactory::find("rtpgccbwe").is_none() {
+            return false;
+        }
+        let Some(extension) = gst_rtp::RTPHeaderExtension::create_from_uri(TWCC_URI) else {
+            return false;
+        };
+        extension.set_id(TWCC_EXTENSION_ID);
+        pay.emit_by_name::<()>("add-extension", &[&extension]);
+
+        let me = self.me.clone();
+        webrtcbin.connect("request-aux-sender", false, move |_| {
+            let bwe = gst::ElementFactory::make("rtpgccbwe").build().ok()?;
+            let me = me.clone();
+            bwe.connect_notify(Some("estimated-bitrate"), move |bwe, _| {
+                let estimate = bwe.property::<u32>("estimated-bitrate");
+                if let Some(session) = me.upgrade() {
+                    session.on_estimate(peer_id, estimate.into());
                 }
-                Ok(None) => error!("create-answer returned None"),
-                Err(e) => error!("create-answer failed: {:?}", e),
-            }
+            });
+            Some(bwe.to_value())
         });
-        
-        self.webrtcbin.emit_by_name::<()>("create-answer", &[&None::<gst::Structure>, &promise]);
-        
-        // Pump GLib main context to process events
-        let main_context = glib::MainContext::default();
-        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
-        
-        loop {
-            // Check if we have an answer
-            if let Ok(guard) = answer_result.lock() {
-                if guard.is_some() {
-                    break;
-                }
+        true
+    }
+
+    /// Bandwidth estimates from the browser's REMB feedback, read on the
+    /// viewer's RTP session (which exists once negotiated)
+    fn watch_remb(&self, peer_id: Uuid, webrtcbin: &gst::Element) {
+        let Some(rtpbin) = webrtcbin
+            .downcast_ref::<gst::Bin>()
+            .and_then(|bin| bin.by_name("rtpbin"))
+        else {
+            debug!("No rtpbin in webrtcbin for viewer {}", peer_id);
+            return;
+        };
+        // max-bundle: everything on session 0
+        let Some(session) = rtpbin.emit_by_name::<Option<glib::Object>>("get-internal-session", &[&0u32]) else {
+            debug!("No RTP session yet for viewer {}", peer_id);
+            return;
+        };
+
+        let me = self.me.clone();
+        session.connect("on-feedback-rtcp", false, move |values| {
+            let kind = values.get(1).and_then(|v| v.get::<u32>().ok());
+            let format = values.get(2).and_then(|v| v.get::<u32>().ok());
+            if kind != Some(RTCP_PSFB) || format != Some(PSFB_AFB) {
+                return None;
             }
-            
-            // Check timeout
-            if std::time::Instant::now() > deadline {
-                return Err(anyhow::anyhow!("Timeout waiting for SDP answer"));
+            let fci = values.get(5).and_then(|v| v.get::<Option<gst::Buffer>>().ok()).flatten()?;
+            let map = fci.map_readable().ok()?;
+            let estimate = parse_remb(map.as_slice())?;
+            if let Some(session) = me.upgrade() {
+                session.on_estimate(peer_id, estimate);
             }
-            
-            // Pump main context to process GStreamer events
-            while main_context.iteration(false) {}
-            
-            // Small sleep to prevent busy-waiting
-            std::thread::sleep(std::time::Duration::from_millis(10));
-        }
-        
-        // Extract answer
-        let answer = answer_result.lock()
-            .map_err(|_| anyhow::anyhow!("Lock poisoned"))?
-            .take()
-            .ok_or_else(|| anyhow::anyhow!("No answer generated"))?;
-        
-        info!("✅ SDP answer ready for camera: {}", self.camera_id);
-        
+            None
+        });
+    }
+
+    /// Layers of this camera with their viewers
+    pub fn layers(&self) -> Vec<LayerInfo> {
+        let mut viewers: HashMap<Layer, usize> = HashMap::new();
+        for peer in self.peers.lock().unwrap().values() {
+            *viewers.entry(peer.layer).or_default() += 1;
+        }
+        let running: Vec<usize> = self.transcoders.lock().unwrap().keys().copied().collect();
+
+        self.available_layers()
+            .into_iter()
+            .map(|layer| {
+                let rendition = match layer {
+                    Layer::Source => None,
+                    Layer::Rendition(index) => self.transcode.as_ref().and_then(|t| t.ladder.get(index)),
+                };
+                LayerInfo {
+                    name: self.layer_name(layer),
+                    width: rendition.map(|r| r.width),
+                    height: rendition.map(|r| r.height),
+                    bitrate: self.layer_bitrate(layer),
+                    transcoded: rendition.is_some(),
+                    active: match layer {
+                        Layer::Source => true,
+                        Layer::Rendition(index) => running.contains(&index),
+                    },
+                    viewers: viewers.get(&layer).copied().unwrap_or(0),
+                }
+            })
+            .collect()
+    }
+
+    /// Transcoders currently running
+    pub fn transcoder_count(&self) -> usize {
+        self.transcoders.lock().unwrap().len()
+    }
+
+    /// A viewer's webrtcbin, or the publisher's
+    fn webrtcbin(&self, peer_id: Uuid) -> Result<gst::Element> {
+        if let Some(publisher) = self.publisher.as_ref().filter(|publisher| publisher.id == peer_id) {
+            return Ok(publisher.webrtcbin.clone());
+        }
+        self.peers
+            .lock()
+            .unwrap()
+            .get(&peer_id)
+            .map(|peer| peer.webrtcbin.clone())
+            .ok_or_else(|| anyhow!("Viewer {} not found on camera {}", peer_id, self.camera_id))
+    }
+
+    /// Handle a viewer's SDP offer on its own webrtcbin; with `trickle` the
+    /// answer goes out right away and our candidates are polled separately,
+    /// otherwise they are gathered into the answer (WHEP)
+    pub async fn handle_offer(&self, peer_id: Uuid, offer_sdp: String, trickle: bool) -> Result<String> {
+        let webrtcbin = self.webrtcbin(peer_id)?;
+        let candidates = if trickle { None } else { self.candidates(peer_id) };
+
+        info!(
+            "📥 Processing SDP offer for camera: {} viewer: {} ({} bytes)",
+            self.camera_id,
+            peer_id,
+            offer_sdp.len()
+        );
+        let answer = negotiate(&webrtcbin, offer_sdp, candidates.as_deref()).await?;
+
+        let adaptive = self
+            .peers
+            .lock()
+            .unwrap()
+            .get(&peer_id)
+            .is_some_and(|peer| peer.adaptive);
+        if adaptive {
+            self.watch_remb(peer_id, &webrtcbin);
+        }
+
+        info!("✅ SDP answer ready for camera: {} viewer: {}", self.camera_id, peer_id);
+
         Ok(answer)
     }
-    
+
+    /// Add a remote ICE candidate to a viewer's (or the publisher's) webrtcbin
+    pub fn add_ice_candidate(&self, peer_id: Uuid, sdp_m_line_index: u32, candidate: &str) -> Result<()> {
+        let webrtcbin = self.webrtcbin(peer_id)?;
+
+        // An empty candidate only marks end-of-candidates
+        if candidate.is_empty() {
+            return Ok(());
+        }
+        webrtcbin.emit_by_name::<()>("add-ice-candidate", &[&sdp_m_line_index, &candidate]);
+        Ok(())
+    }
+
+    /// Server-side candidates gathered for a viewer
+    pub fn candidates(&self, peer_id: Uuid) -> Option<Arc<CandidateQueue>> {
+        self.peers
+            .lock()
+            .unwrap()
+            .get(&peer_id)
+            .map(|peer| peer.candidates.clone())
+    }
+
+    /// Frames currently cached for new viewers
+    pub fn cached_frames(&self) -> usize {
+        self.source.fanout.lock().unwrap().gop.len()
+    }
+
     /// Start the pipeline
     pub fn start(&self) -> Result<()> {
         info!("▶️ Starting GStreamer pipeline for camera: {}", self.camera_id);
         self.pipeline.set_state(gst::State::Playing)?;
         Ok(())
     }
-    
+
     /// Stop the pipeline
     pub fn stop(&self) -> Result<()> {
         info!("⏹️ Stopping GStreamer pipeline for camera: {}", self.camera_id);
@@ -258,20 +878,474 @@ impl Drop for GstWebRTCSession {
     }
 }
 
+/// Where the source's video pad goes: depayloaded and parsed into the tee
+#[derive(Clone)]
+struct SourceSink {
+    tee: gst::Element,
+    codec: Arc<OnceLock<VideoCodec>>,
+    readiness: Arc<Readiness>,
+    /// H.265 can only be served transcoded
+    transcoding: bool,
+}
+
+impl SourceSink {
+    /// Link the first H.264/H.265 video pad of the source to the tee;
+    /// returns false for pads left unlinked
+    fn link(&self, pipeline: &gst::Pipeline, src_pad: &gst::Pad, source: &str) -> bool {
+        let Some((media, encoding)) = rtp_media(src_pad) else {
+            return false;
+        };
+        let tee_sink = self.tee.static_pad("sink").expect("tee has a sink pad");
+        if media != "video" || tee_sink.is_linked() {
+            return false;
+        }
+
+        let codec = match encoding.as_str() {
+            "H264" => VideoCodec::H264,
+            "H265" => VideoCodec::H265,
+            other => {
+                warn!("⚠️ Unsupported {} video encoding: {}", source, other);
+                self.readiness.fail(format!("Unsupported {} video encoding: {}", source, other));
+                return false;
+            }
+        };
+        if codec == VideoCodec::H265 && !self.transcoding {
+            self.readiness
+                .fail(format!("{} video is H.265, which needs transcoding (STREAM_TRANSCODE)", source));
+            return false;
+        }
+
+        match link_depayloader(pipeline, src_pad, &self.tee, codec) {
+            Ok(()) => {
+                let _ = self.codec.set(codec);
+                info!("✅ {} {:?} video stream linked!", source, codec);
+                self.readiness.mark_linked();
+                true
+            }
+            Err(e) => {
+                error!("❌ Failed to link {} video: {:#}", source, e);
+                self.readiness.fail(format!("Failed to link {} pad: {}", source, e));
+                false
+            }
+        }
+    }
+}
+
+/// `media` and `encoding-name` of an RTP pad
+fn rtp_media(pad: &gst::Pad) -> Option<(String, String)> {
+    let caps = pad.current_caps().unwrap_or_else(|| pad.query_caps(None));
+    let structure = caps.structure(0)?;
+    let media = structure.get::<&str>("media").ok()?.to_string();
+    let encoding = structure.get::<&str>("encoding-name").ok()?.to_uppercase();
+    Some((media, encoding))
+}
+
+/// `depay ! parse ! capsfilter` between the source's video pad and the tee
+fn link_depayloader(pipeline: &gst::Pipeline, src_pad: &gst::Pad, tee: &gst::Element, codec: VideoCodec) -> Result<()> {
+    let (depay, parse, media_type) = match codec {
+        VideoCodec::H264 => ("rtph264depay", "h264parse", "video/x-h264"),
+        VideoCodec::H265 => ("rtph265depay", "h265parse", "video/x-h265"),
+        other => bail!("No depayloader for {:?}", other),
+    };
+
+    let depay = gst::ElementFactory::make(depay)
+        .name("depay")
+        .build()
+        .with_context(|| format!("Failed to create {}", depay))?;
+
+    // Parameter sets on every IDR so a replayed GOP is decodable
+    let parse = gst::ElementFactory::make(parse)
+        .name("parse")
+        .property("config-interval", -1i32)
+        .build()
+        .with_context(|| format!("Failed to create {}", parse))?;
+
+    // Whole access units, so one buffer is one frame for the GOP cache
+    let capsfilter = gst::ElementFactory::make("capsfilter")
+        .name("capsfilter")
+        .property(
+            "caps",
+            gst::Caps::builder(media_type)
+                .field("stream-format", "byte-stream")
+                .field("alignment", "au")
+                .build(),
+        )
+        .build()
+        .context("Failed to create capsfilter")?;
+
+    let elements = [depay.clone(), parse, capsfilter.clone()];
+    pipeline.add_many(&elements)?;
+    gst::Element::link_many(&elements).context("Failed to link video elements")?;
+    capsfilter.link(tee).context("Failed to link video to tee")?;
+    for element in elements.iter().rev() {
+        element.sync_state_with_parent()?;
+    }
+
+    src_pad.link(&depay.static_pad("sink").context("No sink pad on depayloader")?)?;
+    Ok(())
+}
+
+/// `rtspsrc` with authentication; its video RTP pad feeds the tee
+fn add_rtsp_source(pipeline: &gst::Pipeline, sink: SourceSink, url: &str, username: &str, password: &str) -> Result<()> {
+    info!("🎬 Creating RTSP pipeline with authentication");
+    let rtspsrc = gst::ElementFactory::make("rtspsrc")
+        .name("source")
+        .property("location", url)
+        .property("user-id", username)
+        .property("user-pw", password)
+        .property("latency", 0u32)
+        .property("drop-on-latency", true)
+        .build()
+        .context("Failed to create rtspsrc")?;
+    pipeline.add(&rtspsrc)?;
+
+    // Connect RTSP dynamic pads (the first video one) to the tee
+    let pipeline_weak = pipeline.downgrade();
+    rtspsrc.connect_pad_added(move |_src, src_pad| {
+        let pad_name = src_pad.name().to_string();
+        info!("🔗 RTSP pad added: {}", pad_name);
+
+        if !pad_name.contains("recv_rtp_src") {
+            return;
+        }
+        if let Some(pipeline) = pipeline_weak.upgrade() {
+            sink.link(&pipeline, src_pad, "RTSP");
+        }
+    });
+    Ok(())
+}
+
+/// Receiving webrtcbin for a WHIP publisher; its video pad feeds the tee
+/// and anything else (audio) is discarded
+fn add_whip_source(
+    pipeline: &gst::Pipeline,
+    sink: SourceSink,
+    ice: &IceConfig,
+    departures: &mpsc::UnboundedSender<Uuid>,
+) -> Result<Publisher> {
+    let id = Uuid::new_v4();
+    let webrtcbin = gst::ElementFactory::make("webrtcbin")
+        .name("whip-publisher")
+        .property_from_str("bundle-policy", "max-bundle")
+        .build()
+        .context("Failed to create webrtcbin")?;
+    let candidates = configure_webrtcbin(&webrtcbin, id, ice, departures);
+
+    webrtcbin.connect("on-new-transceiver", false, |values| {
+        if let Some(transceiver) = values.get(1).and_then(|v| v.get::<gst_webrtc::WebRTCRTPTransceiver>().ok()) {
+            transceiver.set_property("direction", gst_webrtc::WebRTCRTPTransceiverDirection::Recvonly);
+        }
+        None
+    });
+
+    let pipeline_weak = pipeline.downgrade();
+    webrtcbin.connect_pad_added(move |_bin, src_pad| {
+        let Some(pipeline) = pipeline_weak.upgrade() else { return };
+        if sink.link(&pipeline, src_pad, "WHIP") {
+            return;
+        }
+
+        // Unsupported or extra media: drain it so it does not stall the bin
+        info!("🔇 Discarding WHIP pad {}", src_pad.name());
+        let Ok(fakesink) = gst::ElementFactory::make("fakesink")
+            .property("sync", false)
+            .property("async", false)
+            .build()
+        else {
+            return;
+        };
+        if pipeline.add(&fakesink).is_ok() {
+            let _ = fakesink.sync_state_with_parent();
+            if let Some(sink) = fakesink.static_pad("sink") {
+                let _ = src_pad.link(&sink);
+            }
+        }
+    });
+
+    pipeline.add(&webrtcbin)?;
+    Ok(Publisher {
+        id,
+        webrtcbin,
+        candidates,
+    })
+}
+
+/// STUN/TURN, gathered-candidate queue and remote-departure notification
+/// shared by viewer, publisher, talk-down and playback webrtcbins
+pub(crate) fn configure_webrtcbin(
+    webrtcbin: &gst::Element,
+    peer_id: Uuid,
+    ice: &IceConfig,
+    departures: &mpsc::UnboundedSender<Uuid>,
+) -> Arc<CandidateQueue> {
+    // webrtcbin takes a single STUN server but any number of TURN servers
+    if let Some(stun) = ice.stun_servers.first() {
+        webrtcbin.set_property("stun-server", stun.as_str());
+    }
+    for turn in &ice.turn_servers {
+        if !webrtcbin.emit_by_name::<bool>("add-turn-server", &[turn]) {
+            warn!("⚠️ webrtcbin rejected TURN server for peer {}", peer_id);
+        }
+    }
+
+    // Our candidates, as they are gathered
+    let candidates = Arc::new(CandidateQueue::new());
+    let gathered = candidates.clone();
+    webrtcbin.connect("on-ice-candidate", false, move |values| {
+        let mline = values.get(1).and_then(|v| v.get::<u32>().ok());
+        let candidate = values.get(2).and_then(|v| v.get::<String>().ok());
+        if let (Some(mline), Some(candidate)) = (mline, candidate) {
+            gathered.push(mline, candidate);
+        }
+        None
+    });
+    let gathered = candidates.clone();
+    webrtcbin.connect_notify(Some("ice-gathering-state"), move |bin, _| {
+        let state = bin.property::<gst_webrtc::WebRTCICEGatheringState>("ice-gathering-state");
+        if state == gst_webrtc::WebRTCICEGatheringState::Complete {
+            info!("🧊 ICE gathering complete for peer {}", peer_id);
+            gathered.complete();
+        }
+    });
+
+    // Remote went away without DELETE: report so its slot is released
+    let departures = departures.clone();
+    webrtcbin.connect_notify(Some("connection-state"), move |bin, _| {
+        let state = bin.property::<gst_webrtc::WebRTCPeerConnectionState>("connection-state");
+        if matches!(
+            state,
+            gst_webrtc::WebRTCPeerConnectionState::Failed | gst_webrtc::WebRTCPeerConnectionState::Closed
+        ) {
+            warn!("⚠️ Peer {} connection {:?}", peer_id, state);
+            let _ = departures.send(peer_id);
+        }
+    });
+
+    candidates
+}
+
+pub(crate) fn h264_rtp_caps() -> gst::Caps {
+    gst::Caps::builder("application/x-rtp")
+        .field("media", "video")
+        .field("clock-rate", 90000i32)
+        .field("encoding-name", "H264")
+        .field("payload", 96i32)
+        .build()
+}
+
+/// H.264 for a viewer's transceiver; adaptive viewers also negotiate the
+/// feedback their layer switching listens to
+fn viewer_codec_preferences(adaptive: bool, twcc: bool) -> gst::Caps {
+    let mut caps = h264_rtp_caps();
+    if let Some(structure) = caps.make_mut().structure_mut(0) {
+        if adaptive {
+            structure.set("rtcp-fb-goog-remb", true);
+        }
+        if twcc {
+            structure.set("rtcp-fb-transport-cc", true);
+            structure.set(format!("extmap-{}", TWCC_EXTENSION_ID).as_str(), TWCC_URI);
+        }
+    }
+    caps
+}
+
+/// Runs on the tee's streaming thread, before `tee` pushes the current
+/// buffer: pending branches get the sticky events and the cached GOP, then
+/// are linked so the current buffer follows without gaps or duplicates
+fn attach_pending(tee: &gst::Element, tee_sink: &gst::Pad, fanout: &mut Fanout, keyframe: bool) {
+    // A keyframe is about to flow anyway: nothing worth replaying
+    let replay = if keyframe { Vec::new() } else { fanout.gop.snapshot() };
+
+    for (peer_id, queue_sink) in fanout.pending.drain().collect::<Vec<_>>() {
+        // stream-start, caps and segment must precede the replayed buffers
+        tee_sink.sticky_events_foreach(|event| {
+            queue_sink.send_event(event.clone());
+            ControlFlow::Continue(gst::EventForeachAction::Keep)
+        });
+        for buffer in &replay {
+            if let Err(e) = queue_sink.chain(buffer.clone()) {
+                warn!("⚠️ GOP replay to viewer {} stopped: {:?}", peer_id, e);
+                break;
+            }
+        }
+
+        let Some(tee_pad) = tee.request_pad_simple("src_%u") else {
+            error!("❌ No src pad on tee for viewer {}", peer_id);
+            continue;
+        };
+        match tee_pad.link(&queue_sink) {
+            Ok(_) => {
+                debug!("🎞️ Viewer {} primed with {} cached frames", peer_id, replay.len());
+                fanout.attached.insert(peer_id, tee_pad);
+            }
+            Err(e) => {
+                error!("❌ Failed to link viewer {} to tee: {}", peer_id, e);
+                tee.release_request_pad(&tee_pad);
+            }
+        }
+    }
+}
+
+/// Runs on the target tee's streaming thread right before it pushes a
+/// keyframe: the branch leaves its old tee and continues here from that
+/// keyframe, so the viewer's decoder never gets a frame without its
+/// reference (no GOP replay: that would rewind the picture)
+fn complete_switch(tee: &gst::Element, tee_sink: &gst::Pad, fanout: &Mutex<Fanout>, peer_id: Uuid, switch: Switch) {
+    let old_pad = {
+        let mut from = switch.from.fanout.lock().unwrap();
+        from.pending.remove(&peer_id);
+        from.attached.remove(&peer_id)
+    };
+    if let Some(old_pad) = old_pad {
+        let _ = old_pad.unlink(&switch.queue_sink);
+        switch.from.tee.call_async(move |tee| tee.release_request_pad(&old_pad));
+    }
+
+    // New caps (resolution) and segment ahead of the keyframe
+    tee_sink.sticky_events_foreach(|event| {
+        switch.queue_sink.send_event(event.clone());
+        ControlFlow::Continue(gst::EventForeachAction::Keep)
+    });
+
+    match tee.request_pad_simple("src_%u") {
+        Some(tee_pad) => match tee_pad.link(&switch.queue_sink) {
+            Ok(_) => {
+                fanout.lock().unwrap().attached.insert(peer_id, tee_pad);
+            }
+            Err(e) => {
+                error!("❌ Failed to switch viewer {}: {}", peer_id, e);
+                tee.release_request_pad(&tee_pad);
+            }
+        },
+        None => error!("❌ No src pad on tee for viewer {}", peer_id),
+    }
+
+    let on_switched = switch.on_switched;
+    tee.call_async(move |_| on_switched());
+}
+
+/// Forget a branch on an output; returns its tee pad if it was linked
+fn take_branch(output: &Output, key: Uuid) -> Option<(gst::Element, gst::Pad)> {
+    let mut fanout = output.fanout.lock().unwrap();
+    fanout.pending.remove(&key);
+    fanout.switching.remove(&key);
+    fanout.attached.remove(&key).map(|tee_pad| (output.tee.clone(), tee_pad))
+}
+
+/// Unlink a branch from its tee pads once each is idle, then tear it down
+fn unlink_branch(pipeline: &gst::Pipeline, tee_pads: Vec<(gst::Element, gst::Pad)>, elements: Vec<gst::Element>) {
+    if tee_pads.is_empty() {
+        // Never linked to a tee
+        teardown(pipeline, elements);
+        return;
+    }
+
+    let remaining = Arc::new(AtomicUsize::new(tee_pads.len()));
+    for (tee, tee_pad) in tee_pads {
+        let pipeline = pipeline.clone();
+        let elements = elements.clone();
+        let remaining = remaining.clone();
+        tee_pad.add_probe(gst::PadProbeType::IDLE, move |pad, _| {
+            if let Some(peer_pad) = pad.peer() {
+                let _ = pad.unlink(&peer_pad);
+            }
+            tee.release_request_pad(pad);
+            if remaining.fetch_sub(1, Ordering::AcqRel) == 1 {
+                teardown(&pipeline, elements.clone());
+            }
+            gst::PadProbeReturn::Remove
+        });
+    }
+}
+
+/// Stop and remove a branch (off the streaming thread)
+fn teardown(pipeline: &gst::Pipeline, elements: Vec<gst::Element>) {
+    pipeline.call_async(move |pipeline| {
+        for element in &elements {
+            let _ = element.set_state(gst::State::Null);
+        }
+        let _ = pipeline.remove_many(&elements);
+    });
+}
+
+/// Wait for a webrtcbin promise without blocking a runtime thread
+async fn await_promise(reply: gst::PromiseFuture, what: &str) -> Result<Option<gst::Structure>> {
+    let reply = tokio::time::timeout(NEGOTIATION_TIMEOUT, reply)
+        .await
+        .map_err(|_| anyhow!("Timeout waiting for {}", what))?
+        .map_err(|e| anyhow!("{} failed: {:?}", what, e))?;
+
+    let reply = reply.map(|reply| (*reply).to_owned());
+    if let Some(error) = reply.as_ref().and_then(|reply| reply.get::<glib::Error>("error").ok()) {
+        return Err(anyhow!("{} failed: {}", what, error));
+    }
+    Ok(reply)
+}
+
+/// Apply an SDP offer to a webrtcbin and return its answer; with
+/// `gathered`, waits for ICE gathering and returns the answer with our
+/// candidates in it
+pub(crate) async fn negotiate(webrtcbin: &gst::Element, offer_sdp: String, gathered: Option<&CandidateQueue>) -> Result<String> {
+    // Parse SDP offer
+    let sdp = gst_sdp::SDPMessage::parse_buffer(offer_sdp.as_bytes())
+        .map_err(|e| anyhow!("Failed to parse SDP: {:?}", e))?;
+
+    let offer = gst_webrtc::WebRTCSessionDescription::new(
+        gst_webrtc::WebRTCSDPType::Offer,
+        sdp,
+    );
+
+    // Set remote description
+    info!("📥 Setting remote description");
+    let (promise, applied) = gst::Promise::new_with_future();
+    webrtcbin.emit_by_name::<()>("set-remote-description", &[&offer, &promise]);
+    await_promise(applied, "set-remote-description").await?;
+
+    // Create answer
+    info!("📤 Creating SDP answer");
+    let (promise, created) = gst::Promise::new_with_future();
+    webrtcbin.emit_by_name::<()>("create-answer", &[&None::<gst::Structure>, &promise]);
+    let answer = await_promise(created, "create-answer")
+        .await?
+        .ok_or_else(|| anyhow!("create-answer returned no reply"))?
+        .get::<gst_webrtc::WebRTCSessionDescription>("answer")
+        .context("create-answer reply has no answer")?;
+
+    // Set local description
+    let (promise, applied) = gst::Promise::new_with_future();
+    webrtcbin.emit_by_name::<()>("set-local-description", &[&answer, &promise]);
+    await_promise(applied, "set-local-description").await?;
+
+    let mut sdp_text = answer.sdp().to_string();
+    if let Some(gathered) = gathered {
+        if !gathered.wait_complete(GATHERING_TIMEOUT).await {
+            warn!("⚠️ ICE gathering incomplete, answering with partial candidates");
+        }
+        // local-description now carries the gathered candidates
+        if let Some(local) = webrtcbin.property::<Option<gst_webrtc::WebRTCSessionDescription>>("local-description") {
+            sdp_text = local.sdp().to_string();
+        }
+    }
+    info!("📤 Answer created ({} bytes)", sdp_text.len());
+    debug!("📤 SDP Answer content:\n{}", sdp_text);
+
+    Ok(sdp_text)
+}
+
 /// Initialize GStreamer and start GLib main loop (call once at startup)
 pub fn init() -> Result<()> {
     gst::init().context("Failed to initialize GStreamer")?;
     info!("✅ GStreamer initialized");
-    
+
     // Start a dedicated thread for GLib main loop
     // This is REQUIRED for GStreamer callbacks and data flow to work!
     std::thread::spawn(|| {
         let main_context = glib::MainContext::default();
-        
+
         // IMPORTANT: Must acquire context for this thread to process callbacks
         main_context.acquire();
         info!("🔄 GLib main loop thread started and context acquired");
-        
+
         // Run iteration loop forever - this processes all GStreamer events
         loop {
             // process all pending events
@@ -280,10 +1354,10 @@ pub fn init() -> Result<()> {
             std::thread::sleep(std::time::Duration::from_millis(5));
         }
     });
-    
+
     // Give the main loop time to start
     std::thread::sleep(std::time::Duration::from_millis(100));
-    
+
     info!("✅ GLib event processing thread started");
     Ok(())
 }
@@ -0,0 +1,481 @@
+//! Empacotador LL-HLS
+//!
+//! Os access units H.264 de uma câmera viram partes CMAF (fragmentos fMP4,
+//! ver `fmp4`) de até `part_target`, agrupadas em segmentos que começam
+//! sempre num keyframe e duram pelo menos `segment_target`. A playlist
+//! anuncia as partes dos segmentos recentes, a próxima parte como preload
+//! hint e aceita reload bloqueante (`_HLS_msn`/`_HLS_part`).
+//!
+//! Nomes na playlist, relativos a ela: `init.mp4`, `s<msn>.m4s` (segmento
+//! completo) e `p<msn>.<parte>.m4s`.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use bytes::Bytes;
+use chrono::{DateTime, SecondsFormat, Utc};
+use tracing::{debug, warn};
+
+use crate::fmp4::{self, AvcConfig, Sample, TIMESCALE};
+
+/// Segmentos completos mantidos na playlist
+const WINDOW_SEGMENTS: usize = 6;
+
+/// Segmentos completos (além do atual) que ainda listam suas partes
+const PART_SEGMENTS: usize = 2;
+
+/// Duração assumida para um frame quando o timestamp não ajuda (25 fps)
+const DEFAULT_FRAME_TICKS: u64 = TIMESCALE as u64 / 25;
+
+/// Intervalo entre frames acima do qual o timestamp é tratado como salto
+const MAX_FRAME_GAP: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy)]
+pub struct HlsTiming {
+    /// Duração máxima de uma parte (`PART-TARGET`)
+    pub part_target: Duration,
+    /// Duração mínima de um segmento (fecha no keyframe seguinte)
+    pub segment_target: Duration,
+}
+
+/// Arquivo pedido ao lado da playlist
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HlsFile {
+    Playlist,
+    Init,
+    Segment(u64),
+    Part(u64, usize),
+}
+
+impl HlsFile {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "index.m3u8" => return Some(Self::Playlist),
+            "init.mp4" => return Some(Self::Init),
+            _ => {}
+        }
+        let stem = name.strip_suffix(".m4s")?;
+        if let Some(msn) = stem.strip_prefix('s') {
+            return msn.parse().ok().map(Self::Segment);
+        }
+        let (msn, part) = stem.strip_prefix('p')?.split_once('.')?;
+        Some(Self::Part(msn.parse().ok()?, part.parse().ok()?))
+    }
+}
+
+struct Part {
+    data: Bytes,
+    duration: u64,
+    independent: bool,
+}
+
+struct Segment {
+    msn: u64,
+    started_at: DateTime<Utc>,
+    parts: Vec<Part>,
+    duration: u64,
+    /// Partes concatenadas, montado quando o segmento fecha
+    data: Option<Bytes>,
+}
+
+impl Segment {
+    fn close(&mut self) {
+        let mut data = Vec::with_capacity(self.parts.iter().map(|part| part.data.len()).sum());
+        for part in &self.parts {
+            data.extend_from_slice(&part.data);
+        }
+        self.data = Some(data.into());
+    }
+}
+
+/// Frame esperando o próximo para saber sua duração
+struct Held {
+    sample: Vec<u8>,
+    keyframe: bool,
+    at: DateTime<Utc>,
+}
+
+pub struct LlHls {
+    timing: HlsTiming,
+    avc: Option<AvcConfig>,
+    init: Option<Bytes>,
+    segments: VecDeque<Segment>,
+    current: Option<Segment>,
+    part: Vec<Sample>,
+    part_duration: u64,
+    held: Option<Held>,
+    last_duration: u64,
+    next_msn: u64,
+    sequence: u32,
+    decode_time: u64,
+}
+
+impl LlHls {
+    pub fn new(timing: HlsTiming) -> Self {
+        Self {
+            timing,
+            avc: None,
+            init: None,
+            segments: VecDeque::new(),
+            current: None,
+            part: Vec::new(),
+            part_duration: 0,
+            held: None,
+            last_duration: DEFAULT_FRAME_TICKS,
+            next_msn: 0,
+            sequence: 1,
+            decode_time: 0,
+        }
+    }
+
+    /// Entrega um access unit Annex-B; frames anteriores ao primeiro
+    /// keyframe com SPS/PPS são descartados
+    pub fn push(&mut self, data: &[u8], keyframe: bool, at: DateTime<Utc>, width: u32, height: u32) {
+        if keyframe {
+            if let Some(avc) = AvcConfig::from_access_unit(data, width, height) {
+                if self.avc.as_ref() != Some(&avc) {
+                    if self.avc.is_some() {
+                        // Clientes só leem o init uma vez: recomeça limpo
+                        warn!("⚠️ Camera parameters changed ({}x{}), restarting HLS stream", width, height);
+                        self.restart();
+                    }
+                    self.init = Some(avc.init_segment().into());
+                    self.avc = Some(avc);
+                }
+            }
+        }
+        if self.avc.is_none() || (self.held.is_none() && self.current.is_none() && !keyframe) {
+            return;
+        }
+
+        if let Some(held) = self.held.take() {
+            let duration = self.duration_until(held.at, at);
+            self.add(held, duration);
+            if keyframe {
+                // Partes e segmentos começam em keyframes
+                self.flush_part();
+                let segment_target = ticks(self.timing.segment_target);
+                if self.current.as_ref().is_some_and(|segment| segment.duration >= segment_target) {
+                    self.close_segment();
+                }
+            }
+        }
+        self.held = Some(Held {
+            sample: fmp4::avcc_sample(data),
+            keyframe,
+            at,
+        });
+    }
+
+    /// Duração do frame em `from`, pelo timestamp do seguinte
+    fn duration_until(&mut self, from: DateTime<Utc>, to: DateTime<Utc>) -> u64 {
+        let elapsed = (to - from).to_std().ok().filter(|elapsed| !elapsed.is_zero() && *elapsed <= MAX_FRAME_GAP);
+        match elapsed {
+            Some(elapsed) => {
+                self.last_duration = ticks(elapsed);
+                self.last_duration
+            }
+            None => self.last_duration,
+        }
+    }
+
+    fn add(&mut self, held: Held, duration: u64) {
+        let part_target = ticks(self.timing.part_target);
+        if !self.part.is_empty() && self.part_duration + duration > part_target {
+            self.flush_part();
+        }
+        if self.current.is_none() {
+            self.current = Some(Segment {
+                msn: self.next_msn,
+                started_at: held.at,
+                parts: Vec::new(),
+                duration: 0,
+                data: None,
+            });
+            self.next_msn += 1;
+        }
+
+        self.part.push(Sample {
+            data: held.sample,
+            duration: duration as u32,
+            keyframe: held.keyframe,
+        });
+        self.part_duration += duration;
+        // Fecha já se o próximo frame não caberia: a parte sai sem esperá-lo
+        if self.part_duration + duration > part_target {
+            self.flush_part();
+        }
+    }
+
+    fn flush_part(&mut self) {
+        let Some(segment) = self.current.as_mut() else {
+            return;
+        };
+        if self.part.is_empty() {
+            return;
+        }
+        let samples = std::mem::take(&mut self.part);
+        let duration = std::mem::take(&mut self.part_duration);
+        match fmp4::fragment(self.sequence, self.decode_time, &samples) {
+            Ok(data) => segment.parts.push(Part {
+                data: data.into(),
+                duration,
+                independent: samples[0].keyframe,
+            }),
+            Err(e) => debug!("HLS part dropped: {}", e),
+        }
+        self.sequence = self.sequence.wrapping_add(1);
+        self.decode_time += duration;
+        segment.duration += duration;
+    }
+
+    fn close_segment(&mut self) {
+        let Some(mut segment) = self.current.take() else {
+            return;
+        };
+        segment.close();
+        self.segments.push_back(segment);
+        while self.segments.len() > WINDOW_SEGMENTS {
+            self.segments.pop_front();
+        }
+    }
+
+    /// Descarta tudo (a numeração continua, para os clientes perceberem o salto)
+    fn restart(&mut self) {
+        self.segments.clear();
+        self.current = None;
+        self.part.clear();
+        self.part_duration = 0;
+        self.held = None;
+    }
+
+    pub fn init(&self) -> Option<Bytes> {
+        self.init.clone()
+    }
+
+    pub fn segment(&self, msn: u64) -> Option<Bytes> {
+        self.segments.iter().find(|segment| segment.msn == msn)?.data.clone()
+    }
+
+    pub fn part(&self, msn: u64, index: usize) -> Option<Bytes> {
+        self.segments
+            .iter()
+            .chain(&self.current)
+            .find(|segment| segment.msn == msn)?
+            .parts
+            .get(index)
+            .map(|part| part.data.clone())
+    }
+
+    /// Segmentos completos na janela
+    pub fn segment_count(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Último segmento (completo ou não) que já tem alguma parte
+    pub fn last_msn(&self) -> Option<u64> {
+        self.current
+            .as_ref()
+            .filter(|segment| !segment.parts.is_empty())
+            .or(self.segments.back())
+            .map(|segment| segment.msn)
+    }
+
+    /// Se a playlist já traz o segmento `msn` completo ou, com `part`, essa
+    /// parte dele (condição do reload bloqueante)
+    pub fn contains(&self, msn: u64, part: Option<usize>) -> bool {
+        if self.segments.back().is_some_and(|segment| segment.msn >= msn) {
+            return true;
+        }
+        match (&self.current, part) {
+            (Some(current), Some(part)) => current.msn > msn || (current.msn == msn && current.parts.len() > part),
+            (Some(current), None) => current.msn > msn,
+            (None, _) => false,
+        }
+    }
+
+    /// Playlist de mídia; `query` (com `?`, ou vazia) vai em cada URI.
+    /// `None` enquanto não há nenhuma parte
+    pub fn playlist(&self, query: &str) -> Option<String> {
+        self.init.as_ref()?;
+        self.last_msn()?;
+
+        let part_target = self.timing.part_target.as_secs_f64();
+        let longest = self
+            .segments
+            .iter()
+            .map(|segment| segment.duration)
+            .max()
+            .unwrap_or(0)
+            .max(ticks(self.timing.segment_target));
+        let target_duration = longest.div_ceil(u64::from(TIMESCALE));
+        let first_msn = self
+            .segments
+            .front()
+            .or(self.current.as_ref())
+            .map_or(0, |segment| segment.msn);
+
+        let mut out = String::new();
+        out.push_str("#EXTM3U\n#EXT-X-VERSION:9\n");
+        out.push_str(&format!("#EXT-X-TARGETDURATION:{}\n", target_duration));
+        out.push_str(&format!("#EXT-X-PART-INF:PART-TARGET={:.3}\n", part_target));
+        out.push_str(&format!(
+            "#EXT-X-SERVER-CONTROL:CAN-BLOCK-RELOAD=YES,PART-HOLD-BACK={:.3}\n",
+            part_target * 3.0
+        ));
+        out.push_str(&format!("#EXT-X-MEDIA-SEQUENCE:{}\n", first_msn));
+        out.push_str(&format!("#EXT-X-MAP:URI=\"init.mp4{}\"\n", query));
+
+        let with_parts = self.segments.len().saturating_sub(PART_SEGMENTS);
+        for (i, segment) in self.segments.iter().enumerate() {
+            write_date(&mut out, segment);
+            if i >= with_parts {
+                write_parts(&mut out, segment, query);
+            }
+            out.push_str(&format!("#EXTINF:{:.5},\ns{}.m4s{}\n", seconds(segment.duration), segment.msn, query));
+        }
+
+        let (next_msn, next_part) = match &self.current {
+            Some(current) => {
+                write_date(&mut out, current);
+                write_parts(&mut out, current, query);
+                (current.msn, current.parts.len())
+            }
+            None => (self.next_msn, 0),
+        };
+        out.push_str(&format!(
+            "#EXT-X-PRELOAD-HINT:TYPE=PART,URI=\"p{}.{}.m4s{}\"\n",
+            next_msn, next_part, query
+        ));
+        Some(out)
+    }
+}
+
+fn write_date(out: &mut String, segment: &Segment) {
+    out.push_str(&format!(
+        "#EXT-X-PROGRAM-DATE-TIME:{}\n",
+        segment.started_at.to_rfc3339_opts(SecondsFormat::Millis, true)
+    ));
+}
+
+fn write_parts(out: &mut String, segment: &Segment, query: &str) {
+    for (i, part) in segment.parts.iter().enumerate() {
+        out.push_str(&format!(
+            "#EXT-X-PART:DURATION={:.5},URI=\"p{}.{}.m4s{}\"{}\n",
+            seconds(part.duration),
+            segment.msn,
+            i,
+            query,
+            if part.independent { ",INDEPENDENT=YES" } else { "" }
+        ));
+    }
+}
+
+fn ticks(duration: Duration) -> u64 {
+    (duration.as_micros() * u128::from(TIMESCALE) / 1_000_000) as u64
+}
+
+fn seconds(ticks: u64) -> f64 {
+    ticks as f64 / f64::from(TIMESCALE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEYFRAME: &[u8] = &[
+        0, 0, 0, 1, 0x67, 0x42, 0x00, 0x1f, 0xac, 0xd9, // SPS
+        0, 0, 0, 1, 0x68, 0xeb, 0xe3, 0xcb, // PPS
+        0, 0, 0, 1, 0x65, 0x88, 0x84, 0x00, // IDR
+    ];
+    const DELTA: &[u8] = &[0, 0, 0, 1, 0x41, 0x9a, 0x02];
+
+    fn packager() -> LlHls {
+        LlHls::new(HlsTiming {
+            part_target: Duration::from_millis(200),
+            segment_target: Duration::from_secs(1),
+        })
+    }
+
+    /// `frames` frames a 25 fps, keyframe a cada `gop`
+    fn feed(hls: &mut LlHls, start: usize, frames: usize, gop: usize) {
+        let t0 = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).unwrap();
+        for i in start..start + frames {
+            let at = t0 + chrono::Duration::milliseconds(40 * i as i64);
+            let keyframe = i % gop == 0;
+            hls.push(if keyframe { KEYFRAME } else { DELTA }, keyframe, at, 1280, 720);
+        }
+    }
+
+    #[test]
+    fn test_file_names() {
+        assert_eq!(HlsFile::parse("index.m3u8"), Some(HlsFile::Playlist));
+        assert_eq!(HlsFile::parse("init.mp4"), Some(HlsFile::Init));
+        assert_eq!(HlsFile::parse("s12.m4s"), Some(HlsFile::Segment(12)));
+        assert_eq!(HlsFile::parse("p12.3.m4s"), Some(HlsFile::Part(12, 3)));
+        assert_eq!(HlsFile::parse("p12.m4s"), None);
+        assert_eq!(HlsFile::parse("x.m4s"), None);
+    }
+
+    #[test]
+    fn test_waits_for_keyframe_with_parameter_sets() {
+        let mut hls = packager();
+        feed(&mut hls, 1, 20, 50);
+        assert!(hls.init().is_none());
+        assert!(hls.playlist("").is_none());
+
+        // Só o IDR, sem SPS/PPS: ainda não dá para montar o init
+        hls.push(&KEYFRAME[18..], true, Utc::now(), 1280, 720);
+        assert!(hls.init().is_none());
+    }
+
+    #[test]
+    fn test_parts_and_segments() {
+        let mut hls = packager();
+        // Keyframes a cada 1,2 s: segmentos de 30 frames, partes de 5 frames
+        feed(&mut hls, 0, 100, 30);
+
+        assert!(hls.init().is_some());
+        assert_eq!(hls.segment_count(), 3);
+        assert!(hls.segment(0).is_some());
+        assert!(hls.segment(3).is_none());
+        assert!(hls.part(0, 5).is_some());
+        assert!(hls.part(0, 6).is_none());
+        // Frames 90..98 do segmento atual já fecharam uma parte
+        assert_eq!(hls.last_msn(), Some(3));
+        assert!(hls.part(3, 0).is_some());
+
+        assert!(hls.contains(2, None));
+        assert!(!hls.contains(3, None));
+        assert!(hls.contains(3, Some(0)));
+        assert!(!hls.contains(3, Some(1)));
+
+        let playlist = hls.playlist("?token=abc").unwrap();
+        assert!(playlist.contains("#EXT-X-TARGETDURATION:2\n"));
+        assert!(playlist.contains("#EXT-X-PART-INF:PART-TARGET=0.200\n"));
+        assert!(playlist.contains("CAN-BLOCK-RELOAD=YES,PART-HOLD-BACK=0.600"));
+        assert!(playlist.contains("#EXT-X-MEDIA-SEQUENCE:0\n"));
+        assert!(playlist.contains("#EXT-X-MAP:URI=\"init.mp4?token=abc\"\n"));
+        assert!(playlist.contains("#EXTINF:1.20000,\ns0.m4s?token=abc\n"));
+        assert!(playlist.contains("#EXT-X-PART:DURATION=0.20000,URI=\"p1.0.m4s?token=abc\",INDEPENDENT=YES\n"));
+        assert!(playlist.contains("#EXT-X-PART:DURATION=0.20000,URI=\"p1.1.m4s?token=abc\"\n"));
+        // Partes antigas saem da playlist, o segmento continua
+        assert!(!playlist.contains("p0.0.m4s"));
+        assert!(playlist.ends_with("#EXT-X-PRELOAD-HINT:TYPE=PART,URI=\"p3.1.m4s?token=abc\"\n"));
+        assert!(playlist.contains("#EXT-X-PROGRAM-DATE-TIME:2023-11-14T22:13:20.000Z\n"));
+    }
+
+    #[test]
+    fn test_window_slides() {
+        let mut hls = packager();
+        feed(&mut hls, 0, 400, 25);
+
+        assert_eq!(hls.segment_count(), WINDOW_SEGMENTS);
+        let first = hls.last_msn().unwrap() - WINDOW_SEGMENTS as u64;
+        assert!(hls.segment(first - 1).is_none());
+        assert!(hls
+            .playlist("")
+            .unwrap()
+            .contains(&format!("#EXT-X-MEDIA-SEQUENCE:{}\n", first)));
+    }
+}
@@ -0,0 +1,306 @@
+//! Servidor LL-HLS
+//!
+//! Para painéis e audiências grandes, onde uma sessão WebRTC por viewer não
+//! escala: qualquer câmera vira LL-HLS sob demanda a partir dos frames do
+//! NATS, empacotada uma única vez e servida por HTTP a todos os players. O
+//! acesso exige o token emitido pelo vms-api (mesmo formato do SRT, ver
+//! `vms_common::srt`, com segredo e escopo próprios), repassado em cada URI
+//! da playlist. Uma câmera sem requisições por `idle_timeout` é desligada.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use thiserror::Error;
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use vms_common::media_profile::MediaProfileUsage;
+use vms_common::srt::{verify_token, SrtAccessError, TokenScope};
+use vms_common::types::{CameraId, StreamId};
+
+use crate::hls::{HlsFile, HlsTiming, LlHls};
+use crate::nats_consumer::StreamDistributor;
+
+/// Frames do NATS em espera por câmera antes do empacotador
+const FRAME_BUFFER: usize = 256;
+
+/// Espera pela primeira parte de uma câmera recém-ligada
+const START_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Frequência da verificação de câmeras ociosas
+const REAP_INTERVAL: Duration = Duration::from_secs(5);
+
+pub struct HlsSettings {
+    pub timing: HlsTiming,
+    /// Sem requisições por esse tempo, o empacotamento da câmera para
+    pub idle_timeout: Duration,
+    /// Segredo dos tokens; sem ele todas as requisições são recusadas
+    pub token_secret: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum HlsError {
+    #[error("HLS access denied: {0}")]
+    Unauthorized(SrtAccessError),
+
+    #[error("Invalid camera id: {0}")]
+    InvalidCamera(String),
+
+    #[error("Invalid blocking reload: {0}")]
+    BadRequest(String),
+
+    #[error("{0} is no longer available")]
+    Gone(String),
+
+    #[error("Timed out waiting for {0}")]
+    Timeout(String),
+
+    #[error("Camera feed unavailable: {0}")]
+    Unavailable(String),
+}
+
+/// Reload bloqueante pedido pelo player
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlockingReload {
+    pub msn: Option<u64>,
+    pub part: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HlsStreamInfo {
+    pub camera_id: String,
+    pub started_at: DateTime<Utc>,
+    /// Segmentos completos na playlist
+    pub segments: usize,
+    pub last_msn: Option<u64>,
+    pub idle_secs: u64,
+}
+
+/// Empacotamento de uma câmera
+struct HlsStream {
+    packager: Arc<StdMutex<LlHls>>,
+    /// Avança a cada frame empacotado (acorda requisições bloqueadas)
+    updates: Arc<watch::Sender<u64>>,
+    last_access: StdMutex<Instant>,
+    stream_id: StreamId,
+    pump: JoinHandle<()>,
+    started_at: DateTime<Utc>,
+}
+
+pub struct HlsServer {
+    settings: HlsSettings,
+    distributor: Arc<StreamDistributor>,
+    streams: Mutex<HashMap<CameraId, Arc<HlsStream>>>,
+}
+
+impl HlsServer {
+    pub fn new(settings: HlsSettings, distributor: Arc<StreamDistributor>) -> Self {
+        if settings.token_secret.is_none() {
+            warn!("⚠️ No HLS token secret configured: HLS requests will be rejected");
+        }
+        Self {
+            settings,
+            distributor,
+            streams: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Desliga câmeras ociosas até o processo terminar
+    pub fn start_reaper(self: &Arc<Self>) {
+        let server = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(REAP_INTERVAL);
+            loop {
+                interval.tick().await;
+                let Some(server) = server.upgrade() else { break };
+                server.stop_idle().await;
+            }
+        });
+    }
+
+    /// Câmera do pedido, se o token confere
+    pub fn authorize(&self, camera_id: &str, token: Option<&str>) -> Result<CameraId, HlsError> {
+        let secret = self
+            .settings
+            .token_secret
+            .as_deref()
+            .ok_or(HlsError::Unauthorized(SrtAccessError::InvalidToken))?;
+        let token = token.ok_or(HlsError::Unauthorized(SrtAccessError::InvalidToken))?;
+        verify_token(secret.as_bytes(), TokenScope::Hls, camera_id, token, Utc::now().timestamp())
+            .map_err(HlsError::Unauthorized)?;
+
+        let uuid = camera_id
+            .parse()
+            .map_err(|_| HlsError::InvalidCamera(camera_id.to_string()))?;
+        Ok(CameraId::from_uuid(uuid))
+    }
+
+    /// Conteúdo de um arquivo da câmera; `query` vai nas URIs da playlist
+    pub async fn file(
+        &self,
+        camera_id: CameraId,
+        file: HlsFile,
+        reload: BlockingReload,
+        query: &str,
+    ) -> Result<Bytes, HlsError> {
+        let (stream, started) = self.stream(camera_id).await?;
+        *stream.last_access.lock().unwrap() = Instant::now();
+
+        // Um bloqueio responde em até 3 durações de segmento; a primeira
+        // requisição espera o keyframe inicial da câmera
+        let timeout = if started {
+            START_TIMEOUT
+        } else {
+            START_TIMEOUT.min(self.settings.timing.segment_target * 3)
+        };
+
+        match file {
+            HlsFile::Playlist => {
+                let (msn, part) = (reload.msn, reload.part);
+                if msn.is_none() && part.is_some() {
+                    return Err(HlsError::BadRequest("_HLS_part without _HLS_msn".to_string()));
+                }
+                if let (Some(msn), Some(last)) = (msn, stream.packager.lock().unwrap().last_msn()) {
+                    if msn > last + 2 {
+                        return Err(HlsError::BadRequest(format!("segment {} is too far ahead", msn)));
+                    }
+                }
+                wait(&stream, timeout, "playlist", |hls| {
+                    if let Some(msn) = msn {
+                        if !hls.contains(msn, part) {
+                            return None;
+                        }
+                    }
+                    hls.playlist(query).map(|playlist| Ok(Bytes::from(playlist)))
+                })
+                .await
+            }
+            HlsFile::Init => wait(&stream, timeout, "init segment", |hls| hls.init().map(Ok)).await,
+            HlsFile::Segment(msn) => {
+                let name = format!("segment {}", msn);
+                wait(&stream, timeout, &name, |hls| match hls.segment(msn) {
+                    Some(data) => Some(Ok(data)),
+                    None if hls.contains(msn, None) => Some(Err(HlsError::Gone(name.clone()))),
+                    None => None,
+                })
+                .await
+            }
+            HlsFile::Part(msn, index) => {
+                // O preload hint é pedido antes de existir: segura até a parte sair
+                let name = format!("part {}.{}", msn, index);
+                wait(&stream, timeout, &name, |hls| match hls.part(msn, index) {
+                    Some(data) => Some(Ok(data)),
+                    None if hls.contains(msn, Some(index)) => Some(Err(HlsError::Gone(name.clone()))),
+                    None => None,
+                })
+                .await
+            }
+        }
+    }
+
+    /// Empacotamento da câmera, ligado sob demanda; `true` se acabou de ligar
+    async fn stream(&self, camera_id: CameraId) -> Result<(Arc<HlsStream>, bool), HlsError> {
+        let mut streams = self.streams.lock().await;
+        if let Some(stream) = streams.get(&camera_id) {
+            return Ok((stream.clone(), false));
+        }
+
+        let (stream_id, mut frames) = self
+            .distributor
+            .create_stream(camera_id, MediaProfileUsage::LiveView, FRAME_BUFFER)
+            .await
+            .map_err(|e| HlsError::Unavailable(e.to_string()))?;
+
+        let packager = Arc::new(StdMutex::new(LlHls::new(self.settings.timing)));
+        let updates = Arc::new(watch::channel(0u64).0);
+        let (pump_packager, pump_updates) = (packager.clone(), updates.clone());
+        let pump = tokio::spawn(async move {
+            while let Some(frame) = frames.recv().await {
+                pump_packager.lock().unwrap().push(
+                    &frame.data,
+                    frame.is_keyframe,
+                    *frame.timestamp.as_datetime(),
+                    frame.width,
+                    frame.height,
+                );
+                pump_updates.send_modify(|version| *version += 1);
+            }
+        });
+        let stream = Arc::new(HlsStream {
+            packager,
+            updates,
+            last_access: StdMutex::new(Instant::now()),
+            stream_id,
+            pump,
+            started_at: Utc::now(),
+        });
+
+        info!("📺 LL-HLS started for camera {}", camera_id);
+        streams.insert(camera_id, stream.clone());
+        Ok((stream, true))
+    }
+
+    async fn stop_idle(&self) {
+        let idle: Vec<(CameraId, Arc<HlsStream>)> = {
+            let mut streams = self.streams.lock().await;
+            let expired: Vec<CameraId> = streams
+                .iter()
+                .filter(|(_, stream)| stream.last_access.lock().unwrap().elapsed() > self.settings.idle_timeout)
+                .map(|(camera_id, _)| *camera_id)
+                .collect();
+            expired
+                .into_iter()
+                .filter_map(|camera_id| streams.remove(&camera_id).map(|stream| (camera_id, stream)))
+                .collect()
+        };
+
+        for (camera_id, stream) in idle {
+            stream.pump.abort();
+            let _ = self.distributor.remove_stream(camera_id, stream.stream_id).await;
+            info!("💤 LL-HLS stopped for camera {} (idle)", camera_id);
+        }
+    }
+
+    /// Câmeras sendo empacotadas
+    pub async fn streams(&self) -> Vec<HlsStreamInfo> {
+        self.streams
+            .lock()
+            .await
+            .iter()
+            .map(|(camera_id, stream)| {
+                let hls = stream.packager.lock().unwrap();
+                HlsStreamInfo {
+                    camera_id: camera_id.to_string(),
+                    started_at: stream.started_at,
+                    segments: hls.segment_count(),
+                    last_msn: hls.last_msn(),
+                    idle_secs: stream.last_access.lock().unwrap().elapsed().as_secs(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Espera até `check` decidir (a cada frame empacotado) ou o tempo acabar
+async fn wait<T>(
+    stream: &HlsStream,
+    timeout: Duration,
+    what: &str,
+    mut check: impl FnMut(&LlHls) -> Option<Result<T, HlsError>>,
+) -> Result<T, HlsError> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut updates = stream.updates.subscribe();
+    loop {
+        if let Some(result) = check(&stream.packager.lock().unwrap()) {
+            return result;
+        }
+        match tokio::time::timeout_at(deadline, updates.changed()).await {
+            Ok(Ok(())) => {}
+            Ok(Err(_)) | Err(_) => return Err(HlsError::Timeout(what.to_string())),
+        }
+    }
+}
@@ -0,0 +1,381 @@
+//! Escada de qualidade do live
+//!
+//! Cada degrau (`Rendition`) vem de um `MediaProfile` e é produzido por um
+//! transcoder H.264 compartilhado pelos viewers da câmera naquele degrau. O
+//! viewer troca de camada conforme a estimativa de banda (REMB do browser ou
+//! TWCC via `rtpgccbwe`): desce assim que a banda não comporta a camada atual
+//! e só sobe depois de a banda sobrar por alguns segundos, para não oscilar.
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use vms_common::media_profile::MediaProfile;
+
+/// Fração da banda estimada que uma camada pode ocupar
+const HEADROOM: f64 = 0.85;
+
+/// Tempo com banda sobrando antes de subir uma camada
+const UPGRADE_HOLD: Duration = Duration::from_secs(8);
+
+/// Intervalo mínimo entre duas trocas do mesmo viewer
+const MIN_SWITCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Janela da medição de bitrate do stream da câmera
+const RATE_WINDOW: Duration = Duration::from_secs(2);
+
+/// Degrau da escada (sempre H.264, decodificável por qualquer browser)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Rendition {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub fps: f64,
+    /// Bitrate alvo (bits/s)
+    pub bitrate: u32,
+    /// Keyframe a cada `gop` frames (trocas de camada esperam um keyframe)
+    pub gop: u32,
+}
+
+impl Rendition {
+    pub fn from_profile(profile: &MediaProfile) -> Self {
+        Self {
+            name: profile.name.clone(),
+            width: profile.resolution.width,
+            height: profile.resolution.height,
+            fps: profile.fps.as_f64(),
+            bitrate: profile.bitrate_target,
+            gop: profile.gop_size.max(1),
+        }
+    }
+}
+
+/// Degraus dos perfis ativos, do maior bitrate para o menor
+pub fn ladder(profiles: &[MediaProfile]) -> Vec<Rendition> {
+    let mut renditions: Vec<_> = profiles
+        .iter()
+        .filter(|profile| profile.is_active)
+        .map(Rendition::from_profile)
+        .collect();
+    renditions.sort_by_key(|rendition| std::cmp::Reverse(rendition.bitrate));
+    renditions.dedup_by(|a, b| a.width == b.width && a.height == b.height && a.bitrate == b.bitrate);
+    renditions
+}
+
+/// Camada entregue a um viewer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Layer {
+    /// Stream da câmera sem transcodificação (só câmeras H.264)
+    Source,
+    /// Degrau da escada, pelo índice
+    Rendition(usize),
+}
+
+/// Camada de uma câmera, para a API
+#[derive(Debug, Clone, Serialize)]
+pub struct LayerInfo {
+    pub name: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Bitrate alvo, ou o medido no stream da câmera (bits/s)
+    pub bitrate: u64,
+    pub transcoded: bool,
+    /// Transcoder rodando
+    pub active: bool,
+    pub viewers: usize,
+}
+
+/// Escolha de camada de um viewer a partir das estimativas de banda
+#[derive(Debug)]
+pub struct LayerSwitcher {
+    current: usize,
+    /// Desde quando a banda comporta uma camada acima da atual
+    upgradable_since: Option<Instant>,
+    last_switch: Option<Instant>,
+}
+
+impl LayerSwitcher {
+    pub fn new(current: usize) -> Self {
+        Self {
+            current,
+            upgradable_since: None,
+            last_switch: None,
+        }
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Nova estimativa (bits/s); `bitrates` são os das camadas, da maior para
+    /// a menor. Retorna a camada para a qual trocar, se for o caso
+    pub fn observe(&mut self, estimate: u64, bitrates: &[u64], now: Instant) -> Option<usize> {
+        let lowest = bitrates.len().checked_sub(1)?;
+        self.current = self.current.min(lowest);
+
+        let budget = (estimate as f64 * HEADROOM) as u64;
+        let fitting = bitrates.iter().position(|&bitrate| bitrate <= budget).unwrap_or(lowest);
+
+        if fitting >= self.current {
+            self.upgradable_since = None;
+        } else {
+            self.upgradable_since.get_or_insert(now);
+        }
+
+        if self
+            .last_switch
+            .is_some_and(|last| now.duration_since(last) < MIN_SWITCH_INTERVAL)
+        {
+            return None;
+        }
+
+        let target = if fitting > self.current {
+            // Banda não comporta a camada atual: desce direto para a que cabe
+            fitting
+        } else if self
+            .upgradable_since
+            .is_some_and(|since| now.duration_since(since) >= UPGRADE_HOLD)
+        {
+            // Sobe um degrau por vez
+            self.current - 1
+        } else {
+            return None;
+        };
+
+        self.current = target;
+        self.upgradable_since = None;
+        self.last_switch = Some(now);
+        Some(target)
+    }
+}
+
+/// Bitrate medido de um stream
+#[derive(Debug, Default)]
+pub struct RateMeter {
+    window_start: Option<Instant>,
+    bytes: u64,
+    bitrate: u64,
+}
+
+impl RateMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, bytes: usize, now: Instant) {
+        let start = *self.window_start.get_or_insert(now);
+        self.bytes += bytes as u64;
+
+        let elapsed = now.duration_since(start);
+        if elapsed >= RATE_WINDOW {
+            self.bitrate = (self.bytes as f64 * 8.0 / elapsed.as_secs_f64()) as u64;
+            self.bytes = 0;
+            self.window_start = Some(now);
+        }
+    }
+
+    /// Bits/s na última janela completa (0 até completar a primeira)
+    pub fn bitrate(&self) -> u64 {
+        self.bitrate
+    }
+}
+
+/// Bitrate de um REMB: FCI de um PSFB tipo 15 com `"REMB"`, número de SSRCs,
+/// expoente de 6 bits e mantissa de 18 bits
+pub fn parse_remb(fci: &[u8]) -> Option<u64> {
+    if fci.len() < 8 || &fci[..4] != b"REMB" {
+        return None;
+    }
+    let exponent = u32::from(fci[5] >> 2);
+    let mantissa = (u64::from(fci[5] & 0x03) << 16) | (u64::from(fci[6]) << 8) | u64::from(fci[7]);
+    1u64.checked_shl(exponent).and_then(|scale| mantissa.checked_mul(scale))
+}
+
+/// Encoders H.264 suportados
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncoderKind {
+    /// NVENC
+    Nvidia,
+    /// Intel Quick Sync
+    Qsv,
+    /// VA-API (plugin `va`)
+    Va,
+    /// VA-API (plugin legado `vaapi`)
+    Vaapi,
+    X264,
+    OpenH264,
+}
+
+impl EncoderKind {
+    const HARDWARE: [Self; 4] = [Self::Nvidia, Self::Qsv, Self::Va, Self::Vaapi];
+    const SOFTWARE: [Self; 2] = [Self::X264, Self::OpenH264];
+
+    pub fn factory(&self) -> &'static str {
+        match self {
+            Self::Nvidia => "nvh264enc",
+            Self::Qsv => "qsvh264enc",
+            Self::Va => "vah264enc",
+            Self::Vaapi => "vaapih264enc",
+            Self::X264 => "x264enc",
+            Self::OpenH264 => "openh264enc",
+        }
+    }
+
+    pub fn is_hardware(&self) -> bool {
+        Self::HARDWARE.contains(self)
+    }
+
+    /// Encoders a tentar, em ordem, para `StreamingConfig.transcode_encoder`:
+    /// `auto` (hardware se houver), `software` ou o nome de um encoder
+    pub fn candidates(setting: &str) -> Result<Vec<Self>, String> {
+        let candidates = match setting.trim().to_lowercase().as_str() {
+            "auto" => Self::HARDWARE.iter().chain(&Self::SOFTWARE).copied().collect(),
+            "software" => Self::SOFTWARE.to_vec(),
+            "hardware" => Self::HARDWARE.to_vec(),
+            "nvh264" | "nvenc" => vec![Self::Nvidia],
+            "qsv" => vec![Self::Qsv],
+            "va" => vec![Self::Va],
+            "vaapi" => vec![Self::Vaapi],
+            "x264" => vec![Self::X264],
+            "openh264" => vec![Self::OpenH264],
+            other => return Err(format!("Unknown H.264 encoder: {}", other)),
+        };
+        Ok(candidates)
+    }
+
+    /// Propriedades do encoder para o degrau (nome, valor em texto); baixa
+    /// latência, sem B-frames e com keyframe a cada GOP
+    pub fn settings(&self, rendition: &Rendition) -> Vec<(&'static str, String)> {
+        let kbps = (rendition.bitrate / 1000).max(1).to_string();
+        let gop = rendition.gop.to_string();
+        match self {
+            Self::Nvidia => vec![
+                ("bitrate", kbps),
+                ("gop-size", gop),
+                ("bframes", "0".into()),
+                ("zerolatency", "true".into()),
+                ("rc-mode", "cbr".into()),
+            ],
+            Self::Qsv => vec![
+                ("bitrate", kbps),
+                ("gop-size", gop),
+                ("b-frames", "0".into()),
+                ("rate-control", "cbr".into()),
+                ("low-latency", "true".into()),
+            ],
+            Self::Va => vec![
+                ("bitrate", kbps),
+                ("key-int-max", gop),
+                ("b-frames", "0".into()),
+                ("rate-control", "cbr".into()),
+            ],
+            Self::Vaapi => vec![
+                ("bitrate", kbps),
+                ("keyframe-period", gop),
+                ("max-bframes", "0".into()),
+                ("rate-control", "cbr".into()),
+            ],
+            Self::X264 => vec![
+                ("bitrate", kbps),
+                ("key-int-max", gop),
+                ("bframes", "0".into()),
+                ("tune", "zerolatency".into()),
+                ("speed-preset", "veryfast".into()),
+            ],
+            Self::OpenH264 => vec![
+                ("bitrate", rendition.bitrate.to_string()),
+                ("gop-size", gop),
+                ("rate-control", "bitrate".into()),
+                ("complexity", "low".into()),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vms_common::types::Resolution;
+
+    #[test]
+    fn test_ladder_from_profiles() {
+        let mut inactive = MediaProfile::liveview_default();
+        inactive.is_active = false;
+        let profiles = vec![
+            MediaProfile::mobile_default().with_resolution(Resolution::new(640, 360)),
+            MediaProfile::liveview_default().with_resolution(Resolution::HD_720P),
+            inactive,
+        ];
+
+        let ladder = ladder(&profiles);
+        assert_eq!(ladder.len(), 2);
+        assert_eq!((ladder[0].width, ladder[0].height, ladder[0].bitrate), (1280, 720, 2_000_000));
+        assert_eq!((ladder[1].width, ladder[1].height, ladder[1].fps), (640, 360, 15.0));
+        assert_eq!(ladder[1].gop, 30);
+    }
+
+    #[test]
+    fn test_switcher_drops_at_once_and_climbs_slowly() {
+        let bitrates = [4_000_000, 1_500_000, 500_000];
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut switcher = LayerSwitcher::new(0);
+
+        // Banda sobrando para a camada atual: fica
+        assert_eq!(switcher.observe(8_000_000, &bitrates, at(0)), None);
+        // 4G fraco: desce direto para a camada que cabe
+        assert_eq!(switcher.observe(700_000, &bitrates, at(1)), Some(2));
+        // Melhora logo depois de trocar: espera o intervalo mínimo e o tempo de subida
+        assert_eq!(switcher.observe(10_000_000, &bitrates, at(2)), None);
+        assert_eq!(switcher.observe(10_000_000, &bitrates, at(9)), None);
+        assert_eq!(switcher.observe(10_000_000, &bitrates, at(10)), Some(1));
+        assert_eq!(switcher.current(), 1);
+        // Oscilação zera a espera para subir
+        assert_eq!(switcher.observe(10_000_000, &bitrates, at(12)), None);
+        assert_eq!(switcher.observe(1_800_000, &bitrates, at(15)), None);
+        assert_eq!(switcher.observe(10_000_000, &bitrates, at(16)), None);
+        assert_eq!(switcher.observe(10_000_000, &bitrates, at(24)), Some(0));
+
+        // Banda abaixo até da menor camada: fica na menor
+        assert_eq!(switcher.observe(100_000, &bitrates, at(30)), Some(2));
+        assert_eq!(LayerSwitcher::new(0).observe(1, &[], at(0)), None);
+    }
+
+    #[test]
+    fn test_rate_meter() {
+        let start = Instant::now();
+        let mut meter = RateMeter::new();
+        meter.add(100_000, start);
+        meter.add(100_000, start + Duration::from_secs(1));
+        assert_eq!(meter.bitrate(), 0);
+        meter.add(50_000, start + Duration::from_secs(2));
+        assert_eq!(meter.bitrate(), 1_000_000);
+    }
+
+    #[test]
+    fn test_parse_remb() {
+        // 1 SSRC, expoente 3, mantissa 0x1F400 (128000): 1.024 Mbps
+        let fci = [b'R', b'E', b'M', b'B', 1, (3 << 2) | 0x01, 0xF4, 0x00, 0, 0, 0, 1];
+        assert_eq!(parse_remb(&fci), Some(1_024_000));
+        assert_eq!(parse_remb(b"TMMB\x01\x00\x00\x01"), None);
+        assert_eq!(parse_remb(b"REMB"), None);
+        // Expoente máximo não estoura
+        assert_eq!(parse_remb(&[b'R', b'E', b'M', b'B', 1, 0xFF, 0xFF, 0xFF]), None);
+    }
+
+    #[test]
+    fn test_encoder_candidates_and_settings() {
+        let auto = EncoderKind::candidates("auto").unwrap();
+        assert!(auto.first().unwrap().is_hardware());
+        assert_eq!(&auto[auto.len() - 2..], &[EncoderKind::X264, EncoderKind::OpenH264]);
+        assert_eq!(EncoderKind::candidates(" x264 ").unwrap(), vec![EncoderKind::X264]);
+        assert!(EncoderKind::candidates("hevc").is_err());
+
+        let rendition = Rendition::from_profile(&MediaProfile::mobile_default());
+        let x264 = EncoderKind::X264.settings(&rendition);
+        assert!(x264.contains(&("bitrate", "500".to_string())));
+        assert!(x264.contains(&("key-int-max", "30".to_string())));
+        // openh264 recebe bits/s
+        let openh264 = EncoderKind::OpenH264.settings(&rendition);
+        assert!(openh264.contains(&("bitrate", "500000".to_string())));
+    }
+}
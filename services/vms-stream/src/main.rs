@@ -1,173 +1,1043 @@
 //! VMS Stream Service - GStreamer WebRTC Edition
 //! Ultra-low latency video streaming via WebRTC
+//!
+//! Além da sinalização JSON própria, atende WHEP (players padrão) e WHIP
+//! (OBS e encoders publicando uma câmera virtual). Serve também MPEG-TS sobre
+//! SRT e LL-HLS, a partir dos frames do NATS, para salas de controle remotas
+//! e para painéis e audiências grandes.
+//! Com `STREAM_TRANSCODE`, câmeras ganham uma escada de qualidade e cada
+//! viewer WebRTC acompanha a banda estimada do seu link.
+//! O caminho inverso também existe: a voz do operador (WebRTC) ou um clipe
+//! gravado toca no alto-falante da câmera pelo backchannel ONVIF.
+//! Gravações do vms-storage também saem por WebRTC, com seek, pausa,
+//! velocidade e quadro a quadro pelo data channel do player.
 
 use anyhow::{Context, Result};
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    routing::{get, post},
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post},
     Json, Router,
 };
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
+use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
 use tokio::net::TcpListener;
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, Mutex, RwLock};
 use tower_http::cors::{Any, CorsLayer};
-use tracing::{error, info};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+use vms_common::config::StreamingConfig;
+use vms_common::gateway_client::{join_gateway, nats_url, public_url};
+use vms_common::srt::SrtCallTarget;
+use vms_common::{
+    IceCandidateRequest, IceCandidatesResponse, IceServer, NodeOfferRequest, WebRtcAnswerResponse,
+    RTSP_PASSWORD_HEADER, RTSP_URL_HEADER, RTSP_USERNAME_HEADER, SDPFRAG_CONTENT_TYPE, SDP_CONTENT_TYPE,
+};
 
+mod backchannel;
+mod candidates;
+mod fmp4;
+mod gop;
+mod gstreamer_playback;
+mod gstreamer_talk;
 mod gstreamer_webrtc;
+mod hls;
+mod hls_server;
+mod ice;
+mod ladder;
+mod nats_consumer;
+mod playback;
+mod readiness;
+mod srt_server;
+mod srt_streams;
+mod transcode;
+mod ts_mux;
+mod viewers;
+mod whep;
+
+use backchannel::{BackchannelError, TalkError, TalkInfo, TalkKind, TalkRegistry};
+use gstreamer_playback::PlaybackSession;
+use gstreamer_talk::{TalkInput, TalkSession};
+use gstreamer_webrtc::{GstWebRTCSession, Source};
+use hls::{HlsFile, HlsTiming};
+use hls_server::{BlockingReload, HlsError, HlsServer, HlsSettings, HlsStreamInfo};
+use ice::IceConfig;
+use ladder::LayerInfo;
+use nats_consumer::StreamDistributor;
+use playback::{PlaybackError, PlaybackInfo};
+use readiness::ReadinessError;
+use srt_server::{SRTServer, SrtSettings};
+use srt_streams::SrtStream;
+use transcode::TranscodeConfig;
+use viewers::{Viewer, ViewerRegistry};
+
+/// Validade do `peer_id` informada ao viewer (nova oferta depois disso)
+const PEER_TTL_SECS: i64 = 3600;
+
+/// Nome do serviço no registro do vms-gateway
+const SERVICE_NAME: &str = "vms-stream";
+
+/// Porta da API HTTP (sinalização, WHEP/WHIP, SRT, HLS)
+const HTTP_PORT: u16 = 9094;
+
+/// Espera máxima pelo primeiro keyframe da câmera
+const READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Espera máxima do long-poll de candidatos
+const CANDIDATES_MAX_WAIT_MS: u64 = 30_000;
+
+/// Espera máxima pelo SETUP do backchannel de áudio da câmera
+const BACKCHANNEL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Tamanho máximo de um clipe de áudio enviado à câmera
+const CLIP_MAX_BYTES: usize = 10 * 1024 * 1024;
 
-use gstreamer_webrtc::GstWebRTCSession;
+type ApiError = (StatusCode, Json<Value>);
+
+fn api_error(status: StatusCode, message: impl Into<String>) -> ApiError {
+    (status, Json(json!({ "error": message.into() })))
+}
+
+#[derive(Debug, Error)]
+enum SessionError {
+    /// Câmera sem pipeline e sem origem informada na oferta
+    #[error("Camera {0} has no active stream")]
+    NoSource(String),
+
+    /// WHIP numa câmera que já tem pipeline
+    #[error("Camera {0} already has an active stream")]
+    AlreadyStreaming(String),
+}
 
 /// Application state
 struct AppState {
-    sessions: RwLock<HashMap<String, GstWebRTCSession>>,
+    /// Pipeline por câmera (uma conexão RTSP, compartilhada pelos viewers)
+    sessions: RwLock<HashMap<String, Arc<GstWebRTCSession>>>,
+    viewers: Mutex<ViewerRegistry>,
+    /// Viewers (e publicadores WHIP) cuja conexão caiu sem DELETE
+    departures: mpsc::UnboundedSender<Uuid>,
+    /// STUN/TURN entregues ao webrtcbin e aos browsers
+    ice: IceConfig,
+    /// Publicadores WHIP ativos (recurso → câmera)
+    publishers: Mutex<HashMap<Uuid, String>>,
+    /// Token exigido dos publicadores WHIP (`STREAM_WHIP_TOKEN`)
+    whip_token: Option<String>,
+    /// Servidor SRT (desligado sem NATS)
+    srt: Option<Arc<SRTServer>>,
+    /// Servidor LL-HLS (desligado sem NATS)
+    hls: Option<Arc<HlsServer>>,
+    /// Escada de transcodificação (`STREAM_TRANSCODE`)
+    transcode: Option<Arc<TranscodeConfig>>,
+    /// Alto-falantes em uso (um áudio por câmera)
+    talks: Mutex<TalkRegistry>,
+    /// Pipelines de áudio para as câmeras, por sessão
+    talk_sessions: Mutex<HashMap<Uuid, Arc<TalkSession>>>,
+    /// vms-storage de onde saem as gravações (`STREAM_STORAGE_URL`)
+    storage_url: String,
+    /// Sessões de playback de gravações
+    playbacks: Mutex<HashMap<Uuid, Arc<PlaybackSession>>>,
 }
 
 #[derive(Debug, Deserialize)]
-struct WebRTCOfferRequest {
-    camera_id: String,
-    rtsp_url: String,
-    username: String,
-    password: String,
-    sdp: String,
-    #[serde(rename = "type")]
-    sdp_type: String,
+struct HlsQuery {
+    token: Option<String>,
+    /// Reload bloqueante (LL-HLS)
+    #[serde(rename = "_HLS_msn")]
+    msn: Option<u64>,
+    #[serde(rename = "_HLS_part")]
+    part: Option<usize>,
 }
 
-#[derive(Debug, Serialize)]
-struct WebRTCOfferResponse {
-    sdp: String,
-    #[serde(rename = "type")]
-    sdp_type: String,
+#[derive(Debug, Deserialize)]
+struct PlaybackQuery {
+    /// Instante inicial da gravação (RFC 3339)
+    start: chrono::DateTime<chrono::Utc>,
 }
 
 #[derive(Debug, Deserialize)]
-struct ICECandidateRequest {
-    camera_id: String,
-    candidate: String,
-    #[serde(rename = "sdpMid")]
-    sdp_mid: Option<String>,
-    #[serde(rename = "sdpMLineIndex")]
-    sdp_mline_index: Option<u16>,
+struct CandidatesQuery {
+    /// Cursor (`next` da resposta anterior)
+    #[serde(default)]
+    since: usize,
+    timeout_ms: Option<u64>,
 }
 
-/// Handle WebRTC offer from browser
+/// Handle WebRTC offer from browser: each offer becomes a new viewer peer
 async fn webrtc_offer_handler(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<WebRTCOfferRequest>,
-) -> Result<Json<WebRTCOfferResponse>, StatusCode> {
+    Json(req): Json<NodeOfferRequest>,
+) -> Result<Json<WebRtcAnswerResponse>, ApiError> {
     info!("📡 WebRTC offer received for camera: {}", req.camera_id);
-    
-    // Check if session already exists
-    {
-        let sessions = state.sessions.read().await;
-        if let Some(existing_session) = sessions.get(&req.camera_id) {
-            info!("♻️ Session exists for camera: {}, using existing pipeline", req.camera_id);
-            
-            // Use existing session to process the offer
-            let answer = existing_session.handle_offer(req.sdp).map_err(|e| {
-                error!("Failed to handle SDP offer with existing session: {}", e);
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
-            
-            return Ok(Json(WebRTCOfferResponse {
-                sdp: answer,
-                sdp_type: "answer".to_string(),
-            }));
-        }
-    }
-    
+
+    let source = Source::Rtsp {
+        url: req.rtsp_url,
+        username: req.username,
+        password: req.password,
+    };
+    let (peer_id, answer) = join(&state, &req.camera_id, Some(source), req.sdp, true).await?;
+    Ok(Json(WebRtcAnswerResponse {
+        sdp: answer,
+        sdp_type: "answer".to_string(),
+        peer_id,
+        expires_at: chrono::Utc::now().timestamp() + PEER_TTL_SECS,
+        rtp_port: 0,
+    }))
+}
+
+/// Reserves a viewer slot and negotiates; the slot is released on failure
+async fn join(
+    state: &AppState,
+    camera_id: &str,
+    source: Option<Source>,
+    sdp: String,
+    trickle: bool,
+) -> Result<(Uuid, String), ApiError> {
+    // Reserva a vaga antes de montar qualquer coisa no pipeline
+    let peer_id = state.viewers.lock().await.join(camera_id).map_err(|e| {
+        warn!("🚫 {}", e);
+        api_error(StatusCode::SERVICE_UNAVAILABLE, e.to_string())
+    })?;
+
+    match join_camera(state, camera_id, source, sdp, peer_id, trickle).await {
+        Ok(answer) => {
+            info!("✅ Viewer {} joined camera: {}", peer_id, camera_id);
+            Ok((peer_id, answer))
+        }
+        Err(e) => {
+            error!("Failed to set up viewer {} for camera {}: {:#}", peer_id, camera_id, e);
+            leave(state, peer_id).await;
+            Err(api_error(offer_status(&e), e.to_string()))
+        }
+    }
+}
+
+fn offer_status(e: &anyhow::Error) -> StatusCode {
+    if let Some(e) = e.downcast_ref::<SessionError>() {
+        return match e {
+            SessionError::NoSource(_) => StatusCode::NOT_FOUND,
+            SessionError::AlreadyStreaming(_) => StatusCode::CONFLICT,
+        };
+    }
+    // Câmera que não entregou vídeo é falha de upstream, não do serviço
+    match e.downcast_ref::<ReadinessError>() {
+        Some(ReadinessError::Timeout(_)) => StatusCode::GATEWAY_TIMEOUT,
+        Some(ReadinessError::Failed(_)) => StatusCode::BAD_GATEWAY,
+        None => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Adds the viewer to the camera pipeline (creating it if needed) and negotiates
+async fn join_camera(
+    state: &AppState,
+    camera_id: &str,
+    source: Option<Source>,
+    sdp: String,
+    peer_id: Uuid,
+    trickle: bool,
+) -> Result<String> {
+    let session = camera_session(state, camera_id, source).await?;
+
+    // Espera o vídeo de fato chegar (pad RTSP ligado + primeiro keyframe);
+    // imediato quando o pipeline já está rodando
+    let started = std::time::Instant::now();
+    session.wait_ready(READY_TIMEOUT).await?;
+    info!(
+        "🎞️ Camera {} ready after {:?} ({} frames cached)",
+        camera_id,
+        started.elapsed(),
+        session.cached_frames()
+    );
+
+    session.add_peer(peer_id)?;
+    session.handle_offer(peer_id, sdp, trickle).await
+}
+
+/// Pipeline da câmera, criado no primeiro viewer (sem `source`, só serve
+/// uma câmera já ativa, como as publicadas via WHIP)
+async fn camera_session(state: &AppState, camera_id: &str, source: Option<Source>) -> Result<Arc<GstWebRTCSession>> {
+    if let Some(session) = state.sessions.read().await.get(camera_id) {
+        info!("♻️ Pipeline exists for camera: {}, adding viewer", camera_id);
+        return Ok(session.clone());
+    }
+
     // Take write lock for new session creation
     let mut sessions = state.sessions.write().await;
     // Double-check (another request might have won the race)
-    if let Some(existing_session) = sessions.get(&req.camera_id) {
-        info!("♻️ Session created by another request, using it");
-        let answer = existing_session.handle_offer(req.sdp).map_err(|e| {
-            error!("Failed to handle SDP offer: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-        return Ok(Json(WebRTCOfferResponse {
-            sdp: answer,
-            sdp_type: "answer".to_string(),
-        }));
-    }
-    
-    info!("📹 Creating session for RTSP URL: {} (holding lock)", req.rtsp_url);
-    
-    // Create GStreamer session while holding write lock
+    if let Some(session) = sessions.get(camera_id) {
+        info!("♻️ Pipeline created by another request, using it");
+        return Ok(session.clone());
+    }
+
+    let Some(source) = source else {
+        return Err(SessionError::NoSource(camera_id.to_string()).into());
+    };
+    if let Source::Rtsp { url, .. } = &source {
+        info!("📹 Creating session for RTSP URL: {} (holding lock)", url);
+    }
+
     let session = GstWebRTCSession::new(
-        req.camera_id.clone(),
-        &req.rtsp_url,
-        &req.username,
-        &req.password,
-    ).map_err(|e| {
-        error!("Failed to create GStreamer session: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    
-    // Insert session immediately to prevent other requests from creating
-    sessions.insert(req.camera_id.clone(), session);
-    
-    // Get mutable reference to the session we just inserted
-    let session = sessions.get(&req.camera_id).unwrap();
-    
+        camera_id.to_string(),
+        source,
+        state.ice.clone(),
+        state.transcode.clone(),
+        state.departures.clone(),
+    )?;
+
     // Start pipeline FIRST - webrtcbin needs PLAYING state for negotiation
-    session.start().map_err(|e| {
-        error!("Failed to start pipeline: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
+    session.start().context("Failed to start pipeline")?;
+    sessions.insert(camera_id.to_string(), session.clone());
+
+    Ok(session)
+}
+
+/// Releases a viewer; the camera pipeline stops with its last viewer
+/// (unless it is fed by a WHIP publisher, which outlives its viewers)
+async fn leave(state: &AppState, peer_id: Uuid) -> bool {
+    // Mantém o registro travado até decidir o destino do pipeline, para que um
+    // novo viewer não entre num pipeline que está sendo parado
+    let mut viewers = state.viewers.lock().await;
+    let Some((camera_id, remaining)) = viewers.leave(peer_id) else {
+        return false;
+    };
+
+    let mut sessions = state.sessions.write().await;
+    if let Some(session) = sessions.get(&camera_id) {
+        session.remove_peer(peer_id);
+        if remaining == 0 && session.publisher_id().is_none() {
+            if let Some(session) = sessions.remove(&camera_id) {
+                let _ = session.stop();
+            }
+            info!("💤 Last viewer left camera: {}, pipeline stopped", camera_id);
+        }
+    }
+    true
+}
+
+/// Pipeline da câmera em que o viewer está
+async fn peer_session(state: &AppState, peer_id: Uuid) -> Result<Arc<GstWebRTCSession>, ApiError> {
+    let camera_id = state
+        .viewers
+        .lock()
+        .await
+        .camera_of(peer_id)
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Viewer not found"))?;
+    state
+        .sessions
+        .read()
+        .await
+        .get(&camera_id)
+        .cloned()
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Viewer not found"))
+}
+
+/// Handle ICE candidate from browser (trickle ICE)
+async fn webrtc_ice_handler(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<IceCandidateRequest>,
+) -> Result<StatusCode, ApiError> {
+    info!("🧊 ICE candidate received for viewer: {}", req.peer_id);
+
+    let session = peer_session(&state, req.peer_id).await?;
+    session
+        .add_ice_candidate(req.peer_id, req.sdp_m_line_index.unwrap_or(0).into(), &req.candidate)
+        .map_err(|e| api_error(StatusCode::NOT_FOUND, e.to_string()))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Server ICE candidates for a viewer (long-poll from `since`)
+async fn webrtc_candidates_handler(
+    State(state): State<Arc<AppState>>,
+    Path(peer_id): Path<Uuid>,
+    Query(query): Query<CandidatesQuery>,
+) -> Result<Json<IceCandidatesResponse>, ApiError> {
+    let candidates = peer_session(&state, peer_id)
+        .await?
+        .candidates(peer_id)
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Viewer not found"))?;
+
+    let timeout_ms = query.timeout_ms.unwrap_or(CANDIDATES_MAX_WAIT_MS).min(CANDIDATES_MAX_WAIT_MS);
+    Ok(Json(
+        candidates
+            .wait(query.since, Duration::from_millis(timeout_ms))
+            .await,
+    ))
+}
+
+/// STUN/TURN servers for the browser `RTCPeerConnection`
+async fn webrtc_ice_servers_handler(State(state): State<Arc<AppState>>) -> Json<Vec<IceServer>> {
+    Json(state.ice.browser_servers())
+}
+
+/// Viewer leaves (other viewers of the camera are not affected)
+async fn webrtc_peer_close_handler(
+    State(state): State<Arc<AppState>>,
+    Path(peer_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    info!("🔴 Closing WebRTC viewer: {}", peer_id);
+
+    if leave(&state, peer_id).await {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(api_error(StatusCode::NOT_FOUND, "Viewer not found"))
+    }
+}
+
+/// Rejects bodies that are not of the expected media type
+fn check_content_type(headers: &HeaderMap, expected: &str) -> Result<(), ApiError> {
+    let value = headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok());
+    if whep::is_content_type(value, expected) {
+        Ok(())
+    } else {
+        Err(api_error(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            format!("Expected Content-Type: {}", expected),
+        ))
+    }
+}
+
+/// WHEP/WHIP `201 Created`: answer SDP, session resource and ICE servers
+fn sdp_created(state: &AppState, location: String, answer: String) -> Response {
+    let mut response = (
+        StatusCode::CREATED,
+        [(header::CONTENT_TYPE, SDP_CONTENT_TYPE)],
+        answer,
+    )
+        .into_response();
+    let headers = response.headers_mut();
+    if let Ok(location) = HeaderValue::from_str(&location) {
+        headers.insert(header::LOCATION, location);
+    }
+    for link in whep::ice_server_links(&state.ice.browser_servers()) {
+        if let Ok(link) = HeaderValue::from_str(&link) {
+            headers.append(header::LINK, link);
+        }
+    }
+    response
+}
+
+/// Client candidates from a `trickle-ice-sdpfrag` PATCH, handed to `add`
+fn trickle(
+    peer_id: Uuid,
+    headers: &HeaderMap,
+    body: &str,
+    add: impl Fn(u32, &str) -> Result<()>,
+) -> Result<StatusCode, ApiError> {
+    check_content_type(headers, SDPFRAG_CONTENT_TYPE)?;
+    let candidates = whep::parse_sdpfrag(body).map_err(|e| api_error(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    info!("🧊 {} ICE candidate(s) received for peer: {}", candidates.len(), peer_id);
+    for candidate in candidates {
+        add(candidate.sdp_m_line_index, &candidate.candidate)
+            .map_err(|e| api_error(StatusCode::NOT_FOUND, e.to_string()))?;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Camera RTSP source sent by vms-api in the WHEP/talk headers
+fn rtsp_source(headers: &HeaderMap) -> Option<Source> {
+    let value = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(String::from);
+    value(RTSP_URL_HEADER).map(|url| Source::Rtsp {
+        url,
+        username: value(RTSP_USERNAME_HEADER).unwrap_or_default(),
+        password: value(RTSP_PASSWORD_HEADER).unwrap_or_default(),
+    })
+}
+
+/// WHEP offer: plain SDP in, answer with the server candidates out
+async fn whep_offer_handler(
+    State(state): State<Arc<AppState>>,
+    Path(camera_id): Path<String>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Response, ApiError> {
+    check_content_type(&headers, SDP_CONTENT_TYPE)?;
+    info!("📡 WHEP offer received for camera: {}", camera_id);
+
+    // Origem enviada pelo vms-api; sem ela só serve câmera já ativa
+    let source = rtsp_source(&headers);
+    let (peer_id, answer) = join(&state, &camera_id, source, body, false).await?;
+    Ok(sdp_created(&state, format!("/api/v1/whep/{}/{}", camera_id, peer_id), answer))
+}
+
+/// WHEP trickle ICE from the player
+async fn whep_patch_handler(
+    State(state): State<Arc<AppState>>,
+    Path((_camera_id, peer_id)): Path<(String, Uuid)>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<StatusCode, ApiError> {
+    let session = peer_session(&state, peer_id).await?;
+    trickle(peer_id, &headers, &body, |mline, candidate| {
+        session.add_ice_candidate(peer_id, mline, candidate)
+    })
+}
+
+/// WHEP session teardown
+async fn whep_delete_handler(
+    State(state): State<Arc<AppState>>,
+    Path((_camera_id, peer_id)): Path<(String, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    info!("🔴 Closing WHEP viewer: {}", peer_id);
+
+    if leave(&state, peer_id).await {
+        Ok(StatusCode::OK)
+    } else {
+        Err(api_error(StatusCode::NOT_FOUND, "Viewer not found"))
+    }
+}
+
+/// Bearer token of WHIP requests, when one is configured
+fn check_whip_token(state: &AppState, headers: &HeaderMap) -> Result<(), ApiError> {
+    let Some(expected) = &state.whip_token else {
+        return Ok(());
+    };
+    let authorization = headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok());
+    if whep::bearer_token(authorization) == Some(expected.as_str()) {
+        Ok(())
+    } else {
+        Err(api_error(StatusCode::UNAUTHORIZED, "Invalid WHIP token"))
+    }
+}
+
+/// Pipeline alimentado por um publicador WHIP (a câmera não pode ter outra
+/// origem ativa)
+async fn publisher_session(state: &AppState, camera_id: &str) -> Result<(Uuid, Arc<GstWebRTCSession>)> {
+    let mut sessions = state.sessions.write().await;
+    if sessions.contains_key(camera_id) {
+        return Err(SessionError::AlreadyStreaming(camera_id.to_string()).into());
+    }
+
+    let session = GstWebRTCSession::new(
+        camera_id.to_string(),
+        Source::Whip,
+        state.ice.clone(),
+        state.transcode.clone(),
+        state.departures.clone(),
+    )?;
+    let resource_id = session.publisher_id().context("WHIP session without publisher")?;
+
+    session.start().context("Failed to start pipeline")?;
+    sessions.insert(camera_id.to_string(), session.clone());
+    state.publishers.lock().await.insert(resource_id, camera_id.to_string());
+
+    Ok((resource_id, session))
+}
+
+/// Ends a WHIP publication: the camera goes offline along with its viewers
+async fn unpublish(state: &AppState, resource_id: Uuid) -> bool {
+    let mut viewers = state.viewers.lock().await;
+    let mut sessions = state.sessions.write().await;
+    let Some(camera_id) = state.publishers.lock().await.remove(&resource_id) else {
+        return false;
+    };
+
+    let peers = viewers.remove_camera(&camera_id);
+    if let Some(session) = sessions.remove(&camera_id) {
+        let _ = session.stop();
+    }
+    info!(
+        "📴 WHIP publisher {} left camera: {} ({} viewer(s) disconnected)",
+        resource_id,
+        camera_id,
+        peers.len()
+    );
+    true
+}
+
+/// WHIP publish: the camera is fed by the publisher's WebRTC stream
+async fn whip_publish_handler(
+    State(state): State<Arc<AppState>>,
+    Path(camera_id): Path<String>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Response, ApiError> {
+    check_whip_token(&state, &headers)?;
+    check_content_type(&headers, SDP_CONTENT_TYPE)?;
+    info!("📥 WHIP publish request for camera: {}", camera_id);
+
+    let (resource_id, session) = publisher_session(&state, &camera_id).await.map_err(|e| {
+        warn!("🚫 {:#}", e);
+        api_error(offer_status(&e), e.to_string())
     })?;
-    
-    // Drop write lock before sleeping to not block other cameras
-    drop(sessions);
-    
-    // Wait for RTSP to connect - GLib main loop thread handles event processing
-    info!("⏳ Waiting 3s for RTSP connection...");
-    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
-    
-    // Re-acquire read lock to get session
-    let sessions = state.sessions.read().await;
-    let session = sessions.get(&req.camera_id).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
-    
-    // Handle SDP offer and get answer
-    let answer = session.handle_offer(req.sdp).map_err(|e| {
-        error!("Failed to handle SDP offer: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
+
+    match session.publish(body).await {
+        Ok(answer) => {
+            info!("✅ WHIP publisher {} live on camera: {}", resource_id, camera_id);
+            Ok(sdp_created(
+                &state,
+                format!("/api/v1/whip/{}/{}", camera_id, resource_id),
+                answer,
+            ))
+        }
+        Err(e) => {
+            error!("Failed to negotiate WHIP publisher for camera {}: {:#}", camera_id, e);
+            unpublish(&state, resource_id).await;
+            Err(api_error(StatusCode::BAD_REQUEST, e.to_string()))
+        }
+    }
+}
+
+/// Pipeline of an active WHIP publisher
+async fn publisher_camera(state: &AppState, resource_id: Uuid) -> Result<Arc<GstWebRTCSession>, ApiError> {
+    let camera_id = state
+        .publishers
+        .lock()
+        .await
+        .get(&resource_id)
+        .cloned()
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Publisher not found"))?;
+    state
+        .sessions
+        .read()
+        .await
+        .get(&camera_id)
+        .cloned()
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Publisher not found"))
+}
+
+/// WHIP trickle ICE from the publisher
+async fn whip_patch_handler(
+    State(state): State<Arc<AppState>>,
+    Path((_camera_id, resource_id)): Path<(String, Uuid)>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<StatusCode, ApiError> {
+    check_whip_token(&state, &headers)?;
+    let session = publisher_camera(&state, resource_id).await?;
+    trickle(resource_id, &headers, &body, |mline, candidate| {
+        session.add_ice_candidate(resource_id, mline, candidate)
+    })
+}
+
+/// WHIP publication teardown
+async fn whip_delete_handler(
+    State(state): State<Arc<AppState>>,
+    Path((_camera_id, resource_id)): Path<(String, Uuid)>,
+    headers: HeaderMap,
+) -> Result<StatusCode, ApiError> {
+    check_whip_token(&state, &headers)?;
+
+    if unpublish(&state, resource_id).await {
+        Ok(StatusCode::OK)
+    } else {
+        Err(api_error(StatusCode::NOT_FOUND, "Publisher not found"))
+    }
+}
+
+/// Claims the camera speaker and opens its backchannel; the claim is
+/// released on failure
+async fn start_talk(
+    state: &AppState,
+    camera_id: &str,
+    headers: &HeaderMap,
+    input: TalkInput,
+) -> Result<(Uuid, Arc<TalkSession>), ApiError> {
+    let source = rtsp_source(headers).ok_or_else(|| {
+        api_error(
+            StatusCode::BAD_REQUEST,
+            format!("Missing {} header with the camera RTSP source", RTSP_URL_HEADER),
+        )
     })?;
-    
-    info!("✅ WebRTC session ready for camera: {}", req.camera_id);
-    
-    Ok(Json(WebRTCOfferResponse {
-        sdp: answer,
-        sdp_type: "answer".to_string(),
-    }))
+    let kind = match input {
+        TalkInput::WebRtc => TalkKind::Live,
+        TalkInput::Clip(_) => TalkKind::Clip,
+    };
+    let talk_id = state.talks.lock().await.claim(camera_id, kind).map_err(|e| {
+        warn!("🚫 {}", e);
+        match e {
+            TalkError::Busy { .. } => api_error(StatusCode::CONFLICT, e.to_string()),
+        }
+    })?;
+
+    let opened: Result<Arc<TalkSession>, ApiError> = async {
+        let session = TalkSession::new(
+            talk_id,
+            camera_id.to_string(),
+            source,
+            input,
+            &state.ice,
+            state.departures.clone(),
+        )
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        state.talk_sessions.lock().await.insert(talk_id, session.clone());
+        session
+            .start()
+            .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        // Câmera sem backchannel (ou que recusou o `Require`) é falha de upstream
+        session.open(BACKCHANNEL_TIMEOUT).await.map_err(|e| {
+            let status = match e {
+                BackchannelError::Timeout => StatusCode::GATEWAY_TIMEOUT,
+                BackchannelError::Unsupported | BackchannelError::Failed(_) => StatusCode::BAD_GATEWAY,
+            };
+            api_error(status, e.to_string())
+        })?;
+        Ok(session)
+    }
+    .await;
+
+    match opened {
+        Ok(session) => Ok((talk_id, session)),
+        Err(e) => {
+            warn!("⚠️ Talk on camera {} failed ({})", camera_id, e.0);
+            end_talk(state, talk_id).await;
+            Err(e)
+        }
+    }
 }
 
-/// Handle ICE candidate from browser
-async fn webrtc_ice_handler(
-    State(_state): State<Arc<AppState>>,
-    Json(req): Json<ICECandidateRequest>,
-) -> StatusCode {
-    info!("🧊 ICE candidate received for camera: {}", req.camera_id);
-    // ICE candidates are handled by GStreamer automatically via trickle ICE
-    StatusCode::OK
+/// Ends a talk session and frees the camera speaker
+async fn end_talk(state: &AppState, talk_id: Uuid) -> bool {
+    let Some(talk) = state.talks.lock().await.release(talk_id) else {
+        return false;
+    };
+    if let Some(session) = state.talk_sessions.lock().await.remove(&talk_id) {
+        let _ = session.stop();
+    }
+    info!("🔇 Talk session {} ended on camera: {}", talk_id, talk.camera_id);
+    true
+}
+
+async fn talk_session(state: &AppState, talk_id: Uuid) -> Result<Arc<TalkSession>, ApiError> {
+    state
+        .talk_sessions
+        .lock()
+        .await
+        .get(&talk_id)
+        .cloned()
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Talk session not found"))
+}
+
+/// Operator talk-down: the browser's audio goes to the camera speaker and
+/// the camera microphone comes back
+async fn talk_offer_handler(
+    State(state): State<Arc<AppState>>,
+    Path(camera_id): Path<String>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Response, ApiError> {
+    check_content_type(&headers, SDP_CONTENT_TYPE)?;
+    info!("🎙️ Talk offer received for camera: {}", camera_id);
+
+    let (talk_id, session) = start_talk(&state, &camera_id, &headers, TalkInput::WebRtc).await?;
+    match session.answer(body).await {
+        Ok(answer) => {
+            info!("✅ Operator talking on camera: {} (session {})", camera_id, talk_id);
+            Ok(sdp_created(&state, format!("/api/v1/talk/{}/{}", camera_id, talk_id), answer))
+        }
+        Err(e) => {
+            error!("Failed to negotiate talk session for camera {}: {:#}", camera_id, e);
+            end_talk(&state, talk_id).await;
+            Err(api_error(StatusCode::BAD_REQUEST, e.to_string()))
+        }
+    }
+}
+
+/// Plays an uploaded audio clip on the camera speaker (rule warnings)
+async fn talk_clip_handler(
+    State(state): State<Arc<AppState>>,
+    Path(camera_id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    if body.is_empty() {
+        return Err(api_error(StatusCode::BAD_REQUEST, "Empty audio clip"));
+    }
+    info!("📼 Audio clip received for camera: {} ({} bytes)", camera_id, body.len());
+
+    let (talk_id, _) = start_talk(&state, &camera_id, &headers, TalkInput::Clip(body)).await?;
+    Ok((StatusCode::ACCEPTED, Json(json!({ "id": talk_id }))))
+}
+
+/// Talk trickle ICE from the operator
+async fn talk_patch_handler(
+    State(state): State<Arc<AppState>>,
+    Path((_camera_id, talk_id)): Path<(String, Uuid)>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<StatusCode, ApiError> {
+    let session = talk_session(&state, talk_id).await?;
+    trickle(talk_id, &headers, &body, |mline, candidate| {
+        session.add_ice_candidate(mline, candidate)
+    })
+}
+
+/// Stops a talk (or a clip still playing)
+async fn talk_delete_handler(
+    State(state): State<Arc<AppState>>,
+    Path((_camera_id, talk_id)): Path<(String, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    if end_talk(&state, talk_id).await {
+        Ok(StatusCode::OK)
+    } else {
+        Err(api_error(StatusCode::NOT_FOUND, "Talk session not found"))
+    }
 }
 
-/// Close WebRTC session
+/// Camera speakers in use
+async fn talks_handler(State(state): State<Arc<AppState>>) -> Json<Vec<TalkInfo>> {
+    Json(state.talks.lock().await.list())
+}
+
+/// Ends a playback session
+async fn end_playback(state: &AppState, playback_id: Uuid) -> bool {
+    let Some(session) = state.playbacks.lock().await.remove(&playback_id) else {
+        return false;
+    };
+    let _ = session.stop();
+    info!("⏹️ Playback session {} ended on camera: {}", playback_id, session.info().camera_id);
+    true
+}
+
+async fn playback_session(state: &AppState, playback_id: Uuid) -> Result<Arc<PlaybackSession>, ApiError> {
+    state
+        .playbacks
+        .lock()
+        .await
+        .get(&playback_id)
+        .cloned()
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Playback session not found"))
+}
+
+/// Recorded footage over WebRTC from `start`, controlled over the player's
+/// `control` data channel
+async fn playback_offer_handler(
+    State(state): State<Arc<AppState>>,
+    Path(camera_id): Path<String>,
+    Query(query): Query<PlaybackQuery>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Response, ApiError> {
+    check_content_type(&headers, SDP_CONTENT_TYPE)?;
+    if query.start > chrono::Utc::now() {
+        return Err(api_error(StatusCode::BAD_REQUEST, "Playback start is in the future"));
+    }
+    info!("📼 Playback offer received for camera: {} at {}", camera_id, query.start);
+
+    let playback_id = Uuid::new_v4();
+    let session = PlaybackSession::new(
+        playback_id,
+        camera_id.clone(),
+        &state.storage_url,
+        query.start,
+        &state.ice,
+        state.departures.clone(),
+    )
+    .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    state.playbacks.lock().await.insert(playback_id, session.clone());
+
+    let answered: Result<String, ApiError> = async {
+        session
+            .start()
+            .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        // Sem gravação naquele instante é 404; vms-storage fora do ar, upstream
+        session.open().await.map_err(|e| {
+            let status = match e {
+                PlaybackError::NotRecorded { .. } => StatusCode::NOT_FOUND,
+                _ => StatusCode::BAD_GATEWAY,
+            };
+            api_error(status, e.to_string())
+        })?;
+        session
+            .answer(body)
+            .await
+            .map_err(|e| api_error(StatusCode::BAD_REQUEST, e.to_string()))
+    }
+    .await;
+
+    match answered {
+        Ok(answer) => {
+            info!("✅ Playback {} of camera {} ready", playback_id, camera_id);
+            Ok(sdp_created(&state, format!("/api/v1/playback/{}/{}", camera_id, playback_id), answer))
+        }
+        Err(e) => {
+            warn!("⚠️ Playback of camera {} failed ({})", camera_id, e.0);
+            end_playback(&state, playback_id).await;
+            Err(e)
+        }
+    }
+}
+
+/// Playback trickle ICE from the player
+async fn playback_patch_handler(
+    State(state): State<Arc<AppState>>,
+    Path((_camera_id, playback_id)): Path<(String, Uuid)>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<StatusCode, ApiError> {
+    let session = playback_session(&state, playback_id).await?;
+    trickle(playback_id, &headers, &body, |mline, candidate| {
+        session.add_ice_candidate(mline, candidate)
+    })
+}
+
+async fn playback_delete_handler(
+    State(state): State<Arc<AppState>>,
+    Path((_camera_id, playback_id)): Path<(String, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    if end_playback(&state, playback_id).await {
+        Ok(StatusCode::OK)
+    } else {
+        Err(api_error(StatusCode::NOT_FOUND, "Playback session not found"))
+    }
+}
+
+/// Playback sessions in progress
+async fn playbacks_handler(State(state): State<Arc<AppState>>) -> Json<Vec<PlaybackInfo>> {
+    let mut playbacks: Vec<PlaybackInfo> = state.playbacks.lock().await.values().map(|session| session.info()).collect();
+    playbacks.sort_by_key(|playback| playback.started_at);
+    Json(playbacks)
+}
+
+/// Viewers connected to a camera
+async fn webrtc_viewers_handler(
+    State(state): State<Arc<AppState>>,
+    Path(camera_id): Path<String>,
+) -> Json<Vec<Viewer>> {
+    Json(state.viewers.lock().await.viewers_of(&camera_id))
+}
+
+/// Quality layers of a camera (its own stream plus the transcoded ladder)
+async fn webrtc_layers_handler(
+    State(state): State<Arc<AppState>>,
+    Path(camera_id): Path<String>,
+) -> Result<Json<Vec<LayerInfo>>, ApiError> {
+    let sessions = state.sessions.read().await;
+    let session = sessions
+        .get(&camera_id)
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Camera is not streaming"))?;
+    Ok(Json(session.layers()))
+}
+
+/// Close WebRTC session (all viewers of the camera)
 async fn webrtc_close_handler(
     State(state): State<Arc<AppState>>,
     Path(camera_id): Path<String>,
 ) -> StatusCode {
     info!("🔴 Closing WebRTC session for camera: {}", camera_id);
-    
+
+    let mut viewers = state.viewers.lock().await;
+    let peers = viewers.remove_camera(&camera_id);
     if let Some(session) = state.sessions.write().await.remove(&camera_id) {
         let _ = session.stop();
     }
-    
+    state.publishers.lock().await.retain(|_, camera| *camera != camera_id);
+    info!("🔴 {} viewer(s) disconnected from camera: {}", peers.len(), camera_id);
+
     StatusCode::NO_CONTENT
 }
 
+fn srt_server(state: &AppState) -> Result<&Arc<SRTServer>, ApiError> {
+    state
+        .srt
+        .as_ref()
+        .ok_or_else(|| api_error(StatusCode::SERVICE_UNAVAILABLE, "SRT server disabled"))
+}
+
+/// SRT connections with their latest statistics
+async fn srt_streams_handler(State(state): State<Arc<AppState>>) -> Result<Json<Vec<SrtStream>>, ApiError> {
+    Ok(Json(srt_server(&state)?.streams().await))
+}
+
+/// Push a camera to a remote SRT listener (caller mode), as issued by vms-api
+async fn srt_caller_handler(
+    State(state): State<Arc<AppState>>,
+    Json(target): Json<SrtCallTarget>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let server = srt_server(&state)?.clone();
+    if let Err(e) = server.authorize_call(&target) {
+        warn!("🚫 SRT push of camera {} to {} refused: {}", target.camera_id, target.address, e);
+        return Err(api_error(StatusCode::UNAUTHORIZED, e.to_string()));
+    }
+    info!("📡 SRT push of camera {} to {}", target.camera_id, target.address);
+
+    let address = target.address.clone();
+    match server.call(target).await {
+        Ok(id) => Ok((StatusCode::CREATED, Json(json!({ "id": id })))),
+        Err(e) => {
+            warn!("⚠️ SRT push to {} failed: {:#}", address, e);
+            Err(api_error(StatusCode::BAD_GATEWAY, e.to_string()))
+        }
+    }
+}
+
+/// Close an SRT connection
+async fn srt_stop_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    if srt_server(&state)?.stop(id).await {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(api_error(StatusCode::NOT_FOUND, "SRT stream not found"))
+    }
+}
+
+fn hls_server(state: &AppState) -> Result<&Arc<HlsServer>, ApiError> {
+    state
+        .hls
+        .as_ref()
+        .ok_or_else(|| api_error(StatusCode::SERVICE_UNAVAILABLE, "HLS server disabled"))
+}
+
+/// LL-HLS playlist, init segment, segments and parts of a camera
+async fn hls_file_handler(
+    State(state): State<Arc<AppState>>,
+    Path((camera_id, name)): Path<(String, String)>,
+    Query(query): Query<HlsQuery>,
+) -> Result<Response, ApiError> {
+    let server = hls_server(&state)?;
+    let file = HlsFile::parse(&name).ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Unknown HLS file"))?;
+    let hls_status = |e: HlsError| {
+        let status = match e {
+            HlsError::Unauthorized(_) => StatusCode::FORBIDDEN,
+            HlsError::InvalidCamera(_) | HlsError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            HlsError::Gone(_) => StatusCode::NOT_FOUND,
+            HlsError::Timeout(_) | HlsError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        };
+        api_error(status, e.to_string())
+    };
+
+    let camera = server
+        .authorize(&camera_id, query.token.as_deref())
+        .map_err(|e| {
+            warn!("🚫 HLS request for camera {} rejected: {}", camera_id, e);
+            hls_status(e)
+        })?;
+    // O token segue em cada URI da playlist
+    let uri_query = query.token.as_ref().map(|token| format!("?token={}", token)).unwrap_or_default();
+    let reload = BlockingReload {
+        msn: query.msn,
+        part: query.part,
+    };
+    let body = server.file(camera, file, reload, &uri_query).await.map_err(hls_status)?;
+
+    let (content_type, cache_control) = match file {
+        HlsFile::Playlist => ("application/vnd.apple.mpegurl", "no-cache"),
+        HlsFile::Init | HlsFile::Segment(_) | HlsFile::Part(..) => ("video/mp4", "max-age=60"),
+    };
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CACHE_CONTROL, cache_control),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Cameras currently packaged as LL-HLS
+async fn hls_streams_handler(State(state): State<Arc<AppState>>) -> Result<Json<Vec<HlsStreamInfo>>, ApiError> {
+    Ok(Json(hls_server(&state)?.streams().await))
+}
+
 /// Health check
 async fn health() -> &'static str {
     "OK"
@@ -176,10 +1046,45 @@ async fn health() -> &'static str {
 /// Metrics
 async fn metrics(State(state): State<Arc<AppState>>) -> String {
     let sessions = state.sessions.read().await.len();
-    format!(
-        "# VMS Stream Metrics\nvms_webrtc_sessions {}\n",
-        sessions
-    )
+    let viewers = state.viewers.lock().await;
+    let mut out = format!(
+        "# VMS Stream Metrics\nvms_webrtc_sessions {}\nvms_webrtc_viewers {}\nvms_whip_publishers {}\n",
+        sessions,
+        viewers.total(),
+        state.publishers.lock().await.len()
+    );
+    for (camera_id, count) in viewers.counts() {
+        out.push_str(&format!("vms_webrtc_camera_viewers{{camera_id=\"{}\"}} {}\n", camera_id, count));
+    }
+    if state.transcode.is_some() {
+        let transcoders: usize = state
+            .sessions
+            .read()
+            .await
+            .values()
+            .map(|session| session.transcoder_count())
+            .sum();
+        out.push_str(&format!("vms_transcoders {}\n", transcoders));
+    }
+    out.push_str(&format!("vms_talk_sessions {}\n", state.talks.lock().await.total()));
+    out.push_str(&format!("vms_playback_sessions {}\n", state.playbacks.lock().await.len()));
+    if let Some(hls) = &state.hls {
+        out.push_str(&format!("vms_hls_streams {}\n", hls.streams().await.len()));
+    }
+    if let Some(srt) = &state.srt {
+        let streams = srt.streams().await;
+        out.push_str(&format!("vms_srt_streams {}\n", streams.len()));
+        out.push_str(&format!("vms_srt_frames_received {}\n", srt.frames_received().await));
+        for stream in streams {
+            out.push_str(&format!(
+                "vms_srt_packet_loss_percent{{stream_id=\"{}\",camera_id=\"{}\"}} {:.2}\n",
+                stream.id,
+                stream.camera_id,
+                stream.stats.loss_percent()
+            ));
+        }
+    }
+    out
 }
 
 #[tokio::main]
@@ -195,30 +1100,233 @@ async fn main() -> Result<()> {
     // Initialize GStreamer
     gstreamer_webrtc::init().context("Failed to initialize GStreamer")?;
 
+    // Registro no vms-gateway: a seção `streaming` (segredos SRT/HLS,
+    // STUN/TURN) vem da configuração central; as variáveis STREAM_* abaixo
+    // têm precedência
+    let central = join_gateway(SERVICE_NAME, public_url(HTTP_PORT)).await;
+    let mut streaming = match central.get("streaming") {
+        Some(section) => serde_json::from_value(section.clone()).unwrap_or_else(|e| {
+            warn!("⚠️ Invalid streaming configuration from vms-gateway: {}", e);
+            StreamingConfig::default()
+        }),
+        None => StreamingConfig::default(),
+    };
+
+    // Limite de viewers por câmera
+    if let Some(max) = std::env::var("STREAM_MAX_VIEWERS").ok().and_then(|v| v.parse().ok()) {
+        streaming.max_viewers = max;
+    }
+    info!("👥 Max viewers per camera: {}", streaming.max_viewers);
+
+    // STUN/TURN (listas separadas por vírgula)
+    let servers = |var: &str, default: Vec<String>| match std::env::var(var) {
+        Ok(value) => value
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect(),
+        Err(_) => default,
+    };
+    let ice = IceConfig {
+        stun_servers: servers("STREAM_STUN_SERVERS", streaming.stun_servers.clone()),
+        turn_servers: servers("STREAM_TURN_SERVERS", streaming.turn_servers.clone()),
+    };
+    info!(
+        "🧊 ICE servers: {} STUN, {} TURN",
+        ice.stun_servers.len(),
+        ice.turn_servers.len()
+    );
+
+    // Verifica se os servidores respondem (só loga; não impede a inicialização)
+    for url in ice.stun_servers.iter().chain(&ice.turn_servers) {
+        let url = url.clone();
+        tokio::spawn(async move {
+            let host = ice::IceServerUrl::parse(&url)
+                .map(|server| format!("{}:{}", server.host, server.port))
+                .unwrap_or_else(|_| url.clone());
+            match ice::probe(&url, Duration::from_secs(3)).await {
+                Ok(mapped) => info!("✅ ICE server {} reachable (mapped address {})", host, mapped),
+                Err(e) => warn!("⚠️ ICE server {} unreachable: {}", host, e),
+            }
+        });
+    }
+
+    // Token dos publicadores WHIP (sem ele, qualquer um pode publicar)
+    let whip_token = std::env::var("STREAM_WHIP_TOKEN").ok().filter(|token| !token.is_empty());
+    if whip_token.is_none() {
+        warn!("⚠️ STREAM_WHIP_TOKEN not set: WHIP publishing is unauthenticated");
+    }
+
+    // SRT: latência, passphrase AES e segredo dos tokens de `streamid`
+    if let Some(port) = std::env::var("STREAM_SRT_PORT").ok().and_then(|v| v.parse().ok()) {
+        streaming.srt_port = port;
+    }
+    if let Some(latency) = std::env::var("STREAM_SRT_LATENCY_MS").ok().and_then(|v| v.parse().ok()) {
+        streaming.srt_latency_ms = latency;
+    }
+    let non_empty = |var: &str| std::env::var(var).ok().filter(|value| !value.is_empty());
+    streaming.srt_passphrase = non_empty("STREAM_SRT_PASSPHRASE").or(streaming.srt_passphrase);
+    streaming.srt_token_secret = non_empty("STREAM_SRT_SECRET").or(streaming.srt_token_secret);
+
+    // LL-HLS: partes, segmentos, ociosidade e segredo dos tokens
+    if let Some(ms) = std::env::var("STREAM_HLS_PART_MS").ok().and_then(|v| v.parse().ok()) {
+        streaming.hls_part_target_ms = ms;
+    }
+    if let Some(ms) = std::env::var("STREAM_HLS_SEGMENT_MS").ok().and_then(|v| v.parse().ok()) {
+        streaming.hls_segment_target_ms = ms;
+    }
+    if let Some(secs) = std::env::var("STREAM_HLS_IDLE_SECS").ok().and_then(|v| v.parse().ok()) {
+        streaming.hls_idle_timeout_secs = secs;
+    }
+    streaming.hls_token_secret = non_empty("STREAM_HLS_SECRET").or(streaming.hls_token_secret);
+
+    // Os frames do SRT e do HLS vêm do NATS; sem ele o resto do serviço segue no ar
+    let nats_url = nats_url(&central);
+    let distributor = match connect_distributor(&nats_url).await {
+        Ok(distributor) => Some(distributor),
+        Err(e) => {
+            warn!("⚠️ NATS frame feed unavailable, SRT and HLS disabled: {:#}", e);
+            None
+        }
+    };
+    let srt = match &distributor {
+        Some(distributor) => match start_srt(distributor.clone(), &streaming) {
+            Ok(srt) => Some(srt),
+            Err(e) => {
+                warn!("⚠️ SRT server disabled: {:#}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    let hls = distributor.map(|distributor| start_hls(distributor, &streaming));
+
+    // Transcodificação: escada a partir dos perfis e encoder (hardware se houver)
+    if let Ok(enabled) = std::env::var("STREAM_TRANSCODE") {
+        streaming.transcode_enabled = matches!(enabled.as_str(), "true" | "1");
+    }
+    if let Some(encoder) = non_empty("STREAM_ENCODER") {
+        streaming.transcode_encoder = encoder;
+    }
+    let transcode = if streaming.transcode_enabled {
+        match TranscodeConfig::new(ladder::ladder(&streaming.transcode_ladder), &streaming.transcode_encoder) {
+            Ok(config) => {
+                info!(
+                    "🎚️ Transcoding enabled: {} renditions, encoder {}",
+                    config.ladder.len(),
+                    config.encoder.factory()
+                );
+                Some(Arc::new(config))
+            }
+            Err(e) => {
+                warn!("⚠️ Transcoding disabled: {:#}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Gravações lidas do vms-storage
+    let storage_url = std::env::var("STREAM_STORAGE_URL").unwrap_or_else(|_| "http://localhost:9092".to_string());
+    info!("📼 Recordings from {}", storage_url);
+
+    let (departures, mut departed) = mpsc::unbounded_channel();
     let state = Arc::new(AppState {
         sessions: RwLock::new(HashMap::new()),
+        viewers: Mutex::new(ViewerRegistry::new(streaming.max_viewers)),
+        departures,
+        ice,
+        publishers: Mutex::new(HashMap::new()),
+        whip_token,
+        srt,
+        hls,
+        transcode,
+        talks: Mutex::new(TalkRegistry::new()),
+        talk_sessions: Mutex::new(HashMap::new()),
+        storage_url,
+        playbacks: Mutex::new(HashMap::new()),
     });
 
-    // CORS for web client
+    // Libera viewers cuja conexão WebRTC falhou ou foi fechada pelo browser,
+    // derruba câmeras cujo publicador WHIP caiu e encerra falas, clipes e
+    // playbacks
+    let departed_state = state.clone();
+    tokio::spawn(async move {
+        while let Some(peer_id) = departed.recv().await {
+            if leave(&departed_state, peer_id).await {
+                info!("👋 Viewer {} disconnected", peer_id);
+            } else if unpublish(&departed_state, peer_id).await {
+                info!("👋 WHIP publisher {} disconnected", peer_id);
+            } else if end_talk(&departed_state, peer_id).await {
+                info!("👋 Talk session {} closed", peer_id);
+            } else if end_playback(&departed_state, peer_id).await {
+                info!("👋 Playback session {} closed", peer_id);
+            }
+        }
+    });
+
+    // CORS for web client (WHEP/WHIP clients read Location and Link)
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
-        .allow_headers(Any);
+        .allow_headers(Any)
+        .expose_headers([header::LOCATION, header::LINK]);
 
     let app = Router::new()
         .route("/health", get(health))
         .route("/metrics", get(metrics))
         .route("/api/v1/webrtc/offer", post(webrtc_offer_handler))
         .route("/api/v1/webrtc/ice", post(webrtc_ice_handler))
-        .route("/api/v1/webrtc/:camera_id", axum::routing::delete(webrtc_close_handler))
+        .route("/api/v1/webrtc/ice-servers", get(webrtc_ice_servers_handler))
+        .route("/api/v1/webrtc/peers/:peer_id/candidates", get(webrtc_candidates_handler))
+        .route("/api/v1/webrtc/peers/:peer_id", delete(webrtc_peer_close_handler))
+        .route("/api/v1/webrtc/:camera_id", delete(webrtc_close_handler))
+        .route("/api/v1/webrtc/:camera_id/viewers", get(webrtc_viewers_handler))
+        .route("/api/v1/webrtc/:camera_id/layers", get(webrtc_layers_handler))
+        .route("/api/v1/whep/:camera_id", post(whep_offer_handler))
+        .route(
+            "/api/v1/whep/:camera_id/:peer_id",
+            patch(whep_patch_handler).delete(whep_delete_handler),
+        )
+        .route("/api/v1/whip/:camera_id", post(whip_publish_handler))
+        .route(
+            "/api/v1/whip/:camera_id/:resource_id",
+            patch(whip_patch_handler).delete(whip_delete_handler),
+        )
+        .route("/api/v1/talk", get(talks_handler))
+        .route("/api/v1/talk/:camera_id", post(talk_offer_handler))
+        .route(
+            "/api/v1/talk/:camera_id/clip",
+            post(talk_clip_handler).layer(DefaultBodyLimit::max(CLIP_MAX_BYTES)),
+        )
+        .route(
+            "/api/v1/talk/:camera_id/:talk_id",
+            patch(talk_patch_handler).delete(talk_delete_handler),
+        )
+        .route("/api/v1/playback", get(playbacks_handler))
+        .route("/api/v1/playback/:camera_id", post(playback_offer_handler))
+        .route(
+            "/api/v1/playback/:camera_id/:playback_id",
+            patch(playback_patch_handler).delete(playback_delete_handler),
+        )
+        .route("/api/v1/srt/streams", get(srt_streams_handler))
+        .route("/api/v1/srt/streams/:id", delete(srt_stop_handler))
+        .route("/api/v1/srt/callers", post(srt_caller_handler))
+        .route("/api/v1/hls/streams", get(hls_streams_handler))
+        .route("/api/v1/hls/:camera_id/:file", get(hls_file_handler))
         .layer(cors)
         .with_state(state);
 
-    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], 9094));
+    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], HTTP_PORT));
     let listener = TcpListener::bind(addr).await?;
 
     info!("🌐 HTTP API listening on http://{}", addr);
     info!("📡 WebRTC signaling ready at /api/v1/webrtc/offer");
+    info!("📡 WHEP at /api/v1/whep/:camera_id, WHIP at /api/v1/whip/:camera_id");
+    info!("🎙️ Talk-down at /api/v1/talk/:camera_id (ONVIF backchannel)");
+    info!("📼 Recorded playback at /api/v1/playback/:camera_id?start=");
     info!("⚡ Ultra-low latency mode enabled (GStreamer webrtcbin)");
     info!("✅ Service initialized successfully");
     info!("Press Ctrl+C to stop");
@@ -232,3 +1340,46 @@ async fn main() -> Result<()> {
     info!("👋 Goodbye!");
     Ok(())
 }
+
+/// Conecta ao NATS e sobe o listener SRT
+async fn connect_distributor(nats_url: &str) -> Result<Arc<StreamDistributor>> {
+    let distributor = Arc::new(StreamDistributor::connect(nats_url).await?);
+    distributor.start_distributing().await?;
+    Ok(distributor)
+}
+
+fn start_srt(distributor: Arc<StreamDistributor>, streaming: &StreamingConfig) -> Result<Arc<SRTServer>> {
+    let settings = SrtSettings {
+        port: streaming.srt_port,
+        latency: Duration::from_millis(streaming.srt_latency_ms.into()),
+        passphrase: streaming.srt_passphrase.clone(),
+        token_secret: streaming.srt_token_secret.clone(),
+    };
+    let srt = Arc::new(SRTServer::new(settings, distributor)?);
+
+    let listener = srt.clone();
+    tokio::spawn(async move {
+        if let Err(e) = listener.start().await {
+            error!("❌ SRT listener failed: {:#}", e);
+        }
+    });
+    Ok(srt)
+}
+
+fn start_hls(distributor: Arc<StreamDistributor>, streaming: &StreamingConfig) -> Arc<HlsServer> {
+    let settings = HlsSettings {
+        timing: HlsTiming {
+            part_target: Duration::from_millis(streaming.hls_part_target_ms.into()),
+            segment_target: Duration::from_millis(streaming.hls_segment_target_ms.into()),
+        },
+        idle_timeout: Duration::from_secs(streaming.hls_idle_timeout_secs),
+        token_secret: streaming.hls_token_secret.clone(),
+    };
+    info!(
+        "📺 LL-HLS at /api/v1/hls/:camera_id/index.m3u8 (parts {:?}, segments {:?})",
+        settings.timing.part_target, settings.timing.segment_target
+    );
+    let hls = Arc::new(HlsServer::new(settings, distributor));
+    hls.start_reaper();
+    hls
+}
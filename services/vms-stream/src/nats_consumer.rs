@@ -7,13 +7,15 @@ use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
 use tokio_stream::StreamExt;
 use tracing::{error, info, warn};
+use vms_common::media_profile::MediaProfileUsage;
 use vms_common::stream::VideoFrame;
 use vms_common::types::{CameraId, StreamId};
 
 /// Frame buffer para um stream ativo
 struct StreamBuffer {
     tx: mpsc::Sender<VideoFrame>,
-    viewer_count: usize,
+    /// Uso escolhido pelo cliente (live view = main, mobile = sub)
+    usage: MediaProfileUsage,
 }
 
 /// Consumer e distribuidor de frames
@@ -70,13 +72,17 @@ impl StreamDistributor {
         info!("📺 Frame distributor worker started");
 
         while let Some(message) = subscriber.next().await {
-            // Extract camera_id from subject
+            // Extract camera_id and usage from subject (vms.frames.{camera_id}.{usage})
             let subject_parts: Vec<&str> = message.subject.split('.').collect();
-            if subject_parts.len() < 3 {
+            if subject_parts.len() < 4 {
                 continue;
             }
 
             let camera_id_str = subject_parts[2];
+            let usage = match subject_parts[3].parse::<MediaProfileUsage>() {
+                Ok(usage) => usage,
+                Err(_) => continue,
+            };
 
             // Deserialize frame
             match serde_json::from_slice::<VideoFrame>(&message.payload) {
@@ -90,7 +96,9 @@ impl StreamDistributor {
                         let streams_lock = streams.read().await;
 
                         if let Some(camera_streams) = streams_lock.get(&camera_id) {
-                            for (stream_id, buffer) in camera_streams.iter() {
+                            for (stream_id, buffer) in
+                                camera_streams.iter().filter(|(_, b)| b.usage == usage)
+                            {
                                 if let Err(e) = buffer.tx.try_send(frame.clone()) {
                                     if !matches!(e, mpsc::error::TrySendError::Full(_)) {
                                         warn!("Stream {} closed, will cleanup", stream_id);
@@ -109,10 +117,11 @@ impl StreamDistributor {
         info!("📺 Frame distributor worker stopped");
     }
 
-    /// Cria um novo stream para uma câmera
+    /// Cria um novo stream para uma câmera, recebendo os frames do uso escolhido
     pub async fn create_stream(
         &self,
         camera_id: CameraId,
+        usage: MediaProfileUsage,
         buffer_size: usize,
     ) -> Result<(StreamId, mpsc::Receiver<VideoFrame>)> {
         let stream_id = StreamId::new();
@@ -120,7 +129,7 @@ impl StreamDistributor {
 
         let buffer = StreamBuffer {
             tx,
-            viewer_count: 1,
+            usage,
         };
 
         let mut streams = self.streams.write().await;
@@ -130,8 +139,8 @@ impl StreamDistributor {
             .insert(stream_id, buffer);
 
         info!(
-            "➕ Created stream {} for camera {}",
-            stream_id, camera_id
+            "➕ Created stream {} for camera {} ({})",
+            stream_id, camera_id, usage
         );
 
         Ok((stream_id, rx))
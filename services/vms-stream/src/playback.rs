@@ -0,0 +1,238 @@
+//! Playback WebRTC de gravações
+//!
+//! O mesmo player WebRTC do ao vivo toca gravações: o nó lê os arquivos
+//! horários do vms-storage (`{camera}/{data}/video_{HH}.mkv`, servidos em
+//! `/api/v1/playback/:camera_id?start=`) e manda o H.264 sem transcodificar.
+//! O browser abre um data channel `control` na oferta e por ele pede seek,
+//! pausa, velocidade e avanço quadro a quadro; o nó responde com o instante
+//! exibido.
+
+use std::time::Duration;
+
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Label do data channel de comandos aberto pelo browser
+pub const CONTROL_CHANNEL: &str = "control";
+
+/// Faixa de velocidades aceita (só para frente: o H.264 vai sem decodificar)
+pub const MIN_RATE: f64 = 0.25;
+pub const MAX_RATE: f64 = 16.0;
+
+/// Quadros por comando `step`
+pub const MAX_STEP_FRAMES: u32 = 30;
+
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum PlaybackError {
+    #[error("Invalid playback command: {0}")]
+    InvalidCommand(String),
+
+    #[error("Playback rate {0} outside {MIN_RATE}..={MAX_RATE}")]
+    InvalidRate(f64),
+
+    #[error("Step of {0} frames outside 1..={MAX_STEP_FRAMES}")]
+    InvalidStep(u32),
+
+    /// Sem gravação da câmera naquele instante
+    #[error("No recording of camera {camera_id} at {time}")]
+    NotRecorded { camera_id: String, time: DateTime<Utc> },
+
+    #[error("Recording failed: {0}")]
+    Failed(String),
+}
+
+/// Comando recebido no data channel (JSON com `type`)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlaybackCommand {
+    /// Vai para o instante (RFC 3339), no keyframe anterior
+    Seek { time: DateTime<Utc> },
+    Pause,
+    Play,
+    Speed { rate: f64 },
+    /// Pausa e avança `frames` quadros
+    Step {
+        #[serde(default = "default_step")]
+        frames: u32,
+    },
+}
+
+fn default_step() -> u32 {
+    1
+}
+
+impl PlaybackCommand {
+    pub fn parse(text: &str) -> Result<Self, PlaybackError> {
+        let command: Self =
+            serde_json::from_str(text).map_err(|e| PlaybackError::InvalidCommand(e.to_string()))?;
+        match command {
+            Self::Speed { rate } if !(MIN_RATE..=MAX_RATE).contains(&rate) => Err(PlaybackError::InvalidRate(rate)),
+            Self::Step { frames } if !(1..=MAX_STEP_FRAMES).contains(&frames) => Err(PlaybackError::InvalidStep(frames)),
+            command => Ok(command),
+        }
+    }
+}
+
+/// Mensagem do nó para o browser no data channel
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlaybackEvent {
+    /// Instante do último quadro enviado
+    Status {
+        time: DateTime<Utc>,
+        paused: bool,
+        rate: f64,
+    },
+    /// Fim das gravações (alcançou o presente)
+    End { time: DateTime<Utc> },
+    Error { message: String },
+}
+
+impl PlaybackEvent {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Início do arquivo que contém `time` (cada arquivo cobre uma hora)
+pub fn segment_start(time: DateTime<Utc>) -> DateTime<Utc> {
+    time.duration_trunc(TimeDelta::hours(1)).unwrap_or(time)
+}
+
+/// Arquivo seguinte, se já começou antes de `now` (senão acabou a gravação)
+pub fn next_segment(segment: DateTime<Utc>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let next = segment + TimeDelta::hours(1);
+    (next <= now).then_some(next)
+}
+
+/// URL do arquivo no vms-storage
+pub fn segment_url(storage_url: &str, camera_id: &str, segment: DateTime<Utc>) -> String {
+    format!(
+        "{}/api/v1/playback/{}?start={}",
+        storage_url.trim_end_matches('/'),
+        urlencoding::encode(camera_id),
+        urlencoding::encode(&segment.to_rfc3339())
+    )
+}
+
+/// Relógio de um arquivo: stream time do demuxer ↔ instante gravado
+///
+/// O recorder grava o PTS em milissegundos desde a época, então o arquivo
+/// pode vir com timestamps absolutos; arquivos remuxados começam em zero,
+/// contados a partir do início da hora.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeline {
+    pub segment: DateTime<Utc>,
+    absolute: bool,
+}
+
+impl Timeline {
+    /// Decide pelo primeiro quadro do arquivo
+    pub fn detect(segment: DateTime<Utc>, first: Duration) -> Self {
+        let absolute = first.as_secs() >= segment.timestamp().max(0) as u64;
+        Self { segment, absolute }
+    }
+
+    pub fn time_at(&self, stream_time: Duration) -> DateTime<Utc> {
+        let delta = TimeDelta::from_std(stream_time).unwrap_or_default();
+        if self.absolute {
+            DateTime::UNIX_EPOCH + delta
+        } else {
+            self.segment + delta
+        }
+    }
+
+    pub fn stream_time(&self, time: DateTime<Utc>) -> Duration {
+        let origin = if self.absolute { DateTime::UNIX_EPOCH } else { self.segment };
+        (time.max(self.segment) - origin).to_std().unwrap_or_default()
+    }
+}
+
+/// Sessão de playback ativa
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaybackInfo {
+    pub id: Uuid,
+    pub camera_id: String,
+    pub started_at: DateTime<Utc>,
+    /// Instante pedido na oferta
+    pub start: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(
+            PlaybackCommand::parse(r#"{"type":"seek","time":"2026-10-18T10:15:00Z"}"#),
+            Ok(PlaybackCommand::Seek { time: at("2026-10-18T10:15:00Z") })
+        );
+        assert_eq!(PlaybackCommand::parse(r#"{"type":"pause"}"#), Ok(PlaybackCommand::Pause));
+        assert_eq!(PlaybackCommand::parse(r#"{"type":"step"}"#), Ok(PlaybackCommand::Step { frames: 1 }));
+        assert_eq!(
+            PlaybackCommand::parse(r#"{"type":"speed","rate":4}"#),
+            Ok(PlaybackCommand::Speed { rate: 4.0 })
+        );
+
+        assert_eq!(
+            PlaybackCommand::parse(r#"{"type":"speed","rate":-1}"#),
+            Err(PlaybackError::InvalidRate(-1.0))
+        );
+        assert_eq!(
+            PlaybackCommand::parse(r#"{"type":"step","frames":0}"#),
+            Err(PlaybackError::InvalidStep(0))
+        );
+        assert!(matches!(
+            PlaybackCommand::parse(r#"{"type":"rewind"}"#),
+            Err(PlaybackError::InvalidCommand(_))
+        ));
+    }
+
+    #[test]
+    fn test_segments() {
+        let segment = segment_start(at("2026-10-18T10:15:42Z"));
+        assert_eq!(segment, at("2026-10-18T10:00:00Z"));
+        assert_eq!(
+            segment_url("http://storage:9092/", "cam 1", segment),
+            "http://storage:9092/api/v1/playback/cam%201?start=2026-10-18T10%3A00%3A00%2B00%3A00"
+        );
+
+        assert_eq!(
+            next_segment(segment, at("2026-10-18T12:00:00Z")),
+            Some(at("2026-10-18T11:00:00Z"))
+        );
+        assert_eq!(next_segment(segment, at("2026-10-18T10:59:59Z")), None);
+    }
+
+    #[test]
+    fn test_timeline() {
+        let segment = at("2026-10-18T10:00:00Z");
+        let target = at("2026-10-18T10:20:00Z");
+
+        // Arquivo do recorder: PTS em tempo absoluto
+        let first = Duration::from_millis(segment.timestamp_millis() as u64 + 1500);
+        let absolute = Timeline::detect(segment, first);
+        assert_eq!(absolute.time_at(first), at("2026-10-18T10:00:01.5Z"));
+        assert_eq!(absolute.time_at(absolute.stream_time(target)), target);
+
+        // Arquivo começando em zero
+        let relative = Timeline::detect(segment, Duration::ZERO);
+        assert_eq!(relative.stream_time(target), Duration::from_secs(1200));
+        assert_eq!(relative.time_at(Duration::from_secs(1200)), target);
+        // Antes do início do arquivo: começo do arquivo
+        assert_eq!(relative.stream_time(at("2026-10-18T09:59:00Z")), Duration::ZERO);
+
+        let event = PlaybackEvent::Status { time: target, paused: true, rate: 2.0 };
+        assert_eq!(
+            event.to_json(),
+            r#"{"type":"status","time":"2026-10-18T10:20:00Z","paused":true,"rate":2.0}"#
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Prontidão do pipeline da câmera
+//!
+//! O pipeline só aceita viewers depois que a origem (`rtspsrc` ou o
+//! publicador WHIP) expôs o pad de vídeo e o primeiro keyframe passou pelo `tee`. Os eventos chegam de threads do
+//! GStreamer; as requisições esperam por eles sem bloquear o runtime.
+
+use std::time::Duration;
+
+use thiserror::Error;
+use tokio::sync::watch;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Aguardando o pad RTP de vídeo da origem
+    Connecting,
+    /// Pad ligado, aguardando o primeiro keyframe
+    WaitingKeyframe,
+    Ready,
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum ReadinessError {
+    #[error("Timed out waiting for the camera stream ({0:?})")]
+    Timeout(Stage),
+
+    #[error("Camera pipeline failed: {0}")]
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+struct State {
+    linked: bool,
+    keyframe: bool,
+    failed: Option<String>,
+}
+
+impl State {
+    fn stage(&self) -> Stage {
+        match (self.linked, self.keyframe) {
+            (true, true) => Stage::Ready,
+            (true, false) => Stage::WaitingKeyframe,
+            _ => Stage::Connecting,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Readiness {
+    tx: watch::Sender<State>,
+}
+
+impl Default for Readiness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Readiness {
+    pub fn new() -> Self {
+        Self {
+            tx: watch::Sender::new(State {
+                linked: false,
+                keyframe: false,
+                failed: None,
+            }),
+        }
+    }
+
+    /// A origem expôs o pad de vídeo e ele foi ligado ao depayloader
+    pub fn mark_linked(&self) {
+        self.tx.send_if_modified(|state| !std::mem::replace(&mut state.linked, true));
+    }
+
+    /// Primeiro keyframe disponível no cache
+    pub fn mark_keyframe(&self) {
+        self.tx.send_if_modified(|state| !std::mem::replace(&mut state.keyframe, true));
+    }
+
+    /// Erro do pipeline (o primeiro erro prevalece)
+    pub fn fail(&self, error: impl Into<String>) {
+        let error = error.into();
+        self.tx.send_if_modified(|state| {
+            if state.failed.is_some() {
+                return false;
+            }
+            state.failed = Some(error);
+            true
+        });
+    }
+
+    /// Espera o pipeline ficar pronto (ou falhar)
+    pub async fn wait(&self, timeout: Duration) -> Result<(), ReadinessError> {
+        let mut rx = self.tx.subscribe();
+        let settled = tokio::time::timeout(
+            timeout,
+            rx.wait_for(|state| state.failed.is_some() || state.stage() == Stage::Ready),
+        )
+        .await
+        .is_ok();
+
+        let state = self.tx.borrow();
+        match &state.failed {
+            Some(error) => Err(ReadinessError::Failed(error.clone())),
+            None if settled => Ok(()),
+            None => Err(ReadinessError::Timeout(state.stage())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_waits_for_pad_and_keyframe() {
+        let readiness = Arc::new(Readiness::new());
+        let waiter = tokio::spawn({
+            let readiness = readiness.clone();
+            async move { readiness.wait(Duration::from_secs(10)).await }
+        });
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        readiness.mark_linked();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!waiter.is_finished());
+
+        readiness.mark_keyframe();
+        assert_eq!(waiter.await.unwrap(), Ok(()));
+
+        // Pronto: novos viewers não esperam
+        assert_eq!(readiness.wait(Duration::ZERO).await, Ok(()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_timeout_and_failure() {
+        let readiness = Readiness::new();
+        readiness.mark_linked();
+        assert_eq!(
+            readiness.wait(Duration::from_secs(5)).await,
+            Err(ReadinessError::Timeout(Stage::WaitingKeyframe))
+        );
+
+        readiness.fail("401 Unauthorized");
+        readiness.fail("later error");
+        assert_eq!(
+            readiness.wait(Duration::from_secs(5)).await,
+            Err(ReadinessError::Failed("401 Unauthorized".to_string()))
+        );
+    }
+}
@@ -1,68 +1,367 @@
 //! SRT streaming server
+//!
+//! Serve qualquer câmera em MPEG-TS sobre SRT, para salas de controle
+//! remotas em links WAN com perda. No modo listener o cliente escolhe a
+//! câmera pelo `streamid` (câmera + token emitido pelo vms-api, ver
+//! `vms_common::srt`); no modo caller o nó conecta no listener da sala de
+//! controle e empurra o stream, desde que o vms-api tenha emitido um token de
+//! push para aquela câmera e aquele destino. O TS de cada câmera é montado uma única vez,
+//! a partir dos frames do NATS, e compartilhado entre as conexões.
 
-use anyhow::Result;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::info;
-use vms_common::types::StreamId;
+use std::time::{Duration, Instant};
 
-pub struct SRTServer {
-    streams: Arc<RwLock<HashMap<StreamId, SRTStream>>>,
-    port: u16,
+use anyhow::{anyhow, bail, Context, Result};
+use bytes::Bytes;
+use futures::{FutureExt, SinkExt, StreamExt};
+use srt_tokio::access::{RejectReason, ServerRejectReason};
+use srt_tokio::options::{KeySettings, KeySize, Passphrase};
+use srt_tokio::{ConnectionRequest, SocketStatistics, SrtListener, SrtSocket};
+use tokio::sync::{broadcast, Mutex, Notify};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+use vms_common::media_profile::MediaProfileUsage;
+use vms_common::srt::{push_resource, verify_token, SrtAccessError, SrtCallTarget, SrtStreamId, TokenScope};
+use vms_common::types::{CameraId, StreamId};
+
+use crate::nats_consumer::StreamDistributor;
+use crate::srt_streams::{SrtMode, SrtStats, SrtStream, SrtStreams};
+use crate::ts_mux::{TsMuxer, TS_CHUNK_SIZE};
+
+/// Frames do NATS em espera por câmera antes do mux
+const FRAME_BUFFER: usize = 256;
+
+/// Tamanho da chave AES quando há passphrase (bytes)
+const AES_KEY_SIZE: u16 = 16;
+
+pub struct SrtSettings {
+    pub port: u16,
+    /// Latência padrão (mínimo negociado no listener)
+    pub latency: Duration,
+    pub passphrase: Option<String>,
+    /// Segredo dos tokens do `streamid` e de push; sem ele o listener recusa
+    /// conexões e o modo caller fica desligado
+    pub token_secret: Option<String>,
 }
 
-struct SRTStream {
+/// TS de uma câmera, compartilhado pelas conexões
+struct Feed {
+    muxer: Arc<TsMuxer>,
     stream_id: StreamId,
-    camera_id: String,
+    pump: JoinHandle<()>,
+    subscribers: usize,
+}
+
+pub struct SRTServer {
+    settings: SrtSettings,
+    distributor: Arc<StreamDistributor>,
+    feeds: Mutex<HashMap<CameraId, Feed>>,
+    streams: Mutex<SrtStreams>,
 }
 
 impl SRTServer {
-    pub fn new(port: u16) -> Self {
-        Self {
-            streams: Arc::new(RwLock::new(HashMap::new())),
-            port,
+    pub fn new(settings: SrtSettings, distributor: Arc<StreamDistributor>) -> Result<Self> {
+        if let Some(passphrase) = &settings.passphrase {
+            check_passphrase(passphrase)?;
         }
+        Ok(Self {
+            settings,
+            distributor,
+            feeds: Mutex::new(HashMap::new()),
+            streams: Mutex::new(SrtStreams::new()),
+        })
     }
 
-    /// Inicia servidor SRT
-    pub async fn start(&self) -> Result<()> {
-        info!("SRT server would listen on port {}", self.port);
+    /// Inicia o listener; atende conexões até o processo terminar
+    pub async fn start(self: Arc<Self>) -> Result<()> {
+        let (_listener, mut incoming) = SrtListener::builder()
+            .latency(self.settings.latency)
+            .bind(self.settings.port)
+            .await
+            .with_context(|| format!("Failed to bind SRT listener on port {}", self.settings.port))?;
 
-        // Em produção real:
-        // 1. Bind SRT socket
-        // 2. Accept connections
-        // 3. Stream video data
+        info!(
+            "📡 SRT listener on port {} (latency {:?}, {})",
+            self.settings.port,
+            self.settings.latency,
+            if self.settings.passphrase.is_some() { "AES encrypted" } else { "unencrypted" }
+        );
+        if self.settings.token_secret.is_none() {
+            warn!("⚠️ No SRT token secret configured: listener connections will be rejected");
+        }
 
+        while let Some(request) = incoming.incoming().next().await {
+            let server = self.clone();
+            tokio::spawn(async move { server.accept(request).await });
+        }
         Ok(())
     }
 
-    /// Cria stream SRT
-    pub async fn create_stream(&self, camera_id: String) -> Result<StreamId> {
-        let stream_id = StreamId::new();
+    /// Confere um token do vms-api com o segredo do servidor
+    fn verify(&self, scope: TokenScope, resource: &str, token: &str) -> Result<(), SrtAccessError> {
+        let secret = self
+            .settings
+            .token_secret
+            .as_deref()
+            .ok_or(SrtAccessError::InvalidToken)?;
+        verify_token(secret.as_bytes(), scope, resource, token, chrono::Utc::now().timestamp())
+    }
+
+    /// Câmera do `streamid`, se o token confere
+    fn authorize(&self, stream_id: Option<&str>) -> Result<CameraId, SrtAccessError> {
+        let stream_id = SrtStreamId::parse(stream_id.unwrap_or_default())?;
+        self.verify(TokenScope::Play, &stream_id.camera_id, &stream_id.token)?;
+        parse_camera(&stream_id.camera_id).map_err(|_| SrtAccessError::InvalidStreamId(stream_id.camera_id))
+    }
+
+    /// Confere se o vms-api autorizou o push da câmera para o destino
+    pub fn authorize_call(&self, target: &SrtCallTarget) -> Result<(), SrtAccessError> {
+        let resource = push_resource(&target.camera_id, &target.address);
+        self.verify(TokenScope::Push, &resource, &target.token)
+    }
 
-        let stream = SRTStream {
-            stream_id,
-            camera_id: camera_id.clone(),
+    async fn accept(self: Arc<Self>, request: ConnectionRequest) {
+        let remote = request.remote();
+        let stream_id = request.stream_id().map(|id| id.to_string());
+
+        let camera_id = match self.authorize(stream_id.as_deref()) {
+            Ok(camera_id) => camera_id,
+            Err(e) => {
+                warn!("🚫 SRT connection from {} rejected: {}", remote, e);
+                let reason = match e {
+                    SrtAccessError::InvalidStreamId(_) => ServerRejectReason::BadRequest,
+                    SrtAccessError::UnsupportedMode => ServerRejectReason::BadMode,
+                    SrtAccessError::InvalidToken | SrtAccessError::Expired => ServerRejectReason::Unauthorized,
+                };
+                let _ = request.reject(RejectReason::Server(reason)).await;
+                return;
+            }
         };
 
-        self.streams.write().await.insert(stream_id, stream);
+        let key_settings = self.settings.passphrase.as_deref().map(key_settings);
+        let encrypted = key_settings.is_some();
+        let socket = match request.accept(key_settings).await {
+            Ok(socket) => socket,
+            Err(e) => {
+                warn!("⚠️ SRT handshake with {} failed: {}", remote, e);
+                return;
+            }
+        };
 
+        let latency_ms = socket.settings().send_tsbpd_latency.as_millis() as u32;
+        let (id, stop) = self
+            .streams
+            .lock()
+            .await
+            .register(&camera_id.to_string(), SrtMode::Listener, remote.to_string(), latency_ms, encrypted);
         info!(
-            "Created SRT stream {} for camera {}",
-            stream_id, camera_id
+            "🔗 SRT stream {}: {} pulling camera {} (latency {} ms)",
+            id, remote, camera_id, latency_ms
         );
 
-        Ok(stream_id)
+        self.serve(socket, camera_id, id, stop).await;
     }
 
-    /// Para stream
-    pub async fn stop_stream(&self, stream_id: StreamId) -> Result<()> {
-        self.streams.write().await.remove(&stream_id);
-        Ok(())
+    /// Conecta no listener remoto e empurra o TS da câmera
+    pub async fn call(self: Arc<Self>, target: SrtCallTarget) -> Result<Uuid> {
+        self.authorize_call(&target)
+            .with_context(|| format!("SRT push of camera {} to {} refused", target.camera_id, target.address))?;
+        let camera_id = parse_camera(&target.camera_id)?;
+        let latency = target
+            .latency_ms
+            .map(|ms| Duration::from_millis(ms.into()))
+            .unwrap_or(self.settings.latency);
+        let passphrase = target.passphrase.as_deref().or(self.settings.passphrase.as_deref());
+
+        let mut builder = SrtSocket::builder().latency(latency);
+        if let Some(passphrase) = passphrase {
+            check_passphrase(passphrase)?;
+            builder = builder.encryption(AES_KEY_SIZE, passphrase);
+        }
+        let socket = builder
+            .call(target.address.as_str(), target.stream_id.as_deref())
+            .await
+            .with_context(|| format!("Failed to connect to SRT listener {}", target.address))?;
+
+        let latency_ms = socket.settings().send_tsbpd_latency.as_millis() as u32;
+        let (id, stop) = self.streams.lock().await.register(
+            &camera_id.to_string(),
+            SrtMode::Caller,
+            target.address.clone(),
+            latency_ms,
+            passphrase.is_some(),
+        );
+        info!(
+            "🔗 SRT stream {}: pushing camera {} to {} (latency {} ms)",
+            id, camera_id, target.address, latency_ms
+        );
+
+        tokio::spawn(async move { self.serve(socket, camera_id, id, stop).await });
+        Ok(id)
+    }
+
+    /// Envia o TS da câmera até a conexão cair ou ser parada pela API
+    async fn serve(&self, mut socket: SrtSocket, camera_id: CameraId, id: Uuid, stop: Arc<Notify>) {
+        let mut chunks = match self.subscribe(camera_id).await {
+            Ok(chunks) => chunks,
+            Err(e) => {
+                error!("❌ SRT stream {}: camera {} unavailable: {:#}", id, camera_id, e);
+                self.streams.lock().await.remove(id);
+                let _ = socket.close().await;
+                return;
+            }
+        };
+
+        loop {
+            let chunk = tokio::select! {
+                _ = stop.notified() => {
+                    info!("🛑 SRT stream {} stopped", id);
+                    break;
+                }
+                chunk = chunks.recv() => chunk,
+            };
+
+            match chunk {
+                Ok(chunk) => {
+                    if let Err(e) = send_chunk(&mut socket, chunk).await {
+                        info!("🔌 SRT stream {} disconnected: {}", id, e);
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("⚠️ SRT stream {} too slow, {} TS chunks skipped", id, skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+
+            // Estatísticas publicadas pelo socket desde o último bloco
+            while let Some(Some(statistics)) = socket.statistics().next().now_or_never() {
+                self.streams.lock().await.update(id, stats_of(&statistics));
+            }
+        }
+
+        let _ = socket.close().await;
+        self.unsubscribe(camera_id).await;
+        if let Some(stream) = self.streams.lock().await.remove(id) {
+            info!(
+                "👋 SRT stream {} for camera {} closed ({} packets, {:.2}% loss)",
+                id,
+                stream.camera_id,
+                stream.stats.packets_sent,
+                stream.stats.loss_percent()
+            );
+        }
+    }
+
+    /// TS da câmera; o mux é criado na primeira conexão
+    async fn subscribe(&self, camera_id: CameraId) -> Result<broadcast::Receiver<Bytes>> {
+        let mut feeds = self.feeds.lock().await;
+        if let Some(feed) = feeds.get_mut(&camera_id) {
+            feed.subscribers += 1;
+            return Ok(feed.muxer.subscribe());
+        }
+
+        let muxer = Arc::new(TsMuxer::new(&camera_id.to_string())?);
+        let (stream_id, mut frames) = self
+            .distributor
+            .create_stream(camera_id, MediaProfileUsage::LiveView, FRAME_BUFFER)
+            .await?;
+        let pump_muxer = muxer.clone();
+        let pump = tokio::spawn(async move {
+            while let Some(frame) = frames.recv().await {
+                pump_muxer.push(&frame);
+            }
+        });
+
+        let chunks = muxer.subscribe();
+        feeds.insert(
+            camera_id,
+            Feed {
+                muxer,
+                stream_id,
+                pump,
+                subscribers: 1,
+            },
+        );
+        Ok(chunks)
+    }
+
+    /// Libera o TS; o mux para com a última conexão
+    async fn unsubscribe(&self, camera_id: CameraId) {
+        let mut feeds = self.feeds.lock().await;
+        let Some(feed) = feeds.get_mut(&camera_id) else {
+            return;
+        };
+        feed.subscribers -= 1;
+        if feed.subscribers > 0 {
+            return;
+        }
+
+        if let Some(feed) = feeds.remove(&camera_id) {
+            feed.pump.abort();
+            feed.muxer.stop();
+            let _ = self.distributor.remove_stream(camera_id, feed.stream_id).await;
+        }
+    }
+
+    /// Conexões ativas com estatísticas
+    pub async fn streams(&self) -> Vec<SrtStream> {
+        self.streams.lock().await.list()
     }
 
-    pub async fn active_streams(&self) -> usize {
-        self.streams.read().await.len()
+    /// Frames recebidos do NATS desde o início
+    pub async fn frames_received(&self) -> u64 {
+        self.distributor.get_stats().await.2
+    }
+
+    /// Encerra uma conexão
+    pub async fn stop(&self, id: Uuid) -> bool {
+        self.streams.lock().await.stop(id)
+    }
+}
+
+fn parse_camera(camera_id: &str) -> Result<CameraId> {
+    let uuid = camera_id
+        .parse()
+        .map_err(|_| anyhow!("Invalid camera id: {}", camera_id))?;
+    Ok(CameraId::from_uuid(uuid))
+}
+
+/// Limites de passphrase do SRT
+fn check_passphrase(passphrase: &str) -> Result<()> {
+    if !(10..=79).contains(&passphrase.len()) {
+        bail!("SRT passphrase must have 10 to 79 characters");
+    }
+    Ok(())
+}
+
+fn key_settings(passphrase: &str) -> KeySettings {
+    KeySettings {
+        key_size: KeySize::AES128,
+        passphrase: Passphrase::try_from(passphrase.to_string()).expect("passphrase checked on startup"),
+    }
+}
+
+/// Um bloco do mux em pacotes SRT de até 1316 bytes
+async fn send_chunk(socket: &mut SrtSocket, chunk: Bytes) -> std::io::Result<()> {
+    let now = Instant::now();
+    let mut offset = 0;
+    while offset < chunk.len() {
+        let end = (offset + TS_CHUNK_SIZE).min(chunk.len());
+        socket.feed((now, chunk.slice(offset..end))).await?;
+        offset = end;
+    }
+    socket.flush().await
+}
+
+fn stats_of(statistics: &SocketStatistics) -> SrtStats {
+    SrtStats {
+        packets_sent: statistics.tx_data,
+        packets_lost: statistics.tx_loss_data,
+        packets_retransmitted: statistics.tx_retransmit_data,
+        packets_dropped: statistics.tx_dropped_data,
+        bytes_sent: statistics.tx_bytes,
     }
 }
@@ -0,0 +1,154 @@
+//! Conexões SRT ativas
+//!
+//! Cada conexão (cliente no listener ou push em modo caller) fica registrada
+//! com suas estatísticas mais recentes, para a API e as métricas, e com um
+//! sinal de parada para encerrá-la pela API.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SrtMode {
+    /// Sala de controle conectou no nosso listener
+    Listener,
+    /// Nós conectamos no listener da sala de controle
+    Caller,
+}
+
+/// Estatísticas de envio de uma conexão
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SrtStats {
+    pub packets_sent: u64,
+    pub packets_lost: u64,
+    pub packets_retransmitted: u64,
+    /// Descartados por chegarem tarde demais para a latência configurada
+    pub packets_dropped: u64,
+    pub bytes_sent: u64,
+}
+
+impl SrtStats {
+    /// Perda em % dos pacotes enviados
+    pub fn loss_percent(&self) -> f64 {
+        if self.packets_sent == 0 {
+            return 0.0;
+        }
+        self.packets_lost as f64 * 100.0 / self.packets_sent as f64
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SrtStream {
+    pub id: Uuid,
+    pub camera_id: String,
+    pub mode: SrtMode,
+    /// Endereço da sala de controle
+    pub remote: String,
+    pub latency_ms: u32,
+    pub encrypted: bool,
+    pub connected_at: DateTime<Utc>,
+    pub stats: SrtStats,
+}
+
+#[derive(Debug, Default)]
+pub struct SrtStreams {
+    streams: HashMap<Uuid, (SrtStream, Arc<Notify>)>,
+}
+
+impl SrtStreams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra uma conexão; o `Notify` sinaliza pedido de parada
+    pub fn register(
+        &mut self,
+        camera_id: &str,
+        mode: SrtMode,
+        remote: String,
+        latency_ms: u32,
+        encrypted: bool,
+    ) -> (Uuid, Arc<Notify>) {
+        let id = Uuid::new_v4();
+        let stop = Arc::new(Notify::new());
+        let stream = SrtStream {
+            id,
+            camera_id: camera_id.to_string(),
+            mode,
+            remote,
+            latency_ms,
+            encrypted,
+            connected_at: Utc::now(),
+            stats: SrtStats::default(),
+        };
+        self.streams.insert(id, (stream, stop.clone()));
+        (id, stop)
+    }
+
+    pub fn update(&mut self, id: Uuid, stats: SrtStats) {
+        if let Some((stream, _)) = self.streams.get_mut(&id) {
+            stream.stats = stats;
+        }
+    }
+
+    pub fn remove(&mut self, id: Uuid) -> Option<SrtStream> {
+        self.streams.remove(&id).map(|(stream, _)| stream)
+    }
+
+    /// Pede o encerramento da conexão (que se remove ao terminar)
+    pub fn stop(&self, id: Uuid) -> bool {
+        match self.streams.get(&id) {
+            Some((_, stop)) => {
+                stop.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Conexões, das mais antigas para as mais novas
+    pub fn list(&self) -> Vec<SrtStream> {
+        let mut streams: Vec<_> = self.streams.values().map(|(stream, _)| stream.clone()).collect();
+        streams.sort_by_key(|stream| stream.connected_at);
+        streams
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_register_update_stop() {
+        let mut streams = SrtStreams::new();
+        let (id, stop) = streams.register("cam1", SrtMode::Listener, "203.0.113.5:50000".to_string(), 120, true);
+        streams.register("cam2", SrtMode::Caller, "198.51.100.7:9000".to_string(), 500, false);
+        assert_eq!(streams.list().len(), 2);
+
+        let stats = SrtStats {
+            packets_sent: 1000,
+            packets_lost: 25,
+            packets_retransmitted: 25,
+            packets_dropped: 1,
+            bytes_sent: 1_316_000,
+        };
+        streams.update(id, stats.clone());
+        let listed = streams.list();
+        assert_eq!(listed[0].camera_id, "cam1");
+        assert_eq!(listed[0].stats, stats);
+        assert_eq!(listed[0].stats.loss_percent(), 2.5);
+        assert_eq!(SrtStats::default().loss_percent(), 0.0);
+
+        // A parada chega à tarefa da conexão, que então se remove
+        assert!(streams.stop(id));
+        stop.notified().await;
+        assert_eq!(streams.remove(id).unwrap().mode, SrtMode::Listener);
+        assert!(!streams.stop(id));
+        assert_eq!(streams.list().len(), 1);
+    }
+}
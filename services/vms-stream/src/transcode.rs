@@ -0,0 +1,202 @@
+//! Transcoders da escada de qualidade
+//!
+//! Um transcoder por degrau e por câmera, ligado ao `tee` da câmera como um
+//! viewer qualquer: `queue ! decoder ! videoconvert ! videoscale ! videorate
+//! ! capsfilter ! encoder ! h264parse ! capsfilter ! tee`. O `tee` de saída é
+//! compartilhado por todos os viewers do degrau. Decoder e encoder de
+//! hardware são usados quando disponíveis; x264/openh264 cobrem o resto (CI).
+
+use anyhow::{anyhow, bail, Context, Result};
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use tracing::{debug, info, warn};
+use vms_common::media_profile::VideoCodec;
+
+use crate::ladder::{EncoderKind, Rendition};
+
+/// Frames da câmera em espera por transcoder (os mais antigos são perdidos
+/// se o encoder não acompanhar, sem travar o `tee` da câmera)
+const TRANSCODER_QUEUE_BUFFERS: u32 = 30;
+
+/// Escada e encoder escolhidos na inicialização
+pub struct TranscodeConfig {
+    pub ladder: Vec<Rendition>,
+    pub encoder: EncoderKind,
+}
+
+impl TranscodeConfig {
+    /// Usa o primeiro encoder instalado entre os de `encoder_setting`
+    /// (ver `EncoderKind::candidates`)
+    pub fn new(ladder: Vec<Rendition>, encoder_setting: &str) -> Result<Self> {
+        if ladder.is_empty() {
+            bail!("Transcoding ladder has no active profiles");
+        }
+        let encoder = EncoderKind::candidates(encoder_setting)
+            .map_err(|e| anyhow!(e))?
+            .into_iter()
+            .find(|kind| gst::ElementFactory::find(kind.factory()).is_some())
+            .ok_or_else(|| anyhow!("No H.264 encoder installed for '{}'", encoder_setting))?;
+        Ok(Self { ladder, encoder })
+    }
+}
+
+/// Elementos de um transcoder; `sink` é ligado a um pad do `tee` da câmera
+pub struct TranscoderBranch {
+    pub elements: Vec<gst::Element>,
+    pub sink: gst::Pad,
+    pub tee: gst::Element,
+}
+
+/// Monta e liga um transcoder no pipeline, já no estado do pipeline
+pub fn build(
+    pipeline: &gst::Pipeline,
+    name: &str,
+    codec: VideoCodec,
+    rendition: &Rendition,
+    encoder: EncoderKind,
+) -> Result<TranscoderBranch> {
+    let queue = gst::ElementFactory::make("queue")
+        .name(format!("{}-queue", name))
+        .property_from_str("leaky", "downstream")
+        .property("max-size-buffers", TRANSCODER_QUEUE_BUFFERS)
+        .property("max-size-bytes", 0u32)
+        .property("max-size-time", 0u64)
+        .build()
+        .context("Failed to create queue")?;
+
+    let decoder = decoder(codec, encoder.is_hardware())?;
+    let convert = make("videoconvert")?;
+    let scale = make("videoscale")?;
+    let rate = gst::ElementFactory::make("videorate")
+        .property("drop-only", true)
+        .build()
+        .context("Failed to create videorate")?;
+
+    let framerate = gst::Fraction::approximate_f64(rendition.fps).unwrap_or_else(|| gst::Fraction::new(25, 1));
+    let raw_caps = gst::ElementFactory::make("capsfilter")
+        .property(
+            "caps",
+            gst::Caps::builder("video/x-raw")
+                .field("width", rendition.width as i32)
+                .field("height", rendition.height as i32)
+                .field("framerate", framerate)
+                .build(),
+        )
+        .build()
+        .context("Failed to create capsfilter")?;
+
+    let enc = make(encoder.factory())?;
+    for (property, value) in encoder.settings(rendition) {
+        set_setting(&enc, property, &value);
+    }
+
+    // Perfis que qualquer browser decodifica (Firefox só tem baseline)
+    let profile_caps = gst::ElementFactory::make("capsfilter")
+        .property(
+            "caps",
+            gst::Caps::builder("video/x-h264")
+                .field("profile", gst::List::new(["constrained-baseline", "baseline", "main"]))
+                .build(),
+        )
+        .build()
+        .context("Failed to create capsfilter")?;
+
+    // SPS/PPS em todo IDR: viewers entram e trocam de camada em qualquer keyframe
+    let parse = gst::ElementFactory::make("h264parse")
+        .property("config-interval", -1i32)
+        .build()
+        .context("Failed to create h264parse")?;
+    let au_caps = gst::ElementFactory::make("capsfilter")
+        .property(
+            "caps",
+            gst::Caps::builder("video/x-h264")
+                .field("stream-format", "byte-stream")
+                .field("alignment", "au")
+                .build(),
+        )
+        .build()
+        .context("Failed to create capsfilter")?;
+
+    let tee = gst::ElementFactory::make("tee")
+        .name(format!("{}-tee", name))
+        .property("allow-not-linked", true)
+        .build()
+        .context("Failed to create tee")?;
+
+    let elements = vec![
+        queue.clone(),
+        decoder,
+        convert,
+        scale,
+        rate,
+        raw_caps,
+        enc,
+        profile_caps,
+        parse,
+        au_caps,
+        tee.clone(),
+    ];
+    pipeline.add_many(&elements)?;
+
+    let linked = (|| -> Result<gst::Pad> {
+        gst::Element::link_many(&elements).context("Failed to link transcoder")?;
+        for element in elements.iter().rev() {
+            element.sync_state_with_parent()?;
+        }
+        queue.static_pad("sink").context("No sink pad on queue")
+    })();
+
+    match linked {
+        Ok(sink) => {
+            info!(
+                "🎚️ Transcoder {} ready: {}x{} @ {} fps, {} kbps ({})",
+                name,
+                rendition.width,
+                rendition.height,
+                rendition.fps,
+                rendition.bitrate / 1000,
+                encoder.factory()
+            );
+            Ok(TranscoderBranch { elements, sink, tee })
+        }
+        Err(e) => {
+            for element in &elements {
+                let _ = element.set_state(gst::State::Null);
+            }
+            let _ = pipeline.remove_many(&elements);
+            Err(e)
+        }
+    }
+}
+
+fn make(factory: &str) -> Result<gst::Element> {
+    gst::ElementFactory::make(factory)
+        .build()
+        .with_context(|| format!("Failed to create {}", factory))
+}
+
+/// Decoder do codec da câmera; hardware só junto com encoder de hardware
+fn decoder(codec: VideoCodec, hardware: bool) -> Result<gst::Element> {
+    let candidates: &[&str] = match codec {
+        VideoCodec::H264 => &["nvh264dec", "vah264dec", "avdec_h264", "openh264dec"],
+        VideoCodec::H265 => &["nvh265dec", "vah265dec", "avdec_h265", "libde265dec"],
+        other => bail!("No decoder for {:?} cameras", other),
+    };
+    candidates
+        .iter()
+        .filter(|name| hardware || !(name.starts_with("nv") || name.starts_with("va")))
+        .find_map(|name| gst::ElementFactory::make(name).build().ok())
+        .ok_or_else(|| anyhow!("No {:?} decoder installed", codec))
+}
+
+/// Aplica uma propriedade em texto, se o encoder (nesta versão) a tiver
+fn set_setting(element: &gst::Element, property: &str, value: &str) {
+    let Some(pspec) = element.find_property(property) else {
+        debug!("{} has no property {}", element.name(), property);
+        return;
+    };
+    match glib::Value::deserialize(value, pspec.value_type()) {
+        Ok(value) => element.set_property_from_value(property, &value),
+        Err(_) => warn!("⚠️ Invalid value {} for {}::{}", value, element.name(), property),
+    }
+}
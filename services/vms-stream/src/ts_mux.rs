@@ -0,0 +1,121 @@
+//! Mux MPEG-TS por câmera
+//!
+//! Os access units H.264 do NATS entram num `appsrc` e saem do
+//! `mpegtsmux` em blocos de 7 pacotes TS (1316 bytes, o payload padrão de
+//! um pacote SRT), distribuídos a todas as conexões SRT da câmera.
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+use tokio::sync::broadcast;
+use tracing::{debug, info};
+use vms_common::stream::VideoFrame;
+
+/// Pacotes TS por bloco (7 x 188 = 1316 bytes)
+pub const TS_PACKETS_PER_CHUNK: usize = 7;
+pub const TS_CHUNK_SIZE: usize = TS_PACKETS_PER_CHUNK * 188;
+
+/// Blocos em trânsito por conexão antes de uma conexão lenta perder dados
+const BROADCAST_CAPACITY: usize = 2048;
+
+pub struct TsMuxer {
+    camera_id: String,
+    pipeline: gst::Pipeline,
+    appsrc: gst_app::AppSrc,
+    tx: broadcast::Sender<Bytes>,
+}
+
+impl TsMuxer {
+    /// `appsrc ! h264parse ! mpegtsmux ! appsink`, já tocando
+    pub fn new(camera_id: &str) -> Result<Self> {
+        let pipeline = gst::Pipeline::new();
+
+        let appsrc = gst_app::AppSrc::builder()
+            .name("frames")
+            .caps(
+                &gst::Caps::builder("video/x-h264")
+                    .field("stream-format", "byte-stream")
+                    .field("alignment", "au")
+                    .build(),
+            )
+            .is_live(true)
+            .do_timestamp(true)
+            .format(gst::Format::Time)
+            .build();
+
+        // SPS/PPS em todo IDR: quem conecta no meio do stream decodifica no
+        // próximo keyframe
+        let h264parse = gst::ElementFactory::make("h264parse")
+            .property("config-interval", -1i32)
+            .build()
+            .context("Failed to create h264parse")?;
+
+        let mux = gst::ElementFactory::make("mpegtsmux")
+            .property("alignment", TS_PACKETS_PER_CHUNK as i32)
+            .build()
+            .context("Failed to create mpegtsmux")?;
+
+        let appsink = gst_app::AppSink::builder()
+            .name("ts")
+            .sync(false)
+            .enable_last_sample(false)
+            .build();
+
+        pipeline.add_many([appsrc.upcast_ref(), &h264parse, &mux, appsink.upcast_ref()])?;
+        gst::Element::link_many([appsrc.upcast_ref(), &h264parse, &mux, appsink.upcast_ref()])
+            .context("Failed to link TS muxer")?;
+
+        let (tx, _) = broadcast::channel(BROADCAST_CAPACITY);
+        let chunks = tx.clone();
+        appsink.set_callbacks(
+            gst_app::AppSinkCallbacks::builder()
+                .new_sample(move |sink| {
+                    let sample = sink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                    let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+                    let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+                    // Sem conexões o bloco é simplesmente descartado
+                    let _ = chunks.send(Bytes::copy_from_slice(map.as_slice()));
+                    Ok(gst::FlowSuccess::Ok)
+                })
+                .build(),
+        );
+
+        pipeline
+            .set_state(gst::State::Playing)
+            .context("Failed to start TS muxer")?;
+        info!("📦 MPEG-TS muxer started for camera: {}", camera_id);
+
+        Ok(Self {
+            camera_id: camera_id.to_string(),
+            pipeline,
+            appsrc,
+            tx,
+        })
+    }
+
+    /// Entrega um access unit ao mux
+    pub fn push(&self, frame: &VideoFrame) {
+        let mut buffer = gst::Buffer::from_slice(frame.data.clone());
+        if !frame.is_keyframe {
+            if let Some(buffer) = buffer.get_mut() {
+                buffer.set_flags(gst::BufferFlags::DELTA_UNIT);
+            }
+        }
+        if let Err(e) = self.appsrc.push_buffer(buffer) {
+            debug!("⚠️ TS muxer for camera {} rejected frame: {:?}", self.camera_id, e);
+        }
+    }
+
+    /// Blocos TS a partir de agora
+    pub fn subscribe(&self) -> broadcast::Receiver<Bytes> {
+        self.tx.subscribe()
+    }
+
+    pub fn stop(&self) {
+        let _ = self.appsrc.end_of_stream();
+        let _ = self.pipeline.set_state(gst::State::Null);
+        info!("🛑 MPEG-TS muxer stopped for camera: {}", self.camera_id);
+    }
+}
@@ -0,0 +1,158 @@
+//! Registro de viewers WebRTC por câmera
+//!
+//! Cada viewer recebe um `peer_id` próprio (o mesmo de
+//! `vms_common::WebRtcAnswerResponse`) e ocupa uma vaga da câmera até sair;
+//! o limite por câmera vem de `StreamingConfig.max_viewers`.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ViewerError {
+    #[error("Camera {camera_id} already has the maximum of {max} viewers")]
+    CameraFull { camera_id: String, max: usize },
+}
+
+/// Viewer conectado
+#[derive(Debug, Clone, Serialize)]
+pub struct Viewer {
+    pub peer_id: Uuid,
+    pub camera_id: String,
+    pub joined_at: DateTime<Utc>,
+}
+
+/// Vagas de viewers por câmera, indexadas por `peer_id`
+#[derive(Debug)]
+pub struct ViewerRegistry {
+    max_per_camera: usize,
+    viewers: HashMap<Uuid, Viewer>,
+}
+
+impl ViewerRegistry {
+    pub fn new(max_per_camera: usize) -> Self {
+        Self {
+            max_per_camera,
+            viewers: HashMap::new(),
+        }
+    }
+
+    /// Reserva uma vaga na câmera e devolve o `peer_id` do novo viewer
+    pub fn join(&mut self, camera_id: &str) -> Result<Uuid, ViewerError> {
+        if self.count(camera_id) >= self.max_per_camera {
+            return Err(ViewerError::CameraFull {
+                camera_id: camera_id.to_string(),
+                max: self.max_per_camera,
+            });
+        }
+
+        let peer_id = Uuid::new_v4();
+        self.viewers.insert(
+            peer_id,
+            Viewer {
+                peer_id,
+                camera_id: camera_id.to_string(),
+                joined_at: Utc::now(),
+            },
+        );
+        Ok(peer_id)
+    }
+
+    /// Libera a vaga; retorna a câmera e quantos viewers ainda restam nela
+    pub fn leave(&mut self, peer_id: Uuid) -> Option<(String, usize)> {
+        let viewer = self.viewers.remove(&peer_id)?;
+        let remaining = self.count(&viewer.camera_id);
+        Some((viewer.camera_id, remaining))
+    }
+
+    /// Remove todos os viewers da câmera
+    pub fn remove_camera(&mut self, camera_id: &str) -> Vec<Viewer> {
+        let viewers = self.viewers_of(camera_id);
+        for viewer in &viewers {
+            self.viewers.remove(&viewer.peer_id);
+        }
+        viewers
+    }
+
+    /// Câmera em que o viewer está
+    pub fn camera_of(&self, peer_id: Uuid) -> Option<String> {
+        self.viewers.get(&peer_id).map(|v| v.camera_id.clone())
+    }
+
+    pub fn count(&self, camera_id: &str) -> usize {
+        self.viewers.values().filter(|v| v.camera_id == camera_id).count()
+    }
+
+    /// Viewers da câmera, do mais antigo para o mais novo
+    pub fn viewers_of(&self, camera_id: &str) -> Vec<Viewer> {
+        let mut viewers: Vec<Viewer> = self
+            .viewers
+            .values()
+            .filter(|v| v.camera_id == camera_id)
+            .cloned()
+            .collect();
+        viewers.sort_by_key(|v| v.joined_at);
+        viewers
+    }
+
+    /// Viewers por câmera
+    pub fn counts(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for viewer in self.viewers.values() {
+            *counts.entry(viewer.camera_id.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    pub fn total(&self) -> usize {
+        self.viewers.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cap_per_camera() {
+        let mut registry = ViewerRegistry::new(2);
+        let first = registry.join("cam-1").unwrap();
+        let second = registry.join("cam-1").unwrap();
+        assert_ne!(first, second);
+        assert_eq!(
+            registry.join("cam-1"),
+            Err(ViewerError::CameraFull {
+                camera_id: "cam-1".to_string(),
+                max: 2
+            })
+        );
+
+        // O limite é por câmera
+        registry.join("cam-2").unwrap();
+        assert_eq!(registry.total(), 3);
+
+        // Um viewer sai sem afetar o outro e libera a vaga
+        assert_eq!(registry.camera_of(first).as_deref(), Some("cam-1"));
+        assert_eq!(registry.leave(first), Some(("cam-1".to_string(), 1)));
+        assert_eq!(registry.camera_of(first), None);
+        assert_eq!(registry.leave(first), None);
+        assert_eq!(registry.viewers_of("cam-1")[0].peer_id, second);
+        registry.join("cam-1").unwrap();
+    }
+
+    #[test]
+    fn test_remove_camera() {
+        let mut registry = ViewerRegistry::new(10);
+        registry.join("cam-1").unwrap();
+        registry.join("cam-1").unwrap();
+        let other = registry.join("cam-2").unwrap();
+
+        assert_eq!(registry.remove_camera("cam-1").len(), 2);
+        assert_eq!(registry.count("cam-1"), 0);
+        assert_eq!(registry.viewers_of("cam-2")[0].peer_id, other);
+        assert_eq!(registry.counts().get("cam-2"), Some(&1));
+    }
+}
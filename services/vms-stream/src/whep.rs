@@ -0,0 +1,148 @@
+//! WHEP/WHIP (WebRTC-HTTP Egress/Ingestion Protocol)
+//!
+//! Sinalização padrão em HTTP: a oferta vai no corpo `application/sdp` do
+//! POST, a resposta volta com `201 Created` e um `Location` para o recurso
+//! da sessão; candidatos ICE do cliente chegam por PATCH em
+//! `application/trickle-ice-sdpfrag` (RFC 8840) e o DELETE encerra a sessão.
+//! Os candidatos do servidor vão na própria resposta SDP, e os servidores
+//! STUN/TURN em headers `Link` com `rel="ice-server"`.
+
+use anyhow::{bail, Result};
+use vms_common::IceServer;
+
+/// Candidato ICE de um fragmento SDP
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrickleCandidate {
+    pub sdp_m_line_index: u32,
+    /// Sem o prefixo `a=` (formato do `add-ice-candidate` do webrtcbin)
+    pub candidate: String,
+}
+
+/// `Content-Type` compatível (ignora parâmetros como `charset`)
+pub fn is_content_type(value: Option<&str>, expected: &str) -> bool {
+    value
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().eq_ignore_ascii_case(expected))
+        .unwrap_or(false)
+}
+
+/// Candidatos de um `application/trickle-ice-sdpfrag`
+///
+/// O `a=mid` numérico (como os browsers e o OBS geram) indica a m-line; sem
+/// ele vale a posição da seção `m=` no fragmento.
+pub fn parse_sdpfrag(body: &str) -> Result<Vec<TrickleCandidate>> {
+    let mut candidates = Vec::new();
+    let mut section: Option<u32> = None;
+    let mut mline = 0u32;
+
+    for line in body.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if line.starts_with("m=") {
+            let next = section.map(|s| s + 1).unwrap_or(0);
+            section = Some(next);
+            mline = next;
+        } else if let Some(mid) = line.strip_prefix("a=mid:") {
+            if let Ok(index) = mid.trim().parse() {
+                mline = index;
+            }
+        } else if let Some(candidate) = line.strip_prefix("a=candidate:") {
+            candidates.push(TrickleCandidate {
+                sdp_m_line_index: mline,
+                candidate: format!("candidate:{}", candidate),
+            });
+        } else if !line.starts_with("a=") {
+            bail!("Invalid SDP fragment line: {}", line);
+        }
+    }
+    Ok(candidates)
+}
+
+/// Headers `Link` dos servidores STUN/TURN (WHIP §4.6)
+pub fn ice_server_links(servers: &[IceServer]) -> Vec<String> {
+    servers
+        .iter()
+        .flat_map(|server| {
+            server.urls.iter().map(move |url| {
+                let mut link = format!("<{}>; rel=\"ice-server\"", url);
+                if let (Some(username), Some(credential)) = (&server.username, &server.credential) {
+                    link.push_str(&format!(
+                        "; username=\"{}\"; credential=\"{}\"; credential-type=\"password\"",
+                        username, credential
+                    ));
+                }
+                link
+            })
+        })
+        .collect()
+}
+
+/// Token `Bearer` do header `Authorization`
+pub fn bearer_token(authorization: Option<&str>) -> Option<&str> {
+    let (scheme, token) = authorization?.split_once(' ')?;
+    scheme.eq_ignore_ascii_case("bearer").then(|| token.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vms_common::SDP_CONTENT_TYPE;
+
+    #[test]
+    fn test_parse_sdpfrag() {
+        // Exemplo da RFC 8840 / WHIP com duas seções
+        let body = "a=ice-ufrag:EsAw\r\n\
+                    a=ice-pwd:P2uYro0UCOQ4zxjKXaWCBui1\r\n\
+                    m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
+                    a=mid:0\r\n\
+                    a=candidate:1387637174 1 udp 2122260223 192.0.2.1 61764 typ host generation 0\r\n\
+                    m=video 9 UDP/TLS/RTP/SAVPF 96\r\n\
+                    a=mid:1\r\n\
+                    a=candidate:3471623853 1 udp 2122194687 198.51.100.2 61765 typ host generation 0\r\n\
+                    a=end-of-candidates\r\n";
+        let candidates = parse_sdpfrag(body).unwrap();
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].sdp_m_line_index, 0);
+        assert_eq!(
+            candidates[1],
+            TrickleCandidate {
+                sdp_m_line_index: 1,
+                candidate: "candidate:3471623853 1 udp 2122194687 198.51.100.2 61765 typ host generation 0"
+                    .to_string(),
+            }
+        );
+
+        // mid não numérico: posição da seção
+        let body = "m=video 9 UDP/TLS/RTP/SAVPF 96\na=mid:video\na=candidate:1 1 udp 1 10.0.0.1 5000 typ host\n";
+        assert_eq!(parse_sdpfrag(body).unwrap()[0].sdp_m_line_index, 0);
+
+        assert!(parse_sdpfrag("{\"candidate\": \"x\"}").is_err());
+    }
+
+    #[test]
+    fn test_headers() {
+        let links = ice_server_links(&[
+            IceServer {
+                urls: vec!["stun:stun.l.google.com:19302".to_string()],
+                username: None,
+                credential: None,
+            },
+            IceServer {
+                urls: vec!["turn:turn.example.com:3478?transport=udp".to_string()],
+                username: Some("vms".to_string()),
+                credential: Some("secret".to_string()),
+            },
+        ]);
+        assert_eq!(links[0], "<stun:stun.l.google.com:19302>; rel=\"ice-server\"");
+        assert_eq!(
+            links[1],
+            "<turn:turn.example.com:3478?transport=udp>; rel=\"ice-server\"; username=\"vms\"; \
+             credential=\"secret\"; credential-type=\"password\""
+        );
+
+        assert!(is_content_type(Some("application/sdp; charset=utf-8"), SDP_CONTENT_TYPE));
+        assert!(!is_content_type(Some("application/json"), SDP_CONTENT_TYPE));
+        assert!(!is_content_type(None, SDP_CONTENT_TYPE));
+
+        assert_eq!(bearer_token(Some("Bearer obs-key")), Some("obs-key"));
+        assert_eq!(bearer_token(Some("Basic Zm9v")), None);
+    }
+}
@@ -16,8 +16,83 @@ const CameraTile: Component<CameraTileProps> = (props) => {
     const [connectionState, setConnectionState] = createSignal('connecting');
 
     let peerConnection: RTCPeerConnection | null = null;
+    // Viewer id assigned by vms-stream (one per tile, even for the same camera)
+    let peerId: string | null = null;
+    // Local candidates gathered before the answer (and peer id) arrives
+    let pendingCandidates: RTCIceCandidate[] = [];
 
     const VMS_STREAM_URL = 'http://localhost:9094';
+    const DEFAULT_ICE_SERVERS: RTCIceServer[] = [{ urls: 'stun:stun.l.google.com:19302' }];
+
+    // STUN/TURN servers configured on vms-stream
+    const fetchIceServers = async (): Promise<RTCIceServer[]> => {
+        try {
+            const response = await fetch(`${VMS_STREAM_URL}/api/v1/webrtc/ice-servers`);
+            if (response.ok) {
+                const servers: RTCIceServer[] = await response.json();
+                if (servers.length > 0) return servers;
+            }
+        } catch (e) {
+            console.warn(`[CameraTile ${props.id}] ICE servers fetch error:`, e);
+        }
+        return DEFAULT_ICE_SERVERS;
+    };
+
+    const sendCandidate = async (candidate: RTCIceCandidate) => {
+        try {
+            await fetch(`${VMS_STREAM_URL}/api/v1/webrtc/ice`, {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({
+                    peer_id: peerId,
+                    candidate: candidate.candidate,
+                    sdpMid: candidate.sdpMid,
+                    sdpMLineIndex: candidate.sdpMLineIndex
+                })
+            });
+        } catch (e) {
+            console.warn(`[CameraTile ${props.id}] ICE candidate send error:`, e);
+        }
+    };
+
+    // Long-poll the server's candidates until gathering completes
+    const pollServerCandidates = async (pc: RTCPeerConnection, peer: string) => {
+        let since = 0;
+        while (peerConnection === pc && peerId === peer) {
+            try {
+                const response = await fetch(
+                    `${VMS_STREAM_URL}/api/v1/webrtc/peers/${peer}/candidates?since=${since}&timeout_ms=25000`
+                );
+                if (!response.ok) return;
+                const batch = await response.json();
+                for (const candidate of batch.candidates) {
+                    await pc.addIceCandidate(candidate);
+                }
+                since = batch.next;
+                if (batch.complete) return;
+            } catch (e) {
+                console.warn(`[CameraTile ${props.id}] ICE candidate poll error:`, e);
+                return;
+            }
+        }
+    };
+
+    // Answer received: apply it, flush buffered candidates and start trickling
+    const applyAnswer = async (answer: { sdp: string; peer_id: string }) => {
+        const pc = peerConnection!;
+        peerId = answer.peer_id;
+        await pc.setRemoteDescription(new RTCSessionDescription({
+            type: 'answer',
+            sdp: answer.sdp
+        }));
+
+        const buffered = pendingCandidates;
+        pendingCandidates = [];
+        for (const candidate of buffered) {
+            await sendCandidate(candidate);
+        }
+        pollServerCandidates(pc, answer.peer_id);
+    };
 
     const startWebRTC = async () => {
         try {
@@ -26,9 +101,7 @@ const CameraTile: Component<CameraTileProps> = (props) => {
 
             // Create peer connection
             peerConnection = new RTCPeerConnection({
-                iceServers: [
-                    { urls: 'stun:stun.l.google.com:19302' }
-                ]
+                iceServers: await fetchIceServers()
             });
 
             // Handle incoming video track
@@ -58,21 +131,11 @@ const CameraTile: Component<CameraTileProps> = (props) => {
 
             // Handle ICE candidates
             peerConnection.onicecandidate = async (event) => {
-                if (event.candidate) {
-                    try {
-                        await fetch(`${VMS_STREAM_URL}/api/v1/webrtc/ice`, {
-                            method: 'POST',
-                            headers: { 'Content-Type': 'application/json' },
-                            body: JSON.stringify({
-                                camera_id: props.id,
-                                candidate: event.candidate.candidate,
-                                sdpMid: event.candidate.sdpMid,
-                                sdpMLineIndex: event.candidate.sdpMLineIndex
-                            })
-                        });
-                    } catch (e) {
-                        console.warn(`[CameraTile ${props.id}] ICE candidate send error:`, e);
-                    }
+                if (!event.candidate) return;
+                if (peerId) {
+                    await sendCandidate(event.candidate);
+                } else {
+                    pendingCandidates.push(event.candidate);
                 }
             };
 
@@ -120,10 +183,7 @@ const CameraTile: Component<CameraTileProps> = (props) => {
                     if (retryResponse.ok) {
                         const retryAnswer = await retryResponse.json();
                         console.log(`[CameraTile ${props.id}] Got SDP answer on retry`);
-                        await peerConnection!.setRemoteDescription({
-                            type: 'answer',
-                            sdp: retryAnswer.sdp
-                        });
+                        await applyAnswer(retryAnswer);
                         setConnectionState('connected');
                         return;
                     }
@@ -136,13 +196,10 @@ const CameraTile: Component<CameraTileProps> = (props) => {
             }
 
             const answer = await response.json();
-            console.log(`[CameraTile ${props.id}] Received SDP answer from vms-stream`);
+            console.log(`[CameraTile ${props.id}] Received SDP answer from vms-stream (peer ${answer.peer_id})`);
 
-            // Set remote description
-            await peerConnection.setRemoteDescription(new RTCSessionDescription({
-                type: answer.type,
-                sdp: answer.sdp
-            }));
+            // Set remote description and start trickle ICE
+            await applyAnswer(answer);
 
             console.log(`[CameraTile ${props.id}] WebRTC signaling complete!`);
 
@@ -163,6 +220,12 @@ const CameraTile: Component<CameraTileProps> = (props) => {
     };
 
     const cleanup = () => {
+        if (peerId) {
+            // Leave only this viewer; other tiles of the same camera keep playing
+            fetch(`${VMS_STREAM_URL}/api/v1/webrtc/peers/${peerId}`, { method: 'DELETE' }).catch(() => {});
+            peerId = null;
+        }
+        pendingCandidates = [];
         if (peerConnection) {
             peerConnection.close();
             peerConnection = null;
@@ -19,16 +19,35 @@ export class WebRTCService {
     private config: WebRTCConfig;
     private connections: Map<string, RTCPeerConnection> = new Map();
     private streams: Map<string, MediaStream> = new Map();
+    private peers: Map<string, string> = new Map();
+    private pendingCandidates: Map<string, RTCIceCandidate[]> = new Map();
 
     constructor(config: WebRTCConfig) {
         this.config = {
             streamServerUrl: config.streamServerUrl || 'http://localhost:9094',
-            iceServers: config.iceServers || [
-                { urls: 'stun:stun.l.google.com:19302' },
-            ],
+            iceServers: config.iceServers,
         };
     }
 
+    /**
+     * STUN/TURN servers: explicit config, else the ones vms-stream advertises
+     */
+    private async iceServers(): Promise<RTCIceServer[]> {
+        if (this.config.iceServers) {
+            return this.config.iceServers;
+        }
+        try {
+            const response = await fetch(`${this.config.streamServerUrl}/api/v1/webrtc/ice-servers`);
+            if (response.ok) {
+                const servers: RTCIceServer[] = await response.json();
+                if (servers.length > 0) return servers;
+            }
+        } catch (e) {
+            console.warn('[WebRTC] ICE servers fetch error:', e);
+        }
+        return [{ urls: 'stun:stun.l.google.com:19302' }];
+    }
+
     /**
      * Connect to a camera stream via WebRTC
      */
@@ -37,7 +56,7 @@ export class WebRTCService {
 
         // Create peer connection
         const pc = new RTCPeerConnection({
-            iceServers: this.config.iceServers,
+            iceServers: await this.iceServers(),
         });
 
         this.connections.set(cameraId, pc);
@@ -60,9 +79,15 @@ export class WebRTCService {
         });
 
         // Handle ICE candidates
+        // Candidates gathered before the answer wait for the peer id
+        this.pendingCandidates.set(cameraId, []);
         pc.onicecandidate = async (event) => {
-            if (event.candidate) {
-                await this.sendIceCandidate(cameraId, event.candidate);
+            if (!event.candidate) return;
+            const peerId = this.peers.get(cameraId);
+            if (peerId) {
+                await this.sendIceCandidate(peerId, event.candidate);
+            } else {
+                this.pendingCandidates.get(cameraId)?.push(event.candidate);
             }
         };
 
@@ -86,6 +111,14 @@ export class WebRTCService {
         const answer = await this.sendOffer(cameraId, offer);
         await pc.setRemoteDescription(answer);
 
+        // Trickle ICE both ways now that the viewer exists on the server
+        const peerId = this.peers.get(cameraId)!;
+        for (const candidate of this.pendingCandidates.get(cameraId) ?? []) {
+            await this.sendIceCandidate(peerId, candidate);
+        }
+        this.pendingCandidates.delete(cameraId);
+        this.pollServerCandidates(cameraId, pc, peerId);
+
         return streamPromise;
     }
 
@@ -108,6 +141,7 @@ export class WebRTCService {
         }
 
         const data = await response.json();
+        this.peers.set(cameraId, data.peer_id);
         return {
             type: 'answer',
             sdp: data.sdp,
@@ -117,12 +151,12 @@ export class WebRTCService {
     /**
      * Send ICE candidate to signaling server
      */
-    private async sendIceCandidate(cameraId: string, candidate: RTCIceCandidate): Promise<void> {
+    private async sendIceCandidate(peerId: string, candidate: RTCIceCandidate): Promise<void> {
         await fetch(`${this.config.streamServerUrl}/api/v1/webrtc/ice`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
-                camera_id: cameraId,
+                peer_id: peerId,
                 candidate: candidate.candidate,
                 sdpMid: candidate.sdpMid,
                 sdpMLineIndex: candidate.sdpMLineIndex,
@@ -130,10 +164,41 @@ export class WebRTCService {
         });
     }
 
+    /**
+     * Long-poll the server's ICE candidates until gathering completes
+     */
+    private async pollServerCandidates(cameraId: string, pc: RTCPeerConnection, peerId: string): Promise<void> {
+        let since = 0;
+        while (this.connections.get(cameraId) === pc) {
+            try {
+                const response = await fetch(
+                    `${this.config.streamServerUrl}/api/v1/webrtc/peers/${peerId}/candidates?since=${since}&timeout_ms=25000`
+                );
+                if (!response.ok) return;
+                const batch = await response.json();
+                for (const candidate of batch.candidates) {
+                    await pc.addIceCandidate(candidate);
+                }
+                since = batch.next;
+                if (batch.complete) return;
+            } catch (e) {
+                console.warn('[WebRTC] ICE candidate poll error:', e);
+                return;
+            }
+        }
+    }
+
     /**
      * Disconnect from a camera stream
      */
     disconnect(cameraId: string): void {
+        const peerId = this.peers.get(cameraId);
+        if (peerId) {
+            fetch(`${this.config.streamServerUrl}/api/v1/webrtc/peers/${peerId}`, { method: 'DELETE' }).catch(() => {});
+            this.peers.delete(cameraId);
+        }
+        this.pendingCandidates.delete(cameraId);
+
         const pc = this.connections.get(cameraId);
         if (pc) {
             pc.close();